@@ -2,10 +2,12 @@ pub mod user;
 pub mod explorer;
 pub mod workflow;
 pub mod site;
+pub mod share;
 
 // Re-export for convenience
 pub use user::UserApi;
 pub use explorer::ExplorerApi;
 pub use workflow::WorkflowApi;
 pub use site::SiteApi;
+pub use share::ShareApi;
 
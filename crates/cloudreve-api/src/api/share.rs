@@ -0,0 +1,18 @@
+use crate::client::{Client, RequestOptions};
+use crate::error::ApiResult;
+use crate::models::share::*;
+use async_trait::async_trait;
+
+/// Share link API methods
+#[async_trait]
+pub trait ShareApi {
+    /// Create a share link, returning its URL
+    async fn create_share_link(&self, request: &CreateShareLinkService) -> ApiResult<String>;
+}
+
+#[async_trait]
+impl ShareApi for Client {
+    async fn create_share_link(&self, request: &CreateShareLinkService) -> ApiResult<String> {
+        self.put("/share", request, RequestOptions::new()).await
+    }
+}
@@ -23,6 +23,20 @@ pub struct ClientConfig {
     pub client_id: String,
     /// User agent string for HTTP requests
     pub user_agent: Option<String>,
+    /// Proxy requests are sent through
+    pub proxy: ClientProxy,
+}
+
+/// Proxy the client sends requests through
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ClientProxy {
+    /// The proxy configured in the system, if any
+    #[default]
+    System,
+    /// Connect to the server directly
+    Direct,
+    /// The HTTP(S) proxy at this URL, credentials may be given as `user:password@`
+    Url(String),
 }
 
 impl ClientConfig {
@@ -33,6 +47,7 @@ impl ClientConfig {
             timeout_seconds: 60,
             client_id: "".to_string(),
             user_agent: None,
+            proxy: ClientProxy::System,
         }
     }
 
@@ -53,6 +68,12 @@ impl ClientConfig {
         self.user_agent = Some(user_agent.into());
         self
     }
+
+    /// Set the proxy
+    pub fn with_proxy(mut self, proxy: ClientProxy) -> Self {
+        self.proxy = proxy;
+        self
+    }
 }
 
 /// Token storage with expiration tracking
@@ -158,6 +179,17 @@ impl Client {
             builder = builder.user_agent(user_agent);
         }
 
+        match &config.proxy {
+            ClientProxy::System => {}
+            ClientProxy::Direct => builder = builder.no_proxy(),
+            ClientProxy::Url(url) => {
+                // Callers validate the URL; an invalid one falls back to the system proxy
+                if let Ok(proxy) = reqwest::Proxy::all(url) {
+                    builder = builder.proxy(proxy);
+                }
+            }
+        }
+
         let http_client = builder.build().expect("Failed to create HTTP client");
 
         Self {
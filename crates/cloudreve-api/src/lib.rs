@@ -43,5 +43,5 @@ pub mod error;
 pub mod models;
 
 pub use boolset::Boolset;
-pub use client::{Client, ClientConfig};
+pub use client::{Client, ClientConfig, ClientProxy};
 pub use error::{ApiError, ApiResult};
@@ -21,3 +21,22 @@ pub struct ListShareResponse {
     pub pagination: PaginationResults,
 }
 
+
+/// Create share link service
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateShareLinkService {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_private: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub share_view: Option<bool>,
+    /// Seconds until the link expires
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expire: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub downloads: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_readme: Option<bool>,
+}
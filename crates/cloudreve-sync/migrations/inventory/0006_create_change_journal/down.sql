@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_change_journal_created_at;
+DROP INDEX IF EXISTS idx_change_journal_local_path;
+DROP TABLE IF EXISTS change_journal;
@@ -0,0 +1,17 @@
+-- Journal of inventory mutations, used to diagnose sync issues and to support undo
+CREATE TABLE IF NOT EXISTS change_journal (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- NULL when the mutation is not tied to a single drive (e.g. path-based deletes)
+    drive_id TEXT,
+    local_path TEXT NOT NULL,
+    -- insert, update, upsert, delete, rename, conflict_state, clear
+    operation TEXT NOT NULL,
+    -- What caused the change: sync, remote_delta, local_event, upload, download, repair, import, user, unknown
+    source TEXT NOT NULL,
+    -- Operation specific detail, e.g. the previous path of a rename
+    detail TEXT,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_change_journal_local_path ON change_journal(local_path);
+CREATE INDEX IF NOT EXISTS idx_change_journal_created_at ON change_journal(created_at);
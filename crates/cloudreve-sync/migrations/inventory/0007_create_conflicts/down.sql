@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_conflicts_resolution;
+DROP INDEX IF EXISTS idx_conflicts_original_path;
+DROP TABLE IF EXISTS conflicts;
@@ -0,0 +1,32 @@
+-- Conflicts between local and remote versions of a file, tracked for the conflict center
+CREATE TABLE IF NOT EXISTS conflicts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    drive_id TEXT NOT NULL,
+    -- Path the conflict was detected on
+    original_path TEXT NOT NULL,
+    -- Path of the local copy kept aside, if one was created
+    conflict_path TEXT,
+    local_etag TEXT,
+    remote_etag TEXT,
+    -- 'content': both sides changed; 'type_mismatch': file on one side, folder on the other
+    kind TEXT NOT NULL,
+    -- 'pending', 'keep_remote', 'overwrite_remote', 'save_as_new', 'kept_both'
+    resolution TEXT NOT NULL DEFAULT 'pending',
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    resolved_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_conflicts_original_path ON conflicts(original_path);
+CREATE INDEX IF NOT EXISTS idx_conflicts_resolution ON conflicts(resolution);
+
+-- Carry over conflicts recorded only through file_metadata.conflict_state
+INSERT INTO conflicts (drive_id, original_path, local_etag, kind, resolution, created_at, updated_at)
+SELECT drive_id, local_path, etag, 'content', 'pending', updated_at, updated_at
+FROM file_metadata
+WHERE conflict_state = 'pending'
+  AND NOT EXISTS (
+    SELECT 1 FROM conflicts
+    WHERE conflicts.original_path = file_metadata.local_path
+      AND conflicts.resolution = 'pending'
+  );
@@ -0,0 +1 @@
+ALTER TABLE task_queue DROP COLUMN force_override;
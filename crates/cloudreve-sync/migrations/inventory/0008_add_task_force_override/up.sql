@@ -0,0 +1,3 @@
+-- Remember whether an upload task overwrites the remote file, so the flag survives
+-- a restart when the task is resumed from the queue
+ALTER TABLE task_queue ADD COLUMN force_override BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+ALTER TABLE task_queue DROP COLUMN attempt_history;
+ALTER TABLE task_queue DROP COLUMN attempts;
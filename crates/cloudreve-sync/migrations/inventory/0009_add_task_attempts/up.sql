@@ -0,0 +1,4 @@
+-- Track failed attempts of a task so retries survive restarts
+-- attempt_history: JSON array of {attempt, failure, error, failed_at}
+ALTER TABLE task_queue ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE task_queue ADD COLUMN attempt_history TEXT;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_dead_letters_drive_id;
+DROP TABLE IF EXISTS dead_letters;
@@ -0,0 +1,20 @@
+-- Tasks that failed for good, kept until the user requeues or discards them.
+-- The task itself stays in task_queue as failed, for the task history.
+CREATE TABLE IF NOT EXISTS dead_letters (
+    task_id TEXT PRIMARY KEY NOT NULL,
+    drive_id TEXT NOT NULL,
+    task_type TEXT NOT NULL,
+    local_path TEXT NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 0,
+    custom_state TEXT,
+    force_override BOOLEAN NOT NULL DEFAULT 0,
+    -- Failure class and error of the last attempt
+    failure TEXT NOT NULL,
+    error TEXT NOT NULL,
+    attempts INTEGER NOT NULL,
+    -- JSON array of {attempt, failure, error, failed_at}, as in task_queue
+    attempt_history TEXT,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_dead_letters_drive_id ON dead_letters(drive_id);
@@ -0,0 +1,9 @@
+-- How far the volume change journal was read for a drive's sync root, so changes made
+-- while the app was not running can be replayed on the next start.
+CREATE TABLE IF NOT EXISTS usn_checkpoints (
+    drive_id TEXT PRIMARY KEY NOT NULL,
+    -- Identifies the journal instance; a recreated journal invalidates next_usn
+    journal_id BIGINT NOT NULL,
+    next_usn BIGINT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
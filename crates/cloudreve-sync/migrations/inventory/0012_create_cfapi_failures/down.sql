@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_cfapi_failures_drive_id_created_at;
+DROP TABLE IF EXISTS cfapi_failures;
@@ -0,0 +1,18 @@
+-- Cloud filter callbacks that failed, kept for diagnostics and to drive automatic recovery
+CREATE TABLE IF NOT EXISTS cfapi_failures (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    drive_id TEXT NOT NULL,
+    -- fetch_data, fetch_placeholders, validate_data, rename
+    operation TEXT NOT NULL,
+    local_path TEXT NOT NULL,
+    -- HRESULT reported by Windows, NULL when the failure happened in the app
+    hresult INTEGER,
+    -- provider_disconnected, placeholder_corrupt, transient, cancelled, other
+    class TEXT NOT NULL,
+    error TEXT NOT NULL,
+    -- reconnect_sync_root, repair_placeholder, NULL when nothing was started
+    recovery TEXT,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_cfapi_failures_drive_id_created_at ON cfapi_failures(drive_id, created_at);
@@ -0,0 +1,10 @@
+-- Events broadcast to the UI, kept as a ring buffer so they can be replayed after the
+-- UI starts or reconnects
+CREATE TABLE IF NOT EXISTS event_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    -- Event::name()
+    name TEXT NOT NULL,
+    -- The event serialized as JSON
+    payload TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
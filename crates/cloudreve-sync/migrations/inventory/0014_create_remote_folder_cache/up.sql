@@ -0,0 +1,11 @@
+-- Subfolders of remote folders as listed for the selective sync picker, so browsing the
+-- tree again does not list the server again
+CREATE TABLE IF NOT EXISTS remote_folder_cache (
+    drive_id TEXT NOT NULL,
+    -- URI of the listed folder
+    parent_uri TEXT NOT NULL,
+    -- The subfolders serialized as JSON
+    folders TEXT NOT NULL,
+    fetched_at INTEGER NOT NULL,
+    PRIMARY KEY (drive_id, parent_uri)
+);
@@ -9,6 +9,7 @@ use std::{
     ptr,
     time::{Duration, SystemTime},
 };
+use serde::{Deserialize, Serialize};
 use widestring::U16CString;
 use windows::{
     Win32::{
@@ -232,7 +233,7 @@ impl Default for OpenOptions {
 ///
 /// [Read more
 /// here](https://docs.microsoft.com/en-us/windows/win32/api/cfapi/ne-cfapi-cf_pin_state#remarks)
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PinState {
     /// The platform could decide freely.
     Unspecified,
@@ -20,7 +20,7 @@ use windows::{
         StorageFolder,
         Streams::{DataReader, DataWriter},
     },
-    core,
+    core::{self, GUID},
 };
 
 use super::SyncRootId;
@@ -90,6 +90,23 @@ impl SyncRootInfo {
         Ok(())
     }
 
+    /// Ids and display names of the custom states registered for the sync root.
+    pub fn custom_states(&self) -> Vec<(i32, OsString)> {
+        self.0
+            .StorageProviderItemPropertyDefinitions()
+            .map(|definitions| {
+                definitions
+                    .into_iter()
+                    .filter_map(|definition| {
+                        let id = definition.Id().ok()?;
+                        let display_name = definition.DisplayNameResource().ok()?;
+                        Some((id, display_name.to_os_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Sets the display name that maps to the existing sync root registration.
     pub fn with_display_name(mut self, display_name: impl AsRef<OsStr>) -> Self {
         self.set_display_name(display_name);
@@ -344,6 +361,22 @@ impl SyncRootInfo {
         self.set_blob(blob);
         self
     }
+
+    /// The identifier of the app providing the sync root, shared by all of its sync roots.
+    pub fn provider_id(&self) -> GUID {
+        self.0.ProviderId().unwrap()
+    }
+
+    /// Sets the identifier of the app providing the sync root.
+    pub fn set_provider_id(&mut self, provider_id: GUID) {
+        self.0.SetProviderId(provider_id).unwrap()
+    }
+
+    /// Sets the identifier of the app providing the sync root.
+    pub fn with_provider_id(mut self, provider_id: GUID) -> Self {
+        self.set_provider_id(provider_id);
+        self
+    }
 }
 
 impl Default for SyncRootInfo {
@@ -437,7 +470,7 @@ flags! {
 }
 
 /// The population policy of the sync root registration.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PopulationType {
     /// If the placeholder files or directories are not fully populated,
     /// the platform will request that the sync provider populate them before completing a user request.
@@ -1,6 +1,42 @@
-// TODO: this module will be for handling usn's and potentially change journals.
+// TODO: this module handles usn's and reading the change journal.
 // Usn's should ALWAYS be required and this module should make it easy
 
+use std::{
+    ffi::{OsString, c_void},
+    fs::File,
+    mem,
+    ops::Range,
+    os::windows::{
+        ffi::OsStringExt,
+        fs::OpenOptionsExt,
+        io::{AsRawHandle, FromRawHandle},
+    },
+    path::{Path, PathBuf},
+};
+
+use windows::{
+    Win32::{
+        Foundation::HANDLE,
+        Storage::FileSystem::{
+            FILE_ATTRIBUTE_DIRECTORY, FILE_FLAG_BACKUP_SEMANTICS, FILE_ID_DESCRIPTOR,
+            FILE_ID_DESCRIPTOR_0, FILE_NAME_NORMALIZED, FILE_READ_ATTRIBUTES, FILE_SHARE_DELETE,
+            FILE_SHARE_READ, FILE_SHARE_WRITE, FileIdType, GetFinalPathNameByHandleW, OpenFileById,
+            VOLUME_NAME_DOS,
+        },
+        System::{
+            IO::DeviceIoControl,
+            Ioctl::{
+                FSCTL_QUERY_USN_JOURNAL, FSCTL_READ_UNPRIVILEGED_USN_JOURNAL,
+                READ_USN_JOURNAL_DATA_V0, USN_JOURNAL_DATA_V0, USN_REASON_BASIC_INFO_CHANGE,
+                USN_REASON_DATA_EXTEND, USN_REASON_DATA_OVERWRITE, USN_REASON_DATA_TRUNCATION,
+                USN_REASON_FILE_CREATE, USN_REASON_FILE_DELETE, USN_REASON_RENAME_NEW_NAME,
+                USN_REASON_RENAME_OLD_NAME, USN_RECORD_V2,
+            },
+        },
+    },
+    core,
+};
+
 /// An Updated Sequence Number (USN) is as an identifier that represents the version of a file. Each
 /// subsequent file operation will increment the USN, allowing you to recognize when a file has
 /// been updated.
@@ -10,3 +46,273 @@
 /// unless if the passed USN matches the most recent USN of the file. This avoids applying changes
 /// that may be out of date.
 pub type Usn = i64;
+
+/// Size of the buffer a single journal read fills
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Reasons a [UsnRecord] is reported for, everything else is filtered out by the volume.
+const REASON_MASK: u32 = USN_REASON_FILE_CREATE
+    | USN_REASON_FILE_DELETE
+    | USN_REASON_RENAME_OLD_NAME
+    | USN_REASON_RENAME_NEW_NAME
+    | USN_REASON_DATA_OVERWRITE
+    | USN_REASON_DATA_EXTEND
+    | USN_REASON_DATA_TRUNCATION
+    | USN_REASON_BASIC_INFO_CHANGE;
+
+/// The position of a volume's change journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalState {
+    /// Identifies the journal instance. It changes when the journal is deleted and recreated,
+    /// which invalidates every [Usn] read from the previous one.
+    pub journal_id: u64,
+    /// The oldest record still kept in the journal
+    pub first_usn: Usn,
+    /// The [Usn] the next change will be recorded at
+    pub next_usn: Usn,
+}
+
+/// A single change recorded in the journal.
+#[derive(Debug, Clone)]
+pub struct UsnRecord {
+    pub usn: Usn,
+    /// NTFS file reference number of the changed file
+    pub file_id: u64,
+    /// NTFS file reference number of the directory holding the file
+    pub parent_id: u64,
+    /// `USN_REASON_*` flags accumulated for the file since it was opened
+    pub reason: u32,
+    /// File attributes at the time of the change
+    pub attributes: u32,
+    /// The name of the file, without its parent path
+    pub file_name: OsString,
+}
+
+impl UsnRecord {
+    /// # Safety
+    ///
+    /// `record` must point into a buffer that holds the whole record.
+    unsafe fn from_raw(record: &USN_RECORD_V2) -> Self {
+        let name = unsafe {
+            std::slice::from_raw_parts(
+                (record as *const USN_RECORD_V2 as *const u8).add(record.FileNameOffset as usize)
+                    as *const u16,
+                record.FileNameLength as usize / mem::size_of::<u16>(),
+            )
+        };
+        Self {
+            usn: record.Usn,
+            file_id: record.FileReferenceNumber,
+            parent_id: record.ParentFileReferenceNumber,
+            reason: record.Reason,
+            attributes: record.FileAttributes,
+            file_name: OsString::from_wide(name),
+        }
+    }
+
+    pub fn is_directory(&self) -> bool {
+        self.attributes & FILE_ATTRIBUTE_DIRECTORY.0 != 0
+    }
+
+    pub fn created(&self) -> bool {
+        self.reason & USN_REASON_FILE_CREATE != 0
+    }
+
+    pub fn deleted(&self) -> bool {
+        self.reason & USN_REASON_FILE_DELETE != 0
+    }
+
+    /// The record carries the name the file had before a rename
+    pub fn renamed_from(&self) -> bool {
+        self.reason & USN_REASON_RENAME_OLD_NAME != 0
+    }
+
+    /// The record carries the name the file has after a rename
+    pub fn renamed_to(&self) -> bool {
+        self.reason & USN_REASON_RENAME_NEW_NAME != 0
+    }
+
+    /// The content or the attributes of the file changed
+    pub fn modified(&self) -> bool {
+        self.reason
+            & (USN_REASON_DATA_OVERWRITE
+                | USN_REASON_DATA_EXTEND
+                | USN_REASON_DATA_TRUNCATION
+                | USN_REASON_BASIC_INFO_CHANGE)
+            != 0
+    }
+}
+
+/// A reader for the change journal of the volume holding a directory.
+///
+/// Uses the unprivileged journal control codes, so it works without administrator rights
+/// but requires Windows 10 1709 or later.
+#[derive(Debug)]
+pub struct UsnJournal {
+    // Any directory on the volume works as a handle to the journal
+    root: File,
+}
+
+impl UsnJournal {
+    /// Open the change journal of the volume holding `path`, which must be a directory.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let root = std::fs::OpenOptions::new()
+            .access_mode(FILE_READ_ATTRIBUTES.0)
+            .share_mode((FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE).0)
+            .custom_flags(FILE_FLAG_BACKUP_SEMANTICS.0)
+            .open(path)?;
+        Ok(Self { root })
+    }
+
+    /// Query the current position of the journal.
+    pub fn query(&self) -> core::Result<JournalState> {
+        let mut data = USN_JOURNAL_DATA_V0::default();
+        unsafe {
+            DeviceIoControl(
+                self.handle(),
+                FSCTL_QUERY_USN_JOURNAL,
+                None,
+                0,
+                Some(&mut data as *mut _ as *mut c_void),
+                mem::size_of::<USN_JOURNAL_DATA_V0>() as u32,
+                None,
+                None,
+            )
+        }?;
+
+        Ok(JournalState {
+            journal_id: data.UsnJournalID,
+            first_usn: data.FirstUsn,
+            next_usn: data.NextUsn,
+        })
+    }
+
+    /// Read the records of journal `journal_id` within `range`, oldest first.
+    ///
+    /// Fails if the journal was recreated or the start of the range was already purged
+    /// from the journal, in which case the changes can not be recovered.
+    pub fn read(&self, journal_id: u64, range: Range<Usn>) -> core::Result<Vec<UsnRecord>> {
+        let mut records = Vec::new();
+        // u64 keeps the buffer aligned for the records
+        let mut buffer = vec![0u64; READ_BUFFER_SIZE / mem::size_of::<u64>()];
+        let mut start = range.start;
+
+        while start < range.end {
+            let input = READ_USN_JOURNAL_DATA_V0 {
+                StartUsn: start,
+                ReasonMask: REASON_MASK,
+                ReturnOnlyOnClose: 0,
+                Timeout: 0,
+                BytesToWaitFor: 0,
+                UsnJournalID: journal_id,
+            };
+            let mut returned = 0u32;
+            unsafe {
+                DeviceIoControl(
+                    self.handle(),
+                    FSCTL_READ_UNPRIVILEGED_USN_JOURNAL,
+                    Some(&input as *const _ as *const c_void),
+                    mem::size_of::<READ_USN_JOURNAL_DATA_V0>() as u32,
+                    Some(buffer.as_mut_ptr() as *mut c_void),
+                    READ_BUFFER_SIZE as u32,
+                    Some(&mut returned),
+                    None,
+                )
+            }?;
+
+            // The output starts with the Usn to continue reading from
+            let returned = returned as usize;
+            if returned <= mem::size_of::<Usn>() {
+                break;
+            }
+            let bytes =
+                unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const u8, returned) };
+            let next = Usn::from_ne_bytes(bytes[..mem::size_of::<Usn>()].try_into().unwrap());
+
+            let mut offset = mem::size_of::<Usn>();
+            while offset + mem::size_of::<USN_RECORD_V2>() <= returned {
+                let record = unsafe { &*(bytes.as_ptr().add(offset) as *const USN_RECORD_V2) };
+                if record.RecordLength == 0 {
+                    break;
+                }
+                if record.MajorVersion == 2 && record.Usn < range.end {
+                    records.push(unsafe { UsnRecord::from_raw(record) });
+                }
+                offset += record.RecordLength as usize;
+            }
+
+            if next <= start {
+                break;
+            }
+            start = next;
+        }
+
+        Ok(records)
+    }
+
+    /// Resolve the current path of a file or directory by its file reference number.
+    ///
+    /// Returns [None] if the file no longer exists.
+    pub fn path_of(&self, file_id: u64) -> Option<PathBuf> {
+        let descriptor = FILE_ID_DESCRIPTOR {
+            dwSize: mem::size_of::<FILE_ID_DESCRIPTOR>() as u32,
+            Type: FileIdType,
+            Anonymous: FILE_ID_DESCRIPTOR_0 {
+                FileId: file_id as i64,
+            },
+        };
+        let handle = unsafe {
+            OpenFileById(
+                self.handle(),
+                &descriptor,
+                FILE_READ_ATTRIBUTES.0,
+                FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+                None,
+                FILE_FLAG_BACKUP_SEMANTICS,
+            )
+        }
+        .ok()?;
+        // Closes the handle on drop
+        let file = unsafe { File::from_raw_handle(handle.0 as _) };
+        final_path(&file)
+    }
+
+    /// The current path of the directory the journal was opened on
+    pub fn root_path(&self) -> Option<PathBuf> {
+        final_path(&self.root)
+    }
+
+    fn handle(&self) -> HANDLE {
+        HANDLE(self.root.as_raw_handle() as _)
+    }
+}
+
+fn final_path(file: &File) -> Option<PathBuf> {
+    let mut buffer = vec![0u16; 1024];
+    loop {
+        let len = unsafe {
+            GetFinalPathNameByHandleW(
+                HANDLE(file.as_raw_handle() as _),
+                &mut buffer,
+                FILE_NAME_NORMALIZED | VOLUME_NAME_DOS,
+            )
+        } as usize;
+        if len == 0 {
+            return None;
+        }
+        if len < buffer.len() {
+            let path = PathBuf::from(OsString::from_wide(&buffer[..len]));
+            return Some(strip_verbatim_prefix(path));
+        }
+        // Too small, `len` is the required size including the terminator
+        buffer.resize(len, 0);
+    }
+}
+
+/// Turn `\\?\C:\dir` into `C:\dir`, the form the rest of the sync engine uses
+fn strip_verbatim_prefix(path: PathBuf) -> PathBuf {
+    match path.to_str().and_then(|s| s.strip_prefix(r"\\?\")) {
+        Some(stripped) if !stripped.starts_with("UNC\\") => PathBuf::from(stripped),
+        _ => path,
+    }
+}
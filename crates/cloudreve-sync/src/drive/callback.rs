@@ -1,4 +1,4 @@
-use std::{sync::Arc, time::Duration};
+use std::{ops::Range, path::Path, sync::Arc, time::Duration};
 
 use crate::{
     cfapi::{
@@ -8,10 +8,14 @@ use crate::{
     },
     drive::{
         commands::MountCommand,
+        recovery::{CallbackFailure, due_recovery},
         sync::{cloud_file_to_metadata_entry, cloud_file_to_placeholder, is_symbolic_link},
     },
-    inventory::{InventoryDb, MetadataEntry},
+    inventory::{
+        CfapiOperation, ChangeSource, FileMetadata, InventoryDb, MetadataEntry, NewCfapiFailure,
+    },
 };
+use anyhow::anyhow;
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
@@ -38,6 +42,33 @@ impl CallbackHandler {
     pub async fn sleep(&self) {
         tokio::time::sleep(Duration::from_secs(10)).await;
     }
+
+    /// Record a failed callback and start the recovery its class calls for, if any
+    fn report_failure(&self, operation: CfapiOperation, path: &Path, failure: CallbackFailure) {
+        let recovery = due_recovery(&self.inventory, &self.id, failure.class);
+        let record = NewCfapiFailure::new(
+            &self.id,
+            operation,
+            path.to_string_lossy(),
+            failure.class,
+            &failure.error,
+        )
+        .with_hresult(failure.hresult)
+        .with_recovery(recovery);
+        if let Err(e) = self.inventory.record_cfapi_failure(&record) {
+            tracing::warn!(target: "drive::recovery", id = %self.id, error = ?e, "Failed to record cloud filter failure");
+        }
+
+        if let Some(recovery) = recovery {
+            let command = MountCommand::Recover {
+                recovery,
+                path: path.to_path_buf(),
+            };
+            if let Err(e) = self.command_tx.send(command) {
+                tracing::error!(target: "drive::recovery", id = %self.id, error = %e, "Failed to send Recover command");
+            }
+        }
+    }
 }
 
 impl SyncFilter for CallbackHandler {
@@ -52,6 +83,7 @@ impl SyncFilter for CallbackHandler {
             path: request.path().to_path_buf(),
             ticket,
             range: info.required_file_range(),
+            file_size: request.file_size(),
             response: response_tx,
         };
         if let Err(e) = self.command_tx.send(command) {
@@ -59,10 +91,13 @@ impl SyncFilter for CallbackHandler {
             return Err(CloudErrorKind::NotSupported);
         }
 
-        match response_rx.blocking_recv() {
-            Ok(Ok(())) => Ok(()),
-            _ => Err(CloudErrorKind::Unsuccessful),
-        }
+        let failure = match response_rx.blocking_recv() {
+            Ok(Ok(())) => return Ok(()),
+            Ok(Err(e)) => CallbackFailure::from_error(&e),
+            Err(e) => CallbackFailure::from_error(&anyhow!("fetch data response dropped: {}", e)),
+        };
+        self.report_failure(CfapiOperation::FetchData, request.path(), failure);
+        Err(CloudErrorKind::Unsuccessful)
     }
 
     fn deleted(&self, request: Request, _info: info::Deleted) {
@@ -90,13 +125,16 @@ impl SyncFilter for CallbackHandler {
             return Err(CloudErrorKind::NotSupported);
         }
 
-        match response_rx.blocking_recv() {
+        let failure = match response_rx.blocking_recv() {
             Ok(Ok(())) => {
                 let _ = ticket.pass();
-                Ok(())
+                return Ok(());
             }
-            _ => Err(CloudErrorKind::Unsuccessful),
-        }
+            Ok(Err(e)) => CallbackFailure::from_error(&e),
+            Err(e) => CallbackFailure::from_error(&anyhow!("rename response dropped: {}", e)),
+        };
+        self.report_failure(CfapiOperation::Rename, src, failure);
+        Err(CloudErrorKind::Unsuccessful)
         // TODO: delete sometimes trigger rename callback
     }
 
@@ -116,7 +154,7 @@ impl SyncFilter for CallbackHandler {
             return Err(CloudErrorKind::NotSupported);
         }
 
-        match response_rx.blocking_recv() {
+        let failure = match response_rx.blocking_recv() {
             Ok(Ok(files)) => {
                 tracing::debug!(target: "drive::mounts", id = %self.id, files = %files.files.len(), "Received placeholders");
                 let mut placeholders = files.files.iter()
@@ -133,6 +171,11 @@ impl SyncFilter for CallbackHandler {
                     .collect::<Vec<PlaceholderFile>>();
                 if let Err(e) = ticket.pass_with_placeholder(&mut placeholders) {
                     tracing::error!(target: "drive::mounts", id = %self.id, error = %e, "Failed to pass placeholders");
+                    self.report_failure(
+                        CfapiOperation::FetchPlaceholders,
+                        request.path(),
+                        CallbackFailure::from_windows(&e),
+                    );
                     return Err(CloudErrorKind::Unsuccessful);
                 }
                 tracing::debug!(target: "drive::mounts", id = %self.id, placeholders = %placeholders.len(), "Passed placeholders");
@@ -152,14 +195,22 @@ impl SyncFilter for CallbackHandler {
                         }).ok()
                     })
                     .collect::<Vec<MetadataEntry>>();
-                if let Err(e) = self.inventory.batch_insert(&entries) {
+                if let Err(e) = self
+                    .inventory
+                    .with_source(ChangeSource::RemoteDelta)
+                    .batch_insert(&entries)
+                {
                     tracing::error!(target: "drive::mounts", id = %self.id, error = ?e, "Failed to insert placeholders into inventory");
                 }
                 return Ok(());
             }
-            _ => {}
-        }
+            Ok(Err(e)) => CallbackFailure::from_error(&e),
+            Err(e) => {
+                CallbackFailure::from_error(&anyhow!("fetch placeholders response dropped: {}", e))
+            }
+        };
 
+        self.report_failure(CfapiOperation::FetchPlaceholders, request.path(), failure);
         Err(CloudErrorKind::Unsuccessful)
     }
 
@@ -167,18 +218,53 @@ impl SyncFilter for CallbackHandler {
         tracing::debug!(target: "drive::mounts", id = %self.id, path = %request.path().display(), deleted = %info.deleted(), "Closed");
     }
 
-    fn cancel_fetch_data(&self, _request: Request, _info: info::CancelFetchData) {
-        tracing::debug!(target: "drive::mounts", id = %self.id, "CancelFetchData");
+    fn cancel_fetch_data(&self, request: Request, info: info::CancelFetchData) {
+        tracing::debug!(target: "drive::mounts", id = %self.id, path = %request.path().display(), info = ?info, "CancelFetchData");
+        let command = MountCommand::CancelFetchData {
+            path: request.path().to_path_buf(),
+            range: info.file_range(),
+        };
+        if let Err(e) = self.command_tx.send(command) {
+            tracing::error!(target: "drive::mounts", id = %self.id, error = %e, "Failed to send CancelFetchData command");
+        }
     }
 
     fn validate_data(
         &self,
-        _request: Request,
-        _ticket: ticket::ValidateData,
-        _info: info::ValidateData,
+        request: Request,
+        ticket: ticket::ValidateData,
+        info: info::ValidateData,
     ) -> CResult<()> {
-        tracing::debug!(target: "drive::mounts", id = %self.id, "ValidateData");
-        Err(CloudErrorKind::NotSupported)
+        let path = request.path();
+        let range = info.file_range();
+        tracing::debug!(target: "drive::mounts", id = %self.id, path = %path.display(), range = ?range, "ValidateData");
+
+        let file_meta = match self.inventory.query_by_path(&path.to_string_lossy()) {
+            Ok(file_meta) => file_meta,
+            Err(e) => {
+                tracing::error!(target: "drive::mounts", id = %self.id, path = %path.display(), error = %e, "Failed to query metadata for validation");
+                return Err(CloudErrorKind::Unsuccessful);
+            }
+        };
+        if let Err(e) = validate_range(file_meta.as_ref(), request.file_blob(), &range) {
+            tracing::warn!(target: "drive::mounts", id = %self.id, path = %path.display(), range = ?range, error = ?e, "Placeholder data failed validation");
+            self.report_failure(
+                CfapiOperation::ValidateData,
+                path,
+                CallbackFailure::from_kind(e),
+            );
+            return Err(e);
+        }
+
+        ticket.pass(range).map_err(|e| {
+            tracing::error!(target: "drive::mounts", id = %self.id, path = %path.display(), error = %e, "Failed to acknowledge validated data");
+            self.report_failure(
+                CfapiOperation::ValidateData,
+                path,
+                CallbackFailure::from_windows(&e),
+            );
+            CloudErrorKind::Unsuccessful
+        })
     }
 
     fn cancel_fetch_placeholders(&self, _request: Request, _info: info::CancelFetchPlaceholders) {
@@ -226,3 +312,60 @@ impl SyncFilter for CallbackHandler {
         }
     }
 }
+
+/// Check a range of placeholder data against the inventory. The placeholder blob holds
+/// the entity the data was fetched from, which must still be the current version of
+/// the file.
+fn validate_range(
+    file_meta: Option<&FileMetadata>,
+    blob: &[u8],
+    range: &Range<u64>,
+) -> CResult<()> {
+    let Some(file_meta) = file_meta.filter(|meta| !meta.is_folder) else {
+        return Err(CloudErrorKind::NotInSync);
+    };
+    if !file_meta.etag.is_empty() && blob != file_meta.etag.as_bytes() {
+        return Err(CloudErrorKind::NotInSync);
+    }
+    if range.start > range.end || range.end > file_meta.size.max(0) as u64 {
+        return Err(CloudErrorKind::InvalidRequest);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_against_current_entity() {
+        let meta = FileMetadata {
+            id: 1,
+            drive_id: Uuid::new_v4(),
+            is_folder: false,
+            local_path: r"C:\sync\a.txt".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            etag: "entity-2".to_string(),
+            metadata: Default::default(),
+            props: None,
+            permissions: String::new(),
+            shared: false,
+            size: 100,
+            conflict_state: None,
+        };
+        assert!(validate_range(Some(&meta), b"entity-2", &(0..100)).is_ok());
+        assert!(matches!(
+            validate_range(Some(&meta), b"entity-1", &(0..100)),
+            Err(CloudErrorKind::NotInSync)
+        ));
+        assert!(matches!(
+            validate_range(Some(&meta), b"entity-2", &(50..150)),
+            Err(CloudErrorKind::InvalidRequest)
+        ));
+        assert!(matches!(
+            validate_range(None, b"entity-2", &(0..100)),
+            Err(CloudErrorKind::NotInSync)
+        ));
+    }
+}
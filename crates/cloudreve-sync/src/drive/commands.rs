@@ -1,29 +1,34 @@
 use crate::{
     cfapi::{
         filter::ticket,
-        placeholder::{LocalFileInfo, OpenOptions, PinState},
+        placeholder::{LocalFileInfo, OpenOptions, PinOptions, PinState},
         utility::WriteAt,
     },
+    config::{ConfigManager, ProxyConfig},
     drive::{
+        manager::FreedSpace,
         mounts::Mount,
         placeholder::CrPlaceholder,
+        prefetch::PrefetchHint,
         sync::{GroupedFsEvents, SyncMode},
-        utils::{local_path_to_cr_uri, notify_shell_change},
+        thumbnails::ThumbnailCache,
+        utils::{local_path_to_cr_uri, notify_shell_change, refresh_shell_state},
     },
-    inventory::ConflictState,
-    tasks::TaskPayload,
-    utils::toast,
+    inventory::{CfapiRecovery, ChangeSource, ConflictResolution, ConflictState},
+    tasks::{HydrationTask, PlaceholderProgressReporter, TaskPayload},
+    utils::{bidi::isolate, toast},
 };
 use anyhow::{Context, Result};
 use bytes::Bytes;
 use cloudreve_api::{
     ApiError,
-    api::{ExplorerApi, explorer::ExplorerApiExt},
+    api::{ExplorerApi, ShareApi, explorer::ExplorerApiExt},
     models::{
         explorer::{
             DeleteFileService, FileResponse, FileURLService, MoveFileService, RenameFileService,
             metadata,
         },
+        share::CreateShareLinkService,
         uri::CrUri,
         user::Token,
     },
@@ -32,12 +37,16 @@ use notify_debouncer_full::notify::{
     Event, EventKind,
     event::{CreateKind, ModifyKind, RemoveKind, RenameMode},
 };
+use serde::Deserialize;
 use std::{
     collections::HashMap,
     ops::Range,
     path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
 };
 use tokio::sync::oneshot::Sender;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 use windows::Win32::UI::Shell::SHCNE_ATTRIBUTES;
 const PAGE_SIZE: i32 = 1000;
@@ -108,8 +117,14 @@ pub enum MountCommand {
         path: PathBuf,
         ticket: ticket::FetchData,
         range: Range<u64>,
+        file_size: u64,
         response: Sender<Result<()>>,
     },
+    /// The platform no longer needs `range` of a fetch, e.g. the copy was cancelled
+    CancelFetchData {
+        path: PathBuf,
+        range: Range<u64>,
+    },
     ProcessFsEvents {
         events: GroupedFsEvents,
     },
@@ -126,6 +141,11 @@ pub enum MountCommand {
         source: PathBuf,
         destination: PathBuf,
     },
+    /// A callback on `path` failed in a way `recovery` usually fixes
+    Recover {
+        recovery: CfapiRecovery,
+        path: PathBuf,
+    },
 }
 
 // SAFETY: Windows CFAPI is designed to allow callbacks from arbitrary threads.
@@ -137,9 +157,9 @@ unsafe impl Send for MountCommand {}
 /// These can be sent from external sources like context menus or other UI components
 #[derive(Debug)]
 pub enum ManagerCommand {
-    /// View a file or folder online in the web interface
+    /// View files and folders online in the web interface
     ViewOnline {
-        path: PathBuf,
+        paths: Vec<PathBuf>,
     },
     PersistConfig,
     GenerateThumbnail {
@@ -156,6 +176,11 @@ pub enum ManagerCommand {
         path: String,
         action: ConflictAction,
     },
+    /// Pin ("Always keep on this device") or unpin ("Free up space") files and folders
+    SetPinState {
+        paths: Vec<PathBuf>,
+        state: PinState,
+    },
     /// Show conflict resolution toast for a file
     ShowConflictToast {
         path: PathBuf,
@@ -173,10 +198,33 @@ pub enum ManagerCommand {
     OpenStorageDetailsUrl {
         syncroot_id: String,
     },
+    /// Create share links for files and folders and copy them to the clipboard
+    CopyShareLink {
+        paths: Vec<PathBuf>,
+    },
+    /// Get the cloud properties of a synced file for the shell property handler
+    GetCloudProperties {
+        path: PathBuf,
+        response: Sender<Result<Option<CloudFileProperties>>>,
+    },
+    /// Request to open the conflicts window in the UI, filtered to a file
+    OpenConflictsWindow {
+        path: PathBuf,
+    },
+    /// Request to open the "Send to Cloudreve" window for files outside the sync roots
+    OpenSendToWindow {
+        paths: Vec<PathBuf>,
+    },
+    /// Request to sign in again to the drive of a sync root
+    OpenReauthorizeWindow {
+        syncroot_id: String,
+    },
     /// Request to open the sync status window in the UI
     OpenSyncStatusWindow,
     /// Request to open the settings window in the UI
     OpenSettingsWindow,
+    /// Request to restart the app to install the downloaded update
+    RestartToUpdate,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -197,19 +245,172 @@ impl ConflictAction {
     }
 }
 
+/// Which version of a conflicted file the conflict center keeps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictChoice {
+    /// Upload the local version over the remote one
+    KeepLocal,
+    /// Replace the local version with the remote one
+    KeepRemote,
+    /// Keep the remote version and copy the local one to a new file next to it
+    KeepBoth,
+}
+
+impl From<ConflictChoice> for ConflictAction {
+    fn from(choice: ConflictChoice) -> Self {
+        match choice {
+            ConflictChoice::KeepLocal => ConflictAction::OverwriteRemote,
+            ConflictChoice::KeepRemote => ConflictAction::KeepRemote,
+            ConflictChoice::KeepBoth => ConflictAction::SaveAsNew,
+        }
+    }
+}
+
+/// Cloud metadata of a synced file, shown in Explorer's Details pane
+#[derive(Debug, Clone)]
+pub struct CloudFileProperties {
+    pub remote_uri: String,
+    pub shared: bool,
+    /// When the local file last matched the cloud, as a Unix timestamp
+    pub last_synced: i64,
+    pub etag: String,
+}
+
 impl Mount {
+    /// Set the pin state behind "Always keep on this device" (`Pinned`) and "Free up
+    /// space" (`Unpinned`). A folder passes the state on to everything inside it. The
+    /// filesystem watcher then hydrates or dehydrates the files whose state changed.
+    pub async fn set_pin_state(&self, path: &Path, state: PinState) -> Result<()> {
+        let sync_path = self.get_sync_path().await;
+        if !path.starts_with(&sync_path) {
+            return Err(anyhow::anyhow!(
+                "{} is not inside the sync folder",
+                path.display()
+            ));
+        }
+
+        let mut options = PinOptions::default();
+        if path.is_dir() {
+            options.recurse();
+        }
+        OpenOptions::new()
+            .open(path)
+            .with_context(|| format!("Failed to open placeholder {}", path.display()))?
+            .mark_pin(state, options)
+            .with_context(|| format!("Failed to set pin state of {}", path.display()))?;
+
+        tracing::info!(target: "drive::commands", path = %path.display(), state = ?state, "Pin state set");
+        _ = notify_shell_change(&path.to_path_buf(), SHCNE_ATTRIBUTES);
+        Ok(())
+    }
+
+    /// Turn hydrated, in-sync files under `path` (a file or a folder) back into cloud-only
+    /// placeholders. With `unused_for`, only files not accessed for that long are
+    /// dehydrated. Pinned files and files with local changes are left alone.
+    pub async fn free_up_space(
+        &self,
+        path: &Path,
+        unused_for: Option<Duration>,
+    ) -> Result<FreedSpace> {
+        let sync_path = self.get_sync_path().await;
+        if !path.starts_with(&sync_path) {
+            return Err(anyhow::anyhow!(
+                "{} is not inside the sync folder",
+                path.display()
+            ));
+        }
+
+        let cutoff = unused_for.and_then(|unused_for| SystemTime::now().checked_sub(unused_for));
+        let root = path.to_path_buf();
+        let freed = tokio::task::spawn_blocking(move || dehydrate_tree(&root, cutoff))
+            .await
+            .context("Dehydration task panicked")?;
+
+        tracing::info!(
+            target: "drive::commands",
+            path = %path.display(),
+            files = freed.files,
+            bytes = freed.bytes,
+            skipped = freed.skipped,
+            "Freed up space"
+        );
+        if freed.files > 0 {
+            _ = notify_shell_change(&path.to_path_buf(), SHCNE_ATTRIBUTES);
+        }
+        Ok(freed)
+    }
+
     pub async fn fetch_data(
         &self,
         path: PathBuf,
         ticket: ticket::FetchData,
         range: Range<u64>,
+        file_size: u64,
+    ) -> Result<()> {
+        // The user is waiting on this file, so pause bulk transfers until it is fetched.
+        // Prefetches are not waited on and run alongside them.
+        let prefetching = self.prefetcher.is_prefetching(&path);
+        let _interactive = (!prefetching).then(|| self.task_queue.begin_interactive());
+        let opened = (!prefetching && range.start == 0).then(|| path.clone());
+
+        // Track the hydration as a task so it shows in the transfer list and history.
+        // Failing to record it must not fail the fetch itself.
+        let mut hydration = match self
+            .task_queue
+            .begin_hydration(&path, range.end - range.start)
+        {
+            Ok(hydration) => Some(hydration),
+            Err(e) => {
+                tracing::debug!(target: "drive::commands", path = %path.display(), error = %e, "Hydration not tracked as a task");
+                None
+            }
+        };
+
+        // Cancelled by the user through the task list or by the platform through
+        // CancelFetchData
+        let cancel_token = hydration
+            .as_ref()
+            .map(|hydration| hydration.cancel_token().clone())
+            .unwrap_or_default();
+        let _registration =
+            self.fetch_cancellations
+                .register(&path, range.clone(), cancel_token.clone());
+
+        let result = self
+            .hydrate_range(
+                &path,
+                ticket,
+                range,
+                file_size,
+                &cancel_token,
+                hydration.as_mut(),
+            )
+            .await;
+        if let Some(hydration) = hydration {
+            hydration.finish(&result);
+        }
+        if let (Ok(()), Some(path)) = (&result, opened) {
+            self.prefetcher.hint(PrefetchHint::Opened(path));
+        }
+        result
+    }
+
+    async fn hydrate_range(
+        &self,
+        path: &Path,
+        ticket: ticket::FetchData,
+        range: Range<u64>,
+        file_size: u64,
+        cancel_token: &CancellationToken,
+        mut hydration: Option<&mut HydrationTask>,
     ) -> Result<()> {
         let config = self.config.read().await;
         let remote_base = config.remote_path.clone();
         let sync_path = config.sync_path.clone();
         drop(config);
 
-        let uri = local_path_to_cr_uri(path.clone(), sync_path, remote_base)
+        let uri = local_path_to_cr_uri(path.to_path_buf(), sync_path, remote_base)
             .context("failed to convert local path to cloudreve uri")?;
 
         let file_meta = self
@@ -249,7 +450,10 @@ impl Mount {
         const BUFFER_SIZE: usize = 65536;
 
         // Create HTTP client and make a single range request
-        let client = reqwest::Client::new();
+        let client = ProxyConfig::current()
+            .apply(reqwest::Client::builder())
+            .build()
+            .context("failed to create HTTP client")?;
         let range_header = format!("bytes={}-{}", range.start, range.end - 1);
 
         let response = client
@@ -268,10 +472,19 @@ impl Mount {
         let mut current_offset = range.start;
         let mut bytes_transferred = 0u64;
         let mut accumulator: Vec<u8> = Vec::with_capacity(BUFFER_SIZE);
+        let mut progress = PlaceholderProgressReporter::new(&ticket, &range, file_size);
 
         use futures::StreamExt;
 
-        while let Some(chunk_result) = stream.next().await {
+        loop {
+            // Stop waiting on the network as soon as the fetch is cancelled
+            let chunk_result = tokio::select! {
+                _ = cancel_token.cancelled() => anyhow::bail!("Hydration cancelled"),
+                chunk_result = stream.next() => match chunk_result {
+                    Some(chunk_result) => chunk_result,
+                    None => break,
+                },
+            };
             let chunk = chunk_result.context("failed to read chunk from stream")?;
             accumulator.extend_from_slice(&chunk);
 
@@ -287,11 +500,10 @@ impl Mount {
 
                 bytes_transferred += write_data.len() as u64;
                 current_offset += write_data.len() as u64;
-
-                // Report progress to Windows
-                ticket
-                    .report_progress(total_bytes, bytes_transferred)
-                    .map_err(|e| anyhow::anyhow!("failed to report progress: {:?}", e))?;
+                if let Some(hydration) = hydration.as_deref_mut() {
+                    hydration.add_bytes(write_data.len() as u64);
+                }
+                progress.add_bytes(write_data.len() as u64);
             }
         }
 
@@ -302,14 +514,16 @@ impl Mount {
             })?;
 
             bytes_transferred += accumulator.len() as u64;
+            if let Some(hydration) = hydration.as_deref_mut() {
+                hydration.add_bytes(accumulator.len() as u64);
+            }
+            progress.add_bytes(accumulator.len() as u64);
             // current_offset += accumulator.len() as u64;
-
-            // Final progress report
-            ticket
-                .report_progress(total_bytes, bytes_transferred)
-                .map_err(|e| anyhow::anyhow!("failed to report progress: {:?}", e))?;
         }
 
+        // Final progress report
+        progress.finish();
+
         tracing::debug!(
             target: "drive::commands",
             bytes_transferred = bytes_transferred,
@@ -365,7 +579,8 @@ impl Mount {
         })
     }
 
-    pub async fn generate_thumbnail(&self, path: PathBuf) -> Result<Bytes> {
+    /// Get the thumbnail of a file from `cache`, or fetch it from the server and cache it
+    pub async fn generate_thumbnail(&self, path: PathBuf, cache: &ThumbnailCache) -> Result<Bytes> {
         let file_meta = self
             .inventory
             .query_by_path(path.to_str().unwrap_or(""))
@@ -388,6 +603,19 @@ impl Mount {
         let uri = local_path_to_cr_uri(path.clone(), sync_path, remote_base)
             .context("failed to convert local path to cloudreve uri")?
             .to_string();
+
+        // Entity id changes with the content, fall back to the modification time without it
+        let version = if file_meta.etag.is_empty() {
+            file_meta.updated_at.to_string()
+        } else {
+            file_meta.etag.clone()
+        };
+        let cache_key = ThumbnailCache::key(&self.id, &uri, &version);
+        if let Some(cached) = cache.get(&cache_key) {
+            tracing::trace!(target: "drive::commands", path = %path.display(), "Thumbnail cache hit");
+            return Ok(cached);
+        }
+
         let thumb_res = self.cr_client.get_file_thumb(uri.as_str(), None).await?;
 
         // Download the thumbnail
@@ -401,7 +629,82 @@ impl Mount {
                 thumb_response.status()
             ));
         }
-        Ok(thumb_response.bytes().await?)
+        let thumbnail = thumb_response.bytes().await?;
+
+        let max_bytes = ConfigManager::try_get()
+            .map(|config| config.thumbnail_cache_max_bytes())
+            .unwrap_or(0);
+        if let Err(e) = cache.put(&cache_key, &thumbnail, max_bytes) {
+            tracing::warn!(target: "drive::commands", path = %path.display(), error = ?e, "Failed to cache thumbnail");
+        }
+        Ok(thumbnail)
+    }
+
+    /// Cloud metadata of a synced file or folder, `None` if it is not in the inventory.
+    /// The file was last in sync when its current version was recorded, or when a task
+    /// on it last completed, whichever is later.
+    pub async fn cloud_properties(&self, path: &Path) -> Result<Option<CloudFileProperties>> {
+        let local_path = path.to_str().unwrap_or("");
+        let Some(file_meta) = self
+            .inventory
+            .query_by_path(local_path)
+            .context("failed to query metadata by path")?
+        else {
+            return Ok(None);
+        };
+        let last_task_at = self
+            .inventory
+            .last_completed_task_at(local_path)
+            .context("failed to query last completed task")?;
+
+        let (sync_path, remote_base) = {
+            let config = self.config.read().await;
+            (config.sync_path.clone(), config.remote_path.to_string())
+        };
+        let remote_uri = local_path_to_cr_uri(path.to_path_buf(), sync_path, remote_base)
+            .context("failed to convert local path to cloudreve uri")?
+            .to_string();
+        Ok(Some(CloudFileProperties {
+            remote_uri,
+            shared: file_meta.shared,
+            last_synced: last_task_at.map_or(file_meta.updated_at, |last_task_at| {
+                last_task_at.max(file_meta.updated_at)
+            }),
+            etag: file_meta.etag,
+        }))
+    }
+
+    /// Create a public share link for a synced file or folder, returning its URL
+    pub async fn create_share_link(&self, path: &Path) -> Result<String> {
+        let file_meta = self
+            .inventory
+            .query_by_path(path.to_str().unwrap_or(""))
+            .context("failed to query metadata by path")?
+            .ok_or_else(|| anyhow::anyhow!("no metadata found for path: {:?}", path))?;
+        if !file_meta.shareable() {
+            return Err(anyhow::anyhow!(
+                "sharing is not allowed for path: {:?}",
+                path
+            ));
+        }
+
+        let (sync_path, remote_base) = {
+            let config = self.config.read().await;
+            (config.sync_path.clone(), config.remote_path.to_string())
+        };
+        let uri = local_path_to_cr_uri(path.to_path_buf(), sync_path, remote_base)
+            .context("failed to convert local path to cloudreve uri")?
+            .to_string();
+        let url = self
+            .cr_client
+            .create_share_link(&CreateShareLinkService {
+                uri,
+                ..Default::default()
+            })
+            .await
+            .context("failed to create share link")?;
+        tracing::info!(target: "drive::commands", path = %path.display(), "Share link created");
+        Ok(url)
     }
 
     pub async fn rename_completed(&self, source: PathBuf, destination: PathBuf) -> Result<()> {
@@ -413,6 +716,7 @@ impl Mount {
 
         // Commit rename in inventory
         self.inventory
+            .with_source(ChangeSource::LocalEvent)
             .rename_path(
                 source
                     .to_str()
@@ -657,7 +961,7 @@ impl Mount {
                 let cr_placeholder =
                     CrPlaceholder::new(local_path.clone(), sync_root.clone(), drive_id.clone());
                 cr_placeholder
-                    .delete_placeholder(self.inventory.clone())
+                    .delete_placeholder(Arc::new(self.inventory.with_source(ChangeSource::User)))
                     .context("failed to delete local placeholder")?;
                 self.event_blocker.register_once(
                     &EventKind::Remove(RemoveKind::Any),
@@ -670,11 +974,13 @@ impl Mount {
                 if let Err(e) = self.command_tx.send(command) {
                     tracing::error!(target: "drive::commands", error = %e, "Failed to send Sync command");
                 }
+                self.close_conflict_record(&local_path, ConflictResolution::KeepRemote, None);
             }
             ConflictAction::OverwriteRemote => {
                 if file_id > 0 {
                     // Update conflict state with overwrite and trigger upload
                     self.inventory
+                        .with_source(ChangeSource::User)
                         .mark_as_conflicted(&local_path, Some(ConflictState::Override))
                         .context("failed to mark file as conflicted")?;
                     if conflict_state.unwrap() != ConflictState::Pending {
@@ -703,6 +1009,7 @@ impl Mount {
                     );
                     return Err(err.into());
                 }
+                self.close_conflict_record(&local_path, ConflictResolution::OverwriteRemote, None);
             }
             ConflictAction::SaveAsNew => {
                 // Generate a unique new filename for the conflicted local file
@@ -724,7 +1031,7 @@ impl Mount {
                 let cr_placeholder =
                     CrPlaceholder::new(local_path.clone(), sync_root.clone(), drive_id.clone());
                 cr_placeholder
-                    .delete_placeholder(self.inventory.clone())
+                    .delete_placeholder(Arc::new(self.inventory.with_source(ChangeSource::User)))
                     .context("failed to delete local placeholder")?;
                 self.event_blocker.register_once(
                     &EventKind::Remove(RemoveKind::Any),
@@ -742,8 +1049,13 @@ impl Mount {
 
                 toast::send_general_text_toast(
                     &t!("conflictRenamed"),
-                    &t!("newName","name" => new_path.
-                file_name().unwrap_or_default().to_string_lossy().to_string()),
+                    &t!("newName","name" => isolate(new_path.
+                file_name().unwrap_or_default().to_string_lossy())),
+                );
+                self.close_conflict_record(
+                    &local_path,
+                    ConflictResolution::SaveAsNew,
+                    Some(&new_path.to_string_lossy()),
                 );
             }
         }
@@ -751,6 +1063,30 @@ impl Mount {
         Ok(())
     }
 
+    /// Mark the pending conflict record for `local_path` as resolved, if there is one
+    fn close_conflict_record(
+        &self,
+        local_path: &str,
+        resolution: ConflictResolution,
+        conflict_path: Option<&str>,
+    ) {
+        let result = self
+            .inventory
+            .get_pending_conflict_by_path(local_path)
+            .and_then(|record| match record {
+                Some(record) => {
+                    self.inventory
+                        .resolve_conflict_record(record.id, resolution, conflict_path)?;
+                    refresh_shell_state(Path::new(local_path));
+                    Ok(())
+                }
+                None => Ok(()),
+            });
+        if let Err(e) = result {
+            tracing::warn!(target: "drive::commands", path = %local_path, error = %e, "Failed to update conflict record");
+        }
+    }
+
     async fn process_fs_modify_name_event(&self, events: Vec<Event>) -> Result<()> {
         tracing::trace!(target: "drive::commands", count=events.len(), "Processing filesystem modify name event");
         for event in events {
@@ -942,13 +1278,10 @@ impl Mount {
             "Processing filesystem create events"
         );
 
-        for (_remote_uri, path) in path_uri_mappings {
-            let payload = TaskPayload::upload(path.clone());
-
-            self.task_queue
-                .enqueue(payload)
-                .await
-                .context("Failed to enqueue upload task")?;
+        let paths: Vec<PathBuf> = path_uri_mappings.into_values().collect();
+        let result = self.import_local_items(paths).await?;
+        for (path, e) in result.failed {
+            tracing::error!(target: "drive::commands", path = %path.display(), error = ?e, "Failed to enqueue upload task");
         }
 
         Ok(())
@@ -1160,7 +1493,9 @@ impl Mount {
 
         if !path_strs.is_empty() {
             let count = path_strs.len();
-            self.inventory.batch_delete_by_path(path_strs)?;
+            self.inventory
+                .with_source(ChangeSource::LocalEvent)
+                .batch_delete_by_path(path_strs)?;
             tracing::debug!(
                 target: "drive::commands",
                 count = count,
@@ -1171,3 +1506,63 @@ impl Mount {
         Ok(())
     }
 }
+
+/// Dehydrate every eligible file in the tree rooted at `root`, see [`Mount::free_up_space`]
+fn dehydrate_tree(root: &Path, cutoff: Option<SystemTime>) -> FreedSpace {
+    let mut freed = FreedSpace::default();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(path) = pending.pop() {
+        let Ok(local) = LocalFileInfo::from_path(&path) else {
+            continue;
+        };
+        if !local.is_placeholder() {
+            continue;
+        }
+        if local.is_directory {
+            match std::fs::read_dir(&path) {
+                Ok(read_dir) => pending.extend(read_dir.flatten().map(|child| child.path())),
+                Err(e) => {
+                    tracing::warn!(target: "drive::commands", path = %path.display(), error = %e, "Failed to read directory while freeing up space");
+                }
+            }
+            continue;
+        }
+        if !local.in_sync() || local.pinned() == PinState::Pinned {
+            continue;
+        }
+        if let Some(cutoff) = cutoff {
+            let last_used = std::fs::metadata(&path)
+                .and_then(|meta| meta.accessed().or_else(|_| meta.modified()));
+            if !last_used.is_ok_and(|last_used| last_used <= cutoff) {
+                continue;
+            }
+        }
+
+        let on_disk = OpenOptions::new()
+            .open(&path)
+            .ok()
+            .and_then(|handle| handle.info().ok().flatten())
+            .map(|info| info.on_disk_data_size().max(0) as u64)
+            .unwrap_or(0);
+        if on_disk == 0 {
+            continue;
+        }
+
+        match OpenOptions::new()
+            .open_win32(&path)
+            .and_then(|mut placeholder| placeholder.dehydrate(0..))
+        {
+            Ok(()) => {
+                freed.files += 1;
+                freed.bytes += on_disk;
+            }
+            Err(e) => {
+                tracing::debug!(target: "drive::commands", path = %path.display(), error = %e, "Failed to dehydrate placeholder");
+                freed.skipped += 1;
+            }
+        }
+    }
+
+    freed
+}
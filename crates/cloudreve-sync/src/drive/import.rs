@@ -0,0 +1,174 @@
+//! Import of items copied, moved or dropped into the sync root from outside.
+//!
+//! Explorer reports a pasted tree as create events for whatever it touched within the
+//! debounce window, possibly only the top folder when it was moved in from the same
+//! volume. Instead of uploading each reported path on its own, the new items are
+//! collapsed to the topmost ones and walked, then turned into placeholders parent first
+//! so Explorer shows them as pending sync and the upload progress of every file. Upload
+//! tasks are queued in the same order, so folders are created before their content.
+
+use crate::{
+    cfapi::placeholder::{ConvertOptions, LocalFileInfo, OpenOptions},
+    drive::{mounts::Mount, placeholder::CrPlaceholder},
+    tasks::TaskPayload,
+};
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Outcome of [`Mount::import_local_items`]
+#[derive(Debug, Default)]
+pub struct ImportResult {
+    /// Items with an upload task queued
+    pub queued: usize,
+    /// Items already known to the sync root, or ignored
+    pub skipped: usize,
+    pub failed: Vec<(PathBuf, anyhow::Error)>,
+}
+
+impl Mount {
+    /// Turn new local items at `paths`, and everything below new folders among them,
+    /// into pending placeholders and queue their upload, parents first
+    pub async fn import_local_items(&self, paths: Vec<PathBuf>) -> Result<ImportResult> {
+        let (sync_path, drive_id) = {
+            let config = self.config.read().await;
+            (
+                config.sync_path.clone(),
+                Uuid::parse_str(&config.id).context("invalid drive ID")?,
+            )
+        };
+
+        let mut result = ImportResult::default();
+        for path in import_plan(&paths) {
+            if self.ignore_matcher.is_match(&path) {
+                result.skipped += 1;
+                continue;
+            }
+            let Ok(local) = LocalFileInfo::from_path(&path) else {
+                continue;
+            };
+            // Placeholders already belong to the sync root, e.g. an earlier import of
+            // the same tree or items created by a sync
+            if !local.exists || local.is_placeholder() {
+                result.skipped += 1;
+                continue;
+            }
+
+            // A file still being written cannot be converted yet; it is uploaded all
+            // the same and becomes a placeholder once the upload commits it
+            if let Err(e) = convert_to_pending_placeholder(&path, &local) {
+                tracing::debug!(target: "drive::import", path = %path.display(), error = %e, "Failed to convert new item to placeholder");
+            } else if !local.is_directory {
+                let placeholder = CrPlaceholder::new(&path, sync_path.clone(), drive_id);
+                let size = local.file_size.unwrap_or(0);
+                if let Err(e) = placeholder.update_transfer_progress(Some((0, size))) {
+                    tracing::debug!(target: "drive::import", path = %path.display(), error = %e, "Failed to show pending upload");
+                }
+            }
+
+            match self
+                .task_queue
+                .enqueue(TaskPayload::upload(path.clone()))
+                .await
+            {
+                Ok(_) => result.queued += 1,
+                Err(e) => result.failed.push((path, e)),
+            }
+        }
+
+        tracing::info!(
+            target: "drive::import",
+            id = %self.id,
+            queued = result.queued,
+            skipped = result.skipped,
+            failed = result.failed.len(),
+            "Imported new local items"
+        );
+        Ok(result)
+    }
+}
+
+/// Convert a new local item into a placeholder that is not in sync yet. It gets its
+/// file identity and the in-sync state when its upload is committed.
+fn convert_to_pending_placeholder(path: &Path, local: &LocalFileInfo) -> Result<()> {
+    let mut handle = match local.is_directory {
+        true => OpenOptions::new().open(path),
+        false => OpenOptions::new().open_win32(path),
+    }
+    .context("failed to open new item")?;
+    handle
+        .convert_to_placeholder(ConvertOptions::default(), None)
+        .context("failed to convert to placeholder")?;
+    Ok(())
+}
+
+/// Every path to import for the reported `paths`: the topmost ones and, for folders,
+/// their whole content, ordered so a folder always comes before its children
+fn import_plan(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut plan = Vec::new();
+    let mut pending = top_level_paths(paths);
+    pending.reverse();
+    while let Some(path) = pending.pop() {
+        if path.is_dir() {
+            match std::fs::read_dir(&path) {
+                Ok(read_dir) => {
+                    let mut children: Vec<PathBuf> =
+                        read_dir.flatten().map(|child| child.path()).collect();
+                    children.sort();
+                    pending.extend(children.into_iter().rev());
+                }
+                Err(e) => {
+                    tracing::warn!(target: "drive::import", path = %path.display(), error = %e, "Failed to read new folder");
+                }
+            }
+        }
+        plan.push(path);
+    }
+    plan
+}
+
+/// `paths` without duplicates and without those below another one of them
+fn top_level_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut sorted = paths.to_vec();
+    // Ancestors sort before their descendants
+    sorted.sort();
+    sorted.dedup();
+
+    let mut top_level: Vec<PathBuf> = Vec::new();
+    for path in sorted {
+        if top_level.iter().any(|parent| path.starts_with(parent)) {
+            continue;
+        }
+        top_level.push(path);
+    }
+    top_level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_paths_collapse_to_top_level() {
+        let paths: Vec<PathBuf> = [
+            "C:/Drive/Photos/2024/a.jpg",
+            "C:/Drive/Photos",
+            "C:/Drive/Photos/2024",
+            "C:/Drive/report.pdf",
+            "C:/Drive/Photos",
+            "C:/Drive/Photos 2/b.jpg",
+        ]
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+
+        assert_eq!(
+            top_level_paths(&paths),
+            vec![
+                PathBuf::from("C:/Drive/Photos"),
+                PathBuf::from("C:/Drive/Photos 2/b.jpg"),
+                PathBuf::from("C:/Drive/report.pdf"),
+            ]
+        );
+    }
+}
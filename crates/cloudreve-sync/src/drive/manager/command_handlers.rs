@@ -1,13 +1,21 @@
 use super::DriveManager;
 use crate::drive::commands::{ManagerCommand, MountCommand};
+use crate::drive::mounts::Mount;
+use crate::drive::sync::SyncMode;
+use crate::drive::thumbnails::THUMBNAIL_FETCH_TIMEOUT;
 use crate::drive::utils::{local_path_to_cr_uri, view_online_url};
-use crate::utils::toast::send_conflict_toast;
+use crate::utils::clipboard;
+use crate::utils::toast::{send_conflict_toast, send_general_text_toast};
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::spawn;
 use tokio::sync::mpsc;
 
+/// Browser pages opened at most by one "View online" of a multi-selection
+const MAX_VIEW_ONLINE_PAGES: usize = 10;
+
 impl DriveManager {
     /// Spawn the command processor task
     pub async fn spawn_command_processor(self: &Arc<Self>) {
@@ -32,13 +40,9 @@ impl DriveManager {
             tracing::trace!(target: "drive::manager", command = ?command, "Processing command");
             let manager = manager.clone();
             match command {
-                ManagerCommand::ViewOnline { path } => {
-                    let path = path.clone();
+                ManagerCommand::ViewOnline { paths } => {
                     spawn(async move {
-                        let path = path.clone();
-                        let result = manager.handle_view_online(path.clone()).await;
-                        // TODO: handle result in UI
-                        tracing::debug!(target: "drive::manager", path = %path.display(), result = ?result, "ViewOnline command result");
+                        manager.handle_view_online(&paths).await;
                     });
                 }
                 ManagerCommand::PersistConfig => {
@@ -48,25 +52,12 @@ impl DriveManager {
                     }
                 }
                 ManagerCommand::SyncNow { paths, mode } => {
-                    let paths = paths.clone();
-                    if paths.len() < 1 {
+                    if paths.is_empty() {
                         tracing::error!(target: "drive::manager", "No paths provided for sync command");
-                        return;
+                        continue;
                     }
                     spawn(async move {
-                        let drive = manager
-                            .search_drive_by_child_path(
-                                paths.get(0).unwrap().to_str().unwrap_or(""),
-                            )
-                            .await;
-                        if let Some(drive) = drive {
-                            let _ = drive.command_tx.send(MountCommand::Sync {
-                                local_paths: paths,
-                                mode: mode,
-                            });
-                        } else {
-                            tracing::error!(target: "drive::manager", "No drive found for path: {:?}", paths.get(0).unwrap());
-                        }
+                        manager.handle_sync_now(paths, mode).await;
                     });
                 }
                 ManagerCommand::GenerateThumbnail { path, response } => {
@@ -76,7 +67,17 @@ impl DriveManager {
                             .search_drive_by_child_path(path.to_str().unwrap_or(""))
                             .await;
                         if let Some(drive) = drive {
-                            let result = drive.generate_thumbnail(path.clone()).await;
+                            let result = tokio::time::timeout(
+                                THUMBNAIL_FETCH_TIMEOUT,
+                                drive.generate_thumbnail(path.clone(), &manager.thumbnail_cache),
+                            )
+                            .await
+                            .unwrap_or_else(|_| {
+                                Err(anyhow::anyhow!(
+                                    "timed out fetching thumbnail for {:?}",
+                                    path
+                                ))
+                            });
                             if let Err(e) = result {
                                 tracing::error!(target: "drive::manager", error = %e, "Failed to generate thumbnail");
                                 let _ = response.send(Err(e));
@@ -109,6 +110,18 @@ impl DriveManager {
                         }
                     });
                 }
+                ManagerCommand::SetPinState { paths, state } => {
+                    spawn(async move {
+                        if let Err(e) = manager.set_pin_state(&paths, state).await {
+                            tracing::error!(target: "drive::manager", error = %e, "Failed to set pin state");
+                        }
+                    });
+                }
+                ManagerCommand::CopyShareLink { paths } => {
+                    spawn(async move {
+                        manager.handle_copy_share_link(&paths).await;
+                    });
+                }
                 ManagerCommand::ShowConflictToast { path } => {
                     let path = path.clone();
                     spawn(async move {
@@ -118,6 +131,18 @@ impl DriveManager {
                         }
                     });
                 }
+                ManagerCommand::GetCloudProperties { path, response } => {
+                    spawn(async move {
+                        let result = match manager
+                            .search_drive_by_child_path(path.to_str().unwrap_or(""))
+                            .await
+                        {
+                            Some(drive) => drive.cloud_properties(&path).await,
+                            None => Ok(None),
+                        };
+                        let _ = response.send(result);
+                    });
+                }
                 ManagerCommand::GetDriveStatusUI { syncroot_id, response } => {
                     spawn(async move {
                         let result = manager.get_drive_status_by_syncroot_id(&syncroot_id).await;
@@ -140,54 +165,207 @@ impl DriveManager {
                         }
                     });
                 }
+                ManagerCommand::OpenReauthorizeWindow { syncroot_id } => {
+                    spawn(async move {
+                        let result = manager.handle_open_reauthorize_window(&syncroot_id).await;
+                        if let Err(e) = result {
+                            tracing::error!(target: "drive::manager", syncroot_id = %syncroot_id, error = %e, "Failed to open reauthorize window");
+                        }
+                    });
+                }
+                ManagerCommand::OpenSendToWindow { paths } => {
+                    manager.event_broadcaster.open_send_to_window(&paths);
+                }
+                ManagerCommand::OpenConflictsWindow { path } => {
+                    manager
+                        .event_broadcaster
+                        .open_conflicts_window(&path.to_string_lossy());
+                }
                 ManagerCommand::OpenSyncStatusWindow => {
                     manager.event_broadcaster.open_sync_status_window();
                 }
                 ManagerCommand::OpenSettingsWindow => {
                     manager.event_broadcaster.open_settings_window();
                 }
+                ManagerCommand::RestartToUpdate => {
+                    manager.event_broadcaster.restart_to_update();
+                }
             }
         }
 
         tracing::info!(target: "drive::manager", "Command processor stopped");
     }
 
-    /// Handle ViewOnline command
-    pub(super) async fn handle_view_online(&self, path: PathBuf) -> Result<()> {
-        tracing::debug!(target: "drive::manager", path = %path.display(), "ViewOnline command");
+    /// Handle ViewOnline command. A single file opens its folder with the file previewed;
+    /// a batch opens every distinct folder once, up to [`MAX_VIEW_ONLINE_PAGES`].
+    pub(super) async fn handle_view_online(&self, paths: &[PathBuf]) {
+        tracing::debug!(target: "drive::manager", count = paths.len(), "ViewOnline command");
+
+        let preview_file = paths.len() == 1;
+        let mut urls: Vec<String> = Vec::new();
+        for path in paths {
+            match self.view_online_url_for(path, preview_file).await {
+                Ok(url) if !urls.contains(&url) => urls.push(url),
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!(target: "drive::manager", path = %path.display(), error = ?e, "Failed to resolve online URL");
+                }
+            }
+        }
+
+        if urls.len() > MAX_VIEW_ONLINE_PAGES {
+            tracing::info!(target: "drive::manager", count = urls.len(), "Too many folders to view online, opening the first ones");
+            urls.truncate(MAX_VIEW_ONLINE_PAGES);
+        }
+        for url in urls {
+            if let Err(e) = open::that(&url) {
+                tracing::error!(target: "drive::manager", url = %url, error = ?e, "Failed to open online URL");
+            }
+        }
+    }
 
+    /// Web URL showing the file or folder at `path`, as opened by "View online" on that
+    /// single item in Explorer
+    pub async fn online_url(&self, path: &Path) -> Result<String> {
+        self.view_online_url_for(&path.to_path_buf(), true).await
+    }
+
+    /// Web URL showing `path`: folders open themselves, files open their folder, with the
+    /// file previewed if `preview_file` is set
+    pub(super) async fn view_online_url_for(
+        &self,
+        path: &PathBuf,
+        preview_file: bool,
+    ) -> Result<String> {
         // Find the drive that contains this path
         let mount = self
             .search_drive_by_child_path(path.to_str().unwrap_or(""))
             .await
             .ok_or_else(|| anyhow::anyhow!("No drive found for path: {:?}", path))?;
 
-        let file_meta = self
+        let file_meta = mount
             .inventory
             .query_by_path(path.to_str().unwrap_or(""))
             .context("Failed to query file metadata")?;
 
         let config = mount.get_config().await;
+        if *path == config.sync_path {
+            return view_online_url(&config.remote_path, None, &config);
+        }
+
         let (sync_path, remote_path) =
             { (config.sync_path.clone(), config.remote_path.to_string()) };
         let uri = local_path_to_cr_uri(path.clone(), sync_path, remote_path)
             .context("failed to convert local path to cloudreve uri")?
             .to_string();
 
-        // Determine which URL to open
-        let url = match file_meta {
-            // If no metadata, assume it's the sync root, open folder
-            None => view_online_url(&config.remote_path, None, &config)?,
-            Some(ref meta) if meta.is_folder => view_online_url(&uri, None, &config)?,
-            Some(ref _meta) => {
-                use cloudreve_api::models::uri::CrUri;
-                let parent_path = CrUri::new(&uri)?.parent()?.to_string();
-                view_online_url(&parent_path, Some(&uri), &config)?
+        // Items not in the inventory yet are told apart by the local file system
+        let is_folder = match file_meta {
+            Some(ref meta) => meta.is_folder,
+            None => path.is_dir(),
+        };
+        if is_folder {
+            return view_online_url(&uri, None, &config);
+        }
+
+        use cloudreve_api::models::uri::CrUri;
+        let parent_path = CrUri::new(&uri)?.parent()?.to_string();
+        let open_file = preview_file.then_some(uri.as_str());
+        view_online_url(&parent_path, open_file, &config)
+    }
+
+    /// Handle SyncNow command: queue a sync of every path on its drive, and confirm a
+    /// batch with one summary toast
+    pub(super) async fn handle_sync_now(&self, paths: Vec<PathBuf>, mode: SyncMode) {
+        let total = paths.len();
+        let mut batches: HashMap<String, (Arc<Mount>, Vec<PathBuf>)> = HashMap::new();
+        let mut skipped = 0;
+        for path in paths {
+            match self
+                .search_drive_by_child_path(path.to_str().unwrap_or(""))
+                .await
+            {
+                Some(mount) => {
+                    batches
+                        .entry(mount.id.clone())
+                        .or_insert_with(|| (mount.clone(), Vec::new()))
+                        .1
+                        .push(path);
+                }
+                None => {
+                    tracing::warn!(target: "drive::manager", path = %path.display(), "No drive found for path");
+                    skipped += 1;
+                }
+            }
+        }
+
+        let mut queued = 0;
+        for (mount, local_paths) in batches.into_values() {
+            let count = local_paths.len();
+            let command = MountCommand::Sync { local_paths, mode };
+            match mount.command_tx.send(command) {
+                Ok(()) => queued += count,
+                Err(e) => {
+                    tracing::error!(target: "drive::manager", id = %mount.id, error = %e, "Failed to send Sync command");
+                    skipped += count;
+                }
+            }
+        }
+
+        // A single item needs no confirmation, its sync shows up in Explorer right away
+        if total == 1 && skipped == 0 {
+            return;
+        }
+        let message = match skipped {
+            0 => t!("syncQueuedMessage", "count" => queued).to_string(),
+            skipped => {
+                t!("syncQueuedPartlyMessage", "count" => queued, "skipped" => skipped).to_string()
             }
         };
+        send_general_text_toast(&t!("syncQueued"), &message);
+    }
 
-        open::that(url)?;
-        Ok(())
+    /// Handle CopyShareLink command: create a share link for every path, copy the links
+    /// to the clipboard and confirm with a toast
+    pub(super) async fn handle_copy_share_link(&self, paths: &[PathBuf]) {
+        let mut links = Vec::new();
+        let mut failed = 0;
+        for path in paths {
+            let result = match self
+                .search_drive_by_child_path(path.to_str().unwrap_or(""))
+                .await
+            {
+                Some(mount) => mount.create_share_link(path).await,
+                None => Err(anyhow::anyhow!("No drive found for path: {:?}", path)),
+            };
+            match result {
+                Ok(link) => links.push(link),
+                Err(e) => {
+                    tracing::warn!(target: "drive::manager", path = %path.display(), error = ?e, "Failed to create share link");
+                    failed += 1;
+                }
+            }
+        }
+
+        if links.is_empty() {
+            send_general_text_toast(&t!("shareLinkFailed"), &t!("shareLinkFailedMessage"));
+            return;
+        }
+        if let Err(e) = clipboard::set_text(&links.join("\r\n")) {
+            tracing::error!(target: "drive::manager", error = ?e, "Failed to copy share links");
+            send_general_text_toast(&t!("shareLinkFailed"), &t!("shareLinkFailedMessage"));
+            return;
+        }
+
+        let message = match (links.len(), failed) {
+            (1, 0) => links[0].clone(),
+            (count, 0) => t!("shareLinksCopiedMessage", "count" => count).to_string(),
+            (count, failed) => {
+                t!("shareLinksPartlyCopiedMessage", "count" => count, "failed" => failed)
+                    .to_string()
+            }
+        };
+        send_general_text_toast(&t!("shareLinkCopied"), &message);
     }
 
     /// Handle ShowConflictToast command
@@ -201,7 +379,7 @@ impl DriveManager {
             .ok_or_else(|| anyhow::anyhow!("No drive found for path: {:?}", path))?;
 
         // Query inventory for file metadata
-        let file_meta = self
+        let file_meta = mount
             .inventory
             .query_by_path(path.to_str().unwrap_or(""))
             .context("Failed to query file metadata")?
@@ -228,6 +406,23 @@ impl DriveManager {
         Ok(())
     }
 
+    /// Handle OpenReauthorizeWindow command - asks the UI to sign in to the drive again
+    pub(super) async fn handle_open_reauthorize_window(&self, syncroot_id: &str) -> Result<()> {
+        tracing::debug!(target: "drive::manager", syncroot_id = %syncroot_id, "OpenReauthorizeWindow command");
+
+        let status = self
+            .get_drive_status_by_syncroot_id(syncroot_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No drive found for syncroot_id: {}", syncroot_id))?;
+        let site_url = status
+            .sign_in_url
+            .ok_or_else(|| anyhow::anyhow!("Drive credential is not expired: {}", status.id))?;
+
+        self.event_broadcaster
+            .open_reauthorize_window(&status.id, &site_url, &status.name);
+        Ok(())
+    }
+
     /// Handle OpenStorageDetailsUrl command - opens storage/capacity page in browser
     pub(super) async fn handle_open_storage_details_url(&self, syncroot_id: &str) -> Result<()> {
         tracing::debug!(target: "drive::manager", syncroot_id = %syncroot_id, "OpenStorageDetailsUrl command");
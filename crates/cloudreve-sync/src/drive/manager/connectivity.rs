@@ -0,0 +1,74 @@
+use super::DriveManager;
+use crate::config::ProxyConfig;
+use anyhow::Result;
+use cloudreve_api::api::SiteApi;
+use cloudreve_api::{Client, ClientConfig};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Time the instance has to answer a connection test
+const TEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Outcome of [`DriveManager::test_connection`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionTest {
+    /// Whether the instance answered
+    pub ok: bool,
+    /// Time until the answer or the failure, in milliseconds
+    pub latency_ms: u64,
+    /// Title of the instance as reported by the server
+    pub site_title: Option<String>,
+    pub error: Option<String>,
+}
+
+impl DriveManager {
+    /// Reach the instance of drive `drive_id` through `proxy`, the saved proxy if
+    /// `None`, so proxy settings can be verified before they are saved. The drive's
+    /// own client is not used, as it keeps the proxy it was started with.
+    pub async fn test_connection(
+        &self,
+        drive_id: &str,
+        proxy: Option<ProxyConfig>,
+    ) -> Result<ConnectionTest> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+        let proxy = proxy.unwrap_or_else(ProxyConfig::current);
+        proxy.validate()?;
+
+        let instance_url = mount.config.read().await.instance_url.clone();
+        let client = Client::new(
+            ClientConfig::new(instance_url)
+                .with_timeout(TEST_TIMEOUT.as_secs())
+                .with_user_agent(crate::USER_AGENT)
+                .with_proxy(proxy.client_proxy()),
+        );
+
+        let started = Instant::now();
+        let result = tokio::time::timeout(TEST_TIMEOUT, client.get_site_config("basic")).await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        let test = match result {
+            Ok(Ok(site)) => ConnectionTest {
+                ok: true,
+                latency_ms,
+                site_title: site.title,
+                error: None,
+            },
+            Ok(Err(e)) => ConnectionTest {
+                ok: false,
+                latency_ms,
+                site_title: None,
+                error: Some(e.to_string()),
+            },
+            Err(_) => ConnectionTest {
+                ok: false,
+                latency_ms,
+                site_title: None,
+                error: Some("Connection timed out".to_string()),
+            },
+        };
+        tracing::info!(target: "drive::manager", drive_id = %drive_id, ok = test.ok, latency_ms, "Connection tested");
+        Ok(test)
+    }
+}
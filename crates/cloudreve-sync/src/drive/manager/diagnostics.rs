@@ -0,0 +1,425 @@
+use super::DriveManager;
+use crate::config::ConfigManager;
+use crate::updater::CURRENT_VERSION;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use windows::ApplicationModel::Package;
+use windows::Win32::System::Registry::{HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ, RegGetValueW};
+use windows::core::{HSTRING, PCWSTR, w};
+use zip::write::SimpleFileOptions;
+
+/// Prefix of the daily log files, see [`crate::logging::LogConfig`]
+const LOG_FILE_PREFIX: &str = "cloudreve-sync";
+/// Number of the newest log files included in a bundle
+const MAX_LOG_FILES: usize = 3;
+/// Only the end of larger log files is included
+const MAX_LOG_BYTES: u64 = 8 * 1024 * 1024;
+/// Change journal records included per drive
+const JOURNAL_TAIL: i64 = 500;
+/// Window of the cloud filter failure summary, in seconds
+const CFAPI_FAILURE_WINDOW: i64 = 7 * 24 * 60 * 60;
+
+/// JSON keys and parameters whose values are secrets. Keys ending in `_<name>` match
+/// as well.
+const SECRET_KEYS: [&str; 8] = [
+    "access_token",
+    "refresh_token",
+    "token",
+    "secret",
+    "password",
+    "credential",
+    "sign",
+    "signature",
+];
+const REDACTED: &str = "[REDACTED]";
+const CURRENT_VERSION_KEY: &str = r"SOFTWARE\Microsoft\Windows NT\CurrentVersion";
+
+/// A diagnostics bundle written by [`DriveManager::export_diagnostics`]
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsBundle {
+    pub path: String,
+    /// Names of the files in the archive
+    pub files: Vec<String>,
+    /// Size of the archive in bytes
+    pub size_bytes: u64,
+}
+
+#[derive(Serialize)]
+struct EnvironmentInfo {
+    app_version: &'static str,
+    os: Option<String>,
+    os_version: Option<String>,
+    os_build: Option<String>,
+    arch: &'static str,
+    /// Whether the app runs from its MSIX package
+    packaged: bool,
+    language: Option<String>,
+    log_level: &'static str,
+    drive_count: usize,
+    exported_at: i64,
+}
+
+impl DriveManager {
+    /// Write a zip archive for bug reports to `dest`: the newest logs, drives.json,
+    /// inventory statistics and the change journal tail of each drive, and environment
+    /// info. Tokens, passwords and signatures are masked and the user's home directory
+    /// is replaced with `%USERPROFILE%` in every file.
+    pub async fn export_diagnostics(&self, dest: &Path) -> Result<DiagnosticsBundle> {
+        let mounts: Vec<_> = self.drives.read().await.values().cloned().collect();
+        let since = chrono::Utc::now().timestamp() - CFAPI_FAILURE_WINDOW;
+        let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+
+        for mount in &mounts {
+            let id = mount.id.as_str();
+            let stats = json!({
+                "drive_id": id,
+                "file_totals": section(mount.inventory.file_totals().map(|(files, bytes)| {
+                    json!({ "files": files, "bytes": bytes })
+                })),
+                "dead_letters": section(self.dead_letter_count(Some(id))),
+                "task_history": section(self.task_history_count(Some(id))),
+                "task_statistics": mount.task_queue.statistics(),
+                "cfapi_failures": section(self.cfapi_failure_summary(Some(id), since)),
+            });
+            entries.push((format!("inventory/{}.json", id), to_json(&stats)?));
+
+            let journal = section(mount.inventory.query_journal(None, JOURNAL_TAIL));
+            entries.push((format!("journal/{}.json", id), to_json(&journal)?));
+        }
+
+        let drives = fs::read(self.get_config_file())
+            .context("Failed to read drives.json")
+            .and_then(|raw| {
+                serde_json::from_slice::<Value>(&raw).context("Failed to parse drives.json")
+            });
+        match drives {
+            Ok(mut drives) => {
+                redact_json(&mut drives);
+                entries.push(("drives.json".to_string(), to_json(&drives)?));
+            }
+            Err(e) => {
+                tracing::warn!(target: "drive::manager", error = %e, "drives.json not included in diagnostics");
+            }
+        }
+
+        let environment = environment_info(mounts.len());
+        entries.push(("environment.json".to_string(), to_json(&environment)?));
+
+        let dest = dest.to_path_buf();
+        let bundle = tokio::task::spawn_blocking(move || {
+            entries.extend(read_logs(&ConfigManager::get_log_dir()));
+            write_bundle(&dest, entries)
+        })
+        .await
+        .context("Diagnostics export task panicked")??;
+
+        tracing::info!(target: "drive::manager", path = %bundle.path, files = bundle.files.len(), size = bundle.size_bytes, "Diagnostics exported");
+        Ok(bundle)
+    }
+}
+
+/// Value of one bundle section, or the error that kept it from being collected
+fn section<T: Serialize>(result: Result<T>) -> Value {
+    match result {
+        Ok(value) => json!(value),
+        Err(e) => json!({ "error": format!("{:#}", e) }),
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    serde_json::to_vec_pretty(value).context("Failed to serialize diagnostics")
+}
+
+fn environment_info(drive_count: usize) -> EnvironmentInfo {
+    let config = ConfigManager::try_get().map(|manager| manager.get_config());
+    EnvironmentInfo {
+        app_version: CURRENT_VERSION,
+        os: os_string(w!("ProductName")),
+        os_version: os_string(w!("DisplayVersion")),
+        os_build: os_string(w!("CurrentBuild")),
+        arch: std::env::consts::ARCH,
+        packaged: Package::Current().is_ok(),
+        language: config.as_ref().and_then(|c| c.language.clone()),
+        log_level: config
+            .as_ref()
+            .map(|c| c.log_level.as_str())
+            .unwrap_or_default(),
+        drive_count,
+        exported_at: chrono::Utc::now().timestamp(),
+    }
+}
+
+/// A string value of the Windows version key
+fn os_string(name: PCWSTR) -> Option<String> {
+    let key = HSTRING::from(CURRENT_VERSION_KEY);
+    let mut size = 0u32;
+    unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            &key,
+            name,
+            RRF_RT_REG_SZ,
+            None,
+            None,
+            Some(&mut size),
+        )
+    }
+    .ok()
+    .ok()?;
+
+    let mut buffer = vec![0u16; (size as usize).div_ceil(2)];
+    unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            &key,
+            name,
+            RRF_RT_REG_SZ,
+            None,
+            Some(buffer.as_mut_ptr() as *mut _),
+            Some(&mut size),
+        )
+    }
+    .ok()
+    .ok()?;
+
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
+
+/// The newest log files in `log_dir`, each cut to its last [`MAX_LOG_BYTES`]
+fn read_logs(log_dir: &Path) -> Vec<(String, Vec<u8>)> {
+    let mut files: Vec<(PathBuf, std::time::SystemTime)> = match fs::read_dir(log_dir) {
+        Ok(read_dir) => read_dir
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(LOG_FILE_PREFIX)
+            })
+            .filter_map(|entry| {
+                let modified = entry.metadata().ok()?.modified().ok()?;
+                Some((entry.path(), modified))
+            })
+            .collect(),
+        Err(e) => {
+            tracing::warn!(target: "drive::manager", path = %log_dir.display(), error = %e, "Failed to list log files for diagnostics");
+            return Vec::new();
+        }
+    };
+    files.sort_by(|a, b| b.1.cmp(&a.1));
+
+    files
+        .into_iter()
+        .take(MAX_LOG_FILES)
+        .filter_map(|(path, _)| match read_tail(&path) {
+            Ok(content) => {
+                let name = path.file_name()?.to_string_lossy().into_owned();
+                Some((format!("logs/{}", name), content.into_bytes()))
+            }
+            Err(e) => {
+                tracing::warn!(target: "drive::manager", path = %path.display(), error = %e, "Failed to read log file for diagnostics");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Last [`MAX_LOG_BYTES`] of a file, starting at a whole line
+fn read_tail(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let truncated = len > MAX_LOG_BYTES;
+    if truncated {
+        file.seek(SeekFrom::Start(len - MAX_LOG_BYTES))?;
+    }
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+
+    let content = String::from_utf8_lossy(&buffer);
+    let content = match content.find('\n') {
+        Some(newline) if truncated => &content[newline + 1..],
+        _ => &content[..],
+    };
+    Ok(content.to_string())
+}
+
+fn write_bundle(dest: &Path, entries: Vec<(String, Vec<u8>)>) -> Result<DiagnosticsBundle> {
+    let home_dir = dirs::home_dir().map(|home| home.to_string_lossy().into_owned());
+    let file =
+        File::create(dest).with_context(|| format!("Failed to create {}", dest.display()))?;
+    let mut writer = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let mut files = Vec::with_capacity(entries.len());
+    for (name, content) in entries {
+        let content = redact_text(&String::from_utf8_lossy(&content), home_dir.as_deref());
+        writer
+            .start_file(name.as_str(), options)
+            .with_context(|| format!("Failed to add {} to the bundle", name))?;
+        writer.write_all(content.as_bytes())?;
+        files.push(name);
+    }
+    writer
+        .finish()
+        .context("Failed to finish diagnostics bundle")?;
+
+    Ok(DiagnosticsBundle {
+        path: dest.to_string_lossy().into_owned(),
+        files,
+        size_bytes: fs::metadata(dest)?.len(),
+    })
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SECRET_KEYS
+        .iter()
+        .any(|secret| key == *secret || key.ends_with(&format!("_{}", secret)))
+}
+
+/// Mask the values of secret keys anywhere in `value`
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_secret_key(key) && !value.is_null() {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Mask bearer tokens and secret `key=value` or `"key": "value"` pairs in free text, in
+/// any case, such as the `X-Amz-Signature` of presigned URLs, and replace the home
+/// directory, also in its JSON-escaped form, with `%USERPROFILE%`
+fn redact_text(text: &str, home_dir: Option<&str>) -> String {
+    let mut text = text.to_string();
+    if let Some(home) = home_dir.filter(|home| !home.is_empty()) {
+        text = text
+            .replace(home, "%USERPROFILE%")
+            .replace(&home.replace('\\', r"\\"), "%USERPROFILE%");
+    }
+
+    text = redact_values(&text, "Bearer ");
+    for key in SECRET_KEYS {
+        for marker in [
+            format!("{}=", key),
+            format!("\"{}\":\"", key),
+            format!("\"{}\": \"", key),
+        ] {
+            text = redact_values(&text, &marker);
+        }
+    }
+    text
+}
+
+/// Replace the value after each `marker`, matched ignoring ASCII case, with
+/// [`REDACTED`]. Markers in the middle of a word, such as `design=` for `sign=` or
+/// `access_token=` for `token=`, are left alone.
+fn redact_values(text: &str, marker: &str) -> String {
+    // ASCII lowercasing keeps byte offsets, so matches in `lower` index into `text`
+    let lower = text.to_ascii_lowercase();
+    let marker_lower = marker.to_ascii_lowercase();
+    let mut result = String::with_capacity(text.len());
+    let mut pos = 0;
+    while let Some(found) = lower[pos..].find(&marker_lower) {
+        let index = pos + found;
+        result.push_str(&text[pos..index]);
+        let in_word = result
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_');
+        let value_start = index + marker.len();
+        result.push_str(&text[index..value_start]);
+
+        let after = &text[value_start..];
+        let value_len = after
+            .find(|c: char| {
+                c.is_whitespace() || matches!(c, '&' | '"' | '\'' | ',' | ';' | ')' | ']' | '}')
+            })
+            .unwrap_or(after.len());
+        if in_word || value_len == 0 {
+            pos = value_start;
+        } else {
+            result.push_str(REDACTED);
+            pos = value_start + value_len;
+        }
+    }
+    result.push_str(&text[pos..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_secrets_and_home_dir() {
+        let home = r"C:\Users\alice";
+        let line = r#"GET https://cloud.example.com/file?sign=abc123&name=x Authorization: Bearer eyJ.x.y path=C:\Users\alice\Cloudreve design=keep"#;
+        let redacted = redact_text(line, Some(home));
+        assert_eq!(
+            redacted,
+            r#"GET https://cloud.example.com/file?sign=[REDACTED]&name=x Authorization: Bearer [REDACTED] path=%USERPROFILE%\Cloudreve design=keep"#
+        );
+
+        let json = r#"{"refresh_token": "r1", "sync_path": "C:\\Users\\alice\\Cloudreve"}"#;
+        assert_eq!(
+            redact_text(json, Some(home)),
+            r#"{"refresh_token": "[REDACTED]", "sync_path": "%USERPROFILE%\\Cloudreve"}"#
+        );
+
+        let mut drives = json!([{
+            "name": "Cloud",
+            "credentials": {
+                "access_token": "a1",
+                "refresh_token": "r1",
+                "refresh_expires": 1700000000,
+            },
+            "webdav_password": "p",
+            "access_expires": null,
+        }]);
+        redact_json(&mut drives);
+        assert_eq!(drives[0]["credentials"]["access_token"], REDACTED);
+        assert_eq!(drives[0]["credentials"]["refresh_token"], REDACTED);
+        assert_eq!(drives[0]["credentials"]["refresh_expires"], 1700000000);
+        assert_eq!(drives[0]["webdav_password"], REDACTED);
+        assert_eq!(drives[0]["name"], "Cloud");
+    }
+
+    #[test]
+    fn redacts_presigned_url_signatures() {
+        let s3 = "PUT https://bucket.s3.amazonaws.com/a.txt?X-Amz-Algorithm=AWS4-HMAC-SHA256\
+                  &X-Amz-Credential=AKIAEXAMPLE%2F20250101%2Fus-east-1%2Fs3%2Faws4_request\
+                  &X-Amz-Date=20250101T000000Z&X-Amz-SignedHeaders=host\
+                  &X-Amz-Signature=0123abcd failed";
+        assert_eq!(
+            redact_text(s3, None),
+            "PUT https://bucket.s3.amazonaws.com/a.txt?X-Amz-Algorithm=AWS4-HMAC-SHA256\
+             &X-Amz-Credential=[REDACTED]&X-Amz-Date=20250101T000000Z\
+             &X-Amz-SignedHeaders=host&X-Amz-Signature=[REDACTED] failed"
+        );
+
+        let oss = "https://b.oss-cn-hangzhou.aliyuncs.com/a?OSSAccessKeyId=LTAI&Expires=1&Signature=x%2By";
+        assert_eq!(
+            redact_text(oss, None),
+            "https://b.oss-cn-hangzhou.aliyuncs.com/a?OSSAccessKeyId=LTAI&Expires=1&Signature=[REDACTED]"
+        );
+
+        let cos = "https://b.cos.ap-guangzhou.myqcloud.com/a?q-sign-algorithm=sha1&q-ak=AK&q-signature=f00";
+        assert_eq!(
+            redact_text(cos, None),
+            "https://b.cos.ap-guangzhou.myqcloud.com/a?q-sign-algorithm=sha1&q-ak=AK&q-signature=[REDACTED]"
+        );
+    }
+}
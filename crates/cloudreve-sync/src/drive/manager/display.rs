@@ -0,0 +1,113 @@
+use super::{DriveManager, favicon};
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Name, icon and color of a drive as edited in the settings window
+#[derive(Debug, Clone, Deserialize)]
+pub struct DriveDisplaySettings {
+    pub name: String,
+    /// New icon of the drive, `None` keeps the current one
+    #[serde(default)]
+    pub icon: Option<DriveIcon>,
+    /// Accent color as `#rrggbb`, `None` for the default accent
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// Where the icon of a drive comes from
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DriveIcon {
+    /// The favicon of the instance, as for a new drive
+    Site,
+    /// An image file picked by the user
+    File { path: String },
+}
+
+impl DriveManager {
+    /// Rename a drive and change its icon and color. The name and icon are applied to
+    /// the sync root, so Explorer shows them as well.
+    pub async fn update_drive_settings(
+        &self,
+        drive_id: &str,
+        settings: DriveDisplaySettings,
+    ) -> Result<()> {
+        let name = settings.name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("Drive name cannot be empty"));
+        }
+        if let Some(color) = settings.color.as_deref() {
+            if !is_hex_color(color) {
+                return Err(anyhow!("Invalid color: {}", color));
+            }
+        }
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow!("Drive not found: {}", drive_id))?;
+
+        let icon = match settings.icon {
+            None => None,
+            Some(DriveIcon::Site) => {
+                let instance_url = mount.config.read().await.instance_url.clone();
+                Some(favicon::fetch_and_save_favicon(&instance_url).await?)
+            }
+            Some(DriveIcon::File { path }) => {
+                Some(favicon::save_custom_icon(drive_id, Path::new(&path))?)
+            }
+        };
+
+        let replaced_icons = {
+            let mut config = mount.config.write().await;
+            config.name = name.to_string();
+            config.color = settings.color;
+            match icon {
+                Some(icon) => vec![
+                    config.icon_path.replace(icon.ico_path),
+                    config.raw_icon_path.replace(icon.raw_path),
+                ],
+                None => Vec::new(),
+            }
+        };
+        // Favicons are shared with the other drives of the instance
+        for path in replaced_icons.into_iter().flatten() {
+            if favicon::is_custom_icon(&path) {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    tracing::warn!(target: "drive::manager", drive_id = %drive_id, error = %e, "Failed to remove replaced icon");
+                }
+            }
+        }
+
+        mount
+            .update_sync_root_branding()
+            .await
+            .context("failed to update sync root registration")?;
+        self.persist().await?;
+
+        tracing::info!(target: "drive::manager", drive_id = %drive_id, "Drive display settings updated");
+        self.event_broadcaster.drive_updated(drive_id, name);
+        Ok(())
+    }
+}
+
+/// Whether `color` is written as `#rrggbb`
+fn is_hex_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_are_hex_triplets() {
+        assert!(is_hex_color("#1a2B3c"));
+        assert!(!is_hex_color("1a2b3c"));
+        assert!(!is_hex_color("#1a2b3"));
+        assert!(!is_hex_color("#1a2b3g"));
+        assert!(!is_hex_color("red"));
+    }
+}
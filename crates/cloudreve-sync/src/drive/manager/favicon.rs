@@ -1,8 +1,13 @@
+use crate::config::ProxyConfig;
 use crate::utils::app::get_app_root;
 use anyhow::{Context, Result};
+use chrono::Utc;
 use serde::Deserialize;
 use sha2::{Digest, Sha256};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// File name prefix of the icons picked by the user
+const CUSTOM_ICON_PREFIX: &str = "custom_";
 
 /// Manifest.json structure
 #[derive(Debug, Deserialize)]
@@ -200,10 +205,43 @@ pub async fn fetch_and_save_favicon(instance_url: &str) -> Result<FaviconResult>
     })
 }
 
+/// Save an image picked by the user as the icon of drive `drive_id`.
+/// Every call saves under a new name, as Explorer caches sync root icons by path.
+pub fn save_custom_icon(drive_id: &str, source: &Path) -> Result<FaviconResult> {
+    let bytes = std::fs::read(source)
+        .with_context(|| format!("Failed to read icon image: {}", source.display()))?;
+    let img = image::load_from_memory(&bytes).context("Failed to load image")?;
+
+    let icons_dir = get_icons_dir()?;
+    let stamp = Utc::now().timestamp_millis();
+    let stem = format!("{}{}_{}", CUSTOM_ICON_PREFIX, drive_id, stamp);
+    let ico_path = icons_dir.join(format!("{}.ico", stem));
+    let raw_path = icons_dir.join(format!("{}_raw.png", stem));
+
+    save_icon(&bytes, &ico_path, true, false)?;
+    img.save_with_format(&raw_path, image::ImageFormat::Png)
+        .context("Failed to save raw icon")?;
+    tracing::info!(target: "drive::favicon", drive_id = %drive_id, ico_path = %ico_path.display(), "Custom icon saved");
+
+    Ok(FaviconResult {
+        ico_path: ico_path.to_string_lossy().to_string(),
+        raw_path: raw_path.to_string_lossy().to_string(),
+    })
+}
+
+/// Whether `path` is an icon saved by [`save_custom_icon`]. Favicons are shared by the
+/// drives of an instance, custom icons belong to one drive.
+pub fn is_custom_icon(path: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(CUSTOM_ICON_PREFIX))
+}
+
 /// Fetch icons from remote server
 async fn fetch_icons_from_remote(instance_url: &str, icons_dir: &PathBuf, hash: &str) -> Result<FaviconResult> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
+    let client = ProxyConfig::current()
+        .apply(reqwest::Client::builder().timeout(std::time::Duration::from_secs(10)))
         .build()
         .context("Failed to create HTTP client")?;
 
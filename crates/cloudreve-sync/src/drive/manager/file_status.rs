@@ -0,0 +1,118 @@
+use super::DriveManager;
+use crate::cfapi::placeholder::{LocalFileInfo, OpenOptions, PinState};
+use crate::inventory::DeadLetterRecord;
+use crate::tasks::Transfer;
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::path::Path;
+
+/// How much of a file's content is stored locally
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HydrationState {
+    /// Not a placeholder, the item has not been uploaded yet
+    LocalOnly,
+    /// Nothing is stored locally
+    CloudOnly,
+    /// Part of the content is stored locally
+    Partial,
+    /// The full content is stored locally
+    Hydrated,
+}
+
+/// Sync state of a local file, as shown in the file detail popover
+#[derive(Debug, Clone, Serialize)]
+pub struct FileStatus {
+    pub drive_id: String,
+    pub local_path: String,
+    pub is_folder: bool,
+    pub hydration: HydrationState,
+    pub pin_state: PinState,
+    pub in_sync: bool,
+    /// Pending or running task of the path
+    pub pending_task: Option<Transfer>,
+    /// Newest task of the path that gave up after failing, unless a task of the path
+    /// completed since
+    pub last_error: Option<DeadLetterRecord>,
+    /// Web URL showing the item, `None` if it is not on the server yet
+    pub remote_url: Option<String>,
+}
+
+impl DriveManager {
+    /// Sync state of the file or folder at `path`
+    pub async fn get_file_status(&self, path: &Path) -> Result<FileStatus> {
+        let path_str = path
+            .to_str()
+            .ok_or_else(|| anyhow!("Invalid path: {:?}", path))?;
+        let mount = self
+            .search_drive_by_child_path(path_str)
+            .await
+            .ok_or_else(|| anyhow!("No drive found for path: {:?}", path))?;
+        let local = LocalFileInfo::from_path(path)?;
+        if !local.exists {
+            return Err(anyhow!("File not found: {:?}", path));
+        }
+
+        let pending_task = mount
+            .task_queue
+            .transfers()?
+            .into_iter()
+            .find(|transfer| transfer.local_path == path_str);
+        // A failure is stale once a later task of the path completed
+        let last_completed_at = mount.inventory.last_completed_task_at(path_str)?;
+        let last_error = mount
+            .inventory
+            .latest_dead_letter_for_path(path_str)?
+            .filter(|record| {
+                last_completed_at.is_none_or(|completed| record.created_at >= completed)
+            });
+
+        let remote_url = if local.is_placeholder() {
+            match self.view_online_url_for(&path.to_path_buf(), true).await {
+                Ok(url) => Some(url),
+                Err(e) => {
+                    tracing::debug!(target: "drive::manager", path = %path_str, error = %e, "Failed to get remote URL of file");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(FileStatus {
+            drive_id: mount.id.clone(),
+            local_path: path_str.to_string(),
+            is_folder: local.is_directory,
+            hydration: hydration_state(path, &local),
+            pin_state: local.pinned(),
+            in_sync: local.in_sync(),
+            pending_task,
+            last_error,
+            remote_url,
+        })
+    }
+}
+
+fn hydration_state(path: &Path, local: &LocalFileInfo) -> HydrationState {
+    if !local.is_placeholder() {
+        return HydrationState::LocalOnly;
+    }
+    if !local.partial_on_disk() {
+        return HydrationState::Hydrated;
+    }
+    if local.is_directory {
+        // Some of the folder's content is only in the cloud
+        return HydrationState::Partial;
+    }
+    let on_disk = OpenOptions::new()
+        .open(path)
+        .ok()
+        .and_then(|handle| handle.info().ok().flatten())
+        .map(|info| info.on_disk_data_size())
+        .unwrap_or(0);
+    if on_disk > 0 {
+        HydrationState::Partial
+    } else {
+        HydrationState::CloudOnly
+    }
+}
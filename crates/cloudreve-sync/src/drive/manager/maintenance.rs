@@ -0,0 +1,203 @@
+use super::DriveManager;
+use crate::config::ConfigManager;
+use crate::drive::mounts::Mount;
+use crate::inventory::{ChangeSource, TaskStatus};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use cloudreve_api::api::ExplorerApi;
+use cloudreve_api::models::explorer::DeleteUploadSessionService;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Delay before the first cleanup run, so it does not compete with the initial sync
+const CLEANUP_INITIAL_DELAY: Duration = Duration::from_secs(300);
+/// Interval between cleanup runs
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(3600);
+/// How long change journal records are kept
+const JOURNAL_RETENTION: Duration = Duration::from_secs(30 * 24 * 3600);
+
+/// Counters reported by a single orphan cleanup pass
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OrphanCleanupStats {
+    /// Inventory entries whose local path no longer exists
+    pub removed_entries: usize,
+    /// Upload sessions that could no longer be resumed
+    pub removed_sessions: usize,
+}
+
+impl DriveManager {
+    /// Spawn the periodic task that removes orphaned inventory records
+    pub async fn spawn_maintenance_task(self: &Arc<Self>) {
+        let manager = self.clone();
+        let handle = tokio::spawn(async move {
+            tokio::time::sleep(CLEANUP_INITIAL_DELAY).await;
+            loop {
+                manager.cleanup_orphaned_records().await;
+                tokio::time::sleep(CLEANUP_INTERVAL).await;
+            }
+        });
+        *self.maintenance_handle.lock().await = Some(handle);
+    }
+
+    /// Delete finished tasks beyond the configured retention, and the upload sessions
+    /// they leave behind, from every drive. Returns the number of tasks deleted.
+    pub fn prune_task_history(&self) -> Result<usize> {
+        let (retention_days, max_entries) = ConfigManager::try_get()
+            .map(|config| {
+                (
+                    config.task_history_retention_days(),
+                    config.task_history_max_entries(),
+                )
+            })
+            .unwrap_or((30, 5000));
+        let cutoff = Utc::now().timestamp() - i64::from(retention_days) * 24 * 3600;
+
+        let mut deleted = 0;
+        for inventory in self.all_inventories() {
+            deleted += inventory.prune_finished_tasks(cutoff, max_entries)?;
+            inventory.delete_orphaned_upload_sessions()?;
+        }
+        tracing::info!(target: "drive::manager", deleted, "Pruned task history");
+        Ok(deleted)
+    }
+
+    /// Delete all finished tasks of one drive or all drives, and their upload sessions
+    pub fn clear_task_history(&self, drive_id: Option<&str>) -> Result<usize> {
+        let mut deleted = 0;
+        for inventory in self.inventories_for(drive_id)? {
+            deleted += inventory.clear_finished_tasks(drive_id)?;
+            inventory.delete_orphaned_upload_sessions()?;
+        }
+        tracing::info!(target: "drive::manager", drive_id = ?drive_id, deleted, "Cleared task history");
+        Ok(deleted)
+    }
+
+    /// Number of finished tasks kept for one drive or all drives
+    pub fn task_history_count(&self, drive_id: Option<&str>) -> Result<usize> {
+        let mut count = 0;
+        for inventory in self.inventories_for(drive_id)? {
+            count += inventory.count_finished_tasks(drive_id)? as usize;
+        }
+        Ok(count)
+    }
+
+    /// Run one orphan cleanup pass over all drives
+    pub async fn cleanup_orphaned_records(&self) {
+        let mounts: Vec<Arc<Mount>> = self.drives.read().await.values().cloned().collect();
+        for mount in mounts {
+            match mount.cleanup_orphaned_records().await {
+                Ok(Some(stats)) => {
+                    if stats.removed_entries > 0 || stats.removed_sessions > 0 {
+                        self.event_broadcaster.inventory_cleaned(
+                            &mount.id,
+                            stats.removed_entries,
+                            stats.removed_sessions,
+                        );
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::error!(target: "drive::manager", id = %mount.id, error = %e, "Failed to clean up orphaned records");
+                }
+            }
+        }
+    }
+}
+
+impl Mount {
+    /// Remove inventory entries whose local path is gone and upload sessions that can no
+    /// longer be resumed. Entries of paths with a pending or running task are kept.
+    ///
+    /// Returns `None` if the pass was skipped because a sync is running or the sync root
+    /// is unavailable; in the latter case every entry would look orphaned.
+    pub async fn cleanup_orphaned_records(&self) -> Result<Option<OrphanCleanupStats>> {
+        let Ok(_sync_guard) = self.sync_lock.try_lock() else {
+            tracing::debug!(target: "drive::mounts", id = %self.id, "Sync in progress, skipping orphan cleanup");
+            return Ok(None);
+        };
+
+        let sync_path = self.get_sync_path().await;
+        if !sync_path.is_dir() {
+            tracing::warn!(target: "drive::mounts", id = %self.id, sync_path = %sync_path.display(), "Sync root unavailable, skipping orphan cleanup");
+            return Ok(None);
+        }
+
+        let inventory = self.inventory.with_source(ChangeSource::Repair);
+        let drive_id = self.id.clone();
+        let removed_entries = tokio::task::spawn_blocking(move || -> Result<usize> {
+            // A pending or running task may still create the path or need its entry,
+            // e.g. a download of a placeholder that was just moved
+            let active_paths: HashSet<String> = inventory
+                .list_tasks(
+                    Some(&drive_id),
+                    Some(&[TaskStatus::Pending, TaskStatus::Running]),
+                )?
+                .into_iter()
+                .map(|task| task.local_path)
+                .collect();
+
+            let entries = inventory.list_by_drive(&drive_id)?;
+            let missing: Vec<&str> = entries
+                .iter()
+                .map(|entry| entry.local_path.as_str())
+                .filter(|path| !active_paths.contains(*path))
+                .filter(|path| !Path::new(path).exists())
+                .collect();
+            if !missing.is_empty() {
+                inventory.batch_delete_by_path(missing.clone())?;
+            }
+
+            let journal_cutoff = Utc::now().timestamp() - JOURNAL_RETENTION.as_secs() as i64;
+            inventory.prune_journal(journal_cutoff)?;
+
+            Ok(missing.len())
+        })
+        .await
+        .context("Orphan cleanup task panicked")??;
+
+        let stats = OrphanCleanupStats {
+            removed_entries,
+            removed_sessions: self.collect_stale_upload_sessions().await?,
+        };
+
+        tracing::info!(
+            target: "drive::mounts",
+            id = %self.id,
+            removed_entries = stats.removed_entries,
+            removed_sessions = stats.removed_sessions,
+            "Orphan cleanup finished"
+        );
+        Ok(Some(stats))
+    }
+
+    /// Delete upload sessions that can no longer be resumed, cancelling them on the server
+    /// first so the storage provider can release uploaded chunks
+    pub async fn collect_stale_upload_sessions(&self) -> Result<usize> {
+        let inventory = self.inventory.clone();
+        let drive_id = self.id.clone();
+        let stale =
+            tokio::task::spawn_blocking(move || inventory.list_stale_upload_sessions(&drive_id))
+                .await
+                .context("Upload session GC task panicked")??;
+
+        for session in &stale {
+            let request = DeleteUploadSessionService {
+                id: session.session_id().to_string(),
+                uri: session.remote_uri.clone(),
+            };
+            // The server drops expired sessions on its own, so failures are expected here
+            if let Err(e) = self.cr_client.delete_upload_session(&request).await {
+                tracing::debug!(target: "drive::mounts", id = %self.id, session_id = %session.session_id(), error = %e, "Failed to delete remote upload session");
+            }
+            self.inventory.delete_upload_session(&session.id)?;
+        }
+
+        if !stale.is_empty() {
+            tracing::info!(target: "drive::mounts", id = %self.id, count = stale.len(), "Removed stale upload sessions");
+        }
+        Ok(stale.len())
+    }
+}
@@ -1,30 +1,102 @@
 mod command_handlers;
+mod connectivity;
+mod diagnostics;
+mod display;
 pub(crate) mod favicon;
+mod file_status;
+mod maintenance;
+mod pause;
+mod remote_tree;
+mod resync;
+mod scheduler;
+mod send_to;
+mod storage;
+mod troubleshoot;
 mod types;
 
+pub use connectivity::ConnectionTest;
+pub use diagnostics::DiagnosticsBundle;
+pub use display::{DriveDisplaySettings, DriveIcon};
+pub use file_status::{FileStatus, HydrationState};
+pub use maintenance::OrphanCleanupStats;
+pub use pause::PauseDuration;
+pub use remote_tree::RemoteFolderNode;
+pub use scheduler::ScheduledTaskInfo;
+pub use send_to::RemoteFolder;
+pub use storage::{ReclaimedSpace, StorageBreakdown};
+pub use troubleshoot::InventoryHealth;
 pub use types::*;
 
-use crate::drive::commands::ManagerCommand;
-use crate::drive::mounts::{Credentials, DriveConfig, Mount};
+use crate::cfapi::placeholder::PinState;
+use crate::config::{ConfigManager, ScheduledJob};
+use crate::drive::commands::{ConflictAction, ConflictChoice, ManagerCommand, MountCommand};
+use crate::drive::ignore::IgnoreMatcher;
+use crate::drive::mounts::{
+    Credentials, DriveConfig, Mount, PopulationMode, SyncRootPolicy, TASK_TYPE_LIMITS_KEY,
+    VALIDATE_DATA_KEY,
+};
+use crate::drive::sync::SyncMode;
+use crate::drive::thumbnails::ThumbnailCache;
+use crate::drive::usn::USN_JOURNAL_KEY;
 use crate::EventBroadcaster;
-use crate::inventory::InventoryDb;
-use crate::tasks::TaskProgress;
+use crate::events::EventFilter;
+use crate::inventory::{
+    ActivityFilter, ActivityPage, CfapiFailureRecord, CfapiFailureSummary, ConflictPage, ConflictRecord, DeadLetterRecord, EntryFilter, EntryPage, EntrySort, InventoryDb,
+    InventorySnapshot, PageRequest, RecentTasks, SnapshotImportStats, TaskStatus,
+};
+use crate::tasks::{TaskKind, TaskProgress, TaskQueue, TaskQueueStats, TaskStatistics, Transfer};
 use anyhow::{Context, Result};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use std::{fs, thread};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
 use tokio::sync::{Mutex, RwLock, mpsc};
 
 pub struct DriveManager {
     pub(super) drives: Arc<RwLock<HashMap<String, Arc<Mount>>>>,
     config_dir: PathBuf,
-    pub(super) inventory: Arc<InventoryDb>,
+    /// Per-drive inventory databases keyed by drive ID, readable without async locks
+    inventories: Arc<std::sync::RwLock<HashMap<String, DriveInventory>>>,
+    /// Database shared by all drives before inventories were split per drive
+    legacy_inventory: Option<Arc<InventoryDb>>,
     pub(super) command_tx: mpsc::UnboundedSender<ManagerCommand>,
     pub(super) command_rx: Arc<Mutex<Option<mpsc::UnboundedReceiver<ManagerCommand>>>>,
     pub(super) processor_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    pub(super) maintenance_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    pub(super) scheduled_tasks: Arc<Mutex<HashMap<ScheduledJob, scheduler::ScheduledTask>>>,
+    /// Drives whose syncing the user paused, by drive ID
+    pub(super) paused_syncs: Arc<Mutex<HashMap<String, pause::PausedSync>>>,
+    /// Full resyncs running in the background, by drive ID
+    pub(super) resyncs: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
     pub(super) event_broadcaster: Arc<EventBroadcaster>,
+    /// Thumbnails served to Explorer, shared by all drives
+    pub(super) thumbnail_cache: Arc<ThumbnailCache>,
+}
+
+/// Events after which Explorer is asked to query the status UI of the drives again
+const STATUS_UI_EVENTS: &[&str] = &[
+    "ConnectionStatusChanged",
+    "TaskFinished",
+    "TasksUpdated",
+    "DeadLettersChanged",
+    "ConflictDetected",
+    "CredentialExpired",
+    "QuotaWarning",
+    "SyncRootRepaired",
+    "SyncPauseChanged",
+    "DriveUpdated",
+];
+
+/// How long the status UI waits for an event burst to settle before refreshing
+const STATUS_UI_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// A drive's inventory database together with the sync root it covers
+struct DriveInventory {
+    sync_path: PathBuf,
+    inventory: Arc<InventoryDb>,
+    ignore_matcher: IgnoreMatcher,
 }
 
 impl DriveManager {
@@ -41,18 +113,86 @@ impl DriveManager {
         let (command_tx, command_rx) = mpsc::unbounded_channel();
 
         Ok(Self {
-            config_dir,
+            config_dir: config_dir.clone(),
             drives: Arc::new(RwLock::new(HashMap::new())),
-            inventory: Arc::new(InventoryDb::new().context("Failed to create inventory database")?),
+            inventories: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            legacy_inventory: InventoryDb::open_legacy()
+                .context("Failed to open legacy inventory database")?
+                .map(Arc::new),
             command_tx,
             command_rx: Arc::new(Mutex::new(Some(command_rx))),
             processor_handle: Arc::new(Mutex::new(None)),
+            maintenance_handle: Arc::new(Mutex::new(None)),
+            scheduled_tasks: Arc::new(Mutex::new(HashMap::new())),
+            paused_syncs: Arc::new(Mutex::new(HashMap::new())),
+            resyncs: Arc::new(Mutex::new(HashMap::new())),
             event_broadcaster: event_broadcaster,
+            thumbnail_cache: Arc::new(ThumbnailCache::new(config_dir.join("thumbnails"))),
+        })
+    }
+
+    /// Get the inventory database of a drive
+    pub fn get_drive_inventory(&self, id: &str) -> Option<Arc<InventoryDb>> {
+        self.inventories
+            .read()
+            .ok()
+            .and_then(|inventories| inventories.get(id).map(|d| d.inventory.clone()))
+    }
+
+    /// Get the inventory database of the drive whose sync root contains `path`.
+    /// Synchronous so it can be used from shell extension callbacks.
+    pub fn inventory_for_path(&self, path: &str) -> Option<Arc<InventoryDb>> {
+        let target = PathBuf::from(path);
+        let target = target.canonicalize().unwrap_or(target);
+        self.inventories.read().ok().and_then(|inventories| {
+            inventories
+                .values()
+                .find(|d| target.starts_with(&d.sync_path))
+                .map(|d| d.inventory.clone())
+        })
+    }
+
+    /// Whether `path` matches the ignore patterns of the drive containing it. Synchronous
+    /// so it can be used from shell extension callbacks.
+    pub fn is_ignored_path(&self, path: &str) -> bool {
+        let target = PathBuf::from(path);
+        let canonical = target.canonicalize().unwrap_or_else(|_| target.clone());
+        self.inventories.read().is_ok_and(|inventories| {
+            inventories
+                .values()
+                .find(|d| canonical.starts_with(&d.sync_path))
+                .is_some_and(|d| d.ignore_matcher.is_match(&target))
         })
     }
 
-    pub fn get_inventory(&self) -> Arc<InventoryDb> {
-        self.inventory.clone()
+    fn all_inventories(&self) -> Vec<Arc<InventoryDb>> {
+        self.inventories
+            .read()
+            .map(|inventories| inventories.values().map(|d| d.inventory.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// The database of one drive, or of all drives
+    fn inventories_for(&self, drive_id: Option<&str>) -> Result<Vec<Arc<InventoryDb>>> {
+        match drive_id {
+            Some(drive_id) => Ok(vec![
+                self.get_drive_inventory(drive_id)
+                    .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?,
+            ]),
+            None => Ok(self.all_inventories()),
+        }
+    }
+
+    /// Open a drive's own database, moving its rows out of the legacy shared database first
+    fn open_drive_inventory(&self, drive_id: &str) -> Result<InventoryDb> {
+        let encrypt = ConfigManager::try_get().is_some_and(|config| config.encrypt_inventory());
+        let inventory = InventoryDb::for_drive(drive_id, encrypt)?;
+        if let Some(legacy) = self.legacy_inventory.as_ref() {
+            inventory
+                .migrate_drive_from(legacy, drive_id)
+                .context("Failed to migrate drive out of legacy inventory")?;
+        }
+        Ok(inventory.with_events(drive_id, self.event_broadcaster.clone()))
     }
 
     /// Get the .cloudreve config directory path
@@ -67,7 +207,18 @@ impl DriveManager {
     }
 
     /// Load drive configurations from disk
-    pub async fn load(&self) -> Result<()> {
+    pub async fn load(self: &Arc<Self>) -> Result<()> {
+        // Databases of drives removed while still in use are deleted before any opens
+        match InventoryDb::remove_pending_drive_files() {
+            Ok(0) => {}
+            Ok(count) => {
+                tracing::info!(target: "drive", count, "Removed inventory files of removed drive(s)");
+            }
+            Err(e) => {
+                tracing::warn!(target: "drive", error = %e, "Failed to remove inventory files of removed drives");
+            }
+        }
+
         let config_file = self.get_config_file();
 
         if !config_file.exists() {
@@ -81,8 +232,9 @@ impl DriveManager {
         let content =
             fs::read_to_string(&config_file).context("Failed to read drive config file")?;
 
-        let state: DriveState =
+        let mut state: DriveState =
             serde_json::from_str(&content).context("Failed to parse drive config")?;
+        let renumbered = Self::renumber_navigation_pane_orders(&mut state.drives);
 
         // Add drives to manager
         let mut count = 0;
@@ -99,9 +251,36 @@ impl DriveManager {
 
         tracing::info!(target: "drive", count = count, "Loaded drive(s) from config");
 
+        if renumbered {
+            tracing::info!(target: "drive", "Assigned distinct navigation pane positions to drives");
+            self.persist().await?;
+        }
+
+        self.restore_pauses(&state.drives).await;
+
         Ok(())
     }
 
+    /// Drives configured before their navigation pane position was stored all have the
+    /// default position. Give each drive a distinct one, keeping the current order and
+    /// the stored order among equals. Returns whether any position changed.
+    fn renumber_navigation_pane_orders(drives: &mut [DriveConfig]) -> bool {
+        let mut seen = HashSet::new();
+        if drives
+            .iter()
+            .all(|config| seen.insert(config.navigation_pane.order))
+        {
+            return false;
+        }
+
+        let mut indices: Vec<usize> = (0..drives.len()).collect();
+        indices.sort_by_key(|&index| drives[index].navigation_pane.order);
+        for (order, index) in indices.into_iter().enumerate() {
+            drives[index].navigation_pane.order = order as u32;
+        }
+        true
+    }
+
     /// Persist drive configurations to disk
     pub async fn persist(&self) -> Result<()> {
         let config_file = self.get_config_file();
@@ -126,15 +305,25 @@ impl DriveManager {
         Ok(())
     }
 
-    /// Register a callback to be invoked when status UI changes
-    /// This is a dummy implementation that calls the callback every 30 seconds
+    /// Register a callback to be invoked when status UI changes, i.e. after the events
+    /// the status UI shows. Bursts of events are coalesced into one call.
     pub fn register_on_status_ui_changed<F>(&self, fnc: F) -> Result<()>
     where
         F: Fn() + Send + 'static,
     {
+        let mut receiver = self
+            .event_broadcaster
+            .subscribe_filtered(EventFilter::new().with_names(STATUS_UI_EVENTS));
         thread::spawn(move || {
             loop {
-                thread::sleep(Duration::from_secs(30));
+                match receiver.blocking_recv() {
+                    Ok(_) | Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+                // Coalesce the events that followed into the same refresh
+                thread::sleep(STATUS_UI_DEBOUNCE);
+                while let Ok(_) | Err(TryRecvError::Lagged(_)) = receiver.try_recv() {}
+
                 tracing::trace!(target: "drive::manager", "Register_on_status_ui_changed: Invoking status UI changed callback");
                 fnc();
             }
@@ -164,10 +353,33 @@ impl DriveManager {
             }
         }
 
+        // A drive without a sync root is new, place it after the others in Explorer's
+        // navigation pane
+        if config.sync_root_id.is_none() {
+            config.navigation_pane.order = self.next_navigation_pane_order().await;
+        }
+
+        let inventory = Arc::new(
+            self.open_drive_inventory(&config.id)
+                .context("Failed to open drive inventory")?,
+        );
+        let name = config.name.clone();
+        let id = self.start_mount(config, inventory).await?;
+
+        self.event_broadcaster.drive_added(&id, &name);
+        Ok(id)
+    }
+
+    /// Start the mount of a drive on its opened inventory and add it to the manager
+    async fn start_mount(
+        &self,
+        config: DriveConfig,
+        inventory: Arc<InventoryDb>,
+    ) -> Result<String> {
         let mut write_guard = self.drives.write().await;
         let mut mount = Mount::new(
             config.clone(),
-            self.inventory.clone(),
+            inventory.clone(),
             self.command_tx.clone(),
         )
         .await;
@@ -182,7 +394,19 @@ impl DriveManager {
             .spawn_remote_event_processor(mount_arc.clone())
             .await;
         mount_arc.spawn_props_refresh_task().await;
+        mount_arc.spawn_prefetcher().await;
         let id = mount_arc.id.clone();
+        let sync_path = mount_arc.get_sync_path().await;
+        if let Ok(mut inventories) = self.inventories.write() {
+            inventories.insert(
+                id.clone(),
+                DriveInventory {
+                    sync_path: sync_path.canonicalize().unwrap_or(sync_path),
+                    inventory,
+                    ignore_matcher: mount_arc.ignore_matcher().clone(),
+                },
+            );
+        }
         write_guard.insert(id.clone(), mount_arc);
         Ok(id)
     }
@@ -242,10 +466,23 @@ impl DriveManager {
 
         // Drop the write guard before calling delete to avoid potential deadlocks
         drop(write_guard);
+        self.forget_pause(id).await;
+        self.forget_resync(id).await;
 
         // Delete the mount (unregister sync root, cleanup, etc.)
         mount.delete().await.context("Failed to delete mount")?;
 
+        // Drop the drive's database handles, then its files
+        if let Ok(mut inventories) = self.inventories.write() {
+            inventories.remove(id);
+        }
+        drop(mount);
+        if let Err(e) = InventoryDb::remove_drive_files(id) {
+            tracing::warn!(target: "drive::manager", drive_id = %id, error = %e, "Drive inventory files still in use, removing them on next start");
+        }
+
+        self.event_broadcaster.drive_removed(id, &config.name);
+
         // Broadcast no_drive event if no drives remain
         if self.drives.read().await.is_empty() {
             self.event_broadcaster.no_drive();
@@ -363,6 +600,11 @@ impl DriveManager {
 
         drop(config);
 
+        // Show the new name and favicon in Explorer
+        if let Err(e) = mount.update_sync_root_branding().await {
+            tracing::warn!(target: "drive::manager", drive_id = %id, error = ?e, "Failed to update sync root branding");
+        }
+
         // Update the client's tokens
         mount
             .cr_client
@@ -423,10 +665,18 @@ impl DriveManager {
         }
 
         // Query recent tasks from inventory (filtered by drive_id if provided)
-        let recent_tasks = self
-            .inventory
-            .query_recent_tasks(drive_id)
-            .context("Failed to query recent tasks")?;
+        let inventories = match drive_id {
+            Some(id) => self.get_drive_inventory(id).into_iter().collect(),
+            None => self.all_inventories(),
+        };
+        let mut recent_tasks = RecentTasks::default();
+        for inventory in inventories {
+            recent_tasks.merge(
+                inventory
+                    .query_recent_tasks(drive_id)
+                    .context("Failed to query recent tasks")?,
+            );
+        }
 
         // Collect running task progress from all task queues
         // Build a map of task_id -> TaskProgress for quick lookup
@@ -448,6 +698,12 @@ impl DriveManager {
             }
         }
 
+        let queue_stats: Vec<TaskQueueStats> = read_guard
+            .values()
+            .filter(|mount| drive_id.is_none_or(|id| mount.id == id))
+            .map(|mount| mount.task_queue.stats())
+            .collect();
+
         // Merge progress info into active tasks
         let active_tasks: Vec<TaskWithProgress> = recent_tasks
             .active
@@ -462,6 +718,7 @@ impl DriveManager {
             drives,
             active_tasks,
             finished_tasks: recent_tasks.finished,
+            queue_stats,
         })
     }
 
@@ -528,16 +785,39 @@ impl DriveManager {
             config.user_id
         );
 
-        // Determine sync status based on active tasks
-        let active_task_count = self.get_active_task_count(drive_id);
-
-        let sync_status = if active_task_count > 0 {
+        // Determine sync status, the condition needing the user most wins
+        let active_task_count = Self::get_active_task_count(mount, drive_id);
+        let drive_state = mount.get_status_flags().await;
+        let last_synced_at = mount
+            .inventory
+            .last_drive_sync_at(drive_id)
+            .unwrap_or_else(|e| {
+                tracing::warn!(target: "drive::manager", drive_id = %drive_id, error = %e, "Failed to query last sync time");
+                None
+            });
+        let last_error = Self::get_last_dead_letter_error(mount, drive_id, last_synced_at);
+
+        let sync_status = if !config.enabled || mount.task_queue.is_paused() {
+            SyncStatus::Paused
+        } else if drive_state.is_credential_expired() {
+            SyncStatus::CredentialExpired
+        } else if !drive_state.is_event_push_subscribed() {
+            SyncStatus::Offline
+        } else if let Some(message) = last_error {
+            SyncStatus::Error { message }
+        } else if active_task_count > 0 {
             SyncStatus::Syncing
         } else {
             SyncStatus::InSync
         };
 
+        let sign_in_url = match sync_status {
+            SyncStatus::CredentialExpired => Some(config.instance_url.clone()),
+            _ => None,
+        };
+
         Ok(Some(DriveStatusUI {
+            id: config.id.clone(),
             name: config.name.clone(),
             raw_icon_path: config.raw_icon_path.clone(),
             capacity,
@@ -546,6 +826,8 @@ impl DriveManager {
             storage_url,
             sync_status,
             active_task_count,
+            last_synced_at,
+            sign_in_url,
         }))
     }
 
@@ -581,16 +863,667 @@ impl DriveManager {
                 icon_path: config.icon_path.clone(),
                 remote_path: config.remote_path.clone(),
                 raw_icon_path: config.raw_icon_path.clone(),
+                color: config.color.clone(),
                 enabled: config.enabled,
                 user_id: config.user_id.clone(),
                 status,
                 capacity,
+                navigation_pane: config.navigation_pane,
             });
         }
 
+        // List drives in the order Explorer's navigation pane shows them
+        drives_info.sort_by_key(|drive| drive.navigation_pane.order);
         Ok(drives_info)
     }
 
+    /// Export a drive's inventory (metadata entries and task history) to a snapshot file.
+    pub async fn export_inventory_snapshot(&self, id: &str, dest: &std::path::Path) -> Result<()> {
+        let mount = self
+            .get_drive(id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", id))?;
+        let sync_path = mount.get_sync_path().await;
+
+        let snapshot = mount
+            .inventory
+            .export_snapshot(id, &sync_path.to_string_lossy())
+            .context("Failed to export inventory snapshot")?;
+        snapshot.write_to(dest)?;
+
+        tracing::info!(target: "drive::manager", drive_id = %id, path = %dest.display(), entries = snapshot.entries.len(), "Inventory snapshot exported");
+        Ok(())
+    }
+
+    /// Import an inventory snapshot into an existing drive.
+    ///
+    /// Paths recorded under the snapshot's sync root are rewritten to the drive's current
+    /// sync root, so a drive re-added on the same folder picks up its previous state.
+    pub async fn import_inventory_snapshot(
+        &self,
+        id: &str,
+        src: &std::path::Path,
+    ) -> Result<SnapshotImportStats> {
+        let mount = self
+            .get_drive(id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", id))?;
+        let sync_path = mount.get_sync_path().await;
+
+        let snapshot = InventorySnapshot::read_from(src)?;
+        mount
+            .inventory
+            .import_snapshot(&snapshot, id, &sync_path.to_string_lossy())
+            .context("Failed to import inventory snapshot")
+    }
+
+    /// Query inventory entries with filtering, sorting and pagination.
+    ///
+    /// A filter naming a drive reads only that drive's database. Otherwise every drive is
+    /// queried up to the end of the requested page and the results are merged.
+    pub fn query_inventory_entries(
+        &self,
+        filter: &EntryFilter,
+        sort: EntrySort,
+        page: PageRequest,
+    ) -> Result<EntryPage> {
+        let page = page.normalized();
+        if let Some(drive_id) = filter.drive_id.as_deref() {
+            let inventory = self
+                .get_drive_inventory(drive_id)
+                .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+            return inventory.query_entries(filter, sort, page);
+        }
+
+        let window = page.offset + page.limit;
+        let mut total = 0;
+        let mut entries = Vec::new();
+        for inventory in self.all_inventories() {
+            let mut chunk = PageRequest::new(0, PageRequest::MAX_LIMIT);
+            loop {
+                let result = inventory.query_entries(filter, sort, chunk)?;
+                if chunk.offset == 0 {
+                    total += result.total;
+                }
+                let has_more = result.has_more();
+                chunk.offset += result.entries.len() as i64;
+                entries.extend(result.entries);
+                if !has_more || chunk.offset >= window {
+                    break;
+                }
+            }
+        }
+
+        entries.sort_by(|a, b| sort.compare(a, b));
+        let entries = entries
+            .into_iter()
+            .skip(page.offset as usize)
+            .take(page.limit as usize)
+            .collect();
+        Ok(EntryPage {
+            entries,
+            total,
+            offset: page.offset,
+            limit: page.limit,
+        })
+    }
+
+    /// A page of the conflict records of one drive or all drives, newest first
+    pub fn query_conflicts(
+        &self,
+        drive_id: Option<&str>,
+        pending_only: bool,
+        page: PageRequest,
+    ) -> Result<ConflictPage> {
+        let page = page.normalized();
+        if let Some(drive_id) = drive_id {
+            let inventory = self
+                .get_drive_inventory(drive_id)
+                .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+            return inventory.query_conflicts(Some(drive_id), pending_only, page);
+        }
+
+        // Every drive contributes at most the first `offset + limit` conflicts
+        let window = PageRequest::new(0, page.offset + page.limit);
+        let mut total = 0;
+        let mut conflicts = Vec::new();
+        for inventory in self.all_inventories() {
+            let result = inventory.query_conflicts(None, pending_only, window)?;
+            total += result.total;
+            conflicts.extend(result.conflicts);
+        }
+        conflicts.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        let conflicts = conflicts
+            .into_iter()
+            .skip(page.offset as usize)
+            .take(page.limit as usize)
+            .collect();
+        Ok(ConflictPage {
+            conflicts,
+            total,
+            offset: page.offset,
+            limit: page.limit,
+        })
+    }
+
+    /// A page of the activity feed of one drive or all drives, newest first
+    pub fn query_activity(
+        &self,
+        drive_id: Option<&str>,
+        filter: &ActivityFilter,
+        page: PageRequest,
+    ) -> Result<ActivityPage> {
+        let page = page.normalized();
+        if let Some(drive_id) = drive_id {
+            let inventory = self
+                .get_drive_inventory(drive_id)
+                .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+            return inventory.query_activity(drive_id, filter, page);
+        }
+
+        let inventories: Vec<(String, Arc<InventoryDb>)> = self
+            .inventories
+            .read()
+            .map(|inventories| {
+                inventories
+                    .iter()
+                    .map(|(id, d)| (id.clone(), d.inventory.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let window = page.offset + page.limit;
+        let mut total = 0;
+        let mut entries = Vec::new();
+        for (drive_id, inventory) in inventories {
+            let mut chunk = PageRequest::new(0, PageRequest::MAX_LIMIT);
+            loop {
+                let result = inventory.query_activity(&drive_id, filter, chunk)?;
+                if chunk.offset == 0 {
+                    total += result.total;
+                }
+                let has_more = result.has_more();
+                chunk.offset += result.entries.len() as i64;
+                entries.extend(result.entries);
+                if !has_more || chunk.offset >= window {
+                    break;
+                }
+            }
+        }
+        entries.sort_by(|a, b| b.occurred_at.cmp(&a.occurred_at));
+        let entries = entries
+            .into_iter()
+            .skip(page.offset as usize)
+            .take(page.limit as usize)
+            .collect();
+        Ok(ActivityPage {
+            entries,
+            total,
+            offset: page.offset,
+            limit: page.limit,
+        })
+    }
+
+    /// Conflicts involving a file, either as the conflicted file or as a conflict copy,
+    /// newest first
+    pub fn list_conflicts_for_path(&self, path: &str) -> Result<Vec<ConflictRecord>> {
+        let inventory = self
+            .inventory_for_path(path)
+            .ok_or_else(|| anyhow::anyhow!("No drive found for path: {}", path))?;
+        inventory.list_conflicts_for_path(path)
+    }
+
+    /// Resolve the pending conflict of a file with `action`
+    pub async fn resolve_conflict(&self, path: &Path, action: ConflictAction) -> Result<()> {
+        let local_path = path.to_str().unwrap_or("");
+        let mount = self
+            .search_drive_by_child_path(local_path)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No drive found for path: {:?}", path))?;
+        let file_meta = mount
+            .inventory
+            .query_by_path(local_path)
+            .context("Failed to query file metadata")?
+            .ok_or_else(|| anyhow::anyhow!("File not found in inventory: {:?}", path))?;
+        mount
+            .resolve_conflict(action, file_meta.id, file_meta.local_path)
+            .await
+    }
+
+    /// Resolve the pending conflict `id` of `drive_id` as chosen in the conflict center.
+    /// The file operations and the inventory update are those of
+    /// [`DriveManager::resolve_conflict`].
+    pub async fn resolve_conflict_by_id(
+        &self,
+        drive_id: &str,
+        id: i64,
+        choice: ConflictChoice,
+    ) -> Result<()> {
+        let inventory = self
+            .get_drive_inventory(drive_id)
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+        let record = inventory
+            .get_conflict(id)?
+            .ok_or_else(|| anyhow::anyhow!("Conflict not found: {}", id))?;
+        if !record.resolution.is_pending() {
+            return Err(anyhow::anyhow!("Conflict {} is already resolved", id));
+        }
+        self.resolve_conflict(Path::new(&record.original_path), choice.into())
+            .await
+    }
+
+    /// Pin ("Always keep on this device") or unpin ("Free up space") files and folders,
+    /// which may belong to different drives. Unpinned files are also dehydrated, as the
+    /// platform leaves that to the provider. Every path is tried; the first error is
+    /// returned.
+    pub async fn set_pin_state(&self, paths: &[PathBuf], state: PinState) -> Result<()> {
+        let mut result = Ok(());
+        for path in paths {
+            let applied = match self
+                .search_drive_by_child_path(path.to_str().unwrap_or(""))
+                .await
+            {
+                Some(mount) => match mount.set_pin_state(path, state).await {
+                    Ok(()) if state == PinState::Unpinned => {
+                        mount.free_up_space(path, None).await.map(|_| ())
+                    }
+                    applied => applied,
+                },
+                None => Err(anyhow::anyhow!("No drive found for path: {:?}", path)),
+            };
+            if let Err(e) = applied {
+                tracing::warn!(target: "drive::manager", path = %path.display(), error = %e, "Failed to set pin state");
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
+    }
+
+    /// Turn hydrated files and folders back into cloud-only placeholders, see
+    /// [`Mount::free_up_space`]. Paths may belong to different drives.
+    pub async fn free_up_paths(
+        &self,
+        paths: &[PathBuf],
+        unused_for: Option<Duration>,
+    ) -> Result<FreedSpace> {
+        let mut freed = FreedSpace::default();
+        for path in paths {
+            let mount = self
+                .search_drive_by_child_path(path.to_str().unwrap_or(""))
+                .await
+                .ok_or_else(|| anyhow::anyhow!("No drive found for path: {:?}", path))?;
+            freed += mount.free_up_space(path, unused_for).await?;
+        }
+        Ok(freed)
+    }
+
+    /// Dehydrate every file not used for `unused_for` in one drive or all drives
+    pub async fn free_up_unused_space(
+        &self,
+        drive_id: Option<&str>,
+        unused_for: Duration,
+    ) -> Result<FreedSpace> {
+        let mounts: Vec<Arc<Mount>> = {
+            let read_guard = self.drives.read().await;
+            match drive_id {
+                Some(drive_id) => vec![
+                    read_guard
+                        .get(drive_id)
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?,
+                ],
+                None => read_guard.values().cloned().collect(),
+            }
+        };
+
+        let mut freed = FreedSpace::default();
+        for mount in mounts {
+            let sync_path = mount.get_sync_path().await;
+            freed += mount.free_up_space(&sync_path, Some(unused_for)).await?;
+        }
+        Ok(freed)
+    }
+
+    /// Cancel a task of a drive. Returns `false` if the task already finished.
+    pub async fn cancel_task(&self, drive_id: &str, task_id: &str) -> Result<bool> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+        mount.task_queue.cancel_task(task_id).await
+    }
+
+    /// Pending and running transfers of one drive or all drives, running ones first
+    pub async fn get_transfers(&self, drive_id: Option<&str>) -> Result<Vec<Transfer>> {
+        let mut transfers = Vec::new();
+        for queue in self.task_queues_for(drive_id).await? {
+            transfers.extend(queue.transfers()?);
+        }
+        transfers.sort_by_key(|t| (t.status != TaskStatus::Running, t.created_at));
+        Ok(transfers)
+    }
+
+    /// Task counters and timings of one drive's task queue, or of every drive's
+    pub async fn task_statistics(&self, drive_id: Option<&str>) -> Result<Vec<TaskStatistics>> {
+        let read_guard = self.drives.read().await;
+        if let Some(drive_id) = drive_id.filter(|id| !read_guard.contains_key(*id)) {
+            return Err(anyhow::anyhow!("Drive not found: {}", drive_id));
+        }
+        Ok(read_guard
+            .values()
+            .filter(|mount| drive_id.is_none_or(|id| mount.id == id))
+            .map(|mount| mount.task_queue.statistics())
+            .collect())
+    }
+
+    /// List tasks that failed for good, newest first, for one drive or all drives
+    pub fn list_dead_letters(&self, drive_id: Option<&str>) -> Result<Vec<DeadLetterRecord>> {
+        let mut dead_letters = Vec::new();
+        for inventory in self.inventories_for(drive_id)? {
+            dead_letters.extend(inventory.list_dead_letters(drive_id)?);
+        }
+        dead_letters.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(dead_letters)
+    }
+
+    /// Number of tasks that failed for good, for one drive or all drives
+    pub fn dead_letter_count(&self, drive_id: Option<&str>) -> Result<usize> {
+        let mut count = 0;
+        for inventory in self.inventories_for(drive_id)? {
+            count += inventory.count_dead_letters(drive_id)? as usize;
+        }
+        Ok(count)
+    }
+
+    /// Recent cloud filter callback failures, newest first, for one drive or all drives
+    pub fn list_cfapi_failures(
+        &self,
+        drive_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<CfapiFailureRecord>> {
+        let mut failures = Vec::new();
+        for inventory in self.inventories_for(drive_id)? {
+            failures.extend(inventory.list_cfapi_failures(drive_id, limit)?);
+        }
+        failures.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        failures.truncate(limit.max(1) as usize);
+        Ok(failures)
+    }
+
+    /// Cloud filter callback failures per class since `since` (Unix timestamp), most
+    /// frequent first
+    pub fn cfapi_failure_summary(
+        &self,
+        drive_id: Option<&str>,
+        since: i64,
+    ) -> Result<Vec<CfapiFailureSummary>> {
+        let mut summary: Vec<CfapiFailureSummary> = Vec::new();
+        for inventory in self.inventories_for(drive_id)? {
+            for entry in inventory.summarize_cfapi_failures(drive_id, since)? {
+                match summary.iter_mut().find(|s| s.class == entry.class) {
+                    Some(existing) => {
+                        existing.count += entry.count;
+                        existing.last_at = existing.last_at.max(entry.last_at);
+                    }
+                    None => summary.push(entry),
+                }
+            }
+        }
+        summary.sort_by(|a, b| b.count.cmp(&a.count));
+        Ok(summary)
+    }
+
+    /// Remove recorded cloud filter callback failures. Returns how many were removed.
+    pub fn clear_cfapi_failures(&self, drive_id: Option<&str>) -> Result<usize> {
+        let mut cleared = 0;
+        for inventory in self.inventories_for(drive_id)? {
+            cleared += inventory.clear_cfapi_failures(drive_id)?;
+        }
+        Ok(cleared)
+    }
+
+    /// Run a dead-lettered task of a drive again. Returns the id of the new task.
+    pub async fn requeue_dead_letter(&self, drive_id: &str, task_id: &str) -> Result<String> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+        mount.task_queue.requeue_dead_letter(task_id).await
+    }
+
+    /// Drop a dead-lettered task of a drive. Returns `false` if it was not there.
+    pub async fn discard_dead_letter(&self, drive_id: &str, task_id: &str) -> Result<bool> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+        mount.task_queue.discard_dead_letter(task_id)
+    }
+
+    /// Cancel every pending or running task of one drive or all drives, optionally only
+    /// those of `kind`. Returns the number of tasks cancelled.
+    pub async fn cancel_tasks(
+        &self,
+        drive_id: Option<&str>,
+        kind: Option<TaskKind>,
+    ) -> Result<usize> {
+        let mut cancelled = 0;
+        for queue in self.task_queues_for(drive_id).await? {
+            cancelled += queue.cancel_tasks(kind).await?;
+        }
+        Ok(cancelled)
+    }
+
+    /// Run every failed or timed out task of one drive or all drives again. Returns the
+    /// number of tasks requeued.
+    pub async fn requeue_failed_tasks(&self, drive_id: Option<&str>) -> Result<usize> {
+        let mut requeued = 0;
+        for queue in self.task_queues_for(drive_id).await? {
+            requeued += queue.requeue_failed_tasks().await?;
+        }
+        Ok(requeued)
+    }
+
+    /// The task queue of one drive, or of all drives
+    async fn task_queues_for(&self, drive_id: Option<&str>) -> Result<Vec<Arc<TaskQueue>>> {
+        let read_guard = self.drives.read().await;
+        match drive_id {
+            Some(drive_id) => Ok(vec![
+                read_guard
+                    .get(drive_id)
+                    .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?
+                    .task_queue(),
+            ]),
+            None => Ok(read_guard
+                .values()
+                .map(|mount| mount.task_queue())
+                .collect()),
+        }
+    }
+
+    /// Limit how many tasks of each type run at once on a drive, in addition to its
+    /// worker pool. A limit of 0 removes the limit for that type.
+    pub async fn set_task_type_limits(
+        &self,
+        drive_id: &str,
+        limits: HashMap<TaskKind, usize>,
+    ) -> Result<()> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+        let limits: HashMap<TaskKind, usize> =
+            limits.into_iter().filter(|(_, limit)| *limit > 0).collect();
+        let stored: serde_json::Map<String, serde_json::Value> = limits
+            .iter()
+            .map(|(kind, limit)| (kind.as_str().to_string(), (*limit).into()))
+            .collect();
+
+        mount
+            .config
+            .write()
+            .await
+            .extra
+            .insert(TASK_TYPE_LIMITS_KEY.to_string(), stored.into());
+        mount.task_queue.set_type_limits(&limits);
+        self.persist().await
+    }
+
+    /// Replay local changes made while the app was not running from the volume change
+    /// journal when the drive starts. Changes made before enabling it are not replayed.
+    pub async fn set_usn_journal_enabled(&self, drive_id: &str, enabled: bool) -> Result<()> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+
+        mount
+            .config
+            .write()
+            .await
+            .extra
+            .insert(USN_JOURNAL_KEY.to_string(), enabled.into());
+        if enabled {
+            // The watcher covers changes from here on, start the journal from here too
+            mount.save_usn_checkpoint().await;
+        }
+        self.persist().await
+    }
+
+    /// Have Windows ask for validation of hydrated data against the inventory before
+    /// handing it to applications. Applied by re-registering the sync root.
+    pub async fn set_data_validation_enabled(&self, drive_id: &str, enabled: bool) -> Result<()> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+
+        mount
+            .config
+            .write()
+            .await
+            .extra
+            .insert(VALIDATE_DATA_KEY.to_string(), enabled.into());
+        mount
+            .update_sync_root_branding()
+            .await
+            .context("failed to update sync root registration")?;
+        self.persist().await
+    }
+
+    /// Change how Windows hydrates, dehydrates and populates placeholders of a drive.
+    /// Applied by re-registering the sync root.
+    pub async fn set_sync_root_policy(&self, drive_id: &str, policy: SyncRootPolicy) -> Result<()> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+
+        mount.config.write().await.sync_root_policy = policy;
+        mount
+            .update_sync_root_branding()
+            .await
+            .context("failed to update sync root registration")?;
+        self.persist().await?;
+
+        // Windows no longer asks for folder listings, so create every placeholder now
+        if policy.population == PopulationMode::AlwaysFull {
+            mount
+                .command_tx
+                .send(MountCommand::Sync {
+                    local_paths: vec![mount.get_sync_path().await],
+                    mode: SyncMode::FullHierarchy,
+                })
+                .map_err(|e| anyhow::anyhow!("Failed to queue full sync: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Pin or unpin a drive in Explorer's navigation pane
+    pub async fn set_navigation_pane_pinned(&self, drive_id: &str, pinned: bool) -> Result<()> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+
+        mount.config.write().await.navigation_pane.pinned = pinned;
+        mount
+            .update_navigation_pane()
+            .await
+            .context("failed to update navigation pane entry")?;
+        self.persist().await
+    }
+
+    /// Order the drives in Explorer's navigation pane as in `drive_ids`. Drives left out
+    /// follow the listed ones in their current order.
+    pub async fn set_navigation_pane_order(&self, drive_ids: &[String]) -> Result<()> {
+        {
+            let read_guard = self.drives.read().await;
+            if let Some(id) = drive_ids.iter().find(|id| !read_guard.contains_key(*id)) {
+                return Err(anyhow::anyhow!("Drive not found: {}", id));
+            }
+
+            let mut rest = Vec::new();
+            for mount in read_guard.values() {
+                if !drive_ids.contains(&mount.id) {
+                    let order = mount.config.read().await.navigation_pane.order;
+                    rest.push((order, mount.clone()));
+                }
+            }
+            rest.sort_by_key(|(order, _)| *order);
+
+            let ordered = drive_ids
+                .iter()
+                .filter_map(|id| read_guard.get(id).cloned())
+                .chain(rest.into_iter().map(|(_, mount)| mount));
+            for (order, mount) in ordered.enumerate() {
+                mount.config.write().await.navigation_pane.order = order as u32;
+                if let Err(e) = mount.update_navigation_pane().await {
+                    tracing::warn!(target: "drive::manager", drive_id = %mount.id, error = ?e, "Failed to update navigation pane entry");
+                }
+            }
+        }
+        self.persist().await
+    }
+
+    /// Navigation pane position after every existing drive
+    async fn next_navigation_pane_order(&self) -> u32 {
+        let read_guard = self.drives.read().await;
+        let mut next = 0;
+        for mount in read_guard.values() {
+            next = next.max(mount.config.read().await.navigation_pane.order + 1);
+        }
+        next
+    }
+
+    /// Apply a language change to Explorer. Context menu and status UI strings are looked
+    /// up on each request, but the custom state columns are part of the sync root
+    /// registration, so every sync root is registered again with the new names.
+    pub async fn refresh_shell_language(&self) {
+        let read_guard = self.drives.read().await;
+        for mount in read_guard.values() {
+            if let Err(e) = mount.update_sync_root_branding().await {
+                tracing::warn!(target: "drive::manager", drive_id = %mount.id, error = ?e, "Failed to apply language to sync root");
+            }
+        }
+    }
+
+    /// Size of the thumbnail cache in bytes
+    pub fn thumbnail_cache_size(&self) -> Result<u64> {
+        self.thumbnail_cache.size()
+    }
+
+    /// Shrink the thumbnail cache to a new limit
+    pub fn prune_thumbnail_cache(&self, max_bytes: u64) -> Result<()> {
+        self.thumbnail_cache.prune(max_bytes)
+    }
+
+    /// Remove all cached thumbnails, returning the number of bytes freed
+    pub fn clear_thumbnail_cache(&self) -> Result<u64> {
+        self.thumbnail_cache.clear()
+    }
+
     /// Get a command sender for external code to send commands to the manager
     pub fn get_command_sender(&self) -> mpsc::UnboundedSender<ManagerCommand> {
         self.command_tx.clone()
@@ -608,6 +1541,14 @@ impl DriveManager {
             handle.abort();
         }
 
+        if let Some(handle) = self.maintenance_handle.lock().await.take() {
+            tracing::debug!(target: "drive::manager", "Stopping maintenance task");
+            handle.abort();
+        }
+        self.stop_scheduled_tasks().await;
+        self.stop_pause_timers().await;
+        self.stop_resyncs().await;
+
         let write_guard = self.drives.write().await;
         for (_, mount) in write_guard.iter() {
             mount.shutdown().await;
@@ -656,9 +1597,29 @@ impl DriveManager {
         }
     }
 
+    /// Get the error of the most recent dead-lettered task of a drive, unless the
+    /// drive synced successfully since it failed
+    fn get_last_dead_letter_error(
+        mount: &Mount,
+        drive_id: &str,
+        last_synced_at: Option<i64>,
+    ) -> Option<String> {
+        match mount.inventory.list_dead_letters(Some(drive_id)) {
+            Ok(records) => records
+                .into_iter()
+                .next()
+                .filter(|record| last_synced_at.is_none_or(|synced| record.created_at >= synced))
+                .map(|record| record.error),
+            Err(e) => {
+                tracing::warn!(target: "drive::manager", drive_id = %drive_id, error = %e, "Failed to query dead letters");
+                None
+            }
+        }
+    }
+
     /// Get the count of active tasks for a drive
-    fn get_active_task_count(&self, drive_id: &str) -> usize {
-        match self.inventory.query_recent_tasks(Some(drive_id)) {
+    fn get_active_task_count(mount: &Mount, drive_id: &str) -> usize {
+        match mount.inventory.query_recent_tasks(Some(drive_id)) {
             Ok(tasks) => tasks.active.len(),
             Err(e) => {
                 tracing::warn!(target: "drive::manager", drive_id = %drive_id, error = %e, "Failed to query recent tasks");
@@ -0,0 +1,229 @@
+use super::DriveManager;
+use crate::drive::mounts::{DriveConfig, Mount, SyncPause};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Days, Local, TimeZone, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// How long [`DriveManager::pause_sync`] pauses, as offered in the tray menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseDuration {
+    OneHour,
+    /// Until the next local midnight
+    UntilTomorrow,
+    Indefinitely,
+}
+
+impl PauseDuration {
+    /// When a pause starting at `now` ends, `None` if it lasts until resumed
+    pub fn until<Tz: TimeZone>(self, now: DateTime<Tz>) -> Option<DateTime<Utc>> {
+        match self {
+            PauseDuration::OneHour => Some(now.to_utc() + chrono::Duration::hours(1)),
+            PauseDuration::UntilTomorrow => now
+                .date_naive()
+                .checked_add_days(Days::new(1))
+                .and_then(|tomorrow| tomorrow.and_hms_opt(0, 0, 0))
+                .and_then(|midnight| now.timezone().from_local_datetime(&midnight).earliest())
+                .map(|midnight| midnight.to_utc()),
+            PauseDuration::Indefinitely => None,
+        }
+    }
+
+    /// [`PauseDuration::until`] from the current local time
+    pub fn until_now(self) -> Option<DateTime<Utc>> {
+        self.until(Local::now())
+    }
+}
+
+/// Runtime state of a drive whose syncing is paused
+pub(super) struct PausedSync {
+    until: Option<DateTime<Utc>>,
+    /// Resumes the drive at `until`
+    resume_handle: Option<JoinHandle<()>>,
+}
+
+impl DriveManager {
+    /// Pause syncing of `drive_id`, or of every drive if `None`, until `until` or until
+    /// [`DriveManager::resume_sync`]. Pausing a paused drive replaces the end of its
+    /// pause. Files the user opens are still hydrated. The pause is kept in the drive
+    /// config, so it lasts across restarts.
+    pub async fn pause_sync(
+        self: &Arc<Self>,
+        drive_id: Option<&str>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let mounts = self.pause_targets(drive_id).await?;
+        let mut paused_syncs = self.paused_syncs.lock().await;
+        for mount in mounts {
+            mount.task_queue.pause_tasks();
+
+            let resume_handle = until.map(|until| {
+                let manager = self.clone();
+                let drive_id = mount.id.clone();
+                tokio::spawn(async move {
+                    let delay = (until - Utc::now()).to_std().unwrap_or_default();
+                    tokio::time::sleep(delay).await;
+                    // Resuming or pausing again before aborts this task
+                    manager.paused_syncs.lock().await.remove(&drive_id);
+                    if let Some(mount) = manager.get_drive(&drive_id).await {
+                        manager.resume_mount(&mount).await;
+                        if let Err(e) = manager.persist().await {
+                            tracing::warn!(target: "drive::manager", drive_id = %drive_id, error = %e, "Failed to persist the end of the pause");
+                        }
+                    }
+                })
+            });
+            mount.config.write().await.sync_pause = Some(SyncPause {
+                until: until.map(|until| until.timestamp()),
+            });
+            let previous = paused_syncs.insert(
+                mount.id.clone(),
+                PausedSync {
+                    until,
+                    resume_handle,
+                },
+            );
+            if let Some(handle) = previous.and_then(|paused| paused.resume_handle) {
+                handle.abort();
+            }
+
+            tracing::info!(target: "drive::manager", drive_id = %mount.id, until = ?until, "Sync paused");
+            self.event_broadcaster.sync_pause_changed(
+                &mount.id,
+                true,
+                until.map(|until| until.timestamp()),
+            );
+        }
+        drop(paused_syncs);
+        self.persist().await
+    }
+
+    /// Resume syncing of `drive_id`, or of every drive if `None`
+    pub async fn resume_sync(&self, drive_id: Option<&str>) -> Result<()> {
+        let mounts = self.pause_targets(drive_id).await?;
+        let mut paused_syncs = self.paused_syncs.lock().await;
+        for mount in mounts {
+            if let Some(handle) = paused_syncs
+                .remove(&mount.id)
+                .and_then(|paused| paused.resume_handle)
+            {
+                handle.abort();
+            }
+            self.resume_mount(&mount).await;
+        }
+        drop(paused_syncs);
+        self.persist().await
+    }
+
+    /// Pause the drives again that were paused when the app stopped, unless their
+    /// pause ended in the meantime
+    pub(super) async fn restore_pauses(self: &Arc<Self>, drives: &[DriveConfig]) {
+        for config in drives {
+            let Some(pause) = config.sync_pause else {
+                continue;
+            };
+            let until = pause
+                .until
+                .map(|until| DateTime::from_timestamp(until, 0).unwrap_or_default());
+            let result = if until.is_some_and(|until| until <= Utc::now()) {
+                self.resume_sync(Some(&config.id)).await
+            } else {
+                self.pause_sync(Some(&config.id), until).await
+            };
+            if let Err(e) = result {
+                tracing::warn!(target: "drive::manager", drive_id = %config.id, error = %e, "Failed to restore the pause of the drive");
+            }
+        }
+    }
+
+    /// Paused drives by id, with the Unix timestamp their pause ends at, `None` if
+    /// they are paused until resumed
+    pub async fn paused_drives(&self) -> HashMap<String, Option<i64>> {
+        self.paused_syncs
+            .lock()
+            .await
+            .iter()
+            .map(|(drive_id, paused)| {
+                (
+                    drive_id.clone(),
+                    paused.until.map(|until| until.timestamp()),
+                )
+            })
+            .collect()
+    }
+
+    /// Forget the pause of a removed drive
+    pub(super) async fn forget_pause(&self, drive_id: &str) {
+        if let Some(handle) = self
+            .paused_syncs
+            .lock()
+            .await
+            .remove(drive_id)
+            .and_then(|paused| paused.resume_handle)
+        {
+            handle.abort();
+        }
+    }
+
+    /// Stop the timers ending pauses, without resuming the drives. The pauses stay in
+    /// the drive configs and are restored on the next start.
+    pub(super) async fn stop_pause_timers(&self) {
+        for paused in self.paused_syncs.lock().await.values_mut() {
+            if let Some(handle) = paused.resume_handle.take() {
+                handle.abort();
+            }
+        }
+    }
+
+    async fn resume_mount(&self, mount: &Mount) {
+        mount.config.write().await.sync_pause = None;
+        if !mount.task_queue.is_paused() {
+            return;
+        }
+        mount.task_queue.resume_tasks();
+        tracing::info!(target: "drive::manager", drive_id = %mount.id, "Sync resumed");
+        self.event_broadcaster
+            .sync_pause_changed(&mount.id, false, None);
+    }
+
+    async fn pause_targets(&self, drive_id: Option<&str>) -> Result<Vec<Arc<Mount>>> {
+        let drives = self.drives.read().await;
+        match drive_id {
+            Some(id) => drives
+                .get(id)
+                .cloned()
+                .map(|mount| vec![mount])
+                .ok_or_else(|| anyhow!("Drive not found: {}", id)),
+            None => Ok(drives.values().cloned().collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn pause_durations_end_in_local_time() {
+        let zone = FixedOffset::east_opt(8 * 3600).unwrap();
+        let now = zone.with_ymd_and_hms(2025, 3, 31, 22, 30, 0).unwrap();
+
+        assert_eq!(
+            PauseDuration::OneHour.until(now),
+            Some(
+                zone.with_ymd_and_hms(2025, 3, 31, 23, 30, 0)
+                    .unwrap()
+                    .to_utc()
+            )
+        );
+        assert_eq!(
+            PauseDuration::UntilTomorrow.until(now),
+            Some(zone.with_ymd_and_hms(2025, 4, 1, 0, 0, 0).unwrap().to_utc())
+        );
+        assert_eq!(PauseDuration::Indefinitely.until(now), None);
+    }
+}
@@ -0,0 +1,161 @@
+use super::DriveManager;
+use super::send_to::list_subfolders;
+use crate::drive::mounts::Mount;
+use crate::inventory::RemoteFolderEntry;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use cloudreve_api::api::explorer::ExplorerApiExt;
+use cloudreve_api::models::explorer::GetFileInfoService;
+use cloudreve_api::models::uri::CrUri;
+use futures::StreamExt;
+use serde::Serialize;
+
+/// Levels listed at most by one call of [`DriveManager::get_remote_tree`]
+const MAX_TREE_DEPTH: u32 = 3;
+/// How long a cached folder listing is used before the server is asked again
+const CACHE_TTL_SECS: i64 = 5 * 60;
+/// Folder size requests running at once
+const SIZE_CONCURRENCY: usize = 8;
+
+/// A remote folder in the selective sync picker
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteFolderNode {
+    pub name: String,
+    pub uri: String,
+    /// Path relative to the drive's remote root, `/`-separated
+    pub path: String,
+    /// Total size of the folder's content in bytes, `None` if the server did not
+    /// calculate it
+    pub size: Option<i64>,
+    /// Subfolders, `None` if they were not listed within the requested depth
+    pub children: Option<Vec<RemoteFolderNode>>,
+}
+
+impl DriveManager {
+    /// Remote folders below `path` of drive `drive_id`, `depth` levels deep, for the
+    /// selective sync picker. `path` is relative to the drive's remote root. Listings
+    /// are cached in the inventory for a few minutes unless `refresh` is set.
+    pub async fn get_remote_tree(
+        &self,
+        drive_id: &str,
+        path: &str,
+        depth: u32,
+        refresh: bool,
+    ) -> Result<Vec<RemoteFolderNode>> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let mut uri = {
+            let config = mount.config.read().await;
+            CrUri::new(&config.remote_path).context("invalid remote path of drive")?
+        };
+        uri.join(&segments);
+
+        remote_subtree(
+            &mount,
+            &uri.to_string(),
+            &segments.join("/"),
+            depth.clamp(1, MAX_TREE_DEPTH),
+            refresh,
+        )
+        .await
+    }
+}
+
+async fn remote_subtree(
+    mount: &Mount,
+    uri: &str,
+    path: &str,
+    depth: u32,
+    refresh: bool,
+) -> Result<Vec<RemoteFolderNode>> {
+    let folders = cached_subfolders(mount, uri, refresh).await?;
+    let mut nodes = Vec::with_capacity(folders.len());
+    for folder in folders {
+        let path = if path.is_empty() {
+            folder.name.clone()
+        } else {
+            format!("{}/{}", path, folder.name)
+        };
+        let children = if depth > 1 {
+            let subtree = remote_subtree(mount, &folder.uri, &path, depth - 1, refresh);
+            Some(Box::pin(subtree).await?)
+        } else {
+            None
+        };
+        nodes.push(RemoteFolderNode {
+            name: folder.name,
+            uri: folder.uri,
+            path,
+            size: folder.size,
+            children,
+        });
+    }
+    Ok(nodes)
+}
+
+/// Subfolders of `uri` with their sizes, from the inventory cache if it is fresh
+async fn cached_subfolders(
+    mount: &Mount,
+    uri: &str,
+    refresh: bool,
+) -> Result<Vec<RemoteFolderEntry>> {
+    if !refresh {
+        match mount.inventory.get_cached_remote_folders(&mount.id, uri) {
+            Ok(Some((folders, fetched_at)))
+                if Utc::now().timestamp() - fetched_at < CACHE_TTL_SECS =>
+            {
+                return Ok(folders);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(target: "drive::manager", drive_id = %mount.id, error = %e, "Failed to read cached remote folders");
+            }
+        }
+    }
+
+    let folders = list_subfolders(mount, uri).await?;
+    let folders: Vec<RemoteFolderEntry> = futures::stream::iter(folders)
+        .map(|folder| async move {
+            let size = folder_size(mount, &folder.uri).await;
+            RemoteFolderEntry {
+                name: folder.name,
+                uri: folder.uri,
+                size,
+            }
+        })
+        .buffered(SIZE_CONCURRENCY)
+        .collect()
+        .await;
+
+    if let Err(e) = mount
+        .inventory
+        .cache_remote_folders(&mount.id, uri, &folders)
+    {
+        tracing::warn!(target: "drive::manager", drive_id = %mount.id, error = %e, "Failed to cache remote folders");
+    }
+    Ok(folders)
+}
+
+/// Size of the remote folder `uri` as summarized by the server
+async fn folder_size(mount: &Mount, uri: &str) -> Option<i64> {
+    let response = mount
+        .cr_client
+        .get_file_info(&GetFileInfoService {
+            uri: Some(uri.to_string()),
+            id: None,
+            extended: None,
+            folder_summary: Some(true),
+        })
+        .await;
+    match response {
+        Ok(file) => file.folder_summary.map(|summary| summary.size),
+        Err(e) => {
+            tracing::debug!(target: "drive::manager", uri = %uri, error = %e, "Failed to get remote folder size");
+            None
+        }
+    }
+}
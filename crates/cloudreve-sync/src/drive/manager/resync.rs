@@ -0,0 +1,93 @@
+use super::DriveManager;
+use crate::drive::sync::{SyncMode, SyncProgress};
+use anyhow::{Result, anyhow};
+use std::time::Duration;
+
+/// How often a running full resync reports its progress
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(2);
+
+impl DriveManager {
+    /// Walk the whole drive again and reconcile every item with the server, for users
+    /// who suspect the drive drifted from it. The walk lists the remote tree itself and
+    /// runs in the background; the change journal checkpoint is kept, so local changes
+    /// journaled meanwhile are still replayed. [`crate::events::Event::FullResync`]
+    /// reports when it starts and finishes, and
+    /// [`crate::events::Event::FullResyncProgress`] how far it got while it runs.
+    pub async fn force_resync(&self, drive_id: &str) -> Result<()> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow!("Drive not found: {}", drive_id))?;
+
+        let mut resyncs = self.resyncs.lock().await;
+        if resyncs
+            .get(drive_id)
+            .is_some_and(|handle| !handle.is_finished())
+        {
+            return Err(anyhow!("A full resync of this drive is already running"));
+        }
+
+        let sync_path = mount.get_sync_path().await;
+        let event_broadcaster = self.event_broadcaster.clone();
+
+        tracing::info!(target: "drive::manager", drive_id = %drive_id, "Full resync started");
+        event_broadcaster.full_resync(drive_id, true, None);
+        let start = mount.sync_counters.snapshot();
+        let handle = tokio::spawn(async move {
+            let walk = mount.sync_paths(vec![sync_path], SyncMode::FullHierarchy);
+            tokio::pin!(walk);
+            let mut ticker = tokio::time::interval(PROGRESS_INTERVAL);
+            let mut reported = SyncProgress::default();
+            let result = loop {
+                tokio::select! {
+                    result = &mut walk => break result,
+                    _ = ticker.tick() => {
+                        let progress = mount.sync_counters.snapshot().since(start);
+                        if progress != reported {
+                            event_broadcaster.full_resync_progress(
+                                &mount.id,
+                                progress.items_walked,
+                                progress.tasks_queued,
+                            );
+                            reported = progress;
+                        }
+                    }
+                }
+            };
+
+            let progress = mount.sync_counters.snapshot().since(start);
+            event_broadcaster.full_resync_progress(
+                &mount.id,
+                progress.items_walked,
+                progress.tasks_queued,
+            );
+            let error = match result {
+                Ok(()) => {
+                    tracing::info!(target: "drive::manager", drive_id = %mount.id, items_walked = progress.items_walked, tasks_queued = progress.tasks_queued, "Full resync finished");
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!(target: "drive::manager", drive_id = %mount.id, error = %e, "Full resync finished with errors");
+                    Some(e.to_string())
+                }
+            };
+            event_broadcaster.full_resync(&mount.id, false, error);
+        });
+        resyncs.insert(drive_id.to_string(), handle);
+        Ok(())
+    }
+
+    /// Stop the full resync of a removed drive
+    pub(super) async fn forget_resync(&self, drive_id: &str) {
+        if let Some(handle) = self.resyncs.lock().await.remove(drive_id) {
+            handle.abort();
+        }
+    }
+
+    /// Stop all full resyncs
+    pub(super) async fn stop_resyncs(&self) {
+        for (_, handle) in self.resyncs.lock().await.drain() {
+            handle.abort();
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use super::DriveManager;
+use crate::config::{ConfigManager, ScheduleConfig, ScheduledJob};
+use crate::drive::commands::MountCommand;
+use crate::drive::mounts::Mount;
+use crate::drive::sync::SyncMode;
+use crate::logging::{self, LogConfig};
+use anyhow::{Context, Result, anyhow};
+use chrono::{Local, Utc};
+use cron::Schedule;
+use serde::Serialize;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// A registered recurring job, as listed by [`DriveManager::list_scheduled_tasks`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledTaskInfo {
+    pub job: ScheduledJob,
+    pub cron: String,
+    /// Unix timestamp of the next run
+    pub next_run: Option<i64>,
+    /// Unix timestamp of the last run started this session
+    pub last_run: Option<i64>,
+    /// Error of the last run, if it failed
+    pub last_error: Option<String>,
+    pub running: bool,
+}
+
+/// Runtime state of a registered job
+pub(super) struct ScheduledTask {
+    cron: String,
+    schedule: Schedule,
+    state: Arc<std::sync::Mutex<RunState>>,
+    handle: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct RunState {
+    last_run: Option<i64>,
+    last_error: Option<String>,
+    running: bool,
+}
+
+impl DriveManager {
+    /// Register every enabled job from the app config
+    pub async fn spawn_scheduled_tasks(self: &Arc<Self>) {
+        let schedules = ConfigManager::try_get()
+            .map(|config| config.schedules())
+            .unwrap_or_default();
+        for schedule in schedules.into_iter().filter(|s| s.enabled) {
+            if let Err(e) = self
+                .register_scheduled_task(schedule.job, &schedule.cron)
+                .await
+            {
+                tracing::error!(target: "drive::scheduler", job = schedule.job.as_str(), error = %e, "Failed to schedule task");
+            }
+        }
+    }
+
+    /// Run `job` on the given cron expression from now on and save the schedule
+    pub async fn schedule_task(self: &Arc<Self>, job: ScheduledJob, cron: &str) -> Result<()> {
+        self.register_scheduled_task(job, cron).await?;
+        if let Some(config) = ConfigManager::try_get() {
+            config.set_schedule(ScheduleConfig {
+                job,
+                cron: cron.to_string(),
+                enabled: true,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Stop a job, aborting it if it is running, and disable it in the config.
+    /// Returns `false` if the job was not scheduled.
+    pub async fn cancel_scheduled_task(&self, job: ScheduledJob) -> Result<bool> {
+        let Some(task) = self.scheduled_tasks.lock().await.remove(&job) else {
+            return Ok(false);
+        };
+        task.handle.abort();
+        tracing::info!(target: "drive::scheduler", job = job.as_str(), "Cancelled scheduled task");
+
+        if let Some(config) = ConfigManager::try_get() {
+            config.set_schedule(ScheduleConfig {
+                job,
+                cron: task.cron,
+                enabled: false,
+            })?;
+        }
+        Ok(true)
+    }
+
+    /// List registered jobs with their next run time
+    pub async fn list_scheduled_tasks(&self) -> Vec<ScheduledTaskInfo> {
+        let tasks = self.scheduled_tasks.lock().await;
+        let mut result: Vec<ScheduledTaskInfo> = tasks
+            .iter()
+            .map(|(job, task)| {
+                let state = task.state.lock().unwrap_or_else(|e| e.into_inner());
+                ScheduledTaskInfo {
+                    job: *job,
+                    cron: task.cron.clone(),
+                    next_run: task
+                        .schedule
+                        .upcoming(Local)
+                        .next()
+                        .map(|time| time.timestamp()),
+                    last_run: state.last_run,
+                    last_error: state.last_error.clone(),
+                    running: state.running,
+                }
+            })
+            .collect();
+        result.sort_by_key(|info| info.job);
+        result
+    }
+
+    /// Stop all jobs without touching the config
+    pub(super) async fn stop_scheduled_tasks(&self) {
+        for (_, task) in self.scheduled_tasks.lock().await.drain() {
+            task.handle.abort();
+        }
+    }
+
+    async fn register_scheduled_task(
+        self: &Arc<Self>,
+        job: ScheduledJob,
+        cron: &str,
+    ) -> Result<()> {
+        let schedule = Schedule::from_str(cron)
+            .map_err(|e| anyhow!("Invalid cron expression '{}': {}", cron, e))?;
+        let state = Arc::new(std::sync::Mutex::new(RunState::default()));
+
+        let manager = self.clone();
+        let loop_schedule = schedule.clone();
+        let loop_state = state.clone();
+        let handle = tokio::spawn(async move {
+            while let Some(next) = loop_schedule.upcoming(Local).next() {
+                let delay = (next - Local::now()).to_std().unwrap_or_default();
+                tokio::time::sleep(delay).await;
+
+                set_state(&loop_state, |state| {
+                    state.last_run = Some(Utc::now().timestamp());
+                    state.running = true;
+                });
+                tracing::info!(target: "drive::scheduler", job = job.as_str(), "Running scheduled task");
+                let result = manager.run_scheduled_job(job).await;
+                if let Err(e) = &result {
+                    tracing::error!(target: "drive::scheduler", job = job.as_str(), error = %e, "Scheduled task failed");
+                }
+                set_state(&loop_state, |state| {
+                    state.last_error = result.err().map(|e| format!("{:#}", e));
+                    state.running = false;
+                });
+            }
+        });
+
+        let previous = self.scheduled_tasks.lock().await.insert(
+            job,
+            ScheduledTask {
+                cron: cron.to_string(),
+                schedule,
+                state,
+                handle,
+            },
+        );
+        if let Some(previous) = previous {
+            previous.handle.abort();
+        }
+        tracing::info!(target: "drive::scheduler", job = job.as_str(), cron, "Scheduled task registered");
+        Ok(())
+    }
+
+    /// Run one occurrence of a job immediately
+    pub async fn run_scheduled_job(&self, job: ScheduledJob) -> Result<()> {
+        match job {
+            ScheduledJob::FullRescan => {
+                let mounts: Vec<Arc<Mount>> = self.drives.read().await.values().cloned().collect();
+                for mount in mounts {
+                    mount
+                        .command_tx
+                        .send(MountCommand::Sync {
+                            local_paths: vec![mount.get_sync_path().await],
+                            mode: SyncMode::FullHierarchy,
+                        })
+                        .map_err(|e| anyhow!("Failed to queue rescan of {}: {}", mount.id, e))?;
+                }
+            }
+            ScheduledJob::InventoryVacuum => {
+                let mounts: Vec<Arc<Mount>> = self.drives.read().await.values().cloned().collect();
+                for mount in mounts {
+                    mount.vacuum_inventory().await?;
+                }
+            }
+            ScheduledJob::LogRotation => {
+                let config = LogConfig::from_config_manager();
+                let removed = tokio::task::spawn_blocking(move || {
+                    logging::prune_log_files(&config.log_dir, &config.file_prefix, config.max_files)
+                })
+                .await
+                .context("Log rotation task panicked")??;
+                tracing::info!(target: "drive::scheduler", removed, "Removed old log files");
+            }
+            ScheduledJob::TaskHistoryPrune => {
+                self.prune_task_history()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Mount {
+    /// Compact the inventory, unless a sync is using it
+    async fn vacuum_inventory(&self) -> Result<()> {
+        let Ok(_sync_guard) = self.sync_lock.try_lock() else {
+            tracing::debug!(target: "drive::mounts", id = %self.id, "Sync in progress, skipping inventory vacuum");
+            return Ok(());
+        };
+        let inventory = self.inventory.clone();
+        tokio::task::spawn_blocking(move || inventory.vacuum())
+            .await
+            .context("Inventory vacuum task panicked")?
+    }
+}
+
+fn set_state(state: &std::sync::Mutex<RunState>, f: impl FnOnce(&mut RunState)) {
+    f(&mut state.lock().unwrap_or_else(|e| e.into_inner()));
+}
@@ -0,0 +1,154 @@
+use super::DriveManager;
+use crate::drive::mounts::Mount;
+use crate::uploader::{NoOpProgress, UploadParams, Uploader, UploaderConfig};
+use crate::utils::bidi::isolate;
+use crate::utils::toast::send_general_text_toast;
+use anyhow::{Context, Result};
+use cloudreve_api::api::explorer::ExplorerApiExt;
+use cloudreve_api::models::explorer::file_type;
+use cloudreve_api::models::uri::CrUri;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use uuid::Uuid;
+
+const PAGE_SIZE: i32 = 1000;
+
+/// A folder offered by the "Send to Cloudreve" folder picker
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteFolder {
+    pub name: String,
+    pub uri: String,
+}
+
+impl DriveManager {
+    /// Folders directly inside `uri` on drive `drive_id`, for the "Send to Cloudreve"
+    /// folder picker
+    pub async fn list_remote_folders(
+        &self,
+        drive_id: &str,
+        uri: &str,
+    ) -> Result<Vec<RemoteFolder>> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+
+        list_subfolders(&mount, uri).await
+    }
+
+    /// Upload files from outside any sync root into the remote folder `folder_uri` of
+    /// drive `drive_id`. The uploads run in the background and a toast reports the
+    /// outcome; the files are not synced afterwards.
+    pub async fn send_to_drive(
+        &self,
+        drive_id: &str,
+        folder_uri: &str,
+        paths: Vec<PathBuf>,
+    ) -> Result<()> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+        CrUri::new(folder_uri).context("invalid remote folder")?;
+        if paths.is_empty() {
+            return Err(anyhow::anyhow!("No files to send"));
+        }
+        if let Some(path) = paths.iter().find(|path| !path.is_file()) {
+            return Err(anyhow::anyhow!("Not a file: {}", path.display()));
+        }
+
+        let folder_uri = folder_uri.to_string();
+        tokio::spawn(async move {
+            let total = paths.len();
+            let mut failed = 0;
+            for path in &paths {
+                if let Err(e) = send_file(&mount, path, &folder_uri).await {
+                    tracing::error!(target: "drive::manager", path = %path.display(), error = ?e, "Failed to send file to drive");
+                    failed += 1;
+                }
+            }
+
+            let name = mount.get_config().await.name;
+            let message = match failed {
+                0 => t!("sendToDoneMessage", "count" => total, "name" => isolate(&name)),
+                failed => t!(
+                    "sendToFailedMessage",
+                    "count" => total - failed,
+                    "failed" => failed,
+                    "name" => isolate(&name)
+                ),
+            };
+            send_general_text_toast(&t!("sendToDone"), &message);
+        });
+        Ok(())
+    }
+}
+
+/// Upload the local file `path` into `folder_uri`, keeping its name
+async fn send_file(mount: &Mount, path: &Path, folder_uri: &str) -> Result<()> {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .context("invalid file name")?;
+    let metadata = std::fs::metadata(path).context("failed to read file metadata")?;
+    let mut remote_uri = CrUri::new(folder_uri)?;
+    remote_uri.join(&[name]);
+
+    let params = UploadParams {
+        local_path: path.to_path_buf(),
+        remote_uri: remote_uri.to_string(),
+        file_size: metadata.len(),
+        mime_type: None,
+        last_modified: metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|modified| modified.as_millis() as i64),
+        overwrite: false,
+        previous_version: String::new(),
+        task_id: Uuid::new_v4().to_string(),
+        drive_id: mount.id.clone(),
+    };
+
+    // The file is not part of the sync root, so the inventory only holds the upload
+    // session while the upload runs
+    Uploader::new(
+        mount.cr_client.clone(),
+        mount.inventory.clone(),
+        UploaderConfig::default(),
+    )
+    .upload(params, NoOpProgress)
+    .await
+    .context("failed to upload file")
+}
+
+/// Folders directly inside `uri`, following the pages of large folders
+pub(super) async fn list_subfolders(mount: &Mount, uri: &str) -> Result<Vec<RemoteFolder>> {
+    let mut folders = Vec::new();
+    let mut previous_response = None;
+    loop {
+        let response = mount
+            .cr_client
+            .list_files_all(previous_response.as_ref(), uri, PAGE_SIZE)
+            .await
+            .context("failed to list remote folder")?;
+        folders.extend(
+            response
+                .res
+                .files
+                .iter()
+                .filter(|file| file.file_type == file_type::FOLDER)
+                .map(|file| RemoteFolder {
+                    name: file.name.clone(),
+                    uri: file.path.clone(),
+                }),
+        );
+        let has_more = response.more;
+        previous_response = Some(response);
+        if !has_more {
+            break;
+        }
+    }
+    Ok(folders)
+}
@@ -0,0 +1,149 @@
+use super::{CapacitySummary, DriveManager, FreedSpace};
+use crate::cfapi::placeholder::{LocalFileInfo, OpenOptions, PinState};
+use anyhow::{Context, Result, anyhow};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+/// Storage used by a drive on the server and on this device, for the disk usage bar in
+/// settings
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageBreakdown {
+    pub drive_id: String,
+    /// Quota of the account, `None` if it is unknown or the drive does not sync the
+    /// user's own files
+    pub capacity: Option<CapacitySummary>,
+    /// Files of the drive known to the inventory
+    pub synced_files: i64,
+    /// Total size of those files in bytes
+    pub synced_bytes: i64,
+    /// Bytes of file data stored on this device
+    pub on_disk_bytes: u64,
+    /// Part of `on_disk_bytes` held by files pinned to the device
+    pub pinned_bytes: u64,
+    /// Part of `on_disk_bytes` that "Free up space" would remove
+    pub reclaimable_bytes: u64,
+}
+
+/// Disk space reclaimed by [`DriveManager::free_up_space`]
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ReclaimedSpace {
+    /// Files turned back into cloud-only placeholders
+    pub dehydrated: FreedSpace,
+    /// Bytes of cached thumbnails removed
+    pub thumbnail_bytes: u64,
+    /// Cached remote folder listings removed
+    pub cached_listings: usize,
+    /// Bytes reclaimed in total
+    pub bytes: u64,
+}
+
+/// Bytes of file data found on disk by [`local_usage`]
+#[derive(Debug, Default)]
+struct LocalUsage {
+    on_disk: u64,
+    pinned: u64,
+    reclaimable: u64,
+}
+
+impl DriveManager {
+    /// Remote capacity of drive `drive_id` together with how much of it is synced and
+    /// stored locally. Walks the sync folder to add up the hydrated data.
+    pub async fn get_storage_breakdown(&self, drive_id: &str) -> Result<StorageBreakdown> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow!("Drive not found: {}", drive_id))?;
+        let config = mount.get_config().await;
+
+        let capacity = Self::get_capacity_summary(&mount, drive_id, &config.remote_path);
+        let (synced_files, synced_bytes) = mount.inventory.file_totals()?;
+        let sync_path = config.sync_path.clone();
+        let local = tokio::task::spawn_blocking(move || local_usage(&sync_path))
+            .await
+            .context("Storage usage task panicked")?;
+
+        Ok(StorageBreakdown {
+            drive_id: drive_id.to_string(),
+            capacity,
+            synced_files,
+            synced_bytes,
+            on_disk_bytes: local.on_disk,
+            pinned_bytes: local.pinned,
+            reclaimable_bytes: local.reclaimable,
+        })
+    }
+
+    /// Free up the space drive `drive_id` takes on this device: dehydrate its files
+    /// that are in sync and not pinned, with `unused_for` only those not used for that
+    /// long, and clear the drive's cached remote folder listings. Thumbnails of all
+    /// drives share one cache, which is cleared as a whole.
+    pub async fn free_up_space(
+        &self,
+        drive_id: &str,
+        unused_for: Option<Duration>,
+    ) -> Result<ReclaimedSpace> {
+        let mount = self
+            .get_drive(drive_id)
+            .await
+            .ok_or_else(|| anyhow!("Drive not found: {}", drive_id))?;
+
+        let sync_path = mount.get_sync_path().await;
+        let dehydrated = mount.free_up_space(&sync_path, unused_for).await?;
+        let thumbnail_bytes = self.clear_thumbnail_cache().unwrap_or_else(|e| {
+            tracing::warn!(target: "drive::manager", drive_id = %drive_id, error = %e, "Failed to clear thumbnail cache");
+            0
+        });
+        let cached_listings = mount.inventory.clear_remote_folder_cache(drive_id)?;
+
+        let reclaimed = ReclaimedSpace {
+            dehydrated,
+            thumbnail_bytes,
+            cached_listings,
+            bytes: dehydrated.bytes + thumbnail_bytes,
+        };
+        tracing::info!(target: "drive::manager", drive_id = %drive_id, files = dehydrated.files, bytes = reclaimed.bytes, "Freed up space");
+        Ok(reclaimed)
+    }
+}
+
+/// Add up the data stored on disk by the placeholders below `root`. Files count as
+/// reclaimable under the same conditions under which they are dehydrated to free up
+/// space.
+fn local_usage(root: &Path) -> LocalUsage {
+    let mut usage = LocalUsage::default();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(path) = pending.pop() {
+        let Ok(local) = LocalFileInfo::from_path(&path) else {
+            continue;
+        };
+        if !local.is_placeholder() {
+            continue;
+        }
+        if local.is_directory {
+            match std::fs::read_dir(&path) {
+                Ok(read_dir) => pending.extend(read_dir.flatten().map(|child| child.path())),
+                Err(e) => {
+                    tracing::warn!(target: "drive::manager", path = %path.display(), error = %e, "Failed to read directory while measuring storage");
+                }
+            }
+            continue;
+        }
+
+        let on_disk = OpenOptions::new()
+            .open(&path)
+            .ok()
+            .and_then(|handle| handle.info().ok().flatten())
+            .map(|info| info.on_disk_data_size().max(0) as u64)
+            .unwrap_or(0);
+        usage.on_disk += on_disk;
+        if local.pinned() == PinState::Pinned {
+            usage.pinned += on_disk;
+        } else if local.in_sync() {
+            usage.reclaimable += on_disk;
+        }
+    }
+
+    usage
+}
@@ -0,0 +1,268 @@
+use super::DriveManager;
+use crate::cfapi::placeholder::{LocalFileInfo, OpenOptions};
+use crate::drive::commands::MountCommand;
+use crate::drive::mounts::Mount;
+use crate::drive::sync::SyncMode;
+use crate::inventory::{
+    ChangeSource, IntegrityRepair, IntegrityReport, InventoryDb, MetadataEntry,
+};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How often to retry moving a damaged inventory aside while its handles are released
+const MOVE_ASIDE_RETRIES: u32 = 10;
+const MOVE_ASIDE_RETRY_DELAY: Duration = Duration::from_millis(300);
+
+/// Inventory health of one drive, as shown in advanced troubleshooting
+#[derive(Debug, Clone, Serialize)]
+pub struct InventoryHealth {
+    pub drive_id: String,
+    pub report: IntegrityReport,
+    /// Present if a repair was requested
+    pub repair: Option<IntegrityRepair>,
+    /// Entries recreated from placeholders, if the database could not be repaired in place
+    pub rebuilt_entries: Option<usize>,
+}
+
+impl DriveManager {
+    /// Check the inventory of one drive or all drives, repairing problems if `repair` is set
+    pub async fn troubleshoot_inventory(
+        &self,
+        drive_id: Option<&str>,
+        repair: bool,
+    ) -> Result<Vec<InventoryHealth>> {
+        let mounts: Vec<Arc<Mount>> = {
+            let drives = self.drives.read().await;
+            match drive_id {
+                Some(id) => vec![
+                    drives
+                        .get(id)
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", id))?,
+                ],
+                None => drives.values().cloned().collect(),
+            }
+        };
+
+        let mut results = Vec::with_capacity(mounts.len());
+        for mount in mounts {
+            let health = if repair {
+                let mut health = mount.repair_inventory().await?;
+                if health.report.is_corrupt() {
+                    let (rebuilt, report) = self.rebuild_inventory(mount).await?;
+                    health.rebuilt_entries = Some(rebuilt);
+                    health.report = report;
+                }
+                health
+            } else {
+                mount.check_inventory().await?
+            };
+            results.push(health);
+        }
+        Ok(results)
+    }
+
+    /// Replace a drive's damaged inventory with a fresh database rebuilt from the
+    /// placeholders under its sync root. The mount is stopped so the damaged file can be
+    /// moved aside, then started again on the new database, and a full sync is queued to
+    /// restore details the placeholders do not carry. Returns the number of entries
+    /// recreated and the integrity report of the new database.
+    async fn rebuild_inventory(&self, mount: Arc<Mount>) -> Result<(usize, IntegrityReport)> {
+        let drive_id = mount.id.clone();
+        let config = mount.get_config().await;
+        let sync_path = mount.get_sync_path().await;
+
+        {
+            let _sync_guard = mount.sync_lock.lock().await;
+            self.drives.write().await.remove(&drive_id);
+            self.forget_resync(&drive_id).await;
+            mount.stop().await;
+        }
+        if let Ok(mut inventories) = self.inventories.write() {
+            inventories.remove(&drive_id);
+        }
+        drop(mount);
+
+        let rebuilt = match self.move_aside_inventory(&drive_id).await {
+            Ok(aside_path) => {
+                tracing::warn!(target: "drive::manager", drive_id = %drive_id, path = %aside_path.display(), "Damaged inventory moved aside");
+                self.fill_fresh_inventory(&drive_id, &sync_path).await
+            }
+            Err(e) => Err(e),
+        };
+
+        // Bring the drive back even if the rebuild failed, on whichever database is in place
+        let inventory = match &rebuilt {
+            Ok((inventory, _, _)) => inventory.clone(),
+            Err(_) => Arc::new(
+                self.open_drive_inventory(&drive_id)
+                    .context("Failed to reopen drive inventory")?,
+            ),
+        };
+        self.start_mount(config, inventory).await?;
+        let mount = self
+            .get_drive(&drive_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Drive not found: {}", drive_id))?;
+        if self.paused_syncs.lock().await.contains_key(&drive_id) {
+            mount.task_queue.pause_tasks();
+        }
+        let (_, entries, report) = rebuilt?;
+
+        tracing::warn!(target: "drive::manager", drive_id = %drive_id, entries, "Inventory rebuilt from placeholders");
+        if let Err(e) = mount.command_tx.send(MountCommand::Sync {
+            local_paths: vec![sync_path],
+            mode: SyncMode::FullHierarchy,
+        }) {
+            tracing::error!(target: "drive::manager", drive_id = %drive_id, error = %e, "Failed to send Sync command");
+        }
+        Ok((entries, report))
+    }
+
+    /// Move a stopped drive's database files aside, retrying while tasks that were
+    /// still finishing release their handles
+    async fn move_aside_inventory(&self, drive_id: &str) -> Result<PathBuf> {
+        let mut attempt = 0;
+        loop {
+            match InventoryDb::move_aside_drive_files(drive_id) {
+                Ok(path) => return Ok(path),
+                Err(e) if attempt < MOVE_ASIDE_RETRIES => {
+                    tracing::debug!(target: "drive::manager", drive_id = %drive_id, error = %e, "Inventory still in use, retrying");
+                    attempt += 1;
+                    tokio::time::sleep(MOVE_ASIDE_RETRY_DELAY).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Create a new database for a drive and fill it from the placeholders under
+    /// `sync_path`
+    async fn fill_fresh_inventory(
+        &self,
+        drive_id: &str,
+        sync_path: &Path,
+    ) -> Result<(Arc<InventoryDb>, usize, IntegrityReport)> {
+        let inventory = Arc::new(
+            self.open_drive_inventory(drive_id)
+                .context("Failed to create fresh drive inventory")?,
+        );
+        let drive_uuid = Uuid::parse_str(drive_id).context("invalid drive ID")?;
+        let sync_path = sync_path.to_path_buf();
+        let (entries, report) = {
+            let inventory = inventory.with_source(ChangeSource::Repair);
+            tokio::task::spawn_blocking(move || -> Result<(usize, IntegrityReport)> {
+                let entries = collect_placeholder_entries(&sync_path, &drive_uuid);
+                let rebuilt = inventory
+                    .batch_upsert(&entries)
+                    .context("Failed to rebuild inventory from placeholders")?;
+                Ok((rebuilt, inventory.check_integrity()?))
+            })
+            .await
+            .context("Inventory rebuild task panicked")??
+        };
+        Ok((inventory, entries, report))
+    }
+}
+
+impl Mount {
+    /// Run the inventory integrity checks without changing anything
+    pub async fn check_inventory(&self) -> Result<InventoryHealth> {
+        let inventory = self.inventory.clone();
+        let report = tokio::task::spawn_blocking(move || inventory.check_integrity())
+            .await
+            .context("Integrity check task panicked")??;
+        Ok(InventoryHealth {
+            drive_id: self.id.clone(),
+            report,
+            repair: None,
+            rebuilt_entries: None,
+        })
+    }
+
+    /// Repair the inventory in place. A database that is still damaged afterwards must
+    /// be rebuilt into a new file, see [`DriveManager::troubleshoot_inventory`].
+    pub async fn repair_inventory(&self) -> Result<InventoryHealth> {
+        let _sync_guard = self.sync_lock.lock().await;
+        let inventory = self.inventory.with_source(ChangeSource::Repair);
+        let repair = tokio::task::spawn_blocking(move || inventory.repair_integrity())
+            .await
+            .context("Inventory repair task panicked")??;
+
+        Ok(InventoryHealth {
+            drive_id: self.id.clone(),
+            report: repair.after.clone(),
+            repair: Some(repair),
+            rebuilt_entries: None,
+        })
+    }
+
+    /// Stop the mount and disconnect its sync root, keeping the registration so the
+    /// drive can be started again
+    async fn stop(&self) {
+        self.shutdown().await;
+        let connection = self
+            .connection
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(connection) = connection {
+            if let Err(e) = connection.disconnect() {
+                tracing::warn!(target: "drive::mounts", id = %self.id, error = %e, "Failed to disconnect sync root");
+            }
+        }
+    }
+}
+
+/// Walk the sync root and build an inventory entry for every placeholder, using the
+/// etag stored in its file identity blob. `updated_at` is left at zero so the next
+/// sync refreshes each entry from the remote.
+fn collect_placeholder_entries(sync_root: &Path, drive_id: &Uuid) -> Vec<MetadataEntry> {
+    let mut entries = Vec::new();
+    let mut pending: Vec<PathBuf> = vec![sync_root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let read_dir = match std::fs::read_dir(&dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) => {
+                tracing::warn!(target: "drive::mounts", path = %dir.display(), error = %e, "Failed to read directory during rebuild");
+                continue;
+            }
+        };
+
+        for child in read_dir.flatten() {
+            let path = child.path();
+            let Ok(local) = LocalFileInfo::from_path(&path) else {
+                continue;
+            };
+            if !local.is_placeholder() {
+                continue;
+            }
+            if local.is_directory {
+                pending.push(path.clone());
+            }
+
+            let etag = OpenOptions::new()
+                .open(&path)
+                .ok()
+                .and_then(|handle| handle.info().ok().flatten())
+                .map(|info| String::from_utf8_lossy(info.blob()).into_owned())
+                .unwrap_or_default();
+            let Some(path_str) = path.to_str() else {
+                continue;
+            };
+            entries.push(
+                MetadataEntry::new(*drive_id, path_str, local.is_directory)
+                    .with_etag(etag)
+                    .with_size(local.file_size.unwrap_or(0) as i64)
+                    .with_updated_at(0),
+            );
+        }
+    }
+
+    entries
+}
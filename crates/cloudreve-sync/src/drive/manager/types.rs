@@ -1,6 +1,6 @@
-use crate::drive::mounts::DriveConfig;
+use crate::drive::mounts::{DriveConfig, NavigationPane};
 use crate::inventory::TaskRecord;
-use crate::tasks::TaskProgress;
+use crate::tasks::{TaskProgress, TaskQueueStats};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -17,6 +17,8 @@ pub struct StatusSummary {
     pub active_tasks: Vec<TaskWithProgress>,
     /// Recently finished tasks (completed/failed/cancelled)
     pub finished_tasks: Vec<TaskRecord>,
+    /// Worker pool size and queue depth of each drive's task queue
+    pub queue_stats: Vec<TaskQueueStats>,
 }
 
 /// A task record with optional live progress information
@@ -29,6 +31,25 @@ pub struct TaskWithProgress {
     pub live_progress: Option<TaskProgress>,
 }
 
+/// Disk space reclaimed by turning hydrated files back into cloud-only placeholders
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct FreedSpace {
+    /// Files dehydrated
+    pub files: usize,
+    /// Bytes of file data removed from disk
+    pub bytes: u64,
+    /// Candidates left hydrated because they could not be dehydrated, e.g. open files
+    pub skipped: usize,
+}
+
+impl std::ops::AddAssign for FreedSpace {
+    fn add_assign(&mut self, other: Self) {
+        self.files += other.files;
+        self.bytes += other.bytes;
+        self.skipped += other.skipped;
+    }
+}
+
 /// Capacity summary for UI display
 #[derive(Debug, Clone, Serialize)]
 pub struct CapacitySummary {
@@ -50,12 +71,21 @@ pub enum SyncStatus {
     /// Sync is paused
     Paused,
     /// There was an error during sync
-    Error,
+    Error {
+        /// Error of the most recent task that gave up
+        message: String,
+    },
+    /// The drive credential expired and the user has to sign in again
+    CredentialExpired,
+    /// The server cannot be reached, remote changes are not received
+    Offline,
 }
 
 /// Drive status information for the Windows Shell UI
 #[derive(Debug, Clone, Serialize)]
 pub struct DriveStatusUI {
+    /// Drive ID
+    pub id: String,
     /// Drive display name
     pub name: String,
     /// Path to the raw (non-ICO) icon image
@@ -71,6 +101,10 @@ pub struct DriveStatusUI {
     pub sync_status: SyncStatus,
     /// Number of active (pending/running) tasks
     pub active_task_count: usize,
+    /// When a task of the drive last completed, as a Unix timestamp
+    pub last_synced_at: Option<i64>,
+    /// Site to sign in again, set while the credential is expired
+    pub sign_in_url: Option<String>,
 }
 
 /// Drive information for the settings UI
@@ -89,6 +123,8 @@ pub struct DriveInfo {
     pub icon_path: Option<String>,
     /// Path to the raw (non-ICO) icon image
     pub raw_icon_path: Option<String>,
+    /// Accent color as `#rrggbb`
+    pub color: Option<String>,
     /// Whether the drive is enabled
     pub enabled: bool,
     /// User ID
@@ -97,6 +133,8 @@ pub struct DriveInfo {
     pub status: DriveInfoStatus,
     /// Capacity summary (None if not available)
     pub capacity: Option<CapacitySummary>,
+    /// Pinned state and position in Explorer's navigation pane
+    pub navigation_pane: NavigationPane,
 }
 
 /// Drive status for the settings UI
@@ -2,9 +2,16 @@ pub mod callback;
 pub mod commands;
 pub mod event_blocker;
 pub mod ignore;
+pub mod import;
 pub mod manager;
 pub mod mounts;
+pub mod navigation_pane;
 pub mod placeholder;
+pub mod prefetch;
+pub mod recovery;
 pub mod remote_events;
+pub mod repair;
 pub mod sync;
+pub mod thumbnails;
+pub mod usn;
 pub mod utils;
@@ -1,20 +1,32 @@
 use crate::cfapi::root::{
-    Connection, HydrationType, PopulationType, SecurityId, Session, SyncRootId, SyncRootIdBuilder,
-    SyncRootInfo,
+    Connection, HydrationPolicy, HydrationType, PopulationType, SecurityId, Session, SyncRootId,
+    SyncRootIdBuilder, SyncRootInfo,
 };
+use crate::config::ProxyConfig;
 use crate::drive::callback::CallbackHandler;
 use crate::drive::commands::ManagerCommand;
 use crate::drive::commands::MountCommand;
 use crate::drive::event_blocker::EventBlocker;
 use crate::drive::ignore::IgnoreMatcher;
-use crate::drive::sync::group_fs_events;
-use crate::inventory::{DrivePropsUpdate, InventoryDb, TaskRecord};
-use crate::tasks::{TaskProgress, TaskQueue, TaskQueueConfig};
-use crate::utils::toast;
+use crate::drive::navigation_pane;
+use crate::drive::prefetch::Prefetcher;
+use crate::drive::sync::{SyncCounters, group_fs_events};
+use crate::drive::utils::notify_shell_change;
+use crate::inventory::{ChangeSource, DrivePropsUpdate, InventoryDb, TaskRecord};
+use crate::shellext::custom_state::{
+    CUSTOM_STATE_ACCESS, CUSTOM_STATE_CONFLICT, CUSTOM_STATE_MODIFIED_BY, CUSTOM_STATE_OWNER,
+    CUSTOM_STATE_SHARED, CUSTOM_STATE_SYNC_ERROR,
+};
+use crate::tasks::{FetchCancellations, TaskKind, TaskProgress, TaskQueue, TaskQueueConfig};
+use crate::utils::{bidi::isolate, toast};
 use ::serde::{Deserialize, Serialize};
 use anyhow::{Context, Result};
 use cloudreve_api::api::user::UserApi;
-use cloudreve_api::{Client, ClientConfig, models::user::Token};
+use cloudreve_api::{
+    Client, ClientConfig,
+    models::user::{Capacity, Token},
+};
+use flagset::FlagSet;
 use notify_debouncer_full::notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_full::{DebounceEventResult, Debouncer, RecommendedCache, new_debouncer};
 use sha2::{Digest, Sha256};
@@ -29,6 +41,8 @@ use tokio::sync::{Mutex, RwLock, mpsc};
 use tokio::task::JoinHandle;
 use url::Url;
 use windows::Storage::Provider::StorageProviderSyncRootManager;
+use windows::Win32::UI::Shell::SHCNE_UPDATEDIR;
+use windows::core::GUID;
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DriveConfig {
     pub id: String,
@@ -40,6 +54,9 @@ pub struct DriveConfig {
     pub icon_path: Option<String>,
     /// Path to the raw (non-ICO) favicon image
     pub raw_icon_path: Option<String>,
+    /// Accent color of the drive in the UI as `#rrggbb`, the default accent if `None`
+    #[serde(default)]
+    pub color: Option<String>,
     pub enabled: bool,
     pub user_id: String,
 
@@ -50,10 +67,97 @@ pub struct DriveConfig {
     #[serde(default)]
     pub ignore_patterns: Vec<String>,
 
+    /// How Windows hydrates, dehydrates and populates placeholders of the drive
+    #[serde(default)]
+    pub sync_root_policy: SyncRootPolicy,
+
+    /// How the drive appears in Explorer's navigation pane
+    #[serde(default)]
+    pub navigation_pane: NavigationPane,
+
+    /// The user's pause of syncing, restored when the app starts again
+    #[serde(default)]
+    pub sync_pause: Option<SyncPause>,
+
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
 
+/// Cloud Files behaviors of a drive's sync root, applied by registering it again
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct SyncRootPolicy {
+    pub hydration: HydrationMode,
+    /// Let Windows dehydrate files that were not used for a while to free up space
+    pub allow_auto_dehydration: bool,
+    pub population: PopulationMode,
+}
+
+/// Navigation pane entry of a drive's sync root, applied at mount and when changed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct NavigationPane {
+    /// Show the drive pinned in the navigation pane
+    pub pinned: bool,
+    /// Position among the drives in the navigation pane, lower first
+    pub order: u32,
+}
+
+/// A pause of a drive's syncing, see `DriveManager::pause_sync`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SyncPause {
+    /// Unix timestamp the pause ends at, `None` if it lasts until resumed
+    pub until: Option<i64>,
+}
+
+impl Default for NavigationPane {
+    fn default() -> Self {
+        Self {
+            pinned: true,
+            order: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HydrationMode {
+    /// Applications get file data once the whole file is downloaded
+    #[default]
+    Full,
+    /// Applications get file data as soon as the range they read is downloaded
+    Progressive,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PopulationMode {
+    /// Folders are listed from the server when they are first opened
+    #[default]
+    OnDemand,
+    /// Windows assumes folders are always listed, so every folder is created up front
+    /// by a full sync
+    AlwaysFull,
+}
+
+impl From<HydrationMode> for HydrationType {
+    fn from(mode: HydrationMode) -> Self {
+        match mode {
+            HydrationMode::Full => HydrationType::Full,
+            HydrationMode::Progressive => HydrationType::Progressive,
+        }
+    }
+}
+
+impl From<PopulationMode> for PopulationType {
+    fn from(mode: PopulationMode) -> Self {
+        match mode {
+            PopulationMode::OnDemand => PopulationType::Full,
+            PopulationMode::AlwaysFull => PopulationType::AlwaysFull,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Credentials {
     pub access_token: Option<String>,
@@ -127,15 +231,19 @@ type FsWatcher = Debouncer<RecommendedWatcher, RecommendedCache>;
 
 pub struct Mount {
     pub config: Arc<RwLock<DriveConfig>>,
-    connection: Option<Connection<CallbackHandler>>,
+    /// Replaced when the sync root is re-registered, see [`Mount::repair_sync_root`]
+    pub(crate) connection: std::sync::Mutex<Option<Connection<CallbackHandler>>>,
     pub command_tx: mpsc::UnboundedSender<MountCommand>,
     command_rx: Arc<Mutex<Option<mpsc::UnboundedReceiver<MountCommand>>>>,
     processor_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
     props_refresh_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
     remote_event_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
+    pub(crate) prefetch_handle: Arc<Mutex<Option<JoinHandle<()>>>>,
     manager_command_tx: mpsc::UnboundedSender<ManagerCommand>,
     fs_watcher: Mutex<Option<FsWatcher>>,
     pub(crate) sync_lock: Mutex<()>,
+    /// Progress of the syncs, see [`Mount::sync_paths`]
+    pub(crate) sync_counters: SyncCounters,
     pub cr_client: Arc<Client>,
     pub inventory: Arc<InventoryDb>,
     pub task_queue: Arc<TaskQueue>,
@@ -145,6 +253,10 @@ pub struct Mount {
     pub ignore_matcher: IgnoreMatcher,
     /// Status flags for the mount (credential expired, event push subscribed, etc.)
     status_flags: Mutex<MountStatusFlags>,
+    /// Hydrates files likely to be opened next
+    pub prefetcher: Prefetcher,
+    /// Fetches in flight, cancelled when the platform no longer needs their data
+    pub fetch_cancellations: FetchCancellations,
 }
 
 impl Mount {
@@ -153,16 +265,12 @@ impl Mount {
         inventory: Arc<InventoryDb>,
         manager_command_tx: mpsc::UnboundedSender<ManagerCommand>,
     ) -> Self {
-        // let task_config = TaskManagerConfig {
-        //     max_workers: 4,
-        //     completed_buffer_size: 100,
-        // };
-        // let task_manager = TaskManager::new(task_config);
         let (command_tx, command_rx) = mpsc::unbounded_channel();
         // initialize the client with the credentials
         let client_config = ClientConfig::new(config.instance_url.clone())
             .with_client_id(config.id.clone())
-            .with_user_agent(crate::USER_AGENT);
+            .with_user_agent(crate::USER_AGENT)
+            .with_proxy(ProxyConfig::current().client_proxy());
         let mut cr_client = Client::new(client_config);
         let _ = cr_client
             .set_tokens_with_expiry(&Token {
@@ -239,12 +347,13 @@ impl Mount {
 
         Self {
             config: Arc::new(RwLock::new(config)),
-            connection: None,
+            connection: std::sync::Mutex::new(None),
             command_tx,
             command_rx: Arc::new(tokio::sync::Mutex::new(Some(command_rx))),
             processor_handle: Arc::new(tokio::sync::Mutex::new(None)),
             props_refresh_handle: Arc::new(tokio::sync::Mutex::new(None)),
             remote_event_handle: Arc::new(tokio::sync::Mutex::new(None)),
+            prefetch_handle: Arc::new(tokio::sync::Mutex::new(None)),
             cr_client: cr_client_arc,
             inventory,
             task_queue,
@@ -252,9 +361,12 @@ impl Mount {
             manager_command_tx,
             fs_watcher: Mutex::new(None),
             sync_lock: Mutex::new(()),
+            sync_counters: SyncCounters::default(),
             event_blocker: EventBlocker::new(),
             ignore_matcher,
             status_flags: Mutex::new(MountStatusFlags::new()),
+            prefetcher: Prefetcher::new(),
+            fetch_cancellations: FetchCancellations::default(),
         }
     }
 
@@ -323,10 +435,11 @@ impl Mount {
             let drive_id = config.id.clone();
             drop(config);
 
+            self.inventory.notify_credential_expired(&drive_name);
             toast::send_token_expiry_toast(
                 &drive_id,
                 &t!("credentialExpiredTitle"),
-                &t!("credentialExpiredMessage", "drive" => drive_name),
+                &t!("credentialExpiredMessage", "drive" => isolate(drive_name)),
             );
         }
     }
@@ -361,7 +474,8 @@ impl Mount {
         let mut write_guard = self.config.write().await;
 
         // if sync root id is not set, generate one
-        if write_guard.sync_root_id.is_none() {
+        let new_sync_root = write_guard.sync_root_id.is_none();
+        if new_sync_root {
             write_guard.sync_root_id = Some(
                 generate_sync_root_id(
                     &write_guard.instance_url,
@@ -381,28 +495,24 @@ impl Mount {
 
         let sync_root_id = config.sync_root_id.as_ref().unwrap();
 
-        // Register sync root if not registered
-        if !sync_root_id.is_registered()? {
-            tracing::info!(target: "drive::mounts", id = %self.id, "Registering sync root");
-            let mut sync_root_info = SyncRootInfo::default();
-            sync_root_info.set_display_name(config.name.clone());
-            sync_root_info.set_hydration_type(HydrationType::Full);
-            sync_root_info.set_population_type(PopulationType::Full);
-            if let Some(icon_path) = config.icon_path.as_ref() {
-                sync_root_info.set_icon(format!("{},0", icon_path));
+        // Register sync root if not registered, or refresh its branding if the drive was
+        // renamed or its favicon fetched since. A registration missing for an existing
+        // drive was removed behind our back, and its placeholders need re-stamping.
+        let registration_lost = !new_sync_root && !sync_root_id.is_registered()?;
+        if new_sync_root || registration_lost {
+            if registration_lost {
+                tracing::warn!(target: "drive::mounts", id = %self.id, "Sync root registration is missing, re-registering");
+            } else {
+                tracing::info!(target: "drive::mounts", id = %self.id, "Registering sync root");
             }
-            sync_root_info.set_version("1.0.0");
-            sync_root_info
-                .set_recycle_bin_uri("https://cloudreve.org")
-                .context("failed to set recycle bin uri")?;
-            sync_root_info
-                .set_path(Path::new(&config.sync_path))
-                .context("failed to set sync root path")?;
-            sync_root_info.add_custom_state(t!("shared").as_ref(), 1)?;
-            sync_root_info.add_custom_state(t!("accessible").as_ref(), 2)?;
             sync_root_id
-                .register(sync_root_info)
+                .register(build_sync_root_info(&config)?)
                 .context("failed to register sync root")?;
+        } else if let Err(e) = self.refresh_sync_root_branding(&config) {
+            tracing::warn!(target: "drive::mounts", id = %self.id, error = ?e, "Failed to refresh sync root branding");
+        }
+        if let Err(e) = self.refresh_navigation_pane(&config) {
+            tracing::warn!(target: "drive::mounts", id = %self.id, error = ?e, "Failed to apply navigation pane settings");
         }
 
         // Add to search indexer for state management
@@ -410,23 +520,101 @@ impl Mount {
             tracing::warn!(target: "drive::mounts", id = %self.id, error = %e, "Failed to add sync root to search indexer");
         }
 
-        tracing::info!(target: "drive::mounts",sync_path = %config.sync_path.display(), id = %self.id, "Connecting to sync root");
-        let connection = Session::new()
+        let connection = self.connect_sync_root(&config.sync_path)?;
+        *self.connection.lock().unwrap_or_else(|e| e.into_inner()) = Some(connection);
+        drop(config);
+
+        if registration_lost {
+            self.restamp_placeholders().await;
+        }
+
+        // Read the journal position before the watcher starts, so each change is seen by
+        // either the journal replay or the watcher
+        let journal = self.open_usn_journal().await;
+        self.start_fs_watcher().await?;
+        if let Some((journal, live_from)) = journal {
+            if let Err(e) = self.replay_usn_journal(journal, live_from).await {
+                tracing::warn!(target: "drive::mounts", id = %self.id, error = ?e, "Failed to replay change journal");
+            }
+        }
+        self.task_queue.resume().await;
+        Ok(())
+    }
+
+    pub(crate) fn connect_sync_root(
+        &self,
+        sync_path: &Path,
+    ) -> Result<Connection<CallbackHandler>> {
+        tracing::info!(target: "drive::mounts",sync_path = %sync_path.display(), id = %self.id, "Connecting to sync root");
+        Session::new()
             .connect(
-                &config.sync_path,
+                sync_path,
                 CallbackHandler::new(
                     self.command_tx.clone(),
                     self.id.clone(),
                     self.inventory.clone(),
                 ),
             )
-            .context("failed to connect to sync root")?;
+            .context("failed to connect to sync root")
+    }
 
-        self.connection = Some(connection);
-        self.start_fs_watcher().await?;
+    /// Re-register the sync root if its display name, icon, provider, policies or custom
+    /// states differ from the drive config, so Explorer's navigation pane shows the
+    /// drive's current branding and the custom state columns the current language.
+    fn refresh_sync_root_branding(&self, config: &DriveConfig) -> Result<()> {
+        let Some(sync_root_id) = config.sync_root_id.as_ref() else {
+            return Ok(());
+        };
+        let desired = build_sync_root_info(config)?;
+        let current = sync_root_id
+            .info()
+            .context("failed to get sync root info")?;
+        if current.display_name() == desired.display_name()
+            && current.icon() == desired.icon()
+            && current.provider_id() == desired.provider_id()
+            && current.hydration_type() == desired.hydration_type()
+            && current.hydration_policy() == desired.hydration_policy()
+            && current.population_type() == desired.population_type()
+            && current.custom_states() == desired.custom_states()
+        {
+            return Ok(());
+        }
+
+        tracing::info!(target: "drive::mounts", id = %self.id, "Updating sync root branding");
+        sync_root_id
+            .register(desired)
+            .context("failed to update sync root registration")?;
+        Ok(())
+    }
+
+    /// Pin or unpin the sync root in Explorer's navigation pane and move it to its
+    /// position among the drives, as configured
+    fn refresh_navigation_pane(&self, config: &DriveConfig) -> Result<()> {
+        let Some(sync_root_id) = config.sync_root_id.as_ref() else {
+            return Ok(());
+        };
+        if navigation_pane::apply(sync_root_id, config.navigation_pane)? {
+            tracing::info!(target: "drive::mounts", id = %self.id, pinned = config.navigation_pane.pinned, order = config.navigation_pane.order, "Updated navigation pane entry");
+        }
         Ok(())
     }
 
+    /// Apply a new drive name or icon to the sync root registration
+    pub async fn update_sync_root_branding(&self) -> Result<()> {
+        let config = self.config.read().await;
+        self.refresh_sync_root_branding(&config)?;
+        // Registering the sync root again resets its navigation pane entry
+        self.refresh_navigation_pane(&config)?;
+        _ = notify_shell_change(&config.sync_path, SHCNE_UPDATEDIR);
+        Ok(())
+    }
+
+    /// Apply a changed navigation pane setting of the drive
+    pub async fn update_navigation_pane(&self) -> Result<()> {
+        let config = self.config.read().await;
+        self.refresh_navigation_pane(&config)
+    }
+
     pub async fn start_fs_watcher(&self) -> Result<()> {
         let command_tx = self.command_tx.clone();
         let mut debouncer = new_debouncer(
@@ -554,12 +742,13 @@ impl Mount {
                     path,
                     ticket,
                     range,
+                    file_size,
                     response,
                 } => {
                     let s_clone = s.clone();
                     let mount_id_clone = mount_id.clone();
                     spawn(async move {
-                        let result = s_clone.fetch_data(path, ticket, range).await;
+                        let result = s_clone.fetch_data(path, ticket, range, file_size).await;
                         if let Err(e) = result {
                             tracing::error!(target: "drive::mounts", id = %mount_id_clone, error = ?e, "Failed to fetch data");
                             let _ = response.send(Err(e));
@@ -569,6 +758,10 @@ impl Mount {
                         let _ = response.send(result);
                     });
                 }
+                MountCommand::CancelFetchData { path, range } => {
+                    let cancelled = s.fetch_cancellations.cancel(&path, &range);
+                    tracing::debug!(target: "drive::mounts", id = %mount_id, path = %path.display(), range = ?range, cancelled, "Cancelled fetch");
+                }
                 MountCommand::ProcessFsEvents { events } => {
                     let s_clone = s.clone();
                     //let mount_id_clone = mount_id.clone();
@@ -589,6 +782,15 @@ impl Mount {
                         }
                     });
                 }
+                MountCommand::Recover { recovery, path } => {
+                    let s_clone = s.clone();
+                    let mount_id_clone = mount_id.clone();
+                    spawn(async move {
+                        if let Err(e) = s_clone.recover(recovery, path).await {
+                            tracing::error!(target: "drive::mounts", id = %mount_id_clone, error = ?e, "Failed to recover from cloud filter failure");
+                        }
+                    });
+                }
             }
         }
 
@@ -597,17 +799,20 @@ impl Mount {
 
     pub async fn delete(&self) -> Result<()> {
         self.shutdown().await;
-        if let Some(ref connection) = self.connection {
+        if let Some(ref connection) = *self.connection.lock().unwrap_or_else(|e| e.into_inner()) {
             connection.disconnect().context("faield to disconnect sync root")?;
         }
-        self.task_queue.shutdown().await;
         if let Some(sync_root_id) = self.config.read().await.sync_root_id.as_ref() {
             if let Err(e) = sync_root_id.unregister() {
                 tracing::warn!(target: "drive::mounts", id=%self.id, error=%e, "Failed to unregister sync root");
                 return Err(anyhow::anyhow!("Failed to unregister sync root: {}", e));
             }
         }
-        if let Err(e) = self.inventory.nuke_drive(&self.id) {
+        if let Err(e) = self
+            .inventory
+            .with_source(ChangeSource::User)
+            .nuke_drive(&self.id)
+        {
             tracing::error!(target: "drive::mounts", id=%self.id, error=%e, "Failed to nuke drive");
         }
 
@@ -626,6 +831,7 @@ impl Mount {
         if let Some(fs_watcher) = self.fs_watcher.lock().await.take() {
             tracing::debug!(target: "drive::mounts", id=%self.id, "Stopping FS watcher");
             drop(fs_watcher);
+            self.save_usn_checkpoint().await;
         }
 
         // Close the command channel to signal the processor task to stop
@@ -642,7 +848,15 @@ impl Mount {
             tracing::debug!(target: "drive::mounts", id=%self.id, "Stopping props refresh task");
             handle.abort();
         }
-        // self.queue.shutdown().await;
+
+        // Stop the prefetcher
+        if let Some(handle) = self.prefetch_handle.lock().await.take() {
+            tracing::debug!(target: "drive::mounts", id=%self.id, "Stopping prefetcher");
+            handle.abort();
+        }
+
+        // Saves progress of running tasks; queued ones are already in the inventory
+        self.task_queue.shutdown().await;
     }
 
     /// Spawn the periodic props refresh task
@@ -678,6 +892,10 @@ impl Mount {
                 if let Err(e) = mount.refresh_drive_props().await {
                     tracing::error!(target: "drive::mounts", id=%mount_id, error=%e, "Failed to refresh drive props");
                 }
+
+                if let Err(e) = mount.repair_sync_root().await {
+                    tracing::error!(target: "drive::mounts", id=%mount_id, error=?e, "Failed to repair sync root registration");
+                }
             }
         });
 
@@ -694,6 +912,7 @@ impl Mount {
         match self.cr_client.get_user_capacity().await {
             Ok(capacity) => {
                 tracing::debug!(target: "drive::mounts", id=%self.id, used=%capacity.used, total=%capacity.total, "Fetched user capacity");
+                self.check_quota(&capacity);
                 update = update.with_capacity(capacity);
             }
             Err(e) => {
@@ -729,6 +948,27 @@ impl Mount {
             .get_drive_props(&self.id)
             .context("Failed to get drive props")
     }
+
+    /// Warn once when the used capacity crosses [`QUOTA_WARNING_RATIO`], comparing with
+    /// the capacity saved by the previous refresh
+    fn check_quota(&self, capacity: &Capacity) {
+        let is_low = |capacity: &Capacity| {
+            capacity.total > 0
+                && capacity.used as f64 >= capacity.total as f64 * QUOTA_WARNING_RATIO
+        };
+        if !is_low(capacity) {
+            return;
+        }
+        let was_low = matches!(
+            self.get_drive_props(),
+            Ok(Some(props)) if props.capacity.as_ref().is_some_and(is_low)
+        );
+        if !was_low {
+            tracing::info!(target: "drive::mounts", id=%self.id, used=%capacity.used, total=%capacity.total, "Drive capacity is running low");
+            self.inventory
+                .notify_quota_warning(capacity.used, capacity.total);
+        }
+    }
 }
 
 fn ensure_sync_path_exists(sync_path: &PathBuf, id: &str) -> Result<()> {
@@ -746,6 +986,66 @@ fn ensure_sync_path_exists(sync_path: &PathBuf, id: &str) -> Result<()> {
     Ok(())
 }
 
+/// Drive config key making Windows ask the provider to validate hydrated data before
+/// handing it to applications, e.g. `"validate_data": true`
+pub(crate) const VALIDATE_DATA_KEY: &str = "validate_data";
+
+/// Share of the capacity in use above which [`Event::QuotaWarning`] is broadcast
+///
+/// [`Event::QuotaWarning`]: crate::events::Event::QuotaWarning
+const QUOTA_WARNING_RATIO: f64 = 0.9;
+
+/// Identifies Cloudreve as the provider of its sync roots, e.g. to group them in Explorer
+const SYNC_ROOT_PROVIDER_ID: GUID = GUID::from_u128(0x5c8e2a7d_3f14_4b69_9d0e_a61b7c42f853);
+
+/// Registration info for the sync root of a drive
+pub(crate) fn build_sync_root_info(config: &DriveConfig) -> Result<SyncRootInfo> {
+    let mut sync_root_info = SyncRootInfo::default();
+    sync_root_info.set_display_name(config.name.clone());
+    sync_root_info.set_provider_id(SYNC_ROOT_PROVIDER_ID);
+    let policy = config.sync_root_policy;
+    sync_root_info.set_hydration_type(policy.hydration.into());
+    sync_root_info.set_population_type(policy.population.into());
+    let validate_data = config
+        .extra
+        .get(VALIDATE_DATA_KEY)
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    let mut hydration_policy = FlagSet::<HydrationPolicy>::default();
+    if validate_data {
+        hydration_policy |= HydrationPolicy::ValidationRequired;
+    }
+    if policy.allow_auto_dehydration {
+        hydration_policy |= HydrationPolicy::AutoDehydrationAllowed;
+    }
+    if !hydration_policy.is_empty() {
+        sync_root_info.set_hydration_policy(hydration_policy);
+    }
+    // The favicon of the instance, or the app icon until it is fetched
+    let icon_path = config.icon_path.clone().or_else(|| {
+        std::env::current_exe()
+            .ok()
+            .map(|exe| exe.to_string_lossy().into_owned())
+    });
+    if let Some(icon_path) = icon_path {
+        sync_root_info.set_icon(format!("{},0", icon_path));
+    }
+    sync_root_info.set_version("1.0.0");
+    sync_root_info
+        .set_recycle_bin_uri("https://cloudreve.org")
+        .context("failed to set recycle bin uri")?;
+    sync_root_info
+        .set_path(Path::new(&config.sync_path))
+        .context("failed to set sync root path")?;
+    sync_root_info.add_custom_state(t!("shared").as_ref(), CUSTOM_STATE_SHARED)?;
+    sync_root_info.add_custom_state(t!("accessible").as_ref(), CUSTOM_STATE_ACCESS)?;
+    sync_root_info.add_custom_state(t!("owner").as_ref(), CUSTOM_STATE_OWNER)?;
+    sync_root_info.add_custom_state(t!("modifiedBy").as_ref(), CUSTOM_STATE_MODIFIED_BY)?;
+    sync_root_info.add_custom_state(t!("conflictState").as_ref(), CUSTOM_STATE_CONFLICT)?;
+    sync_root_info.add_custom_state(t!("syncErrorState").as_ref(), CUSTOM_STATE_SYNC_ERROR)?;
+    Ok(sync_root_info)
+}
+
 fn generate_sync_root_id(
     instance_url: &str,
     _account_name: &str,
@@ -809,18 +1109,99 @@ mod tests {
         assert!(res.is_err());
         std::fs::remove_file(&p).unwrap();
     }
+
+    #[test]
+    fn task_type_limits_skip_unknown_and_zero() {
+        let limits = parse_task_type_limits(&serde_json::json!({
+            "upload": 2,
+            "download": 0,
+            "rename": 8,
+        }));
+        assert_eq!(limits, HashMap::from([(TaskKind::Upload, 2)]));
+    }
 }
 
 fn resolve_task_queue_config(config: &DriveConfig) -> TaskQueueConfig {
-    let concurrency = config
+    let defaults = TaskQueueConfig::default();
+    let concurrency_option = |key: &str| {
+        config
+            .extra
+            .get(key)
+            .and_then(|value| value.as_u64())
+            .map(|value| value as usize)
+            .filter(|value| *value > 0)
+    };
+    let max_concurrent =
+        concurrency_option("task_queue_max_concurrency").unwrap_or(defaults.max_concurrent);
+    let min_concurrent =
+        concurrency_option("task_queue_min_concurrency").unwrap_or(defaults.min_concurrent);
+
+    let type_limits = config
+        .extra
+        .get(TASK_TYPE_LIMITS_KEY)
+        .map(parse_task_type_limits)
+        .unwrap_or(defaults.type_limits);
+
+    let timeouts = config
         .extra
-        .get("task_queue_max_concurrency")
-        .and_then(|value| value.as_u64())
-        .map(|value| value as usize)
-        .filter(|value| *value > 0)
-        .unwrap_or(2);
+        .get("task_timeouts")
+        .map(parse_task_timeouts)
+        .unwrap_or(defaults.timeouts);
+
+    let mut retry_policies = defaults.retry_policies;
+    if let Some(overrides) = config
+        .extra
+        .get("task_retry_policies")
+        .and_then(|value| value.as_object())
+    {
+        for (kind, policy) in retry_policies.iter_mut() {
+            let Some(policy_overrides) = overrides.get(kind.as_str()) else {
+                continue;
+            };
+            match policy.clone().with_overrides(policy_overrides) {
+                Ok(updated) => *policy = updated,
+                Err(e) => {
+                    tracing::warn!(target: "drive::mounts", id = %config.id, kind = kind.as_str(), error = %e, "Ignoring invalid task retry policy");
+                }
+            }
+        }
+    }
 
     TaskQueueConfig {
-        max_concurrent: concurrency,
+        min_concurrent,
+        max_concurrent,
+        type_limits,
+        timeouts,
+        retry_policies,
     }
 }
+
+/// Parse per-task-type timeouts in seconds, e.g. `{"upload": 3600}`
+fn parse_task_timeouts(value: &serde_json::Value) -> HashMap<TaskKind, Duration> {
+    value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(kind, secs)| {
+            let kind = TaskKind::from_str(kind)?;
+            let secs = secs.as_u64().filter(|secs| *secs > 0)?;
+            Some((kind, Duration::from_secs(secs)))
+        })
+        .collect()
+}
+
+/// Drive config key holding per-task-type concurrency limits, e.g. `{"upload": 2}`
+pub(crate) const TASK_TYPE_LIMITS_KEY: &str = "task_type_concurrency";
+
+fn parse_task_type_limits(value: &serde_json::Value) -> HashMap<TaskKind, usize> {
+    value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter_map(|(kind, limit)| {
+            let kind = TaskKind::from_str(kind)?;
+            let limit = limit.as_u64().filter(|limit| *limit > 0)?;
+            Some((kind, limit as usize))
+        })
+        .collect()
+}
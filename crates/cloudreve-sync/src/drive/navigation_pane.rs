@@ -0,0 +1,122 @@
+//! Explorer navigation pane entries of sync roots.
+//!
+//! Registering a sync root makes Windows add a shell namespace extension for it, whose
+//! CLSID is recorded under the `SyncRootManager` key. Whether it is pinned to the
+//! navigation pane and where it is placed there are values of that CLSID. Windows writes
+//! them again whenever the sync root is registered, so they are applied after each
+//! registration.
+
+use crate::cfapi::root::SyncRootId;
+use crate::drive::mounts::NavigationPane;
+use anyhow::{Context, Result};
+use windows::Win32::System::Registry::{
+    HKEY_CURRENT_USER, REG_DWORD, RRF_RT_REG_DWORD, RRF_RT_REG_SZ, RegGetValueW, RegSetKeyValueW,
+};
+use windows::Win32::UI::Shell::{SHCNE_ASSOCCHANGED, SHCNF_IDLIST, SHChangeNotify};
+use windows::core::{HSTRING, PCWSTR, w};
+
+const SYNC_ROOT_MANAGER_KEY: &str =
+    r"Software\Microsoft\Windows\CurrentVersion\Explorer\SyncRootManager";
+const PINNED_VALUE: PCWSTR = w!("System.IsPinnedToNameSpaceTree");
+const SORT_ORDER_VALUE: PCWSTR = w!("SortOrderIndex");
+/// Sort order index of the first drive, the one Windows gives OneDrive, so drives are
+/// listed next to the other cloud providers
+const SORT_ORDER_BASE: u32 = 0x42;
+
+/// Pin or unpin the sync root in the navigation pane and move it to its position. Returns
+/// whether anything changed.
+pub fn apply(sync_root_id: &SyncRootId, navigation_pane: NavigationPane) -> Result<bool> {
+    let clsid = namespace_clsid(sync_root_id)?;
+    let key = HSTRING::from(format!(r"Software\Classes\CLSID\{}", clsid));
+
+    let pinned = navigation_pane.pinned as u32;
+    let sort_order = SORT_ORDER_BASE.saturating_add(navigation_pane.order);
+    if get_dword(&key, PINNED_VALUE) == Some(pinned)
+        && get_dword(&key, SORT_ORDER_VALUE) == Some(sort_order)
+    {
+        return Ok(false);
+    }
+
+    set_dword(&key, PINNED_VALUE, pinned).context("failed to set pinned state")?;
+    set_dword(&key, SORT_ORDER_VALUE, sort_order).context("failed to set sort order")?;
+    // Explorer reads namespace extensions again on association changes
+    unsafe { SHChangeNotify(SHCNE_ASSOCCHANGED, SHCNF_IDLIST, None, None) };
+    Ok(true)
+}
+
+/// CLSID of the namespace extension Windows created for the sync root
+fn namespace_clsid(sync_root_id: &SyncRootId) -> Result<String> {
+    let key = HSTRING::from(format!(
+        r"{}\{}",
+        SYNC_ROOT_MANAGER_KEY,
+        sync_root_id.to_os_string().to_string_lossy()
+    ));
+    let value = w!("NamespaceCLSID");
+
+    let mut size = 0u32;
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            &key,
+            value,
+            RRF_RT_REG_SZ,
+            None,
+            None,
+            Some(&mut size),
+        )
+    }
+    .ok()
+    .context("sync root has no namespace extension")?;
+
+    let mut buffer = vec![0u16; (size as usize).div_ceil(2)];
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            &key,
+            value,
+            RRF_RT_REG_SZ,
+            None,
+            Some(buffer.as_mut_ptr() as *mut _),
+            Some(&mut size),
+        )
+    }
+    .ok()
+    .context("failed to read namespace extension CLSID")?;
+
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Ok(String::from_utf16_lossy(&buffer[..len]))
+}
+
+fn get_dword(key: &HSTRING, name: PCWSTR) -> Option<u32> {
+    let mut data = 0u32;
+    let mut size = size_of::<u32>() as u32;
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            key,
+            name,
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut data as *mut u32 as *mut _),
+            Some(&mut size),
+        )
+    }
+    .ok()
+    .ok()
+    .map(|_| data)
+}
+
+fn set_dword(key: &HSTRING, name: PCWSTR, data: u32) -> Result<()> {
+    unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            key,
+            name,
+            REG_DWORD.0,
+            Some(&data as *const u32 as *const _),
+            size_of::<u32>() as u32,
+        )
+    }
+    .ok()?;
+    Ok(())
+}
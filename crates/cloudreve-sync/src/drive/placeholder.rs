@@ -1,34 +1,38 @@
 use crate::{
     cfapi::{
         metadata::Metadata,
-        placeholder::{ConvertOptions, LocalFileInfo, OpenOptions, UpdateOptions},
+        placeholder::{ConvertOptions, LocalFileInfo, OpenOptions, Placeholder, UpdateOptions},
         placeholder_file::PlaceholderFile,
     },
     drive::utils::notify_shell_change,
-    inventory::{FileMetadata, InventoryDb, MetadataEntry},
+    inventory::{CloudItemProps, FileMetadata, InventoryDb, MetadataEntry, MetadataWriteBatch},
 };
 use anyhow::{Context, Result};
 use chrono::DateTime;
 use cloudreve_api::models::explorer::{FileResponse, file_type};
 use nt_time::FileTime;
 use std::{
+    collections::BTreeSet,
     ffi::OsString,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
+    time::UNIX_EPOCH,
 };
 use uuid::Uuid;
 use widestring::U16CString;
 use windows::{
     Win32::{
         Foundation::E_FAIL,
-        Storage::EnhancedStorage::PKEY_LastSyncError,
-        System::Variant::VT_UI4,
+        Storage::EnhancedStorage::{
+            PKEY_LastSyncError, PKEY_StorageProviderTransferProgress, PKEY_SyncTransferStatus,
+        },
+        System::{Com::StructuredStorage::InitPropVariantFromUInt64Vector, Variant::VT_UI4},
         UI::Shell::{
             IShellItem2,
             PropertiesSystem::{
-                GPS_EXTRINSICPROPERTIESONLY, GPS_READWRITE, IPropertyStore,
+                GPS_EXTRINSICPROPERTIESONLY, GPS_READWRITE, IPropertyStore, STS_TRANSFERRING,
             },
-            SHCNE_CREATE, SHCNE_DELETE, SHCNE_MKDIR,
+            SHCNE_CREATE, SHCNE_DELETE, SHCNE_MKDIR, SHCNE_UPDATEDIR,
             SHCreateItemFromParsingName,
         },
     },
@@ -46,6 +50,16 @@ pub struct CrPlaceholder {
     options: u32,
 }
 
+/// Outcome of [`CrPlaceholder::mark_in_sync_batch`]
+#[derive(Debug, Default)]
+pub struct InSyncBatchResult {
+    /// Placeholders converted or updated
+    pub updated: usize,
+    /// Placeholders already in sync with matching metadata
+    pub unchanged: usize,
+    pub failed: Vec<(PathBuf, anyhow::Error)>,
+}
+
 enum CrPlaceholderOptions {
     InvalidateAllRange = 1 << 0,
     MarkNoChildren = 1 << 1,
@@ -117,6 +131,24 @@ impl CrPlaceholder {
 
     // Commit changes to file system and inventory
     pub fn commit(&mut self, inventory: Arc<InventoryDb>) -> Result<()> {
+        self.commit_with(|entry| {
+            inventory
+                .upsert(&entry)
+                .map(|_| ())
+                .context("failed to upsert inventory")
+        })
+    }
+
+    /// Same as [`CrPlaceholder::commit`], but queues the inventory write on `batch`
+    /// instead of writing it immediately.
+    pub fn commit_batched(&mut self, batch: &MetadataWriteBatch) -> Result<()> {
+        self.commit_with(|entry| batch.push(entry).context("failed to queue inventory write"))
+    }
+
+    fn commit_with<F>(&mut self, write_inventory: F) -> Result<()>
+    where
+        F: FnOnce(MetadataEntry) -> Result<()>,
+    {
         if self.file_meta.is_none() {
             return Err(anyhow::anyhow!("File metadata is not set"));
         }
@@ -149,14 +181,19 @@ impl CrPlaceholder {
                     .context("failed to convert to placeholder")?;
             }
 
-            // Update file metadata
-            let mut upload_options = UpdateOptions::default().mark_in_sync().metadata(
-                Metadata::default()
-                    .size(file_meta.size as u64)
-                    .changed(FileTime::from_unix_time(file_meta.updated_at)?)
-                    .written(FileTime::from_unix_time(file_meta.updated_at)?)
-                    .created(FileTime::from_unix_time(file_meta.created_at)?),
-            );
+            // Update file metadata. The identity is set again for placeholders converted
+            // before their upload, see `drive::import`.
+            let identity = OsString::from(file_meta.etag.clone()).into_encoded_bytes();
+            let mut upload_options = UpdateOptions::default()
+                .mark_in_sync()
+                .blob(&identity)
+                .metadata(
+                    Metadata::default()
+                        .size(file_meta.size as u64)
+                        .changed(FileTime::from_unix_time(file_meta.updated_at)?)
+                        .written(FileTime::from_unix_time(file_meta.updated_at)?)
+                        .created(FileTime::from_unix_time(file_meta.created_at)?),
+                );
 
             let dehydrate_requested =
                 self.options & CrPlaceholderOptions::InvalidateAllRange as u32 != 0;
@@ -223,9 +260,7 @@ impl CrPlaceholder {
         }
 
         // Upser inventory
-        inventory
-            .upsert(&MetadataEntry::from(file_meta))
-            .context("failed to upsert inventory")?;
+        write_inventory(MetadataEntry::from(file_meta))?;
 
         // Notify shell change
         notify_shell_change(
@@ -241,33 +276,78 @@ impl CrPlaceholder {
         Ok(())
     }
 
+    /// Mark many files in sync and update their metadata in one pass, queueing the
+    /// inventory writes on `batch`.
+    ///
+    /// Unlike committing each entry, files are inspected from directory data and only
+    /// opened when that matches, placeholders already in sync with matching size,
+    /// modification time and identity are left untouched, and the rest are converted
+    /// and updated through a single handle. Explorer is notified once per parent folder.
+    /// Entries missing locally are created as placeholders.
+    pub fn mark_in_sync_batch(
+        sync_root: &Path,
+        entries: Vec<FileMetadata>,
+        batch: &MetadataWriteBatch,
+    ) -> InSyncBatchResult {
+        let mut result = InSyncBatchResult::default();
+        let mut changed_folders = BTreeSet::new();
+        for file_meta in entries {
+            let local_path = PathBuf::from(&file_meta.local_path);
+            let local_file_info =
+                LocalFileInfo::from_path(&local_path).unwrap_or(LocalFileInfo::missing());
+            if !local_file_info.exists {
+                let commit = CrPlaceholder::new(
+                    local_path.clone(),
+                    sync_root.to_path_buf(),
+                    file_meta.drive_id,
+                )
+                .with_file_meta(file_meta)
+                .commit_batched(batch);
+                match commit {
+                    Ok(()) => result.updated += 1,
+                    Err(e) => result.failed.push((local_path, e)),
+                }
+                continue;
+            }
+
+            let outcome = if is_up_to_date(&local_path, &local_file_info, &file_meta) {
+                Ok(false)
+            } else {
+                update_in_sync(&local_path, &local_file_info, &file_meta).map(|()| true)
+            };
+            let outcome = outcome.and_then(|updated| {
+                batch
+                    .push(MetadataEntry::from(&file_meta))
+                    .context("failed to queue inventory write")
+                    .map(|()| updated)
+            });
+            match outcome {
+                Ok(true) => {
+                    result.updated += 1;
+                    if let Some(parent) = local_path.parent() {
+                        changed_folders.insert(parent.to_path_buf());
+                    }
+                }
+                Ok(false) => result.unchanged += 1,
+                Err(e) => result.failed.push((local_path, e)),
+            }
+        }
+
+        for folder in changed_folders {
+            _ = notify_shell_change(&folder, SHCNE_UPDATEDIR);
+        }
+        tracing::debug!(
+            target: "drive::placeholder",
+            updated = result.updated,
+            unchanged = result.unchanged,
+            failed = result.failed.len(),
+            "Applied batched in-sync updates"
+        );
+        result
+    }
+
     pub fn with_remote_file(mut self, file_info: &FileResponse) -> Self {
-        // Parse RFC3339 time strings from Golang
-        let created_at = DateTime::parse_from_rfc3339(&file_info.created_at)
-            .ok()
-            .map(|dt| dt.timestamp())
-            .unwrap_or_default();
-
-        let updated_at = DateTime::parse_from_rfc3339(&file_info.updated_at)
-            .ok()
-            .map(|dt| dt.timestamp())
-            .unwrap_or_default();
-
-        self.file_meta = Some(FileMetadata {
-            drive_id: self.drive_id,
-            local_path: self.local_path.to_string_lossy().to_string(),
-            is_folder: file_info.file_type == file_type::FOLDER,
-            created_at,
-            updated_at,
-            size: file_info.size,
-            etag: file_info.primary_entity.clone().unwrap_or_default(),
-            id: 0,
-            metadata: file_info.metadata.clone().unwrap_or_default(),
-            props: None,
-            permissions: file_info.permission.clone().unwrap_or_default(),
-            shared: file_info.shared.unwrap_or(false),
-            conflict_state: None,
-        });
+        self.file_meta = Some(remote_file_meta(&self.local_path, self.drive_id, file_info));
         self
     }
 
@@ -298,6 +378,55 @@ impl CrPlaceholder {
             // Skip non-placeholder file
             return Ok(());
         }
+
+        // Set error state: VT_UI4 with E_FAIL value
+        let prop_var = if set_error {
+            uint32_prop(E_FAIL.0 as u32)
+        } else {
+            PROPVARIANT::default()
+        };
+        self.write_shell_properties(|property_store| unsafe {
+            property_store.SetValue(&PKEY_LastSyncError, &prop_var)
+        })
+        .context("failed to set PKEY_LastSyncError value")?;
+
+        tracing::debug!(
+            target: "drive::placeholder",
+            path = %self.local_path.display(),
+            set_error,
+            "Updated sync error state"
+        );
+
+        Ok(())
+    }
+
+    /// Show the file as transferring in Explorer, with `completed` of `total` bytes
+    /// done, or clear the indication with `None` once the transfer ends.
+    pub fn update_transfer_progress(&self, progress: Option<(u64, u64)>) -> Result<()> {
+        if !self.local_file_info.is_placeholder() {
+            return Ok(());
+        }
+
+        let (status, progress_var) = match progress {
+            Some((completed, total)) => (
+                uint32_prop(STS_TRANSFERRING.0 as u32),
+                unsafe { InitPropVariantFromUInt64Vector(Some(&[completed, total.max(1)])) }
+                    .context("failed to build transfer progress value")?,
+            ),
+            None => (PROPVARIANT::default(), PROPVARIANT::default()),
+        };
+        self.write_shell_properties(|property_store| unsafe {
+            property_store.SetValue(&PKEY_SyncTransferStatus, &status)?;
+            property_store.SetValue(&PKEY_StorageProviderTransferProgress, &progress_var)
+        })
+        .context("failed to set transfer progress")
+    }
+
+    /// Write extrinsic shell properties of the file through its property store
+    fn write_shell_properties(
+        &self,
+        write: impl FnOnce(&IPropertyStore) -> windows::core::Result<()>,
+    ) -> Result<()> {
         let path_wide = U16CString::from_os_str(&self.local_path)
             .context("failed to convert path to wide string")?;
 
@@ -312,36 +441,163 @@ impl CrPlaceholder {
                 .GetPropertyStore(flags)
                 .context("failed to get property store")?;
 
-            // Prepare the PROPVARIANT to set or clear the error state
-            let prop_var = if set_error {
-                // Set error state: VT_UI4 with E_FAIL value
-                let mut pv = PROPVARIANT::default().as_raw().clone();
-                pv.Anonymous.Anonymous.vt = VT_UI4.0;
-                pv.Anonymous.Anonymous.Anonymous.ulVal = E_FAIL.0 as u32;
-                PROPVARIANT::from_raw(pv)
-            } else {
-                let pv = PROPVARIANT::default().as_raw().clone();
-                PROPVARIANT::from_raw(pv)
-            };
-
-            // Set the PKEY_LastSyncError property
-            property_store
-                .SetValue(&PKEY_LastSyncError, &prop_var)
-                .context("failed to set PKEY_LastSyncError value")?;
+            write(&property_store)?;
 
             // Commit the changes
             property_store
                 .Commit()
                 .context("failed to commit property store changes")?;
         }
+        Ok(())
+    }
+}
 
-        tracing::debug!(
-            target: "drive::placeholder",
-            path = %self.local_path.display(),
-            set_error,
-            "Updated sync error state"
-        );
+/// A `VT_UI4` property value
+fn uint32_prop(value: u32) -> PROPVARIANT {
+    let mut pv = PROPVARIANT::default().as_raw().clone();
+    pv.Anonymous.Anonymous.vt = VT_UI4.0;
+    pv.Anonymous.Anonymous.Anonymous.ulVal = value;
+    unsafe { PROPVARIANT::from_raw(pv) }
+}
 
-        Ok(())
+/// Inventory metadata for the remote file `file_info` placed at `local_path`
+pub fn remote_file_meta(
+    local_path: &Path,
+    drive_id: Uuid,
+    file_info: &FileResponse,
+) -> FileMetadata {
+    // Parse RFC3339 time strings from Golang
+    let created_at = DateTime::parse_from_rfc3339(&file_info.created_at)
+        .ok()
+        .map(|dt| dt.timestamp())
+        .unwrap_or_default();
+
+    let updated_at = DateTime::parse_from_rfc3339(&file_info.updated_at)
+        .ok()
+        .map(|dt| dt.timestamp())
+        .unwrap_or_default();
+
+    FileMetadata {
+        drive_id,
+        local_path: local_path.to_string_lossy().to_string(),
+        is_folder: file_info.file_type == file_type::FOLDER,
+        created_at,
+        updated_at,
+        size: file_info.size,
+        etag: file_info.primary_entity.clone().unwrap_or_default(),
+        id: 0,
+        metadata: file_info.metadata.clone().unwrap_or_default(),
+        props: cloud_props(file_info),
+        permissions: file_info.permission.clone().unwrap_or_default(),
+        shared: file_info.shared.unwrap_or(false),
+        conflict_state: None,
+    }
+}
+
+/// Cloud properties of `file_info` shown as Explorer columns. Listings may leave out
+/// the uploader, so nothing is returned when no property is known to keep the recorded
+/// ones.
+fn cloud_props(file_info: &FileResponse) -> Option<serde_json::Value> {
+    let modified_by = file_info
+        .extended_info
+        .as_ref()
+        .and_then(|info| info.entities.as_ref())
+        .and_then(|entities| {
+            entities
+                .iter()
+                .find(|entity| file_info.primary_entity.as_ref() == Some(&entity.id))
+        })
+        .and_then(|entity| entity.created_by.as_ref())
+        .map(|user| user.nickname.clone());
+    let props = CloudItemProps {
+        owned: file_info.owned,
+        modified_by,
+    };
+    if props == CloudItemProps::default() {
+        return None;
+    }
+    serde_json::to_value(props).ok()
+}
+
+/// Whether the placeholder is in sync and already carries the metadata and identity in
+/// `file_meta`. The identity is only read once the directory data matches.
+fn is_up_to_date(
+    local_path: &Path,
+    local_file_info: &LocalFileInfo,
+    file_meta: &FileMetadata,
+) -> bool {
+    if !local_file_info.is_placeholder() || !local_file_info.in_sync() {
+        return false;
+    }
+    if local_file_info.is_directory() != file_meta.is_folder {
+        return false;
+    }
+    if !file_meta.is_folder {
+        let modified = local_file_info
+            .last_modified
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|modified| modified.as_secs() as i64);
+        if local_file_info.file_size != Some(file_meta.size.max(0) as u64)
+            || modified != Some(file_meta.updated_at)
+        {
+            return false;
+        }
+    }
+
+    let identity = OsString::from(file_meta.etag.clone()).into_encoded_bytes();
+    open_placeholder(local_path, local_file_info)
+        .ok()
+        .and_then(|handle| handle.info().ok().flatten())
+        .is_some_and(|info| info.blob() == identity.as_slice())
+}
+
+/// Open a file or directory for reading and updating its placeholder state
+fn open_placeholder(local_path: &Path, local_file_info: &LocalFileInfo) -> Result<Placeholder> {
+    match local_file_info.is_directory {
+        true => OpenOptions::new()
+            .open(local_path)
+            .context("failed to open local directory"),
+        false => OpenOptions::new()
+            .open_win32(local_path)
+            .context("failed to open local file"),
     }
 }
+
+/// Convert `local_path` to a placeholder if needed, then mark it in sync with the
+/// metadata and identity in `file_meta`, using one handle for both
+fn update_in_sync(
+    local_path: &Path,
+    local_file_info: &LocalFileInfo,
+    file_meta: &FileMetadata,
+) -> Result<()> {
+    let mut local_handle = open_placeholder(local_path, local_file_info)?;
+    let identity = OsString::from(file_meta.etag.clone()).into_encoded_bytes();
+    if !local_file_info.is_placeholder() {
+        local_handle
+            .convert_to_placeholder(
+                ConvertOptions::default()
+                    .mark_in_sync()
+                    .blob(identity.clone()),
+                None,
+            )
+            .context("failed to convert to placeholder")?;
+    }
+    // The identity is set again, as the remote file may have changed since the
+    // placeholder was created
+    local_handle
+        .update(
+            UpdateOptions::default()
+                .mark_in_sync()
+                .blob(&identity)
+                .metadata(
+                    Metadata::default()
+                        .size(file_meta.size as u64)
+                        .changed(FileTime::from_unix_time(file_meta.updated_at)?)
+                        .written(FileTime::from_unix_time(file_meta.updated_at)?)
+                        .created(FileTime::from_unix_time(file_meta.created_at)?),
+                ),
+            None,
+        )
+        .context("failed to update placeholder")?;
+    Ok(())
+}
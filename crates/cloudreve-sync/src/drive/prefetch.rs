@@ -0,0 +1,246 @@
+//! Background hydration of files the user is likely to open next.
+//!
+//! Two hints feed the prefetcher: a file opened from the cloud makes the next few files
+//! of its folder candidates, and files the user changed on another device are candidates
+//! once their placeholder is updated. Candidates are hydrated one at a time, and only
+//! while the files hydrated this way since the drive started fit in the disk budget.
+
+use crate::{
+    cfapi::placeholder::{LocalFileInfo, OpenOptions, PinState},
+    config::ConfigManager,
+    drive::{mounts::Mount, utils::notify_shell_change},
+};
+use anyhow::{Context, Result};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::mpsc;
+use windows::Win32::UI::Shell::SHCNE_ATTRIBUTES;
+
+/// How many files following an opened file in its folder are prefetched
+const SIBLING_COUNT: usize = 5;
+
+/// Remote changes are applied to placeholders by a sync triggered by the same event, so
+/// wait for it before looking at the file
+const REMOTE_CHANGE_DELAY: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub enum PrefetchHint {
+    /// A file was opened, prefetch the files after it in its folder
+    Opened(PathBuf),
+    /// A file was changed remotely, prefetch it
+    Changed(PathBuf),
+}
+
+pub struct Prefetcher {
+    hint_tx: mpsc::UnboundedSender<PrefetchHint>,
+    hint_rx: Mutex<Option<mpsc::UnboundedReceiver<PrefetchHint>>>,
+    state: Mutex<PrefetchState>,
+}
+
+#[derive(Default)]
+struct PrefetchState {
+    /// File being hydrated by the prefetcher, its fetch is not treated as a user access
+    in_flight: Option<PathBuf>,
+    /// Files hydrated by the prefetcher and their size, counted against the budget until
+    /// they are dehydrated
+    prefetched: HashMap<PathBuf, u64>,
+}
+
+impl Prefetcher {
+    pub fn new() -> Self {
+        let (hint_tx, hint_rx) = mpsc::unbounded_channel();
+        Self {
+            hint_tx,
+            hint_rx: Mutex::new(Some(hint_rx)),
+            state: Mutex::new(PrefetchState::default()),
+        }
+    }
+
+    /// Send a hint to the prefetcher, ignored when prefetching is disabled
+    pub fn hint(&self, hint: PrefetchHint) {
+        if !prefetch_enabled() {
+            return;
+        }
+        match hint {
+            PrefetchHint::Changed(path) => {
+                let hint_tx = self.hint_tx.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(REMOTE_CHANGE_DELAY).await;
+                    let _ = hint_tx.send(PrefetchHint::Changed(path));
+                });
+            }
+            hint => {
+                let _ = self.hint_tx.send(hint);
+            }
+        }
+    }
+
+    /// Whether `path` is being hydrated by the prefetcher rather than by the user
+    pub fn is_prefetching(&self, path: &Path) -> bool {
+        self.lock().in_flight.as_deref() == Some(path)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PrefetchState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Bytes of prefetched files that are still hydrated
+    fn used_bytes(&self) -> u64 {
+        let mut state = self.lock();
+        state.prefetched.retain(|path, _| {
+            LocalFileInfo::from_path(path)
+                .map(|info| info.exists && !info.partial_on_disk())
+                .unwrap_or(false)
+        });
+        state.prefetched.values().sum()
+    }
+}
+
+impl Default for Prefetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mount {
+    /// Spawn the task hydrating files hinted to the prefetcher
+    pub async fn spawn_prefetcher(self: &Arc<Self>) {
+        let Some(mut hint_rx) = self
+            .prefetcher
+            .hint_rx
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+        else {
+            return;
+        };
+
+        let mount = self.clone();
+        let handle = tokio::spawn(async move {
+            while let Some(hint) = hint_rx.recv().await {
+                let candidates = match hint {
+                    PrefetchHint::Opened(path) => {
+                        tokio::task::spawn_blocking(move || next_siblings(&path, SIBLING_COUNT))
+                            .await
+                            .unwrap_or_default()
+                    }
+                    PrefetchHint::Changed(path) => vec![path],
+                };
+                for path in candidates {
+                    if let Err(e) = mount.prefetch(&path).await {
+                        tracing::debug!(target: "drive::prefetch", id = %mount.id, path = %path.display(), error = ?e, "Failed to prefetch file");
+                    }
+                }
+            }
+        });
+        *self.prefetch_handle.lock().await = Some(handle);
+    }
+
+    /// Hydrate `path` if it is still cloud-only and fits in the budget
+    async fn prefetch(&self, path: &Path) -> Result<()> {
+        if !prefetch_enabled() {
+            return Ok(());
+        }
+        let info = LocalFileInfo::from_path(path).context("failed to get local file info")?;
+        if !info.exists
+            || !info.is_placeholder()
+            || info.is_directory()
+            || !info.partial_on_disk()
+            || info.pinned() == PinState::Unpinned
+        {
+            return Ok(());
+        }
+
+        let size = fs::metadata(path).context("failed to get file size")?.len();
+        let budget = ConfigManager::try_get()
+            .map(|config| config.prefetch_budget_bytes())
+            .unwrap_or(0);
+        let used = self.prefetcher.used_bytes();
+        if used + size > budget {
+            tracing::debug!(target: "drive::prefetch", id = %self.id, path = %path.display(), size, used, budget, "Prefetch budget exhausted, skipping file");
+            return Ok(());
+        }
+
+        tracing::debug!(target: "drive::prefetch", id = %self.id, path = %path.display(), size, "Prefetching file");
+        self.prefetcher.lock().in_flight = Some(path.to_path_buf());
+        let hydrate_path = path.to_path_buf();
+        let result = tokio::task::spawn_blocking(move || {
+            OpenOptions::new()
+                .open_win32(&hydrate_path)
+                .and_then(|mut placeholder| placeholder.hydrate(0..))
+        })
+        .await;
+        self.prefetcher.lock().in_flight = None;
+        result
+            .context("prefetch task panicked")?
+            .context("failed to hydrate placeholder")?;
+
+        self.prefetcher
+            .lock()
+            .prefetched
+            .insert(path.to_path_buf(), size);
+        _ = notify_shell_change(&path.to_path_buf(), SHCNE_ATTRIBUTES);
+        Ok(())
+    }
+}
+
+fn prefetch_enabled() -> bool {
+    ConfigManager::try_get().is_some_and(|config| config.prefetch_enabled())
+}
+
+/// The files following `opened` in its folder, by case-insensitive name
+fn next_siblings(opened: &Path, count: usize) -> Vec<PathBuf> {
+    let (Some(parent), Some(opened_name)) = (opened.parent(), opened.file_name()) else {
+        return Vec::new();
+    };
+    let Ok(read_dir) = fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    let names: Vec<String> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    pick_following(&opened_name.to_string_lossy(), names, count)
+        .into_iter()
+        .map(|name| parent.join(name))
+        .collect()
+}
+
+fn pick_following(opened: &str, mut names: Vec<String>, count: usize) -> Vec<String> {
+    let opened_key = opened.to_lowercase();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+        .into_iter()
+        .filter(|name| name.to_lowercase() > opened_key)
+        .take(count)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_files_following_the_opened_one() {
+        let names = [
+            "IMG_003.jpg",
+            "img_001.jpg",
+            "IMG_002.jpg",
+            "IMG_004.jpg",
+            "a.txt",
+        ]
+        .map(String::from)
+        .to_vec();
+        assert_eq!(
+            pick_following("img_002.jpg", names.clone(), 2),
+            vec!["IMG_003.jpg", "IMG_004.jpg"]
+        );
+        assert!(pick_following("IMG_004.jpg", names, 2).is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! Telemetry for failed cloud filter callbacks, and automatic recovery from the failure
+//! classes that clear up once the sync root or the placeholder is fixed.
+//!
+//! Failures are recorded in the inventory with the HRESULT Windows reported, so they can
+//! be reviewed later. A lost connection to the sync root triggers a reconnect, at most
+//! once per cooldown, and a damaged placeholder is synced with the remote file again.
+
+use crate::{
+    cfapi::error::CloudErrorKind,
+    drive::{commands::MountCommand, mounts::Mount, sync::SyncMode},
+    inventory::{CfapiFailureClass, CfapiRecovery, InventoryDb, TaskFailureClass},
+    tasks::retry,
+};
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use std::path::PathBuf;
+use windows::Win32::Foundation::{
+    ERROR_CLOUD_FILE_IN_USE, ERROR_CLOUD_FILE_INSUFFICIENT_RESOURCES,
+    ERROR_CLOUD_FILE_METADATA_CORRUPT, ERROR_CLOUD_FILE_NETWORK_UNAVAILABLE,
+    ERROR_CLOUD_FILE_NOT_IN_SYNC, ERROR_CLOUD_FILE_NOT_UNDER_SYNC_ROOT,
+    ERROR_CLOUD_FILE_PROPERTY_BLOB_CHECKSUM_MISMATCH, ERROR_CLOUD_FILE_PROPERTY_CORRUPT,
+    ERROR_CLOUD_FILE_PROPERTY_LOCK_CONFLICT, ERROR_CLOUD_FILE_PROVIDER_NOT_RUNNING,
+    ERROR_CLOUD_FILE_PROVIDER_TERMINATED, ERROR_CLOUD_FILE_REQUEST_ABORTED,
+    ERROR_CLOUD_FILE_REQUEST_CANCELED, ERROR_CLOUD_FILE_REQUEST_TIMEOUT, NTSTATUS, WIN32_ERROR,
+};
+
+/// Minimum time between two automatic reconnects of a drive's sync root
+const RECONNECT_COOLDOWN_SECS: i64 = 300;
+
+/// Windows errors with a matching [`CloudErrorKind`], used to classify them alike
+const KNOWN_ERRORS: [(WIN32_ERROR, CloudErrorKind); 14] = [
+    (
+        ERROR_CLOUD_FILE_PROVIDER_NOT_RUNNING,
+        CloudErrorKind::ProviderNotRunning,
+    ),
+    (
+        ERROR_CLOUD_FILE_PROVIDER_TERMINATED,
+        CloudErrorKind::ProviderTerminated,
+    ),
+    (
+        ERROR_CLOUD_FILE_NOT_UNDER_SYNC_ROOT,
+        CloudErrorKind::NotUnderSyncRoot,
+    ),
+    (
+        ERROR_CLOUD_FILE_METADATA_CORRUPT,
+        CloudErrorKind::MetadataCorrupt,
+    ),
+    (
+        ERROR_CLOUD_FILE_PROPERTY_CORRUPT,
+        CloudErrorKind::PropertyCorrupt,
+    ),
+    (
+        ERROR_CLOUD_FILE_PROPERTY_BLOB_CHECKSUM_MISMATCH,
+        CloudErrorKind::PropertyBlobChecksumMismatch,
+    ),
+    (ERROR_CLOUD_FILE_NOT_IN_SYNC, CloudErrorKind::NotInSync),
+    (
+        ERROR_CLOUD_FILE_NETWORK_UNAVAILABLE,
+        CloudErrorKind::NetworkUnavailable,
+    ),
+    (
+        ERROR_CLOUD_FILE_REQUEST_TIMEOUT,
+        CloudErrorKind::RequestTimeout,
+    ),
+    (
+        ERROR_CLOUD_FILE_INSUFFICIENT_RESOURCES,
+        CloudErrorKind::InsufficientResources,
+    ),
+    (ERROR_CLOUD_FILE_IN_USE, CloudErrorKind::InUse),
+    (
+        ERROR_CLOUD_FILE_PROPERTY_LOCK_CONFLICT,
+        CloudErrorKind::PropertyLockConflict,
+    ),
+    (
+        ERROR_CLOUD_FILE_REQUEST_ABORTED,
+        CloudErrorKind::RequestAborted,
+    ),
+    (
+        ERROR_CLOUD_FILE_REQUEST_CANCELED,
+        CloudErrorKind::RequestCancelled,
+    ),
+];
+
+/// A failed cloud filter callback, classified for telemetry and recovery
+#[derive(Debug)]
+pub struct CallbackFailure {
+    pub hresult: Option<i32>,
+    pub class: CfapiFailureClass,
+    pub error: String,
+}
+
+impl CallbackFailure {
+    /// A failure of the app while serving the callback, classified by the error types
+    /// in its chain
+    pub fn from_error(err: &anyhow::Error) -> Self {
+        let error = format!("{:#}", err);
+        for cause in err.chain() {
+            if let Some(windows_err) = cause.downcast_ref::<windows::core::Error>() {
+                return Self {
+                    error,
+                    ..Self::from_windows(windows_err)
+                };
+            }
+        }
+        let class = match retry::classify(err) {
+            TaskFailureClass::Network
+            | TaskFailureClass::Server
+            | TaskFailureClass::Locked
+            | TaskFailureClass::LocalIo => CfapiFailureClass::Transient,
+            TaskFailureClass::Cancelled => CfapiFailureClass::Cancelled,
+            TaskFailureClass::Auth | TaskFailureClass::Other => CfapiFailureClass::Other,
+        };
+        Self {
+            hresult: None,
+            class,
+            error,
+        }
+    }
+
+    /// A failure reported by Windows, e.g. when acknowledging a callback
+    pub fn from_windows(err: &windows::core::Error) -> Self {
+        let class = KNOWN_ERRORS
+            .iter()
+            .find(|(code, _)| code.to_hresult() == err.code())
+            .map(|(_, kind)| classify_kind(*kind))
+            .unwrap_or(CfapiFailureClass::Other);
+        Self {
+            hresult: Some(err.code().0),
+            class,
+            error: err.message().to_string(),
+        }
+    }
+
+    /// A failure the app returned to Windows
+    pub fn from_kind(kind: CloudErrorKind) -> Self {
+        let status: NTSTATUS = kind.into();
+        Self {
+            hresult: Some(status.to_hresult().0),
+            class: classify_kind(kind),
+            error: format!("{:?}", kind),
+        }
+    }
+}
+
+fn classify_kind(kind: CloudErrorKind) -> CfapiFailureClass {
+    match kind {
+        CloudErrorKind::ProviderNotRunning
+        | CloudErrorKind::ProviderTerminated
+        | CloudErrorKind::NotUnderSyncRoot => CfapiFailureClass::ProviderDisconnected,
+        CloudErrorKind::MetadataCorrupt
+        | CloudErrorKind::PropertyCorrupt
+        | CloudErrorKind::PropertyBlobChecksumMismatch
+        | CloudErrorKind::NotInSync => CfapiFailureClass::PlaceholderCorrupt,
+        CloudErrorKind::NetworkUnavailable
+        | CloudErrorKind::RequestTimeout
+        | CloudErrorKind::InsufficientResources
+        | CloudErrorKind::InUse
+        | CloudErrorKind::PropertyLockConflict => CfapiFailureClass::Transient,
+        CloudErrorKind::RequestAborted | CloudErrorKind::RequestCancelled => {
+            CfapiFailureClass::Cancelled
+        }
+        _ => CfapiFailureClass::Other,
+    }
+}
+
+/// The recovery to start for a failure of `class`, unless the same recovery ran for the
+/// drive too recently
+pub fn due_recovery(
+    inventory: &InventoryDb,
+    drive_id: &str,
+    class: CfapiFailureClass,
+) -> Option<CfapiRecovery> {
+    let recovery = class.recovery()?;
+    if recovery != CfapiRecovery::ReconnectSyncRoot {
+        return Some(recovery);
+    }
+    match inventory.last_cfapi_recovery_at(drive_id, recovery) {
+        Ok(Some(last)) if Utc::now().timestamp() - last < RECONNECT_COOLDOWN_SECS => None,
+        Ok(_) => Some(recovery),
+        Err(e) => {
+            tracing::warn!(target: "drive::recovery", id = %drive_id, error = %e, "Failed to check last recovery");
+            None
+        }
+    }
+}
+
+impl Mount {
+    /// Run a recovery started after a failed callback on `path`
+    pub async fn recover(&self, recovery: CfapiRecovery, path: PathBuf) -> Result<()> {
+        tracing::info!(target: "drive::recovery", id = %self.id, path = %path.display(), recovery = recovery.as_str(), "Recovering from cloud filter failure");
+        match recovery {
+            CfapiRecovery::ReconnectSyncRoot => {
+                if !self.repair_sync_root().await? {
+                    self.reconnect_sync_root().await?;
+                }
+            }
+            CfapiRecovery::RepairPlaceholder => {
+                self.command_tx
+                    .send(MountCommand::Sync {
+                        local_paths: vec![path],
+                        mode: SyncMode::PathOnly,
+                    })
+                    .map_err(|e| anyhow!("failed to queue placeholder sync: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     cfapi::placeholder::LocalFileInfo,
-    drive::{commands::MountCommand, mounts::Mount, sync::SyncMode},
+    drive::{commands::MountCommand, mounts::Mount, prefetch::PrefetchHint, sync::SyncMode},
 };
 use anyhow::{Context, Result};
 use cloudreve_api::{
@@ -354,6 +354,10 @@ impl Mount {
             let relative_path: PathBuf = event.from.trim_start_matches('/').split('/').collect();
             let local_from_path = sync_root.join(&relative_path);
 
+            // Changes pushed to this device were made by the same user elsewhere
+            self.prefetcher
+                .hint(PrefetchHint::Changed(local_from_path.clone()));
+
             if let Some(parent) = local_from_path.parent() {
                 grouped_by_parent
                     .entry(parent.to_path_buf())
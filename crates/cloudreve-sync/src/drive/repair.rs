@@ -0,0 +1,144 @@
+//! Repair of a sync root registration removed while the drive config remains, e.g. by
+//! an OS reset or by the user through another tool.
+//!
+//! The drive is registered and connected again, and placeholders that still match the
+//! inventory are marked in sync. Anything else is left to a full sync, which uploads
+//! local changes and re-creates what is missing.
+
+use crate::{
+    cfapi::placeholder::LocalFileInfo,
+    drive::{
+        commands::MountCommand,
+        mounts::{Mount, build_sync_root_info},
+        placeholder::CrPlaceholder,
+        sync::SyncMode,
+    },
+    inventory::{ChangeSource, FileMetadata, MetadataWriteBatch},
+};
+use anyhow::{Context, Result};
+use std::{path::Path, sync::Arc, time::UNIX_EPOCH};
+
+impl Mount {
+    /// Re-register and reconnect the sync root if its registration is gone. Returns
+    /// whether a repair was needed.
+    pub async fn repair_sync_root(&self) -> Result<bool> {
+        let config = self.config.read().await;
+        let Some(sync_root_id) = config.sync_root_id.as_ref() else {
+            return Ok(false);
+        };
+        if sync_root_id
+            .is_registered()
+            .context("failed to check sync root registration")?
+        {
+            return Ok(false);
+        }
+
+        tracing::warn!(target: "drive::repair", id = %self.id, "Sync root registration is missing, re-registering");
+        sync_root_id
+            .register(build_sync_root_info(&config)?)
+            .context("failed to register sync root")?;
+        if let Err(e) = sync_root_id.index() {
+            tracing::warn!(target: "drive::repair", id = %self.id, error = %e, "Failed to add sync root to search indexer");
+        }
+
+        // The old connection went away with the registration
+        self.replace_connection(&config.sync_path)?;
+        drop(config);
+
+        self.restamp_placeholders().await;
+        Ok(true)
+    }
+
+    /// Connect to the sync root again, e.g. after Windows reported the provider as not
+    /// running while the registration is intact
+    pub async fn reconnect_sync_root(&self) -> Result<()> {
+        let sync_path = self.get_sync_path().await;
+        tracing::warn!(target: "drive::repair", id = %self.id, "Reconnecting to sync root");
+        self.replace_connection(&sync_path)
+    }
+
+    /// Drop the current connection, if any, then connect again. A sync root only accepts
+    /// one connection at a time.
+    fn replace_connection(&self, sync_path: &Path) -> Result<()> {
+        let mut connection = self.connection.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = connection.take() {
+            let _ = previous.disconnect();
+        }
+        *connection = Some(self.connect_sync_root(sync_path)?);
+        Ok(())
+    }
+
+    /// Mark placeholders matching the inventory in sync, then queue a full sync for the
+    /// rest and report the repair
+    pub(crate) async fn restamp_placeholders(&self) {
+        let sync_path = self.get_sync_path().await;
+        let inventory = Arc::new(self.inventory.with_source(ChangeSource::Repair));
+        let drive_id = self.id.clone();
+        let root = sync_path.clone();
+        let restamped = tokio::task::spawn_blocking(move || -> Result<usize> {
+            let entries: Vec<_> = inventory
+                .list_by_drive(&drive_id)?
+                .into_iter()
+                .filter(|entry| matches_inventory(Path::new(&entry.local_path), entry))
+                .collect();
+            let batch = MetadataWriteBatch::new(inventory);
+            let result = CrPlaceholder::mark_in_sync_batch(&root, entries, &batch);
+            batch.flush().context("failed to flush inventory writes")?;
+            for (path, e) in &result.failed {
+                tracing::debug!(target: "drive::repair", path = %path.display(), error = ?e, "Failed to re-stamp placeholder");
+            }
+            Ok(result.updated + result.unchanged)
+        })
+        .await
+        .context("placeholder re-stamp task panicked")
+        .and_then(|result| result);
+
+        let restamped = match restamped {
+            Ok(restamped) => restamped,
+            Err(e) => {
+                tracing::error!(target: "drive::repair", id = %self.id, error = ?e, "Failed to re-stamp placeholders");
+                0
+            }
+        };
+        tracing::info!(target: "drive::repair", id = %self.id, restamped, "Sync root registration repaired");
+        self.inventory.notify_sync_root_repaired(restamped);
+
+        if let Err(e) = self.command_tx.send(MountCommand::Sync {
+            local_paths: vec![sync_path],
+            mode: SyncMode::FullHierarchy,
+        }) {
+            tracing::error!(target: "drive::repair", id = %self.id, error = %e, "Failed to queue full sync after repair");
+        }
+    }
+}
+
+/// Whether the local file is still the version recorded in the inventory, so it can be
+/// marked in sync without losing local changes. Folders always match; placeholders match
+/// unless they were modified locally.
+fn matches_inventory(local_path: &Path, entry: &FileMetadata) -> bool {
+    let Ok(info) = LocalFileInfo::from_path(local_path) else {
+        return false;
+    };
+    if !info.exists {
+        return false;
+    }
+    if info.is_directory() {
+        return entry.is_folder;
+    }
+    if entry.is_folder {
+        return false;
+    }
+    if info.is_placeholder() {
+        return info.in_sync();
+    }
+
+    let Ok(metadata) = std::fs::metadata(local_path) else {
+        return false;
+    };
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|modified| modified.as_secs() as i64);
+    metadata.len() == entry.size.max(0) as u64 && modified == Some(entry.updated_at)
+}
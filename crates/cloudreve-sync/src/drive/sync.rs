@@ -6,10 +6,13 @@ use crate::{
     },
     drive::{
         mounts::Mount,
-        placeholder::CrPlaceholder,
+        placeholder::{CrPlaceholder, remote_file_meta},
         utils::{local_path_to_cr_uri, remote_path_to_local_relative_path},
     },
-    inventory::{ConflictState, FileMetadata, MetadataEntry},
+    inventory::{
+        ChangeSource, ConflictKind, ConflictResolution, ConflictState, FileMetadata, MetadataEntry,
+        MetadataWriteBatch, NewConflictRecord,
+    },
     tasks::TaskPayload,
 };
 use anyhow::{Context, Result};
@@ -33,6 +36,10 @@ use std::{
     ffi::OsString,
     fmt, fs, io,
     path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
     time::SystemTime,
 };
 use tokio::task;
@@ -223,9 +230,22 @@ enum SyncAction {
     RenameLocalWithConflict {
         original: PathBuf,
         renamed: PathBuf,
+        local_etag: Option<String>,
+        remote_etag: Option<String>,
     },
 }
 
+impl SyncAction {
+    /// Whether the action writes the inventory only through a [`MetadataWriteBatch`]
+    fn writes_through_batch(&self) -> bool {
+        matches!(
+            self,
+            SyncAction::CreatePlaceholderAndInventory { .. }
+                | SyncAction::UpdateInventoryFromRemote { .. }
+        )
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum UploadReason {
     RemoteMismatch,
@@ -361,6 +381,14 @@ fn system_time_to_unix_secs(time: SystemTime) -> Option<i64> {
     }
 }
 
+/// Whether `path` is a local copy moved aside by a conflict, named by
+/// [`generate_conflict_path`]
+pub fn is_conflict_copy(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(CONFLICT_PREFIX))
+}
+
 fn generate_conflict_path(path: &Path) -> PathBuf {
     let timestamp = Utc::now().format("%Y%m%d%H%M%S");
     let stem = path
@@ -386,6 +414,47 @@ fn next_child_mode(mode: SyncMode) -> SyncMode {
     }
 }
 
+/// Items walked and tasks queued by the syncs of a mount so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncProgress {
+    pub items_walked: u64,
+    pub tasks_queued: u64,
+}
+
+impl SyncProgress {
+    /// The progress made after `start`
+    pub fn since(self, start: SyncProgress) -> SyncProgress {
+        SyncProgress {
+            items_walked: self.items_walked.saturating_sub(start.items_walked),
+            tasks_queued: self.tasks_queued.saturating_sub(start.tasks_queued),
+        }
+    }
+}
+
+/// Counts [`SyncProgress`] of a mount, for reporting the progress of long walks
+#[derive(Debug, Default)]
+pub struct SyncCounters {
+    items_walked: AtomicU64,
+    tasks_queued: AtomicU64,
+}
+
+impl SyncCounters {
+    pub fn snapshot(&self) -> SyncProgress {
+        SyncProgress {
+            items_walked: self.items_walked.load(Ordering::Relaxed),
+            tasks_queued: self.tasks_queued.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record_walked(&self, items: usize) {
+        self.items_walked.fetch_add(items as u64, Ordering::Relaxed);
+    }
+
+    fn record_queued(&self) {
+        self.tasks_queued.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
 /// Result of collecting child targets, including pre-fetched remote file info.
 struct CollectChildResult {
     /// All child paths (union of local and remote).
@@ -510,6 +579,7 @@ impl Mount {
             &local_files,
             &inventory_files,
         );
+        self.sync_counters.record_walked(paths.len());
 
         tracing::debug!(
             target: "drive::sync",
@@ -554,11 +624,54 @@ impl Mount {
             (Uuid::parse_str(&config.id)?, config.sync_path.clone())
         };
 
+        // Inventory writes from placeholder actions are grouped into transactions
+        let batch =
+            MetadataWriteBatch::new(Arc::new(self.inventory.with_source(ChangeSource::Sync)));
+
+        // Plain metadata refreshes are applied together, the rest one by one
+        let (in_sync_updates, actions): (Vec<_>, Vec<_>) = actions.iter().partition(|action| {
+            matches!(
+                action,
+                SyncAction::UpdateInventoryFromRemote {
+                    invalidate_all: false,
+                    ..
+                }
+            )
+        });
+        if !in_sync_updates.is_empty() {
+            let entries = in_sync_updates
+                .into_iter()
+                .filter_map(|action| match action {
+                    SyncAction::UpdateInventoryFromRemote { path, remote, .. } => {
+                        Some(remote_file_meta(path, drive_id, remote))
+                    }
+                    _ => None,
+                })
+                .collect();
+            let result = CrPlaceholder::mark_in_sync_batch(&sync_root, entries, &batch);
+            for (path, err) in result.failed {
+                tracing::error!(
+                    target: "drive::sync",
+                    id = %self.id,
+                    path = %path.display(),
+                    error = ?err,
+                    "Failed to update inventory from remote"
+                );
+                aggregate_error.push(path, err);
+            }
+        }
+
         for action in actions {
-            self.process_action(action, &sync_root, &drive_id, aggregate_error)
+            // Tasks and deletions work on the inventory directly, so the writes batched
+            // so far land first and the order of the plan is kept
+            if !action.writes_through_batch() {
+                batch.flush().context("failed to flush inventory writes")?;
+            }
+            self.process_action(action, &sync_root, &drive_id, &batch, aggregate_error)
                 .await;
         }
 
+        batch.flush().context("failed to flush inventory writes")?;
         Ok(())
     }
 
@@ -567,6 +680,7 @@ impl Mount {
         action: &SyncAction,
         sync_root: &PathBuf,
         drive_id: &Uuid,
+        batch: &MetadataWriteBatch,
         aggregate_error: &mut SyncAggregateError,
     ) {
         match action {
@@ -575,7 +689,7 @@ impl Mount {
                     CrPlaceholder::new(path.clone(), sync_root.clone(), drive_id.clone());
                 if let Err(err) = cr_placeholder
                     .with_remote_file(remote)
-                    .commit(self.inventory.clone())
+                    .commit_batched(batch)
                 {
                     tracing::error!(
                         target: "drive::sync",
@@ -597,7 +711,7 @@ impl Mount {
                 if let Err(err) = cr_placeholder
                     .with_invalidate_all_range(*invalidate_all)
                     .with_remote_file(remote)
-                    .commit(self.inventory.clone())
+                    .commit_batched(batch)
                 {
                     tracing::error!(
                         target: "drive::sync",
@@ -631,6 +745,8 @@ impl Mount {
                         "Failed to enqueue upload task"
                     );
                     aggregate_error.push(path.clone(), anyhow::Error::from(err));
+                } else {
+                    self.sync_counters.record_queued();
                 }
             }
             SyncAction::QueueDownload { path, remote:_ } => {
@@ -657,6 +773,8 @@ impl Mount {
                         "Failed to enqueue download task"
                     );
                     aggregate_error.push(path.clone(), anyhow::Error::from(err));
+                } else {
+                    self.sync_counters.record_queued();
                 }
             }
             SyncAction::DeleteLocalAndInventory {
@@ -687,7 +805,8 @@ impl Mount {
 
                 let cr_placeholder =
                     CrPlaceholder::new(path.clone(), sync_root.clone(), drive_id.clone());
-                if let Err(err) = cr_placeholder.delete_placeholder(self.inventory.clone()) {
+                let inventory = Arc::new(self.inventory.with_source(ChangeSource::Sync));
+                if let Err(err) = cr_placeholder.delete_placeholder(inventory) {
                     tracing::error!(
                         target: "drive::sync",
                         id = %self.id,
@@ -723,9 +842,16 @@ impl Mount {
                         "Failed to enqueue upload task"
                     );
                     aggregate_error.push(path.clone(), anyhow::Error::from(err));
+                } else {
+                    self.sync_counters.record_queued();
                 }
             }
-            SyncAction::RenameLocalWithConflict { original, renamed } => {
+            SyncAction::RenameLocalWithConflict {
+                original,
+                renamed,
+                local_etag,
+                remote_etag,
+            } => {
                 tracing::info!(
                     target: "drive::sync",
                     id = %self.id,
@@ -747,6 +873,30 @@ impl Mount {
                         "Failed to rename local file"
                     );
                     aggregate_error.push(original.clone(), anyhow::Error::from(err));
+                    return;
+                }
+
+                let mut record = NewConflictRecord::new(
+                    drive_id.to_string(),
+                    original.to_string_lossy(),
+                    ConflictKind::TypeMismatch,
+                )
+                .with_conflict_path(renamed.to_string_lossy())
+                .with_resolution(ConflictResolution::KeptBoth);
+                if let Some(etag) = local_etag {
+                    record = record.with_local_etag(etag.clone());
+                }
+                if let Some(etag) = remote_etag {
+                    record = record.with_remote_etag(etag.clone());
+                }
+                if let Err(err) = self.inventory.insert_conflict(&record) {
+                    tracing::warn!(
+                        target: "drive::sync",
+                        id = %self.id,
+                        original = %original.display(),
+                        error = ?err,
+                        "Failed to record conflict"
+                    );
                 }
             }
         }
@@ -989,6 +1139,8 @@ impl Mount {
                 plan.actions.push(SyncAction::RenameLocalWithConflict {
                     original: path.clone(),
                     renamed: conflict_path,
+                    local_etag: inventory.map(|meta| meta.etag.clone()),
+                    remote_etag: remote.primary_entity.clone(),
                 });
             }
 
@@ -0,0 +1,189 @@
+//! Disk cache for thumbnails served to Explorer, so placeholders that are not hydrated,
+//! and files in formats Windows cannot preview, show a thumbnail without asking the
+//! server every time Explorer redraws them.
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Longest Explorer waits for a thumbnail from the server before showing the file icon
+pub const THUMBNAIL_FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Thumbnails larger than this are served but not cached
+const MAX_ENTRY_BYTES: usize = 4 * 1024 * 1024;
+
+/// When over the limit, the least recently used entries are removed until the cache is
+/// this share of the limit, so the next few inserts do not prune again
+const PRUNE_TARGET_PERCENT: u64 = 90;
+
+pub struct ThumbnailCache {
+    dir: PathBuf,
+    /// Keeps concurrent inserts from pruning the same entries
+    prune_lock: Mutex<()>,
+}
+
+impl ThumbnailCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            prune_lock: Mutex::new(()),
+        }
+    }
+
+    /// Cache key of one version of a file; a new version gets a new thumbnail
+    pub fn key(drive_id: &str, uri: &str, version: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(drive_id.as_bytes());
+        hasher.update([0]);
+        hasher.update(uri.as_bytes());
+        hasher.update([0]);
+        hasher.update(version.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Get a cached thumbnail, marking it as recently used
+    pub fn get(&self, key: &str) -> Option<Bytes> {
+        let path = self.entry_path(key);
+        let data = fs::read(&path).ok()?;
+        // Pruning goes by modification time, so touch the entry on every hit
+        if let Ok(file) = fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(Bytes::from(data))
+    }
+
+    /// Cache a thumbnail, then prune the cache down to `max_bytes`. A limit of 0
+    /// disables caching.
+    pub fn put(&self, key: &str, data: &[u8], max_bytes: u64) -> Result<()> {
+        if max_bytes == 0 || data.len() > MAX_ENTRY_BYTES {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir).context("failed to create thumbnail cache directory")?;
+
+        // Write to a temporary file first so readers never see a partial thumbnail
+        let path = self.entry_path(key);
+        let temp_path = path.with_extension(format!(
+            "tmp{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        fs::write(&temp_path, data).context("failed to write thumbnail")?;
+        if let Err(e) = fs::rename(&temp_path, &path) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e).context("failed to move thumbnail into the cache");
+        }
+
+        self.prune(max_bytes)
+    }
+
+    /// Remove the least recently used thumbnails until the cache fits in `max_bytes`
+    pub fn prune(&self, max_bytes: u64) -> Result<()> {
+        let _guard = self.prune_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        if total <= max_bytes {
+            return Ok(());
+        }
+
+        let target = max_bytes / 100 * PRUNE_TARGET_PERCENT;
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        let mut removed = 0;
+        for (path, size, _) in entries {
+            if total <= target {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total = total.saturating_sub(size);
+                removed += 1;
+            }
+        }
+        tracing::debug!(target: "drive::thumbnails", removed, total, "Pruned thumbnail cache");
+        Ok(())
+    }
+
+    /// Remove every cached thumbnail, returning the number of bytes freed
+    pub fn clear(&self) -> Result<u64> {
+        let _guard = self.prune_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut freed = 0;
+        for (path, size, _) in self.entries()? {
+            if fs::remove_file(&path).is_ok() {
+                freed += size;
+            }
+        }
+        Ok(freed)
+    }
+
+    /// Size of the cache in bytes
+    pub fn size(&self) -> Result<u64> {
+        Ok(self.entries()?.iter().map(|(_, size, _)| size).sum())
+    }
+
+    /// Cached files with their size and last use
+    fn entries(&self) -> Result<Vec<(PathBuf, u64, SystemTime)>> {
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).context("failed to list thumbnail cache"),
+        };
+        Ok(read_dir
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                if !metadata.is_file() {
+                    return None;
+                }
+                let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+                Some((entry.path(), metadata.len(), modified))
+            })
+            .collect())
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.thumb", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn prunes_least_recently_used() {
+        let dir = tempdir().unwrap();
+        let cache = ThumbnailCache::new(dir.path().join("thumbnails"));
+        let data = vec![0u8; 1000];
+
+        cache.put("old", &data, 10_000).unwrap();
+        cache.put("new", &data, 10_000).unwrap();
+        // Make "old" the least recently used regardless of timestamp resolution
+        let old = fs::File::options()
+            .write(true)
+            .open(cache.entry_path("old"))
+            .unwrap();
+        old.set_modified(SystemTime::now() - Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(cache.size().unwrap(), 2000);
+
+        cache.put("newest", &data, 2500).unwrap();
+        assert!(cache.get("old").is_none());
+        assert!(cache.get("new").is_some());
+        assert_eq!(cache.get("newest").unwrap().len(), 1000);
+
+        // A limit of 0 disables caching
+        cache.put("skipped", &data, 0).unwrap();
+        assert!(cache.get("skipped").is_none());
+
+        assert_eq!(cache.clear().unwrap(), 2000);
+        assert_eq!(cache.size().unwrap(), 0);
+    }
+}
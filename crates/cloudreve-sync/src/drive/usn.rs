@@ -0,0 +1,336 @@
+//! Catch-up of local changes from the volume change journal.
+//!
+//! The FS watcher only sees changes made while the app is running. When enabled for a
+//! drive, the journal position is saved as a checkpoint, and changes recorded after it are
+//! replayed through the same path as watcher events on the next start. The replay stops at
+//! the position read right before the watcher starts, so a change is picked up by either
+//! the replay or the watcher, not both.
+
+use crate::{
+    cfapi::usn::{JournalState, Usn, UsnJournal, UsnRecord},
+    drive::{commands::MountCommand, mounts::Mount, sync::group_fs_events},
+    inventory::UsnCheckpoint,
+};
+use anyhow::{Context, Result};
+use notify_debouncer_full::{
+    DebouncedEvent,
+    notify::{
+        Event, EventKind,
+        event::{CreateKind, ModifyKind, RemoveKind, RenameMode},
+    },
+};
+use std::{
+    collections::HashMap,
+    ops::Range,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+/// Drive config key enabling the change journal catch-up, e.g. `"usn_journal": true`
+pub(crate) const USN_JOURNAL_KEY: &str = "usn_journal";
+
+impl Mount {
+    pub async fn usn_journal_enabled(&self) -> bool {
+        self.config
+            .read()
+            .await
+            .extra
+            .get(USN_JOURNAL_KEY)
+            .and_then(|value| value.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Open the change journal of the sync root and read its position. Call this before the
+    /// FS watcher starts; the position marks where the watcher takes over.
+    pub(crate) async fn open_usn_journal(&self) -> Option<(UsnJournal, JournalState)> {
+        if !self.usn_journal_enabled().await {
+            return None;
+        }
+        let sync_path = self.get_sync_path().await;
+        let journal = match UsnJournal::open(&sync_path) {
+            Ok(journal) => journal,
+            Err(e) => {
+                tracing::warn!(target: "drive::usn", id = %self.id, error = %e, "Failed to open change journal");
+                return None;
+            }
+        };
+        match journal.query() {
+            Ok(state) => Some((journal, state)),
+            Err(e) => {
+                tracing::warn!(target: "drive::usn", id = %self.id, error = %e, "Failed to query change journal");
+                None
+            }
+        }
+    }
+
+    /// Replay changes recorded between the saved checkpoint and `live_from`, then move the
+    /// checkpoint to `live_from`.
+    pub(crate) async fn replay_usn_journal(
+        &self,
+        journal: UsnJournal,
+        live_from: JournalState,
+    ) -> Result<()> {
+        let checkpoint = self.inventory.get_usn_checkpoint(&self.id)?;
+        let start = match checkpoint {
+            Some(checkpoint)
+                if checkpoint.journal_id == live_from.journal_id
+                    && checkpoint.next_usn >= live_from.first_usn =>
+            {
+                Some(checkpoint.next_usn)
+            }
+            Some(_) => {
+                tracing::warn!(target: "drive::usn", id = %self.id, "Change journal was reset or truncated, changes made while not running are not replayed");
+                None
+            }
+            // First start with the journal enabled, nothing to catch up on
+            None => None,
+        };
+
+        if let Some(start) = start.filter(|start| *start < live_from.next_usn) {
+            let sync_path = self.get_sync_path().await;
+            let events = tokio::task::spawn_blocking(move || {
+                collect_journal_events(
+                    &journal,
+                    live_from.journal_id,
+                    start..live_from.next_usn,
+                    &sync_path,
+                )
+            })
+            .await
+            .context("change journal reader panicked")??;
+
+            tracing::info!(target: "drive::usn", id = %self.id, events = events.len(), "Replaying changes from the change journal");
+            if !events.is_empty() {
+                self.command_tx
+                    .send(MountCommand::ProcessFsEvents {
+                        events: group_fs_events(events),
+                    })
+                    .context("failed to send ProcessFsEvents command")?;
+            }
+        }
+
+        self.inventory.set_usn_checkpoint(
+            &self.id,
+            UsnCheckpoint {
+                journal_id: live_from.journal_id,
+                next_usn: live_from.next_usn,
+            },
+        )
+    }
+
+    /// Save the current journal position, called once the FS watcher stopped. If the app
+    /// exits without saving, the next start replays from the previous checkpoint; the
+    /// replayed changes were already synced and resolve to no-ops.
+    pub(crate) async fn save_usn_checkpoint(&self) {
+        let Some((_, state)) = self.open_usn_journal().await else {
+            return;
+        };
+        let checkpoint = UsnCheckpoint {
+            journal_id: state.journal_id,
+            next_usn: state.next_usn,
+        };
+        if let Err(e) = self.inventory.set_usn_checkpoint(&self.id, checkpoint) {
+            tracing::warn!(target: "drive::usn", id = %self.id, error = %e, "Failed to save change journal checkpoint");
+        }
+    }
+}
+
+/// Read the journal and turn the records under `sync_path` into watcher events, one net
+/// change per file.
+fn collect_journal_events(
+    journal: &UsnJournal,
+    journal_id: u64,
+    range: Range<Usn>,
+    sync_path: &Path,
+) -> Result<Vec<DebouncedEvent>> {
+    let records = journal
+        .read(journal_id, range)
+        .context("failed to read change journal")?;
+    // Paths from the journal are normalized, so compare them against the normalized root
+    let root = journal
+        .root_path()
+        .context("failed to resolve sync root path")?;
+
+    let mut parents: HashMap<u64, Option<PathBuf>> = HashMap::new();
+    let mut changes: Vec<FileChange> = Vec::new();
+    let mut change_index: HashMap<u64, usize> = HashMap::new();
+    for record in &records {
+        // A parent that no longer exists was deleted, its own record covers the subtree
+        let Some(parent) = parents
+            .entry(record.parent_id)
+            .or_insert_with(|| journal.path_of(record.parent_id))
+            .clone()
+        else {
+            continue;
+        };
+        let path = parent
+            .join(&record.file_name)
+            .strip_prefix(&root)
+            .ok()
+            .filter(|relative| !relative.as_os_str().is_empty())
+            .map(|relative| sync_path.join(relative));
+
+        // Files outside the sync root only matter once they were seen inside it
+        let index = match change_index.get(&record.file_id) {
+            Some(index) => *index,
+            None if path.is_some() => {
+                changes.push(FileChange::default());
+                change_index.insert(record.file_id, changes.len() - 1);
+                changes.len() - 1
+            }
+            None => continue,
+        };
+        changes[index].apply(record, path);
+    }
+
+    let now = Instant::now();
+    Ok(changes
+        .into_iter()
+        .flat_map(FileChange::into_events)
+        .map(|event| DebouncedEvent::new(event, now))
+        .collect())
+}
+
+/// The net change of a single file over a run of journal records
+#[derive(Debug, Default)]
+struct FileChange {
+    seen: bool,
+    /// Path before the first record, [None] if the file was created or moved in from
+    /// outside the sync root
+    original: Option<PathBuf>,
+    /// Path after the last record, [None] if the file was deleted or moved out
+    current: Option<PathBuf>,
+    modified: bool,
+    is_directory: bool,
+}
+
+impl FileChange {
+    /// `path` is where the record places the file, [None] if outside the sync root
+    fn apply(&mut self, record: &UsnRecord, path: Option<PathBuf>) {
+        if !self.seen {
+            self.seen = true;
+            // First seen under its new name means it was moved in from outside the sync root
+            let moved_in = record.renamed_to() && !record.renamed_from();
+            if !record.created() && !moved_in {
+                self.original = path.clone();
+            }
+        }
+        self.is_directory = record.is_directory();
+        self.modified |= record.modified();
+        if record.deleted() {
+            self.current = None;
+        } else if !record.renamed_from() {
+            self.current = path;
+        }
+    }
+
+    fn into_events(self) -> Vec<Event> {
+        let (create, remove) = if self.is_directory {
+            (CreateKind::Folder, RemoveKind::Folder)
+        } else {
+            (CreateKind::File, RemoveKind::File)
+        };
+        match (self.original, self.current) {
+            (None, None) => vec![],
+            (None, Some(current)) => {
+                vec![Event::new(EventKind::Create(create)).add_path(current)]
+            }
+            (Some(original), None) => {
+                vec![Event::new(EventKind::Remove(remove)).add_path(original)]
+            }
+            (Some(original), Some(current)) => {
+                let mut events = Vec::new();
+                if original != current {
+                    events.push(
+                        Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+                            .add_path(original)
+                            .add_path(current.clone()),
+                    );
+                }
+                if self.modified && !self.is_directory {
+                    events.push(Event::new(EventKind::Modify(ModifyKind::Any)).add_path(current));
+                }
+                events
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows::Win32::System::Ioctl::{
+        USN_REASON_CLOSE, USN_REASON_DATA_EXTEND, USN_REASON_FILE_CREATE, USN_REASON_FILE_DELETE,
+        USN_REASON_RENAME_NEW_NAME, USN_REASON_RENAME_OLD_NAME,
+    };
+
+    fn record(reason: u32) -> UsnRecord {
+        UsnRecord {
+            usn: 0,
+            file_id: 1,
+            parent_id: 2,
+            reason,
+            attributes: 0,
+            file_name: "a.txt".into(),
+        }
+    }
+
+    fn net_change(records: &[(u32, Option<&str>)]) -> Vec<Event> {
+        let mut change = FileChange::default();
+        for (reason, path) in records {
+            change.apply(&record(*reason), path.map(PathBuf::from));
+        }
+        change.into_events()
+    }
+
+    #[test]
+    fn journal_records_collapse_to_net_change() {
+        // Created and deleted while not running
+        assert!(
+            net_change(&[
+                (USN_REASON_FILE_CREATE, Some(r"C:\sync\a.txt")),
+                (
+                    USN_REASON_FILE_DELETE | USN_REASON_CLOSE,
+                    Some(r"C:\sync\a.txt")
+                ),
+            ])
+            .is_empty()
+        );
+
+        // Renamed, then written
+        let events = net_change(&[
+            (USN_REASON_RENAME_OLD_NAME, Some(r"C:\sync\a.txt")),
+            (USN_REASON_RENAME_NEW_NAME, Some(r"C:\sync\b.txt")),
+            (
+                USN_REASON_DATA_EXTEND | USN_REASON_CLOSE,
+                Some(r"C:\sync\b.txt"),
+            ),
+        ]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].kind,
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both))
+        );
+        assert_eq!(
+            events[0].paths,
+            vec![
+                PathBuf::from(r"C:\sync\a.txt"),
+                PathBuf::from(r"C:\sync\b.txt")
+            ]
+        );
+        assert_eq!(events[1].paths, vec![PathBuf::from(r"C:\sync\b.txt")]);
+
+        // Moved in from outside the sync root
+        let events = net_change(&[(USN_REASON_RENAME_NEW_NAME, Some(r"C:\sync\a.txt"))]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, EventKind::Create(CreateKind::File));
+
+        // Moved out of the sync root
+        let events = net_change(&[
+            (USN_REASON_RENAME_OLD_NAME, Some(r"C:\sync\a.txt")),
+            (USN_REASON_RENAME_NEW_NAME, None),
+        ]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, EventKind::Remove(RemoveKind::File));
+    }
+}
@@ -1,10 +1,10 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use cloudreve_api::models::uri::CrUri;
 use url::Url;
 use widestring::U16CString;
-use windows::Win32::UI::Shell::{SHCNE_ID, SHCNF_PATHW, SHChangeNotify};
+use windows::Win32::UI::Shell::{SHCNE_ID, SHCNE_UPDATEITEM, SHCNF_PATHW, SHChangeNotify};
 
 use crate::drive::mounts::DriveConfig;
 
@@ -92,3 +92,11 @@ pub fn notify_shell_change(path: &PathBuf, event: SHCNE_ID) -> Result<()> {
     }
     Ok(())
 }
+
+/// Ask Explorer to query the custom states of `path` again, after a conflict or a failed
+/// sync was recorded or cleared for it
+pub fn refresh_shell_state(path: &Path) {
+    if let Err(e) = notify_shell_change(&path.to_path_buf(), SHCNE_UPDATEITEM) {
+        tracing::debug!(target: "drive::utils", path = %path.display(), error = %e, "Failed to refresh shell state");
+    }
+}
@@ -0,0 +1,43 @@
+//! Loopback HTTP listener serving the event stream and the metrics to local dashboards.
+//!
+//! Disabled by default, enabled with [`LocalApiConfig`](crate::config::LocalApiConfig).
+//! It binds to 127.0.0.1 only, so only processes on this machine can connect.
+
+use super::{EventBroadcaster, metrics, metrics::MetricsCollector, sse};
+use crate::config::ConfigManager;
+use anyhow::{Context, Result};
+use std::net::{Ipv4Addr, TcpListener};
+use std::sync::Arc;
+
+/// Start the listener if enabled in the config. Fails if the port cannot be bound.
+pub fn spawn_server(
+    event_broadcaster: &Arc<EventBroadcaster>,
+    metrics: &MetricsCollector,
+) -> Result<()> {
+    let config = ConfigManager::try_get()
+        .map(|config| config.local_api())
+        .unwrap_or_default();
+    if !config.enabled {
+        return Ok(());
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, config.port))
+        .with_context(|| format!("Failed to bind local API to port {}", config.port))?;
+    listener
+        .set_nonblocking(true)
+        .context("Failed to configure local API listener")?;
+    let listener = tokio::net::TcpListener::from_std(listener)
+        .context("Failed to register local API listener")?;
+
+    let router = sse::routes()
+        .with_state(event_broadcaster.clone())
+        .merge(metrics::routes().with_state(metrics.clone()));
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            tracing::error!(target: "events::api", error = %e, "Local API server stopped");
+        }
+    });
+
+    tracing::info!(target: "events::api", port = config.port, "Local API listening on 127.0.0.1");
+    Ok(())
+}
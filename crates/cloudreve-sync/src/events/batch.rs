@@ -0,0 +1,151 @@
+use super::{Event, SyncedFile};
+use crate::tasks::TaskProgress;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Coalesces the high-frequency events before they are emitted to the UI. Task
+/// progress is reduced to the latest progress of each task and synced files are
+/// collected, both are emitted per drive by [`EventBatcher::flush`] as an
+/// [`Event::TaskProgressBatch`] and an [`Event::FilesSynced`]. Other events pass
+/// through.
+#[derive(Default)]
+pub struct EventBatcher {
+    progress: HashMap<String, Vec<TaskProgress>>,
+    synced: HashMap<String, Vec<SyncedFile>>,
+}
+
+impl EventBatcher {
+    /// Flushing at this interval emits at most 4 batches of each kind per drive and second
+    pub const INTERVAL: Duration = Duration::from_millis(250);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold back `event` until the next flush if it is batched, otherwise return it
+    pub fn push(&mut self, event: Event) -> Option<Event> {
+        // Progress of a finished task would arrive after the task left the list
+        match &event {
+            Event::TaskFinished {
+                drive_id, task_id, ..
+            } => self.forget_progress(drive_id, |id| id == task_id),
+            Event::TasksUpdated {
+                drive_id, task_ids, ..
+            } => self.forget_progress(drive_id, |id| task_ids.iter().any(|t| t == id)),
+            _ => {}
+        }
+
+        match event {
+            Event::TaskProgress { drive_id, progress } => {
+                let pending = self.progress.entry(drive_id).or_default();
+                match pending.iter_mut().find(|p| p.task_id == progress.task_id) {
+                    Some(latest) => *latest = progress,
+                    None => pending.push(progress),
+                }
+                None
+            }
+            Event::FileSynced {
+                drive_id,
+                local_path,
+                kind,
+            } => {
+                self.synced
+                    .entry(drive_id)
+                    .or_default()
+                    .push(SyncedFile { local_path, kind });
+                None
+            }
+            event => Some(event),
+        }
+    }
+
+    /// The batches of the events held back since the last flush
+    pub fn flush(&mut self) -> Vec<Event> {
+        let progress = self
+            .progress
+            .drain()
+            .filter(|(_, progress)| !progress.is_empty())
+            .map(|(drive_id, progress)| Event::TaskProgressBatch { drive_id, progress });
+        let synced = self
+            .synced
+            .drain()
+            .map(|(drive_id, files)| Event::FilesSynced { drive_id, files });
+        progress.chain(synced).collect()
+    }
+
+    fn forget_progress(&mut self, drive_id: &str, finished: impl Fn(&str) -> bool) {
+        if let Some(pending) = self.progress.get_mut(drive_id) {
+            pending.retain(|progress| !finished(&progress.task_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::TaskStatus;
+    use crate::tasks::TaskKind;
+
+    fn progress(task_id: &str, value: f64) -> Event {
+        Event::TaskProgress {
+            drive_id: "drive".to_string(),
+            progress: TaskProgress {
+                task_id: task_id.to_string(),
+                kind: TaskKind::Upload,
+                local_path: format!("C:\\{task_id}"),
+                progress: value,
+                processed_bytes: None,
+                total_bytes: None,
+                speed_bytes_per_sec: 0,
+                eta_seconds: None,
+                custom_state: None,
+            },
+        }
+    }
+
+    #[test]
+    fn progress_and_synced_files_are_batched_per_drive() {
+        let mut batcher = EventBatcher::new();
+
+        assert!(batcher.push(progress("a", 0.1)).is_none());
+        assert!(batcher.push(progress("b", 0.1)).is_none());
+        assert!(batcher.push(progress("a", 0.5)).is_none());
+        assert!(batcher.push(progress("c", 0.1)).is_none());
+        let finished = Event::TaskFinished {
+            drive_id: "drive".to_string(),
+            task_id: "c".to_string(),
+            status: TaskStatus::Completed,
+        };
+        assert!(batcher.push(finished).is_some());
+        for local_path in ["C:\\c", "C:\\d"] {
+            assert!(
+                batcher
+                    .push(Event::FileSynced {
+                        drive_id: "drive".to_string(),
+                        local_path: local_path.to_string(),
+                        kind: TaskKind::Upload,
+                    })
+                    .is_none()
+            );
+        }
+
+        let mut batches = batcher.flush();
+        assert_eq!(batches.len(), 2);
+        let Event::FilesSynced { files, .. } = batches.pop().unwrap() else {
+            panic!("expected the synced files last");
+        };
+        assert_eq!(files.len(), 2);
+        let Event::TaskProgressBatch { progress, .. } = batches.pop().unwrap() else {
+            panic!("expected a progress batch");
+        };
+        assert_eq!(
+            progress
+                .iter()
+                .map(|p| (p.task_id.as_str(), p.progress))
+                .collect::<Vec<_>>(),
+            [("a", 0.5), ("b", 0.1)]
+        );
+
+        assert!(batcher.flush().is_empty());
+    }
+}
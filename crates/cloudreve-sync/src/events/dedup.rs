@@ -0,0 +1,130 @@
+use super::{Event, EventSeverity};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Collapses identical warnings and errors repeating within a window, such as the
+/// same upload failing on every retry. The first event is broadcast right away, the
+/// repeats are counted and reported by a single [`Event::Repeated`] once the window
+/// ends. Info events are never held back.
+pub struct EventDeduper {
+    window: Duration,
+    /// Events in their window, keyed by their serialized form
+    pending: HashMap<String, PendingEvent>,
+}
+
+struct PendingEvent {
+    event: Event,
+    since: Instant,
+    repeats: usize,
+}
+
+impl EventDeduper {
+    pub const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Whether `event` should be broadcast. Identical events within the window of the
+    /// first one are counted instead.
+    pub fn admit(&mut self, event: &Event, now: Instant) -> bool {
+        if event.severity() == EventSeverity::Info {
+            return true;
+        }
+        let Ok(key) = serde_json::to_string(event) else {
+            return true;
+        };
+
+        match self.pending.get_mut(&key) {
+            Some(pending) if now.duration_since(pending.since) < self.window => {
+                pending.repeats += 1;
+                false
+            }
+            _ => {
+                self.pending.insert(
+                    key,
+                    PendingEvent {
+                        event: event.clone(),
+                        since: now,
+                        repeats: 0,
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    /// Forget the events whose window ended, returning an [`Event::Repeated`] for each
+    /// of them that was held back at least once
+    pub fn take_expired(&mut self, now: Instant) -> Vec<Event> {
+        let mut repeated = Vec::new();
+        self.pending.retain(|_, pending| {
+            if now.duration_since(pending.since) < self.window {
+                return true;
+            }
+            if pending.repeats > 0 {
+                repeated.push(Event::Repeated {
+                    event: Box::new(pending.event.clone()),
+                    count: pending.repeats,
+                });
+            }
+            false
+        });
+        repeated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload_failed(error: &str) -> Event {
+        Event::UploadFailed {
+            drive_id: "drive".to_string(),
+            task_id: "task".to_string(),
+            local_path: "C:\\a.txt".to_string(),
+            error: error.to_string(),
+        }
+    }
+
+    #[test]
+    fn repeats_are_collapsed_within_the_window() {
+        let mut deduper = EventDeduper::new(Duration::from_secs(10));
+        let start = Instant::now();
+
+        assert!(deduper.admit(&upload_failed("timeout"), start));
+        assert!(!deduper.admit(&upload_failed("timeout"), start + Duration::from_secs(1)));
+        assert!(!deduper.admit(&upload_failed("timeout"), start + Duration::from_secs(2)));
+        assert!(deduper.admit(&upload_failed("denied"), start + Duration::from_secs(3)));
+        assert!(deduper.admit(&Event::ConnectionStatusChanged { connected: true }, start));
+        assert!(deduper.admit(&Event::ConnectionStatusChanged { connected: true }, start));
+
+        assert!(
+            deduper
+                .take_expired(start + Duration::from_secs(5))
+                .is_empty()
+        );
+        let repeated = deduper.take_expired(start + Duration::from_secs(10));
+        assert_eq!(repeated.len(), 1);
+        let Event::Repeated { event, count } = &repeated[0] else {
+            panic!("expected a repeated event");
+        };
+        assert_eq!(*count, 2);
+        assert!(matches!(**event, Event::UploadFailed { ref error, .. } if error == "timeout"));
+
+        // The window of the second error ends without repeats
+        assert!(
+            deduper
+                .take_expired(start + Duration::from_secs(13))
+                .is_empty()
+        );
+        assert!(deduper.admit(&upload_failed("timeout"), start + Duration::from_secs(13)));
+    }
+}
@@ -0,0 +1,285 @@
+//! Counters and gauges kept from the broadcast events since the app started.
+
+use super::{Event, EventBroadcaster, EventSeverity};
+use crate::inventory::TaskStatus;
+use crate::tasks::{TaskKind, TaskProgress};
+use axum::{Router, extract::State, http::header, response::IntoResponse, routing::get};
+use chrono::{Local, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::error::RecvError;
+
+/// The metrics at one point in time
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetricsSnapshot {
+    /// Unix timestamp the counters start from
+    pub since: i64,
+    pub files_synced_total: u64,
+    /// Files synced since local midnight
+    pub files_synced_today: u64,
+    /// Bytes of completed uploads, as far as they reported progress
+    pub bytes_uploaded_total: u64,
+    /// Bytes of completed downloads and hydrations, as far as they reported progress
+    pub bytes_downloaded_total: u64,
+    /// Error events, including the repeats held back by the deduper
+    pub errors_total: u64,
+    pub conflicts_total: u64,
+    /// Tasks that reported progress and did not finish yet
+    pub running_tasks: usize,
+    /// Tasks in the dead-letter stores of all drives
+    pub dead_letters: usize,
+}
+
+impl MetricsSnapshot {
+    /// The metrics in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let metrics = [
+            ("files_synced_total", "counter", self.files_synced_total),
+            ("files_synced_today", "gauge", self.files_synced_today),
+            ("bytes_uploaded_total", "counter", self.bytes_uploaded_total),
+            (
+                "bytes_downloaded_total",
+                "counter",
+                self.bytes_downloaded_total,
+            ),
+            ("errors_total", "counter", self.errors_total),
+            ("conflicts_total", "counter", self.conflicts_total),
+            ("running_tasks", "gauge", self.running_tasks as u64),
+            ("dead_letters", "gauge", self.dead_letters as u64),
+        ];
+
+        let mut text = String::new();
+        for (name, kind, value) in metrics {
+            let _ = writeln!(text, "# TYPE cloudreve_{name} {kind}");
+            let _ = writeln!(text, "cloudreve_{name} {value}");
+        }
+        text
+    }
+}
+
+#[derive(Default)]
+struct MetricsState {
+    snapshot: MetricsSnapshot,
+    today: Option<NaiveDate>,
+    /// Drive and latest progress of the running tasks, by task id
+    progress: HashMap<String, (String, TaskProgress)>,
+    /// Dead-letter store sizes by drive id
+    dead_letters: HashMap<String, usize>,
+}
+
+impl MetricsState {
+    fn roll_over(&mut self, today: NaiveDate) {
+        if self.today != Some(today) {
+            self.today = Some(today);
+            self.snapshot.files_synced_today = 0;
+        }
+    }
+
+    fn record(&mut self, event: &Event, today: NaiveDate) {
+        self.roll_over(today);
+
+        if event.severity() == EventSeverity::Error {
+            self.snapshot.errors_total += match event {
+                Event::Repeated { count, .. } => *count as u64,
+                _ => 1,
+            };
+        }
+
+        match event {
+            Event::FileSynced { .. } => {
+                self.snapshot.files_synced_total += 1;
+                self.snapshot.files_synced_today += 1;
+            }
+            Event::ConflictDetected { .. } => self.snapshot.conflicts_total += 1,
+            Event::TaskProgress { drive_id, progress } => {
+                self.progress.insert(
+                    progress.task_id.clone(),
+                    (drive_id.clone(), progress.clone()),
+                );
+            }
+            Event::TaskFinished {
+                task_id, status, ..
+            } => {
+                let progress = self.progress.remove(task_id);
+                if let (TaskStatus::Completed, Some((_, progress))) = (status, progress) {
+                    let bytes = progress
+                        .total_bytes
+                        .or(progress.processed_bytes)
+                        .unwrap_or(0)
+                        .max(0) as u64;
+                    match progress.kind {
+                        TaskKind::Upload => self.snapshot.bytes_uploaded_total += bytes,
+                        TaskKind::Download | TaskKind::Hydrate => {
+                            self.snapshot.bytes_downloaded_total += bytes
+                        }
+                    }
+                }
+            }
+            Event::TasksUpdated { task_ids, .. } => {
+                for task_id in task_ids {
+                    self.progress.remove(task_id);
+                }
+            }
+            Event::DeadLettersChanged { drive_id, count } => {
+                self.dead_letters.insert(drive_id.clone(), *count);
+            }
+            Event::DriveRemoved { drive_id, .. } => {
+                self.dead_letters.remove(drive_id);
+                self.progress
+                    .retain(|_, (task_drive, _)| task_drive != drive_id);
+            }
+            _ => {}
+        }
+    }
+
+    fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            running_tasks: self.progress.len(),
+            dead_letters: self.dead_letters.values().sum(),
+            ..self.snapshot.clone()
+        }
+    }
+}
+
+/// Keeps [`MetricsSnapshot`] up to date from the events of a broadcaster
+#[derive(Clone)]
+pub struct MetricsCollector {
+    state: Arc<Mutex<MetricsState>>,
+}
+
+impl MetricsCollector {
+    /// Spawn a task that records the events of `event_broadcaster`
+    pub fn spawn(event_broadcaster: &EventBroadcaster) -> Self {
+        let collector = Self::new();
+        let mut receiver = event_broadcaster.subscribe();
+        let state = collector.state.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => state
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .record(&event, Local::now().date_naive()),
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(target: "events::metrics", skipped = n, "Metrics collector lagged, some events were not counted");
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        collector
+    }
+
+    fn new() -> Self {
+        let state = MetricsState {
+            snapshot: MetricsSnapshot {
+                since: Utc::now().timestamp(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        // Reset the daily counter even if nothing was synced today yet
+        state.roll_over(Local::now().date_naive());
+        state.snapshot()
+    }
+}
+
+/// Routes serving the metrics at `/api/metrics`, in the Prometheus text format
+pub fn routes() -> Router<MetricsCollector> {
+    Router::new().route("/api/metrics", get(metrics_handler))
+}
+
+async fn metrics_handler(State(collector): State<MetricsCollector>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        collector.snapshot().to_prometheus(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_follow_the_events() {
+        let mut state = MetricsState::default();
+        let day = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let drive_id = "drive".to_string();
+
+        state.record(
+            &Event::TaskProgress {
+                drive_id: drive_id.clone(),
+                progress: TaskProgress {
+                    task_id: "t".to_string(),
+                    kind: TaskKind::Upload,
+                    local_path: "C:\\a.txt".to_string(),
+                    progress: 0.5,
+                    processed_bytes: Some(512),
+                    total_bytes: Some(1024),
+                    speed_bytes_per_sec: 0,
+                    eta_seconds: None,
+                    custom_state: None,
+                },
+            },
+            day,
+        );
+        assert_eq!(state.snapshot().running_tasks, 1);
+
+        state.record(
+            &Event::TaskFinished {
+                drive_id: drive_id.clone(),
+                task_id: "t".to_string(),
+                status: TaskStatus::Completed,
+            },
+            day,
+        );
+        state.record(
+            &Event::FileSynced {
+                drive_id: drive_id.clone(),
+                local_path: "C:\\a.txt".to_string(),
+                kind: TaskKind::Upload,
+            },
+            day,
+        );
+        let failed = Event::UploadFailed {
+            drive_id: drive_id.clone(),
+            task_id: "u".to_string(),
+            local_path: "C:\\b.txt".to_string(),
+            error: "denied".to_string(),
+        };
+        state.record(&failed, day);
+        state.record(
+            &Event::Repeated {
+                event: Box::new(failed),
+                count: 3,
+            },
+            day,
+        );
+
+        let snapshot = state.snapshot();
+        assert_eq!(snapshot.running_tasks, 0);
+        assert_eq!(snapshot.bytes_uploaded_total, 1024);
+        assert_eq!(snapshot.files_synced_today, 1);
+        assert_eq!(snapshot.errors_total, 4);
+        assert!(
+            snapshot
+                .to_prometheus()
+                .contains("cloudreve_errors_total 4\n")
+        );
+
+        state.roll_over(day.succ_opt().unwrap());
+        assert_eq!(state.snapshot().files_synced_today, 0);
+        assert_eq!(state.snapshot().files_synced_total, 1);
+    }
+}
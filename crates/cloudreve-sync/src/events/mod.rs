@@ -1,8 +1,25 @@
+use crate::inventory::{ConflictKind, ConflictResolution, InventoryDb, TaskStatus};
+use crate::tasks::{TaskKind, TaskProgress};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread;
+use std::time::Instant;
 use tokio::sync::broadcast;
 use tracing;
 
+pub mod api;
+mod batch;
+mod dedup;
+pub mod metrics;
+mod schema;
+pub mod sse;
+pub mod webhook;
+
+pub use batch::EventBatcher;
+pub use dedup::EventDeduper;
+pub use schema::{EVENT_SCHEMA_VERSION, VersionedEvent};
+
 /// Different types of events that can be broadcast to GUI
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
@@ -16,15 +33,425 @@ pub enum Event {
     OpenSyncStatusWindow,
     /// Request to open the settings window
     OpenSettingsWindow,
+    /// Request to restart the app to install the downloaded update
+    RestartToUpdate,
+    /// Request to open the conflicts window, showing only the conflicts of `local_path`
+    OpenConflictsWindow {
+        local_path: String,
+    },
+    /// Request to open the "Send to Cloudreve" window to upload `local_paths`
+    OpenSendToWindow {
+        local_paths: Vec<String>,
+    },
+    /// Request to open the reauthorize window of a drive whose credential expired
+    OpenReauthorizeWindow {
+        drive_id: String,
+        site_url: String,
+        drive_name: String,
+    },
+    /// Orphaned inventory records were removed by the periodic cleanup job
+    InventoryCleaned {
+        drive_id: String,
+        removed_entries: usize,
+        removed_sessions: usize,
+    },
+    /// Inventory entries were written. Renames are reported as a removal of the old
+    /// path followed by an addition of the new one.
+    InventoryEntriesChanged {
+        drive_id: String,
+        change: EntryChange,
+        local_paths: Vec<String>,
+    },
+    /// A queued task completed, failed or was cancelled
+    TaskFinished {
+        drive_id: String,
+        task_id: String,
+        status: TaskStatus,
+    },
+    /// Several tasks were moved to `status` by one bulk operation, reported instead of
+    /// a [`Event::TaskFinished`] per task
+    TasksUpdated {
+        drive_id: String,
+        status: TaskStatus,
+        task_ids: Vec<String>,
+    },
+    /// Tasks were added to or removed from a drive's dead-letter store
+    DeadLettersChanged {
+        drive_id: String,
+        count: usize,
+    },
+    /// Live progress of a running task, sent at most every half second per task
+    TaskProgress {
+        drive_id: String,
+        progress: TaskProgress,
+    },
+    /// The sync root registration of a drive was missing and has been restored.
+    /// `restamped` placeholders were marked in sync again; the rest are reconciled by a
+    /// full sync.
+    SyncRootRepaired {
+        drive_id: String,
+        restamped: usize,
+    },
+    /// The Explorer integration stopped (`healthy: false`, with the reason) or came back
+    /// after `restarts` automatic restarts
+    ShellServiceHealthChanged {
+        healthy: bool,
+        restarts: u32,
+        error: Option<String>,
+    },
+    /// An upload or download task completed, the local file matches the remote one
+    FileSynced {
+        drive_id: String,
+        local_path: String,
+        kind: TaskKind,
+    },
+    /// A download task started running
+    FileDownloadStarted {
+        drive_id: String,
+        task_id: String,
+        local_path: String,
+    },
+    /// A download task stopped, with the error if it failed for good. Downloads that
+    /// will be retried or were cancelled are not reported.
+    FileDownloadFinished {
+        drive_id: String,
+        task_id: String,
+        local_path: String,
+        error: Option<String>,
+    },
+    /// An upload task failed for good or timed out
+    UploadFailed {
+        drive_id: String,
+        task_id: String,
+        local_path: String,
+        error: String,
+    },
+    /// A conflict was recorded. Conflicts resolved on detection, such as type
+    /// mismatches kept side by side, are reported with their resolution.
+    ConflictDetected {
+        drive_id: String,
+        conflict_id: i64,
+        local_path: String,
+        kind: ConflictKind,
+        resolution: ConflictResolution,
+    },
+    /// A drive was mounted, including the drives loaded on startup
+    DriveAdded {
+        drive_id: String,
+        name: String,
+    },
+    /// A drive was unmounted and its sync root removed
+    DriveRemoved {
+        drive_id: String,
+        name: String,
+    },
+    /// The name, icon or color of a drive was changed
+    DriveUpdated {
+        drive_id: String,
+        name: String,
+    },
+    /// The credential of a drive expired and the user has to sign in again
+    CredentialExpired {
+        drive_id: String,
+        drive_name: String,
+    },
+    /// The used capacity of a drive crossed the warning threshold, in bytes
+    QuotaWarning {
+        drive_id: String,
+        used: i64,
+        total: i64,
+    },
+    /// Syncing of a drive was paused or resumed. `until` is the Unix timestamp the
+    /// pause ends at, `None` while paused until resumed.
+    SyncPauseChanged {
+        drive_id: String,
+        paused: bool,
+        until: Option<i64>,
+    },
+    /// A full resync of a drive started (`running: true`) or finished. `error` is set
+    /// if part of the drive could not be synced.
+    FullResync {
+        drive_id: String,
+        running: bool,
+        error: Option<String>,
+    },
+    /// Progress of a running full resync: items walked and tasks queued since it started
+    FullResyncProgress {
+        drive_id: String,
+        items_walked: u64,
+        tasks_queued: u64,
+    },
+    /// A newer version of the app was found (`ready: false`), or was downloaded and is
+    /// installed on the next restart
+    UpdateAvailable {
+        version: String,
+        ready: bool,
+    },
+    /// `event` was broadcast and then repeated `count` more times within the window of
+    /// the [`EventDeduper`], the repeats were held back
+    Repeated {
+        event: Box<Event>,
+        count: usize,
+    },
+    /// The latest progress of the tasks that reported progress since the last batch,
+    /// emitted to the UI by the [`EventBatcher`] instead of [`Event::TaskProgress`]
+    TaskProgressBatch {
+        drive_id: String,
+        progress: Vec<TaskProgress>,
+    },
+    /// The files synced since the last batch, emitted to the UI by the
+    /// [`EventBatcher`] instead of [`Event::FileSynced`]
+    FilesSynced {
+        drive_id: String,
+        files: Vec<SyncedFile>,
+    },
+}
+
+/// Kind of write reported by [`Event::InventoryEntriesChanged`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EntryChange {
+    Added,
+    /// Also used for upserts, which may have created the entry
+    Updated,
+    /// Removing a folder also removes its descendants, which are not listed
+    Removed,
+}
+
+/// A file reported by [`Event::FilesSynced`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncedFile {
+    pub local_path: String,
+    pub kind: TaskKind,
+}
+
+/// How much attention an event needs, see [`Event::severity`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum EventSeverity {
+    Info,
+    /// Something the user may want to look at, sync goes on
+    Warning,
+    /// Something failed and the user has to act
+    Error,
+}
+
+/// An event read back from the event log, see [`EventBroadcaster::with_log`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    /// Increases with every logged event, pass it to [`EventBroadcaster::replay`] to get
+    /// the events that followed
+    pub id: i64,
+    /// Unix timestamp
+    pub created_at: i64,
+    pub event: Event,
+}
+
+/// An event together with the id it was logged under, see
+/// [`EventBroadcaster::subscribe_sequenced`]
+#[derive(Debug, Clone)]
+pub struct SequencedEvent {
+    /// `None` for events that are not logged, or if the broadcaster has no log
+    pub id: Option<i64>,
+    pub event: Event,
 }
 
 impl Event {
+    /// Whether the event is kept in the event log. Requests to open windows or restart
+    /// only make sense when they are sent, and per-file, per-task, progress and entry
+    /// changes are too frequent.
+    pub fn is_logged(&self) -> bool {
+        !matches!(
+            self,
+            Event::OpenSyncStatusWindow
+                | Event::OpenSettingsWindow
+                | Event::RestartToUpdate
+                | Event::OpenConflictsWindow { .. }
+                | Event::OpenSendToWindow { .. }
+                | Event::OpenReauthorizeWindow { .. }
+                | Event::TaskProgress { .. }
+                | Event::TaskProgressBatch { .. }
+                | Event::FullResyncProgress { .. }
+                | Event::TaskFinished { .. }
+                | Event::TasksUpdated { .. }
+                | Event::FileSynced { .. }
+                | Event::FilesSynced { .. }
+                | Event::FileDownloadStarted { .. }
+                | Event::FileDownloadFinished { .. }
+                | Event::InventoryEntriesChanged { .. }
+        )
+    }
+
+    pub fn severity(&self) -> EventSeverity {
+        match self {
+            Event::UploadFailed { .. }
+            | Event::CredentialExpired { .. }
+            | Event::FileDownloadFinished { error: Some(_), .. }
+            | Event::ShellServiceHealthChanged { healthy: false, .. } => EventSeverity::Error,
+            Event::TaskFinished {
+                status: TaskStatus::Failed | TaskStatus::TimedOut,
+                ..
+            }
+            | Event::ConflictDetected { .. }
+            | Event::QuotaWarning { .. }
+            | Event::SyncRootRepaired { .. }
+            | Event::FullResync { error: Some(_), .. } => EventSeverity::Warning,
+            Event::DeadLettersChanged { count, .. } if *count > 0 => EventSeverity::Warning,
+            Event::Repeated { event, .. } => event.severity(),
+            _ => EventSeverity::Info,
+        }
+    }
+
+    /// The drive the event belongs to, `None` for app-wide events
+    pub fn drive_id(&self) -> Option<&str> {
+        match self {
+            Event::Repeated { event, .. } => event.drive_id(),
+            Event::InventoryCleaned { drive_id, .. }
+            | Event::InventoryEntriesChanged { drive_id, .. }
+            | Event::TaskFinished { drive_id, .. }
+            | Event::TasksUpdated { drive_id, .. }
+            | Event::DeadLettersChanged { drive_id, .. }
+            | Event::TaskProgress { drive_id, .. }
+            | Event::SyncRootRepaired { drive_id, .. }
+            | Event::OpenReauthorizeWindow { drive_id, .. }
+            | Event::FileSynced { drive_id, .. }
+            | Event::FileDownloadStarted { drive_id, .. }
+            | Event::FileDownloadFinished { drive_id, .. }
+            | Event::UploadFailed { drive_id, .. }
+            | Event::ConflictDetected { drive_id, .. }
+            | Event::DriveAdded { drive_id, .. }
+            | Event::DriveRemoved { drive_id, .. }
+            | Event::DriveUpdated { drive_id, .. }
+            | Event::CredentialExpired { drive_id, .. }
+            | Event::QuotaWarning { drive_id, .. }
+            | Event::SyncPauseChanged { drive_id, .. }
+            | Event::FullResync { drive_id, .. }
+            | Event::FullResyncProgress { drive_id, .. }
+            | Event::TaskProgressBatch { drive_id, .. }
+            | Event::FilesSynced { drive_id, .. } => Some(drive_id),
+            Event::ConnectionStatusChanged { .. }
+            | Event::NoDrive { .. }
+            | Event::OpenSyncStatusWindow
+            | Event::OpenSettingsWindow
+            | Event::RestartToUpdate
+            | Event::OpenConflictsWindow { .. }
+            | Event::OpenSendToWindow { .. }
+            | Event::ShellServiceHealthChanged { .. }
+            | Event::UpdateAvailable { .. } => None,
+        }
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             Event::ConnectionStatusChanged { .. } => "ConnectionStatusChanged",
             Event::NoDrive {  } => "NoDrive",
             Event::OpenSyncStatusWindow => "OpenSyncStatusWindow",
             Event::OpenSettingsWindow => "OpenSettingsWindow",
+            Event::RestartToUpdate => "RestartToUpdate",
+            Event::OpenConflictsWindow { .. } => "OpenConflictsWindow",
+            Event::OpenSendToWindow { .. } => "OpenSendToWindow",
+            Event::OpenReauthorizeWindow { .. } => "OpenReauthorizeWindow",
+            Event::InventoryCleaned { .. } => "InventoryCleaned",
+            Event::InventoryEntriesChanged { .. } => "InventoryEntriesChanged",
+            Event::TaskFinished { .. } => "TaskFinished",
+            Event::TaskProgress { .. } => "TaskProgress",
+            Event::TasksUpdated { .. } => "TasksUpdated",
+            Event::DeadLettersChanged { .. } => "DeadLettersChanged",
+            Event::SyncRootRepaired { .. } => "SyncRootRepaired",
+            Event::ShellServiceHealthChanged { .. } => "ShellServiceHealthChanged",
+            Event::FileSynced { .. } => "FileSynced",
+            Event::FileDownloadStarted { .. } => "FileDownloadStarted",
+            Event::FileDownloadFinished { .. } => "FileDownloadFinished",
+            Event::UploadFailed { .. } => "UploadFailed",
+            Event::ConflictDetected { .. } => "ConflictDetected",
+            Event::DriveAdded { .. } => "DriveAdded",
+            Event::DriveRemoved { .. } => "DriveRemoved",
+            Event::DriveUpdated { .. } => "DriveUpdated",
+            Event::CredentialExpired { .. } => "CredentialExpired",
+            Event::QuotaWarning { .. } => "QuotaWarning",
+            Event::SyncPauseChanged { .. } => "SyncPauseChanged",
+            Event::FullResync { .. } => "FullResync",
+            Event::FullResyncProgress { .. } => "FullResyncProgress",
+            Event::UpdateAvailable { .. } => "UpdateAvailable",
+            Event::Repeated { .. } => "Repeated",
+            Event::TaskProgressBatch { .. } => "TaskProgressBatch",
+            Event::FilesSynced { .. } => "FilesSynced",
+        }
+    }
+}
+
+/// Selects the events yielded by [`EventBroadcaster::subscribe_filtered`]. An empty
+/// filter yields every event.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    names: Option<Vec<&'static str>>,
+    drive_id: Option<String>,
+}
+
+impl EventFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only events whose [`Event::name`] is one of `names`
+    pub fn with_names(mut self, names: &[&'static str]) -> Self {
+        self.names = Some(names.to_vec());
+        self
+    }
+
+    /// Only events of `drive_id`. App-wide events, which belong to no drive, still pass.
+    pub fn with_drive(mut self, drive_id: impl Into<String>) -> Self {
+        self.drive_id = Some(drive_id.into());
+        self
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        let name_matches = self
+            .names
+            .as_ref()
+            .is_none_or(|names| names.contains(&event.name()));
+        let drive_matches = match (&self.drive_id, event.drive_id()) {
+            (Some(wanted), Some(drive_id)) => wanted == drive_id,
+            _ => true,
+        };
+        name_matches && drive_matches
+    }
+}
+
+/// A subscription that skips the events its [`EventFilter`] does not match
+pub struct FilteredReceiver {
+    receiver: broadcast::Receiver<Event>,
+    filter: EventFilter,
+}
+
+impl FilteredReceiver {
+    /// Wait for the next matching event. Lagging behind is reported as for an
+    /// unfiltered receiver, skipped events may or may not have matched.
+    pub async fn recv(&mut self) -> Result<Event, broadcast::error::RecvError> {
+        loop {
+            let event = self.receiver.recv().await?;
+            if self.filter.matches(&event) {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Blocking version of [`FilteredReceiver::recv`], for threads outside the runtime
+    pub fn blocking_recv(&mut self) -> Result<Event, broadcast::error::RecvError> {
+        loop {
+            let event = self.receiver.blocking_recv()?;
+            if self.filter.matches(&event) {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Take the next matching event that was already broadcast, if any
+    pub fn try_recv(&mut self) -> Result<Event, broadcast::error::TryRecvError> {
+        loop {
+            let event = self.receiver.try_recv()?;
+            if self.filter.matches(&event) {
+                return Ok(event);
+            }
         }
     }
 }
@@ -33,6 +460,15 @@ impl Event {
 #[derive(Clone)]
 pub struct EventBroadcaster {
     sender: Arc<broadcast::Sender<Event>>,
+    /// The same events with their log id, for subscribers that resume from the log
+    sequenced: Arc<broadcast::Sender<SequencedEvent>>,
+    /// Keeps the last events for [`EventBroadcaster::replay`], if set
+    log: Option<Arc<InventoryDb>>,
+    /// Hands events to the thread appending them to `log`, so broadcasting never waits
+    /// on the database. Set together with `log`.
+    log_writer: Option<mpsc::Sender<Event>>,
+    /// Holds back repeating warnings and errors, if set
+    dedup: Option<Arc<Mutex<EventDeduper>>>,
 }
 
 impl EventBroadcaster {
@@ -42,8 +478,99 @@ impl EventBroadcaster {
     /// * `capacity` - The capacity of the broadcast channel (default: 100)
     pub fn new(capacity: usize) -> Self {
         let (sender, _) = broadcast::channel(capacity);
+        let (sequenced, _) = broadcast::channel(capacity);
         Self {
             sender: Arc::new(sender),
+            sequenced: Arc::new(sequenced),
+            log: None,
+            log_writer: None,
+            dedup: None,
+        }
+    }
+
+    /// Keep the last events in `log` (see [`InventoryDb::for_event_log`]), so a UI that
+    /// opens or reconnects can catch up on the events it missed. Events are appended by
+    /// a background thread, which also sends them to sequenced subscribers once they
+    /// have their log id.
+    pub fn with_log(mut self, log: InventoryDb) -> Self {
+        let log = Arc::new(log);
+        let (writer, events) = mpsc::channel();
+        let sequenced = self.sequenced.clone();
+        let thread_log = log.clone();
+        let spawned = thread::Builder::new()
+            .name("event-log".into())
+            .spawn(move || Self::write_log(&thread_log, &sequenced, events));
+        match spawned {
+            Ok(_) => {
+                self.log = Some(log);
+                self.log_writer = Some(writer);
+            }
+            Err(e) => {
+                tracing::warn!(target: "events", error = %e, "Failed to start the event log writer, events will not be logged");
+            }
+        }
+        self
+    }
+
+    /// Append the logged events to `log` and pass every event on to the sequenced
+    /// subscribers, in broadcast order, until the broadcaster is dropped
+    fn write_log(
+        log: &InventoryDb,
+        sequenced: &broadcast::Sender<SequencedEvent>,
+        events: mpsc::Receiver<Event>,
+    ) {
+        for event in events {
+            let id = if event.is_logged() {
+                log.append_event(&event)
+                    .inspect_err(|e| {
+                        tracing::warn!(target: "events", error = %e, "Failed to append event to the log");
+                    })
+                    .ok()
+            } else {
+                None
+            };
+            if sequenced.receiver_count() > 0 {
+                let _ = sequenced.send(SequencedEvent { id, event });
+            }
+        }
+    }
+
+    /// Collapse identical warnings and errors repeating within `window`, see
+    /// [`EventDeduper`]. Call [`EventBroadcaster::spawn_repeat_flusher`] so the repeats
+    /// are reported when no further event is broadcast.
+    pub fn with_dedup(mut self, window: std::time::Duration) -> Self {
+        self.dedup = Some(Arc::new(Mutex::new(EventDeduper::new(window))));
+        self
+    }
+
+    /// Spawn a task that reports the repeats held back by the deduper once their
+    /// window ends. Does nothing without [`EventBroadcaster::with_dedup`].
+    pub fn spawn_repeat_flusher(&self) {
+        let Some(dedup) = &self.dedup else {
+            return;
+        };
+        let period = dedup.lock().unwrap_or_else(|e| e.into_inner()).window() / 2;
+        let broadcaster = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                broadcaster.flush_repeated();
+            }
+        });
+    }
+
+    /// Broadcast an [`Event::Repeated`] for the held back repeats whose window ended
+    pub fn flush_repeated(&self) {
+        let Some(dedup) = &self.dedup else {
+            return;
+        };
+        let repeated = dedup
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take_expired(Instant::now());
+        for event in repeated {
+            self.send(event);
         }
     }
 
@@ -58,8 +585,42 @@ impl EventBroadcaster {
     /// * `event` - The event to broadcast
     ///
     /// # Returns
-    /// The number of receivers that received the event
+    /// The number of receivers that received the event, 0 if it was held back as a
+    /// repeat
     pub fn broadcast(&self, event: Event) -> usize {
+        if let Some(dedup) = &self.dedup {
+            let now = Instant::now();
+            let (repeated, admitted) = {
+                let mut dedup = dedup.lock().unwrap_or_else(|e| e.into_inner());
+                (dedup.take_expired(now), dedup.admit(&event, now))
+            };
+            for repeated in repeated {
+                self.send(repeated);
+            }
+            if !admitted {
+                tracing::trace!(target: "events", event = ?event, "Held back repeated event");
+                return 0;
+            }
+        }
+        self.send(event)
+    }
+
+    fn send(&self, event: Event) -> usize {
+        match &self.log_writer {
+            Some(writer) => {
+                if writer.send(event.clone()).is_err() {
+                    tracing::warn!(target: "events", "Event log writer stopped, event not logged");
+                }
+            }
+            None if self.sequenced.receiver_count() > 0 => {
+                let _ = self.sequenced.send(SequencedEvent {
+                    id: None,
+                    event: event.clone(),
+                });
+            }
+            None => {}
+        }
+
         match self.sender.send(event.clone()) {
             Ok(count) => {
                 tracing::debug!(target: "events", subscribers = count, "Broadcast event to subscriber(s)");
@@ -73,6 +634,45 @@ impl EventBroadcaster {
         }
     }
 
+    /// Subscribe to the events matching `filter` only
+    pub fn subscribe_filtered(&self, filter: EventFilter) -> FilteredReceiver {
+        FilteredReceiver {
+            receiver: self.subscribe(),
+            filter,
+        }
+    }
+
+    /// Logged events, oldest first: those after the event `after`, or the last `limit`
+    /// events. Empty if the broadcaster has no log.
+    pub fn replay(&self, after: Option<i64>, limit: usize) -> Vec<LoggedEvent> {
+        let Some(log) = &self.log else {
+            return Vec::new();
+        };
+        log.list_events(after, limit as i64).unwrap_or_else(|e| {
+            tracing::warn!(target: "events", error = %e, "Failed to read the event log");
+            Vec::new()
+        })
+    }
+
+    /// Subscribe to events together with the id they were logged under, which a
+    /// subscriber can later pass to [`EventBroadcaster::replay`] to resume
+    pub fn subscribe_sequenced(&self) -> broadcast::Receiver<SequencedEvent> {
+        self.sequenced.subscribe()
+    }
+
+    /// Subscribe to events, together with the logged events after `after` that the
+    /// subscriber missed, e.g. for an SSE client reconnecting with `Last-Event-ID`.
+    /// Events broadcast while the log is read are both replayed and received, skip the
+    /// received ones whose id is not above the last replayed id.
+    pub fn subscribe_with_replay(
+        &self,
+        after: Option<i64>,
+        limit: usize,
+    ) -> (Vec<LoggedEvent>, broadcast::Receiver<SequencedEvent>) {
+        let receiver = self.subscribe_sequenced();
+        (self.replay(after, limit), receiver)
+    }
+
     /// Helper: Broadcast no drive event
     pub fn no_drive(&self) {
         self.broadcast(Event::NoDrive {  });
@@ -93,6 +693,259 @@ impl EventBroadcaster {
         self.broadcast(Event::OpenSettingsWindow);
     }
 
+    /// Helper: Broadcast restart to update event
+    pub fn restart_to_update(&self) {
+        self.broadcast(Event::RestartToUpdate);
+    }
+
+    /// Helper: Broadcast open conflicts window event
+    pub fn open_conflicts_window(&self, local_path: &str) {
+        self.broadcast(Event::OpenConflictsWindow {
+            local_path: local_path.to_string(),
+        });
+    }
+
+    /// Helper: Broadcast open send-to window event
+    pub fn open_send_to_window(&self, local_paths: &[PathBuf]) {
+        self.broadcast(Event::OpenSendToWindow {
+            local_paths: local_paths
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect(),
+        });
+    }
+
+    /// Helper: Broadcast open reauthorize window event
+    pub fn open_reauthorize_window(&self, drive_id: &str, site_url: &str, drive_name: &str) {
+        self.broadcast(Event::OpenReauthorizeWindow {
+            drive_id: drive_id.to_string(),
+            site_url: site_url.to_string(),
+            drive_name: drive_name.to_string(),
+        });
+    }
+
+    /// Helper: Broadcast inventory cleanup result
+    pub fn inventory_cleaned(
+        &self,
+        drive_id: &str,
+        removed_entries: usize,
+        removed_sessions: usize,
+    ) {
+        self.broadcast(Event::InventoryCleaned {
+            drive_id: drive_id.to_string(),
+            removed_entries,
+            removed_sessions,
+        });
+    }
+
+    /// Helper: Broadcast sync root registration repaired
+    pub fn sync_root_repaired(&self, drive_id: &str, restamped: usize) {
+        self.broadcast(Event::SyncRootRepaired {
+            drive_id: drive_id.to_string(),
+            restamped,
+        });
+    }
+
+    /// Helper: Broadcast shell service health change
+    pub fn shell_service_health_changed(
+        &self,
+        healthy: bool,
+        restarts: u32,
+        error: Option<String>,
+    ) {
+        self.broadcast(Event::ShellServiceHealthChanged {
+            healthy,
+            restarts,
+            error,
+        });
+    }
+
+    /// Helper: Broadcast inventory entries written
+    pub fn inventory_entries_changed(
+        &self,
+        drive_id: &str,
+        change: EntryChange,
+        local_paths: Vec<String>,
+    ) {
+        self.broadcast(Event::InventoryEntriesChanged {
+            drive_id: drive_id.to_string(),
+            change,
+            local_paths,
+        });
+    }
+
+    /// Helper: Broadcast task finished event
+    pub fn task_finished(&self, drive_id: &str, task_id: &str, status: TaskStatus) {
+        self.broadcast(Event::TaskFinished {
+            drive_id: drive_id.to_string(),
+            task_id: task_id.to_string(),
+            status,
+        });
+    }
+
+    /// Helper: Broadcast tasks moved to a status in bulk
+    pub fn tasks_updated(&self, drive_id: &str, status: TaskStatus, task_ids: Vec<String>) {
+        self.broadcast(Event::TasksUpdated {
+            drive_id: drive_id.to_string(),
+            status,
+            task_ids,
+        });
+    }
+
+    /// Helper: Broadcast dead-letter store size
+    pub fn dead_letters_changed(&self, drive_id: &str, count: usize) {
+        self.broadcast(Event::DeadLettersChanged {
+            drive_id: drive_id.to_string(),
+            count,
+        });
+    }
+
+    /// Helper: Broadcast task progress event
+    pub fn task_progress(&self, drive_id: &str, progress: TaskProgress) {
+        self.broadcast(Event::TaskProgress {
+            drive_id: drive_id.to_string(),
+            progress,
+        });
+    }
+
+    /// Helper: Broadcast a file synced by a completed task
+    pub fn file_synced(&self, drive_id: &str, local_path: &str, kind: TaskKind) {
+        self.broadcast(Event::FileSynced {
+            drive_id: drive_id.to_string(),
+            local_path: local_path.to_string(),
+            kind,
+        });
+    }
+
+    /// Helper: Broadcast download task started
+    pub fn file_download_started(&self, drive_id: &str, task_id: &str, local_path: &str) {
+        self.broadcast(Event::FileDownloadStarted {
+            drive_id: drive_id.to_string(),
+            task_id: task_id.to_string(),
+            local_path: local_path.to_string(),
+        });
+    }
+
+    /// Helper: Broadcast download task finished
+    pub fn file_download_finished(
+        &self,
+        drive_id: &str,
+        task_id: &str,
+        local_path: &str,
+        error: Option<String>,
+    ) {
+        self.broadcast(Event::FileDownloadFinished {
+            drive_id: drive_id.to_string(),
+            task_id: task_id.to_string(),
+            local_path: local_path.to_string(),
+            error,
+        });
+    }
+
+    /// Helper: Broadcast upload task failed for good
+    pub fn upload_failed(&self, drive_id: &str, task_id: &str, local_path: &str, error: &str) {
+        self.broadcast(Event::UploadFailed {
+            drive_id: drive_id.to_string(),
+            task_id: task_id.to_string(),
+            local_path: local_path.to_string(),
+            error: error.to_string(),
+        });
+    }
+
+    /// Helper: Broadcast conflict recorded
+    pub fn conflict_detected(
+        &self,
+        drive_id: &str,
+        conflict_id: i64,
+        local_path: &str,
+        kind: ConflictKind,
+        resolution: ConflictResolution,
+    ) {
+        self.broadcast(Event::ConflictDetected {
+            drive_id: drive_id.to_string(),
+            conflict_id,
+            local_path: local_path.to_string(),
+            kind,
+            resolution,
+        });
+    }
+
+    /// Helper: Broadcast drive mounted
+    pub fn drive_added(&self, drive_id: &str, name: &str) {
+        self.broadcast(Event::DriveAdded {
+            drive_id: drive_id.to_string(),
+            name: name.to_string(),
+        });
+    }
+
+    /// Helper: Broadcast drive removed
+    pub fn drive_removed(&self, drive_id: &str, name: &str) {
+        self.broadcast(Event::DriveRemoved {
+            drive_id: drive_id.to_string(),
+            name: name.to_string(),
+        });
+    }
+
+    /// Helper: Broadcast drive display settings changed
+    pub fn drive_updated(&self, drive_id: &str, name: &str) {
+        self.broadcast(Event::DriveUpdated {
+            drive_id: drive_id.to_string(),
+            name: name.to_string(),
+        });
+    }
+
+    /// Helper: Broadcast drive credential expired
+    pub fn credential_expired(&self, drive_id: &str, drive_name: &str) {
+        self.broadcast(Event::CredentialExpired {
+            drive_id: drive_id.to_string(),
+            drive_name: drive_name.to_string(),
+        });
+    }
+
+    /// Helper: Broadcast drive capacity running low
+    pub fn quota_warning(&self, drive_id: &str, used: i64, total: i64) {
+        self.broadcast(Event::QuotaWarning {
+            drive_id: drive_id.to_string(),
+            used,
+            total,
+        });
+    }
+
+    /// Helper: Broadcast syncing of a drive paused or resumed
+    pub fn sync_pause_changed(&self, drive_id: &str, paused: bool, until: Option<i64>) {
+        self.broadcast(Event::SyncPauseChanged {
+            drive_id: drive_id.to_string(),
+            paused,
+            until,
+        });
+    }
+
+    /// Helper: Broadcast a full resync of a drive started or finished
+    pub fn full_resync(&self, drive_id: &str, running: bool, error: Option<String>) {
+        self.broadcast(Event::FullResync {
+            drive_id: drive_id.to_string(),
+            running,
+            error,
+        });
+    }
+
+    /// Helper: Broadcast the progress of a running full resync of a drive
+    pub fn full_resync_progress(&self, drive_id: &str, items_walked: u64, tasks_queued: u64) {
+        self.broadcast(Event::FullResyncProgress {
+            drive_id: drive_id.to_string(),
+            items_walked,
+            tasks_queued,
+        });
+    }
+
+    /// Helper: Broadcast a newer version of the app was found or downloaded
+    pub fn update_available(&self, version: &str, ready: bool) {
+        self.broadcast(Event::UpdateAvailable {
+            version: version.to_string(),
+            ready,
+        });
+    }
+
     /// Get the number of active subscribers
     pub fn subscriber_count(&self) -> usize {
         self.sender.receiver_count()
@@ -103,4 +956,67 @@ impl Default for EventBroadcaster {
     fn default() -> Self {
         Self::new(100)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filtered_subscription_skips_other_types_and_drives() {
+        let broadcaster = EventBroadcaster::new(16);
+        let mut rx = broadcaster.subscribe_filtered(
+            EventFilter::new()
+                .with_names(&["DeadLettersChanged", "ShellServiceHealthChanged"])
+                .with_drive("a"),
+        );
+
+        broadcaster.dead_letters_changed("b", 1);
+        broadcaster.task_finished("a", "t1", TaskStatus::Completed);
+        broadcaster.dead_letters_changed("a", 2);
+        broadcaster.shell_service_health_changed(true, 0, None);
+
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(Event::DeadLettersChanged { count: 2, .. })
+        ));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(Event::ShellServiceHealthChanged { .. })
+        ));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn sequenced_events_carry_their_log_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let broadcaster = EventBroadcaster::new(16)
+            .with_log(InventoryDb::with_path(dir.path().join("events.db")).unwrap());
+
+        // Events reach sequenced subscribers once the writer logged them
+        let mut logged = broadcaster.subscribe_sequenced();
+        broadcaster.dead_letters_changed("a", 1);
+        let first = logged.blocking_recv().unwrap().id.unwrap();
+        broadcaster.task_finished("a", "t1", TaskStatus::Completed);
+        broadcaster.dead_letters_changed("a", 2);
+        assert!(logged.blocking_recv().unwrap().id.is_none());
+        assert_eq!(logged.blocking_recv().unwrap().id, Some(first + 1));
+
+        let (replayed, mut rx) = broadcaster.subscribe_with_replay(Some(first), 10);
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].id, first + 1);
+
+        broadcaster.open_settings_window();
+        broadcaster.dead_letters_changed("a", 3);
+        assert!(matches!(
+            rx.blocking_recv(),
+            Ok(SequencedEvent {
+                id: None,
+                event: Event::OpenSettingsWindow
+            })
+        ));
+        assert!(
+            matches!(rx.blocking_recv(), Ok(SequencedEvent { id: Some(id), .. }) if id == first + 2)
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Versioning of the events sent to frontends, the SSE stream and webhooks.
+//!
+//! Within a schema version, events and fields are only ever added, consumers should
+//! ignore the ones they don't know. Changes that would break a consumer, such as
+//! replacing an event by another one, bump [`EVENT_SCHEMA_VERSION`], and
+//! [`Event::downgrade`] converts the new events for consumers of older versions.
+//!
+//! - 1: the initial events
+//! - 2: [`Event::TaskProgressBatch`] and [`Event::FilesSynced`] replace the single
+//!   progress and synced file events emitted to the UI, [`Event::Repeated`] reports
+//!   repeats held back by the deduper
+
+use super::Event;
+use serde::Serialize;
+
+/// The version of the events as serialized by [`VersionedEvent`]
+pub const EVENT_SCHEMA_VERSION: u32 = 2;
+
+/// An event as sent to consumers: `{"schema": 2, "type": "...", "data": {...}}`
+#[derive(Debug, Serialize)]
+pub struct VersionedEvent<'a> {
+    pub schema: u32,
+    #[serde(flatten)]
+    pub event: &'a Event,
+}
+
+impl<'a> VersionedEvent<'a> {
+    /// `event` for a consumer of [`EVENT_SCHEMA_VERSION`]
+    pub fn current(event: &'a Event) -> Self {
+        Self {
+            schema: EVENT_SCHEMA_VERSION,
+            event,
+        }
+    }
+}
+
+impl Event {
+    /// The schema version that introduced the event
+    pub fn since_schema(&self) -> u32 {
+        match self {
+            Event::Repeated { .. }
+            | Event::TaskProgressBatch { .. }
+            | Event::FilesSynced { .. } => 2,
+            _ => 1,
+        }
+    }
+
+    /// The event as a consumer of schema `version` understands it. Batches are split
+    /// into the events they replaced, other events newer than `version` are dropped.
+    pub fn downgrade(&self, version: u32) -> Vec<Event> {
+        if self.since_schema() <= version {
+            return vec![self.clone()];
+        }
+        match self {
+            Event::TaskProgressBatch { drive_id, progress } => progress
+                .iter()
+                .map(|progress| Event::TaskProgress {
+                    drive_id: drive_id.clone(),
+                    progress: progress.clone(),
+                })
+                .collect(),
+            Event::FilesSynced { drive_id, files } => files
+                .iter()
+                .map(|file| Event::FileSynced {
+                    drive_id: drive_id.clone(),
+                    local_path: file.local_path.clone(),
+                    kind: file.kind,
+                })
+                .collect(),
+            // The first of the repeats was sent as usual
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::SyncedFile;
+    use crate::tasks::TaskKind;
+
+    #[test]
+    fn newer_events_are_downgraded() {
+        let batch = Event::FilesSynced {
+            drive_id: "drive".to_string(),
+            files: vec![
+                SyncedFile {
+                    local_path: "C:\\a.txt".to_string(),
+                    kind: TaskKind::Upload,
+                },
+                SyncedFile {
+                    local_path: "C:\\b.txt".to_string(),
+                    kind: TaskKind::Download,
+                },
+            ],
+        };
+        assert_eq!(batch.downgrade(EVENT_SCHEMA_VERSION).len(), 1);
+        let single = batch.downgrade(1);
+        assert_eq!(single.len(), 2);
+        assert!(single.iter().all(|event| event.name() == "FileSynced"));
+
+        let repeated = Event::Repeated {
+            event: Box::new(Event::NoDrive {}),
+            count: 2,
+        };
+        assert!(repeated.downgrade(1).is_empty());
+
+        let json = serde_json::to_value(VersionedEvent::current(&single[0])).unwrap();
+        assert_eq!(json["schema"], EVENT_SCHEMA_VERSION);
+        assert_eq!(json["type"], "FileSynced");
+        assert_eq!(json["data"]["local_path"], "C:\\a.txt");
+    }
+}
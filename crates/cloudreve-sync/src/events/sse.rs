@@ -0,0 +1,110 @@
+//! Server-Sent Events stream of the broadcast events, for dashboards.
+//!
+//! Logged events are sent with their log id as the SSE event id. A client that
+//! reconnects with the `Last-Event-ID` header first gets the logged events it missed,
+//! as far as the log still holds them. Events that are not logged, such as task
+//! progress, are sent without an id.
+//!
+//! Clients pass the event schema version they understand as `?schema=`, see
+//! [`EVENT_SCHEMA_VERSION`]. Clients that don't get the events of schema version 1.
+
+use super::{EVENT_SCHEMA_VERSION, Event, EventBroadcaster, SequencedEvent, VersionedEvent};
+use axum::{
+    Router,
+    extract::{Query, State},
+    http::HeaderMap,
+    response::sse::{Event as SseEvent, KeepAlive, Sse},
+    routing::get,
+};
+use futures::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
+
+const LAST_EVENT_ID: &str = "Last-Event-ID";
+
+/// Logged events replayed to a reconnecting client at most
+const MAX_REPLAYED_EVENTS: usize = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    /// Event schema version of the client
+    #[serde(default = "EventsQuery::default_schema")]
+    schema: u32,
+}
+
+impl EventsQuery {
+    fn default_schema() -> u32 {
+        1
+    }
+}
+
+/// Routes serving the event stream at `/api/events`
+pub fn routes() -> Router<Arc<EventBroadcaster>> {
+    Router::new().route("/api/events", get(events_handler))
+}
+
+/// `GET /api/events`
+pub async fn events_handler(
+    State(broadcaster): State<Arc<EventBroadcaster>>,
+    Query(query): Query<EventsQuery>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<SseEvent, Infallible>>> {
+    let last_event_id = headers
+        .get(LAST_EVENT_ID)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<i64>().ok());
+
+    let (replayed, receiver) = match last_event_id {
+        Some(after) => broadcaster.subscribe_with_replay(Some(after), MAX_REPLAYED_EVENTS),
+        None => (Vec::new(), broadcaster.subscribe_sequenced()),
+    };
+    tracing::debug!(target: "api::sse", last_event_id = ?last_event_id, replayed = replayed.len(), "SSE client connected");
+
+    // Events logged while the replay was read arrive on the receiver as well
+    let last_replayed = replayed.last().map(|logged| logged.id).or(last_event_id);
+
+    let schema = query.schema.min(EVENT_SCHEMA_VERSION);
+    let replayed = stream::iter(replayed).map(|logged| (Some(logged.id), logged.event));
+    let live = BroadcastStream::new(receiver).map(move |received| match received {
+        Ok(SequencedEvent { id: Some(id), .. })
+            if last_replayed.is_some_and(|last| id <= last) =>
+        {
+            None
+        }
+        Ok(SequencedEvent { id, event }) => Some((id, event)),
+        Err(BroadcastStreamRecvError::Lagged(n)) => {
+            tracing::warn!(target: "api::sse", skipped = n, "SSE client lagged, some events were skipped");
+            None
+        }
+    });
+
+    let events = replayed
+        .chain(live.filter_map(futures::future::ready))
+        .flat_map(move |(id, event)| {
+            let events: Vec<_> = event
+                .downgrade(schema)
+                .iter()
+                .filter_map(|event| to_sse(id, schema, event))
+                .map(Ok)
+                .collect();
+            stream::iter(events)
+        });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+fn to_sse(id: Option<i64>, schema: u32, event: &Event) -> Option<SseEvent> {
+    let versioned = VersionedEvent { schema, event };
+    let mut sse_event = match SseEvent::default().event(event.name()).json_data(versioned) {
+        Ok(sse_event) => sse_event,
+        Err(e) => {
+            tracing::warn!(target: "api::sse", error = %e, "Failed to serialize event");
+            return None;
+        }
+    };
+    if let Some(id) = id {
+        sse_event = sse_event.id(id.to_string());
+    }
+    Some(sse_event)
+}
@@ -0,0 +1,134 @@
+//! Forwards selected events to a user-configured webhook, e.g. for homelab alerting.
+//!
+//! Every event whose name is listed in [`WebhookConfig::events`] is POSTed as JSON:
+//!
+//! ```json
+//! { "timestamp": 1760000000, "event": { "schema": 2, "type": "UploadFailed", "data": { ... } } }
+//! ```
+//!
+//! The event name is also sent in the `X-Cloudreve-Event` header. With a secret, the
+//! body is signed in `X-Cloudreve-Signature: sha256=<hex HMAC-SHA256 of the body>`.
+
+use super::{Event, EventBroadcaster, VersionedEvent};
+use crate::config::{ConfigManager, WebhookConfig};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+pub const EVENT_HEADER: &str = "X-Cloudreve-Event";
+pub const SIGNATURE_HEADER: &str = "X-Cloudreve-Signature";
+
+/// Attempts to deliver an event before it is dropped
+const MAX_ATTEMPTS: u32 = 3;
+/// Delay before the first retry, doubled for every further retry
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Spawn a task that forwards events to the webhook in the config. The config is read
+/// for every event, so changes apply without restarting the task.
+pub fn spawn_dispatcher(event_broadcaster: &EventBroadcaster) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .user_agent(crate::USER_AGENT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .context("Failed to create HTTP client")?;
+    let mut receiver = event_broadcaster.subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(n)) => {
+                    tracing::warn!(target: "events::webhook", skipped = n, "Webhook dispatcher lagged, some events were not forwarded");
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+
+            let Some(webhook) = ConfigManager::try_get().and_then(|config| config.webhook()) else {
+                continue;
+            };
+            if !webhook.events.iter().any(|name| name == event.name()) {
+                continue;
+            }
+
+            // Deliveries are retried with a delay, don't hold up the following events
+            tokio::spawn(deliver(client.clone(), webhook, event));
+        }
+    });
+
+    Ok(())
+}
+
+async fn deliver(client: reqwest::Client, webhook: WebhookConfig, event: Event) {
+    let body = match serde_json::to_vec(&serde_json::json!({
+        "timestamp": Utc::now().timestamp(),
+        "event": VersionedEvent::current(&event),
+    })) {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::warn!(target: "events::webhook", error = %e, "Failed to serialize event for the webhook");
+            return;
+        }
+    };
+
+    let mut delay = RETRY_BASE_DELAY;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event.name())
+            .body(body.clone());
+        if let Some(secret) = webhook.secret.as_deref().filter(|s| !s.is_empty()) {
+            request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &body)));
+        }
+
+        let retryable = match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                tracing::debug!(target: "events::webhook", event = event.name(), attempt, "Event forwarded to the webhook");
+                return;
+            }
+            Ok(response) => {
+                let status = response.status();
+                tracing::warn!(target: "events::webhook", event = event.name(), attempt, status = %status, "Webhook rejected the event");
+                status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            Err(e) => {
+                tracing::warn!(target: "events::webhook", event = event.name(), attempt, error = %e, "Failed to reach the webhook");
+                true
+            }
+        };
+
+        if !retryable || attempt == MAX_ATTEMPTS {
+            break;
+        }
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+
+    tracing::error!(target: "events::webhook", event = event.name(), "Giving up forwarding the event to the webhook");
+}
+
+/// Hex HMAC-SHA256 of `body` keyed with `secret`
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_matches_rfc_4231() {
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -0,0 +1,372 @@
+use super::tasks::finished_status_strings;
+use super::{InventoryDb, LIKE_ESCAPE, escape_like};
+use crate::inventory::{
+    ActivityEntry, ActivityFilter, ActivityKind, ActivityPage, ChangeSource, ConflictKind,
+    ConflictResolution, JournalOperation, PageRequest, TaskStatus,
+};
+use anyhow::{Context, Result, anyhow};
+use diesel::prelude::*;
+use diesel::sqlite::{Sqlite, SqliteConnection};
+
+use crate::inventory::schema::change_journal::{self, dsl as change_journal_dsl};
+use crate::inventory::schema::conflicts::{self, dsl as conflicts_dsl};
+use crate::inventory::schema::task_queue::{self, dsl as task_queue_dsl};
+use crate::tasks::TaskKind;
+
+/// Deletions made while repairing or importing the inventory, which are not activity
+/// the user would recognize
+const HIDDEN_DELETE_SOURCES: [ChangeSource; 2] = [ChangeSource::Repair, ChangeSource::Import];
+
+impl InventoryDb {
+    /// A page of the activity feed of drive `drive_id`, newest first: finished uploads
+    /// and downloads from the task history, deletions from the change journal, and
+    /// conflicts. Journal records carry no drive, so this expects the drive's own
+    /// database.
+    pub fn query_activity(
+        &self,
+        drive_id: &str,
+        filter: &ActivityFilter,
+        page: PageRequest,
+    ) -> Result<ActivityPage> {
+        let page = page.normalized();
+        // Every source contributes at most the first `offset + limit` events
+        let window = page.offset + page.limit;
+        let mut conn = self.connection()?;
+        let mut total = 0;
+        let mut entries = Vec::new();
+
+        let task_types = activity_task_types(filter);
+        if !task_types.is_empty() {
+            total += filtered_tasks(drive_id, filter, &task_types)
+                .count()
+                .get_result::<i64>(&mut conn)
+                .context("Failed to count finished tasks")?;
+            entries.extend(task_activity(
+                &mut conn,
+                drive_id,
+                filter,
+                &task_types,
+                window,
+            )?);
+        }
+        if filter.includes(ActivityKind::Delete) {
+            total += filtered_deletes(filter)
+                .count()
+                .get_result::<i64>(&mut conn)
+                .context("Failed to count journaled deletions")?;
+            entries.extend(delete_activity(&mut conn, drive_id, filter, window)?);
+        }
+        if filter.includes(ActivityKind::Conflict) {
+            total += filtered_conflicts(drive_id, filter)
+                .count()
+                .get_result::<i64>(&mut conn)
+                .context("Failed to count conflict records")?;
+            entries.extend(conflict_activity(&mut conn, drive_id, filter, window)?);
+        }
+
+        entries.sort_by(|a, b| b.occurred_at.cmp(&a.occurred_at));
+        let entries = entries
+            .into_iter()
+            .skip(page.offset as usize)
+            .take(page.limit as usize)
+            .collect();
+        Ok(ActivityPage {
+            entries,
+            total,
+            offset: page.offset,
+            limit: page.limit,
+        })
+    }
+}
+
+/// Task types shown in the feed under the filter's kinds
+fn activity_task_types(filter: &ActivityFilter) -> Vec<&'static str> {
+    let mut task_types = Vec::new();
+    if filter.includes(ActivityKind::Upload) {
+        task_types.push(TaskKind::Upload.as_str());
+    }
+    if filter.includes(ActivityKind::Download) {
+        task_types.extend([TaskKind::Download.as_str(), TaskKind::Hydrate.as_str()]);
+    }
+    task_types
+}
+
+/// `path_prefix` of the filter and the LIKE pattern matching its descendants, escaped
+/// with [`LIKE_ESCAPE`]
+fn path_patterns(filter: &ActivityFilter) -> Option<(String, String)> {
+    filter.path_prefix.as_deref().map(|path| {
+        let path = path.trim_end_matches(std::path::MAIN_SEPARATOR);
+        let descendant_like = format!("{}{}%", escape_like(path), std::path::MAIN_SEPARATOR);
+        (path.to_string(), descendant_like)
+    })
+}
+
+fn filtered_tasks<'a>(
+    drive_id: &'a str,
+    filter: &ActivityFilter,
+    task_types: &[&'static str],
+) -> task_queue::BoxedQuery<'a, Sqlite> {
+    let mut query = task_queue_dsl::task_queue
+        .filter(task_queue_dsl::drive_id.eq(drive_id))
+        .filter(task_queue_dsl::task_type.eq_any(task_types.to_vec()))
+        .filter(task_queue_dsl::status.eq_any(finished_status_strings()))
+        .into_boxed();
+    if let Some((path, descendant_like)) = path_patterns(filter) {
+        query = query.filter(
+            task_queue_dsl::local_path
+                .eq(path)
+                .or(task_queue_dsl::local_path
+                    .like(descendant_like)
+                    .escape(LIKE_ESCAPE)),
+        );
+    }
+    query
+}
+
+fn filtered_deletes(filter: &ActivityFilter) -> change_journal::BoxedQuery<'static, Sqlite> {
+    let hidden_sources: Vec<&str> = HIDDEN_DELETE_SOURCES.iter().map(|s| s.as_str()).collect();
+    let mut query = change_journal_dsl::change_journal
+        .filter(change_journal_dsl::operation.eq(JournalOperation::Delete.as_str()))
+        .filter(change_journal_dsl::source.ne_all(hidden_sources))
+        .into_boxed();
+    if let Some((path, descendant_like)) = path_patterns(filter) {
+        query = query.filter(
+            change_journal_dsl::local_path
+                .eq(path)
+                .or(change_journal_dsl::local_path
+                    .like(descendant_like)
+                    .escape(LIKE_ESCAPE)),
+        );
+    }
+    query
+}
+
+fn filtered_conflicts<'a>(
+    drive_id: &'a str,
+    filter: &ActivityFilter,
+) -> conflicts::BoxedQuery<'a, Sqlite> {
+    let mut query = conflicts_dsl::conflicts
+        .filter(conflicts_dsl::drive_id.eq(drive_id))
+        .into_boxed();
+    if let Some((path, descendant_like)) = path_patterns(filter) {
+        query = query.filter(
+            conflicts_dsl::original_path
+                .eq(path)
+                .or(conflicts_dsl::original_path
+                    .like(descendant_like)
+                    .escape(LIKE_ESCAPE)),
+        );
+    }
+    query
+}
+
+fn task_activity(
+    conn: &mut SqliteConnection,
+    drive_id: &str,
+    filter: &ActivityFilter,
+    task_types: &[&'static str],
+    limit: i64,
+) -> Result<Vec<ActivityEntry>> {
+    let rows = filtered_tasks(drive_id, filter, task_types)
+        .select((
+            task_queue_dsl::id,
+            task_queue_dsl::task_type,
+            task_queue_dsl::local_path,
+            task_queue_dsl::status,
+            task_queue_dsl::error,
+            task_queue_dsl::updated_at,
+        ))
+        .order(task_queue_dsl::updated_at.desc())
+        .limit(limit)
+        .load::<(String, String, String, String, Option<String>, i64)>(conn)
+        .context("Failed to query finished tasks")?;
+
+    rows.into_iter()
+        .map(|(id, task_type, local_path, status, error, updated_at)| {
+            let kind = match TaskKind::from_str(&task_type) {
+                Some(TaskKind::Upload) => ActivityKind::Upload,
+                _ => ActivityKind::Download,
+            };
+            let status = TaskStatus::from_str(&status)
+                .ok_or_else(|| anyhow!("Unknown task status value {}", status))?;
+            Ok(ActivityEntry {
+                id: format!("task:{}", id),
+                drive_id: drive_id.to_string(),
+                kind,
+                local_path,
+                occurred_at: updated_at,
+                task_status: Some(status),
+                error,
+                source: None,
+                conflict_kind: None,
+                conflict_resolution: None,
+            })
+        })
+        .collect()
+}
+
+fn delete_activity(
+    conn: &mut SqliteConnection,
+    drive_id: &str,
+    filter: &ActivityFilter,
+    limit: i64,
+) -> Result<Vec<ActivityEntry>> {
+    let rows = filtered_deletes(filter)
+        .select((
+            change_journal_dsl::id,
+            change_journal_dsl::local_path,
+            change_journal_dsl::source,
+            change_journal_dsl::created_at,
+        ))
+        .order(change_journal_dsl::id.desc())
+        .limit(limit)
+        .load::<(i64, String, String, i64)>(conn)
+        .context("Failed to query journaled deletions")?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, local_path, source, created_at)| ActivityEntry {
+            id: format!("journal:{}", id),
+            drive_id: drive_id.to_string(),
+            kind: ActivityKind::Delete,
+            local_path,
+            occurred_at: created_at,
+            task_status: None,
+            error: None,
+            source: Some(ChangeSource::from_str(&source)),
+            conflict_kind: None,
+            conflict_resolution: None,
+        })
+        .collect())
+}
+
+fn conflict_activity(
+    conn: &mut SqliteConnection,
+    drive_id: &str,
+    filter: &ActivityFilter,
+    limit: i64,
+) -> Result<Vec<ActivityEntry>> {
+    let rows = filtered_conflicts(drive_id, filter)
+        .select((
+            conflicts_dsl::id,
+            conflicts_dsl::original_path,
+            conflicts_dsl::kind,
+            conflicts_dsl::resolution,
+            conflicts_dsl::created_at,
+        ))
+        .order(conflicts_dsl::id.desc())
+        .limit(limit)
+        .load::<(i64, String, String, String, i64)>(conn)
+        .context("Failed to query conflict records")?;
+
+    rows.into_iter()
+        .map(|(id, original_path, kind, resolution, created_at)| {
+            let kind = ConflictKind::from_str(&kind)
+                .ok_or_else(|| anyhow!("Unknown conflict kind: {}", kind))?;
+            let resolution = ConflictResolution::from_str(&resolution)
+                .ok_or_else(|| anyhow!("Unknown conflict resolution: {}", resolution))?;
+            Ok(ActivityEntry {
+                id: format!("conflict:{}", id),
+                drive_id: drive_id.to_string(),
+                kind: ActivityKind::Conflict,
+                local_path: original_path,
+                occurred_at: created_at,
+                task_status: None,
+                error: None,
+                source: None,
+                conflict_kind: Some(kind),
+                conflict_resolution: Some(resolution),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::{MetadataEntry, NewConflictRecord, NewTaskRecord, TaskUpdate};
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    #[test]
+    fn activity_merges_tasks_deletions_and_conflicts() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        let drive_id = Uuid::new_v4();
+        let drive = drive_id.to_string();
+
+        for (id, task_type, path) in [
+            ("t1", "upload", r"C:\sync\a.txt"),
+            ("t2", "hydrate", r"C:\sync\docs\b.txt"),
+            ("t3", "download", r"C:\sync\c.txt"),
+        ] {
+            db.insert_task_if_not_exist(&NewTaskRecord::new(id, &drive, task_type, path))
+                .unwrap();
+        }
+        for id in ["t1", "t2"] {
+            db.update_task(
+                id,
+                TaskUpdate {
+                    status: Some(TaskStatus::Completed),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        }
+        db.upsert(&MetadataEntry::new(drive_id, r"C:\sync\docs\d.txt", false))
+            .unwrap();
+        db.batch_delete_by_path(vec![r"C:\sync\docs\d.txt"])
+            .unwrap();
+        db.insert_conflict(&NewConflictRecord::new(
+            &drive,
+            r"C:\sync\docs\e.txt",
+            ConflictKind::Content,
+        ))
+        .unwrap();
+
+        let page = db
+            .query_activity(&drive, &ActivityFilter::default(), PageRequest::default())
+            .unwrap();
+        // The pending download is not activity yet
+        assert_eq!(page.total, 4);
+        assert_eq!(page.entries.len(), 4);
+
+        let filter = ActivityFilter {
+            kinds: vec![ActivityKind::Download, ActivityKind::Delete],
+            path_prefix: Some(r"C:\sync\docs".to_string()),
+        };
+        let page = db
+            .query_activity(&drive, &filter, PageRequest::default())
+            .unwrap();
+        let mut kinds: Vec<_> = page.entries.iter().map(|e| e.kind).collect();
+        kinds.sort_by_key(|kind| *kind as u8);
+        assert_eq!(kinds, vec![ActivityKind::Download, ActivityKind::Delete]);
+
+        let page = db
+            .query_activity(&drive, &ActivityFilter::default(), PageRequest::new(3, 2))
+            .unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert!(!page.has_more());
+    }
+
+    #[test]
+    fn path_filter_matches_wildcards_literally() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        let drive = Uuid::new_v4().to_string();
+        for path in [r"C:\sync\a_b\f.txt", r"C:\sync\axb\f.txt"] {
+            db.insert_conflict(&NewConflictRecord::new(&drive, path, ConflictKind::Content))
+                .unwrap();
+        }
+
+        let filter = ActivityFilter {
+            kinds: vec![ActivityKind::Conflict],
+            path_prefix: Some(r"C:\sync\a_b".to_string()),
+        };
+        let page = db
+            .query_activity(&drive, &filter, PageRequest::default())
+            .unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.entries[0].local_path, r"C:\sync\a_b\f.txt");
+    }
+}
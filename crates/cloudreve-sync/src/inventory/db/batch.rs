@@ -0,0 +1,144 @@
+use super::InventoryDb;
+use crate::inventory::MetadataEntry;
+use anyhow::Result;
+use std::sync::{Arc, Mutex};
+
+/// Buffers metadata upserts and writes them to the inventory in a single transaction.
+///
+/// Entries are flushed once `max_pending` is reached, on an explicit [`MetadataWriteBatch::flush`],
+/// or when the batch is dropped. Later entries for the same local path win.
+pub struct MetadataWriteBatch {
+    inventory: Arc<InventoryDb>,
+    pending: Mutex<Vec<MetadataEntry>>,
+    max_pending: usize,
+}
+
+impl MetadataWriteBatch {
+    pub const DEFAULT_MAX_PENDING: usize = 256;
+
+    pub fn new(inventory: Arc<InventoryDb>) -> Self {
+        Self {
+            inventory,
+            pending: Mutex::new(Vec::new()),
+            max_pending: Self::DEFAULT_MAX_PENDING,
+        }
+    }
+
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending.max(1);
+        self
+    }
+
+    /// Queue an upsert, flushing the batch if it is full
+    pub fn push(&self, entry: MetadataEntry) -> Result<()> {
+        let full = {
+            let mut pending = self.lock_pending();
+            pending.push(entry);
+            pending.len() >= self.max_pending
+        };
+        if full {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Write all queued entries. Returns the number of rows written.
+    pub fn flush(&self) -> Result<usize> {
+        let entries = std::mem::take(&mut *self.lock_pending());
+        if entries.is_empty() {
+            return Ok(0);
+        }
+        self.inventory.batch_upsert(&entries)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock_pending().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock_pending(&self) -> std::sync::MutexGuard<'_, Vec<MetadataEntry>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MetadataWriteBatch {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::error!(target: "inventory", error = %e, "Failed to flush pending inventory writes");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    fn entries(drive_id: Uuid, count: usize) -> Vec<MetadataEntry> {
+        (0..count)
+            .map(|i| {
+                MetadataEntry::new(drive_id, format!("/sync/file-{i}.txt"), false)
+                    .with_etag(format!("etag-{i}"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn batch_flushes_when_full_and_on_drop() {
+        let dir = tempdir().unwrap();
+        let db = Arc::new(InventoryDb::with_path(dir.path().join("meta.db")).unwrap());
+        let drive_id = Uuid::new_v4();
+
+        let batch = MetadataWriteBatch::new(db.clone()).with_max_pending(4);
+        for entry in entries(drive_id, 6) {
+            batch.push(entry).unwrap();
+        }
+        assert_eq!(batch.len(), 2);
+        assert_eq!(db.count().unwrap(), 4);
+
+        drop(batch);
+        assert_eq!(db.count().unwrap(), 6);
+    }
+
+    /// Compares per-row upserts against batched upserts.
+    /// Run with `cargo test -- --ignored --nocapture` to see the numbers.
+    #[test]
+    #[ignore]
+    fn bench_batched_upserts() {
+        const ROWS: usize = 2000;
+        let dir = tempdir().unwrap();
+        let drive_id = Uuid::new_v4();
+
+        let single = InventoryDb::with_path(dir.path().join("single.db")).unwrap();
+        let started = Instant::now();
+        for entry in entries(drive_id, ROWS) {
+            single.upsert(&entry).unwrap();
+        }
+        let single_elapsed = started.elapsed();
+
+        let batched = Arc::new(InventoryDb::with_path(dir.path().join("batched.db")).unwrap());
+        let started = Instant::now();
+        {
+            let batch = MetadataWriteBatch::new(batched.clone());
+            for entry in entries(drive_id, ROWS) {
+                batch.push(entry).unwrap();
+            }
+        }
+        let batched_elapsed = started.elapsed();
+
+        println!(
+            "{ROWS} upserts: single {:?} ({:.0} rows/s), batched {:?} ({:.0} rows/s)",
+            single_elapsed,
+            ROWS as f64 / single_elapsed.as_secs_f64(),
+            batched_elapsed,
+            ROWS as f64 / batched_elapsed.as_secs_f64(),
+        );
+        assert_eq!(batched.count().unwrap(), ROWS as i64);
+        assert!(batched_elapsed < single_elapsed);
+    }
+}
@@ -0,0 +1,259 @@
+use super::InventoryDb;
+use crate::inventory::{
+    CfapiFailureClass, CfapiFailureRecord, CfapiFailureSummary, CfapiOperation, CfapiRecovery,
+    NewCfapiFailure,
+};
+use anyhow::{Context, Result, anyhow};
+use chrono::Utc;
+use diesel::dsl::max;
+use diesel::prelude::*;
+
+use crate::inventory::schema::cfapi_failures::{self, dsl as cfapi_failures_dsl};
+
+diesel::define_sql_function!(fn last_insert_rowid() -> diesel::sql_types::BigInt);
+
+/// Failures kept per drive, older ones are dropped as new ones are recorded
+const MAX_FAILURES_PER_DRIVE: i64 = 1000;
+
+impl InventoryDb {
+    /// Record a failed cloud filter callback, dropping the oldest records of the drive
+    /// beyond the retention limit. Returns the id of the record.
+    pub fn record_cfapi_failure(&self, failure: &NewCfapiFailure) -> Result<i64> {
+        let row = NewCfapiFailureRow {
+            drive_id: failure.drive_id.clone(),
+            operation: failure.operation.as_str().to_string(),
+            local_path: failure.local_path.clone(),
+            hresult: failure.hresult,
+            class: failure.class.as_str().to_string(),
+            error: failure.error.clone(),
+            recovery: failure
+                .recovery
+                .map(|recovery| recovery.as_str().to_string()),
+            created_at: Utc::now().timestamp(),
+        };
+
+        let mut conn = self.connection()?;
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::insert_into(cfapi_failures::table)
+                .values(&row)
+                .execute(conn)?;
+            let id = diesel::select(last_insert_rowid()).get_result::<i64>(conn)?;
+            let oldest_kept = cfapi_failures_dsl::cfapi_failures
+                .filter(cfapi_failures_dsl::drive_id.eq(&row.drive_id))
+                .select(cfapi_failures_dsl::id)
+                .order(cfapi_failures_dsl::id.desc())
+                .offset(MAX_FAILURES_PER_DRIVE - 1)
+                .first::<i64>(conn)
+                .optional()?;
+            if let Some(oldest_kept) = oldest_kept {
+                diesel::delete(
+                    cfapi_failures_dsl::cfapi_failures
+                        .filter(cfapi_failures_dsl::drive_id.eq(&row.drive_id))
+                        .filter(cfapi_failures_dsl::id.lt(oldest_kept)),
+                )
+                .execute(conn)?;
+            }
+            Ok(id)
+        })
+        .context("Failed to record cloud filter failure")
+    }
+
+    /// Most recent cloud filter failures, newest first
+    pub fn list_cfapi_failures(
+        &self,
+        drive_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<CfapiFailureRecord>> {
+        let mut conn = self.connection()?;
+        let mut query = cfapi_failures_dsl::cfapi_failures.into_boxed();
+        if let Some(drive_id) = drive_id {
+            query = query.filter(cfapi_failures_dsl::drive_id.eq(drive_id));
+        }
+        let rows = query
+            .order(cfapi_failures_dsl::id.desc())
+            .limit(limit.max(1))
+            .load::<CfapiFailureRow>(&mut conn)
+            .context("Failed to list cloud filter failures")?;
+        rows.into_iter().map(CfapiFailureRecord::try_from).collect()
+    }
+
+    /// Failures per class since `since` (Unix timestamp), most frequent first
+    pub fn summarize_cfapi_failures(
+        &self,
+        drive_id: Option<&str>,
+        since: i64,
+    ) -> Result<Vec<CfapiFailureSummary>> {
+        let mut conn = self.connection()?;
+        let mut query = cfapi_failures_dsl::cfapi_failures
+            .filter(cfapi_failures_dsl::created_at.ge(since))
+            .into_boxed();
+        if let Some(drive_id) = drive_id {
+            query = query.filter(cfapi_failures_dsl::drive_id.eq(drive_id));
+        }
+        let rows = query
+            .select((cfapi_failures_dsl::class, cfapi_failures_dsl::created_at))
+            .load::<(String, i64)>(&mut conn)
+            .context("Failed to summarize cloud filter failures")?;
+
+        let mut summary: Vec<CfapiFailureSummary> = Vec::new();
+        for (class, created_at) in rows {
+            let class = CfapiFailureClass::from_str(&class)
+                .ok_or_else(|| anyhow!("Unknown cloud filter failure class: {}", class))?;
+            match summary.iter_mut().find(|entry| entry.class == class) {
+                Some(entry) => {
+                    entry.count += 1;
+                    entry.last_at = entry.last_at.max(created_at);
+                }
+                None => summary.push(CfapiFailureSummary {
+                    class,
+                    count: 1,
+                    last_at: created_at,
+                }),
+            }
+        }
+        summary.sort_by(|a, b| b.count.cmp(&a.count));
+        Ok(summary)
+    }
+
+    /// When `recovery` was last started for a drive, as a Unix timestamp
+    pub fn last_cfapi_recovery_at(
+        &self,
+        drive_id: &str,
+        recovery: CfapiRecovery,
+    ) -> Result<Option<i64>> {
+        let mut conn = self.connection()?;
+        cfapi_failures_dsl::cfapi_failures
+            .filter(cfapi_failures_dsl::drive_id.eq(drive_id))
+            .filter(cfapi_failures_dsl::recovery.eq(recovery.as_str()))
+            .select(max(cfapi_failures_dsl::created_at))
+            .first::<Option<i64>>(&mut conn)
+            .context("Failed to query last cloud filter recovery")
+    }
+
+    /// Remove the cloud filter failures of a drive, or of all drives
+    pub fn clear_cfapi_failures(&self, drive_id: Option<&str>) -> Result<usize> {
+        let mut conn = self.connection()?;
+        match drive_id {
+            Some(drive_id) => diesel::delete(
+                cfapi_failures_dsl::cfapi_failures
+                    .filter(cfapi_failures_dsl::drive_id.eq(drive_id)),
+            )
+            .execute(&mut conn),
+            None => diesel::delete(cfapi_failures_dsl::cfapi_failures).execute(&mut conn),
+        }
+        .context("Failed to clear cloud filter failures")
+    }
+}
+
+// =========================================================================
+// Row Types
+// =========================================================================
+
+#[derive(Queryable)]
+struct CfapiFailureRow {
+    id: i64,
+    drive_id: String,
+    operation: String,
+    local_path: String,
+    hresult: Option<i32>,
+    class: String,
+    error: String,
+    recovery: Option<String>,
+    created_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = cfapi_failures)]
+struct NewCfapiFailureRow {
+    drive_id: String,
+    operation: String,
+    local_path: String,
+    hresult: Option<i32>,
+    class: String,
+    error: String,
+    recovery: Option<String>,
+    created_at: i64,
+}
+
+impl TryFrom<CfapiFailureRow> for CfapiFailureRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(row: CfapiFailureRow) -> Result<Self> {
+        let operation = CfapiOperation::from_str(&row.operation)
+            .ok_or_else(|| anyhow!("Unknown cloud filter operation: {}", row.operation))?;
+        let class = CfapiFailureClass::from_str(&row.class)
+            .ok_or_else(|| anyhow!("Unknown cloud filter failure class: {}", row.class))?;
+        let recovery = row
+            .recovery
+            .map(|recovery| {
+                CfapiRecovery::from_str(&recovery)
+                    .ok_or_else(|| anyhow!("Unknown cloud filter recovery: {}", recovery))
+            })
+            .transpose()?;
+        Ok(CfapiFailureRecord {
+            id: row.id,
+            drive_id: row.drive_id,
+            operation,
+            local_path: row.local_path,
+            hresult: row.hresult,
+            class,
+            error: row.error,
+            recovery,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn records_and_summarizes_failures() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        let failure = |class| {
+            NewCfapiFailure::new(
+                "drive",
+                CfapiOperation::FetchData,
+                "/sync/a.txt",
+                class,
+                "failed",
+            )
+        };
+
+        db.record_cfapi_failure(&failure(CfapiFailureClass::Transient))
+            .unwrap();
+        db.record_cfapi_failure(
+            &failure(CfapiFailureClass::ProviderDisconnected)
+                .with_hresult(Some(0x8007016Au32 as i32))
+                .with_recovery(Some(CfapiRecovery::ReconnectSyncRoot)),
+        )
+        .unwrap();
+        db.record_cfapi_failure(&failure(CfapiFailureClass::Transient))
+            .unwrap();
+
+        let failures = db.list_cfapi_failures(Some("drive"), 10).unwrap();
+        assert_eq!(failures.len(), 3);
+        assert_eq!(failures[1].hresult, Some(0x8007016Au32 as i32));
+        assert_eq!(failures[1].recovery, Some(CfapiRecovery::ReconnectSyncRoot));
+
+        let summary = db.summarize_cfapi_failures(None, 0).unwrap();
+        assert_eq!(summary[0].class, CfapiFailureClass::Transient);
+        assert_eq!(summary[0].count, 2);
+        assert!(
+            db.last_cfapi_recovery_at("drive", CfapiRecovery::ReconnectSyncRoot)
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            db.last_cfapi_recovery_at("drive", CfapiRecovery::RepairPlaceholder)
+                .unwrap()
+                .is_none()
+        );
+
+        assert_eq!(db.clear_cfapi_failures(Some("other")).unwrap(), 0);
+        assert_eq!(db.clear_cfapi_failures(Some("drive")).unwrap(), 3);
+    }
+}
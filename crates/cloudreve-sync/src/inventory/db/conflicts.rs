@@ -0,0 +1,341 @@
+use super::InventoryDb;
+use crate::inventory::{
+    ConflictKind, ConflictPage, ConflictRecord, ConflictResolution, NewConflictRecord, PageRequest,
+};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::sqlite::Sqlite;
+
+use crate::inventory::schema::conflicts::{self, dsl as conflicts_dsl};
+
+diesel::define_sql_function!(fn last_insert_rowid() -> diesel::sql_types::BigInt);
+
+impl InventoryDb {
+    /// Record a new conflict. Returns the id of the inserted row.
+    pub fn insert_conflict(&self, record: &NewConflictRecord) -> Result<i64> {
+        let mut conn = self.connection()?;
+        let now = Utc::now().timestamp();
+        let resolved_at = (!record.resolution.is_pending()).then_some(now);
+        let row = NewConflictRow {
+            drive_id: &record.drive_id,
+            original_path: &record.original_path,
+            conflict_path: record.conflict_path.as_deref(),
+            local_etag: record.local_etag.as_deref(),
+            remote_etag: record.remote_etag.as_deref(),
+            kind: record.kind.as_str(),
+            resolution: record.resolution.as_str(),
+            created_at: now,
+            updated_at: now,
+            resolved_at,
+        };
+
+        let id = conn
+            .transaction(|conn| {
+                diesel::insert_into(conflicts::table)
+                    .values(&row)
+                    .execute(conn)?;
+                diesel::select(last_insert_rowid()).get_result::<i64>(conn)
+            })
+            .context("Failed to insert conflict record")?;
+        self.notify_conflict_detected(id, record);
+        Ok(id)
+    }
+
+    /// Get a conflict by id
+    pub fn get_conflict(&self, id: i64) -> Result<Option<ConflictRecord>> {
+        let mut conn = self.connection()?;
+        let row = conflicts_dsl::conflicts
+            .filter(conflicts_dsl::id.eq(id))
+            .first::<ConflictRow>(&mut conn)
+            .optional()
+            .context("Failed to query conflict record")?;
+
+        row.map(ConflictRecord::try_from).transpose()
+    }
+
+    /// Most recent unresolved conflict recorded for `path`
+    pub fn get_pending_conflict_by_path(&self, path: &str) -> Result<Option<ConflictRecord>> {
+        let mut conn = self.connection()?;
+        let row = conflicts_dsl::conflicts
+            .filter(conflicts_dsl::original_path.eq(path))
+            .filter(conflicts_dsl::resolution.eq(ConflictResolution::Pending.as_str()))
+            .order(conflicts_dsl::id.desc())
+            .first::<ConflictRow>(&mut conn)
+            .optional()
+            .context("Failed to query pending conflict")?;
+
+        row.map(ConflictRecord::try_from).transpose()
+    }
+
+    /// Conflicts involving `path`, either as the conflicted file or as the copy a
+    /// resolution left behind, newest first
+    pub fn list_conflicts_for_path(&self, path: &str) -> Result<Vec<ConflictRecord>> {
+        let mut conn = self.connection()?;
+        let rows = conflicts_dsl::conflicts
+            .filter(
+                conflicts_dsl::original_path
+                    .eq(path)
+                    .or(conflicts_dsl::conflict_path.eq(path)),
+            )
+            .order(conflicts_dsl::id.desc())
+            .load::<ConflictRow>(&mut conn)
+            .context("Failed to list conflict records for path")?;
+
+        rows.into_iter().map(ConflictRecord::try_from).collect()
+    }
+
+    /// List conflicts, newest first. `pending_only` skips resolved conflicts.
+    pub fn list_conflicts(
+        &self,
+        drive_id: Option<&str>,
+        pending_only: bool,
+    ) -> Result<Vec<ConflictRecord>> {
+        let mut conn = self.connection()?;
+        let rows = filtered_conflicts(drive_id, pending_only)
+            .order(conflicts_dsl::id.desc())
+            .load::<ConflictRow>(&mut conn)
+            .context("Failed to list conflict records")?;
+
+        rows.into_iter().map(ConflictRecord::try_from).collect()
+    }
+
+    /// A page of [`InventoryDb::list_conflicts`], with the total number of matches
+    pub fn query_conflicts(
+        &self,
+        drive_id: Option<&str>,
+        pending_only: bool,
+        page: PageRequest,
+    ) -> Result<ConflictPage> {
+        let page = page.normalized();
+        let mut conn = self.connection()?;
+
+        let total: i64 = filtered_conflicts(drive_id, pending_only)
+            .count()
+            .get_result(&mut conn)
+            .context("Failed to count conflict records")?;
+        let rows = filtered_conflicts(drive_id, pending_only)
+            .order(conflicts_dsl::id.desc())
+            .offset(page.offset)
+            .limit(page.limit)
+            .load::<ConflictRow>(&mut conn)
+            .context("Failed to query conflict records")?;
+
+        Ok(ConflictPage {
+            conflicts: rows
+                .into_iter()
+                .map(ConflictRecord::try_from)
+                .collect::<Result<Vec<_>>>()?,
+            total,
+            offset: page.offset,
+            limit: page.limit,
+        })
+    }
+
+    /// Mark a conflict as resolved. `conflict_path` records where the local copy ended up,
+    /// if the resolution produced one. Returns `true` if the conflict existed.
+    pub fn resolve_conflict_record(
+        &self,
+        id: i64,
+        resolution: ConflictResolution,
+        conflict_path: Option<&str>,
+    ) -> Result<bool> {
+        let mut conn = self.connection()?;
+        let now = Utc::now().timestamp();
+        let resolved_at = (!resolution.is_pending()).then_some(now);
+        let target = conflicts_dsl::conflicts.filter(conflicts_dsl::id.eq(id));
+
+        let updated = match conflict_path {
+            Some(conflict_path) => diesel::update(target)
+                .set((
+                    conflicts_dsl::resolution.eq(resolution.as_str()),
+                    conflicts_dsl::conflict_path.eq(Some(conflict_path)),
+                    conflicts_dsl::updated_at.eq(now),
+                    conflicts_dsl::resolved_at.eq(resolved_at),
+                ))
+                .execute(&mut conn),
+            None => diesel::update(target)
+                .set((
+                    conflicts_dsl::resolution.eq(resolution.as_str()),
+                    conflicts_dsl::updated_at.eq(now),
+                    conflicts_dsl::resolved_at.eq(resolved_at),
+                ))
+                .execute(&mut conn),
+        }
+        .context("Failed to resolve conflict record")?;
+
+        Ok(updated > 0)
+    }
+
+    /// Delete a conflict record. Returns `true` if it existed.
+    pub fn delete_conflict(&self, id: i64) -> Result<bool> {
+        let mut conn = self.connection()?;
+        let deleted = diesel::delete(conflicts_dsl::conflicts.filter(conflicts_dsl::id.eq(id)))
+            .execute(&mut conn)
+            .context("Failed to delete conflict record")?;
+        Ok(deleted > 0)
+    }
+
+    /// Copy conflict records as-is, keeping their timestamps and resolution
+    pub(super) fn import_conflicts(&self, records: &[ConflictRecord]) -> Result<usize> {
+        if records.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.connection()?;
+        let rows: Vec<NewConflictRow> = records
+            .iter()
+            .map(|record| NewConflictRow {
+                drive_id: &record.drive_id,
+                original_path: &record.original_path,
+                conflict_path: record.conflict_path.as_deref(),
+                local_etag: record.local_etag.as_deref(),
+                remote_etag: record.remote_etag.as_deref(),
+                kind: record.kind.as_str(),
+                resolution: record.resolution.as_str(),
+                created_at: record.created_at,
+                updated_at: record.updated_at,
+                resolved_at: record.resolved_at,
+            })
+            .collect();
+
+        diesel::insert_into(conflicts::table)
+            .values(&rows)
+            .execute(&mut conn)
+            .context("Failed to import conflict records")
+    }
+
+    /// Delete all conflict records for a drive
+    pub fn delete_conflicts_by_drive(&self, drive_id: &str) -> Result<usize> {
+        let mut conn = self.connection()?;
+        diesel::delete(conflicts_dsl::conflicts.filter(conflicts_dsl::drive_id.eq(drive_id)))
+            .execute(&mut conn)
+            .context("Failed to delete conflict records for drive")
+    }
+}
+
+fn filtered_conflicts<'a>(
+    drive_id: Option<&'a str>,
+    pending_only: bool,
+) -> conflicts::BoxedQuery<'a, Sqlite> {
+    let mut query = conflicts_dsl::conflicts.into_boxed();
+    if let Some(drive_id) = drive_id {
+        query = query.filter(conflicts_dsl::drive_id.eq(drive_id));
+    }
+    if pending_only {
+        query = query.filter(conflicts_dsl::resolution.eq(ConflictResolution::Pending.as_str()));
+    }
+    query
+}
+
+// =========================================================================
+// Row Types
+// =========================================================================
+
+#[derive(Queryable)]
+struct ConflictRow {
+    id: i64,
+    drive_id: String,
+    original_path: String,
+    conflict_path: Option<String>,
+    local_etag: Option<String>,
+    remote_etag: Option<String>,
+    kind: String,
+    resolution: String,
+    created_at: i64,
+    updated_at: i64,
+    resolved_at: Option<i64>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = conflicts)]
+struct NewConflictRow<'a> {
+    drive_id: &'a str,
+    original_path: &'a str,
+    conflict_path: Option<&'a str>,
+    local_etag: Option<&'a str>,
+    remote_etag: Option<&'a str>,
+    kind: &'a str,
+    resolution: &'a str,
+    created_at: i64,
+    updated_at: i64,
+    resolved_at: Option<i64>,
+}
+
+impl TryFrom<ConflictRow> for ConflictRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ConflictRow) -> Result<Self> {
+        let kind = ConflictKind::from_str(&row.kind)
+            .ok_or_else(|| anyhow::anyhow!("Unknown conflict kind: {}", row.kind))?;
+        let resolution = ConflictResolution::from_str(&row.resolution)
+            .ok_or_else(|| anyhow::anyhow!("Unknown conflict resolution: {}", row.resolution))?;
+        Ok(ConflictRecord {
+            id: row.id,
+            drive_id: row.drive_id,
+            original_path: row.original_path,
+            conflict_path: row.conflict_path,
+            local_etag: row.local_etag,
+            remote_etag: row.remote_etag,
+            kind,
+            resolution,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            resolved_at: row.resolved_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn conflict_lifecycle() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+
+        let id = db
+            .insert_conflict(
+                &NewConflictRecord::new("drive", "/sync/a.txt", ConflictKind::Content)
+                    .with_local_etag("local")
+                    .with_remote_etag("remote"),
+            )
+            .unwrap();
+        let pending = db
+            .get_pending_conflict_by_path("/sync/a.txt")
+            .unwrap()
+            .unwrap();
+        assert_eq!(pending.id, id);
+        assert_eq!(pending.remote_etag.as_deref(), Some("remote"));
+        assert_eq!(db.list_conflicts(Some("drive"), true).unwrap().len(), 1);
+        let page = db
+            .query_conflicts(None, true, PageRequest::new(0, 10))
+            .unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.conflicts[0].id, id);
+        assert!(!page.has_more());
+
+        assert!(
+            db.resolve_conflict_record(id, ConflictResolution::SaveAsNew, Some("/sync/a (1).txt"))
+                .unwrap()
+        );
+        assert_eq!(
+            db.list_conflicts_for_path("/sync/a (1).txt").unwrap()[0].id,
+            id
+        );
+        let resolved = db.get_conflict(id).unwrap().unwrap();
+        assert_eq!(resolved.resolution, ConflictResolution::SaveAsNew);
+        assert_eq!(resolved.conflict_path.as_deref(), Some("/sync/a (1).txt"));
+        assert!(resolved.resolved_at.is_some());
+        assert!(
+            db.get_pending_conflict_by_path("/sync/a.txt")
+                .unwrap()
+                .is_none()
+        );
+        assert!(db.list_conflicts(None, true).unwrap().is_empty());
+
+        assert!(db.delete_conflict(id).unwrap());
+        assert!(db.list_conflicts(None, false).unwrap().is_empty());
+    }
+}
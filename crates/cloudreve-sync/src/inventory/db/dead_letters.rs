@@ -0,0 +1,273 @@
+use super::InventoryDb;
+use super::tasks::parse_attempt_history;
+use crate::inventory::{DeadLetterRecord, TaskFailureClass};
+use anyhow::{Context, Result, anyhow};
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::inventory::schema::dead_letters::{self, dsl as dead_letters_dsl};
+
+impl InventoryDb {
+    /// Copy a failed task into the dead-letter store, replacing an earlier entry for it.
+    /// Returns `false` if the task does not exist.
+    pub fn dead_letter_task(
+        &self,
+        task_id: &str,
+        failure: TaskFailureClass,
+        error: &str,
+    ) -> Result<bool> {
+        let Some(task) = self.get_task(task_id)? else {
+            return Ok(false);
+        };
+        let custom_state = task
+            .custom_state
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .context("Failed to serialize task custom_state")?;
+        let attempt_history = serde_json::to_string(&task.attempt_history)
+            .context("Failed to serialize task attempt history")?;
+        let row = DeadLetterRow {
+            task_id: task.id,
+            drive_id: task.drive_id,
+            task_type: task.task_type,
+            local_path: task.local_path,
+            priority: task.priority,
+            custom_state,
+            force_override: task.force_override,
+            failure: failure.as_str().to_string(),
+            error: error.to_string(),
+            attempts: task.attempts,
+            attempt_history: Some(attempt_history),
+            created_at: Utc::now().timestamp(),
+        };
+
+        let mut conn = self.connection()?;
+        diesel::replace_into(dead_letters::table)
+            .values(&row)
+            .execute(&mut conn)
+            .context("Failed to insert dead letter")?;
+        self.notify_dead_letters_changed();
+        Ok(true)
+    }
+
+    /// Get a dead-lettered task by the id of the task
+    pub fn get_dead_letter(&self, task_id: &str) -> Result<Option<DeadLetterRecord>> {
+        let mut conn = self.connection()?;
+        let row = dead_letters_dsl::dead_letters
+            .filter(dead_letters_dsl::task_id.eq(task_id))
+            .first::<DeadLetterRow>(&mut conn)
+            .optional()
+            .context("Failed to query dead letter")?;
+        row.map(DeadLetterRecord::try_from).transpose()
+    }
+
+    /// List dead-lettered tasks, newest first
+    pub fn list_dead_letters(&self, drive_id: Option<&str>) -> Result<Vec<DeadLetterRecord>> {
+        let mut conn = self.connection()?;
+        let mut query = dead_letters_dsl::dead_letters.into_boxed();
+        if let Some(drive_id) = drive_id {
+            query = query.filter(dead_letters_dsl::drive_id.eq(drive_id));
+        }
+        let rows = query
+            .order(dead_letters_dsl::created_at.desc())
+            .load::<DeadLetterRow>(&mut conn)
+            .context("Failed to list dead letters")?;
+        rows.into_iter().map(DeadLetterRecord::try_from).collect()
+    }
+
+    /// Count dead-lettered tasks
+    pub fn count_dead_letters(&self, drive_id: Option<&str>) -> Result<i64> {
+        let mut conn = self.connection()?;
+        let mut query = dead_letters_dsl::dead_letters.into_boxed();
+        if let Some(drive_id) = drive_id {
+            query = query.filter(dead_letters_dsl::drive_id.eq(drive_id));
+        }
+        query
+            .count()
+            .get_result(&mut conn)
+            .context("Failed to count dead letters")
+    }
+
+    /// Whether a task for `local_path` failed for good and is still dead-lettered
+    pub fn has_dead_letter_for_path(&self, local_path: &str) -> Result<bool> {
+        let mut conn = self.connection()?;
+        let count: i64 = dead_letters_dsl::dead_letters
+            .filter(dead_letters_dsl::local_path.eq(local_path))
+            .count()
+            .get_result(&mut conn)
+            .context("Failed to query dead letters by path")?;
+        Ok(count > 0)
+    }
+
+    /// Get the newest dead-lettered task of a local path
+    pub fn latest_dead_letter_for_path(
+        &self,
+        local_path: &str,
+    ) -> Result<Option<DeadLetterRecord>> {
+        let mut conn = self.connection()?;
+        let row = dead_letters_dsl::dead_letters
+            .filter(dead_letters_dsl::local_path.eq(local_path))
+            .order(dead_letters_dsl::created_at.desc())
+            .first::<DeadLetterRow>(&mut conn)
+            .optional()
+            .context("Failed to query dead letter by path")?;
+        row.map(DeadLetterRecord::try_from).transpose()
+    }
+
+    /// Remove a task from the dead-letter store. Returns `true` if it was there.
+    pub fn delete_dead_letter(&self, task_id: &str) -> Result<bool> {
+        let mut conn = self.connection()?;
+        let deleted = diesel::delete(
+            dead_letters_dsl::dead_letters.filter(dead_letters_dsl::task_id.eq(task_id)),
+        )
+        .execute(&mut conn)
+        .context("Failed to delete dead letter")?;
+        if deleted > 0 {
+            self.notify_dead_letters_changed();
+        }
+        Ok(deleted > 0)
+    }
+
+    /// Remove the dead letters of `task_type` tasks for `local_path`, e.g. once a
+    /// later task of the same type completed. Returns the number of removed entries.
+    pub fn delete_dead_letters_for_path(&self, task_type: &str, local_path: &str) -> Result<usize> {
+        let mut conn = self.connection()?;
+        let deleted = diesel::delete(
+            dead_letters_dsl::dead_letters
+                .filter(dead_letters_dsl::task_type.eq(task_type))
+                .filter(dead_letters_dsl::local_path.eq(local_path)),
+        )
+        .execute(&mut conn)
+        .context("Failed to delete dead letters by path")?;
+        if deleted > 0 {
+            self.notify_dead_letters_changed();
+        }
+        Ok(deleted)
+    }
+}
+
+// =========================================================================
+// Row Types
+// =========================================================================
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = dead_letters)]
+struct DeadLetterRow {
+    task_id: String,
+    drive_id: String,
+    task_type: String,
+    local_path: String,
+    priority: i32,
+    custom_state: Option<String>,
+    force_override: bool,
+    failure: String,
+    error: String,
+    attempts: i32,
+    attempt_history: Option<String>,
+    created_at: i64,
+}
+
+impl TryFrom<DeadLetterRow> for DeadLetterRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(row: DeadLetterRow) -> Result<Self> {
+        let failure = TaskFailureClass::from_str(&row.failure)
+            .ok_or_else(|| anyhow!("Unknown task failure class: {}", row.failure))?;
+        let custom_state = row
+            .custom_state
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .context("Failed to deserialize dead letter custom_state")?;
+        Ok(DeadLetterRecord {
+            task_id: row.task_id,
+            drive_id: row.drive_id,
+            task_type: row.task_type,
+            local_path: row.local_path,
+            priority: row.priority,
+            custom_state,
+            force_override: row.force_override,
+            failure,
+            error: row.error,
+            attempts: row.attempts,
+            attempt_history: parse_attempt_history(row.attempt_history.as_deref())?,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::NewTaskRecord;
+    use tempfile::tempdir;
+
+    #[test]
+    fn dead_letter_lifecycle() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        db.insert_task_if_not_exist(
+            &NewTaskRecord::new("t1", "drive", "upload", "/sync/a.txt").with_priority(5),
+        )
+        .unwrap();
+        db.record_task_failure("t1", TaskFailureClass::Auth, "token expired")
+            .unwrap();
+
+        assert!(
+            db.dead_letter_task("t1", TaskFailureClass::Auth, "token expired")
+                .unwrap()
+        );
+        assert!(
+            !db.dead_letter_task("missing", TaskFailureClass::Other, "")
+                .unwrap()
+        );
+
+        let record = db.get_dead_letter("t1").unwrap().unwrap();
+        assert_eq!(record.priority, 5);
+        assert_eq!(record.failure, TaskFailureClass::Auth);
+        assert_eq!(record.attempts, 1);
+        assert_eq!(record.attempt_history.len(), 1);
+        assert_eq!(db.count_dead_letters(Some("drive")).unwrap(), 1);
+        assert_eq!(db.count_dead_letters(Some("other")).unwrap(), 0);
+        let by_path = db.latest_dead_letter_for_path("/sync/a.txt").unwrap();
+        assert_eq!(by_path.map(|r| r.task_id).as_deref(), Some("t1"));
+        assert!(
+            db.latest_dead_letter_for_path("/sync/b.txt")
+                .unwrap()
+                .is_none()
+        );
+
+        assert!(db.delete_dead_letter("t1").unwrap());
+        assert!(db.list_dead_letters(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn completed_task_clears_dead_letters_of_path() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        for (id, task_type, path) in [
+            ("t1", "upload", "/sync/a.txt"),
+            ("t2", "download", "/sync/a.txt"),
+            ("t3", "upload", "/sync/b.txt"),
+        ] {
+            db.insert_task_if_not_exist(&NewTaskRecord::new(id, "drive", task_type, path))
+                .unwrap();
+            db.dead_letter_task(id, TaskFailureClass::Other, "failed")
+                .unwrap();
+        }
+
+        assert_eq!(
+            db.delete_dead_letters_for_path("upload", "/sync/a.txt")
+                .unwrap(),
+            1
+        );
+        assert!(db.get_dead_letter("t1").unwrap().is_none());
+        assert!(db.get_dead_letter("t2").unwrap().is_some());
+        assert!(db.has_dead_letter_for_path("/sync/b.txt").unwrap());
+        assert_eq!(
+            db.delete_dead_letters_for_path("upload", "/sync/a.txt")
+                .unwrap(),
+            0
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use crate::utils::credential_store;
+use anyhow::{Context, Result, anyhow, bail};
+use diesel::Connection;
+use diesel::connection::SimpleConnection;
+use diesel::sqlite::SqliteConnection;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Credential store entry holding the inventory key
+const KEY_CREDENTIAL_NAME: &str = "InventoryKey";
+const KEY_LEN: usize = 32;
+/// First bytes of every unencrypted SQLite database file
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Key used to encrypt inventory databases with SQLCipher.
+///
+/// A single key is shared by all drives and lives in the OS credential store, never on disk
+/// next to the databases.
+#[derive(Clone)]
+pub struct InventoryKey([u8; KEY_LEN]);
+
+impl std::fmt::Debug for InventoryKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("InventoryKey(..)")
+    }
+}
+
+impl InventoryKey {
+    /// Load the key from the credential store, creating and storing a new one if needed
+    pub fn load_or_create() -> Result<Self> {
+        if let Some(key) = Self::load()? {
+            return Ok(key);
+        }
+        let mut bytes = [0u8; KEY_LEN];
+        getrandom::getrandom(&mut bytes)
+            .map_err(|e| anyhow!("Failed to generate inventory key: {e}"))?;
+        credential_store::write_secret(KEY_CREDENTIAL_NAME, &bytes)?;
+        tracing::info!(target: "inventory", "Created inventory encryption key");
+        Ok(Self(bytes))
+    }
+
+    /// Load the key from the credential store, if one was created before
+    pub fn load() -> Result<Option<Self>> {
+        let Some(bytes) = credential_store::read_secret(KEY_CREDENTIAL_NAME)? else {
+            return Ok(None);
+        };
+        let bytes: [u8; KEY_LEN] = bytes
+            .try_into()
+            .map_err(|_| anyhow!("Stored inventory key has an unexpected length"))?;
+        Ok(Some(Self(bytes)))
+    }
+
+    /// SQL literal for SQLCipher's raw-key syntax, `"x'<hex>'"`
+    fn sql_literal(&self) -> String {
+        let hex: String = self.0.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("\"x'{}'\"", hex)
+    }
+
+    /// Statement that unlocks a connection; must run before anything else touches the file
+    pub(super) fn pragma(&self) -> String {
+        format!("PRAGMA key = {};", self.sql_literal())
+    }
+}
+
+/// Whether the file at `path` exists and is not a plain SQLite database
+fn is_encrypted(path: &Path) -> Result<bool> {
+    let mut file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("Failed to open inventory file {}", path.display()));
+        }
+    };
+    let mut header = [0u8; 16];
+    match file.read_exact(&mut header) {
+        Ok(()) => Ok(&header != SQLITE_HEADER),
+        // Empty or truncated files are treated as new databases
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => {
+            Err(e).with_context(|| format!("Failed to read inventory file {}", path.display()))
+        }
+    }
+}
+
+/// Bring the database at `path` in line with the requested encryption setting,
+/// converting it in place if needed. Returns the key connections must be opened with.
+pub(super) fn prepare(path: &Path, encrypt: bool) -> Result<Option<InventoryKey>> {
+    let encrypted = is_encrypted(path)?;
+
+    if encrypt {
+        let key = InventoryKey::load_or_create()?;
+        if path.exists() && !encrypted {
+            convert(path, None, Some(&key))?;
+            tracing::info!(target: "inventory", path = %path.display(), "Encrypted inventory database");
+        }
+        return Ok(Some(key));
+    }
+
+    if encrypted {
+        let Some(key) = InventoryKey::load()? else {
+            bail!(
+                "Inventory database {} is encrypted but its key is missing from the credential store",
+                path.display()
+            );
+        };
+        convert(path, Some(&key), None)?;
+        tracing::info!(target: "inventory", path = %path.display(), "Decrypted inventory database");
+    }
+    Ok(None)
+}
+
+/// Re-write the database with a different key using `sqlcipher_export`, then swap it
+/// into place. `None` means no encryption.
+fn convert(path: &Path, from: Option<&InventoryKey>, to: Option<&InventoryKey>) -> Result<()> {
+    let database_url = path
+        .to_str()
+        .ok_or_else(|| anyhow!("Invalid inventory database path"))?;
+    let converted = sibling(path, "-convert");
+    let converted_str = converted
+        .to_str()
+        .ok_or_else(|| anyhow!("Invalid inventory database path"))?;
+    if converted.exists() {
+        fs::remove_file(&converted).context("Failed to remove stale converted inventory")?;
+    }
+
+    {
+        let mut conn = SqliteConnection::establish(database_url)
+            .with_context(|| format!("Failed to open inventory database at {}", database_url))?;
+        if let Some(key) = from {
+            conn.batch_execute(&key.pragma())
+                .context("Failed to unlock inventory database")?;
+        }
+        let target_key = to
+            .map(|key| key.sql_literal())
+            .unwrap_or_else(|| "''".into());
+        conn.batch_execute(&format!(
+            "PRAGMA wal_checkpoint(TRUNCATE); \
+             ATTACH DATABASE '{}' AS converted KEY {}; \
+             SELECT sqlcipher_export('converted'); \
+             DETACH DATABASE converted;",
+            converted_str.replace('\'', "''"),
+            target_key
+        ))
+        .context("Failed to convert inventory database")?;
+    }
+
+    for suffix in ["-wal", "-shm"] {
+        let file = sibling(path, suffix);
+        if file.exists() {
+            fs::remove_file(&file)
+                .with_context(|| format!("Failed to remove inventory file {}", file.display()))?;
+        }
+    }
+    fs::rename(&converted, path).context("Failed to replace inventory database")
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut file = path.as_os_str().to_owned();
+    file.push(suffix);
+    PathBuf::from(file)
+}
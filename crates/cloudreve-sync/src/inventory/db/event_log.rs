@@ -0,0 +1,135 @@
+use super::InventoryDb;
+use crate::events::{Event, LoggedEvent};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::inventory::schema::event_log::{self, dsl as event_log_dsl};
+
+diesel::define_sql_function!(fn last_insert_rowid() -> diesel::sql_types::BigInt);
+
+/// Events kept in the log, older ones are dropped as new ones are appended
+const MAX_LOGGED_EVENTS: i64 = 500;
+
+impl InventoryDb {
+    /// Append an event to the log, dropping the oldest events beyond the retention
+    /// limit. Returns the id of the event, which increases with every append.
+    pub fn append_event(&self, event: &Event) -> Result<i64> {
+        let row = NewEventLogRow {
+            name: event.name().to_string(),
+            payload: serde_json::to_string(event).context("Failed to serialize event")?,
+            created_at: Utc::now().timestamp(),
+        };
+
+        let mut conn = self.connection()?;
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            diesel::insert_into(event_log::table)
+                .values(&row)
+                .execute(conn)?;
+            let id = diesel::select(last_insert_rowid()).get_result::<i64>(conn)?;
+            diesel::delete(
+                event_log_dsl::event_log.filter(event_log_dsl::id.le(id - MAX_LOGGED_EVENTS)),
+            )
+            .execute(conn)?;
+            Ok(id)
+        })
+        .context("Failed to append event to the log")
+    }
+
+    /// Logged events, oldest first. With `after`, only the events logged after that id,
+    /// otherwise the last `limit` events.
+    pub fn list_events(&self, after: Option<i64>, limit: i64) -> Result<Vec<LoggedEvent>> {
+        let mut conn = self.connection()?;
+        let limit = limit.clamp(1, MAX_LOGGED_EVENTS);
+        let rows = match after {
+            Some(after) => event_log_dsl::event_log
+                .filter(event_log_dsl::id.gt(after))
+                .order(event_log_dsl::id.asc())
+                .limit(limit)
+                .load::<EventLogRow>(&mut conn),
+            None => event_log_dsl::event_log
+                .order(event_log_dsl::id.desc())
+                .limit(limit)
+                .load::<EventLogRow>(&mut conn)
+                .map(|mut rows| {
+                    rows.reverse();
+                    rows
+                }),
+        }
+        .context("Failed to list logged events")?;
+
+        // Events of an older version that no longer deserialize are skipped
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| match serde_json::from_str(&row.payload) {
+                Ok(event) => Some(LoggedEvent {
+                    id: row.id,
+                    created_at: row.created_at,
+                    event,
+                }),
+                Err(e) => {
+                    tracing::debug!(target: "inventory", id = row.id, name = %row.name, error = %e, "Skipping unreadable logged event");
+                    None
+                }
+            })
+            .collect())
+    }
+}
+
+// =========================================================================
+// Row Types
+// =========================================================================
+
+#[derive(Queryable)]
+#[diesel(table_name = event_log)]
+struct EventLogRow {
+    id: i64,
+    name: String,
+    payload: String,
+    created_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = event_log)]
+struct NewEventLogRow {
+    name: String,
+    payload: String,
+    created_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn log_is_a_ring_buffer() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("events.db")).unwrap();
+
+        let mut last = 0;
+        for count in 0..MAX_LOGGED_EVENTS + 10 {
+            last = db
+                .append_event(&Event::DeadLettersChanged {
+                    drive_id: "drive".to_string(),
+                    count: count as usize,
+                })
+                .unwrap();
+        }
+
+        let all = db.list_events(Some(0), MAX_LOGGED_EVENTS + 10).unwrap();
+        assert_eq!(all.len(), MAX_LOGGED_EVENTS as usize);
+        assert_eq!(all.first().unwrap().id, last - MAX_LOGGED_EVENTS + 1);
+
+        let recent = db.list_events(None, 2).unwrap();
+        assert_eq!(
+            recent.iter().map(|e| e.id).collect::<Vec<_>>(),
+            [last - 1, last]
+        );
+        assert!(matches!(
+            recent[1].event,
+            Event::DeadLettersChanged { count, .. } if count as i64 == MAX_LOGGED_EVENTS + 9
+        ));
+        assert!(db.list_events(Some(last), 10).unwrap().is_empty());
+    }
+}
@@ -1,10 +1,14 @@
-use super::InventoryDb;
+use super::journal::{self, NewJournalRow};
+use super::{InventoryDb, LIKE_ESCAPE, escape_like};
+use crate::events::EntryChange;
 use crate::inventory::{
-    ConflictState, FileMetadata, MetadataEntry,
+    ConflictState, EntryFilter, EntryPage, EntrySort, EntrySortField, EntrySyncState, FileMetadata,
+    JournalOperation, MetadataEntry, PageRequest,
 };
 use anyhow::{Context, Result};
 use diesel::prelude::*;
-use diesel::sql_types::Text;
+use diesel::sql_types::{BigInt, Text};
+use diesel::sqlite::Sqlite;
 use std::collections::HashMap;
 use uuid::Uuid;
 
@@ -21,21 +25,101 @@ impl InventoryDb {
             .map(NewFileMetadata::try_from)
             .collect::<Result<_>>()?;
 
+        let journal: Vec<NewJournalRow> = entries
+            .iter()
+            .map(|entry| self.entry_journal_row(entry, JournalOperation::Insert))
+            .collect();
+
         let mut conn = self.connection()?;
-        diesel::insert_into(file_metadata::table)
-            .values(&rows)
-            .execute(&mut conn)
+        (&mut *conn)
+            .transaction::<_, diesel::result::Error, _>(|tx_conn| {
+                diesel::insert_into(file_metadata::table)
+                    .values(&rows)
+                    .execute(tx_conn)?;
+                journal::record(tx_conn, &journal)
+            })
             .context("Failed to batch insert inventory metadata")?;
+        self.notify_entries(
+            EntryChange::Added,
+            entries.iter().map(|entry| entry.local_path.as_str()),
+        );
         Ok(())
     }
 
+    /// Insert or update many entries (keyed by local_path) inside a single transaction.
+    /// Returns the number of rows written.
+    pub fn batch_upsert(&self, entries: &[MetadataEntry]) -> Result<usize> {
+        if entries.is_empty() {
+            return Ok(0);
+        }
+
+        let rows: Vec<(NewFileMetadata, FileMetadataChangeset)> = entries
+            .iter()
+            .map(|entry| {
+                Ok((
+                    NewFileMetadata::try_from(entry)?,
+                    FileMetadataChangeset::from_entry(entry)?,
+                ))
+            })
+            .collect::<Result<_>>()?;
+
+        let journal: Vec<NewJournalRow> = entries
+            .iter()
+            .map(|entry| self.entry_journal_row(entry, JournalOperation::Upsert))
+            .collect();
+
+        let mut conn = self.connection()?;
+        let written = (&mut *conn)
+            .transaction::<usize, diesel::result::Error, _>(|tx_conn| {
+                let mut total = 0;
+                for (insert_data, update_data) in &rows {
+                    total += diesel::insert_into(file_metadata::table)
+                        .values(insert_data)
+                        .on_conflict(file_metadata::local_path)
+                        .do_update()
+                        .set(update_data)
+                        .execute(tx_conn)?;
+                }
+                journal::record(tx_conn, &journal)?;
+                Ok(total)
+            })
+            .context("Failed to batch upsert inventory metadata")?;
+        self.notify_entries(
+            EntryChange::Updated,
+            entries.iter().map(|entry| entry.local_path.as_str()),
+        );
+        Ok(written)
+    }
+
+    /// List all file metadata entries belonging to a drive, ordered by local path
+    pub fn list_by_drive(&self, drive: &str) -> Result<Vec<FileMetadata>> {
+        let mut conn = self.connection()?;
+        let rows = file_metadata_dsl::file_metadata
+            .filter(file_metadata_dsl::drive_id.eq(drive))
+            .order(file_metadata_dsl::local_path.asc())
+            .load::<FileMetadataRow>(&mut conn)
+            .context("Failed to query inventory metadata by drive")?;
+
+        rows.into_iter()
+            .map(FileMetadata::try_from)
+            .collect::<Result<Vec<_>>>()
+    }
+
     pub fn nuke_drive(&self, drive: &str) -> Result<()> {
+        let journal = self.journal_row(Some(drive), "", JournalOperation::Clear, None);
         let mut conn = self.connection()?;
-        diesel::delete(
-            file_metadata_dsl::file_metadata.filter(file_metadata_dsl::drive_id.eq(drive)),
-        )
-        .execute(&mut conn)
-        .context("Failed to delete inventory rows for drive")?;
+        (&mut *conn)
+            .transaction::<_, diesel::result::Error, _>(|tx_conn| {
+                let deleted = diesel::delete(
+                    file_metadata_dsl::file_metadata.filter(file_metadata_dsl::drive_id.eq(drive)),
+                )
+                .execute(tx_conn)?;
+                if deleted > 0 {
+                    journal::record(tx_conn, &[journal])?;
+                }
+                Ok(())
+            })
+            .context("Failed to delete inventory rows for drive")?;
         Ok(())
     }
 
@@ -43,23 +127,42 @@ impl InventoryDb {
     pub fn insert(&self, entry: &MetadataEntry) -> Result<usize> {
         let mut conn = self.connection()?;
         let new_entry = NewFileMetadata::try_from(entry)?;
-        diesel::insert_into(file_metadata::table)
-            .values(&new_entry)
-            .execute(&mut conn)
-            .context("Failed to insert inventory metadata")
+        let journal = self.entry_journal_row(entry, JournalOperation::Insert);
+        let inserted = (&mut *conn)
+            .transaction::<usize, diesel::result::Error, _>(|tx_conn| {
+                let inserted = diesel::insert_into(file_metadata::table)
+                    .values(&new_entry)
+                    .execute(tx_conn)?;
+                journal::record(tx_conn, &[journal])?;
+                Ok(inserted)
+            })
+            .context("Failed to insert inventory metadata")?;
+        self.notify_entries(EntryChange::Added, [entry.local_path.as_str()]);
+        Ok(inserted)
     }
 
     /// Update an existing file metadata entry by local path
     pub fn update(&self, entry: &MetadataEntry) -> Result<bool> {
         let mut conn = self.connection()?;
         let changeset = FileMetadataChangeset::from_entry(entry)?;
-        let rows_affected = diesel::update(
-            file_metadata_dsl::file_metadata
-                .filter(file_metadata_dsl::local_path.eq(&entry.local_path)),
-        )
-        .set(changeset)
-        .execute(&mut conn)
-        .context("Failed to update inventory metadata")?;
+        let journal = self.entry_journal_row(entry, JournalOperation::Update);
+        let rows_affected = (&mut *conn)
+            .transaction::<usize, diesel::result::Error, _>(|tx_conn| {
+                let updated = diesel::update(
+                    file_metadata_dsl::file_metadata
+                        .filter(file_metadata_dsl::local_path.eq(&entry.local_path)),
+                )
+                .set(changeset)
+                .execute(tx_conn)?;
+                if updated > 0 {
+                    journal::record(tx_conn, &[journal])?;
+                }
+                Ok(updated)
+            })
+            .context("Failed to update inventory metadata")?;
+        if rows_affected > 0 {
+            self.notify_entries(EntryChange::Updated, [entry.local_path.as_str()]);
+        }
         Ok(rows_affected > 0)
     }
 
@@ -68,14 +171,22 @@ impl InventoryDb {
         let mut conn = self.connection()?;
         let insert_data = NewFileMetadata::try_from(entry)?;
         let update_data = FileMetadataChangeset::from_entry(entry)?;
+        let journal = self.entry_journal_row(entry, JournalOperation::Upsert);
 
-        diesel::insert_into(file_metadata::table)
-            .values(&insert_data)
-            .on_conflict(file_metadata::local_path)
-            .do_update()
-            .set(update_data)
-            .execute(&mut conn)
-            .context("Failed to upsert inventory metadata")
+        let written = (&mut *conn)
+            .transaction::<usize, diesel::result::Error, _>(|tx_conn| {
+                let written = diesel::insert_into(file_metadata::table)
+                    .values(&insert_data)
+                    .on_conflict(file_metadata::local_path)
+                    .do_update()
+                    .set(update_data)
+                    .execute(tx_conn)?;
+                journal::record(tx_conn, &[journal])?;
+                Ok(written)
+            })
+            .context("Failed to upsert inventory metadata")?;
+        self.notify_entries(EntryChange::Updated, [entry.local_path.as_str()]);
+        Ok(written)
     }
 
     /// Query file metadata by local path
@@ -102,39 +213,104 @@ impl InventoryDb {
         row.map(FileMetadata::try_from).transpose()
     }
 
+    /// Query file metadata entries matching `filter`, ordered by `sort` and paginated by `page`.
+    ///
+    /// The returned page carries the total number of matching rows so callers can render
+    /// pagination controls without issuing a second query.
+    pub fn query_entries(
+        &self,
+        filter: &EntryFilter,
+        sort: EntrySort,
+        page: PageRequest,
+    ) -> Result<EntryPage> {
+        let page = page.normalized();
+        let mut conn = self.connection()?;
+
+        let total: i64 = filtered_entries(filter)
+            .count()
+            .get_result(&mut conn)
+            .context("Failed to count filtered inventory metadata")?;
+
+        let mut query = filtered_entries(filter);
+        query = match (sort.field, sort.descending) {
+            (EntrySortField::LocalPath, false) => query.order(file_metadata_dsl::local_path.asc()),
+            (EntrySortField::LocalPath, true) => query.order(file_metadata_dsl::local_path.desc()),
+            (EntrySortField::Size, false) => query.order(file_metadata_dsl::size.asc()),
+            (EntrySortField::Size, true) => query.order(file_metadata_dsl::size.desc()),
+            (EntrySortField::UpdatedAt, false) => query.order(file_metadata_dsl::updated_at.asc()),
+            (EntrySortField::UpdatedAt, true) => query.order(file_metadata_dsl::updated_at.desc()),
+            (EntrySortField::CreatedAt, false) => query.order(file_metadata_dsl::created_at.asc()),
+            (EntrySortField::CreatedAt, true) => query.order(file_metadata_dsl::created_at.desc()),
+        };
+
+        // Tie-break on id so pages stay stable when the sort column has duplicates
+        let rows = query
+            .then_order_by(file_metadata_dsl::id.asc())
+            .offset(page.offset)
+            .limit(page.limit)
+            .load::<FileMetadataRow>(&mut conn)
+            .context("Failed to query filtered inventory metadata")?;
+
+        let entries = rows
+            .into_iter()
+            .map(FileMetadata::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(EntryPage {
+            entries,
+            total,
+            offset: page.offset,
+            limit: page.limit,
+        })
+    }
+
     /// Batch delete file metadata by local path
     pub fn batch_delete_by_path(&self, paths: Vec<&str>) -> Result<bool> {
         if paths.is_empty() {
             return Ok(false);
         }
 
-        let affected = {
+        let removed = {
             let mut conn = self.connection()?;
             (&mut *conn)
-                .transaction::<i64, diesel::result::Error, _>(|tx_conn| {
-                    let mut total: i64 = 0;
+                .transaction::<Vec<&str>, diesel::result::Error, _>(|tx_conn| {
+                    let mut removed = Vec::new();
+                    let mut journal = Vec::new();
                     for path in &paths {
-                        total += diesel::delete(
+                        let mut deleted = diesel::delete(
                             file_metadata_dsl::file_metadata
                                 .filter(file_metadata_dsl::local_path.eq(path)),
                         )
                         .execute(tx_conn)? as i64;
 
                         let prefix = format!("{}/%", path);
-                        total += diesel::delete(
+                        deleted += diesel::delete(
                             file_metadata_dsl::file_metadata
                                 .filter(file_metadata_dsl::local_path.like(&prefix)),
                         )
                         .execute(tx_conn)? as i64;
+
+                        if deleted > 0 {
+                            journal.push(self.journal_row(
+                                None,
+                                path,
+                                JournalOperation::Delete,
+                                None,
+                            ));
+                            removed.push(*path);
+                        }
                     }
-                    Ok(total)
+                    journal::record(tx_conn, &journal)?;
+                    Ok(removed)
                 })
                 .context("Failed to batch delete inventory metadata")?
         }; // conn is dropped here, releasing it back to the pool
 
         // Delete upload sessions - now safe to acquire a new connection
         self.batch_delete_upload_session_by_path(&paths)?;
-        Ok(affected > 0)
+        let affected = !removed.is_empty();
+        self.notify_entries(EntryChange::Removed, removed);
+        Ok(affected)
     }
 
     /// Get total count of entries in the database
@@ -146,11 +322,27 @@ impl InventoryDb {
             .context("Failed to count inventory metadata")
     }
 
+    /// Number of files in the database and their total size in bytes, folders excluded
+    pub fn file_totals(&self) -> Result<(i64, i64)> {
+        let mut conn = self.connection()?;
+        let row = diesel::sql_query(
+            "SELECT COUNT(*) AS files, COALESCE(SUM(size), 0) AS bytes \
+             FROM file_metadata WHERE is_folder = 0",
+        )
+        .get_result::<FileTotalsRow>(&mut conn)
+        .context("Failed to sum inventory file sizes")?;
+        Ok((row.files, row.bytes))
+    }
+
     /// Clear all entries from the database
     pub fn clear(&self) -> Result<()> {
+        let journal = self.journal_row(None, "", JournalOperation::Clear, None);
         let mut conn = self.connection()?;
-        diesel::delete(file_metadata::table)
-            .execute(&mut conn)
+        (&mut *conn)
+            .transaction::<_, diesel::result::Error, _>(|tx_conn| {
+                diesel::delete(file_metadata::table).execute(tx_conn)?;
+                journal::record(tx_conn, &[journal])
+            })
             .context("Failed to clear inventory metadata")?;
         Ok(())
     }
@@ -169,6 +361,12 @@ impl InventoryDb {
         let old_prefix = format!("{}{}", old_path, std::path::MAIN_SEPARATOR);
         let new_prefix = format!("{}{}", new_path, std::path::MAIN_SEPARATOR);
         let descendant_like = format!("{}%", old_prefix);
+        let journal = self.journal_row(
+            None,
+            new_path,
+            JournalOperation::Rename,
+            Some(old_path.to_string()),
+        );
 
         let total = (&mut *conn)
             .transaction::<usize, diesel::result::Error, _>(|tx_conn| {
@@ -189,10 +387,17 @@ impl InventoryDb {
                 .bind::<Text, _>(&descendant_like)
                 .execute(tx_conn)?;
 
+                if exact + descendants > 0 {
+                    journal::record(tx_conn, &[journal])?;
+                }
                 Ok(exact + descendants)
             })
             .context("Failed to rename metadata path")?;
 
+        if total > 0 {
+            self.notify_entries(EntryChange::Removed, [old_path]);
+            self.notify_entries(EntryChange::Added, [new_path]);
+        }
         Ok(total)
     }
 
@@ -203,16 +408,100 @@ impl InventoryDb {
     pub fn mark_as_conflicted(&self, path: &str, state: Option<ConflictState>) -> Result<bool> {
         let mut conn = self.connection()?;
         let state_str = state.map(|s| s.as_str().to_string());
-        let rows_affected = diesel::update(
-            file_metadata_dsl::file_metadata.filter(file_metadata_dsl::local_path.eq(path)),
-        )
-        .set(file_metadata_dsl::conflict_state.eq(state_str))
-        .execute(&mut conn)
-        .context("Failed to update conflict state")?;
+        let journal = self.journal_row(
+            None,
+            path,
+            JournalOperation::ConflictState,
+            state_str.clone(),
+        );
+        let rows_affected = (&mut *conn)
+            .transaction::<usize, diesel::result::Error, _>(|tx_conn| {
+                let updated = diesel::update(
+                    file_metadata_dsl::file_metadata.filter(file_metadata_dsl::local_path.eq(path)),
+                )
+                .set(file_metadata_dsl::conflict_state.eq(state_str))
+                .execute(tx_conn)?;
+                if updated > 0 {
+                    journal::record(tx_conn, &[journal])?;
+                }
+                Ok(updated)
+            })
+            .context("Failed to update conflict state")?;
+        if rows_affected > 0 {
+            self.notify_entries(EntryChange::Updated, [path]);
+        }
         Ok(rows_affected > 0)
     }
 }
 
+impl InventoryDb {
+    fn entry_journal_row(
+        &self,
+        entry: &MetadataEntry,
+        operation: JournalOperation,
+    ) -> NewJournalRow {
+        self.journal_row(
+            Some(&entry.drive_id.to_string()),
+            &entry.local_path,
+            operation,
+            None,
+        )
+    }
+}
+
+/// Build a boxed query over `file_metadata` with all filter conditions applied.
+fn filtered_entries(filter: &EntryFilter) -> file_metadata::BoxedQuery<'_, Sqlite> {
+    let mut query = file_metadata_dsl::file_metadata.into_boxed();
+
+    if let Some(drive) = filter.drive_id.as_deref() {
+        query = query.filter(file_metadata_dsl::drive_id.eq(drive));
+    }
+
+    if let Some(prefix) = filter.path_prefix.as_deref() {
+        let prefix = prefix.trim_end_matches(std::path::MAIN_SEPARATOR);
+        let descendant_like = format!("{}{}%", escape_like(prefix), std::path::MAIN_SEPARATOR);
+        query = query.filter(
+            file_metadata_dsl::local_path
+                .eq(prefix)
+                .or(file_metadata_dsl::local_path
+                    .like(descendant_like)
+                    .escape(LIKE_ESCAPE)),
+        );
+    }
+
+    if let Some(is_folder) = filter.is_folder {
+        query = query.filter(file_metadata_dsl::is_folder.eq(is_folder));
+    }
+
+    if let Some(min) = filter.min_size {
+        query = query.filter(file_metadata_dsl::size.ge(min));
+    }
+
+    if let Some(max) = filter.max_size {
+        query = query.filter(file_metadata_dsl::size.le(max));
+    }
+
+    match filter.sync_state {
+        Some(EntrySyncState::Synced) => {
+            query = query.filter(file_metadata_dsl::conflict_state.is_null());
+        }
+        Some(EntrySyncState::Conflicted) => {
+            query = query.filter(file_metadata_dsl::conflict_state.is_not_null());
+        }
+        None => {}
+    }
+
+    if let Some(after) = filter.updated_after {
+        query = query.filter(file_metadata_dsl::updated_at.ge(after));
+    }
+
+    if let Some(before) = filter.updated_before {
+        query = query.filter(file_metadata_dsl::updated_at.le(before));
+    }
+
+    query
+}
+
 // =========================================================================
 // Row Types
 // =========================================================================
@@ -234,6 +523,14 @@ struct FileMetadataRow {
     conflict_state: Option<String>,
 }
 
+#[derive(QueryableByName)]
+struct FileTotalsRow {
+    #[diesel(sql_type = BigInt)]
+    files: i64,
+    #[diesel(sql_type = BigInt)]
+    bytes: i64,
+}
+
 #[derive(Insertable)]
 #[diesel(table_name = file_metadata)]
 struct NewFileMetadata {
@@ -354,3 +651,65 @@ impl FileMetadataChangeset {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn matching_paths(db: &InventoryDb, filter: &EntryFilter) -> Vec<String> {
+        db.query_entries(filter, EntrySort::default(), PageRequest::default())
+            .unwrap()
+            .entries
+            .into_iter()
+            .map(|entry| entry.local_path)
+            .collect()
+    }
+
+    #[test]
+    fn query_entries_filters_by_path_state_and_update_time() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        let drive_id = Uuid::new_v4();
+        for (path, is_folder, updated_at) in [
+            (r"C:\sync\docs", true, 100),
+            (r"C:\sync\docs\a.txt", false, 200),
+            (r"C:\sync\docs\sub\b.txt", false, 300),
+            (r"C:\sync\docs2\c.txt", false, 400),
+            (r"C:\sync\100%_done\d.txt", false, 500),
+            (r"C:\sync\100ab_done\e.txt", false, 600),
+        ] {
+            db.upsert(&MetadataEntry::new(drive_id, path, is_folder).with_updated_at(updated_at))
+                .unwrap();
+        }
+        db.mark_as_conflicted(r"C:\sync\docs\a.txt", Some(ConflictState::Pending))
+            .unwrap();
+
+        // A trailing separator is ignored and sibling folders sharing the name's
+        // beginning do not match
+        let filter = EntryFilter::default().with_path_prefix("C:\\sync\\docs\\");
+        assert_eq!(
+            matching_paths(&db, &filter),
+            [
+                r"C:\sync\docs",
+                r"C:\sync\docs\a.txt",
+                r"C:\sync\docs\sub\b.txt"
+            ]
+        );
+
+        // LIKE wildcards in the prefix match literally
+        let filter = EntryFilter::default().with_path_prefix(r"C:\sync\100%_done");
+        assert_eq!(matching_paths(&db, &filter), [r"C:\sync\100%_done\d.txt"]);
+
+        let filter = EntryFilter::default().with_sync_state(EntrySyncState::Conflicted);
+        assert_eq!(matching_paths(&db, &filter), [r"C:\sync\docs\a.txt"]);
+        let filter = EntryFilter::default().with_sync_state(EntrySyncState::Synced);
+        assert_eq!(matching_paths(&db, &filter).len(), 5);
+
+        let filter = EntryFilter::default().with_updated_range(Some(200), Some(300));
+        assert_eq!(
+            matching_paths(&db, &filter),
+            [r"C:\sync\docs\a.txt", r"C:\sync\docs\sub\b.txt"]
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use super::InventoryDb;
+use crate::inventory::TaskStatus;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Text};
+use diesel::sqlite::SqliteConnection;
+use serde::Serialize;
+
+/// Error recorded on download tasks whose inventory entry is missing
+const MISSING_ENTRY_ERROR: &str = "Inventory entry missing";
+
+/// Result of [`InventoryDb::check_integrity`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    /// Problems reported by SQLite's own integrity check
+    pub sqlite_errors: Vec<String>,
+    /// Upload sessions whose task no longer exists
+    pub orphaned_sessions: usize,
+    /// Upload sessions whose drive or path disagrees with their task
+    pub mismatched_sessions: usize,
+    /// Active download tasks whose inventory entry no longer exists
+    pub dangling_downloads: usize,
+}
+
+impl IntegrityReport {
+    /// The database file itself is damaged; only a rebuild can fix it reliably
+    pub fn is_corrupt(&self) -> bool {
+        !self.sqlite_errors.is_empty()
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        !self.is_corrupt()
+            && self.orphaned_sessions == 0
+            && self.mismatched_sessions == 0
+            && self.dangling_downloads == 0
+    }
+}
+
+/// Result of [`InventoryDb::repair_integrity`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityRepair {
+    pub before: IntegrityReport,
+    pub after: IntegrityReport,
+    pub removed_sessions: usize,
+    pub failed_tasks: usize,
+    /// Indexes were rebuilt because SQLite reported damage
+    pub reindexed: bool,
+}
+
+impl InventoryDb {
+    /// Run SQLite's integrity checks and validate the links between tasks, upload
+    /// sessions and metadata entries
+    pub fn check_integrity(&self) -> Result<IntegrityReport> {
+        let mut conn = self.connection()?;
+        let mut report = IntegrityReport::default();
+
+        // A badly damaged file may fail the check itself; report that as damage too
+        match diesel::sql_query("SELECT integrity_check FROM pragma_integrity_check")
+            .load::<IntegrityCheckRow>(&mut conn)
+        {
+            Ok(rows) => {
+                report.sqlite_errors = rows
+                    .into_iter()
+                    .map(|row| row.integrity_check)
+                    .filter(|message| message != "ok")
+                    .collect();
+            }
+            Err(e) => report.sqlite_errors.push(e.to_string()),
+        }
+        if report.is_corrupt() {
+            return Ok(report);
+        }
+
+        // The schema declares no foreign keys, so the links are checked explicitly
+        report.orphaned_sessions = count(
+            &mut conn,
+            "SELECT COUNT(*) AS count FROM upload_sessions \
+             WHERE task_id NOT IN (SELECT id FROM task_queue)",
+        )?;
+        report.mismatched_sessions = count(
+            &mut conn,
+            "SELECT COUNT(*) AS count FROM upload_sessions s \
+             JOIN task_queue t ON t.id = s.task_id \
+             WHERE s.drive_id != t.drive_id OR s.local_path != t.local_path",
+        )?;
+        report.dangling_downloads = diesel::sql_query(
+            "SELECT COUNT(*) AS count FROM task_queue t \
+             WHERE t.task_type = 'download' AND t.status IN (?, ?) \
+             AND NOT EXISTS (SELECT 1 FROM file_metadata m WHERE m.local_path = t.local_path)",
+        )
+        .bind::<Text, _>(TaskStatus::Pending.as_str())
+        .bind::<Text, _>(TaskStatus::Running.as_str())
+        .get_result::<CountRow>(&mut conn)
+        .context("Failed to check download tasks")?
+        .count as usize;
+
+        Ok(report)
+    }
+
+    /// Fix what [`InventoryDb::check_integrity`] finds: drop broken upload sessions,
+    /// fail download tasks that lost their entry and rebuild indexes if SQLite reports
+    /// damage. Check [`IntegrityReport::is_corrupt`] on `after` to see whether the file
+    /// still needs a rebuild.
+    pub fn repair_integrity(&self) -> Result<IntegrityRepair> {
+        let before = self.check_integrity()?;
+        let mut repair = IntegrityRepair::default();
+
+        if before.is_corrupt() {
+            let mut conn = self.connection()?;
+            conn.batch_execute("REINDEX;")
+                .context("Failed to rebuild inventory indexes")?;
+            repair.reindexed = true;
+        } else if !before.is_healthy() {
+            let mut conn = self.connection()?;
+            let now = Utc::now().timestamp();
+            (repair.removed_sessions, repair.failed_tasks) = conn
+                .transaction(|conn| {
+                    let removed = diesel::sql_query(
+                        "DELETE FROM upload_sessions \
+                         WHERE task_id NOT IN (SELECT id FROM task_queue) \
+                         OR EXISTS (SELECT 1 FROM task_queue t WHERE t.id = upload_sessions.task_id \
+                         AND (t.drive_id != upload_sessions.drive_id \
+                         OR t.local_path != upload_sessions.local_path))",
+                    )
+                    .execute(conn)?;
+                    let failed = diesel::sql_query(
+                        "UPDATE task_queue SET status = ?, error = ?, updated_at = ? \
+                         WHERE task_type = 'download' AND status IN (?, ?) \
+                         AND NOT EXISTS (SELECT 1 FROM file_metadata m \
+                         WHERE m.local_path = task_queue.local_path)",
+                    )
+                    .bind::<Text, _>(TaskStatus::Failed.as_str())
+                    .bind::<Text, _>(MISSING_ENTRY_ERROR)
+                    .bind::<BigInt, _>(now)
+                    .bind::<Text, _>(TaskStatus::Pending.as_str())
+                    .bind::<Text, _>(TaskStatus::Running.as_str())
+                    .execute(conn)?;
+                    diesel::QueryResult::Ok((removed, failed))
+                })
+                .context("Failed to repair inventory records")?;
+        }
+
+        repair.after = self.check_integrity()?;
+        repair.before = before;
+        tracing::info!(
+            target: "inventory",
+            removed_sessions = repair.removed_sessions,
+            failed_tasks = repair.failed_tasks,
+            reindexed = repair.reindexed,
+            healthy = repair.after.is_healthy(),
+            "Inventory integrity repair finished"
+        );
+        Ok(repair)
+    }
+}
+
+fn count(conn: &mut SqliteConnection, query: &str) -> Result<usize> {
+    let row = diesel::sql_query(query)
+        .get_result::<CountRow>(conn)
+        .with_context(|| format!("Failed to run integrity query: {}", query))?;
+    Ok(row.count as usize)
+}
+
+// =========================================================================
+// Row Types
+// =========================================================================
+
+#[derive(QueryableByName)]
+struct IntegrityCheckRow {
+    #[diesel(sql_type = Text)]
+    integrity_check: String,
+}
+
+#[derive(QueryableByName)]
+struct CountRow {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::{MetadataEntry, NewTaskRecord};
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    #[test]
+    fn repair_fails_downloads_without_entries() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        let drive_id = Uuid::new_v4();
+        assert!(db.check_integrity().unwrap().is_healthy());
+
+        db.insert(&MetadataEntry::new(drive_id, "/sync/kept.txt", false))
+            .unwrap();
+        for (id, path) in [("t1", "/sync/kept.txt"), ("t2", "/sync/gone.txt")] {
+            db.insert_task_if_not_exist(&NewTaskRecord::new(
+                id,
+                drive_id.to_string(),
+                "download",
+                path,
+            ))
+            .unwrap();
+        }
+
+        let report = db.check_integrity().unwrap();
+        assert_eq!(report.dangling_downloads, 1);
+        assert!(!report.is_corrupt());
+
+        let repair = db.repair_integrity().unwrap();
+        assert_eq!(repair.failed_tasks, 1);
+        assert!(repair.after.is_healthy());
+        assert_eq!(db.get_task_status("t2").unwrap(), Some(TaskStatus::Failed));
+        assert_eq!(db.get_task_status("t1").unwrap(), Some(TaskStatus::Pending));
+    }
+}
@@ -0,0 +1,188 @@
+use super::{InventoryDb, LIKE_ESCAPE, escape_like};
+use crate::inventory::{ChangeSource, JournalEntry, JournalOperation};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+use crate::inventory::schema::change_journal::{self, dsl as change_journal_dsl};
+
+impl InventoryDb {
+    /// Return a handle to the same database whose writes are journaled as `source`
+    pub fn with_source(&self, source: ChangeSource) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            source,
+            events: self.events.clone(),
+        }
+    }
+
+    /// Source recorded for writes made through this handle
+    pub fn source(&self) -> ChangeSource {
+        self.source
+    }
+
+    /// Most recent journal records, newest first. `path` limits the result to that path
+    /// and its descendants.
+    pub fn query_journal(&self, path: Option<&str>, limit: i64) -> Result<Vec<JournalEntry>> {
+        let mut conn = self.connection()?;
+        let mut query = change_journal_dsl::change_journal.into_boxed();
+
+        if let Some(path) = path {
+            let path = path.trim_end_matches(std::path::MAIN_SEPARATOR);
+            let descendant_like = format!("{}{}%", escape_like(path), std::path::MAIN_SEPARATOR);
+            query = query.filter(
+                change_journal_dsl::local_path
+                    .eq(path)
+                    .or(change_journal_dsl::local_path
+                        .like(descendant_like)
+                        .escape(LIKE_ESCAPE)),
+            );
+        }
+
+        let rows = query
+            .order(change_journal_dsl::id.desc())
+            .limit(limit.max(1))
+            .load::<JournalRow>(&mut conn)
+            .context("Failed to query change journal")?;
+
+        rows.into_iter().map(JournalEntry::try_from).collect()
+    }
+
+    /// Delete journal records created before `before` (Unix timestamp)
+    pub fn prune_journal(&self, before: i64) -> Result<usize> {
+        let mut conn = self.connection()?;
+        diesel::delete(
+            change_journal_dsl::change_journal.filter(change_journal_dsl::created_at.lt(before)),
+        )
+        .execute(&mut conn)
+        .context("Failed to prune change journal")
+    }
+
+    /// Build a journal record attributed to this handle's source
+    pub(super) fn journal_row(
+        &self,
+        drive_id: Option<&str>,
+        local_path: &str,
+        operation: JournalOperation,
+        detail: Option<String>,
+    ) -> NewJournalRow {
+        NewJournalRow {
+            drive_id: drive_id.map(|d| d.to_string()),
+            local_path: local_path.to_string(),
+            operation: operation.as_str().to_string(),
+            source: self.source.as_str().to_string(),
+            detail,
+            created_at: Utc::now().timestamp(),
+        }
+    }
+}
+
+/// Append journal records using an existing (usually transactional) connection
+pub(super) fn record(
+    conn: &mut SqliteConnection,
+    rows: &[NewJournalRow],
+) -> diesel::QueryResult<usize> {
+    if rows.is_empty() {
+        return Ok(0);
+    }
+    diesel::insert_into(change_journal::table)
+        .values(rows)
+        .execute(conn)
+}
+
+// =========================================================================
+// Row Types
+// =========================================================================
+
+#[derive(Queryable)]
+struct JournalRow {
+    id: i64,
+    drive_id: Option<String>,
+    local_path: String,
+    operation: String,
+    source: String,
+    detail: Option<String>,
+    created_at: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = change_journal)]
+pub(super) struct NewJournalRow {
+    drive_id: Option<String>,
+    local_path: String,
+    operation: String,
+    source: String,
+    detail: Option<String>,
+    created_at: i64,
+}
+
+impl TryFrom<JournalRow> for JournalEntry {
+    type Error = anyhow::Error;
+
+    fn try_from(row: JournalRow) -> Result<Self> {
+        let operation = JournalOperation::from_str(&row.operation)
+            .ok_or_else(|| anyhow::anyhow!("Unknown journal operation: {}", row.operation))?;
+        Ok(JournalEntry {
+            id: row.id,
+            drive_id: row.drive_id,
+            local_path: row.local_path,
+            operation,
+            source: ChangeSource::from_str(&row.source),
+            detail: row.detail,
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::MetadataEntry;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    #[test]
+    fn writes_are_journaled_with_their_source() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        let drive_id = Uuid::new_v4();
+
+        db.with_source(ChangeSource::Sync)
+            .upsert(&MetadataEntry::new(drive_id, "/sync/a.txt", false))
+            .unwrap();
+        db.with_source(ChangeSource::LocalEvent)
+            .rename_path("/sync/a.txt", "/sync/b.txt")
+            .unwrap();
+        db.batch_delete_by_path(vec!["/sync/b.txt"]).unwrap();
+
+        let journal = db.query_journal(None, 10).unwrap();
+        let ops: Vec<_> = journal.iter().map(|e| (e.operation, e.source)).collect();
+        assert_eq!(
+            ops,
+            vec![
+                (JournalOperation::Delete, ChangeSource::Unknown),
+                (JournalOperation::Rename, ChangeSource::LocalEvent),
+                (JournalOperation::Upsert, ChangeSource::Sync),
+            ]
+        );
+        assert_eq!(journal[1].detail.as_deref(), Some("/sync/a.txt"));
+        assert_eq!(journal[2].drive_id, Some(drive_id.to_string()));
+    }
+
+    #[test]
+    fn path_filter_matches_wildcards_literally() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        let drive_id = Uuid::new_v4();
+
+        for path in [r"C:\sync\100%_done\a.txt", r"C:\sync\100x1done\b.txt"] {
+            db.upsert(&MetadataEntry::new(drive_id, path, false))
+                .unwrap();
+        }
+
+        let journal = db.query_journal(Some(r"C:\sync\100%_done"), 10).unwrap();
+        let paths: Vec<_> = journal.iter().map(|e| e.local_path.as_str()).collect();
+        assert_eq!(paths, [r"C:\sync\100%_done\a.txt"]);
+    }
+}
@@ -1,25 +1,53 @@
+mod activity;
+mod batch;
+mod cfapi_failures;
+mod conflicts;
+mod dead_letters;
 mod drive_props;
+mod encryption;
+mod event_log;
 mod file_metadata;
+mod integrity;
+mod journal;
+mod notify;
+mod partition;
+mod remote_folders;
+mod snapshot;
 mod tasks;
 mod upload_sessions;
+mod usn_checkpoints;
 
+pub use batch::MetadataWriteBatch;
+pub use encryption::InventoryKey;
+pub use integrity::{IntegrityRepair, IntegrityReport};
+pub use snapshot::{InventorySnapshot, SNAPSHOT_VERSION, SnapshotImportStats};
 pub use tasks::RecentTasks;
 
+use crate::inventory::ChangeSource;
 use anyhow::{Context, Result, anyhow};
 use diesel::Connection;
-use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use diesel::connection::SimpleConnection;
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool, PooledConnection};
 use diesel::sqlite::SqliteConnection;
 use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
 use dirs::home_dir;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use uuid::Uuid;
 
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations/inventory");
 
+/// Appended to the path of a drive database to mark it for deletion on the next start
+const REMOVAL_MARKER_SUFFIX: &str = ".removed";
+
 /// SQLite-backed inventory database that relies on Diesel for schema management.
 pub struct InventoryDb {
     pool: Arc<Pool<ConnectionManager<SqliteConnection>>>,
+    /// Recorded in the change journal for metadata writes made through this handle
+    source: ChangeSource,
+    /// Broadcasts writes made through this handle, if set
+    events: Option<notify::InventoryEvents>,
 }
 
 impl InventoryDb {
@@ -32,6 +60,13 @@ impl InventoryDb {
     /// Create or open the inventory database at a specific path.
     /// The schema is automatically migrated to the latest version on startup.
     pub fn with_path(path: PathBuf) -> Result<Self> {
+        Self::open(path, false)
+    }
+
+    /// Create or open the inventory database at a specific path, encrypting it with the
+    /// key from the OS credential store if `encrypt` is set. An existing database is
+    /// encrypted or decrypted in place to match.
+    pub fn open(path: PathBuf, encrypt: bool) -> Result<Self> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).with_context(|| {
                 format!(
@@ -46,24 +81,142 @@ impl InventoryDb {
             .map(|s| s.to_string())
             .ok_or_else(|| anyhow!("Invalid inventory database path"))?;
 
-        run_migrations(&database_url)?;
+        let key = encryption::prepare(&path, encrypt)?;
+        run_migrations(&database_url, key.as_ref())?;
 
         let manager = ConnectionManager::<SqliteConnection>::new(database_url);
         let pool = Pool::builder()
             .max_size(1)
+            .connection_customizer(Box::new(SqliteConnectionOptions { key }))
             .build(manager)
             .context("Failed to build inventory database connection pool")?;
 
         Ok(Self {
             pool: Arc::new(pool),
+            source: ChangeSource::Unknown,
+            events: None,
         })
     }
 
+    /// Create or open the inventory database of a single drive
+    /// (~/.cloudreve/inventory/<drive_id>.db)
+    pub fn for_drive(drive_id: &str, encrypt: bool) -> Result<Self> {
+        Self::open(Self::get_drive_db_path(drive_id)?, encrypt)
+    }
+
+    /// Create or open the database holding the event log (~/.cloudreve/events.db)
+    pub fn for_event_log(encrypt: bool) -> Result<Self> {
+        let home = home_dir().ok_or_else(|| anyhow!("Unable to determine home directory"))?;
+        Self::open(home.join(".cloudreve").join("events.db"), encrypt)
+    }
+
+    /// Open the legacy database shared by all drives, if it still exists
+    pub fn open_legacy() -> Result<Option<Self>> {
+        let db_path = Self::get_db_path()?;
+        if !db_path.exists() {
+            return Ok(None);
+        }
+        Self::with_path(db_path).map(Some)
+    }
+
+    /// Delete a drive's database file together with its WAL and shared-memory files.
+    /// All handles to the database must be dropped first. If a file cannot be deleted,
+    /// e.g. because a task still holds the database open, the drive's files are left for
+    /// [`InventoryDb::remove_pending_drive_files`] on the next start.
+    pub fn remove_drive_files(drive_id: &str) -> Result<()> {
+        let db_path = Self::get_drive_db_path(drive_id)?;
+        if let Err(e) = remove_db_files(&db_path) {
+            let marker = removal_marker(&db_path);
+            fs::write(&marker, drive_id)
+                .with_context(|| format!("Failed to schedule removal of {}", db_path.display()))?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Rename a damaged drive database out of the way, together with its WAL and
+    /// shared-memory files, so the next open starts from an empty database. All handles
+    /// to the database must be dropped first. Nothing is moved if any file cannot be.
+    /// Returns the new path of the database file.
+    pub fn move_aside_drive_files(drive_id: &str) -> Result<PathBuf> {
+        let db_path = Self::get_drive_db_path(drive_id)?;
+        let aside_path = db_path.with_extension(format!(
+            "corrupt-{}.db",
+            chrono::Utc::now().format("%Y%m%d%H%M%S")
+        ));
+
+        let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+        for suffix in ["-wal", "-shm", ""] {
+            let from = with_suffix(&db_path, suffix);
+            if !from.exists() {
+                continue;
+            }
+            let to = with_suffix(&aside_path, suffix);
+            if let Err(e) = fs::rename(&from, &to) {
+                for (from, to) in moved.iter().rev() {
+                    let _ = fs::rename(to, from);
+                }
+                return Err(e).with_context(|| {
+                    format!("Failed to move aside inventory file {}", from.display())
+                });
+            }
+            moved.push((from, to));
+        }
+        Ok(aside_path)
+    }
+
+    /// Delete the database files of drives that were removed while their database was
+    /// still open. Must run before any drive database is opened. Returns the number of
+    /// drives cleaned up.
+    pub fn remove_pending_drive_files() -> Result<usize> {
+        let home = home_dir().ok_or_else(|| anyhow!("Unable to determine home directory"))?;
+        let dir = home.join(".cloudreve").join("inventory");
+        let read_dir = match fs::read_dir(&dir) {
+            Ok(read_dir) => read_dir,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e).context("Failed to list inventory databases"),
+        };
+
+        let mut removed = 0;
+        for marker in read_dir.flatten().map(|entry| entry.path()) {
+            let Some(db_path) = marker
+                .to_str()
+                .and_then(|path| path.strip_suffix(REMOVAL_MARKER_SUFFIX))
+                .map(PathBuf::from)
+            else {
+                continue;
+            };
+            remove_db_files(&db_path)?;
+            fs::remove_file(&marker).with_context(|| {
+                format!("Failed to remove inventory marker {}", marker.display())
+            })?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
     fn get_db_path() -> Result<PathBuf> {
         let home = home_dir().ok_or_else(|| anyhow!("Unable to determine home directory"))?;
         Ok(home.join(".cloudreve").join("meta.db"))
     }
 
+    fn get_drive_db_path(drive_id: &str) -> Result<PathBuf> {
+        // Drive IDs are UUIDs; reject anything else so the ID cannot escape the directory
+        let drive_id = Uuid::parse_str(drive_id).context("Invalid drive id")?;
+        let home = home_dir().ok_or_else(|| anyhow!("Unable to determine home directory"))?;
+        Ok(home
+            .join(".cloudreve")
+            .join("inventory")
+            .join(format!("{}.db", drive_id)))
+    }
+
+    /// Rebuild the database file to reclaim the space left behind by deleted rows
+    pub fn vacuum(&self) -> Result<()> {
+        let mut conn = self.connection()?;
+        conn.batch_execute("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")
+            .context("Failed to vacuum inventory database")
+    }
+
     pub(crate) fn connection(
         &self,
     ) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>> {
@@ -73,10 +226,83 @@ impl InventoryDb {
     }
 }
 
-fn run_migrations(database_url: &str) -> Result<()> {
+/// Per-connection SQLite settings applied whenever the pool opens a connection.
+///
+/// WAL lets readers proceed while a write transaction is open, and `synchronous = NORMAL`
+/// avoids an fsync per commit, which dominates the cost of the many small writes issued
+/// during sync bursts. Encrypted databases are unlocked first.
+#[derive(Debug)]
+struct SqliteConnectionOptions {
+    key: Option<InventoryKey>,
+}
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for SqliteConnectionOptions {
+    fn on_acquire(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> std::result::Result<(), diesel::r2d2::Error> {
+        if let Some(key) = &self.key {
+            conn.batch_execute(&key.pragma())
+                .map_err(diesel::r2d2::Error::QueryError)?;
+        }
+        conn.batch_execute(
+            "PRAGMA journal_mode = WAL; \
+             PRAGMA synchronous = NORMAL; \
+             PRAGMA busy_timeout = 5000;",
+        )
+        .map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+fn run_migrations(database_url: &str, key: Option<&InventoryKey>) -> Result<()> {
     let mut conn = SqliteConnection::establish(database_url)
         .with_context(|| format!("Failed to open inventory database at {}", database_url))?;
+    if let Some(key) = key {
+        conn.batch_execute(&key.pragma())
+            .context("Failed to unlock inventory database")?;
+    }
     conn.run_pending_migrations(MIGRATIONS)
         .map_err(|err| anyhow!("Failed to run inventory database migrations: {err}"))?;
     Ok(())
 }
+
+/// Delete a database file together with its WAL and shared-memory files
+fn remove_db_files(db_path: &Path) -> Result<()> {
+    for suffix in ["", "-wal", "-shm"] {
+        let file = with_suffix(db_path, suffix);
+        if file.exists() {
+            fs::remove_file(&file)
+                .with_context(|| format!("Failed to remove inventory file {}", file.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// `path` with `suffix` appended to its file name
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file = path.as_os_str().to_owned();
+    file.push(suffix);
+    PathBuf::from(file)
+}
+
+/// Path of the marker scheduling the deletion of `db_path` on the next start
+fn removal_marker(db_path: &Path) -> PathBuf {
+    with_suffix(db_path, REMOVAL_MARKER_SUFFIX)
+}
+
+/// Escape character of LIKE patterns built from paths. Not `\`, which separates the
+/// components of Windows paths.
+pub(super) const LIKE_ESCAPE: char = '!';
+
+/// `value` with `%`, `_` and [`LIKE_ESCAPE`] escaped, so it matches literally in a LIKE
+/// pattern using [`LIKE_ESCAPE`]
+pub(super) fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '%' | '_' | LIKE_ESCAPE) {
+            escaped.push(LIKE_ESCAPE);
+        }
+        escaped.push(c);
+    }
+    escaped
+}
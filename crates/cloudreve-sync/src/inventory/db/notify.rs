@@ -0,0 +1,286 @@
+use super::InventoryDb;
+use crate::events::{EntryChange, EventBroadcaster};
+use crate::inventory::{NewConflictRecord, TaskStatus};
+use crate::tasks::{TaskKind, TaskProgress};
+use std::sync::Arc;
+
+/// Where a handle reports its writes, see [`InventoryDb::with_events`]
+#[derive(Clone)]
+pub(super) struct InventoryEvents {
+    drive_id: String,
+    broadcaster: Arc<EventBroadcaster>,
+}
+
+impl InventoryDb {
+    /// Broadcast entry and task changes made through this handle (and handles derived
+    /// from it) as events of `drive_id`, so the UI can refresh without polling
+    pub fn with_events(mut self, drive_id: &str, broadcaster: Arc<EventBroadcaster>) -> Self {
+        self.events = Some(InventoryEvents {
+            drive_id: drive_id.to_string(),
+            broadcaster,
+        });
+        self
+    }
+
+    /// Report entries written successfully. Called after the transaction commits.
+    pub(super) fn notify_entries<I, S>(&self, change: EntryChange, local_paths: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let Some(events) = self.subscribed_events() else {
+            return;
+        };
+        let local_paths: Vec<String> = local_paths.into_iter().map(Into::into).collect();
+        if local_paths.is_empty() {
+            return;
+        }
+        events
+            .broadcaster
+            .inventory_entries_changed(&events.drive_id, change, local_paths);
+    }
+
+    /// Report a task reaching a final status
+    pub(super) fn notify_task_finished(&self, task_id: &str, status: TaskStatus) {
+        if status.is_active() {
+            return;
+        }
+        if let Some(events) = self.subscribed_events() {
+            events
+                .broadcaster
+                .task_finished(&events.drive_id, task_id, status);
+        }
+    }
+
+    /// Report tasks moved to `status` by a bulk operation
+    pub(super) fn notify_tasks_updated(&self, task_ids: &[String], status: TaskStatus) {
+        if task_ids.is_empty() {
+            return;
+        }
+        if let Some(events) = self.subscribed_events() {
+            events
+                .broadcaster
+                .tasks_updated(&events.drive_id, status, task_ids.to_vec());
+        }
+    }
+
+    /// Report live progress of running tasks. Progress is kept in memory by the task
+    /// queue; this only shares the drive's event channel.
+    pub fn notify_task_progress(&self, progress: impl IntoIterator<Item = TaskProgress>) {
+        let Some(events) = self.subscribed_events() else {
+            return;
+        };
+        for progress in progress {
+            events.broadcaster.task_progress(&events.drive_id, progress);
+        }
+    }
+
+    /// Report the sync root registration of the drive being restored
+    pub fn notify_sync_root_repaired(&self, restamped: usize) {
+        if let Some(events) = self.subscribed_events() {
+            events
+                .broadcaster
+                .sync_root_repaired(&events.drive_id, restamped);
+        }
+    }
+
+    /// Report the new size of the dead-letter store
+    pub(super) fn notify_dead_letters_changed(&self) {
+        let Some(events) = self.subscribed_events() else {
+            return;
+        };
+        match self.count_dead_letters(Some(&events.drive_id)) {
+            Ok(count) => events
+                .broadcaster
+                .dead_letters_changed(&events.drive_id, count.max(0) as usize),
+            Err(e) => {
+                tracing::warn!(target: "inventory", error = %e, "Failed to count dead letters");
+            }
+        }
+    }
+
+    /// Report a conflict recorded with `conflict_id`
+    pub(super) fn notify_conflict_detected(&self, conflict_id: i64, record: &NewConflictRecord) {
+        if let Some(events) = self.subscribed_events() {
+            events.broadcaster.conflict_detected(
+                &events.drive_id,
+                conflict_id,
+                &record.original_path,
+                record.kind,
+                record.resolution,
+            );
+        }
+    }
+
+    /// Report a task that started running. Only downloads are reported.
+    pub fn notify_task_started(&self, task_id: &str, kind: TaskKind, local_path: &str) {
+        if kind != TaskKind::Download {
+            return;
+        }
+        if let Some(events) = self.subscribed_events() {
+            events
+                .broadcaster
+                .file_download_started(&events.drive_id, task_id, local_path);
+        }
+    }
+
+    /// Report the outcome of a task that stopped running for good, `error` is set when
+    /// it failed. Hydrations are not reported.
+    pub fn notify_task_outcome(
+        &self,
+        task_id: &str,
+        kind: TaskKind,
+        local_path: &str,
+        error: Option<&str>,
+    ) {
+        let Some(events) = self.subscribed_events() else {
+            return;
+        };
+        let broadcaster = &events.broadcaster;
+        match (kind, error) {
+            (TaskKind::Upload, None) => {
+                broadcaster.file_synced(&events.drive_id, local_path, kind);
+            }
+            (TaskKind::Upload, Some(error)) => {
+                broadcaster.upload_failed(&events.drive_id, task_id, local_path, error);
+            }
+            (TaskKind::Download, error) => {
+                broadcaster.file_download_finished(
+                    &events.drive_id,
+                    task_id,
+                    local_path,
+                    error.map(str::to_string),
+                );
+                if error.is_none() {
+                    broadcaster.file_synced(&events.drive_id, local_path, kind);
+                }
+            }
+            (TaskKind::Hydrate, _) => {}
+        }
+    }
+
+    /// Report the credential of the drive expiring
+    pub fn notify_credential_expired(&self, drive_name: &str) {
+        if let Some(events) = self.subscribed_events() {
+            events
+                .broadcaster
+                .credential_expired(&events.drive_id, drive_name);
+        }
+    }
+
+    /// Report the used capacity of the drive crossing the warning threshold
+    pub fn notify_quota_warning(&self, used: i64, total: i64) {
+        if let Some(events) = self.subscribed_events() {
+            events
+                .broadcaster
+                .quota_warning(&events.drive_id, used, total);
+        }
+    }
+
+    /// Skip building events nobody listens to
+    fn subscribed_events(&self) -> Option<&InventoryEvents> {
+        self.events
+            .as_ref()
+            .filter(|events| events.broadcaster.subscriber_count() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Event;
+    use crate::inventory::{ConflictKind, MetadataEntry, NewTaskRecord, TaskUpdate};
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    #[test]
+    fn writes_are_broadcast() {
+        let dir = tempdir().unwrap();
+        let broadcaster = Arc::new(EventBroadcaster::new(16));
+        let mut rx = broadcaster.subscribe();
+        let drive_id = Uuid::new_v4();
+        let db = InventoryDb::with_path(dir.path().join("meta.db"))
+            .unwrap()
+            .with_events(&drive_id.to_string(), broadcaster);
+
+        db.insert(&MetadataEntry::new(drive_id, "/sync/a.txt", false))
+            .unwrap();
+        db.batch_delete_by_path(vec!["/sync/a.txt", "/sync/missing.txt"])
+            .unwrap();
+        db.insert_task_if_not_exist(&NewTaskRecord::new(
+            "t1",
+            drive_id.to_string(),
+            "upload",
+            "/sync/a.txt",
+        ))
+        .unwrap();
+        db.update_task(
+            "t1",
+            TaskUpdate {
+                status: Some(TaskStatus::Completed),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let events: Vec<Event> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[0],
+            Event::InventoryEntriesChanged { change: EntryChange::Added, local_paths, .. }
+                if local_paths == &["/sync/a.txt"]
+        ));
+        assert!(matches!(
+            &events[1],
+            Event::InventoryEntriesChanged { change: EntryChange::Removed, local_paths, .. }
+                if local_paths == &["/sync/a.txt"]
+        ));
+        assert!(matches!(
+            &events[2],
+            Event::TaskFinished { task_id, status: TaskStatus::Completed, .. } if task_id == "t1"
+        ));
+    }
+
+    #[test]
+    fn conflicts_and_task_outcomes_are_broadcast() {
+        let dir = tempdir().unwrap();
+        let broadcaster = Arc::new(EventBroadcaster::new(16));
+        let mut rx = broadcaster.subscribe();
+        let db = InventoryDb::with_path(dir.path().join("meta.db"))
+            .unwrap()
+            .with_events("drive", broadcaster);
+
+        let conflict_id = db
+            .insert_conflict(&NewConflictRecord::new(
+                "drive",
+                "/sync/a.txt",
+                ConflictKind::Content,
+            ))
+            .unwrap();
+        db.notify_task_outcome("t1", TaskKind::Download, "/sync/b.txt", None);
+        db.notify_task_outcome(
+            "t2",
+            TaskKind::Upload,
+            "/sync/c.txt",
+            Some("quota exceeded"),
+        );
+        db.notify_task_outcome("t3", TaskKind::Hydrate, "/sync/d.txt", None);
+
+        let events: Vec<Event> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        let names: Vec<&str> = events.iter().map(Event::name).collect();
+        assert_eq!(
+            names,
+            [
+                "ConflictDetected",
+                "FileDownloadFinished",
+                "FileSynced",
+                "UploadFailed"
+            ]
+        );
+        assert!(matches!(
+            &events[0],
+            Event::ConflictDetected { conflict_id: id, local_path, .. }
+                if *id == conflict_id && local_path == "/sync/a.txt"
+        ));
+    }
+}
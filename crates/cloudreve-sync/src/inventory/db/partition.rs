@@ -0,0 +1,94 @@
+use super::InventoryDb;
+use crate::inventory::{ChangeSource, MetadataEntry};
+use anyhow::{Context, Result};
+
+impl InventoryDb {
+    /// Move a drive's rows out of the legacy shared database into this per-drive database.
+    ///
+    /// Metadata entries, task history, upload sessions and conflicts are copied, then
+    /// deleted from `legacy`. Drive props are dropped and refetched on the next props refresh.
+    /// Returns the number of metadata entries moved; a drive with nothing left in the
+    /// legacy database is a no-op.
+    pub fn migrate_drive_from(&self, legacy: &InventoryDb, drive_id: &str) -> Result<usize> {
+        let entries: Vec<MetadataEntry> = legacy
+            .list_by_drive(drive_id)?
+            .iter()
+            .map(MetadataEntry::from)
+            .collect();
+        let tasks = legacy.list_tasks(Some(drive_id), None)?;
+        let sessions = legacy.list_upload_sessions_by_drive(drive_id)?;
+        let conflicts = legacy.list_conflicts(Some(drive_id), false)?;
+        if entries.is_empty() && tasks.is_empty() && sessions.is_empty() && conflicts.is_empty() {
+            return Ok(0);
+        }
+
+        let moved = self
+            .with_source(ChangeSource::Import)
+            .batch_upsert(&entries)
+            .context("Failed to copy legacy inventory entries")?;
+        self.import_task_records(&tasks)
+            .context("Failed to copy legacy task records")?;
+        for session in &sessions {
+            if self.get_upload_session(&session.task_id)?.is_none() {
+                self.insert_upload_session(session)?;
+            }
+        }
+        self.import_conflicts(&conflicts)
+            .context("Failed to copy legacy conflict records")?;
+
+        legacy.nuke_drive(drive_id)?;
+        legacy.delete_tasks_by_drive(drive_id)?;
+        for session in &sessions {
+            legacy.delete_upload_session(&session.id)?;
+        }
+        legacy.delete_conflicts_by_drive(drive_id)?;
+        legacy.delete_drive_props(drive_id)?;
+
+        tracing::info!(
+            target: "inventory",
+            drive = %drive_id,
+            entries = moved,
+            tasks = tasks.len(),
+            sessions = sessions.len(),
+            conflicts = conflicts.len(),
+            "Moved drive out of legacy inventory database"
+        );
+        Ok(moved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use uuid::Uuid;
+
+    #[test]
+    fn migrate_moves_only_the_requested_drive() {
+        let dir = tempdir().unwrap();
+        let legacy = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        let drive_a = Uuid::new_v4();
+        let drive_b = Uuid::new_v4();
+        legacy
+            .insert(&MetadataEntry::new(drive_a, "/a/file.txt", false))
+            .unwrap();
+        legacy
+            .insert(&MetadataEntry::new(drive_b, "/b/file.txt", false))
+            .unwrap();
+
+        let partition = InventoryDb::with_path(dir.path().join("a.db")).unwrap();
+        let moved = partition
+            .migrate_drive_from(&legacy, &drive_a.to_string())
+            .unwrap();
+        assert_eq!(moved, 1);
+        assert!(partition.query_by_path("/a/file.txt").unwrap().is_some());
+        assert!(partition.query_by_path("/b/file.txt").unwrap().is_none());
+        assert!(legacy.query_by_path("/a/file.txt").unwrap().is_none());
+        assert!(legacy.query_by_path("/b/file.txt").unwrap().is_some());
+
+        let again = partition
+            .migrate_drive_from(&legacy, &drive_a.to_string())
+            .unwrap();
+        assert_eq!(again, 0);
+    }
+}
@@ -0,0 +1,126 @@
+use super::InventoryDb;
+use crate::inventory::RemoteFolderEntry;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::inventory::schema::remote_folder_cache::{self, dsl as remote_folder_cache_dsl};
+
+impl InventoryDb {
+    /// Cached subfolders of the remote folder `parent_uri`, with the Unix timestamp
+    /// they were listed at
+    pub fn get_cached_remote_folders(
+        &self,
+        drive_id: &str,
+        parent_uri: &str,
+    ) -> Result<Option<(Vec<RemoteFolderEntry>, i64)>> {
+        let mut conn = self.connection()?;
+        let row = remote_folder_cache_dsl::remote_folder_cache
+            .filter(remote_folder_cache_dsl::drive_id.eq(drive_id))
+            .filter(remote_folder_cache_dsl::parent_uri.eq(parent_uri))
+            .first::<RemoteFolderCacheRow>(&mut conn)
+            .optional()
+            .context("Failed to query cached remote folders")?;
+
+        // A cache entry of an older version that no longer deserializes is a miss
+        Ok(row.and_then(|row| {
+            serde_json::from_str(&row.folders)
+                .ok()
+                .map(|folders| (folders, row.fetched_at))
+        }))
+    }
+
+    /// Cache the subfolders of the remote folder `parent_uri`, replacing the previous
+    /// listing
+    pub fn cache_remote_folders(
+        &self,
+        drive_id: &str,
+        parent_uri: &str,
+        folders: &[RemoteFolderEntry],
+    ) -> Result<()> {
+        let row = RemoteFolderCacheRow {
+            drive_id: drive_id.to_string(),
+            parent_uri: parent_uri.to_string(),
+            folders: serde_json::to_string(folders)
+                .context("Failed to serialize remote folders")?,
+            fetched_at: Utc::now().timestamp(),
+        };
+
+        let mut conn = self.connection()?;
+        diesel::replace_into(remote_folder_cache::table)
+            .values(&row)
+            .execute(&mut conn)
+            .context("Failed to cache remote folders")?;
+        Ok(())
+    }
+
+    /// Remove the cached remote folder listings of a drive. Returns how many were removed.
+    pub fn clear_remote_folder_cache(&self, drive_id: &str) -> Result<usize> {
+        let mut conn = self.connection()?;
+        diesel::delete(
+            remote_folder_cache_dsl::remote_folder_cache
+                .filter(remote_folder_cache_dsl::drive_id.eq(drive_id)),
+        )
+        .execute(&mut conn)
+        .context("Failed to clear cached remote folders")
+    }
+}
+
+// =========================================================================
+// Row Types
+// =========================================================================
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = remote_folder_cache)]
+struct RemoteFolderCacheRow {
+    drive_id: String,
+    parent_uri: String,
+    folders: String,
+    fetched_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn remote_folder_listing_is_replaced() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        let root = "cloudreve://my";
+        assert!(
+            db.get_cached_remote_folders("drive", root)
+                .unwrap()
+                .is_none()
+        );
+
+        let folder = |name: &str, size| RemoteFolderEntry {
+            name: name.to_string(),
+            uri: format!("{}/{}", root, name),
+            size,
+        };
+        db.cache_remote_folders("drive", root, &[folder("a", Some(10)), folder("b", None)])
+            .unwrap();
+        db.cache_remote_folders("drive", root, &[folder("c", Some(20))])
+            .unwrap();
+
+        let (folders, _) = db
+            .get_cached_remote_folders("drive", root)
+            .unwrap()
+            .unwrap();
+        assert_eq!(folders, [folder("c", Some(20))]);
+        assert!(
+            db.get_cached_remote_folders("other", root)
+                .unwrap()
+                .is_none()
+        );
+
+        assert_eq!(db.clear_remote_folder_cache("drive").unwrap(), 1);
+        assert!(
+            db.get_cached_remote_folders("drive", root)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use super::InventoryDb;
+use crate::inventory::{ChangeSource, FileMetadata, MetadataEntry, TaskRecord, TaskStatus};
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{MAIN_SEPARATOR, Path};
+use uuid::Uuid;
+
+/// Current snapshot format version. Bump when the layout changes incompatibly.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Portable copy of a drive's inventory, used to restore state after reinstalling
+/// without rebuilding it from a full walk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventorySnapshot {
+    pub version: u32,
+    /// Drive the snapshot was taken from
+    pub drive_id: String,
+    /// Sync root the entries were recorded under
+    pub sync_path: String,
+    /// Unix timestamp
+    pub exported_at: i64,
+    pub entries: Vec<FileMetadata>,
+    /// Finished tasks only; active tasks are never exported
+    pub tasks: Vec<TaskRecord>,
+}
+
+/// Counters reported after importing a snapshot
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SnapshotImportStats {
+    pub entries: usize,
+    pub tasks: usize,
+    /// Rows outside the snapshot's sync root that could not be remapped
+    pub skipped: usize,
+}
+
+impl InventorySnapshot {
+    /// Write the snapshot as JSON to `path`
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create snapshot parent dir {}", parent.display())
+            })?;
+        }
+        let content = serde_json::to_vec(self).context("Failed to serialize inventory snapshot")?;
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write inventory snapshot {}", path.display()))
+    }
+
+    /// Read a snapshot previously written by [`InventorySnapshot::write_to`]
+    pub fn read_from(path: &Path) -> Result<Self> {
+        let content = fs::read(path)
+            .with_context(|| format!("Failed to read inventory snapshot {}", path.display()))?;
+        let snapshot: Self =
+            serde_json::from_slice(&content).context("Failed to parse inventory snapshot")?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            bail!(
+                "Unsupported inventory snapshot version {} (max {})",
+                snapshot.version,
+                SNAPSHOT_VERSION
+            );
+        }
+        Ok(snapshot)
+    }
+}
+
+impl InventoryDb {
+    /// Capture all metadata entries and finished task history for a drive
+    pub fn export_snapshot(&self, drive_id: &str, sync_path: &str) -> Result<InventorySnapshot> {
+        let entries = self.list_by_drive(drive_id)?;
+        let tasks = self.list_tasks(
+            Some(drive_id),
+            Some(&[
+                TaskStatus::Completed,
+                TaskStatus::Failed,
+                TaskStatus::Cancelled,
+                TaskStatus::TimedOut,
+            ]),
+        )?;
+
+        Ok(InventorySnapshot {
+            version: SNAPSHOT_VERSION,
+            drive_id: drive_id.to_string(),
+            sync_path: sync_path.to_string(),
+            exported_at: Utc::now().timestamp(),
+            entries,
+            tasks,
+        })
+    }
+
+    /// Import a snapshot into `drive_id`, rewriting paths from the snapshot's sync root
+    /// to `sync_path`. Existing entries with the same local path are overwritten.
+    pub fn import_snapshot(
+        &self,
+        snapshot: &InventorySnapshot,
+        drive_id: &str,
+        sync_path: &str,
+    ) -> Result<SnapshotImportStats> {
+        let drive_uuid = Uuid::parse_str(drive_id).context("Invalid drive id for import")?;
+        let mut stats = SnapshotImportStats::default();
+
+        let mut entries = Vec::with_capacity(snapshot.entries.len());
+        for meta in &snapshot.entries {
+            let Some(local_path) = remap_path(&meta.local_path, &snapshot.sync_path, sync_path)
+            else {
+                stats.skipped += 1;
+                continue;
+            };
+            let mut entry = MetadataEntry::from(meta);
+            entry.drive_id = drive_uuid;
+            entry.local_path = local_path;
+            entries.push(entry);
+        }
+
+        let mut tasks = Vec::with_capacity(snapshot.tasks.len());
+        for record in snapshot.tasks.iter().filter(|t| !t.status.is_active()) {
+            let Some(local_path) = remap_path(&record.local_path, &snapshot.sync_path, sync_path)
+            else {
+                stats.skipped += 1;
+                continue;
+            };
+            let mut task = record.clone();
+            task.drive_id = drive_id.to_string();
+            task.local_path = local_path;
+            tasks.push(task);
+        }
+
+        stats.entries = self
+            .with_source(ChangeSource::Import)
+            .batch_upsert(&entries)?;
+        stats.tasks = self.import_task_records(&tasks)?;
+
+        tracing::info!(
+            target: "inventory",
+            drive = %drive_id,
+            entries = stats.entries,
+            tasks = stats.tasks,
+            skipped = stats.skipped,
+            "Imported inventory snapshot"
+        );
+
+        Ok(stats)
+    }
+}
+
+/// Rewrite `path` from `old_root` to `new_root`. Returns `None` if `path` is not under `old_root`.
+fn remap_path(path: &str, old_root: &str, new_root: &str) -> Option<String> {
+    let old_root = old_root.trim_end_matches(MAIN_SEPARATOR);
+    let new_root = new_root.trim_end_matches(MAIN_SEPARATOR);
+
+    if path == old_root {
+        return Some(new_root.to_string());
+    }
+
+    let rest = path.strip_prefix(old_root)?;
+    if !rest.starts_with(MAIN_SEPARATOR) {
+        return None;
+    }
+    Some(format!("{}{}", new_root, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn sep(parts: &[&str]) -> String {
+        parts.join(&MAIN_SEPARATOR.to_string())
+    }
+
+    #[test]
+    fn remap_path_rewrites_root_and_descendants() {
+        let old_root = sep(&["", "old", "root"]);
+        let new_root = sep(&["", "new"]);
+        assert_eq!(
+            remap_path(&old_root, &old_root, &new_root),
+            Some(new_root.clone())
+        );
+        assert_eq!(
+            remap_path(&sep(&["", "old", "root", "a.txt"]), &old_root, &new_root),
+            Some(sep(&["", "new", "a.txt"]))
+        );
+        assert_eq!(
+            remap_path(
+                &sep(&["", "old", "rootless", "a.txt"]),
+                &old_root,
+                &new_root
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn snapshot_round_trip_remaps_drive_and_paths() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        let old_drive = Uuid::new_v4();
+        let new_drive = Uuid::new_v4();
+        let old_root = sep(&["", "sync"]);
+        let new_root = sep(&["", "restored"]);
+
+        let entry =
+            MetadataEntry::new(old_drive, sep(&["", "sync", "doc.txt"]), false).with_etag("e1");
+        db.insert(&entry).unwrap();
+
+        let file = dir.path().join("snapshot.json");
+        db.export_snapshot(&old_drive.to_string(), &old_root)
+            .unwrap()
+            .write_to(&file)
+            .unwrap();
+
+        let snapshot = InventorySnapshot::read_from(&file).unwrap();
+        let stats = db
+            .import_snapshot(&snapshot, &new_drive.to_string(), &new_root)
+            .unwrap();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.skipped, 0);
+
+        let restored = db
+            .query_by_path(&sep(&["", "restored", "doc.txt"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored.drive_id, new_drive);
+        assert_eq!(restored.etag, "e1");
+    }
+}
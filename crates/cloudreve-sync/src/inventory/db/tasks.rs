@@ -1,10 +1,16 @@
 use super::InventoryDb;
-use crate::inventory::{NewTaskRecord, TaskRecord, TaskStatus, TaskUpdate};
+use crate::inventory::{
+    NewTaskRecord, TaskAttempt, TaskFailureClass, TaskRecord, TaskStatus, TaskUpdate,
+};
 use anyhow::{Context, Result, anyhow};
 use chrono::Utc;
+use diesel::dsl::max;
 use diesel::prelude::*;
 
+use crate::inventory::schema::dead_letters::dsl as dead_letters_dsl;
 use crate::inventory::schema::task_queue::{self, dsl as task_queue_dsl};
+use crate::tasks::TaskKind;
+use std::collections::HashSet;
 
 impl InventoryDb {
     /// Insert a task queue record if no pending/running task with the same type and path exists.
@@ -40,6 +46,26 @@ impl InventoryDb {
         Ok(true)
     }
 
+    /// Insert task records as-is, skipping any whose id already exists.
+    /// Used to restore task history from an inventory snapshot.
+    /// Returns the number of records inserted.
+    pub fn import_task_records(&self, records: &[TaskRecord]) -> Result<usize> {
+        if records.is_empty() {
+            return Ok(0);
+        }
+
+        let rows: Vec<NewTaskRow> = records
+            .iter()
+            .map(NewTaskRow::try_from)
+            .collect::<Result<_>>()?;
+
+        let mut conn = self.connection()?;
+        diesel::insert_or_ignore_into(task_queue::table)
+            .values(&rows)
+            .execute(&mut conn)
+            .context("Failed to import task queue records")
+    }
+
     /// Update task queue record
     pub fn update_task(&self, task_id: &str, update: TaskUpdate) -> Result<()> {
         if update.is_empty() {
@@ -47,13 +73,36 @@ impl InventoryDb {
         }
 
         let mut conn = self.connection()?;
+        let status = update.status;
         let changeset = TaskChangeset::try_from(update)?;
-        diesel::update(task_queue_dsl::task_queue.filter(task_queue_dsl::id.eq(task_id)))
-            .set(changeset)
-            .execute(&mut conn)?;
+        let updated =
+            diesel::update(task_queue_dsl::task_queue.filter(task_queue_dsl::id.eq(task_id)))
+                .set(changeset)
+                .execute(&mut conn)?;
+        if let Some(status) = status.filter(|_| updated > 0) {
+            self.notify_task_finished(task_id, status);
+        }
         Ok(())
     }
 
+    /// Move a pending task to running. Returns `false` if the task is no longer pending,
+    /// e.g. because it was cancelled after it was dispatched.
+    pub fn mark_task_running(&self, task_id: &str) -> Result<bool> {
+        let mut conn = self.connection()?;
+        let updated = diesel::update(
+            task_queue_dsl::task_queue
+                .filter(task_queue_dsl::id.eq(task_id))
+                .filter(task_queue_dsl::status.eq(TaskStatus::Pending.as_str())),
+        )
+        .set((
+            task_queue_dsl::status.eq(TaskStatus::Running.as_str()),
+            task_queue_dsl::updated_at.eq(Utc::now().timestamp()),
+        ))
+        .execute(&mut conn)
+        .context("Failed to mark task as running")?;
+        Ok(updated > 0)
+    }
+
     /// List task queue records with optional filters
     pub fn list_tasks(
         &self,
@@ -94,6 +143,75 @@ impl InventoryDb {
         Ok(())
     }
 
+    /// Delete all task entries of a drive
+    pub fn delete_tasks_by_drive(&self, drive_id: &str) -> Result<usize> {
+        let mut conn = self.connection()?;
+        diesel::delete(task_queue_dsl::task_queue.filter(task_queue_dsl::drive_id.eq(drive_id)))
+            .execute(&mut conn)
+            .context("Failed to delete task queue records for drive")
+    }
+
+    /// Delete finished tasks last updated before `cutoff` (Unix timestamp), then all but
+    /// the `keep` most recently updated finished tasks. Returns the number deleted.
+    pub fn prune_finished_tasks(&self, cutoff: i64, keep: usize) -> Result<usize> {
+        let mut conn = self.connection()?;
+        let finished = finished_status_strings();
+        conn.transaction(|conn| {
+            let mut deleted = diesel::delete(
+                task_queue_dsl::task_queue
+                    .filter(task_queue_dsl::status.eq_any(&finished))
+                    .filter(task_queue_dsl::updated_at.lt(cutoff)),
+            )
+            .execute(conn)
+            .context("Failed to delete expired task history")?;
+
+            let excess: Vec<String> = task_queue_dsl::task_queue
+                .filter(task_queue_dsl::status.eq_any(&finished))
+                .order((task_queue_dsl::updated_at.desc(), task_queue_dsl::id.desc()))
+                .offset(keep as i64)
+                .select(task_queue_dsl::id)
+                .load(conn)
+                .context("Failed to query excess task history")?;
+            for ids in excess.chunks(500) {
+                deleted += diesel::delete(
+                    task_queue_dsl::task_queue.filter(task_queue_dsl::id.eq_any(ids)),
+                )
+                .execute(conn)
+                .context("Failed to delete excess task history")?;
+            }
+            Ok(deleted)
+        })
+    }
+
+    /// Delete all finished tasks, optionally only those of one drive
+    pub fn clear_finished_tasks(&self, drive_id: Option<&str>) -> Result<usize> {
+        let mut conn = self.connection()?;
+        let mut query = diesel::delete(task_queue_dsl::task_queue)
+            .filter(task_queue_dsl::status.eq_any(finished_status_strings()))
+            .into_boxed();
+        if let Some(drive) = drive_id {
+            query = query.filter(task_queue_dsl::drive_id.eq(drive));
+        }
+        query
+            .execute(&mut conn)
+            .context("Failed to clear task history")
+    }
+
+    /// Count finished tasks, optionally only those of one drive
+    pub fn count_finished_tasks(&self, drive_id: Option<&str>) -> Result<i64> {
+        let mut conn = self.connection()?;
+        let mut query = task_queue_dsl::task_queue
+            .filter(task_queue_dsl::status.eq_any(finished_status_strings()))
+            .into_boxed();
+        if let Some(drive) = drive_id {
+            query = query.filter(task_queue_dsl::drive_id.eq(drive));
+        }
+        query
+            .count()
+            .get_result(&mut conn)
+            .context("Failed to count task history")
+    }
+
     /// Cancel all pending/running tasks matching a path or its descendants.
     /// Returns the list of task IDs that were cancelled.
     pub fn cancel_tasks_by_path(&self, drive_id: &str, path: &str) -> Result<Vec<String>> {
@@ -132,9 +250,185 @@ impl InventoryDb {
                 .context("Failed to cancel tasks by path")?;
         }
 
+        for task_id in &task_ids {
+            self.notify_task_finished(task_id, TaskStatus::Cancelled);
+        }
+
         Ok(task_ids)
     }
 
+    /// Cancel every pending or running task of a drive, or only those of `task_type`, in
+    /// one transaction. Reported as a single [`Event::TasksUpdated`](crate::events::Event).
+    /// Returns the ids of the cancelled tasks.
+    pub fn cancel_tasks(&self, drive_id: &str, task_type: Option<&str>) -> Result<Vec<String>> {
+        let mut conn = self.connection()?;
+        let active_statuses = vec![
+            TaskStatus::Pending.as_str().to_string(),
+            TaskStatus::Running.as_str().to_string(),
+        ];
+        let now = Utc::now().timestamp();
+
+        let task_ids = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                let mut query = task_queue_dsl::task_queue
+                    .filter(task_queue_dsl::drive_id.eq(drive_id))
+                    .filter(task_queue_dsl::status.eq_any(&active_statuses))
+                    .select(task_queue_dsl::id)
+                    .into_boxed();
+                if let Some(task_type) = task_type {
+                    query = query.filter(task_queue_dsl::task_type.eq(task_type));
+                }
+                let task_ids: Vec<String> = query.load(conn)?;
+
+                diesel::update(
+                    task_queue_dsl::task_queue.filter(task_queue_dsl::id.eq_any(&task_ids)),
+                )
+                .set((
+                    task_queue_dsl::status.eq(TaskStatus::Cancelled.as_str()),
+                    task_queue_dsl::updated_at.eq(now),
+                ))
+                .execute(conn)?;
+                Ok(task_ids)
+            })
+            .context("Failed to cancel tasks")?;
+
+        self.notify_tasks_updated(&task_ids, TaskStatus::Cancelled);
+        Ok(task_ids)
+    }
+
+    /// Move every failed or timed out task of a drive back to pending with a fresh
+    /// attempt count, and drop the dead letters of their type and path, in one
+    /// transaction.
+    /// Hydrations and tasks whose path already has an active task of the same type are
+    /// left alone. Returns the requeued tasks.
+    pub fn requeue_failed_tasks(&self, drive_id: &str) -> Result<Vec<TaskRecord>> {
+        let mut conn = self.connection()?;
+        let failed_statuses = vec![
+            TaskStatus::Failed.as_str().to_string(),
+            TaskStatus::TimedOut.as_str().to_string(),
+        ];
+        let active_statuses = vec![
+            TaskStatus::Pending.as_str().to_string(),
+            TaskStatus::Running.as_str().to_string(),
+        ];
+        let now = Utc::now().timestamp();
+
+        let (rows, dead_letters_removed) = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                let mut active: HashSet<(String, String)> = task_queue_dsl::task_queue
+                    .filter(task_queue_dsl::drive_id.eq(drive_id))
+                    .filter(task_queue_dsl::status.eq_any(&active_statuses))
+                    .select((task_queue_dsl::task_type, task_queue_dsl::local_path))
+                    .load::<(String, String)>(conn)?
+                    .into_iter()
+                    .collect();
+
+                // A path may have failed more than once; requeue only its newest task
+                let rows: Vec<TaskRow> = task_queue_dsl::task_queue
+                    .filter(task_queue_dsl::drive_id.eq(drive_id))
+                    .filter(task_queue_dsl::status.eq_any(&failed_statuses))
+                    .filter(task_queue_dsl::task_type.ne(TaskKind::Hydrate.as_str()))
+                    .order(task_queue_dsl::updated_at.desc())
+                    .load::<TaskRow>(conn)?
+                    .into_iter()
+                    .filter(|row| active.insert((row.task_type.clone(), row.local_path.clone())))
+                    .collect();
+                let task_ids: Vec<&String> = rows.iter().map(|row| &row.id).collect();
+
+                diesel::update(
+                    task_queue_dsl::task_queue.filter(task_queue_dsl::id.eq_any(&task_ids)),
+                )
+                .set((
+                    task_queue_dsl::status.eq(TaskStatus::Pending.as_str()),
+                    task_queue_dsl::error.eq(None::<String>),
+                    task_queue_dsl::attempts.eq(0),
+                    task_queue_dsl::updated_at.eq(now),
+                ))
+                .execute(conn)?;
+                // Older failures of a requeued path are superseded by the new attempt
+                let mut dead_letters_removed = 0;
+                for row in &rows {
+                    dead_letters_removed += diesel::delete(
+                        dead_letters_dsl::dead_letters
+                            .filter(dead_letters_dsl::drive_id.eq(drive_id))
+                            .filter(dead_letters_dsl::task_type.eq(&row.task_type))
+                            .filter(dead_letters_dsl::local_path.eq(&row.local_path)),
+                    )
+                    .execute(conn)?;
+                }
+                Ok((rows, dead_letters_removed))
+            })
+            .context("Failed to requeue failed tasks")?;
+
+        let records = rows
+            .into_iter()
+            .map(|row| {
+                TaskRecord::try_from(row).map(|record| TaskRecord {
+                    status: TaskStatus::Pending,
+                    error: None,
+                    attempts: 0,
+                    updated_at: now,
+                    ..record
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let task_ids: Vec<String> = records.iter().map(|record| record.id.clone()).collect();
+        self.notify_tasks_updated(&task_ids, TaskStatus::Pending);
+        if dead_letters_removed > 0 {
+            self.notify_dead_letters_changed();
+        }
+        Ok(records)
+    }
+
+    /// Append a failed attempt to a task's history. Returns the number of failed
+    /// attempts so far, including this one.
+    pub fn record_task_failure(
+        &self,
+        task_id: &str,
+        failure: TaskFailureClass,
+        error: &str,
+    ) -> Result<i32> {
+        let mut conn = self.connection()?;
+        conn.transaction(|conn| {
+            let (attempts, history): (i32, Option<String>) = task_queue_dsl::task_queue
+                .filter(task_queue_dsl::id.eq(task_id))
+                .select((task_queue_dsl::attempts, task_queue_dsl::attempt_history))
+                .first(conn)
+                .with_context(|| format!("Task {} not found", task_id))?;
+
+            let attempt = attempts + 1;
+            let mut history = parse_attempt_history(history.as_deref())?;
+            history.push(TaskAttempt {
+                attempt,
+                failure,
+                error: error.to_string(),
+                failed_at: Utc::now().timestamp(),
+            });
+            let history = serde_json::to_string(&history)
+                .context("Failed to serialize task attempt history")?;
+
+            diesel::update(task_queue_dsl::task_queue.filter(task_queue_dsl::id.eq(task_id)))
+                .set((
+                    task_queue_dsl::attempts.eq(attempt),
+                    task_queue_dsl::attempt_history.eq(Some(history)),
+                ))
+                .execute(conn)
+                .context("Failed to record task attempt")?;
+            Ok(attempt)
+        })
+    }
+
+    /// Get a task queue record by ID
+    pub fn get_task(&self, task_id: &str) -> Result<Option<TaskRecord>> {
+        let mut conn = self.connection()?;
+        let row = task_queue_dsl::task_queue
+            .filter(task_queue_dsl::id.eq(task_id))
+            .first::<TaskRow>(&mut conn)
+            .optional()
+            .context("Failed to query task")?;
+        row.map(TaskRecord::try_from).transpose()
+    }
+
     /// Get task status by task ID
     pub fn get_task_status(&self, task_id: &str) -> Result<Option<TaskStatus>> {
         let mut conn = self.connection()?;
@@ -151,6 +445,28 @@ impl InventoryDb {
         }
     }
 
+    /// When a task on `path` last completed, as a Unix timestamp
+    pub fn last_completed_task_at(&self, path: &str) -> Result<Option<i64>> {
+        let mut conn = self.connection()?;
+        task_queue_dsl::task_queue
+            .filter(task_queue_dsl::local_path.eq(path))
+            .filter(task_queue_dsl::status.eq(TaskStatus::Completed.as_str()))
+            .select(max(task_queue_dsl::updated_at))
+            .first::<Option<i64>>(&mut conn)
+            .context("Failed to query last completed task")
+    }
+
+    /// When any task of the drive last completed, as a Unix timestamp
+    pub fn last_drive_sync_at(&self, drive_id: &str) -> Result<Option<i64>> {
+        let mut conn = self.connection()?;
+        task_queue_dsl::task_queue
+            .filter(task_queue_dsl::drive_id.eq(drive_id))
+            .filter(task_queue_dsl::status.eq(TaskStatus::Completed.as_str()))
+            .select(max(task_queue_dsl::updated_at))
+            .first::<Option<i64>>(&mut conn)
+            .context("Failed to query last drive sync")
+    }
+
     /// Query recent tasks for status summary.
     /// Returns up to 25 pending/running tasks and up to 25 completed/failed/cancelled tasks,
     /// ordered by updated_at descending.
@@ -173,7 +489,7 @@ impl InventoryDb {
 
         let active_rows = active_query
             .order(task_queue_dsl::updated_at.desc())
-            .limit(25)
+            .limit(RecentTasks::LIMIT as i64)
             .load::<TaskRow>(&mut conn)
             .context("Failed to query active tasks")?;
 
@@ -182,12 +498,8 @@ impl InventoryDb {
             .map(TaskRecord::try_from)
             .collect::<Result<Vec<_>>>()?;
 
-        // Query finished tasks (completed/failed/cancelled) - limit 25, order by updated_at desc
-        let finished_statuses = vec![
-            TaskStatus::Completed.as_str().to_string(),
-            TaskStatus::Failed.as_str().to_string(),
-            TaskStatus::Cancelled.as_str().to_string(),
-        ];
+        // Query finished tasks (completed/failed/cancelled/timed out) - limit 25, order by updated_at desc
+        let finished_statuses = finished_status_strings();
 
         let mut finished_query = task_queue_dsl::task_queue
             .filter(task_queue_dsl::status.eq_any(&finished_statuses))
@@ -199,7 +511,7 @@ impl InventoryDb {
 
         let finished_rows = finished_query
             .order(task_queue_dsl::updated_at.desc())
-            .limit(25)
+            .limit(RecentTasks::LIMIT as i64)
             .load::<TaskRow>(&mut conn)
             .context("Failed to query finished tasks")?;
 
@@ -216,7 +528,7 @@ impl InventoryDb {
 }
 
 /// Result of querying recent tasks
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RecentTasks {
     /// Pending and running tasks (up to 25)
     pub active: Vec<TaskRecord>,
@@ -224,6 +536,44 @@ pub struct RecentTasks {
     pub finished: Vec<TaskRecord>,
 }
 
+impl RecentTasks {
+    /// Maximum number of tasks kept in each list
+    pub const LIMIT: usize = 25;
+
+    /// Merge results queried from another database, keeping the most recently updated tasks
+    pub fn merge(&mut self, other: RecentTasks) {
+        for (list, extra) in [
+            (&mut self.active, other.active),
+            (&mut self.finished, other.finished),
+        ] {
+            list.extend(extra);
+            list.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+            list.truncate(Self::LIMIT);
+        }
+    }
+}
+
+pub(super) fn finished_status_strings() -> Vec<String> {
+    [
+        TaskStatus::Completed,
+        TaskStatus::Failed,
+        TaskStatus::Cancelled,
+        TaskStatus::TimedOut,
+    ]
+    .iter()
+    .map(|status| status.as_str().to_string())
+    .collect()
+}
+
+pub(super) fn parse_attempt_history(json: Option<&str>) -> Result<Vec<TaskAttempt>> {
+    match json {
+        Some(json) => {
+            serde_json::from_str(json).context("Failed to deserialize task attempt history")
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
 // =========================================================================
 // Row Types
 // =========================================================================
@@ -243,6 +593,9 @@ struct TaskRow {
     error: Option<String>,
     created_at: i64,
     updated_at: i64,
+    force_override: bool,
+    attempts: i32,
+    attempt_history: Option<String>,
 }
 
 impl TryFrom<TaskRow> for TaskRecord {
@@ -257,6 +610,7 @@ impl TryFrom<TaskRow> for TaskRecord {
             ),
             None => None,
         };
+        let attempt_history = parse_attempt_history(row.attempt_history.as_deref())?;
 
         Ok(TaskRecord {
             id: row.id,
@@ -272,6 +626,9 @@ impl TryFrom<TaskRow> for TaskRecord {
             error: row.error,
             created_at: row.created_at,
             updated_at: row.updated_at,
+            force_override: row.force_override,
+            attempts: row.attempts,
+            attempt_history,
         })
     }
 }
@@ -292,6 +649,9 @@ struct NewTaskRow {
     error: Option<String>,
     created_at: i64,
     updated_at: i64,
+    force_override: bool,
+    attempts: i32,
+    attempt_history: Option<String>,
 }
 
 impl TryFrom<&NewTaskRecord> for NewTaskRow {
@@ -318,6 +678,42 @@ impl TryFrom<&NewTaskRecord> for NewTaskRow {
             error: record.error.clone(),
             created_at: record.created_at,
             updated_at: record.updated_at,
+            force_override: record.force_override,
+            attempts: 0,
+            attempt_history: None,
+        })
+    }
+}
+
+impl TryFrom<&TaskRecord> for NewTaskRow {
+    type Error = anyhow::Error;
+
+    fn try_from(record: &TaskRecord) -> Result<Self> {
+        Ok(Self {
+            id: record.id.clone(),
+            drive_id: record.drive_id.clone(),
+            task_type: record.task_type.clone(),
+            local_path: record.local_path.clone(),
+            status: record.status.as_str().to_string(),
+            progress: record.progress,
+            total_bytes: record.total_bytes,
+            processed_bytes: record.processed_bytes,
+            priority: record.priority,
+            custom_state: record
+                .custom_state
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()
+                .context("Failed to serialize task custom_state")?,
+            error: record.error.clone(),
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+            force_override: record.force_override,
+            attempts: record.attempts,
+            attempt_history: (!record.attempt_history.is_empty())
+                .then(|| serde_json::to_string(&record.attempt_history))
+                .transpose()
+                .context("Failed to serialize task attempt history")?,
         })
     }
 }
@@ -361,3 +757,140 @@ impl TaskChangeset {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn resumable_state_survives_reopen() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("meta.db");
+        {
+            let db = InventoryDb::with_path(path.clone()).unwrap();
+            db.insert_task_if_not_exist(
+                &NewTaskRecord::new("t1", "drive", "upload", "/sync/a.txt")
+                    .with_force_override(true),
+            )
+            .unwrap();
+            db.update_task(
+                "t1",
+                TaskUpdate {
+                    progress: Some(0.5),
+                    processed_bytes: Some(512),
+                    total_bytes: Some(1024),
+                    custom_state: Some(Some(serde_json::json!({ "chunk": 3 }))),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        }
+
+        let db = InventoryDb::with_path(path).unwrap();
+        let tasks = db
+            .list_tasks(Some("drive"), Some(&[TaskStatus::Pending]))
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert!(tasks[0].force_override);
+        assert_eq!(tasks[0].processed_bytes, 512);
+        assert_eq!(
+            tasks[0].custom_state,
+            Some(serde_json::json!({ "chunk": 3 }))
+        );
+    }
+
+    #[test]
+    fn prune_keeps_active_and_recent_tasks() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        for (id, status, updated_at) in [
+            ("old", TaskStatus::Completed, 100),
+            ("older_active", TaskStatus::Pending, 50),
+            ("a", TaskStatus::Failed, 1000),
+            ("b", TaskStatus::Completed, 2000),
+            ("c", TaskStatus::Cancelled, 3000),
+        ] {
+            let mut record = NewTaskRecord::new(id, "drive", "upload", format!("/sync/{}", id));
+            record.status = status;
+            record.updated_at = updated_at;
+            db.insert_task_if_not_exist(&record).unwrap();
+        }
+
+        assert_eq!(db.prune_finished_tasks(500, 2).unwrap(), 2);
+        let mut remaining: Vec<String> = db
+            .list_tasks(None, None)
+            .unwrap()
+            .into_iter()
+            .map(|task| task.id)
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, ["b", "c", "older_active"]);
+        assert_eq!(db.count_finished_tasks(Some("drive")).unwrap(), 2);
+
+        assert_eq!(db.clear_finished_tasks(None).unwrap(), 2);
+        assert_eq!(db.count_finished_tasks(None).unwrap(), 0);
+    }
+
+    #[test]
+    fn bulk_cancel_and_requeue() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        for (id, task_type, path, status, updated_at) in [
+            ("up", "upload", "/sync/a", TaskStatus::Pending, 1),
+            ("down", "download", "/sync/b", TaskStatus::Running, 1),
+            ("failed_old", "upload", "/sync/c", TaskStatus::Failed, 1),
+            ("failed_new", "upload", "/sync/c", TaskStatus::TimedOut, 2),
+            ("hydrate", "hydrate", "/sync/d", TaskStatus::Failed, 1),
+        ] {
+            let mut record = NewTaskRecord::new(id, "drive", task_type, path);
+            record.status = status;
+            record.updated_at = updated_at;
+            db.insert_task_if_not_exist(&record).unwrap();
+        }
+        db.record_task_failure("failed_old", TaskFailureClass::Other, "boom")
+            .unwrap();
+        db.dead_letter_task("failed_old", TaskFailureClass::Other, "boom")
+            .unwrap();
+
+        assert_eq!(db.cancel_tasks("drive", Some("upload")).unwrap(), ["up"]);
+        assert_eq!(db.cancel_tasks("drive", None).unwrap(), ["down"]);
+
+        let requeued = db.requeue_failed_tasks("drive").unwrap();
+        assert_eq!(requeued.len(), 1);
+        assert_eq!(requeued[0].id, "failed_new");
+        assert_eq!(
+            db.get_task_status("failed_new").unwrap(),
+            Some(TaskStatus::Pending)
+        );
+        assert_eq!(
+            db.get_task_status("failed_old").unwrap(),
+            Some(TaskStatus::Failed)
+        );
+        assert_eq!(db.count_dead_letters(None).unwrap(), 0);
+    }
+
+    #[test]
+    fn cancelled_task_is_not_marked_running() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        for id in ["started", "cancelled"] {
+            db.insert_task_if_not_exist(&NewTaskRecord::new(
+                id,
+                "drive",
+                "upload",
+                format!("/sync/{id}"),
+            ))
+            .unwrap();
+        }
+        db.cancel_tasks_by_path("drive", "/sync/cancelled").unwrap();
+
+        assert!(db.mark_task_running("started").unwrap());
+        assert!(!db.mark_task_running("started").unwrap());
+        assert!(!db.mark_task_running("cancelled").unwrap());
+        assert_eq!(
+            db.get_task_status("cancelled").unwrap(),
+            Some(TaskStatus::Cancelled)
+        );
+    }
+}
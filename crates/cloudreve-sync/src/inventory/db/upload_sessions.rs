@@ -1,4 +1,5 @@
 use super::InventoryDb;
+use crate::inventory::TaskStatus;
 use anyhow::{Context, Result};
 use chrono::Utc;
 use diesel::prelude::*;
@@ -17,6 +18,24 @@ impl InventoryDb {
         Ok(())
     }
 
+    /// Persist the chunk progress and owning task of an existing upload session
+    pub fn update_upload_session(&self, session: &crate::uploader::UploadSession) -> Result<()> {
+        let mut conn = self.connection()?;
+        let chunk_progress = serde_json::to_string(&session.chunk_progress)
+            .context("Failed to serialize chunk progress")?;
+        diesel::update(
+            upload_sessions_dsl::upload_sessions.filter(upload_sessions_dsl::id.eq(&session.id)),
+        )
+        .set((
+            upload_sessions_dsl::task_id.eq(&session.task_id),
+            upload_sessions_dsl::chunk_progress.eq(chunk_progress),
+            upload_sessions_dsl::updated_at.eq(session.updated_at),
+        ))
+        .execute(&mut conn)
+        .context("Failed to update upload session")?;
+        Ok(())
+    }
+
     /// Get upload session by task ID
     pub fn get_upload_session(
         &self,
@@ -49,6 +68,48 @@ impl InventoryDb {
             .transpose()
     }
 
+    /// List all upload sessions of a drive
+    pub fn list_upload_sessions_by_drive(
+        &self,
+        drive_id: &str,
+    ) -> Result<Vec<crate::uploader::UploadSession>> {
+        let mut conn = self.connection()?;
+        let rows = upload_sessions_dsl::upload_sessions
+            .filter(upload_sessions_dsl::drive_id.eq(drive_id))
+            .load::<UploadSessionQueryRow>(&mut conn)
+            .context("Failed to list upload sessions")?;
+
+        rows.into_iter()
+            .map(crate::uploader::UploadSession::try_from)
+            .collect()
+    }
+
+    /// List upload sessions of a drive that can no longer be resumed: expired, owned by a
+    /// completed or cancelled task, or owned by a task that is neither in the queue nor
+    /// dead-lettered. Failed tasks keep their session until it expires, since requeuing
+    /// them reuses the task id. Sessions of running tasks are never returned.
+    pub fn list_stale_upload_sessions(
+        &self,
+        drive_id: &str,
+    ) -> Result<Vec<crate::uploader::UploadSession>> {
+        let now = Utc::now().timestamp();
+        let mut stale = Vec::new();
+        for session in self.list_upload_sessions_by_drive(drive_id)? {
+            let is_stale = match self.get_task_status(&session.task_id)? {
+                Some(TaskStatus::Running) => false,
+                Some(TaskStatus::Completed | TaskStatus::Cancelled) => true,
+                Some(_) => session.expires_at <= now,
+                None => {
+                    session.expires_at <= now || self.get_dead_letter(&session.task_id)?.is_none()
+                }
+            };
+            if is_stale {
+                stale.push(session);
+            }
+        }
+        Ok(stale)
+    }
+
     /// Delete upload session
     pub fn delete_upload_session(&self, session_id: &str) -> Result<()> {
         let mut conn = self.connection()?;
@@ -86,6 +147,19 @@ impl InventoryDb {
         .context("Failed to delete expired upload sessions")?;
         Ok(deleted)
     }
+
+    /// Delete upload sessions whose owning task is neither in the queue nor dead-lettered
+    pub fn delete_orphaned_upload_sessions(&self) -> Result<usize> {
+        let mut conn = self.connection()?;
+        let deleted = diesel::sql_query(
+            "DELETE FROM upload_sessions \
+             WHERE task_id NOT IN (SELECT id FROM task_queue) \
+             AND task_id NOT IN (SELECT task_id FROM dead_letters)",
+        )
+        .execute(&mut conn)
+        .context("Failed to delete orphaned upload sessions")?;
+        Ok(deleted)
+    }
 }
 
 // =========================================================================
@@ -200,3 +274,98 @@ impl TryFrom<UploadSessionQueryRow> for crate::uploader::UploadSession {
         Ok(session)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::{NewTaskRecord, TaskFailureClass, TaskUpdate};
+    use crate::uploader::UploadSession;
+    use cloudreve_api::models::explorer::UploadCredential;
+    use tempfile::tempdir;
+
+    fn session(task_id: &str, expires: i64) -> UploadSession {
+        let credential = UploadCredential {
+            session_id: format!("session-{task_id}"),
+            expires,
+            chunk_size: 1024,
+            ..Default::default()
+        };
+        UploadSession::new(
+            task_id.to_string(),
+            "drive".to_string(),
+            format!("/sync/{task_id}.txt"),
+            "cloudreve://my".to_string(),
+            10,
+            credential,
+        )
+    }
+
+    #[test]
+    fn stale_sessions_skip_resumable_ones() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        let now = Utc::now().timestamp();
+
+        for (task_id, status) in [
+            ("running", TaskStatus::Running),
+            ("pending", TaskStatus::Pending),
+            ("expired", TaskStatus::Pending),
+            ("failed", TaskStatus::Failed),
+            ("dead", TaskStatus::Failed),
+            ("done", TaskStatus::Completed),
+            ("cancelled", TaskStatus::Cancelled),
+        ] {
+            db.insert_task_if_not_exist(&NewTaskRecord::new(
+                task_id,
+                "drive",
+                "upload",
+                format!("/sync/{task_id}.txt"),
+            ))
+            .unwrap();
+            db.update_task(
+                task_id,
+                TaskUpdate {
+                    status: Some(status),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        }
+        for (task_id, expires) in [
+            ("running", now - 10),
+            ("pending", now + 3600),
+            ("expired", now - 10),
+            ("failed", now + 3600),
+            ("dead", now + 3600),
+            ("done", now + 3600),
+            ("cancelled", now + 3600),
+            ("orphan", now + 3600),
+        ] {
+            db.insert_upload_session(&session(task_id, expires))
+                .unwrap();
+        }
+        // A dead-lettered task can be requeued after it left the queue
+        db.dead_letter_task("dead", TaskFailureClass::Network, "timeout")
+            .unwrap();
+        db.delete_task("dead").unwrap();
+
+        let mut stale: Vec<_> = db
+            .list_stale_upload_sessions("drive")
+            .unwrap()
+            .into_iter()
+            .map(|s| s.task_id)
+            .collect();
+        stale.sort();
+        assert_eq!(stale, vec!["cancelled", "done", "expired", "orphan"]);
+
+        // Pruning the task history leaves only the dead-lettered session without a task
+        db.delete_task("done").unwrap();
+        db.delete_task("cancelled").unwrap();
+        assert_eq!(db.delete_orphaned_upload_sessions().unwrap(), 3);
+        assert!(
+            db.get_upload_session_by_path("/sync/dead.txt")
+                .unwrap()
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use super::InventoryDb;
+use crate::inventory::UsnCheckpoint;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use diesel::prelude::*;
+
+use crate::inventory::schema::usn_checkpoints::{self, dsl as usn_checkpoints_dsl};
+
+impl InventoryDb {
+    /// Get how far the change journal was replayed for a drive
+    pub fn get_usn_checkpoint(&self, drive_id: &str) -> Result<Option<UsnCheckpoint>> {
+        let mut conn = self.connection()?;
+        let row = usn_checkpoints_dsl::usn_checkpoints
+            .filter(usn_checkpoints_dsl::drive_id.eq(drive_id))
+            .first::<UsnCheckpointRow>(&mut conn)
+            .optional()
+            .context("Failed to query usn checkpoint")?;
+
+        Ok(row.map(|row| UsnCheckpoint {
+            journal_id: row.journal_id as u64,
+            next_usn: row.next_usn,
+        }))
+    }
+
+    /// Record how far the change journal was replayed for a drive
+    pub fn set_usn_checkpoint(&self, drive_id: &str, checkpoint: UsnCheckpoint) -> Result<()> {
+        let row = UsnCheckpointRow {
+            drive_id: drive_id.to_string(),
+            journal_id: checkpoint.journal_id as i64,
+            next_usn: checkpoint.next_usn,
+            updated_at: Utc::now().timestamp(),
+        };
+
+        let mut conn = self.connection()?;
+        diesel::replace_into(usn_checkpoints::table)
+            .values(&row)
+            .execute(&mut conn)
+            .context("Failed to save usn checkpoint")?;
+        Ok(())
+    }
+}
+
+// =========================================================================
+// Row Types
+// =========================================================================
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = usn_checkpoints)]
+struct UsnCheckpointRow {
+    drive_id: String,
+    journal_id: i64,
+    next_usn: i64,
+    updated_at: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn usn_checkpoint_roundtrip() {
+        let dir = tempdir().unwrap();
+        let db = InventoryDb::with_path(dir.path().join("meta.db")).unwrap();
+        assert!(db.get_usn_checkpoint("drive").unwrap().is_none());
+
+        // Journal ids use the full u64 range
+        let checkpoint = UsnCheckpoint {
+            journal_id: u64::MAX - 1,
+            next_usn: 4096,
+        };
+        db.set_usn_checkpoint("drive", checkpoint).unwrap();
+        db.set_usn_checkpoint(
+            "drive",
+            UsnCheckpoint {
+                next_usn: 8192,
+                ..checkpoint
+            },
+        )
+        .unwrap();
+
+        let stored = db.get_usn_checkpoint("drive").unwrap().unwrap();
+        assert_eq!(stored.journal_id, u64::MAX - 1);
+        assert_eq!(stored.next_usn, 8192);
+        assert!(db.get_usn_checkpoint("other").unwrap().is_none());
+    }
+}
@@ -2,10 +2,18 @@ mod db;
 mod models;
 pub(crate) mod schema;
 
-pub use db::{InventoryDb, RecentTasks};
+pub use db::{
+    IntegrityRepair, IntegrityReport, InventoryDb, InventoryKey, InventorySnapshot,
+    MetadataWriteBatch, RecentTasks, SNAPSHOT_VERSION, SnapshotImportStats,
+};
 pub use models::{
-    ConflictState, DriveProps, DrivePropsUpdate, FileMetadata, MetadataEntry, NewTaskRecord,
-    TaskRecord, TaskStatus, TaskUpdate,
+    ActivityEntry, ActivityFilter, ActivityKind, ActivityPage, CfapiFailureClass,
+    CfapiFailureRecord, CfapiFailureSummary, CfapiOperation, CfapiRecovery, ChangeSource,
+    CloudItemProps, ConflictKind, ConflictPage, ConflictRecord, ConflictResolution, ConflictState,
+    DeadLetterRecord, DriveProps, DrivePropsUpdate, EntryFilter, EntryPage, EntrySort,
+    EntrySortField, EntrySyncState, FileMetadata, JournalEntry, JournalOperation, MetadataEntry,
+    NewCfapiFailure, NewConflictRecord, NewTaskRecord, PageRequest, RemoteFolderEntry, TaskAttempt,
+    TaskFailureClass, TaskRecord, TaskStatus, TaskUpdate, UsnCheckpoint,
 };
 
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
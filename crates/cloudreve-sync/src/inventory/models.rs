@@ -1,4 +1,5 @@
-use cloudreve_api::models::explorer::StoragePolicy;
+use cloudreve_api::Boolset;
+use cloudreve_api::models::explorer::{StoragePolicy, file_permission};
 use cloudreve_api::models::user::{Capacity, UserSettings};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -48,6 +49,41 @@ pub struct FileMetadata {
     pub conflict_state: Option<ConflictState>,
 }
 
+impl FileMetadata {
+    /// Cloud properties stored in `props`, empty if none were recorded
+    pub fn cloud_props(&self) -> CloudItemProps {
+        self.props
+            .clone()
+            .and_then(|props| serde_json::from_value(props).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether the signed-in user may create a share link for the file: only the owner
+    /// can share, and only what they can read
+    pub fn shareable(&self) -> bool {
+        if self.cloud_props().owned == Some(false) {
+            return false;
+        }
+        if self.permissions.is_empty() {
+            return true;
+        }
+        Boolset::from_base64(&self.permissions)
+            .map(|permission| permission.enabled(file_permission::READ as usize))
+            .unwrap_or(false)
+    }
+}
+
+/// Cloud properties of a file that have no local equivalent, shown as Explorer columns
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CloudItemProps {
+    /// Whether the file belongs to the signed-in user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owned: Option<bool>,
+    /// Nickname of the user who uploaded the current version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_by: Option<String>,
+}
+
 /// Entry for inserting or updating file metadata
 #[derive(Debug, Clone)]
 pub struct MetadataEntry {
@@ -143,6 +179,151 @@ impl From<&FileMetadata> for MetadataEntry {
     }
 }
 
+/// Sync state used when filtering inventory entries
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntrySyncState {
+    /// Entry has no recorded conflict
+    Synced,
+    /// Entry has a conflict in any state
+    Conflicted,
+}
+
+/// Filter for paginated inventory queries. All fields are optional and combined with AND.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntryFilter {
+    pub drive_id: Option<String>,
+    /// Only match this path and its descendants
+    pub path_prefix: Option<String>,
+    pub is_folder: Option<bool>,
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>,
+    pub sync_state: Option<EntrySyncState>,
+    /// Unix timestamp, inclusive. Compared with the time the inventory record was last
+    /// updated, not the modification time of the file, which the inventory does not keep.
+    pub updated_after: Option<i64>,
+    /// Unix timestamp, inclusive, see `updated_after`
+    pub updated_before: Option<i64>,
+}
+
+impl EntryFilter {
+    pub fn with_drive(mut self, drive_id: impl Into<String>) -> Self {
+        self.drive_id = Some(drive_id.into());
+        self
+    }
+
+    pub fn with_path_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.path_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn with_size_range(mut self, min: Option<i64>, max: Option<i64>) -> Self {
+        self.min_size = min;
+        self.max_size = max;
+        self
+    }
+
+    pub fn with_sync_state(mut self, state: EntrySyncState) -> Self {
+        self.sync_state = Some(state);
+        self
+    }
+
+    pub fn with_updated_range(mut self, after: Option<i64>, before: Option<i64>) -> Self {
+        self.updated_after = after;
+        self.updated_before = before;
+        self
+    }
+}
+
+/// Column used to order inventory query results
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EntrySortField {
+    #[default]
+    LocalPath,
+    Size,
+    UpdatedAt,
+    CreatedAt,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct EntrySort {
+    pub field: EntrySortField,
+    pub descending: bool,
+}
+
+impl EntrySort {
+    /// Compare two entries the way [`InventoryDb::query_entries`] orders them, with the
+    /// local path as tie-breaker. Used to merge pages read from separate drive databases.
+    ///
+    /// [`InventoryDb::query_entries`]: crate::inventory::InventoryDb::query_entries
+    pub fn compare(&self, a: &FileMetadata, b: &FileMetadata) -> std::cmp::Ordering {
+        let ordering = match self.field {
+            EntrySortField::LocalPath => a.local_path.cmp(&b.local_path),
+            EntrySortField::Size => a.size.cmp(&b.size),
+            EntrySortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+            EntrySortField::CreatedAt => a.created_at.cmp(&b.created_at),
+        }
+        .then_with(|| a.local_path.cmp(&b.local_path));
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// Offset-based page request
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageRequest {
+    pub offset: i64,
+    pub limit: i64,
+}
+
+impl PageRequest {
+    /// Upper bound for a single page to keep IPC payloads small
+    pub const MAX_LIMIT: i64 = 500;
+
+    pub fn new(offset: i64, limit: i64) -> Self {
+        Self { offset, limit }
+    }
+
+    /// Clamp offset and limit to sane values
+    pub fn normalized(self) -> Self {
+        Self {
+            offset: self.offset.max(0),
+            limit: self.limit.clamp(1, Self::MAX_LIMIT),
+        }
+    }
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            limit: 100,
+        }
+    }
+}
+
+/// A page of inventory entries along with the total number of matches
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryPage {
+    pub entries: Vec<FileMetadata>,
+    pub total: i64,
+    pub offset: i64,
+    pub limit: i64,
+}
+
+impl EntryPage {
+    pub fn has_more(&self) -> bool {
+        self.offset + (self.entries.len() as i64) < self.total
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskRecord {
     pub id: String,
@@ -158,6 +339,46 @@ pub struct TaskRecord {
     pub error: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
+    /// Upload overwrites the remote file even if it changed
+    #[serde(default)]
+    pub force_override: bool,
+    /// Number of failed attempts so far
+    #[serde(default)]
+    pub attempts: i32,
+    /// Failed attempts, oldest first
+    #[serde(default)]
+    pub attempt_history: Vec<TaskAttempt>,
+}
+
+/// One failed run of a task
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskAttempt {
+    /// 1-based attempt number
+    pub attempt: i32,
+    pub failure: TaskFailureClass,
+    pub error: String,
+    pub failed_at: i64,
+}
+
+/// A task that failed for good, kept in the dead-letter store until it is requeued or
+/// discarded
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeadLetterRecord {
+    pub task_id: String,
+    pub drive_id: String,
+    pub task_type: String,
+    pub local_path: String,
+    pub priority: i32,
+    pub custom_state: Option<serde_json::Value>,
+    pub force_override: bool,
+    /// Failure of the last attempt
+    pub failure: TaskFailureClass,
+    pub error: String,
+    pub attempts: i32,
+    /// Failed attempts, oldest first
+    pub attempt_history: Vec<TaskAttempt>,
+    /// When the task was moved to the dead-letter store
+    pub created_at: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -175,6 +396,7 @@ pub struct NewTaskRecord {
     pub error: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
+    pub force_override: bool,
 }
 
 impl NewTaskRecord {
@@ -199,6 +421,7 @@ impl NewTaskRecord {
             error: None,
             created_at: now,
             updated_at: now,
+            force_override: false,
         }
     }
 
@@ -233,6 +456,11 @@ impl NewTaskRecord {
         self
     }
 
+    pub fn with_force_override(mut self, force: bool) -> Self {
+        self.force_override = force;
+        self
+    }
+
     pub fn touch(mut self) -> Self {
         self.updated_at = chrono::Utc::now().timestamp();
         self
@@ -246,6 +474,8 @@ pub enum TaskStatus {
     Completed,
     Failed,
     Cancelled,
+    /// Stopped for running longer than its type's timeout
+    TimedOut,
 }
 
 impl TaskStatus {
@@ -256,6 +486,7 @@ impl TaskStatus {
             TaskStatus::Completed => "completed",
             TaskStatus::Failed => "failed",
             TaskStatus::Cancelled => "cancelled",
+            TaskStatus::TimedOut => "timed_out",
         }
     }
 
@@ -266,6 +497,7 @@ impl TaskStatus {
             "completed" => Some(TaskStatus::Completed),
             "failed" => Some(TaskStatus::Failed),
             "cancelled" => Some(TaskStatus::Cancelled),
+            "timed_out" => Some(TaskStatus::TimedOut),
             _ => None,
         }
     }
@@ -275,6 +507,51 @@ impl TaskStatus {
     }
 }
 
+/// Broad cause of a task failure, used to decide whether retrying can help
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskFailureClass {
+    /// Connection problems and timeouts
+    Network,
+    /// The server or storage provider reported an error
+    Server,
+    /// The remote file is locked by another client
+    Locked,
+    /// Credentials are missing or expired
+    Auth,
+    /// Reading or writing the local file failed
+    LocalIo,
+    Cancelled,
+    Other,
+}
+
+impl TaskFailureClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskFailureClass::Network => "network",
+            TaskFailureClass::Server => "server",
+            TaskFailureClass::Locked => "locked",
+            TaskFailureClass::Auth => "auth",
+            TaskFailureClass::LocalIo => "local_io",
+            TaskFailureClass::Cancelled => "cancelled",
+            TaskFailureClass::Other => "other",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "network" => Some(TaskFailureClass::Network),
+            "server" => Some(TaskFailureClass::Server),
+            "locked" => Some(TaskFailureClass::Locked),
+            "auth" => Some(TaskFailureClass::Auth),
+            "local_io" => Some(TaskFailureClass::LocalIo),
+            "cancelled" => Some(TaskFailureClass::Cancelled),
+            "other" => Some(TaskFailureClass::Other),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct TaskUpdate {
     pub status: Option<TaskStatus>,
@@ -339,3 +616,508 @@ impl DrivePropsUpdate {
         self
     }
 }
+
+/// How far the volume change journal was read for a drive's sync root
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsnCheckpoint {
+    /// The journal instance `next_usn` belongs to
+    pub journal_id: u64,
+    /// The first change not replayed yet
+    pub next_usn: i64,
+}
+
+/// Subfolder of a remote folder, as cached for the selective sync picker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteFolderEntry {
+    pub name: String,
+    pub uri: String,
+    /// Total size of the folder's content in bytes, `None` if the server did not
+    /// calculate it
+    pub size: Option<i64>,
+}
+
+/// What caused an inventory mutation, recorded in the change journal
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeSource {
+    #[default]
+    Unknown,
+    /// Reconciliation of remote, local and inventory state
+    Sync,
+    /// Remote listing served to the cloud filter (placeholder population)
+    RemoteDelta,
+    /// Local filesystem event (rename, delete, modify)
+    LocalEvent,
+    Upload,
+    Download,
+    /// Automated cleanup or repair
+    Repair,
+    /// Snapshot import or legacy database migration
+    Import,
+    /// Explicit user action, e.g. conflict resolution
+    User,
+}
+
+impl ChangeSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeSource::Unknown => "unknown",
+            ChangeSource::Sync => "sync",
+            ChangeSource::RemoteDelta => "remote_delta",
+            ChangeSource::LocalEvent => "local_event",
+            ChangeSource::Upload => "upload",
+            ChangeSource::Download => "download",
+            ChangeSource::Repair => "repair",
+            ChangeSource::Import => "import",
+            ChangeSource::User => "user",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Self {
+        match value {
+            "sync" => ChangeSource::Sync,
+            "remote_delta" => ChangeSource::RemoteDelta,
+            "local_event" => ChangeSource::LocalEvent,
+            "upload" => ChangeSource::Upload,
+            "download" => ChangeSource::Download,
+            "repair" => ChangeSource::Repair,
+            "import" => ChangeSource::Import,
+            "user" => ChangeSource::User,
+            _ => ChangeSource::Unknown,
+        }
+    }
+}
+
+/// Kind of inventory mutation recorded in the change journal
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalOperation {
+    Insert,
+    Update,
+    Upsert,
+    /// Path and all its descendants removed
+    Delete,
+    /// `detail` holds the previous path
+    Rename,
+    /// `detail` holds the new conflict state, if any
+    ConflictState,
+    /// All entries of a drive (or the whole database) removed
+    Clear,
+}
+
+impl JournalOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JournalOperation::Insert => "insert",
+            JournalOperation::Update => "update",
+            JournalOperation::Upsert => "upsert",
+            JournalOperation::Delete => "delete",
+            JournalOperation::Rename => "rename",
+            JournalOperation::ConflictState => "conflict_state",
+            JournalOperation::Clear => "clear",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "insert" => Some(JournalOperation::Insert),
+            "update" => Some(JournalOperation::Update),
+            "upsert" => Some(JournalOperation::Upsert),
+            "delete" => Some(JournalOperation::Delete),
+            "rename" => Some(JournalOperation::Rename),
+            "conflict_state" => Some(JournalOperation::ConflictState),
+            "clear" => Some(JournalOperation::Clear),
+            _ => None,
+        }
+    }
+}
+
+/// A single change journal record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: i64,
+    pub drive_id: Option<String>,
+    pub local_path: String,
+    pub operation: JournalOperation,
+    pub source: ChangeSource,
+    pub detail: Option<String>,
+    pub created_at: i64,
+}
+
+/// Why a conflict was raised
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Local and remote content both changed
+    Content,
+    /// A file on one side and a folder on the other
+    TypeMismatch,
+}
+
+impl ConflictKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictKind::Content => "content",
+            ConflictKind::TypeMismatch => "type_mismatch",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "content" => Some(ConflictKind::Content),
+            "type_mismatch" => Some(ConflictKind::TypeMismatch),
+            _ => None,
+        }
+    }
+}
+
+/// How a conflict was resolved
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    Pending,
+    KeepRemote,
+    OverwriteRemote,
+    SaveAsNew,
+    /// The local version was moved aside automatically and both versions kept
+    KeptBoth,
+}
+
+impl ConflictResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictResolution::Pending => "pending",
+            ConflictResolution::KeepRemote => "keep_remote",
+            ConflictResolution::OverwriteRemote => "overwrite_remote",
+            ConflictResolution::SaveAsNew => "save_as_new",
+            ConflictResolution::KeptBoth => "kept_both",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(ConflictResolution::Pending),
+            "keep_remote" => Some(ConflictResolution::KeepRemote),
+            "overwrite_remote" => Some(ConflictResolution::OverwriteRemote),
+            "save_as_new" => Some(ConflictResolution::SaveAsNew),
+            "kept_both" => Some(ConflictResolution::KeptBoth),
+            _ => None,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        matches!(self, ConflictResolution::Pending)
+    }
+}
+
+/// A conflict record stored in the inventory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictRecord {
+    pub id: i64,
+    pub drive_id: String,
+    pub original_path: String,
+    pub conflict_path: Option<String>,
+    pub local_etag: Option<String>,
+    pub remote_etag: Option<String>,
+    pub kind: ConflictKind,
+    pub resolution: ConflictResolution,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub resolved_at: Option<i64>,
+}
+
+/// A page of conflict records along with the total number of matches
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictPage {
+    pub conflicts: Vec<ConflictRecord>,
+    pub total: i64,
+    pub offset: i64,
+    pub limit: i64,
+}
+
+impl ConflictPage {
+    pub fn has_more(&self) -> bool {
+        self.offset + (self.conflicts.len() as i64) < self.total
+    }
+}
+
+/// Kind of event in the activity feed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    Upload,
+    /// Download or hydration of a file
+    Download,
+    Delete,
+    Conflict,
+}
+
+/// Filter of the activity feed. All fields are optional and combined with AND.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActivityFilter {
+    /// Kinds of events to include, all if empty
+    pub kinds: Vec<ActivityKind>,
+    /// Only match this path and its descendants
+    pub path_prefix: Option<String>,
+}
+
+impl ActivityFilter {
+    pub fn includes(&self, kind: ActivityKind) -> bool {
+        self.kinds.is_empty() || self.kinds.contains(&kind)
+    }
+}
+
+/// One event of the activity feed: a finished transfer, a deletion or a conflict
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityEntry {
+    /// Unique within the feed, prefixed by the record it comes from
+    pub id: String,
+    pub drive_id: String,
+    pub kind: ActivityKind,
+    pub local_path: String,
+    /// Unix timestamp
+    pub occurred_at: i64,
+    /// Outcome of an upload or download
+    pub task_status: Option<TaskStatus>,
+    /// Why an upload or download failed
+    pub error: Option<String>,
+    /// What deleted the item
+    pub source: Option<ChangeSource>,
+    pub conflict_kind: Option<ConflictKind>,
+    pub conflict_resolution: Option<ConflictResolution>,
+}
+
+/// A page of the activity feed along with the total number of matches
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityPage {
+    pub entries: Vec<ActivityEntry>,
+    pub total: i64,
+    pub offset: i64,
+    pub limit: i64,
+}
+
+impl ActivityPage {
+    pub fn has_more(&self) -> bool {
+        self.offset + (self.entries.len() as i64) < self.total
+    }
+}
+
+/// Conflict to be inserted into the inventory
+#[derive(Debug, Clone)]
+pub struct NewConflictRecord {
+    pub drive_id: String,
+    pub original_path: String,
+    pub conflict_path: Option<String>,
+    pub local_etag: Option<String>,
+    pub remote_etag: Option<String>,
+    pub kind: ConflictKind,
+    pub resolution: ConflictResolution,
+}
+
+impl NewConflictRecord {
+    pub fn new(
+        drive_id: impl Into<String>,
+        original_path: impl Into<String>,
+        kind: ConflictKind,
+    ) -> Self {
+        Self {
+            drive_id: drive_id.into(),
+            original_path: original_path.into(),
+            conflict_path: None,
+            local_etag: None,
+            remote_etag: None,
+            kind,
+            resolution: ConflictResolution::Pending,
+        }
+    }
+
+    pub fn with_conflict_path(mut self, path: impl Into<String>) -> Self {
+        self.conflict_path = Some(path.into());
+        self
+    }
+
+    pub fn with_local_etag(mut self, etag: impl Into<String>) -> Self {
+        self.local_etag = Some(etag.into());
+        self
+    }
+
+    pub fn with_remote_etag(mut self, etag: impl Into<String>) -> Self {
+        self.remote_etag = Some(etag.into());
+        self
+    }
+
+    pub fn with_resolution(mut self, resolution: ConflictResolution) -> Self {
+        self.resolution = resolution;
+        self
+    }
+}
+
+/// Cloud filter callback that failed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CfapiOperation {
+    FetchData,
+    FetchPlaceholders,
+    ValidateData,
+    Rename,
+}
+
+impl CfapiOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CfapiOperation::FetchData => "fetch_data",
+            CfapiOperation::FetchPlaceholders => "fetch_placeholders",
+            CfapiOperation::ValidateData => "validate_data",
+            CfapiOperation::Rename => "rename",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "fetch_data" => Some(CfapiOperation::FetchData),
+            "fetch_placeholders" => Some(CfapiOperation::FetchPlaceholders),
+            "validate_data" => Some(CfapiOperation::ValidateData),
+            "rename" => Some(CfapiOperation::Rename),
+            _ => None,
+        }
+    }
+}
+
+/// Broad cause of a cloud filter callback failure, used to pick a recovery
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CfapiFailureClass {
+    /// Windows lost the connection to the sync root
+    ProviderDisconnected,
+    /// The placeholder's metadata or properties are damaged or out of sync
+    PlaceholderCorrupt,
+    /// Network, timeout or resource problems that go away on their own
+    Transient,
+    Cancelled,
+    Other,
+}
+
+impl CfapiFailureClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CfapiFailureClass::ProviderDisconnected => "provider_disconnected",
+            CfapiFailureClass::PlaceholderCorrupt => "placeholder_corrupt",
+            CfapiFailureClass::Transient => "transient",
+            CfapiFailureClass::Cancelled => "cancelled",
+            CfapiFailureClass::Other => "other",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "provider_disconnected" => Some(CfapiFailureClass::ProviderDisconnected),
+            "placeholder_corrupt" => Some(CfapiFailureClass::PlaceholderCorrupt),
+            "transient" => Some(CfapiFailureClass::Transient),
+            "cancelled" => Some(CfapiFailureClass::Cancelled),
+            "other" => Some(CfapiFailureClass::Other),
+            _ => None,
+        }
+    }
+
+    /// Recovery that usually fixes failures of this class
+    pub fn recovery(&self) -> Option<CfapiRecovery> {
+        match self {
+            CfapiFailureClass::ProviderDisconnected => Some(CfapiRecovery::ReconnectSyncRoot),
+            CfapiFailureClass::PlaceholderCorrupt => Some(CfapiRecovery::RepairPlaceholder),
+            _ => None,
+        }
+    }
+}
+
+/// Recovery started automatically after a cloud filter callback failure
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CfapiRecovery {
+    /// Register the sync root again if needed and open a new connection to it
+    ReconnectSyncRoot,
+    /// Sync the placeholder with the remote file again
+    RepairPlaceholder,
+}
+
+impl CfapiRecovery {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CfapiRecovery::ReconnectSyncRoot => "reconnect_sync_root",
+            CfapiRecovery::RepairPlaceholder => "repair_placeholder",
+        }
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "reconnect_sync_root" => Some(CfapiRecovery::ReconnectSyncRoot),
+            "repair_placeholder" => Some(CfapiRecovery::RepairPlaceholder),
+            _ => None,
+        }
+    }
+}
+
+/// A recorded cloud filter callback failure
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CfapiFailureRecord {
+    pub id: i64,
+    pub drive_id: String,
+    pub operation: CfapiOperation,
+    pub local_path: String,
+    /// HRESULT reported by Windows, if the failure came from it
+    pub hresult: Option<i32>,
+    pub class: CfapiFailureClass,
+    pub error: String,
+    pub recovery: Option<CfapiRecovery>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewCfapiFailure {
+    pub drive_id: String,
+    pub operation: CfapiOperation,
+    pub local_path: String,
+    pub hresult: Option<i32>,
+    pub class: CfapiFailureClass,
+    pub error: String,
+    pub recovery: Option<CfapiRecovery>,
+}
+
+impl NewCfapiFailure {
+    pub fn new(
+        drive_id: impl Into<String>,
+        operation: CfapiOperation,
+        local_path: impl Into<String>,
+        class: CfapiFailureClass,
+        error: impl Into<String>,
+    ) -> Self {
+        Self {
+            drive_id: drive_id.into(),
+            operation,
+            local_path: local_path.into(),
+            hresult: None,
+            class,
+            error: error.into(),
+            recovery: None,
+        }
+    }
+
+    pub fn with_hresult(mut self, hresult: Option<i32>) -> Self {
+        self.hresult = hresult;
+        self
+    }
+
+    pub fn with_recovery(mut self, recovery: Option<CfapiRecovery>) -> Self {
+        self.recovery = recovery;
+        self
+    }
+}
+
+/// Number of failures of one class within a time window
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CfapiFailureSummary {
+    pub class: CfapiFailureClass,
+    pub count: i64,
+    /// When the latest failure of the class happened
+    pub last_at: i64,
+}
@@ -32,6 +32,9 @@ diesel::table! {
         error -> Nullable<Text>,
         created_at -> BigInt,
         updated_at -> BigInt,
+        force_override -> Bool,
+        attempts -> Integer,
+        attempt_history -> Nullable<Text>,
     }
 }
 
@@ -68,3 +71,89 @@ diesel::table! {
         updated_at -> BigInt,
     }
 }
+
+diesel::table! {
+    change_journal (id) {
+        id -> BigInt,
+        drive_id -> Nullable<Text>,
+        local_path -> Text,
+        operation -> Text,
+        source -> Text,
+        detail -> Nullable<Text>,
+        created_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    conflicts (id) {
+        id -> BigInt,
+        drive_id -> Text,
+        original_path -> Text,
+        conflict_path -> Nullable<Text>,
+        local_etag -> Nullable<Text>,
+        remote_etag -> Nullable<Text>,
+        kind -> Text,
+        resolution -> Text,
+        created_at -> BigInt,
+        updated_at -> BigInt,
+        resolved_at -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    dead_letters (task_id) {
+        task_id -> Text,
+        drive_id -> Text,
+        task_type -> Text,
+        local_path -> Text,
+        priority -> Integer,
+        custom_state -> Nullable<Text>,
+        force_override -> Bool,
+        failure -> Text,
+        error -> Text,
+        attempts -> Integer,
+        attempt_history -> Nullable<Text>,
+        created_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    usn_checkpoints (drive_id) {
+        drive_id -> Text,
+        journal_id -> BigInt,
+        next_usn -> BigInt,
+        updated_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    cfapi_failures (id) {
+        id -> BigInt,
+        drive_id -> Text,
+        operation -> Text,
+        local_path -> Text,
+        hresult -> Nullable<Integer>,
+        class -> Text,
+        error -> Text,
+        recovery -> Nullable<Text>,
+        created_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    event_log (id) {
+        id -> BigInt,
+        name -> Text,
+        payload -> Text,
+        created_at -> BigInt,
+    }
+}
+
+diesel::table! {
+    remote_folder_cache (drive_id, parent_uri) {
+        drive_id -> Text,
+        parent_uri -> Text,
+        folders -> Text,
+        fetched_at -> BigInt,
+    }
+}
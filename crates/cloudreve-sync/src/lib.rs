@@ -6,6 +6,7 @@ pub mod inventory;
 pub mod logging;
 pub mod shellext;
 pub mod tasks;
+pub mod updater;
 pub mod uploader;
 pub mod utils;
 
@@ -1,5 +1,5 @@
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 use tracing_subscriber::{
     EnvFilter,
@@ -186,6 +186,47 @@ pub fn set_log_level(level: LogLevel) -> Result<()> {
     Ok(())
 }
 
+/// Delete the oldest log files so that at most `max_files` remain.
+///
+/// The file appender only prunes when it rolls over to a new file, so files pile up
+/// if `max_files` is lowered or the app never runs across midnight.
+/// Returns the number of files removed.
+pub fn prune_log_files(log_dir: &Path, file_prefix: &str, max_files: usize) -> Result<usize> {
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = Vec::new();
+    for entry in std::fs::read_dir(log_dir)
+        .context("Failed to read log directory")?
+        .flatten()
+    {
+        if !entry.file_name().to_string_lossy().starts_with(file_prefix) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if let (true, Ok(modified)) = (metadata.is_file(), metadata.modified()) {
+            files.push((modified, entry.path()));
+        }
+    }
+
+    // The newest file is the one being written to, so it is always kept
+    let keep = max_files.max(1);
+    if files.len() <= keep {
+        return Ok(0);
+    }
+
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    let mut removed = 0;
+    for (_, path) in files.into_iter().skip(keep) {
+        match std::fs::remove_file(&path) {
+            Ok(()) => removed += 1,
+            Err(e) => {
+                tracing::warn!(target: "main", path = %path.display(), error = %e, "Failed to remove old log file");
+            }
+        }
+    }
+    Ok(removed)
+}
+
 /// Get the current file logging enabled state
 pub fn is_file_logging_enabled() -> bool {
     FILE_LOGGING_ENABLED
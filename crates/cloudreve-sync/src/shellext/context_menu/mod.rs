@@ -3,19 +3,27 @@
 
 mod explorer_command;
 mod factory;
+mod pin;
 mod resolve_conflict;
+mod send_to;
+mod share_link;
 mod sub_commands;
 mod sync_now;
 mod view_online;
 
 pub use explorer_command::CrExplorerCommandHandler;
 pub use factory::CrExplorerCommandFactory;
+pub use pin::PinCommandHandler;
 pub use resolve_conflict::ResolveConflictCommandHandler;
+pub use send_to::{CLSID_SEND_TO_COMMAND, SendToCommandFactory, SendToCommandHandler};
+pub use share_link::ShareLinkCommandHandler;
 pub use sub_commands::SubCommands;
 pub use sync_now::SyncNowCommandHandler;
 pub use view_online::ViewOnlineCommandHandler;
 
+use std::path::PathBuf;
 use windows::ApplicationModel;
+use windows::Win32::UI::Shell::{IShellItemArray, SIGDN_FILESYSPATH};
 use windows::core::*;
 
 // UUID for our context menu handler - matches the C++ implementation
@@ -30,3 +38,17 @@ pub fn get_images_path() -> Result<String> {
             .to_string(),
     ))
 }
+
+/// File system paths of the selected items
+fn selected_paths(items: &IShellItemArray) -> Result<Vec<PathBuf>> {
+    unsafe {
+        let count = items.GetCount()?;
+        let mut paths = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            let item = items.GetItemAt(i)?;
+            let display_name = item.GetDisplayName(SIGDN_FILESYSPATH)?;
+            paths.push(PathBuf::from(display_name.to_string()?));
+        }
+        Ok(paths)
+    }
+}
@@ -0,0 +1,149 @@
+use super::selected_paths;
+use crate::cfapi::placeholder::{LocalFileInfo, PinState};
+use crate::drive::manager::DriveManager;
+use crate::{drive::commands::ManagerCommand, utils::app::AppRoot};
+use rust_i18n::t;
+use std::sync::Arc;
+use windows::{
+    Win32::{Foundation::*, System::Com::*, UI::Shell::*},
+    core::*,
+};
+
+/// "Always keep on this device" (pins) or "Free up space" (unpins) for the selection
+#[implement(IExplorerCommand)]
+pub struct PinCommandHandler {
+    drive_manager: Arc<DriveManager>,
+    app_root: AppRoot,
+    state: PinState,
+}
+
+impl PinCommandHandler {
+    pub fn keep_on_device(drive_manager: Arc<DriveManager>, app_root: AppRoot) -> Self {
+        Self {
+            drive_manager,
+            app_root,
+            state: PinState::Pinned,
+        }
+    }
+
+    pub fn free_up_space(drive_manager: Arc<DriveManager>, app_root: AppRoot) -> Self {
+        Self {
+            drive_manager,
+            app_root,
+            state: PinState::Unpinned,
+        }
+    }
+
+    /// Whether the command would change `info`: pinning an item not pinned yet, or freeing
+    /// up a file whose content is on disk. Folders may hold hydrated children, so they can
+    /// always be freed up.
+    fn applies_to(&self, info: &LocalFileInfo) -> bool {
+        match self.state {
+            PinState::Pinned => info.pinned() != PinState::Pinned,
+            _ => {
+                info.is_directory() || info.pinned() == PinState::Pinned || !info.partial_on_disk()
+            }
+        }
+    }
+}
+
+impl IExplorerCommand_Impl for PinCommandHandler_Impl {
+    fn GetTitle(&self, _items: Option<&IShellItemArray>) -> Result<PWSTR> {
+        let title = match self.state {
+            PinState::Pinned => t!("keepOnDevice"),
+            _ => t!("freeUpSpace"),
+        };
+        let hstring = HSTRING::from(title.as_ref());
+        unsafe { SHStrDupW(&hstring) }
+    }
+
+    fn GetIcon(&self, _items: Option<&IShellItemArray>) -> Result<PWSTR> {
+        let icon = match self.state {
+            PinState::Pinned => "sync-from1.ico",
+            _ => "sync-to1.ico",
+        };
+        let icon_path = format!("{}\\{}", self.app_root.image_path(), icon);
+        let hstring = HSTRING::from(icon_path);
+        unsafe { SHStrDupW(&hstring) }
+    }
+
+    fn GetToolTip(&self, _items: Option<&IShellItemArray>) -> Result<PWSTR> {
+        Err(Error::from(E_NOTIMPL))
+    }
+
+    fn GetCanonicalName(&self) -> Result<GUID> {
+        match self.state {
+            PinState::Pinned => Ok(GUID::from_u128(0xf697fc3d_f8ee_46c8_86bb_11e14a2e598b)),
+            _ => Ok(GUID::from_u128(0x5fd185e0_0172_45b5_812c_ebab5c5e3db6)),
+        }
+    }
+
+    fn GetState(&self, items: Option<&IShellItemArray>, _oktobeslow: BOOL) -> Result<u32> {
+        let Some(paths) = items.and_then(|items| selected_paths(items).ok()) else {
+            return Ok(ECS_HIDDEN.0 as u32);
+        };
+        let in_drive = !paths.is_empty()
+            && paths.iter().all(|path| {
+                self.drive_manager
+                    .inventory_for_path(&path.to_string_lossy())
+                    .is_some()
+            });
+        if !in_drive {
+            return Ok(ECS_HIDDEN.0 as u32);
+        }
+
+        // Gray out when every selected item is already in the requested state
+        let applies = paths.iter().any(|path| {
+            LocalFileInfo::from_path(path).is_ok_and(|info| info.exists && self.applies_to(&info))
+        });
+        if applies {
+            Ok(ECS_ENABLED.0 as u32)
+        } else {
+            Ok(ECS_DISABLED.0 as u32)
+        }
+    }
+
+    fn Invoke(
+        &self,
+        selection: Option<&IShellItemArray>,
+        _bindctx: Option<&IBindCtx>,
+    ) -> Result<()> {
+        tracing::debug!(
+            target: "shellext::context_menu",
+            state = ?self.state,
+            "Pin context menu command invoked"
+        );
+
+        let Some(items) = selection else {
+            return Ok(());
+        };
+
+        let paths = selected_paths(items)?;
+        if paths.is_empty() {
+            return Ok(());
+        }
+
+        // Send command through channel to async processor
+        let command_tx = self.drive_manager.get_command_sender();
+        if let Err(e) = command_tx.send(ManagerCommand::SetPinState {
+            paths,
+            state: self.state,
+        }) {
+            tracing::error!(
+                target: "shellext::context_menu",
+                error = %e,
+                "Failed to send SetPinState command"
+            );
+        }
+
+        Ok(())
+    }
+
+    fn GetFlags(&self) -> Result<u32> {
+        Ok(ECF_DEFAULT.0 as u32)
+    }
+
+    fn EnumSubCommands(&self) -> Result<IEnumExplorerCommand> {
+        Err(Error::from(E_NOTIMPL))
+    }
+}
@@ -1,15 +1,18 @@
 use crate::drive::commands::ManagerCommand;
 use crate::drive::manager::DriveManager;
+use crate::drive::sync::is_conflict_copy;
 use crate::inventory::ConflictState;
 use crate::utils::app::AppRoot;
 use rust_i18n::t;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use windows::{
     Win32::{Foundation::*, System::Com::*, UI::Shell::*},
     core::*,
 };
 
-/// Command that shows "Resolve conflict" menu item for files with pending conflicts
+/// Command that shows "Resolve conflict…" for conflicted files and conflict copies, and
+/// opens the conflicts window filtered to the selected file
 #[implement(IExplorerCommand)]
 pub struct ResolveConflictCommandHandler {
     drive_manager: Arc<DriveManager>,
@@ -24,47 +27,40 @@ impl ResolveConflictCommandHandler {
         }
     }
 
-    /// Check if the selected file has a pending conflict state
-    fn has_pending_conflict(&self, items: Option<&IShellItemArray>) -> bool {
-        let Some(items) = items else {
-            return false;
-        };
-
+    /// Path of the selected item, if exactly one is selected
+    fn selected_path(items: Option<&IShellItemArray>) -> Option<String> {
+        let items = items?;
         unsafe {
-            let count = match items.GetCount() {
-                Ok(c) => c,
-                Err(_) => return false,
-            };
-
             // Only show for single file selection
-            if count != 1 {
-                return false;
+            if items.GetCount().ok()? != 1 {
+                return None;
             }
+            let item = items.GetItemAt(0).ok()?;
+            let display_name = item.GetDisplayName(SIGDN_FILESYSPATH).ok()?;
+            display_name.to_string().ok()
+        }
+    }
 
-            let item = match items.GetItemAt(0) {
-                Ok(i) => i,
-                Err(_) => return false,
-            };
-
-            let display_name = match item.GetDisplayName(SIGDN_FILESYSPATH) {
-                Ok(d) => d,
-                Err(_) => return false,
-            };
-
-            let path_str = match display_name.to_string() {
-                Ok(s) => s,
-                Err(_) => return false,
-            };
-
-            // Query the inventory for conflict state
-            let inventory = self.drive_manager.get_inventory();
-            match inventory.query_by_path(&path_str) {
-                Ok(Some(metadata)) => {
-                    matches!(metadata.conflict_state, Some(ConflictState::Pending))
-                }
-                _ => false,
-            }
+    /// Check if the file is a conflict copy, is marked conflicted or has a conflict
+    /// record
+    fn has_conflict(&self, path_str: &str) -> bool {
+        let Some(inventory) = self.drive_manager.inventory_for_path(path_str) else {
+            return false;
+        };
+        if is_conflict_copy(Path::new(path_str)) {
+            return true;
         }
+
+        let pending = match inventory.query_by_path(path_str) {
+            Ok(Some(metadata)) => {
+                matches!(metadata.conflict_state, Some(ConflictState::Pending))
+            }
+            _ => false,
+        };
+        pending
+            || inventory
+                .list_conflicts_for_path(path_str)
+                .is_ok_and(|conflicts| !conflicts.is_empty())
     }
 }
 
@@ -90,7 +86,8 @@ impl IExplorerCommand_Impl for ResolveConflictCommandHandler_Impl {
     }
 
     fn GetState(&self, items: Option<&IShellItemArray>, _oktobeslow: BOOL) -> Result<u32> {
-        if self.has_pending_conflict(items) {
+        let has_conflict = Self::selected_path(items).is_some_and(|path| self.has_conflict(&path));
+        if has_conflict {
             Ok(ECS_ENABLED.0 as u32)
         } else {
             Ok(ECS_HIDDEN.0 as u32)
@@ -107,37 +104,27 @@ impl IExplorerCommand_Impl for ResolveConflictCommandHandler_Impl {
             "Resolve conflict context menu command invoked"
         );
 
-        let Some(items) = selection else {
+        let Some(path_str) = Self::selected_path(selection) else {
             return Ok(());
         };
 
-        unsafe {
-            let count = items.GetCount()?;
-            if count != 1 {
-                return Ok(());
-            }
+        tracing::debug!(
+            target: "shellext::context_menu",
+            path = %path_str,
+            "Opening conflicts window"
+        );
 
-            let item = items.GetItemAt(0)?;
-            let display_name = item.GetDisplayName(SIGDN_FILESYSPATH)?;
-            let path_str = display_name.to_string()?;
-            let path = std::path::PathBuf::from(&path_str);
+        // Send command through channel to async processor
+        let command_tx = self.drive_manager.get_command_sender();
 
-            tracing::debug!(
+        if let Err(e) = command_tx.send(ManagerCommand::OpenConflictsWindow {
+            path: PathBuf::from(path_str),
+        }) {
+            tracing::error!(
                 target: "shellext::context_menu",
-                path = %path_str,
-                "Opening conflict resolution toast"
+                error = %e,
+                "Failed to send OpenConflictsWindow command"
             );
-
-            // Send command through channel to async processor
-            let command_tx = self.drive_manager.get_command_sender();
-
-            if let Err(e) = command_tx.send(ManagerCommand::ShowConflictToast { path }) {
-                tracing::error!(
-                    target: "shellext::context_menu",
-                    error = %e,
-                    "Failed to send ShowConflictToast command"
-                );
-            }
         }
 
         Ok(())
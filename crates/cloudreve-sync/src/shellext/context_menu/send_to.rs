@@ -0,0 +1,138 @@
+use super::selected_paths;
+use crate::drive::commands::ManagerCommand;
+use crate::drive::manager::DriveManager;
+use crate::utils::app::{AppRoot, get_app_root};
+use rust_i18n::t;
+use std::sync::Arc;
+use windows::{
+    Win32::{Foundation::*, System::Com::*, UI::Shell::*},
+    core::*,
+};
+
+pub const CLSID_SEND_TO_COMMAND: GUID = GUID::from_u128(0x7a4e9c13_5d2b_4f86_b0e7_19c3d8a6f452);
+
+/// "Send to Cloudreve…" for files outside the sync roots, registered for every file
+/// type. Opens a window to pick the drive and remote folder to upload them to.
+#[implement(IExplorerCommand)]
+pub struct SendToCommandHandler {
+    drive_manager: Arc<DriveManager>,
+    app_root: AppRoot,
+}
+
+impl SendToCommandHandler {
+    pub fn new(drive_manager: Arc<DriveManager>) -> Self {
+        Self {
+            drive_manager,
+            app_root: get_app_root(),
+        }
+    }
+
+    /// Whether the selection only holds files that are not synced already
+    fn can_send(&self, items: Option<&IShellItemArray>) -> bool {
+        let Some(paths) = items.and_then(|items| selected_paths(items).ok()) else {
+            return false;
+        };
+        !paths.is_empty()
+            && paths.iter().all(|path| {
+                path.is_file()
+                    && self
+                        .drive_manager
+                        .inventory_for_path(&path.to_string_lossy())
+                        .is_none()
+            })
+    }
+}
+
+impl IExplorerCommand_Impl for SendToCommandHandler_Impl {
+    fn GetTitle(&self, _items: Option<&IShellItemArray>) -> Result<PWSTR> {
+        let title = t!("sendToCloudreve");
+        let hstring = HSTRING::from(title.as_ref());
+        unsafe { SHStrDupW(&hstring) }
+    }
+
+    fn GetIcon(&self, _items: Option<&IShellItemArray>) -> Result<PWSTR> {
+        let icon_path = format!("{}\\cloudreve.ico", self.app_root.image_path_general());
+        let hstring = HSTRING::from(icon_path);
+        unsafe { SHStrDupW(&hstring) }
+    }
+
+    fn GetToolTip(&self, _items: Option<&IShellItemArray>) -> Result<PWSTR> {
+        Err(Error::from(E_NOTIMPL))
+    }
+
+    fn GetCanonicalName(&self) -> Result<GUID> {
+        Ok(CLSID_SEND_TO_COMMAND)
+    }
+
+    fn GetState(&self, items: Option<&IShellItemArray>, _oktobeslow: BOOL) -> Result<u32> {
+        if self.can_send(items) {
+            Ok(ECS_ENABLED.0 as u32)
+        } else {
+            Ok(ECS_HIDDEN.0 as u32)
+        }
+    }
+
+    fn Invoke(
+        &self,
+        selection: Option<&IShellItemArray>,
+        _bindctx: Option<&IBindCtx>,
+    ) -> Result<()> {
+        tracing::debug!(target: "shellext::context_menu", "Send to context menu command invoked");
+
+        let Some(items) = selection else {
+            return Ok(());
+        };
+        let paths = selected_paths(items)?;
+        if paths.is_empty() {
+            return Ok(());
+        }
+
+        let command_tx = self.drive_manager.get_command_sender();
+        if let Err(e) = command_tx.send(ManagerCommand::OpenSendToWindow { paths }) {
+            tracing::error!(target: "shellext::context_menu", error = %e, "Failed to send OpenSendToWindow command");
+        }
+        Ok(())
+    }
+
+    fn GetFlags(&self) -> Result<u32> {
+        Ok(ECF_DEFAULT.0 as u32)
+    }
+
+    fn EnumSubCommands(&self) -> Result<IEnumExplorerCommand> {
+        Err(Error::from(E_NOTIMPL))
+    }
+}
+
+// Class factory for creating instances of the send-to command
+#[implement(IClassFactory)]
+pub struct SendToCommandFactory {
+    drive_manager: Arc<DriveManager>,
+}
+
+impl SendToCommandFactory {
+    pub fn new(drive_manager: Arc<DriveManager>) -> Self {
+        Self { drive_manager }
+    }
+}
+
+impl IClassFactory_Impl for SendToCommandFactory_Impl {
+    fn CreateInstance(
+        &self,
+        outer: Option<&IUnknown>,
+        iid: *const GUID,
+        result: *mut *mut core::ffi::c_void,
+    ) -> Result<()> {
+        if outer.is_some() {
+            return Err(Error::from(CLASS_E_NOAGGREGATION));
+        }
+
+        let handler = SendToCommandHandler::new(self.drive_manager.clone());
+        let handler: IUnknown = handler.into();
+
+        unsafe { handler.query(iid, result).ok() }
+    }
+
+    fn LockServer(&self, _lock: BOOL) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use super::selected_paths;
+use crate::drive::commands::ManagerCommand;
+use crate::drive::manager::DriveManager;
+use crate::utils::app::AppRoot;
+use rust_i18n::t;
+use std::sync::Arc;
+use windows::{
+    Win32::{Foundation::*, System::Com::*, UI::Shell::*},
+    core::*,
+};
+
+/// "Copy share link" for the selection, shown only if every selected item can be shared
+#[implement(IExplorerCommand)]
+pub struct ShareLinkCommandHandler {
+    drive_manager: Arc<DriveManager>,
+    app_root: AppRoot,
+}
+
+impl ShareLinkCommandHandler {
+    pub fn new(drive_manager: Arc<DriveManager>, app_root: AppRoot) -> Self {
+        Self {
+            drive_manager,
+            app_root,
+        }
+    }
+
+    /// Whether every selected item is synced and the user may share it
+    fn can_share(&self, items: Option<&IShellItemArray>) -> bool {
+        let Some(paths) = items.and_then(|items| selected_paths(items).ok()) else {
+            return false;
+        };
+        !paths.is_empty()
+            && paths.iter().all(|path| {
+                let path = path.to_string_lossy();
+                self.drive_manager
+                    .inventory_for_path(&path)
+                    .and_then(|inventory| inventory.query_by_path(&path).ok().flatten())
+                    .is_some_and(|metadata| metadata.shareable())
+            })
+    }
+}
+
+impl IExplorerCommand_Impl for ShareLinkCommandHandler_Impl {
+    fn GetTitle(&self, _items: Option<&IShellItemArray>) -> Result<PWSTR> {
+        let title = t!("copyShareLink");
+        let hstring = HSTRING::from(title.as_ref());
+        unsafe { SHStrDupW(&hstring) }
+    }
+
+    fn GetIcon(&self, _items: Option<&IShellItemArray>) -> Result<PWSTR> {
+        let icon_path = format!("{}\\people.ico", self.app_root.image_path());
+        let hstring = HSTRING::from(icon_path);
+        unsafe { SHStrDupW(&hstring) }
+    }
+
+    fn GetToolTip(&self, _items: Option<&IShellItemArray>) -> Result<PWSTR> {
+        Err(Error::from(E_NOTIMPL))
+    }
+
+    fn GetCanonicalName(&self) -> Result<GUID> {
+        Ok(GUID::from_u128(0x4c1f7a52_93be_4e0d_a8c6_2d5b7e91f3a8))
+    }
+
+    fn GetState(&self, items: Option<&IShellItemArray>, _oktobeslow: BOOL) -> Result<u32> {
+        if self.can_share(items) {
+            Ok(ECS_ENABLED.0 as u32)
+        } else {
+            Ok(ECS_HIDDEN.0 as u32)
+        }
+    }
+
+    fn Invoke(
+        &self,
+        selection: Option<&IShellItemArray>,
+        _bindctx: Option<&IBindCtx>,
+    ) -> Result<()> {
+        tracing::debug!(target: "shellext::context_menu", "Copy share link context menu command invoked");
+
+        let Some(items) = selection else {
+            return Ok(());
+        };
+        let paths = selected_paths(items)?;
+        if paths.is_empty() {
+            return Ok(());
+        }
+
+        // Send command through channel to async processor
+        let command_tx = self.drive_manager.get_command_sender();
+        if let Err(e) = command_tx.send(ManagerCommand::CopyShareLink { paths }) {
+            tracing::error!(target: "shellext::context_menu", error = %e, "Failed to send CopyShareLink command");
+        }
+        Ok(())
+    }
+
+    fn GetFlags(&self) -> Result<u32> {
+        Ok(ECF_DEFAULT.0 as u32)
+    }
+
+    fn EnumSubCommands(&self) -> Result<IEnumExplorerCommand> {
+        Err(Error::from(E_NOTIMPL))
+    }
+}
@@ -1,4 +1,7 @@
-use super::{ResolveConflictCommandHandler, SyncNowCommandHandler, ViewOnlineCommandHandler};
+use super::{
+    PinCommandHandler, ResolveConflictCommandHandler, ShareLinkCommandHandler,
+    SyncNowCommandHandler, ViewOnlineCommandHandler,
+};
 use crate::{drive::manager::DriveManager, utils::app::AppRoot};
 use std::sync::{Arc, Mutex};
 use windows::{
@@ -109,8 +112,29 @@ macro_rules! sub_command_factory {
 }
 
 sub_command_factory!(create_view_online_command, ViewOnlineCommandHandler);
+sub_command_factory!(create_share_link_command, ShareLinkCommandHandler);
 sub_command_factory!(create_sync_now_command, SyncNowCommandHandler);
 sub_command_factory!(create_resolve_conflict_command, ResolveConflictCommandHandler);
 
-const SUB_COMMAND_FACTORIES: [SubCommandFactory; 3] =
-    [create_view_online_command, create_sync_now_command, create_resolve_conflict_command];
+fn create_keep_on_device_command(
+    drive_manager: Arc<DriveManager>,
+    app_root: AppRoot,
+) -> IExplorerCommand {
+    PinCommandHandler::keep_on_device(drive_manager, app_root).into()
+}
+
+fn create_free_up_space_command(
+    drive_manager: Arc<DriveManager>,
+    app_root: AppRoot,
+) -> IExplorerCommand {
+    PinCommandHandler::free_up_space(drive_manager, app_root).into()
+}
+
+const SUB_COMMAND_FACTORIES: [SubCommandFactory; 6] = [
+    create_view_online_command,
+    create_share_link_command,
+    create_sync_now_command,
+    create_keep_on_device_command,
+    create_free_up_space_command,
+    create_resolve_conflict_command,
+];
@@ -1,3 +1,4 @@
+use super::selected_paths;
 use crate::drive::manager::DriveManager;
 use crate::drive::sync::SyncMode;
 use crate::{drive::commands::ManagerCommand, utils::app::AppRoot};
@@ -57,8 +58,21 @@ impl IExplorerCommand_Impl for SyncNowCommandHandler_Impl {
         Ok(GUID::from_u128(0x50f8d185_47c9_45f8_a592_2d2cfefc9cd0))
     }
 
-    fn GetState(&self, _items: Option<&IShellItemArray>, _oktobeslow: BOOL) -> Result<u32> {
-        Ok(ECS_ENABLED.0 as u32)
+    fn GetState(&self, items: Option<&IShellItemArray>, _oktobeslow: BOOL) -> Result<u32> {
+        // Gray out when every selected item is excluded by the ignore rules
+        let paths = match items {
+            Some(items) => selected_paths(items)?,
+            None => Vec::new(),
+        };
+        let all_ignored = !paths.is_empty()
+            && paths
+                .iter()
+                .all(|path| self.drive_manager.is_ignored_path(&path.to_string_lossy()));
+        if all_ignored {
+            Ok(ECS_DISABLED.0 as u32)
+        } else {
+            Ok(ECS_ENABLED.0 as u32)
+        }
     }
 
     fn Invoke(
@@ -1,7 +1,7 @@
+use super::selected_paths;
 use crate::{drive::commands::ManagerCommand, utils::app::AppRoot};
 use crate::drive::manager::DriveManager;
 use rust_i18n::t;
-use std::path::PathBuf;
 use std::sync::Arc;
 use windows::{
     Win32::{Foundation::*, System::Com::*, UI::Shell::*},
@@ -55,13 +55,17 @@ impl IExplorerCommand_Impl for ViewOnlineCommandHandler_Impl {
             return Ok(ECS_ENABLED.0 as u32);
         };
 
-        unsafe {
-            let count = items.GetCount()?;
-            if count <= 1 {
-                Ok(ECS_ENABLED.0 as u32)
-            } else {
-                Ok(ECS_HIDDEN.0 as u32)
-            }
+        // Files and folders, single or batched, all have a page online
+        let paths = selected_paths(items)?;
+        let in_drive = paths.iter().any(|path| {
+            self.drive_manager
+                .inventory_for_path(&path.to_string_lossy())
+                .is_some()
+        });
+        if in_drive {
+            Ok(ECS_ENABLED.0 as u32)
+        } else {
+            Ok(ECS_HIDDEN.0 as u32)
         }
     }
 
@@ -72,28 +76,20 @@ impl IExplorerCommand_Impl for ViewOnlineCommandHandler_Impl {
     ) -> Result<()> {
         tracing::debug!(target: "shellext::context_menu", "View online context menu command invoked");
 
-        if let Some(items) = selection {
-            unsafe {
-                let count = items.GetCount()?;
-                if count != 1 {
-                    return Ok(());
-                }
-
-                // Get the first item
-                let item = items.GetItemAt(0)?;
-                let display_name = item.GetDisplayName(SIGDN_FILESYSPATH)?;
-                let path_str = display_name.to_string()?;
-                let path = PathBuf::from(path_str.clone());
-
-                tracing::debug!(target: "shellext::context_menu", path = %path_str, "View online requested");
+        let Some(items) = selection else {
+            return Ok(());
+        };
+        let paths = selected_paths(items)?;
+        if paths.is_empty() {
+            return Ok(());
+        }
 
-                // Send command through channel to async processor
-                let command_tx = self.drive_manager.get_command_sender();
+        tracing::debug!(target: "shellext::context_menu", count = paths.len(), "View online requested");
 
-                if let Err(e) = command_tx.send(ManagerCommand::ViewOnline { path: path.clone() }) {
-                    tracing::error!(target: "shellext::context_menu", error = %e, "Failed to send ViewOnline command");
-                }
-            }
+        // Send command through channel to async processor
+        let command_tx = self.drive_manager.get_command_sender();
+        if let Err(e) = command_tx.send(ManagerCommand::ViewOnline { paths }) {
+            tracing::error!(target: "shellext::context_menu", error = %e, "Failed to send ViewOnline command");
         }
 
         Ok(())
@@ -1,5 +1,5 @@
 use crate::drive::manager::DriveManager;
-use crate::inventory::InventoryDb;
+use crate::inventory::ConflictState;
 use crate::utils::app::{AppRoot, get_app_root};
 use cloudreve_api::Boolset;
 use cloudreve_api::models::explorer::file_permission;
@@ -15,21 +15,28 @@ use windows::{
 pub const CLSID_CUSTOM_STATE_HANDLER: GUID =
     GUID::from_u128(0xf0c9de6c_6c76_44d7_a58e_579cdf7af263);
 
+// Ids of the custom states registered with the sync root
+pub const CUSTOM_STATE_SHARED: i32 = 1;
+pub const CUSTOM_STATE_ACCESS: i32 = 2;
+pub const CUSTOM_STATE_OWNER: i32 = 3;
+pub const CUSTOM_STATE_MODIFIED_BY: i32 = 4;
+pub const CUSTOM_STATE_CONFLICT: i32 = 5;
+pub const CUSTOM_STATE_SYNC_ERROR: i32 = 6;
+
+// Red "error" icon shipped with Windows, used for the sync error state
+const SYNC_ERROR_ICON: &str = "user32.dll,-103";
+
 #[implement(IStorageProviderItemPropertySource)]
 pub struct CustomStateHandler {
-     #[allow(dead_code)]
     drive_manager: Arc<DriveManager>,
-    inventory: Arc<InventoryDb>,
     app_root: AppRoot,
 }
 
 impl CustomStateHandler {
     pub fn new(drive_manager: Arc<DriveManager>) -> Self {
-        let inventory = drive_manager.get_inventory();
         Self {
             drive_manager,
             app_root: get_app_root(),
-            inventory,
         }
     }
 }
@@ -41,24 +48,54 @@ impl IStorageProviderItemPropertySource_Impl for CustomStateHandler_Impl {
     ) -> Result<IIterable<StorageProviderItemProperty>> {
         tracing::info!(target: "shellext::custom_state", "Getting item properties for {}", itempath);
 
-        let file_metadata = self
-            .inventory
-            .query_by_path(itempath.to_string().as_str())
-            .map_err(|e| {
-                tracing::error!(target: "shellext::custom_state", "Failed to query inventory for path {}: {:?}", itempath, e);
-                Error::from(E_FAIL)
-            })?
+        let inventory = self
+            .drive_manager
+            .inventory_for_path(itempath.to_string().as_str())
             .ok_or_else(|| {
-                tracing::error!(target: "shellext::custom_state", "No metadata found for path {}", itempath);
+                tracing::error!(target: "shellext::custom_state", "No drive found for path {}", itempath);
                 Error::from(E_FAIL)
             })?;
+        let path = itempath.to_string();
+        let file_metadata = inventory.query_by_path(&path).map_err(|e| {
+            tracing::error!(target: "shellext::custom_state", "Failed to query inventory for path {}: {:?}", itempath, e);
+            Error::from(E_FAIL)
+        })?;
 
         let image_path = self.app_root.image_path();
         let mut vec = Vec::new();
 
+        // Conflicts and failed syncs apply to new local files too, which are not in the
+        // inventory yet
+        let conflicted = file_metadata
+            .as_ref()
+            .is_some_and(|meta| meta.conflict_state == Some(ConflictState::Pending))
+            || inventory
+                .get_pending_conflict_by_path(&path)
+                .is_ok_and(|record| record.is_some());
+        if conflicted {
+            let properties = StorageProviderItemProperty::new()?;
+            properties.SetId(CUSTOM_STATE_CONFLICT)?;
+            properties
+                .SetIconResource(&HSTRING::from(format!("{}\\conflict1.ico,0", image_path)))?;
+            properties.SetValue(&HSTRING::from(t!("conflictStateValue").as_ref()))?;
+            vec.push(Some(properties));
+        }
+
+        if inventory.has_dead_letter_for_path(&path).unwrap_or(false) {
+            let properties = StorageProviderItemProperty::new()?;
+            properties.SetId(CUSTOM_STATE_SYNC_ERROR)?;
+            properties.SetIconResource(&HSTRING::from(SYNC_ERROR_ICON))?;
+            properties.SetValue(&HSTRING::from(t!("syncErrorStateValue").as_ref()))?;
+            vec.push(Some(properties));
+        }
+
+        let Some(file_metadata) = file_metadata else {
+            return IIterable::<StorageProviderItemProperty>::try_from(vec);
+        };
+
         if file_metadata.shared {
             let properties = StorageProviderItemProperty::new()?;
-            properties.SetId(1)?;
+            properties.SetId(CUSTOM_STATE_SHARED)?;
             properties.SetIconResource(&HSTRING::from(format!("{}\\people.ico,0", image_path)))?;
             properties.SetValue(&HSTRING::from(t!("shared").as_ref()))?;
             vec.push(Some(properties));
@@ -71,7 +108,7 @@ impl IStorageProviderItemPropertySource_Impl for CustomStateHandler_Impl {
             })?;
             if !permission.enabled(file_permission::READ as usize) {
                 let properties = StorageProviderItemProperty::new()?;
-                properties.SetId(2)?;
+                properties.SetId(CUSTOM_STATE_ACCESS)?;
                 properties
                     .SetIconResource(&HSTRING::from(format!("{}\\lock.ico,0", image_path)))?;
                 properties.SetValue(&HSTRING::from(t!("noAccess").as_ref()))?;
@@ -79,6 +116,27 @@ impl IStorageProviderItemPropertySource_Impl for CustomStateHandler_Impl {
             }
         }
 
+        let cloud_props = file_metadata.cloud_props();
+        if let Some(owned) = cloud_props.owned {
+            let owner = match owned {
+                true => t!("ownedByMe"),
+                false => t!("ownedByOthers"),
+            };
+            let properties = StorageProviderItemProperty::new()?;
+            properties.SetId(CUSTOM_STATE_OWNER)?;
+            properties.SetIconResource(&HSTRING::from(format!("{}\\people.ico,0", image_path)))?;
+            properties.SetValue(&HSTRING::from(owner.as_ref()))?;
+            vec.push(Some(properties));
+        }
+
+        if let Some(modified_by) = cloud_props.modified_by {
+            let properties = StorageProviderItemProperty::new()?;
+            properties.SetId(CUSTOM_STATE_MODIFIED_BY)?;
+            properties.SetIconResource(&HSTRING::from(format!("{}\\people.ico,0", image_path)))?;
+            properties.SetValue(&HSTRING::from(modified_by))?;
+            vec.push(Some(properties));
+        }
+
         IIterable::<StorageProviderItemProperty>::try_from(vec)
     }
 }
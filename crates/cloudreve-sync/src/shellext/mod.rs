@@ -1,5 +1,6 @@
 pub mod context_menu;
 pub mod custom_state;
+pub mod property_handler;
 pub mod shell_service;
 pub mod status_ui;
 pub mod thumbnail;
@@ -0,0 +1,219 @@
+use crate::drive::commands::{CloudFileProperties, ManagerCommand};
+use crate::drive::manager::DriveManager;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use windows::{
+    Win32::{
+        Foundation::*,
+        Storage::EnhancedStorage::{
+            PKEY_StorageProviderFileRemoteUri, PKEY_StorageProviderFileVersion,
+            PKEY_StorageProviderSharingStatus,
+        },
+        System::Com::{StructuredStorage::InitPropVariantFromFileTime, *},
+        UI::Shell::{PropertiesSystem::*, *},
+    },
+    core::*,
+};
+
+pub const CLSID_PROPERTY_HANDLER: GUID = GUID::from_u128(0x5e7c2d9a_41b8_4f36_9a0d_c3e8f1b6a274);
+
+/// Last time the file was in sync with the cloud, described by `Cloudreve.propdesc`
+pub const PKEY_CLOUDREVE_LAST_SYNCED: PROPERTYKEY = PROPERTYKEY {
+    fmtid: GUID::from_u128(0x9b3f6e21_7c4d_4a58_b1e2_6d0a8f3c5b97),
+    pid: 2,
+};
+
+// Values of System.StorageProviderSharingStatus
+const SHARING_STATUS_PRIVATE: u32 = 0;
+const SHARING_STATUS_PUBLIC: u32 = 2;
+
+/// Seconds between 1601-01-01 (FILETIME epoch) and 1970-01-01
+const FILETIME_UNIX_EPOCH_OFFSET: i64 = 11_644_473_600;
+
+/// Read-only property store answering the Details pane with the cloud metadata of a
+/// synced file: remote path, share state, last synced time and etag
+#[implement(IPropertyStore, IPropertyStoreCapabilities, IInitializeWithItem)]
+pub struct PropertyHandler {
+    drive_manager: Arc<DriveManager>,
+    values: Mutex<Vec<(PROPERTYKEY, PROPVARIANT)>>,
+}
+
+impl PropertyHandler {
+    pub fn new(drive_manager: Arc<DriveManager>) -> Self {
+        Self {
+            drive_manager,
+            values: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Ask the drive for the cloud metadata of `path`
+    fn query_properties(&self, path: PathBuf) -> Result<Option<CloudFileProperties>> {
+        let command_tx = self.drive_manager.get_command_sender();
+        let (response_tx, response_rx) = tokio::sync::oneshot::channel();
+        if let Err(e) = command_tx.send(ManagerCommand::GetCloudProperties {
+            path,
+            response: response_tx,
+        }) {
+            tracing::error!(target: "shellext::property_handler", error = %e, "Failed to send GetCloudProperties command");
+            return Err(Error::from(E_FAIL));
+        }
+
+        response_rx
+            .blocking_recv()
+            .map_err(|e| {
+                tracing::error!(target: "shellext::property_handler", error = %e, "Failed to receive GetCloudProperties response");
+                Error::from(E_FAIL)
+            })?
+            .map_err(|e| {
+                tracing::error!(target: "shellext::property_handler", error = %e, "GetCloudProperties command failed");
+                Error::from(E_FAIL)
+            })
+    }
+}
+
+/// Property values for the Details pane
+fn property_values(properties: CloudFileProperties) -> Result<Vec<(PROPERTYKEY, PROPVARIANT)>> {
+    let sharing_status = match properties.shared {
+        true => SHARING_STATUS_PUBLIC,
+        false => SHARING_STATUS_PRIVATE,
+    };
+    let intervals =
+        (properties.last_synced + FILETIME_UNIX_EPOCH_OFFSET).max(0) as u64 * 10_000_000;
+    let last_synced = FILETIME {
+        dwLowDateTime: intervals as u32,
+        dwHighDateTime: (intervals >> 32) as u32,
+    };
+
+    Ok(vec![
+        (
+            PKEY_StorageProviderFileRemoteUri,
+            PROPVARIANT::from(properties.remote_uri.as_str()),
+        ),
+        (
+            PKEY_StorageProviderSharingStatus,
+            PROPVARIANT::from(sharing_status),
+        ),
+        (PKEY_CLOUDREVE_LAST_SYNCED, unsafe {
+            InitPropVariantFromFileTime(&last_synced)?
+        }),
+        (
+            PKEY_StorageProviderFileVersion,
+            PROPVARIANT::from(properties.etag.as_str()),
+        ),
+    ])
+}
+
+impl IInitializeWithItem_Impl for PropertyHandler_Impl {
+    fn Initialize(&self, psi: Option<&IShellItem>, _grfmode: u32) -> Result<()> {
+        let Some(psi) = psi else {
+            return Err(Error::from(E_INVALIDARG));
+        };
+        let path = unsafe { psi.GetDisplayName(SIGDN_FILESYSPATH)?.to_string()? };
+        tracing::trace!(target: "shellext::property_handler", path = %path, "Initializing property handler");
+
+        // Items outside the inventory have no cloud metadata
+        if self.drive_manager.inventory_for_path(&path).is_none() {
+            return Ok(());
+        }
+        let Some(properties) = self.query_properties(PathBuf::from(path))? else {
+            return Ok(());
+        };
+
+        *self.values.lock().map_err(|_| Error::from(E_FAIL))? = property_values(properties)?;
+        Ok(())
+    }
+}
+
+impl IPropertyStore_Impl for PropertyHandler_Impl {
+    fn GetCount(&self) -> Result<u32> {
+        let values = self.values.lock().map_err(|_| Error::from(E_FAIL))?;
+        Ok(values.len() as u32)
+    }
+
+    fn GetAt(&self, iprop: u32, pkey: *mut PROPERTYKEY) -> Result<()> {
+        let values = self.values.lock().map_err(|_| Error::from(E_FAIL))?;
+        let (key, _) = values
+            .get(iprop as usize)
+            .ok_or_else(|| Error::from(E_INVALIDARG))?;
+        if pkey.is_null() {
+            return Err(Error::from(E_POINTER));
+        }
+        unsafe { pkey.write(*key) };
+        Ok(())
+    }
+
+    fn GetValue(&self, key: *const PROPERTYKEY) -> Result<PROPVARIANT> {
+        if key.is_null() {
+            return Err(Error::from(E_POINTER));
+        }
+        let key = unsafe { *key };
+        let values = self.values.lock().map_err(|_| Error::from(E_FAIL))?;
+        // Unknown keys are answered with an empty value, as the property store contract asks
+        Ok(values
+            .iter()
+            .find(|(candidate, _)| *candidate == key)
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default())
+    }
+
+    fn SetValue(&self, _key: *const PROPERTYKEY, _propvar: *const PROPVARIANT) -> Result<()> {
+        Err(Error::from(STG_E_ACCESSDENIED))
+    }
+
+    fn Commit(&self) -> Result<()> {
+        Err(Error::from(STG_E_ACCESSDENIED))
+    }
+}
+
+impl IPropertyStoreCapabilities_Impl for PropertyHandler_Impl {
+    fn IsPropertyWritable(&self, _key: *const PROPERTYKEY) -> HRESULT {
+        S_FALSE
+    }
+}
+
+/// Register the description of the custom properties, so Explorer can label and format
+/// them. Needs administrator rights; without them the values are still readable.
+pub fn register_property_schema(schema_path: &str) {
+    match unsafe { PSRegisterPropertySchema(&HSTRING::from(schema_path)) } {
+        Ok(()) => {
+            tracing::info!(target: "shellext::property_handler", path = %schema_path, "Property schema registered")
+        }
+        Err(e) => {
+            tracing::debug!(target: "shellext::property_handler", path = %schema_path, error = %e, "Failed to register property schema")
+        }
+    }
+}
+
+// Class factory for creating instances of our property handler
+#[implement(IClassFactory)]
+pub struct PropertyHandlerFactory {
+    drive_manager: Arc<DriveManager>,
+}
+
+impl PropertyHandlerFactory {
+    pub fn new(drive_manager: Arc<DriveManager>) -> Self {
+        Self { drive_manager }
+    }
+}
+
+impl IClassFactory_Impl for PropertyHandlerFactory_Impl {
+    fn CreateInstance(
+        &self,
+        outer: Option<&IUnknown>,
+        iid: *const GUID,
+        result: *mut *mut core::ffi::c_void,
+    ) -> Result<()> {
+        if outer.is_some() {
+            return Err(Error::from(CLASS_E_NOAGGREGATION));
+        }
+
+        let handler = PropertyHandler::new(self.drive_manager.clone());
+        let handler: IUnknown = handler.into();
+
+        unsafe { handler.query(iid, result).ok() }
+    }
+
+    fn LockServer(&self, _lock: BOOL) -> Result<()> {
+        Ok(())
+    }
+}
@@ -1,13 +1,20 @@
 use super::context_menu::*;
+use crate::EventBroadcaster;
 use crate::drive::manager::DriveManager;
 use crate::shellext::custom_state::{CLSID_CUSTOM_STATE_HANDLER, CustomStateHandlerFactory};
+use crate::shellext::property_handler::{
+    CLSID_PROPERTY_HANDLER, PropertyHandlerFactory, register_property_schema,
+};
 use crate::shellext::status_ui::{
     CLSID_STATUS_UI_HANDLER, StatusUIHandlerFactoryFactory,
 };
 use crate::shellext::thumbnail::{CLSID_THUMBNAIL_PROVIDER, ThumbnailProviderFactory};
 use crate::shellext::toast::{CLSID_TOAST_ACTIVATOR, ToastActivatorFactory};
+use crate::utils::app::get_app_root;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, mpsc};
 use std::thread;
+use std::time::{Duration, Instant};
 use windows::Win32::System::Com::{COINIT_MULTITHREADED, CoWaitForMultipleHandles};
 use windows::Win32::System::Threading::CreateEventW;
 use windows::{
@@ -15,68 +22,128 @@ use windows::{
     core::*,
 };
 
-pub fn init_and_start_service_task(drive_manager: Arc<DriveManager>) -> ServiceHandle {
+/// Delay before the first restart after the shell services failed, doubled for each
+/// consecutive failure
+const RESTART_BASE_DELAY: Duration = Duration::from_secs(2);
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(60);
+/// Consecutive failures after which the services are left stopped
+const MAX_CONSECUTIVE_FAILURES: u32 = 5;
+/// A run lasting this long no longer counts towards consecutive failures
+const HEALTHY_RUN: Duration = Duration::from_secs(300);
+
+pub fn init_and_start_service_task(
+    drive_manager: Arc<DriveManager>,
+    event_broadcaster: Arc<EventBroadcaster>,
+) -> ServiceHandle {
     let (tx, rx) = mpsc::channel();
+    let stopping = Arc::new(AtomicBool::new(false));
 
-    let mut services = ShellServices::new(drive_manager);
-    let handle: thread::JoinHandle<()> = thread::spawn(move || {
-        // Step 1: Initialize COM
-        if let Err(e) = services.init_com() {
-            tracing::error!(target: "shellext::shell_service", "Failed to initialize COM: {:?}", e);
-            let _ = tx.send(Err(e));
-            return;
-        }
+    let supervisor_stopping = stopping.clone();
+    let handle: thread::JoinHandle<()> =
+        thread::spawn(move || supervise(drive_manager, event_broadcaster, tx, supervisor_stopping));
 
-        // Step 2: Initialize handlers
-        if let Err(e) = services.init_and_start_custom_state_handler() {
-            tracing::error!(target: "shellext::shell_service", "Failed to initialize custom state handler: {:?}", e);
-            let _ = tx.send(Err(e));
-            return;
-        }
+    ServiceHandle {
+        thread: Some(handle),
+        init_result: rx,
+        stopping,
+    }
+}
 
-        if let Err(e) = services.init_and_start_view_online_handler() {
-            tracing::error!(target: "shellext::shell_service", "Failed to initialize view online handler: {:?}", e);
-            let _ = tx.send(Err(e));
-            return;
-        }
+/// Run the shell services on their own thread, and register them again whenever the
+/// thread panics or the COM message loop ends. Each change of health is broadcast.
+fn supervise(
+    drive_manager: Arc<DriveManager>,
+    event_broadcaster: Arc<EventBroadcaster>,
+    init_tx: mpsc::Sender<Result<()>>,
+    stopping: Arc<AtomicBool>,
+) {
+    let mut init_tx = Some(init_tx);
+    let mut restarts = 0u32;
+    let mut failures = 0u32;
+
+    loop {
+        let started = Instant::now();
+        let (run_tx, run_rx) = mpsc::channel();
+        let manager = drive_manager.clone();
+        let worker = match thread::Builder::new()
+            .name("shell-services".to_string())
+            .spawn(move || run_services(manager, run_tx))
+        {
+            Ok(worker) => worker,
+            Err(e) => {
+                tracing::error!(target: "shellext::shell_service", error = %e, "Failed to spawn shell services thread");
+                if let Some(tx) = init_tx.take() {
+                    let _ = tx.send(Err(Error::from(E_FAIL)));
+                }
+                event_broadcaster.shell_service_health_changed(
+                    false,
+                    restarts,
+                    Some(e.to_string()),
+                );
+                return;
+            }
+        };
 
-        if let Err(e) = services.init_and_start_status_ui_handler() {
-            tracing::error!(target: "shellext::shell_service", "Failed to initialize status ui handler: {:?}", e);
-            let _ = tx.send(Err(e));
-            return;
+        // The channel closes without a result if the thread panicked during registration
+        let init = run_rx.recv().unwrap_or_else(|_| Err(Error::from(E_FAIL)));
+        let initialized = init.is_ok();
+        if let Some(tx) = init_tx.take() {
+            let _ = tx.send(init);
         }
-
-        if let Err(e) = services.init_and_start_thumbnail_provider_handler() {
-            tracing::error!(target: "shellext::shell_service", "Failed to initialize thumbnail provider handler: {:?}", e);
-            let _ = tx.send(Err(e));
-            return;
+        if initialized && restarts > 0 {
+            tracing::info!(target: "shellext::shell_service", restarts, "Shell services restarted");
+            event_broadcaster.shell_service_health_changed(true, restarts, None);
         }
 
-        if let Err(e) = services.init_and_start_toast_handler() {
-            tracing::error!(target: "shellext::shell_service", "Failed to initialize toast handler: {:?}", e);
-            let _ = tx.send(Err(e));
+        let error = match worker.join() {
+            Ok(Ok(())) => "COM message loop ended".to_string(),
+            Ok(Err(e)) => e.to_string(),
+            Err(panic) => panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "shell services thread panicked".to_string()),
+        };
+        if stopping.load(Ordering::SeqCst) {
             return;
         }
 
-        // Notify that initialization is complete
-        let _ = tx.send(Ok(()));
+        tracing::error!(target: "shellext::shell_service", error = %error, restarts, "Shell services stopped");
+        event_broadcaster.shell_service_health_changed(false, restarts, Some(error));
 
-        // Step 3: Start message loop
-        if let Err(e) = services.run_message_loop() {
-            tracing::error!(target: "shellext::shell_service", "Error in message loop: {:?}", e);
+        if started.elapsed() >= HEALTHY_RUN {
+            failures = 0;
+        }
+        failures += 1;
+        if failures > MAX_CONSECUTIVE_FAILURES {
+            tracing::error!(target: "shellext::shell_service", failures, "Shell services keep failing, giving up");
+            return;
         }
-    });
 
-    ServiceHandle {
-        thread: Some(handle),
-        init_result: rx,
+        let delay = (RESTART_BASE_DELAY * 2u32.pow(failures - 1)).min(RESTART_MAX_DELAY);
+        tracing::info!(target: "shellext::shell_service", delay = ?delay, "Restarting shell services");
+        thread::sleep(delay);
+        restarts += 1;
     }
 }
 
+/// Register all handlers, report the outcome through `init_tx`, then serve COM requests
+/// until the message loop ends
+fn run_services(drive_manager: Arc<DriveManager>, init_tx: mpsc::Sender<Result<()>>) -> Result<()> {
+    let mut services = ShellServices::new(drive_manager);
+    let init = services.init_all();
+    let _ = init_tx.send(init.clone());
+    init?;
+
+    services.run_message_loop()
+}
+
 pub struct ServiceHandle {
      #[allow(dead_code)]
     thread: Option<thread::JoinHandle<()>>,
     init_result: mpsc::Receiver<windows::core::Result<()>>,
+    /// Set on shutdown, so the supervisor does not restart the services
+    stopping: Arc<AtomicBool>,
 }
 
 impl ServiceHandle {
@@ -90,6 +157,7 @@ impl ServiceHandle {
 impl Drop for ServiceHandle {
     fn drop(&mut self) {
         tracing::info!(target: "shellext::shell_service", "Shutting down shell services thread...");
+        self.stopping.store(true, Ordering::SeqCst);
     }
 }
 
@@ -107,6 +175,51 @@ impl ShellServices {
         }
     }
 
+    /// Initialize COM and register every handler
+    pub fn init_all(&mut self) -> Result<()> {
+        if let Err(e) = self.init_com() {
+            tracing::error!(target: "shellext::shell_service", "Failed to initialize COM: {:?}", e);
+            return Err(e);
+        }
+
+        if let Err(e) = self.init_and_start_custom_state_handler() {
+            tracing::error!(target: "shellext::shell_service", "Failed to initialize custom state handler: {:?}", e);
+            return Err(e);
+        }
+
+        if let Err(e) = self.init_and_start_view_online_handler() {
+            tracing::error!(target: "shellext::shell_service", "Failed to initialize view online handler: {:?}", e);
+            return Err(e);
+        }
+
+        if let Err(e) = self.init_and_start_send_to_handler() {
+            tracing::error!(target: "shellext::shell_service", "Failed to initialize send to handler: {:?}", e);
+            return Err(e);
+        }
+
+        if let Err(e) = self.init_and_start_status_ui_handler() {
+            tracing::error!(target: "shellext::shell_service", "Failed to initialize status ui handler: {:?}", e);
+            return Err(e);
+        }
+
+        if let Err(e) = self.init_and_start_thumbnail_provider_handler() {
+            tracing::error!(target: "shellext::shell_service", "Failed to initialize thumbnail provider handler: {:?}", e);
+            return Err(e);
+        }
+
+        if let Err(e) = self.init_and_start_property_handler() {
+            tracing::error!(target: "shellext::shell_service", "Failed to initialize property handler: {:?}", e);
+            return Err(e);
+        }
+
+        if let Err(e) = self.init_and_start_toast_handler() {
+            tracing::error!(target: "shellext::shell_service", "Failed to initialize toast handler: {:?}", e);
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
     pub fn init_com(&mut self) -> Result<()> {
         unsafe {
             CoInitializeEx(None, COINIT_MULTITHREADED).ok()?;
@@ -134,6 +247,27 @@ impl ShellServices {
         Ok(())
     }
 
+    pub fn init_and_start_property_handler(&mut self) -> Result<()> {
+        tracing::info!(target: "shellext::property_handler", "Initializing Shell Services (Property Handler)...");
+
+        register_property_schema(&get_app_root().property_schema_path());
+        unsafe {
+            let factory: IClassFactory =
+                PropertyHandlerFactory::new(self.drive_manager.clone()).into();
+            let cookie = CoRegisterClassObject(
+                &CLSID_PROPERTY_HANDLER,
+                &factory,
+                CLSCTX_LOCAL_SERVER,
+                REGCLS_MULTIPLEUSE,
+            )?;
+
+            self.cookies.push(cookie);
+            tracing::info!(target: "shellext::property_handler", "Property Handler registered with cookie: {}", cookie);
+        }
+
+        Ok(())
+    }
+
     pub fn init_and_start_toast_handler(&mut self) -> Result<()> {
         tracing::info!(target: "shellext::toast", "Initializing Shell Services (Toast Handler)...");
 
@@ -220,6 +354,27 @@ impl ShellServices {
         Ok(())
     }
 
+    pub fn init_and_start_send_to_handler(&mut self) -> Result<()> {
+        tracing::info!(target: "shellext::context_menu", "Initializing Shell Services (Send To Handler)...");
+
+        unsafe {
+            let factory: IClassFactory =
+                SendToCommandFactory::new(self.drive_manager.clone()).into();
+
+            let cookie = CoRegisterClassObject(
+                &CLSID_SEND_TO_COMMAND,
+                &factory,
+                CLSCTX_LOCAL_SERVER,
+                REGCLS_MULTIPLEUSE,
+            )?;
+
+            self.cookies.push(cookie);
+            tracing::info!(target: "shellext::context_menu", "Send To Handler registered with cookie: {}", cookie);
+        }
+
+        Ok(())
+    }
+
     pub fn run_message_loop(&self) -> Result<()> {
         tracing::info!(target: "shellext::context_menu", "Context Menu Handler is running. Press Ctrl+C to exit...");
 
@@ -230,8 +385,11 @@ impl ShellServices {
             // Use INVALID_HANDLE_VALUE as a dummy handle for CoWaitForMultipleHandles
             // This keeps the COM message pump running
             let dymmyevent = CreateEventW(None, FALSE, FALSE, None)?;
-            let index =
-                CoWaitForMultipleHandles((COWAIT_DISPATCH_CALLS).0 as u32, u32::MAX, &[dymmyevent]);
+            let index = CoWaitForMultipleHandles(
+                (COWAIT_DISPATCH_CALLS).0 as u32,
+                u32::MAX,
+                &[dymmyevent],
+            )?;
             tracing::info!(target: "shellext::context_menu", "CoWaitForMultipleHandles index: {:?}", index);
         }
 
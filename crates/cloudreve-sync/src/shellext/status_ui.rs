@@ -2,6 +2,8 @@ use crate::drive::commands::ManagerCommand;
 use crate::drive::manager::{DriveManager, DriveStatusUI, SyncStatus};
 use crate::shellext::vector::create_vector;
 use crate::utils::app::{AppRoot, get_app_root};
+use crate::utils::bidi::isolate;
+use chrono::{DateTime, Local};
 use std::sync::Arc;
 use windows::Foundation::{EventRegistrationToken, TypedEventHandler, Uri};
 use windows::{
@@ -25,6 +27,8 @@ pub enum StatusUIAction {
     OpenStorageDetails { syncroot_id: String },
     /// Open settings window
     OpenSettings,
+    /// Sign in again to the drive, after its credential expired
+    SignIn { syncroot_id: String },
 }
 
 #[implement(IStorageProviderUICommand)]
@@ -91,6 +95,10 @@ impl IStorageProviderUICommand_Impl for SyncStatusUICommand_Impl {
                 tracing::debug!(target: "shellext::status_ui", "OpenSettings action - opening settings window");
                 ManagerCommand::OpenSettingsWindow
             }
+            StatusUIAction::SignIn { syncroot_id } => {
+                tracing::debug!(target: "shellext::status_ui", syncroot_id = %syncroot_id, "SignIn action");
+                ManagerCommand::OpenReauthorizeWindow { syncroot_id: syncroot_id.clone() }
+            }
         };
 
         if let Err(e) = self.command_tx.send(command) {
@@ -180,12 +188,26 @@ impl IStorageProviderStatusUISource_Impl for StatusUIHandler_Impl {
                         t!("paused").to_string(),
                         t!("pausedDescription").to_string(),
                     ),
-                    SyncStatus::Error => (
+                    SyncStatus::Error { ref message } => (
                         StorageProviderState::Error,
                         status.name.clone(),
                         format!("{}\\CloudIconError.svg", image_path),
                         t!("error").to_string(),
-                        t!("errorDescription").to_string(),
+                        t!("errorMessageDescription", "message" => isolate(message)).to_string(),
+                    ),
+                    SyncStatus::CredentialExpired => (
+                        StorageProviderState::Warning,
+                        status.name.clone(),
+                        format!("{}\\CloudIconError.svg", image_path),
+                        t!("credentialExpired").to_string(),
+                        t!("credentialExpiredDescription").to_string(),
+                    ),
+                    SyncStatus::Offline => (
+                        StorageProviderState::Offline,
+                        status.name.clone(),
+                        format!("{}\\CloudIconOffline.svg", image_path),
+                        t!("offline").to_string(),
+                        t!("offlineDescription").to_string(),
                     ),
                 }
             }
@@ -198,6 +220,20 @@ impl IStorageProviderStatusUISource_Impl for StatusUIHandler_Impl {
             ),
         };
 
+        // Tell when the drive last synced, unless it is syncing right now
+        let sync_description = match &drive_status {
+            Some(status) if !matches!(status.sync_status, SyncStatus::Syncing) => {
+                match status.last_synced_at.and_then(format_last_synced) {
+                    Some(time) => {
+                        let last_synced = t!("lastSynced", "time" => isolate(time));
+                        format!("{} {}", sync_description, last_synced)
+                    }
+                    None => sync_description,
+                }
+            }
+            _ => sync_description,
+        };
+
         ui.SetProviderState(provider_state)?;
 
         ui.SetProviderStateLabel(&HSTRING::from("Cloudreve"))?;
@@ -218,13 +254,25 @@ impl IStorageProviderStatusUISource_Impl for StatusUIHandler_Impl {
         .into();
         ui.SetSyncStatusCommand(&sync_command)?;
 
-        // Set primary command (capacity details) - only if capacity is available
+        // Set primary command - signing in again when the credential expired, otherwise
+        // capacity details if available
         if let Some(ref status) = drive_status {
-            if let Some(ref capacity) = status.capacity {
+            if status.sign_in_url.is_some() {
+                let primary_command: IStorageProviderUICommand = SyncStatusUICommand::new(
+                    self.app_root.clone(),
+                    HSTRING::from(t!("signIn").to_string()),
+                    HSTRING::from(t!("credentialExpiredDescription").to_string()),
+                    Uri::CreateUri(&HSTRING::from(format!("{}\\ProfileIcon.svg", image_path)))?,
+                    StatusUIAction::SignIn { syncroot_id: self.syncroot_id.clone() },
+                    command_tx.clone(),
+                )
+                .into();
+                ui.SetProviderPrimaryCommand(&primary_command)?;
+            } else if let Some(ref capacity) = status.capacity {
                 let primary_command: IStorageProviderUICommand = SyncStatusUICommand::new(
                     self.app_root.clone(),
                     HSTRING::from(t!("capacityDetails").to_string()),
-                    HSTRING::from(&capacity.label),
+                    HSTRING::from(isolate(&capacity.label)),
                     Uri::CreateUri(&HSTRING::from(format!(
                         "{}\\CloudIconSynced.svg",
                         image_path
@@ -272,7 +320,7 @@ impl IStorageProviderStatusUISource_Impl for StatusUIHandler_Impl {
                 let quota_ui = StorageProviderQuotaUI::new()?;
                 quota_ui.SetQuotaUsedInBytes(capacity.used as u64)?;
                 quota_ui.SetQuotaTotalInBytes(capacity.total as u64)?;
-                quota_ui.SetQuotaUsedLabel(&HSTRING::from(&capacity.label))?;
+                quota_ui.SetQuotaUsedLabel(&HSTRING::from(isolate(&capacity.label)))?;
                 ui.SetQuotaUI(&quota_ui)?;
             }
         }
@@ -303,6 +351,15 @@ impl IStorageProviderStatusUISource_Impl for StatusUIHandler_Impl {
     }
 }
 
+/// Local date and time of a Unix timestamp, for the status descriptions
+fn format_last_synced(timestamp: i64) -> Option<String> {
+    DateTime::from_timestamp(timestamp, 0).map(|time| {
+        time.with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    })
+}
+
 #[implement(IStorageProviderStatusUISourceFactory)]
 pub struct StatusUIHandlerFactory {
     drive_manager: Arc<DriveManager>,
@@ -1,6 +1,5 @@
 use crate::drive::commands::{ConflictAction, ManagerCommand};
 use crate::drive::manager::DriveManager;
-use crate::inventory::InventoryDb;
 use crate::utils::app::{AppRoot, get_app_root};
 use std::collections::HashMap;
 use base64::{Engine as _, engine::general_purpose::URL_SAFE};
@@ -60,18 +59,14 @@ pub struct ToastInputData {
 pub struct ToastActivator {
     drive_manager: Arc<DriveManager>,
      #[allow(dead_code)]
-    inventory: Arc<InventoryDb>,
-     #[allow(dead_code)]
     app_root: AppRoot,
 }
 
 impl ToastActivator {
     pub fn new(drive_manager: Arc<DriveManager>) -> Self {
-        let inventory = drive_manager.get_inventory();
         Self {
             drive_manager,
             app_root: get_app_root(),
-            inventory,
         }
     }
 
@@ -139,6 +134,15 @@ impl ToastActivator {
         }
     }
 
+    /// Handle the restart action of the update toast
+    fn handle_restart_update_action(&self) {
+        tracing::debug!("Restarting to update from toast");
+        let command_tx = self.drive_manager.get_command_sender();
+        if let Err(e) = command_tx.send(ManagerCommand::RestartToUpdate) {
+            tracing::error!(error = ?e, "Failed to send RestartToUpdate command");
+        }
+    }
+
     /// Handle the dismiss action
     fn handle_dismiss_action(&self, params: &HashMap<String, String>) {
         tracing::debug!(?params, "Toast dismissed by user");
@@ -213,6 +217,9 @@ impl INotificationActivationCallback_Impl for ToastActivator_Impl {
                 // Open settings window
                 self.handle_settings_action(&toast_action.params);
             }
+            "restart_update" => {
+                self.handle_restart_update_action();
+            }
             "" => {
                 // Empty action - foreground activation (user clicked on toast body)
                 self.handle_foreground_activation(&toast_action.params);
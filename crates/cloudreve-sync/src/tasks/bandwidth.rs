@@ -0,0 +1,201 @@
+//! Bandwidth limits of uploads and background downloads, following the bandwidth
+//! settings. Transfers take their bytes from token buckets: one shared by all drives
+//! and one for each drive with limits of its own. Files hydrated because they were
+//! opened are not limited, as the user is waiting for them.
+
+use crate::config::{BandwidthConfig, BandwidthLimits, ConfigManager};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+use windows::Networking::Connectivity::{NetworkCostType, NetworkInformation};
+
+/// How long the metered state of the connection is trusted before it is checked again
+const METERED_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    Upload,
+    Download,
+}
+
+/// Token bucket limiting a transfer direction to a rate in bytes per second
+struct RateLimiter {
+    rate: f64,
+    /// Bytes that may be sent right away, negative while senders are waiting
+    available: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    fn new(kbps: u32, now: Instant) -> Self {
+        let rate = kbps as f64 * 1024.0;
+        Self {
+            rate,
+            available: rate,
+            updated: now,
+        }
+    }
+
+    /// Take `bytes` from the bucket, returning how long to wait before sending them
+    fn reserve(&mut self, bytes: usize, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        // At most one second of unused bandwidth is saved up
+        self.available = (self.available + elapsed * self.rate).min(self.rate);
+        self.updated = now;
+        self.available -= bytes as f64;
+        if self.available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available / self.rate)
+        }
+    }
+}
+
+/// The buckets of one set of limits
+struct LimitBuckets {
+    limit_on_metered: bool,
+    upload: Option<RateLimiter>,
+    download: Option<RateLimiter>,
+}
+
+impl LimitBuckets {
+    fn new(limits: &BandwidthLimits, now: Instant) -> Self {
+        Self {
+            limit_on_metered: limits.limit_on_metered,
+            upload: limits.upload_kbps.map(|kbps| RateLimiter::new(kbps, now)),
+            download: limits.download_kbps.map(|kbps| RateLimiter::new(kbps, now)),
+        }
+    }
+
+    fn reserve(
+        &mut self,
+        direction: TransferDirection,
+        bytes: usize,
+        now: Instant,
+        is_metered: &dyn Fn() -> bool,
+    ) -> Duration {
+        let bucket = match direction {
+            TransferDirection::Upload => self.upload.as_mut(),
+            TransferDirection::Download => self.download.as_mut(),
+        };
+        match bucket {
+            Some(bucket) if !self.limit_on_metered || is_metered() => bucket.reserve(bytes, now),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Bandwidth limits of all transfers
+pub struct TransferLimits {
+    global: Mutex<LimitBuckets>,
+    drives: Mutex<HashMap<String, LimitBuckets>>,
+    /// Whether the connection is metered, and when that was checked
+    metered: Mutex<Option<(bool, Instant)>>,
+}
+
+impl TransferLimits {
+    /// The limits of the bandwidth settings
+    pub fn get() -> &'static TransferLimits {
+        static LIMITS: OnceLock<TransferLimits> = OnceLock::new();
+        LIMITS.get_or_init(|| {
+            let config = ConfigManager::try_get()
+                .map(|config| config.bandwidth())
+                .unwrap_or_default();
+            TransferLimits::new(&config)
+        })
+    }
+
+    fn new(config: &BandwidthConfig) -> Self {
+        let limits = Self {
+            global: Mutex::new(LimitBuckets::new(&config.global, Instant::now())),
+            drives: Mutex::new(HashMap::new()),
+            metered: Mutex::new(None),
+        };
+        limits.apply(config);
+        limits
+    }
+
+    /// Replace the limits after the bandwidth settings changed
+    pub fn apply(&self, config: &BandwidthConfig) {
+        let now = Instant::now();
+        *lock(&self.global) = LimitBuckets::new(&config.global, now);
+        *lock(&self.drives) = config
+            .drives
+            .iter()
+            .map(|(drive_id, limits)| (drive_id.clone(), LimitBuckets::new(limits, now)))
+            .collect();
+    }
+
+    /// Take `bytes` sent in `direction` by drive `drive_id` from the buckets, returning
+    /// how long to wait before sending them
+    pub fn reserve(&self, drive_id: &str, direction: TransferDirection, bytes: usize) -> Duration {
+        let now = Instant::now();
+        let is_metered = || self.is_metered(now);
+        let global = lock(&self.global).reserve(direction, bytes, now, &is_metered);
+        let drive = lock(&self.drives)
+            .get_mut(drive_id)
+            .map(|buckets| buckets.reserve(direction, bytes, now, &is_metered))
+            .unwrap_or_default();
+        global.max(drive)
+    }
+
+    /// Wait until `bytes` sent in `direction` by drive `drive_id` fit the limits
+    pub async fn acquire(&self, drive_id: &str, direction: TransferDirection, bytes: usize) {
+        let delay = self.reserve(drive_id, direction, bytes);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    fn is_metered(&self, now: Instant) -> bool {
+        let mut metered = lock(&self.metered);
+        match *metered {
+            Some((value, checked)) if now.duration_since(checked) < METERED_CHECK_INTERVAL => value,
+            _ => {
+                let value = connection_is_metered();
+                *metered = Some((value, now));
+                value
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Whether the internet connection is charged by the amount of data, `false` without
+/// a connection
+fn connection_is_metered() -> bool {
+    let cost_type = NetworkInformation::GetInternetConnectionProfile()
+        .and_then(|profile| profile.GetConnectionCost())
+        .and_then(|cost| cost.NetworkCostType());
+    matches!(
+        cost_type,
+        Ok(NetworkCostType::Fixed) | Ok(NetworkCostType::Variable)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiter_paces_to_its_rate() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(100, start);
+
+        // One second of bandwidth is available right away
+        assert_eq!(limiter.reserve(100 * 1024, start), Duration::ZERO);
+        assert_eq!(
+            limiter.reserve(50 * 1024, start),
+            Duration::from_millis(500)
+        );
+        // Waiting senders queue up behind each other
+        assert_eq!(limiter.reserve(50 * 1024, start), Duration::from_secs(1));
+        // Time passing pays the debt back, but saves up at most one second
+        let later = start + Duration::from_secs(60);
+        assert_eq!(limiter.reserve(100 * 1024, later), Duration::ZERO);
+        assert_eq!(limiter.reserve(1024, later), Duration::from_millis(10));
+    }
+}
@@ -8,6 +8,7 @@
 //! - Only operates on hydrated placeholder files
 
 use std::{
+    ops::Range,
     path::PathBuf,
     str::FromStr,
     sync::{
@@ -27,10 +28,11 @@ use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 use crate::{
-    cfapi::placeholder::LocalFileInfo,
+    cfapi::{filter::ticket, placeholder::LocalFileInfo},
+    config::ProxyConfig,
     drive::{placeholder::CrPlaceholder, utils::local_path_to_cr_uri},
     inventory::{FileMetadata, InventoryDb},
-    tasks::queue::QueuedTask,
+    tasks::{TransferDirection, TransferLimits, queue::QueuedTask},
 };
 
 use super::types::TaskProgress;
@@ -39,7 +41,7 @@ use super::types::TaskProgress;
 /// Uses atomic counters for thread-safe byte tracking and sliding window for speed calculation.
 pub struct DownloadProgressTracker {
     /// Total file size in bytes
-    total_size: u64,
+    total_size: AtomicU64,
     /// Downloaded bytes (atomic for thread safety)
     downloaded_bytes: AtomicU64,
     /// Speed calculation samples
@@ -51,13 +53,18 @@ pub struct DownloadProgressTracker {
 impl DownloadProgressTracker {
     pub fn new(total_size: u64) -> Self {
         Self {
-            total_size,
+            total_size: AtomicU64::new(total_size),
             downloaded_bytes: AtomicU64::new(0),
             samples: std::sync::Mutex::new(Vec::with_capacity(32)),
             window_duration: Duration::from_secs(10),
         }
     }
 
+    /// Grow the total by `bytes`, e.g. when another range of the file is requested
+    pub fn add_total(&self, bytes: u64) {
+        self.total_size.fetch_add(bytes, Ordering::SeqCst);
+    }
+
     /// Add downloaded bytes
     pub fn add_bytes(&self, bytes: u64) {
         self.downloaded_bytes.fetch_add(bytes, Ordering::SeqCst);
@@ -71,6 +78,7 @@ impl DownloadProgressTracker {
     /// Calculate current speed and return progress update
     pub fn create_update(&self) -> DownloadProgressUpdate {
         let downloaded = self.downloaded();
+        let total_size = self.total_size.load(Ordering::SeqCst);
         let now = Instant::now();
 
         // Calculate speed using sliding window
@@ -96,20 +104,20 @@ impl DownloadProgressTracker {
             }
         };
 
-        let progress = if self.total_size > 0 {
-            (downloaded as f64 / self.total_size as f64).clamp(0.0, 1.0)
+        let progress = if total_size > 0 {
+            (downloaded as f64 / total_size as f64).clamp(0.0, 1.0)
         } else {
             1.0
         };
 
-        let eta_seconds = if speed > 0 && downloaded < self.total_size {
-            Some((self.total_size - downloaded) / speed)
+        let eta_seconds = if speed > 0 && downloaded < total_size {
+            Some((total_size - downloaded) / speed)
         } else {
             None
         };
 
         DownloadProgressUpdate {
-            total_size: self.total_size,
+            total_size,
             downloaded,
             progress,
             speed_bytes_per_sec: speed,
@@ -159,6 +167,64 @@ impl InMemoryDownloadProgressReporter {
     }
 }
 
+/// Minimum interval between progress reports to the platform
+const PLACEHOLDER_REPORT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Reports hydration progress through a fetch ticket, which drives the progress bar
+/// next to the file and Explorer's copy dialog.
+///
+/// The platform expects progress for the whole file, so bytes are counted from the
+/// start of the requested range.
+pub struct PlaceholderProgressReporter<'a> {
+    ticket: &'a ticket::FetchData,
+    offset: u64,
+    file_size: u64,
+    tracker: DownloadProgressTracker,
+    last_report: Option<Instant>,
+}
+
+impl<'a> PlaceholderProgressReporter<'a> {
+    pub fn new(ticket: &'a ticket::FetchData, range: &Range<u64>, file_size: u64) -> Self {
+        Self {
+            ticket,
+            offset: range.start,
+            file_size: file_size.max(range.end),
+            tracker: DownloadProgressTracker::new(range.end - range.start),
+            last_report: None,
+        }
+    }
+
+    /// Report bytes written through the ticket
+    pub fn add_bytes(&mut self, bytes: u64) {
+        self.tracker.add_bytes(bytes);
+        let due = self
+            .last_report
+            .is_none_or(|last| last.elapsed() >= PLACEHOLDER_REPORT_INTERVAL);
+        if due {
+            self.report();
+        }
+    }
+
+    /// Send the final progress, regardless of the interval
+    pub fn finish(&mut self) {
+        self.report();
+    }
+
+    fn report(&mut self) {
+        let update = self.tracker.create_update();
+        let completed = self.offset + update.downloaded;
+        // Progress is cosmetic, failing to report it must not fail the hydration
+        if let Err(e) = self.ticket.report_progress(self.file_size, completed) {
+            debug!(target: "tasks::download", error = ?e, completed, total = self.file_size, "Failed to report hydration progress");
+        }
+        self.last_report = Some(Instant::now());
+    }
+}
+
+fn temp_download_path(task_id: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("cloudreve_download_{}", task_id))
+}
+
 /// Download task that downloads a file from remote to local placeholder
 pub struct DownloadTask<'a> {
     inventory: Arc<InventoryDb>,
@@ -201,12 +267,23 @@ impl<'a> DownloadTask<'a> {
     }
 
     /// Set the cancellation token
-    #[allow(dead_code)]
     pub fn with_cancel_token(mut self, token: CancellationToken) -> Self {
         self.cancel_token = token;
         self
     }
 
+    /// Remove the partial download of a download stopped midway. The placeholder is
+    /// only replaced once the download is complete, so it needs no rollback.
+    pub async fn cleanup(&mut self) -> Result<()> {
+        let temp_path = temp_download_path(&self.task.task_id);
+        if temp_path.exists() {
+            tokio::fs::remove_file(&temp_path)
+                .await
+                .context("failed to remove partial download")?;
+        }
+        Ok(())
+    }
+
     /// Execute the download task
     pub async fn execute(&mut self) -> Result<()> {
         // Get local file info
@@ -345,9 +422,7 @@ impl<'a> DownloadTask<'a> {
         );
 
         // Create temp file for download
-        let temp_dir = std::env::temp_dir();
-        let temp_file_name = format!("cloudreve_download_{}", self.task.task_id);
-        let temp_path = temp_dir.join(&temp_file_name);
+        let temp_path = temp_download_path(&self.task.task_id);
 
         // Clean up any existing temp file
         if temp_path.exists() {
@@ -408,16 +483,17 @@ impl<'a> DownloadTask<'a> {
         tracker: Arc<DownloadProgressTracker>,
         reporter: &InMemoryDownloadProgressReporter,
     ) -> Result<()> {
-        let client = reqwest::Client::new();
+        let client = ProxyConfig::current()
+            .apply(reqwest::Client::builder())
+            .build()
+            .context("failed to create HTTP client")?;
         let response = client
             .get(url)
             .send()
             .await
-            .context("failed to send download request")?;
-
-        if !response.status().is_success() {
-            anyhow::bail!("Download request failed with status: {}", response.status());
-        }
+            .context("failed to send download request")?
+            .error_for_status()
+            .context("Download request failed")?;
 
         // Create temp file
         let mut file = tokio::fs::File::create(&temp_path)
@@ -436,6 +512,9 @@ impl<'a> DownloadTask<'a> {
             }
 
             let chunk = chunk_result.context("failed to read chunk from stream")?;
+            TransferLimits::get()
+                .acquire(self.drive_id, TransferDirection::Download, chunk.len())
+                .await;
             file.write_all(&chunk)
                 .await
                 .context("failed to write chunk to temp file")?;
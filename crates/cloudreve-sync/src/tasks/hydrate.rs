@@ -0,0 +1,285 @@
+//! Hydration tasks for data the platform requests when a cloud-only file is opened.
+//!
+//! The data is written through the fetch ticket by `Mount::fetch_data`, so these tasks are
+//! not dispatched by the queue. They are recorded and tracked like queued tasks, so they
+//! show up in the transfer list and task history and can be cancelled.
+//!
+//! The platform cancels a fetch when the application waiting on it gives up, e.g. the
+//! user cancels a copy in Explorer. [`FetchCancellations`] routes that to the fetch.
+
+use std::{
+    collections::HashMap,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use tokio_util::sync::CancellationToken;
+
+use crate::inventory::TaskStatus;
+use crate::tasks::download::{DownloadProgressTracker, InMemoryDownloadProgressReporter};
+use crate::tasks::queue::{PREEMPT_PRIORITY, TaskQueue};
+use crate::tasks::types::{TaskKind, TaskPayload};
+
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A hydration task shared by the fetches of one file. The platform may request several
+/// ranges of a file at once; they are recorded as one task.
+struct SharedHydration {
+    task_id: String,
+    cancel_token: CancellationToken,
+    tracker: DownloadProgressTracker,
+    reporter: InMemoryDownloadProgressReporter,
+    /// Fetches of the file that have not finished
+    fetches: usize,
+    /// Error of the first fetch that failed
+    error: Option<String>,
+    last_report: Instant,
+    started: Instant,
+}
+
+/// Hydrations in flight by file, see [`TaskQueue::begin_hydration`]
+#[derive(Default)]
+pub(super) struct ActiveHydrations(Mutex<HashMap<PathBuf, Arc<Mutex<SharedHydration>>>>);
+
+impl ActiveHydrations {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, Arc<Mutex<SharedHydration>>>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A fetch of one range of a file, tracked as part of the file's hydration task, see
+/// [`TaskQueue::begin_hydration`]
+pub struct HydrationTask {
+    queue: Arc<TaskQueue>,
+    local_path: PathBuf,
+    shared: Arc<Mutex<SharedHydration>>,
+    task_id: String,
+    cancel_token: CancellationToken,
+}
+
+impl TaskQueue {
+    /// Record a fetch of `total_bytes` for `local_path` as part of a running hydration
+    /// task. Fetches of a file that is already being hydrated join its task.
+    pub fn begin_hydration(
+        self: &Arc<Self>,
+        local_path: &Path,
+        total_bytes: u64,
+    ) -> Result<HydrationTask> {
+        let mut hydrations = self.hydrations.lock();
+
+        // A cancelled task only waits for its fetches to stop; later fetches start anew
+        let active = hydrations
+            .get(local_path)
+            .filter(|shared| !lock(shared).cancel_token.is_cancelled())
+            .cloned();
+        let shared = match active {
+            Some(shared) => {
+                let mut hydration = lock(&shared);
+                hydration.fetches += 1;
+                hydration.tracker.add_total(total_bytes);
+                hydration
+                    .reporter
+                    .on_progress(&hydration.tracker.create_update());
+                drop(hydration);
+                shared
+            }
+            None => {
+                let payload = TaskPayload::new(TaskKind::Hydrate, local_path)
+                    .with_priority(PREEMPT_PRIORITY)
+                    .with_totals(0, total_bytes as i64);
+                let (task_id, cancel_token) = self.track_external_task(&payload)?;
+                let shared = Arc::new(Mutex::new(SharedHydration {
+                    reporter: InMemoryDownloadProgressReporter::new(
+                        task_id.clone(),
+                        self.progress_map(),
+                    ),
+                    task_id,
+                    cancel_token,
+                    tracker: DownloadProgressTracker::new(total_bytes),
+                    fetches: 1,
+                    error: None,
+                    last_report: Instant::now(),
+                    started: Instant::now(),
+                }));
+                hydrations.insert(local_path.to_path_buf(), Arc::clone(&shared));
+                shared
+            }
+        };
+
+        let (task_id, cancel_token) = {
+            let hydration = lock(&shared);
+            (hydration.task_id.clone(), hydration.cancel_token.clone())
+        };
+        Ok(HydrationTask {
+            queue: Arc::clone(self),
+            local_path: local_path.to_path_buf(),
+            shared,
+            task_id,
+            cancel_token,
+        })
+    }
+}
+
+impl HydrationTask {
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    /// Cancelled when the task is cancelled by the user or by path
+    pub fn cancel_token(&self) -> &CancellationToken {
+        &self.cancel_token
+    }
+
+    /// Report bytes written to the placeholder
+    pub fn add_bytes(&mut self, bytes: u64) {
+        let mut hydration = lock(&self.shared);
+        hydration.tracker.add_bytes(bytes);
+        if hydration.last_report.elapsed() >= REPORT_INTERVAL {
+            hydration
+                .reporter
+                .on_progress(&hydration.tracker.create_update());
+            hydration.last_report = Instant::now();
+        }
+    }
+
+    /// Record the outcome of this fetch. The task finishes with the last fetch of the
+    /// file, failed if any of them failed.
+    pub fn finish(self, result: &Result<()>) {
+        let mut hydrations = self.queue.hydrations.lock();
+        let mut hydration = lock(&self.shared);
+        if hydration.error.is_none() && !self.cancel_token.is_cancelled() {
+            hydration.error = result.as_ref().err().map(|err| format!("{:?}", err));
+        }
+        hydration.fetches -= 1;
+        if hydration.fetches > 0 {
+            return;
+        }
+        if hydrations
+            .get(&self.local_path)
+            .is_some_and(|shared| Arc::ptr_eq(shared, &self.shared))
+        {
+            hydrations.remove(&self.local_path);
+        }
+        drop(hydrations);
+
+        let (status, error) = if self.cancel_token.is_cancelled() {
+            (TaskStatus::Cancelled, None)
+        } else if let Some(error) = hydration.error.take() {
+            (TaskStatus::Failed, Some(error))
+        } else {
+            (TaskStatus::Completed, None)
+        };
+        hydration
+            .reporter
+            .on_progress(&hydration.tracker.create_update());
+        self.queue.finish_external_task(
+            &hydration.task_id,
+            status,
+            error,
+            hydration.started.elapsed(),
+        );
+    }
+}
+
+fn lock(shared: &Mutex<SharedHydration>) -> std::sync::MutexGuard<'_, SharedHydration> {
+    shared.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Fetches in flight by file, with their registration id and range
+type InFlightFetches = HashMap<PathBuf, Vec<(u64, Range<u64>, CancellationToken)>>;
+
+/// Cancellation tokens of the fetches in flight, by file and range
+#[derive(Default)]
+pub struct FetchCancellations {
+    fetches: Mutex<InFlightFetches>,
+    next_id: AtomicU64,
+}
+
+impl FetchCancellations {
+    /// Make `token` cancellable through [`Self::cancel`] until the returned guard drops
+    pub fn register(
+        &self,
+        path: &Path,
+        range: Range<u64>,
+        token: CancellationToken,
+    ) -> FetchRegistration<'_> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.lock()
+            .entry(path.to_path_buf())
+            .or_default()
+            .push((id, range, token));
+        FetchRegistration {
+            registry: self,
+            path: path.to_path_buf(),
+            id,
+        }
+    }
+
+    /// Cancel the fetches of `path` overlapping `range`. Returns the number cancelled.
+    pub fn cancel(&self, path: &Path, range: &Range<u64>) -> usize {
+        let fetches = self.lock();
+        let Some(entries) = fetches.get(path) else {
+            return 0;
+        };
+        let mut cancelled = 0;
+        for (_, fetch_range, token) in entries {
+            if fetch_range.start < range.end && range.start < fetch_range.end {
+                token.cancel();
+                cancelled += 1;
+            }
+        }
+        cancelled
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, InFlightFetches> {
+        self.fetches.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Keeps a fetch registered in [`FetchCancellations`]
+pub struct FetchRegistration<'a> {
+    registry: &'a FetchCancellations,
+    path: PathBuf,
+    id: u64,
+}
+
+impl Drop for FetchRegistration<'_> {
+    fn drop(&mut self) {
+        let mut fetches = self.registry.lock();
+        if let Some(entries) = fetches.get_mut(&self.path) {
+            entries.retain(|(id, _, _)| *id != self.id);
+            if entries.is_empty() {
+                fetches.remove(&self.path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancels_overlapping_fetches() {
+        let registry = FetchCancellations::default();
+        let path = Path::new(r"C:\sync\a.bin");
+        let head = CancellationToken::new();
+        let tail = CancellationToken::new();
+        let _head = registry.register(path, 0..4096, head.clone());
+        let tail_registration = registry.register(path, 4096..8192, tail.clone());
+
+        assert_eq!(registry.cancel(path, &(0..100)), 1);
+        assert!(head.is_cancelled());
+        assert!(!tail.is_cancelled());
+
+        drop(tail_registration);
+        assert_eq!(registry.cancel(path, &(4096..8192)), 0);
+        assert_eq!(registry.cancel(Path::new(r"C:\sync\b.bin"), &(0..100)), 0);
+    }
+}
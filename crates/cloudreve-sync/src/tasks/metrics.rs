@@ -0,0 +1,161 @@
+//! Per-type counters and timings behind [`TaskQueue::statistics`](super::TaskQueue::statistics)
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::inventory::TaskStatus;
+use crate::tasks::types::{TaskKind, TaskStatistics, TaskTypeStatistics};
+
+#[derive(Default)]
+struct KindCounters {
+    queued: usize,
+    completed: u64,
+    failed: u64,
+    timed_out: u64,
+    cancelled: u64,
+    run_time: Duration,
+    started: u64,
+    wait_time: Duration,
+    max_wait: Duration,
+}
+
+pub(super) struct TaskMetrics {
+    since: i64,
+    counters: Mutex<HashMap<TaskKind, KindCounters>>,
+}
+
+impl TaskMetrics {
+    pub fn new() -> Self {
+        Self {
+            since: Utc::now().timestamp(),
+            counters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn update(&self, kind: TaskKind, f: impl FnOnce(&mut KindCounters)) {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        f(counters.entry(kind).or_default());
+    }
+
+    /// A task was handed to a dispatcher
+    pub fn record_queued(&self, kind: TaskKind) {
+        self.update(kind, |c| c.queued += 1);
+    }
+
+    /// A dispatched task was picked up, whether it runs or is skipped
+    pub fn record_dequeued(&self, kind: TaskKind) {
+        self.update(kind, |c| c.queued = c.queued.saturating_sub(1));
+    }
+
+    /// A task started running after waiting `wait` since it was dispatched
+    pub fn record_started(&self, kind: TaskKind, wait: Duration) {
+        self.update(kind, |c| {
+            c.started += 1;
+            c.wait_time += wait;
+            c.max_wait = c.max_wait.max(wait);
+        });
+    }
+
+    /// A task reached a final status after running for `run_time`
+    pub fn record_finished(&self, kind: TaskKind, status: TaskStatus, run_time: Duration) {
+        self.update(kind, |c| {
+            let count = match status {
+                TaskStatus::Completed => &mut c.completed,
+                TaskStatus::Failed => &mut c.failed,
+                TaskStatus::TimedOut => &mut c.timed_out,
+                TaskStatus::Cancelled => &mut c.cancelled,
+                TaskStatus::Pending | TaskStatus::Running => return,
+            };
+            *count += 1;
+            // Cancelled runs are cut short and would skew the average
+            if status != TaskStatus::Cancelled {
+                c.run_time += run_time;
+            }
+        });
+    }
+
+    /// Snapshot with one entry per task type; `running` counts running tasks by type
+    pub fn snapshot(&self, drive_id: &str, running: &HashMap<TaskKind, usize>) -> TaskStatistics {
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let empty = KindCounters::default();
+        let types = TaskKind::ALL
+            .iter()
+            .map(|kind| {
+                let c = counters.get(kind).unwrap_or(&empty);
+                let finished = c.completed + c.failed + c.timed_out;
+                let unsuccessful = c.failed + c.timed_out;
+                TaskTypeStatistics {
+                    kind: *kind,
+                    queued: c.queued,
+                    running: running.get(kind).copied().unwrap_or_default(),
+                    completed: c.completed,
+                    failed: c.failed,
+                    timed_out: c.timed_out,
+                    cancelled: c.cancelled,
+                    failure_rate: if finished > 0 {
+                        unsuccessful as f64 / finished as f64
+                    } else {
+                        0.0
+                    },
+                    avg_duration_ms: average_ms(c.run_time, finished),
+                    avg_wait_ms: average_ms(c.wait_time, c.started),
+                    max_wait_ms: c.max_wait.as_millis() as u64,
+                }
+            })
+            .collect();
+
+        TaskStatistics {
+            drive_id: drive_id.to_string(),
+            since: self.since,
+            types,
+        }
+    }
+}
+
+fn average_ms(total: Duration, count: u64) -> u64 {
+    if count == 0 {
+        return 0;
+    }
+    (total.as_millis() / count as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_covers_every_type() {
+        let metrics = TaskMetrics::new();
+        metrics.record_queued(TaskKind::Upload);
+        metrics.record_queued(TaskKind::Upload);
+        metrics.record_dequeued(TaskKind::Upload);
+        metrics.record_started(TaskKind::Upload, Duration::from_millis(100));
+        metrics.record_finished(
+            TaskKind::Upload,
+            TaskStatus::Completed,
+            Duration::from_millis(300),
+        );
+        metrics.record_finished(
+            TaskKind::Upload,
+            TaskStatus::Failed,
+            Duration::from_millis(100),
+        );
+        metrics.record_finished(TaskKind::Upload, TaskStatus::Cancelled, Duration::ZERO);
+
+        let running = HashMap::from([(TaskKind::Download, 2)]);
+        let stats = metrics.snapshot("drive", &running);
+        assert_eq!(stats.types.len(), TaskKind::ALL.len());
+
+        let upload = &stats.types[0];
+        assert_eq!(upload.queued, 1);
+        assert_eq!(upload.cancelled, 1);
+        assert_eq!(upload.failure_rate, 0.5);
+        assert_eq!(upload.avg_duration_ms, 200);
+        assert_eq!(upload.avg_wait_ms, 100);
+        assert_eq!(stats.types[1].running, 2);
+        assert_eq!(stats.types[2].completed, 0);
+    }
+}
@@ -1,7 +1,18 @@
+mod bandwidth;
 mod download;
+mod hydrate;
+mod metrics;
 mod queue;
+pub(crate) mod retry;
 mod types;
 mod upload;
 
-pub use queue::{TaskQueue, TaskQueueConfig};
-pub use types::{TaskKind, TaskPayload, TaskProgress};
+pub use bandwidth::{TransferDirection, TransferLimits};
+pub use download::PlaceholderProgressReporter;
+pub use hydrate::{FetchCancellations, FetchRegistration, HydrationTask};
+pub use queue::{InteractiveGuard, PREEMPT_PRIORITY, TaskQueue, TaskQueueConfig};
+pub use retry::RetryPolicy;
+pub use types::{
+    TaskKind, TaskPayload, TaskProgress, TaskQueueStats, TaskStatistics, TaskTypeStatistics,
+    Transfer,
+};
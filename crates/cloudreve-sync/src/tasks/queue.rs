@@ -1,30 +1,93 @@
-use crate::inventory::{InventoryDb, NewTaskRecord, TaskRecord, TaskStatus, TaskUpdate};
+use crate::drive::utils::refresh_shell_state;
+use crate::inventory::{
+    ChangeSource, InventoryDb, NewTaskRecord, TaskRecord, TaskStatus, TaskUpdate,
+};
 use crate::tasks::download::DownloadTask;
-use crate::tasks::types::{TaskKind, TaskPayload, TaskProgress};
+use crate::tasks::hydrate::ActiveHydrations;
+use crate::tasks::metrics::TaskMetrics;
+use crate::tasks::retry::{self, RetryPolicy};
+use crate::tasks::types::{
+    TaskKind, TaskPayload, TaskProgress, TaskQueueStats, TaskStatistics, Transfer,
+};
 use crate::tasks::upload::UploadTask;
 use anyhow::{Context, Result, anyhow};
 use cloudreve_api::Client;
 use dashmap::DashMap;
 use serde_json::Value;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::{
-    Mutex, Notify, Semaphore,
+    Mutex, Notify, OwnedSemaphorePermit, Semaphore,
     mpsc::{self, UnboundedReceiver, UnboundedSender},
 };
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+/// How often progress of running tasks is written back to the inventory
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often live progress of running tasks is broadcast as events
+const PROGRESS_EVENT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often the worker pool is resized
+const SCALE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Scaler ticks tasks must keep waiting before a worker is added. Throughput is compared
+/// over windows of this many ticks.
+const GROW_AFTER_TICKS: u32 = 3;
+
+/// Scaler ticks workers must keep sitting idle before one is removed
+const SHRINK_AFTER_TICKS: u32 = 5;
+
+/// How long a timed out task gets to stop on its own before it is dropped
+const TIMEOUT_GRACE: Duration = Duration::from_secs(10);
+
+/// Tasks at or above this priority are interactive: they start right away and pause
+/// running tasks below it until they are done
+pub const PREEMPT_PRIORITY: i32 = 100;
+
 #[derive(Debug, Clone)]
 pub struct TaskQueueConfig {
+    /// Workers kept when the queue is idle
+    pub min_concurrent: usize,
+    /// Workers the pool grows to under a deep queue
     pub max_concurrent: usize,
+    /// Cap on running tasks of a type, on top of the worker pool. Types without an
+    /// entry are only limited by the pool.
+    pub type_limits: HashMap<TaskKind, usize>,
+    /// Longest a task of a type may run before it is stopped and marked timed out.
+    /// Types without an entry run for as long as they need.
+    pub timeouts: HashMap<TaskKind, Duration>,
+    pub retry_policies: HashMap<TaskKind, RetryPolicy>,
+}
+
+impl TaskQueueConfig {
+    /// Retry policy for a task type, falling back to the built-in one
+    pub fn retry_policy(&self, kind: TaskKind) -> RetryPolicy {
+        self.retry_policies
+            .get(&kind)
+            .cloned()
+            .unwrap_or_else(|| RetryPolicy::default_for(kind))
+    }
 }
 
 impl Default for TaskQueueConfig {
     fn default() -> Self {
-        Self { max_concurrent: 2 }
+        Self {
+            min_concurrent: 2,
+            max_concurrent: 2,
+            type_limits: HashMap::new(),
+            timeouts: HashMap::new(),
+            retry_policies: [TaskKind::Upload, TaskKind::Download]
+                .into_iter()
+                .map(|kind| (kind, RetryPolicy::default_for(kind)))
+                .collect(),
+        }
     }
 }
 
@@ -35,9 +98,23 @@ pub struct TaskQueue {
     pub sync_path: PathBuf,
     pub remote_base: String,
     config: TaskQueueConfig,
+    /// Sized for `max_concurrent`; permits above the current worker count are parked
     semaphore: Arc<Semaphore>,
+    parked_permits: std::sync::Mutex<Vec<OwnedSemaphorePermit>>,
+    /// One semaphore per task type with a limit in `config.type_limits`
+    type_semaphores: std::sync::Mutex<HashMap<TaskKind, Arc<Semaphore>>>,
+    workers: AtomicUsize,
+    /// Tasks dispatched to the regular channel and not yet started
+    queued: AtomicUsize,
+    /// Tasks finished since the last scaling decision
+    completed: AtomicUsize,
+    scaler_handle: Mutex<Option<JoinHandle<()>>>,
     command_tx: UnboundedSender<QueueCommand>,
     dispatcher_handle: Mutex<Option<JoinHandle<()>>>,
+    interactive_tx: UnboundedSender<QueuedTask>,
+    interactive_handle: Mutex<Option<JoinHandle<()>>>,
+    checkpoint_handle: Mutex<Option<JoinHandle<()>>>,
+    progress_events_handle: Mutex<Option<JoinHandle<()>>>,
     inflight: AtomicUsize,
     idle_notify: Notify,
     shutting_down: AtomicBool,
@@ -46,6 +123,17 @@ pub struct TaskQueue {
     task_handles: DashMap<String, JoinHandle<()>>,
     /// Maps task_id to local_path for running tasks, used for path-based cancellation
     task_paths: DashMap<String, String>,
+    /// Number of interactive tasks and [`InteractiveGuard`]s alive, plus one while
+    /// paused by [`TaskQueue::pause_tasks`]
+    interactive_active: AtomicUsize,
+    user_paused: AtomicBool,
+    interactive_done: Notify,
+    /// Maps task_id to the priority and preemption token of running tasks
+    preempt_tokens: DashMap<String, (i32, CancellationToken)>,
+    /// Tasks paused for interactive work, dispatched again once it is done
+    paused: std::sync::Mutex<Vec<QueuedTask>>,
+    hydrations: ActiveHydrations,
+    metrics: TaskMetrics,
 }
 
 impl TaskQueue {
@@ -59,9 +147,22 @@ impl TaskQueue {
     ) -> Arc<Self> {
         let drive_id = drive_id.into();
         let max_concurrent = config.max_concurrent.max(1);
-        let sanitized_config = TaskQueueConfig { max_concurrent };
+        let min_concurrent = config.min_concurrent.clamp(1, max_concurrent);
+        let sanitized_config = TaskQueueConfig {
+            min_concurrent,
+            max_concurrent,
+            ..config
+        };
+
+        // Start with the minimum pool and park the remaining permits
+        let semaphore = Arc::new(Semaphore::new(max_concurrent));
+        let parked_permits = (min_concurrent..max_concurrent)
+            .filter_map(|_| semaphore.clone().try_acquire_owned().ok())
+            .collect();
+        let type_semaphores = type_semaphores(&sanitized_config.type_limits);
 
         let (command_tx, command_rx) = mpsc::unbounded_channel();
+        let (interactive_tx, interactive_rx) = mpsc::unbounded_channel();
         let queue = Arc::new(Self {
             drive_id,
             inventory,
@@ -69,9 +170,19 @@ impl TaskQueue {
             sync_path,
             remote_base,
             config: sanitized_config,
-            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            semaphore,
+            parked_permits: std::sync::Mutex::new(parked_permits),
+            type_semaphores: std::sync::Mutex::new(type_semaphores),
+            workers: AtomicUsize::new(min_concurrent),
+            queued: AtomicUsize::new(0),
+            completed: AtomicUsize::new(0),
+            scaler_handle: Mutex::new(None),
             command_tx,
             dispatcher_handle: Mutex::new(None),
+            interactive_tx,
+            interactive_handle: Mutex::new(None),
+            checkpoint_handle: Mutex::new(None),
+            progress_events_handle: Mutex::new(None),
             inflight: AtomicUsize::new(0),
             idle_notify: Notify::new(),
             shutting_down: AtomicBool::new(false),
@@ -79,24 +190,84 @@ impl TaskQueue {
             progress: Arc::new(DashMap::new()),
             task_handles: DashMap::new(),
             task_paths: DashMap::new(),
+            interactive_active: AtomicUsize::new(0),
+            user_paused: AtomicBool::new(false),
+            interactive_done: Notify::new(),
+            preempt_tokens: DashMap::new(),
+            paused: std::sync::Mutex::new(Vec::new()),
+            hydrations: ActiveHydrations::default(),
+            metrics: TaskMetrics::new(),
         });
 
         queue.spawn_dispatcher(command_rx).await;
-        if let Err(err) = queue.resume_incomplete_tasks().await {
+        queue.spawn_interactive_dispatcher(interactive_rx).await;
+        queue.spawn_checkpoint_loop().await;
+        queue.spawn_progress_events().await;
+        queue.spawn_scaler().await;
+        queue
+    }
+
+    /// Re-dispatch tasks left pending or running by a previous session. Call this once
+    /// the sync root is connected, since resumed tasks touch placeholders right away.
+    pub async fn resume(self: &Arc<Self>) {
+        if let Err(err) = self.resume_incomplete_tasks().await {
             warn!(
                 target: "tasks::queue",
-                drive = %queue.drive_id,
+                drive = %self.drive_id,
                 error = %err,
                 "Failed to resume pending tasks from inventory"
             );
         }
-        queue
+    }
+
+    /// Replace the per-type limits. Running tasks keep the slot they took under the
+    /// previous limits.
+    pub fn set_type_limits(&self, limits: &HashMap<TaskKind, usize>) {
+        *self
+            .type_semaphores
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = type_semaphores(limits);
+        info!(
+            target: "tasks::queue",
+            drive = %self.drive_id,
+            limits = ?limits,
+            "Updated task type limits"
+        );
+    }
+
+    fn type_semaphore(&self, kind: TaskKind) -> Option<Arc<Semaphore>> {
+        self.type_semaphores
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&kind)
+            .cloned()
     }
 
     pub fn max_concurrent(&self) -> usize {
         self.config.max_concurrent
     }
 
+    /// Current size of the worker pool and queue
+    pub fn stats(&self) -> TaskQueueStats {
+        TaskQueueStats {
+            drive_id: self.drive_id.clone(),
+            workers: self.workers.load(Ordering::SeqCst),
+            min_workers: self.config.min_concurrent,
+            max_workers: self.config.max_concurrent,
+            running: self.inflight.load(Ordering::SeqCst),
+            queued: self.queued.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Per-type task counts, failure rate, run and queue wait times since the queue started
+    pub fn statistics(&self) -> TaskStatistics {
+        let mut running: HashMap<TaskKind, usize> = HashMap::new();
+        for entry in self.progress.iter() {
+            *running.entry(entry.value().kind).or_default() += 1;
+        }
+        self.metrics.snapshot(&self.drive_id, &running)
+    }
+
     pub fn drive_id(&self) -> &str {
         &self.drive_id
     }
@@ -117,7 +288,8 @@ impl TaskQueue {
             payload.kind.as_str().to_string(),
             payload.local_path_display(),
         )
-        .with_priority(payload.priority);
+        .with_priority(payload.priority)
+        .with_force_override(payload.force_override);
 
         match (payload.total_bytes, payload.processed_bytes) {
             (Some(total), Some(processed)) => {
@@ -161,6 +333,46 @@ impl TaskQueue {
         )
     }
 
+    /// Pending and running tasks with their live progress, oldest first
+    pub fn transfers(&self) -> Result<Vec<Transfer>> {
+        let paused = self.is_paused();
+        let transfers = self
+            .list_active_tasks()?
+            .into_iter()
+            .filter_map(|record| {
+                let kind = TaskKind::from_str(&record.task_type)?;
+                let live = self
+                    .progress
+                    .get(&record.id)
+                    .map(|entry| entry.value().clone());
+                let pausable = record.priority < PREEMPT_PRIORITY;
+                Some(Transfer {
+                    drive_id: record.drive_id,
+                    kind,
+                    local_path: record.local_path,
+                    status: record.status,
+                    progress: live.as_ref().map_or(record.progress, |p| p.progress),
+                    processed_bytes: live
+                        .as_ref()
+                        .and_then(|p| p.processed_bytes)
+                        .unwrap_or(record.processed_bytes),
+                    total_bytes: live
+                        .as_ref()
+                        .and_then(|p| p.total_bytes)
+                        .unwrap_or(record.total_bytes),
+                    speed_bytes_per_sec: live.as_ref().map_or(0, |p| p.speed_bytes_per_sec),
+                    eta_seconds: live.as_ref().and_then(|p| p.eta_seconds),
+                    paused: paused && pausable,
+                    pausable,
+                    cancelable: record.status.is_active(),
+                    created_at: record.created_at,
+                    task_id: record.id,
+                })
+            })
+            .collect();
+        Ok(transfers)
+    }
+
     pub async fn ongoing_progress(&self) -> Vec<TaskProgress> {
         self.progress
             .iter()
@@ -168,14 +380,259 @@ impl TaskQueue {
             .collect()
     }
 
+    /// Record a task that runs outside the queue, such as a hydration, as running and
+    /// register it for progress reporting and cancellation
+    pub(super) fn track_external_task(
+        &self,
+        payload: &TaskPayload,
+    ) -> Result<(String, CancellationToken)> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(anyhow!("task queue is shutting down"));
+        }
+
+        let task_id = Uuid::new_v4().to_string();
+        let record = NewTaskRecord::new(
+            task_id.clone(),
+            self.drive_id.clone(),
+            payload.kind.as_str().to_string(),
+            payload.local_path_display(),
+        )
+        .with_status(TaskStatus::Running)
+        .with_priority(payload.priority)
+        .with_totals(
+            payload.total_bytes.unwrap_or_default(),
+            payload.processed_bytes.unwrap_or_default(),
+        );
+
+        let inserted = self
+            .inventory
+            .insert_task_if_not_exist(&record)
+            .with_context(|| format!("Failed to persist task {}", task_id))?;
+        if !inserted {
+            return Err(anyhow!(
+                "Task already exists for {} with type {}",
+                payload.local_path_display(),
+                payload.kind.as_str()
+            ));
+        }
+
+        let token = CancellationToken::new();
+        self.metrics.record_started(payload.kind, Duration::ZERO);
+        self.progress.insert(
+            task_id.clone(),
+            TaskProgress::from_payload(&task_id, payload),
+        );
+        self.task_paths
+            .insert(task_id.clone(), payload.local_path_display());
+        self.preempt_tokens
+            .insert(task_id.clone(), (payload.priority, token.clone()));
+        Ok((task_id, token))
+    }
+
+    /// Record the outcome of a task started with [`Self::track_external_task`]
+    pub(super) fn finish_external_task(
+        &self,
+        task_id: &str,
+        status: TaskStatus,
+        error: Option<String>,
+        run_time: Duration,
+    ) {
+        // A cancelled task is already marked by `cancel_task` or `cancel_by_path`
+        if status != TaskStatus::Cancelled {
+            let progress = self
+                .progress
+                .get(task_id)
+                .map(|entry| entry.value().clone());
+            if let Err(err) = self.inventory.update_task(
+                task_id,
+                TaskUpdate {
+                    status: Some(status),
+                    progress: progress.as_ref().map(|p| p.progress),
+                    processed_bytes: progress.as_ref().and_then(|p| p.processed_bytes),
+                    error: error.map(Some),
+                    ..Default::default()
+                },
+            ) {
+                warn!(
+                    target: "tasks::queue",
+                    drive = %self.drive_id,
+                    task_id = %task_id,
+                    error = %err,
+                    "Failed to record task result"
+                );
+            }
+        }
+
+        if let Some((_, progress)) = self.progress.remove(task_id) {
+            self.metrics
+                .record_finished(progress.kind, status, run_time);
+        }
+        self.task_paths.remove(task_id);
+        self.preempt_tokens.remove(task_id);
+    }
+
+    pub(super) fn progress_map(&self) -> Arc<DashMap<String, TaskProgress>> {
+        Arc::clone(&self.progress)
+    }
+
     fn dispatch_task(&self, task_id: String, payload: TaskPayload) -> Result<()> {
-        let command = QueueCommand::Enqueue(QueuedTask { task_id, payload });
-        self.command_tx
-            .send(command)
-            .context("Task dispatcher closed")?;
+        let kind = payload.kind;
+        let task = QueuedTask {
+            task_id,
+            payload,
+            queued_at: Instant::now(),
+        };
+        if task.payload.priority >= PREEMPT_PRIORITY {
+            self.interactive_tx
+                .send(task)
+                .map_err(|_| anyhow!("Interactive task dispatcher closed"))?;
+            self.metrics.record_queued(kind);
+            return Ok(());
+        }
+
+        // Counted before sending, so the dispatcher never takes the task off an empty count
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        let command = QueueCommand::Enqueue(task);
+        if let Err(err) = self.command_tx.send(command) {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(err).context("Task dispatcher closed");
+        }
+        self.metrics.record_queued(kind);
         Ok(())
     }
 
+    /// Mark the start of interactive work, such as hydrating a file the user opened.
+    /// Running tasks below [`PREEMPT_PRIORITY`] are asked to stop and are re-queued,
+    /// and no new ones start, until every returned guard is dropped.
+    pub fn begin_interactive(self: &Arc<Self>) -> InteractiveGuard {
+        self.interactive_active.fetch_add(1, Ordering::SeqCst);
+        self.preempt_running();
+        InteractiveGuard {
+            queue: Arc::clone(self),
+        }
+    }
+
+    /// Pause the tasks below [`PREEMPT_PRIORITY`] until [`TaskQueue::resume_tasks`],
+    /// as interactive work does. Interactive tasks, such as hydrating a file the user
+    /// opened, still run. Preempted uploads keep their upload session and continue
+    /// from the uploaded chunks once resumed.
+    pub fn pause_tasks(&self) {
+        if !self.user_paused.swap(true, Ordering::SeqCst) {
+            self.interactive_active.fetch_add(1, Ordering::SeqCst);
+            self.preempt_running();
+            info!(target: "tasks::queue", drive = %self.drive_id, "Tasks paused");
+        }
+    }
+
+    /// Resume the tasks paused by [`TaskQueue::pause_tasks`]
+    pub fn resume_tasks(&self) {
+        if self.user_paused.swap(false, Ordering::SeqCst) {
+            info!(target: "tasks::queue", drive = %self.drive_id, "Tasks resumed");
+            self.end_interactive();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.user_paused.load(Ordering::SeqCst)
+    }
+
+    /// Signal running low-priority tasks to pause. Returns the number signalled.
+    fn preempt_running(&self) -> usize {
+        let mut count = 0;
+        for entry in self.preempt_tokens.iter() {
+            let (priority, token) = entry.value();
+            if *priority < PREEMPT_PRIORITY && !token.is_cancelled() {
+                token.cancel();
+                count += 1;
+            }
+        }
+        if count > 0 {
+            info!(
+                target: "tasks::queue",
+                drive = %self.drive_id,
+                count,
+                "Pausing running tasks for interactive work"
+            );
+        }
+        count
+    }
+
+    fn end_interactive(&self) {
+        if self.interactive_active.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.interactive_done.notify_waiters();
+            self.resume_paused();
+        }
+    }
+
+    /// Park a task that stopped for interactive work, to be dispatched again later
+    fn pause_task(&self, task: QueuedTask) {
+        if let Err(err) = self.inventory.update_task(
+            &task.task_id,
+            TaskUpdate {
+                status: Some(TaskStatus::Pending),
+                ..Default::default()
+            },
+        ) {
+            warn!(
+                target: "tasks::queue",
+                drive = %self.drive_id,
+                task_id = %task.task_id,
+                error = %err,
+                "Failed to persist paused task state"
+            );
+        }
+        debug!(
+            target: "tasks::queue",
+            drive = %self.drive_id,
+            task_id = %task.task_id,
+            "Task paused for interactive work"
+        );
+        self.paused
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(task);
+
+        // The interactive work may have finished while this task was stopping
+        if self.interactive_active.load(Ordering::SeqCst) == 0 {
+            self.resume_paused();
+        }
+    }
+
+    fn resume_paused(&self) {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return;
+        }
+        let paused: Vec<QueuedTask> = self
+            .paused
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .drain(..)
+            .collect();
+        for task in paused {
+            let task_id = task.task_id.clone();
+            if let Err(err) = self.dispatch_task(task.task_id, task.payload) {
+                warn!(
+                    target: "tasks::queue",
+                    drive = %self.drive_id,
+                    task_id = %task_id,
+                    error = %err,
+                    "Failed to dispatch paused task"
+                );
+            }
+        }
+    }
+
+    /// Wait until no interactive work is running
+    async fn wait_for_interactive(&self) {
+        loop {
+            let done = self.interactive_done.notified();
+            if self.interactive_active.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            done.await;
+        }
+    }
+
     pub async fn persist_progress(
         &self,
         task_id: &str,
@@ -193,6 +650,32 @@ impl TaskQueue {
         }
     }
 
+    /// Write the in-memory progress of running tasks to the inventory, so a resumed task
+    /// starts from its last known state. Returns the number of tasks written.
+    pub fn checkpoint(&self) -> Result<usize> {
+        let snapshots: Vec<TaskProgress> = self
+            .progress
+            .iter()
+            .map(|entry| entry.value().clone())
+            .collect();
+
+        for snapshot in &snapshots {
+            self.inventory
+                .update_task(
+                    &snapshot.task_id,
+                    TaskUpdate {
+                        progress: Some(snapshot.progress),
+                        processed_bytes: snapshot.processed_bytes,
+                        total_bytes: snapshot.total_bytes,
+                        custom_state: snapshot.custom_state.clone().map(Some),
+                        ..Default::default()
+                    },
+                )
+                .with_context(|| format!("Failed to checkpoint task {}", snapshot.task_id))?;
+        }
+        Ok(snapshots.len())
+    }
+
     pub async fn shutdown(&self) {
         if self.shutting_down.swap(true, Ordering::SeqCst) {
             return;
@@ -200,18 +683,231 @@ impl TaskQueue {
 
         self.cancel_requested.store(true, Ordering::SeqCst);
 
+        // Aborted tasks are awaited at the end, so no task holds the inventory open once
+        // this returns and the database of a removed drive can be deleted
+        let mut stopped = Vec::new();
+        for handle in [
+            &self.checkpoint_handle,
+            &self.progress_events_handle,
+            &self.scaler_handle,
+        ] {
+            if let Some(handle) = handle.lock().await.take() {
+                handle.abort();
+                stopped.push(handle);
+            }
+        }
+        match self.checkpoint() {
+            Ok(count) if count > 0 => {
+                info!(
+                    target: "tasks::queue",
+                    drive = %self.drive_id,
+                    count,
+                    "Saved progress of running tasks"
+                );
+            }
+            Ok(_) => {}
+            Err(err) => {
+                warn!(
+                    target: "tasks::queue",
+                    drive = %self.drive_id,
+                    error = %err,
+                    "Failed to save progress of running tasks"
+                );
+            }
+        }
+
         if let Err(err) = self.command_tx.send(QueueCommand::Shutdown) {
             warn!(target: "tasks::queue", error = %err, "Task queue dispatcher already closed");
         }
 
-        if let Some(handle) = self.dispatcher_handle.lock().await.take() {
-            handle.abort();
+        for handle in [&self.dispatcher_handle, &self.interactive_handle] {
+            if let Some(handle) = handle.lock().await.take() {
+                handle.abort();
+                stopped.push(handle);
+            }
+        }
+
+        stopped.extend(self.cancel_running_tasks().await);
+        let task_ids: Vec<String> = self
+            .task_handles
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+        for task_id in task_ids {
+            if let Some((_, handle)) = self.task_handles.remove(&task_id) {
+                handle.abort();
+                stopped.push(handle);
+            }
         }
+        futures::future::join_all(stopped).await;
 
-        self.cancel_running_tasks().await;
-        self.task_handles.clear();
         self.task_paths.clear();
         self.progress.clear();
+        self.preempt_tokens.clear();
+        self.paused
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+
+    /// Cancel a single task. A running task is stopped through its cancellation token,
+    /// so the uploader can clean up its session. Returns `false` if the task already
+    /// finished.
+    pub async fn cancel_task(&self, task_id: &str) -> Result<bool> {
+        let status = self
+            .inventory
+            .get_task_status(task_id)?
+            .ok_or_else(|| anyhow!("Task not found: {}", task_id))?;
+        if !status.is_active() {
+            return Ok(false);
+        }
+
+        self.inventory
+            .update_task(
+                task_id,
+                TaskUpdate {
+                    status: Some(TaskStatus::Cancelled),
+                    ..Default::default()
+                },
+            )
+            .context("Failed to mark task as cancelled")?;
+
+        self.paused
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|task| task.task_id != task_id);
+        if let Some(entry) = self.preempt_tokens.get(task_id) {
+            entry.value().1.cancel();
+        }
+
+        info!(
+            target: "tasks::queue",
+            drive = %self.drive_id,
+            task_id = %task_id,
+            "Task cancelled"
+        );
+        Ok(true)
+    }
+
+    /// Run a dead-lettered task again as a new task and remove it from the dead-letter
+    /// store. Returns the id of the new task.
+    pub async fn requeue_dead_letter(&self, task_id: &str) -> Result<String> {
+        let record = self
+            .inventory
+            .get_dead_letter(task_id)?
+            .ok_or_else(|| anyhow!("Dead-lettered task not found: {}", task_id))?;
+        let kind = TaskKind::from_str(&record.task_type)
+            .ok_or_else(|| anyhow!("Unknown task type {}", record.task_type))?;
+
+        let mut payload = TaskPayload::new(kind, PathBuf::from(&record.local_path))
+            .with_priority(record.priority)
+            .with_force_override(record.force_override);
+        if let Some(state) = record.custom_state {
+            payload = payload.with_custom_state(state);
+        }
+
+        let new_task_id = self.enqueue(payload).await?;
+        self.inventory.delete_dead_letter(task_id)?;
+        refresh_shell_state(Path::new(&record.local_path));
+        info!(
+            target: "tasks::queue",
+            drive = %self.drive_id,
+            task_id = %task_id,
+            new_task_id = %new_task_id,
+            "Requeued dead-lettered task"
+        );
+        Ok(new_task_id)
+    }
+
+    /// Drop a task from the dead-letter store. Returns `false` if it was not there.
+    pub fn discard_dead_letter(&self, task_id: &str) -> Result<bool> {
+        let Some(record) = self.inventory.get_dead_letter(task_id)? else {
+            return Ok(false);
+        };
+        let deleted = self.inventory.delete_dead_letter(task_id)?;
+        refresh_shell_state(Path::new(&record.local_path));
+        Ok(deleted)
+    }
+
+    /// Cancel every pending or running task, or only those of `kind`, in one go.
+    /// Returns the number of tasks cancelled.
+    pub async fn cancel_tasks(&self, kind: Option<TaskKind>) -> Result<usize> {
+        let task_ids: HashSet<String> = self
+            .inventory
+            .cancel_tasks(&self.drive_id, kind.map(|kind| kind.as_str()))
+            .context("Failed to cancel tasks in inventory")?
+            .into_iter()
+            .collect();
+        if task_ids.is_empty() {
+            return Ok(0);
+        }
+
+        // Queued tasks are skipped by the status check before they run
+        self.paused
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|task| !task_ids.contains(&task.task_id));
+        for entry in self.preempt_tokens.iter() {
+            if task_ids.contains(entry.key()) {
+                entry.value().1.cancel();
+            }
+        }
+
+        info!(
+            target: "tasks::queue",
+            drive = %self.drive_id,
+            kind = kind.map(|kind| kind.as_str()).unwrap_or("all"),
+            count = task_ids.len(),
+            "Cancelled tasks"
+        );
+        Ok(task_ids.len())
+    }
+
+    /// Run every failed or timed out task again. Returns the number of tasks requeued.
+    pub async fn requeue_failed_tasks(&self) -> Result<usize> {
+        let records = self
+            .inventory
+            .requeue_failed_tasks(&self.drive_id)
+            .context("Failed to requeue tasks in inventory")?;
+
+        // A task that fails to dispatch stays pending and is resumed on the next start
+        let mut requeued = 0usize;
+        for record in records {
+            let payload = match Self::payload_from_record(&record) {
+                Ok(payload) => payload,
+                Err(err) => {
+                    warn!(
+                        target: "tasks::queue",
+                        drive = %self.drive_id,
+                        task_id = %record.id,
+                        error = %err,
+                        "Failed to build payload for requeued task"
+                    );
+                    continue;
+                }
+            };
+            if let Err(err) = self.dispatch_task(record.id.clone(), payload) {
+                warn!(
+                    target: "tasks::queue",
+                    drive = %self.drive_id,
+                    task_id = %record.id,
+                    error = ?err,
+                    "Failed to dispatch requeued task"
+                );
+                continue;
+            }
+            requeued += 1;
+        }
+
+        if requeued > 0 {
+            info!(
+                target: "tasks::queue",
+                drive = %self.drive_id,
+                count = requeued,
+                "Requeued failed tasks"
+            );
+        }
+        Ok(requeued)
     }
 
     /// Cancel all tasks for a given path or its descendants.
@@ -261,6 +957,10 @@ impl TaskQueue {
                     "Aborted running task"
                 );
             }
+            // Tasks running outside the queue, such as hydrations, have no handle
+            if let Some((_, (_, token))) = self.preempt_tokens.remove(&task_id) {
+                token.cancel();
+            }
             self.task_paths.remove(&task_id);
             self.progress.remove(&task_id);
         }
@@ -278,12 +978,167 @@ impl TaskQueue {
         Ok(cancelled_count)
     }
 
-    async fn spawn_dispatcher(self: &Arc<Self>, command_rx: UnboundedReceiver<QueueCommand>) {
+    async fn spawn_dispatcher(self: &Arc<Self>, command_rx: UnboundedReceiver<QueueCommand>) {
+        let queue = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            queue.run_dispatch_loop(command_rx).await;
+        });
+        *self.dispatcher_handle.lock().await = Some(handle);
+    }
+
+    async fn spawn_interactive_dispatcher(
+        self: &Arc<Self>,
+        mut interactive_rx: UnboundedReceiver<QueuedTask>,
+    ) {
+        let queue = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            while let Some(task) = interactive_rx.recv().await {
+                queue.launch_interactive_task(task);
+            }
+        });
+        *self.interactive_handle.lock().await = Some(handle);
+    }
+
+    async fn spawn_checkpoint_loop(self: &Arc<Self>) {
+        let queue = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CHECKPOINT_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(err) = queue.checkpoint() {
+                    warn!(
+                        target: "tasks::queue",
+                        drive = %queue.drive_id,
+                        error = %err,
+                        "Failed to checkpoint task progress"
+                    );
+                }
+            }
+        });
+        *self.checkpoint_handle.lock().await = Some(handle);
+    }
+
+    /// Broadcast progress of running tasks that changed since the last tick, so the UI
+    /// can update progress bars without polling
+    async fn spawn_progress_events(self: &Arc<Self>) {
+        let queue = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(PROGRESS_EVENT_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            let mut last_sent: HashMap<String, (f64, Option<i64>)> = HashMap::new();
+            loop {
+                interval.tick().await;
+                last_sent.retain(|task_id, _| queue.progress.contains_key(task_id));
+                let changed: Vec<TaskProgress> = queue
+                    .progress
+                    .iter()
+                    .filter(|entry| {
+                        let current = (entry.progress, entry.processed_bytes);
+                        last_sent.insert(entry.key().clone(), current) != Some(current)
+                    })
+                    .map(|entry| entry.value().clone())
+                    .collect();
+                if !changed.is_empty() {
+                    queue.inventory.notify_task_progress(changed);
+                }
+            }
+        });
+        *self.progress_events_handle.lock().await = Some(handle);
+    }
+
+    async fn spawn_scaler(self: &Arc<Self>) {
+        if self.config.min_concurrent == self.config.max_concurrent {
+            return;
+        }
         let queue = Arc::clone(self);
         let handle = tokio::spawn(async move {
-            queue.run_dispatch_loop(command_rx).await;
+            let mut interval = tokio::time::interval(SCALE_INTERVAL);
+            let mut state = ScalerState::default();
+            loop {
+                interval.tick().await;
+                let completed = queue.completed.swap(0, Ordering::SeqCst);
+                queue.rescale(&mut state, completed);
+            }
         });
-        *self.dispatcher_handle.lock().await = Some(handle);
+        *self.scaler_handle.lock().await = Some(handle);
+    }
+
+    /// Add a worker once tasks have been waiting for [`GROW_AFTER_TICKS`], as long as
+    /// the last added worker raised throughput, and remove one once workers have sat idle
+    /// for [`SHRINK_AFTER_TICKS`]
+    fn rescale(&self, state: &mut ScalerState, completed: usize) {
+        let queued = self.queued.load(Ordering::SeqCst);
+        let running = self.inflight.load(Ordering::SeqCst);
+        let workers = self.workers.load(Ordering::SeqCst);
+
+        if queued > 0 && workers < self.config.max_concurrent {
+            state.idle_ticks = 0;
+            state.backlog_ticks += 1;
+            state.window_completed += completed;
+            if state.backlog_ticks < GROW_AFTER_TICKS {
+                return;
+            }
+            state.backlog_ticks = 0;
+            let window_completed = std::mem::take(&mut state.window_completed);
+
+            // Transfers share bandwidth, so more workers do not always finish more tasks.
+            // Hold the pool until the backlog clears if the last added worker did not help.
+            if state
+                .completed_before_grow
+                .is_some_and(|before| window_completed <= before)
+            {
+                return;
+            }
+            let Some(permit) = self
+                .parked_permits
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .pop()
+            else {
+                return;
+            };
+            drop(permit);
+            self.workers.fetch_add(1, Ordering::SeqCst);
+            state.completed_before_grow = Some(window_completed);
+            debug!(
+                target: "tasks::queue",
+                drive = %self.drive_id,
+                workers = workers + 1,
+                queued,
+                "Added task worker"
+            );
+            return;
+        }
+
+        state.backlog_ticks = 0;
+        state.window_completed = 0;
+        if queued == 0 {
+            state.completed_before_grow = None;
+        }
+        if !(queued == 0 && running < workers && workers > self.config.min_concurrent) {
+            state.idle_ticks = 0;
+            return;
+        }
+        state.idle_ticks += 1;
+        if state.idle_ticks < SHRINK_AFTER_TICKS {
+            return;
+        }
+        let Ok(permit) = self.semaphore.clone().try_acquire_owned() else {
+            return;
+        };
+        state.idle_ticks = 0;
+        self.parked_permits
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(permit);
+        self.workers.fetch_sub(1, Ordering::SeqCst);
+        debug!(
+            target: "tasks::queue",
+            drive = %self.drive_id,
+            workers = workers - 1,
+            "Removed idle task worker"
+        );
     }
 
     async fn run_dispatch_loop(self: Arc<Self>, mut command_rx: UnboundedReceiver<QueueCommand>) {
@@ -297,7 +1152,10 @@ impl TaskQueue {
         while let Some(command) = command_rx.recv().await {
             match command {
                 QueueCommand::Enqueue(task) => {
-                    self.launch_task(task).await;
+                    self.launch_task(task, None).await;
+                }
+                QueueCommand::Deferred(task, type_permit) => {
+                    self.launch_task(task, Some(type_permit)).await;
                 }
                 QueueCommand::Shutdown => {
                     debug!(
@@ -317,8 +1175,49 @@ impl TaskQueue {
         );
     }
 
-    async fn launch_task(self: &Arc<Self>, task: QueuedTask) {
-        let permit = match self.semaphore.clone().acquire_owned().await {
+    /// Start an interactive task without waiting for a worker slot
+    fn launch_interactive_task(self: &Arc<Self>, task: QueuedTask) {
+        let guard = self.begin_interactive();
+        self.inflight.fetch_add(1, Ordering::SeqCst);
+        let queue = Arc::clone(self);
+        let task_id = task.task_id.clone();
+        let handle_task_id = task_id.clone();
+
+        let handle = tokio::spawn(async move {
+            Arc::clone(&queue).execute_task(task).await;
+            queue.inflight.fetch_sub(1, Ordering::SeqCst);
+            queue.idle_notify.notify_waiters();
+            queue.task_handles.remove(&handle_task_id);
+            drop(guard);
+        });
+
+        self.task_handles.insert(task_id, handle);
+    }
+
+    async fn launch_task(
+        self: &Arc<Self>,
+        task: QueuedTask,
+        type_permit: Option<OwnedSemaphorePermit>,
+    ) {
+        // Set aside a task whose type is at its limit, so it does not hold up other types
+        let type_permit = match (type_permit, self.type_semaphore(task.payload.kind)) {
+            (Some(permit), _) => Some(permit),
+            (None, Some(semaphore)) => match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    self.defer_task(task, semaphore);
+                    return;
+                }
+            },
+            (None, None) => None,
+        };
+
+        // Hold back regular tasks while interactive work is running
+        self.wait_for_interactive().await;
+
+        let permit = self.semaphore.clone().acquire_owned().await;
+        self.queued.fetch_sub(1, Ordering::SeqCst);
+        let permit = match permit {
             Ok(permit) => permit,
             Err(err) => {
                 error!(
@@ -327,6 +1226,7 @@ impl TaskQueue {
                     error = %err,
                     "Failed to acquire semaphore permit"
                 );
+                self.metrics.record_dequeued(task.payload.kind);
                 if let Err(update_err) = self.inventory.update_task(
                     &task.task_id,
                     TaskUpdate {
@@ -354,7 +1254,9 @@ impl TaskQueue {
 
         let handle = tokio::spawn(async move {
             queue_for_execute.execute_task(task).await;
+            drop(type_permit);
             drop(permit);
+            queue_for_notify.completed.fetch_add(1, Ordering::SeqCst);
             queue_for_notify.inflight.fetch_sub(1, Ordering::SeqCst);
             queue_for_notify.idle_notify.notify_waiters();
             queue_for_notify.task_handles.remove(&handle_task_id);
@@ -363,7 +1265,28 @@ impl TaskQueue {
         self.task_handles.insert(task_id, handle);
     }
 
+    /// Dispatch a task again once a slot of its type is free
+    fn defer_task(self: &Arc<Self>, task: QueuedTask, semaphore: Arc<Semaphore>) {
+        debug!(
+            target: "tasks::queue",
+            drive = %self.drive_id,
+            task_id = %task.task_id,
+            kind = task.payload.kind.as_str(),
+            "Task type at its limit, deferring task"
+        );
+        let command_tx = self.command_tx.clone();
+        tokio::spawn(async move {
+            if let Ok(permit) = semaphore.acquire_owned().await {
+                // The dispatcher is gone on shutdown; the task stays pending in the inventory
+                let _ = command_tx.send(QueueCommand::Deferred(task, permit));
+            }
+        });
+    }
+
     async fn execute_task(self: Arc<Self>, task: QueuedTask) {
+        let kind = task.payload.kind;
+        self.metrics.record_dequeued(kind);
+
         // Check if task was cancelled while in the channel queue
         match self.inventory.get_task_status(&task.task_id) {
             Ok(Some(TaskStatus::Cancelled)) => {
@@ -397,30 +1320,96 @@ impl TaskQueue {
             _ => {}
         }
 
-        if let Err(err) = self.inventory.update_task(
-            &task.task_id,
-            TaskUpdate {
-                status: Some(TaskStatus::Running),
-                ..Default::default()
-            },
-        ) {
-            error!(
-                target: "tasks::queue",
-                drive = %self.drive_id,
-                task_id = %task.task_id,
-                error = %err,
-                "Failed to mark task as running"
-            );
+        // Register the preemption token; a low-priority task that lost the race with
+        // interactive work steps aside before starting
+        let preempt = CancellationToken::new();
+        self.preempt_tokens.insert(
+            task.task_id.clone(),
+            (task.payload.priority, preempt.clone()),
+        );
+        if task.payload.priority < PREEMPT_PRIORITY
+            && self.interactive_active.load(Ordering::SeqCst) > 0
+        {
+            self.preempt_tokens.remove(&task.task_id);
+            self.pause_task(task);
             return;
         }
 
+        // Only a task that is still pending starts; `cancel_task` may have won the race
+        // since the status check above
+        match self.inventory.mark_task_running(&task.task_id) {
+            Ok(true) => {}
+            Ok(false) => {
+                debug!(
+                    target: "tasks::queue",
+                    drive = %self.drive_id,
+                    task_id = %task.task_id,
+                    "Task is no longer pending, skipping"
+                );
+                self.preempt_tokens.remove(&task.task_id);
+                return;
+            }
+            Err(err) => {
+                error!(
+                    target: "tasks::queue",
+                    drive = %self.drive_id,
+                    task_id = %task.task_id,
+                    error = %err,
+                    "Failed to mark task as running"
+                );
+                self.preempt_tokens.remove(&task.task_id);
+                return;
+            }
+        }
+
+        self.metrics.record_started(kind, task.queued_at.elapsed());
+        let started = Instant::now();
+        let local_path = task.payload.local_path_display();
+        self.inventory
+            .notify_task_started(&task.task_id, kind, &local_path);
+
         // Register task path for path-based cancellation
-        self.task_paths
-            .insert(task.task_id.clone(), task.payload.local_path_display());
+        self.task_paths.insert(task.task_id.clone(), local_path);
 
         self.register_progress_entry(&task).await;
 
-        match self.run_placeholder_task(&task).await {
+        let run = self.run_placeholder_task(&task, preempt.clone());
+        let result = match self.config.timeouts.get(&task.payload.kind) {
+            Some(limit) => {
+                tokio::pin!(run);
+                match tokio::time::timeout(*limit, &mut run).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        // Ask the executor to stop, then drop it if it does not
+                        preempt.cancel();
+                        let _ = tokio::time::timeout(TIMEOUT_GRACE, &mut run).await;
+                        self.cleanup_task_entry(&task.task_id).await;
+                        self.handle_task_timeout(&task, *limit).await;
+                        self.metrics
+                            .record_finished(kind, TaskStatus::TimedOut, started.elapsed());
+                        return;
+                    }
+                }
+            }
+            None => run.await,
+        };
+        if result.is_err() && preempt.is_cancelled() {
+            self.cleanup_task_entry(&task.task_id).await;
+            // The token is shared by preemption and `cancel_task`, which marks the
+            // task cancelled before firing it
+            if matches!(
+                self.inventory.get_task_status(&task.task_id),
+                Ok(Some(TaskStatus::Cancelled))
+            ) {
+                self.metrics
+                    .record_finished(kind, TaskStatus::Cancelled, started.elapsed());
+            } else {
+                self.pause_task(task);
+            }
+            return;
+        }
+
+        match result {
             Ok(TaskRunState::Completed) => {
                 if let Err(err) = self.inventory.update_task(
                     &task.task_id,
@@ -437,6 +1426,27 @@ impl TaskQueue {
                         "Failed to mark task as completed"
                     );
                 }
+                // A success supersedes earlier failures of the same kind on this path
+                if let Err(err) = self
+                    .inventory
+                    .delete_dead_letters_for_path(kind.as_str(), &task.payload.local_path_display())
+                {
+                    warn!(
+                        target: "tasks::queue",
+                        drive = %self.drive_id,
+                        task_id = %task.task_id,
+                        error = %err,
+                        "Failed to clear dead letters of completed task path"
+                    );
+                }
+                self.metrics
+                    .record_finished(kind, TaskStatus::Completed, started.elapsed());
+                self.inventory.notify_task_outcome(
+                    &task.task_id,
+                    kind,
+                    &task.payload.local_path_display(),
+                    None,
+                );
             }
             Ok(TaskRunState::Cancelled) => {
                 if let Err(err) = self.inventory.update_task(
@@ -454,42 +1464,237 @@ impl TaskQueue {
                         "Failed to mark task as cancelled"
                     );
                 }
+                self.metrics
+                    .record_finished(kind, TaskStatus::Cancelled, started.elapsed());
                 self.cleanup_task_entry(&task.task_id).await;
                 return;
             }
             Err(err) => {
-                error!(
+                self.cleanup_task_entry(&task.task_id).await;
+                self.handle_task_failure(task, err, started.elapsed());
+                return;
+            }
+        }
+
+        self.cleanup_task_entry(&task.task_id).await;
+    }
+
+    /// Run the executor's cleanup for a task stopped for running too long and mark it
+    /// timed out
+    async fn handle_task_timeout(&self, task: &QueuedTask, limit: Duration) {
+        warn!(
+            target: "tasks::queue",
+            drive = %self.drive_id,
+            task_id = %task.task_id,
+            kind = task.payload.kind.as_str(),
+            timeout_secs = limit.as_secs(),
+            "Task timed out"
+        );
+
+        if let Err(err) = self.cleanup_task(task).await {
+            warn!(
+                target: "tasks::queue",
+                drive = %self.drive_id,
+                task_id = %task.task_id,
+                error = %err,
+                "Failed to clean up after timed out task"
+            );
+        }
+
+        let message = format!("Timed out after {}s", limit.as_secs());
+        if let Err(err) = self.inventory.update_task(
+            &task.task_id,
+            TaskUpdate {
+                status: Some(TaskStatus::TimedOut),
+                error: Some(Some(message.clone())),
+                ..Default::default()
+            },
+        ) {
+            warn!(
+                target: "tasks::queue",
+                drive = %self.drive_id,
+                task_id = %task.task_id,
+                error = %err,
+                "Failed to mark task as timed out"
+            );
+        }
+        self.inventory.notify_task_outcome(
+            &task.task_id,
+            task.payload.kind,
+            &task.payload.local_path_display(),
+            Some(&message),
+        );
+    }
+
+    /// Undo what a task stopped midway left behind, such as a remote upload session
+    async fn cleanup_task(&self, task: &QueuedTask) -> Result<()> {
+        match task.payload.kind {
+            TaskKind::Upload => {
+                UploadTask::new(
+                    Arc::new(self.inventory.with_source(ChangeSource::Upload)),
+                    self.cr_client.clone(),
+                    self.drive_id.as_str(),
+                    task,
+                    self.sync_path.clone(),
+                    self.remote_base.clone(),
+                    Arc::clone(&self.progress),
+                )
+                .cleanup()
+                .await
+            }
+            TaskKind::Download => {
+                DownloadTask::new(
+                    Arc::new(self.inventory.with_source(ChangeSource::Download)),
+                    self.cr_client.clone(),
+                    self.drive_id.as_str(),
+                    task,
+                    self.sync_path.clone(),
+                    self.remote_base.clone(),
+                    Arc::clone(&self.progress),
+                )
+                .cleanup()
+                .await
+            }
+            TaskKind::Hydrate => Ok(()),
+        }
+    }
+
+    /// Record a failed run and either schedule a retry according to the task type's
+    /// policy or mark the task as failed
+    fn handle_task_failure(
+        self: &Arc<Self>,
+        task: QueuedTask,
+        err: anyhow::Error,
+        run_time: Duration,
+    ) {
+        let failure = retry::classify(&err);
+        let message = format!("{:?}", err);
+        let attempts = match self
+            .inventory
+            .record_task_failure(&task.task_id, failure, &message)
+        {
+            Ok(attempts) => attempts.max(0) as u32,
+            Err(record_err) => {
+                warn!(
                     target: "tasks::queue",
                     drive = %self.drive_id,
                     task_id = %task.task_id,
-                    error = ?err,
-                    "Task execution failed"
+                    error = %record_err,
+                    "Failed to record task attempt"
                 );
-                if let Err(update_err) = self.inventory.update_task(
-                    &task.task_id,
-                    TaskUpdate {
-                        status: Some(TaskStatus::Failed),
-                        error: Some(Some(format!("{:?}", err))),
-                        ..Default::default()
-                    },
-                ) {
+                u32::MAX
+            }
+        };
+
+        let policy = self.config.retry_policy(task.payload.kind);
+        if policy.should_retry(attempts, failure) && !self.shutting_down.load(Ordering::SeqCst) {
+            let delay = policy.delay(attempts);
+            warn!(
+                target: "tasks::queue",
+                drive = %self.drive_id,
+                task_id = %task.task_id,
+                failure = failure.as_str(),
+                attempts,
+                delay_secs = delay.as_secs(),
+                error = ?err,
+                "Task execution failed, retrying"
+            );
+            if let Err(update_err) = self.inventory.update_task(
+                &task.task_id,
+                TaskUpdate {
+                    status: Some(TaskStatus::Pending),
+                    error: Some(Some(message)),
+                    ..Default::default()
+                },
+            ) {
+                warn!(
+                    target: "tasks::queue",
+                    drive = %self.drive_id,
+                    task_id = %task.task_id,
+                    error = %update_err,
+                    "Failed to persist task retry state"
+                );
+                return;
+            }
+
+            // Dispatching re-checks the status, so a task cancelled during the
+            // delay is dropped
+            let queue = Arc::clone(self);
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                if queue.shutting_down.load(Ordering::SeqCst) {
+                    return;
+                }
+                let task_id = task.task_id.clone();
+                if let Err(err) = queue.dispatch_task(task.task_id, task.payload) {
                     warn!(
                         target: "tasks::queue",
-                        drive = %self.drive_id,
-                        task_id = %task.task_id,
-                        error = %update_err,
-                        "Failed to persist task failure state"
+                        drive = %queue.drive_id,
+                        task_id = %task_id,
+                        error = %err,
+                        "Failed to dispatch task retry"
                     );
                 }
-                self.cleanup_task_entry(&task.task_id).await;
-                return;
-            }
+            });
+            return;
         }
 
-        self.cleanup_task_entry(&task.task_id).await;
+        error!(
+            target: "tasks::queue",
+            drive = %self.drive_id,
+            task_id = %task.task_id,
+            failure = failure.as_str(),
+            attempts,
+            error = ?err,
+            "Task execution failed"
+        );
+        self.metrics
+            .record_finished(task.payload.kind, TaskStatus::Failed, run_time);
+        self.inventory.notify_task_outcome(
+            &task.task_id,
+            task.payload.kind,
+            &task.payload.local_path_display(),
+            Some(&format!("{:#}", err)),
+        );
+        if let Err(update_err) = self.inventory.update_task(
+            &task.task_id,
+            TaskUpdate {
+                status: Some(TaskStatus::Failed),
+                error: Some(Some(message.clone())),
+                ..Default::default()
+            },
+        ) {
+            warn!(
+                target: "tasks::queue",
+                drive = %self.drive_id,
+                task_id = %task.task_id,
+                error = %update_err,
+                "Failed to persist task failure state"
+            );
+        }
+
+        // Keep it in the dead-letter store until the user requeues or discards it
+        if let Err(dlq_err) = self
+            .inventory
+            .dead_letter_task(&task.task_id, failure, &message)
+        {
+            warn!(
+                target: "tasks::queue",
+                drive = %self.drive_id,
+                task_id = %task.task_id,
+                error = %dlq_err,
+                "Failed to move task to the dead-letter store"
+            );
+        } else {
+            refresh_shell_state(&task.payload.local_path);
+        }
     }
 
-    async fn run_placeholder_task(&self, task: &QueuedTask) -> Result<TaskRunState> {
+    async fn run_placeholder_task(
+        &self,
+        task: &QueuedTask,
+        preempt: CancellationToken,
+    ) -> Result<TaskRunState> {
         info!(
             target: "tasks::queue",
             drive = %self.drive_id,
@@ -502,30 +1707,35 @@ impl TaskQueue {
         match &task.payload.kind {
             TaskKind::Upload => {
                 let mut task_executor = UploadTask::new(
-                    self.inventory.clone(),
+                    Arc::new(self.inventory.with_source(ChangeSource::Upload)),
                     self.cr_client.clone(),
                     self.drive_id.as_str(),
                     &task,
                     self.sync_path.clone(),
                     self.remote_base.clone(),
                     Arc::clone(&self.progress),
-                );
+                )
+                .with_cancel_token(preempt.clone());
 
                 task_executor.execute().await?;
             }
             TaskKind::Download => {
                 let mut task_executor = DownloadTask::new(
-                    self.inventory.clone(),
+                    Arc::new(self.inventory.with_source(ChangeSource::Download)),
                     self.cr_client.clone(),
                     self.drive_id.as_str(),
                     &task,
                     self.sync_path.clone(),
                     self.remote_base.clone(),
                     Arc::clone(&self.progress),
-                );
+                )
+                .with_cancel_token(preempt.clone());
 
                 task_executor.execute().await?;
             }
+            TaskKind::Hydrate => {
+                return Err(anyhow!("Hydration tasks are not run by the queue"));
+            }
         }
 
         // for step in 0..PLACEHOLDER_STEPS {
@@ -589,6 +1799,7 @@ impl TaskQueue {
     async fn cleanup_task_entry(&self, task_id: &str) {
         self.progress.remove(task_id);
         self.task_paths.remove(task_id);
+        self.preempt_tokens.remove(task_id);
     }
 
     async fn resume_incomplete_tasks(self: &Arc<Self>) -> Result<()> {
@@ -603,6 +1814,27 @@ impl TaskQueue {
 
         let mut resumed = 0usize;
         for record in records {
+            // The fetch that started a hydration is gone after a restart
+            if record.task_type == TaskKind::Hydrate.as_str() {
+                if let Err(err) = self.inventory.update_task(
+                    &record.id,
+                    TaskUpdate {
+                        status: Some(TaskStatus::Failed),
+                        error: Some(Some("Interrupted".to_string())),
+                        ..Default::default()
+                    },
+                ) {
+                    warn!(
+                        target: "tasks::queue",
+                        drive = %self.drive_id,
+                        task_id = %record.id,
+                        error = ?err,
+                        "Failed to mark interrupted hydration as failed"
+                    );
+                }
+                continue;
+            }
+
             if record.status == TaskStatus::Running {
                 if let Err(err) = self.inventory.update_task(
                     &record.id,
@@ -662,21 +1894,26 @@ impl TaskQueue {
         Ok(())
     }
 
-    async fn cancel_running_tasks(&self) {
+    /// Abort the running tasks, returning their handles to wait for them to stop
+    async fn cancel_running_tasks(&self) -> Vec<JoinHandle<()>> {
         let running: Vec<String> = self
             .progress
             .iter()
             .map(|entry| entry.key().clone())
             .collect();
 
+        let mut aborted = Vec::new();
         for task_id in running {
             if let Some((_, handle)) = self.task_handles.remove(&task_id) {
                 handle.abort();
+                aborted.push(handle);
             }
 
             self.progress.remove(&task_id);
             self.task_paths.remove(&task_id);
+            self.preempt_tokens.remove(&task_id);
         }
+        aborted
     }
 
     fn payload_from_record(record: &TaskRecord) -> Result<TaskPayload> {
@@ -685,7 +1922,8 @@ impl TaskQueue {
 
         let mut payload = TaskPayload::new(kind, PathBuf::from(&record.local_path))
             .with_priority(record.priority)
-            .with_task_id(record.id.clone());
+            .with_task_id(record.id.clone())
+            .with_force_override(record.force_override);
 
         let total_bytes = record.total_bytes;
         let processed_bytes = record.processed_bytes;
@@ -701,18 +1939,54 @@ impl TaskQueue {
     }
 }
 
+/// Keeps low-priority tasks paused while alive, see [`TaskQueue::begin_interactive`]
+pub struct InteractiveGuard {
+    queue: Arc<TaskQueue>,
+}
+
+impl Drop for InteractiveGuard {
+    fn drop(&mut self) {
+        self.queue.end_interactive();
+    }
+}
+
 #[allow(dead_code)]
 pub enum TaskRunState {
     Completed,
     Cancelled,
 }
 
+/// What the scaler remembers between ticks, see [`TaskQueue::rescale`]
+#[derive(Default)]
+struct ScalerState {
+    /// Consecutive ticks with tasks waiting
+    backlog_ticks: u32,
+    /// Consecutive ticks with idle workers and nothing waiting
+    idle_ticks: u32,
+    /// Tasks finished during the current backlog window
+    window_completed: usize,
+    /// Tasks finished in the window before the last worker was added
+    completed_before_grow: Option<usize>,
+}
+
 enum QueueCommand {
     Enqueue(QueuedTask),
+    /// A task that waited for a slot of its type and holds it
+    Deferred(QueuedTask, OwnedSemaphorePermit),
     Shutdown,
 }
 
+fn type_semaphores(limits: &HashMap<TaskKind, usize>) -> HashMap<TaskKind, Arc<Semaphore>> {
+    limits
+        .iter()
+        .filter(|(_, limit)| **limit > 0)
+        .map(|(kind, limit)| (*kind, Arc::new(Semaphore::new(*limit))))
+        .collect()
+}
+
 pub struct QueuedTask {
     pub task_id: String,
     pub payload: TaskPayload,
+    /// When the task was dispatched, for queue wait times
+    pub queued_at: Instant,
 }
@@ -0,0 +1,198 @@
+use crate::inventory::TaskFailureClass;
+use crate::tasks::types::TaskKind;
+use crate::uploader::UploadError;
+use anyhow::{Context, Result};
+use cloudreve_api::ApiError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::ErrorKind;
+use std::time::Duration;
+
+/// When and how often a failed task is run again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total number of runs, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for every further retry
+    pub base_delay_secs: u64,
+    /// Upper bound for the delay between retries
+    pub max_delay_secs: u64,
+    /// Failures worth retrying; anything else fails the task right away
+    pub retry_on: Vec<TaskFailureClass>,
+}
+
+impl RetryPolicy {
+    /// Built-in policy for a task type
+    pub fn default_for(kind: TaskKind) -> Self {
+        match kind {
+            // Uploads also retry on local I/O, which is usually another program
+            // holding the file open
+            TaskKind::Upload => Self {
+                max_attempts: 5,
+                base_delay_secs: 5,
+                max_delay_secs: 300,
+                retry_on: vec![
+                    TaskFailureClass::Network,
+                    TaskFailureClass::Server,
+                    TaskFailureClass::Locked,
+                    TaskFailureClass::LocalIo,
+                ],
+            },
+            TaskKind::Download => Self {
+                max_attempts: 3,
+                base_delay_secs: 5,
+                max_delay_secs: 120,
+                retry_on: vec![TaskFailureClass::Network, TaskFailureClass::Server],
+            },
+            // The system retries the fetch itself when a hydration fails
+            TaskKind::Hydrate => Self {
+                max_attempts: 1,
+                base_delay_secs: 0,
+                max_delay_secs: 0,
+                retry_on: Vec::new(),
+            },
+        }
+    }
+
+    /// Apply the fields present in a JSON object on top of this policy
+    pub fn with_overrides(self, overrides: &Value) -> Result<Self> {
+        let Some(overrides) = overrides.as_object() else {
+            return Ok(self);
+        };
+        let mut value = serde_json::to_value(&self).context("Failed to serialize retry policy")?;
+        if let Some(fields) = value.as_object_mut() {
+            for (key, field) in overrides {
+                fields.insert(key.clone(), field.clone());
+            }
+        }
+        serde_json::from_value(value).context("Invalid retry policy")
+    }
+
+    /// Whether a task that failed `attempts` times, most recently with `failure`,
+    /// should run again
+    pub fn should_retry(&self, attempts: u32, failure: TaskFailureClass) -> bool {
+        attempts < self.max_attempts && self.retry_on.contains(&failure)
+    }
+
+    /// Delay before the run following failed attempt number `attempts` (1-based)
+    pub fn delay(&self, attempts: u32) -> Duration {
+        let exponent = attempts.saturating_sub(1).min(16);
+        let secs = self
+            .base_delay_secs
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay_secs);
+        Duration::from_secs(secs)
+    }
+}
+
+/// Work out why a task failed by looking for known error types in the error chain
+pub fn classify(err: &anyhow::Error) -> TaskFailureClass {
+    for cause in err.chain() {
+        if let Some(err) = cause.downcast_ref::<UploadError>() {
+            return classify_upload_error(err);
+        }
+        if let Some(err) = cause.downcast_ref::<ApiError>() {
+            return classify_api_error(err);
+        }
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            return classify_http_error(err);
+        }
+        if let Some(err) = cause.downcast_ref::<std::io::Error>() {
+            return classify_io_error(err);
+        }
+    }
+    TaskFailureClass::Other
+}
+
+fn classify_upload_error(err: &UploadError) -> TaskFailureClass {
+    match err {
+        UploadError::Cancelled => TaskFailureClass::Cancelled,
+        UploadError::FileReadError(_) => TaskFailureClass::LocalIo,
+        UploadError::HttpError(_) | UploadError::ChunkUploadFailed { .. } => {
+            TaskFailureClass::Network
+        }
+        UploadError::SessionCreationFailed(_)
+        | UploadError::SessionExpired
+        | UploadError::CompletionFailed(_)
+        | UploadError::ProviderError { .. }
+        | UploadError::OneDriveChunkOverlap(_)
+        | UploadError::S3Error { .. }
+        | UploadError::QiniuError(_)
+        | UploadError::UpyunError { .. }
+        | UploadError::CallbackFailed(_) => TaskFailureClass::Server,
+        _ => TaskFailureClass::Other,
+    }
+}
+
+fn classify_api_error(err: &ApiError) -> TaskFailureClass {
+    match err {
+        ApiError::LockConflict { .. } => TaskFailureClass::Locked,
+        ApiError::LoginRequired(_)
+        | ApiError::AccessTokenExpired
+        | ApiError::RefreshTokenExpired
+        | ApiError::NoTokensAvailable
+        | ApiError::InvalidToken(_) => TaskFailureClass::Auth,
+        ApiError::RequestError(err) => classify_http_error(err),
+        ApiError::ApiError { .. } | ApiError::BatchError { .. } => TaskFailureClass::Server,
+        _ => TaskFailureClass::Other,
+    }
+}
+
+fn classify_http_error(err: &reqwest::Error) -> TaskFailureClass {
+    match err.status() {
+        Some(status) if status.as_u16() == 401 => TaskFailureClass::Auth,
+        Some(status) if status.as_u16() == 423 => TaskFailureClass::Locked,
+        Some(status) if status.is_server_error() || status.as_u16() == 429 => {
+            TaskFailureClass::Server
+        }
+        Some(_) => TaskFailureClass::Other,
+        None => TaskFailureClass::Network,
+    }
+}
+
+fn classify_io_error(err: &std::io::Error) -> TaskFailureClass {
+    match err.kind() {
+        ErrorKind::TimedOut
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::ConnectionRefused
+        | ErrorKind::BrokenPipe => TaskFailureClass::Network,
+        _ => TaskFailureClass::LocalIo,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy::default_for(TaskKind::Download);
+        assert_eq!(policy.delay(1), Duration::from_secs(5));
+        assert_eq!(policy.delay(2), Duration::from_secs(10));
+        assert_eq!(policy.delay(10), Duration::from_secs(120));
+        assert!(policy.should_retry(2, TaskFailureClass::Network));
+        assert!(!policy.should_retry(3, TaskFailureClass::Network));
+        assert!(!policy.should_retry(1, TaskFailureClass::Auth));
+    }
+
+    #[test]
+    fn overrides_replace_only_given_fields() {
+        let policy = RetryPolicy::default_for(TaskKind::Upload)
+            .with_overrides(&serde_json::json!({ "max_attempts": 2, "retry_on": ["network"] }))
+            .unwrap();
+        assert_eq!(policy.max_attempts, 2);
+        assert_eq!(policy.retry_on, vec![TaskFailureClass::Network]);
+        assert_eq!(policy.base_delay_secs, 5);
+    }
+
+    #[test]
+    fn classifies_through_context() {
+        let err = anyhow::Error::new(UploadError::SessionExpired).context("failed to upload file");
+        assert_eq!(classify(&err), TaskFailureClass::Server);
+        let err = anyhow::Error::new(std::io::Error::from(ErrorKind::PermissionDenied))
+            .context("failed to open file");
+        assert_eq!(classify(&err), TaskFailureClass::LocalIo);
+        assert_eq!(classify(&anyhow::anyhow!("boom")), TaskFailureClass::Other);
+    }
+}
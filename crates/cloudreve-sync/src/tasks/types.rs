@@ -1,18 +1,25 @@
+use crate::inventory::TaskStatus;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum TaskKind {
     Upload,
     Download,
+    /// Data fetched for a placeholder the system asked to hydrate. Runs outside the
+    /// queue's workers and is only tracked by it.
+    Hydrate,
 }
 
 impl TaskKind {
+    pub const ALL: [TaskKind; 3] = [TaskKind::Upload, TaskKind::Download, TaskKind::Hydrate];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             TaskKind::Upload => "upload",
             TaskKind::Download => "download",
+            TaskKind::Hydrate => "hydrate",
         }
     }
 
@@ -20,6 +27,7 @@ impl TaskKind {
         match value {
             "upload" => Some(TaskKind::Upload),
             "download" => Some(TaskKind::Download),
+            "hydrate" => Some(TaskKind::Hydrate),
             _ => None,
         }
     }
@@ -96,7 +104,51 @@ impl TaskPayload {
     }
 }
 
+/// Worker pool and queue size of a drive's task queue
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskQueueStats {
+    pub drive_id: String,
+    /// Current number of workers
+    pub workers: usize,
+    pub min_workers: usize,
+    pub max_workers: usize,
+    /// Tasks being executed, including interactive ones
+    pub running: usize,
+    /// Tasks waiting for a worker
+    pub queued: usize,
+}
+
+/// Task counters and timings of a drive's task queue since it started
 #[derive(Debug, Clone, Serialize)]
+pub struct TaskStatistics {
+    pub drive_id: String,
+    /// Unix timestamp the counters start from
+    pub since: i64,
+    /// One entry per task type, in the order of [`TaskKind::ALL`]
+    pub types: Vec<TaskTypeStatistics>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskTypeStatistics {
+    pub kind: TaskKind,
+    /// Tasks waiting for a worker
+    pub queued: usize,
+    pub running: usize,
+    pub completed: u64,
+    /// Tasks that failed after all retries
+    pub failed: u64,
+    pub timed_out: u64,
+    pub cancelled: u64,
+    /// Share of completed, failed and timed out tasks that failed or timed out (0.0 - 1.0)
+    pub failure_rate: f64,
+    /// Average run time of completed, failed and timed out tasks
+    pub avg_duration_ms: u64,
+    /// Average and longest time a task waited in the queue before it started
+    pub avg_wait_ms: u64,
+    pub max_wait_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskProgress {
     pub task_id: String,
     pub kind: TaskKind,
@@ -175,3 +227,29 @@ impl TaskProgress {
         }
     }
 }
+
+/// A pending or running upload, download or hydration, as listed on the transfers page
+#[derive(Debug, Clone, Serialize)]
+pub struct Transfer {
+    pub task_id: String,
+    pub drive_id: String,
+    pub kind: TaskKind,
+    pub local_path: String,
+    pub status: TaskStatus,
+    /// Progress percentage (0.0 - 1.0)
+    pub progress: f64,
+    pub processed_bytes: i64,
+    pub total_bytes: i64,
+    /// Transfer speed in bytes per second, 0 unless running
+    pub speed_bytes_per_sec: u64,
+    /// Estimated time remaining in seconds
+    pub eta_seconds: Option<u64>,
+    /// Waiting for the drive's syncing to be resumed
+    pub paused: bool,
+    /// Pausing the drive's syncing stops the transfer. Hydrations and other
+    /// interactive tasks keep running.
+    pub pausable: bool,
+    /// The transfer can be stopped with `cancel_task`
+    pub cancelable: bool,
+    pub created_at: i64,
+}
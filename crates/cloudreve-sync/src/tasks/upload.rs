@@ -1,9 +1,23 @@
-use std::{path::PathBuf, str::FromStr, sync::Arc, time::SystemTime};
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicU8, Ordering},
+    },
+    time::SystemTime,
+};
 
 use crate::utils::toast::send_conflict_toast;
 use crate::{
-    drive::{placeholder::CrPlaceholder, utils::local_path_to_cr_uri},
-    inventory::{ConflictState, FileMetadata, InventoryDb},
+    drive::{
+        placeholder::{CrPlaceholder, remote_file_meta},
+        utils::{local_path_to_cr_uri, refresh_shell_state},
+    },
+    inventory::{
+        ConflictKind, ConflictState, FileMetadata, InventoryDb, MetadataWriteBatch,
+        NewConflictRecord,
+    },
     tasks::queue::QueuedTask,
     uploader::{ProgressCallback, ProgressUpdate, UploadParams, Uploader, UploaderConfig},
 };
@@ -13,7 +27,9 @@ use cloudreve_api::{
     ApiError, Client,
     api::ExplorerApi,
     error::ErrorCode,
-    models::explorer::{CreateFileService, FileResponse, FileUpdateService, file_type},
+    models::explorer::{
+        CreateFileService, DeleteUploadSessionService, FileResponse, FileUpdateService, file_type,
+    },
 };
 use dashmap::DashMap;
 use tokio_util::sync::CancellationToken;
@@ -27,6 +43,10 @@ use super::types::TaskProgress;
 pub struct InMemoryProgressReporter {
     task_id: String,
     progress_map: Arc<DashMap<String, TaskProgress>>,
+    /// Placeholder showing the upload progress in Explorer
+    placeholder: Option<CrPlaceholder>,
+    /// Last percentage shown in Explorer, so the shell is only updated once per percent
+    shown_percent: AtomicU8,
 }
 
 impl InMemoryProgressReporter {
@@ -34,8 +54,16 @@ impl InMemoryProgressReporter {
         Self {
             task_id,
             progress_map,
+            placeholder: None,
+            shown_percent: AtomicU8::new(0),
         }
     }
+
+    /// Also show the progress on `placeholder` in Explorer
+    pub fn with_placeholder(mut self, placeholder: CrPlaceholder) -> Self {
+        self.placeholder = Some(placeholder);
+        self
+    }
 }
 
 impl ProgressCallback for InMemoryProgressReporter {
@@ -43,6 +71,19 @@ impl ProgressCallback for InMemoryProgressReporter {
         if let Some(mut entry) = self.progress_map.get_mut(&self.task_id) {
             entry.update_from_progress(&update);
         }
+
+        let Some(placeholder) = self.placeholder.as_ref() else {
+            return;
+        };
+        let percent = (update.progress.clamp(0.0, 1.0) * 100.0) as u8;
+        if self.shown_percent.swap(percent, Ordering::Relaxed) == percent {
+            return;
+        }
+        if let Err(e) =
+            placeholder.update_transfer_progress(Some((update.uploaded, update.total_size)))
+        {
+            debug!(target: "tasks::upload", task_id = %self.task_id, error = ?e, "Failed to update transfer progress");
+        }
     }
 }
 
@@ -85,7 +126,6 @@ impl<'a> UploadTask<'a> {
     }
 
     /// Set the cancellation token
-    #[allow(dead_code)]
     pub fn with_cancel_token(mut self, token: CancellationToken) -> Self {
         self.cancel_token = token;
         self
@@ -148,6 +188,11 @@ impl<'a> UploadTask<'a> {
             warn!(target: "tasks::upload", task_id = %self.task.task_id, local_path = %self.task.payload.local_path_display(), error = ?e, "Failed to clear sync error state");
         }
 
+        // Show the syncing arrows on the file while it uploads
+        if !is_directory {
+            self.set_transfer_progress(Some((0, file_size)));
+        }
+
         // Handle empty files and directories separately
         let upload_res = match (
             is_directory,
@@ -160,12 +205,54 @@ impl<'a> UploadTask<'a> {
             (false, false, _) => self.upload_file_with_uploader().await,
         };
 
+        if !is_directory {
+            self.set_transfer_progress(None);
+        }
         self.handle_error(upload_res).await
     }
 
+    /// Show or clear the upload progress of the file in Explorer
+    fn set_transfer_progress(&self, progress: Option<(u64, u64)>) {
+        let Some(local_file) = self.local_file.as_ref() else {
+            return;
+        };
+        if let Err(e) = local_file.update_transfer_progress(progress) {
+            debug!(target: "tasks::upload", task_id = %self.task.task_id, local_path = %self.task.payload.local_path_display(), error = ?e, "Failed to update transfer progress");
+        }
+    }
+
+    /// Release what an upload stopped midway left behind: its upload session, on the
+    /// server and in the inventory, and the placeholder's sync and transfer state
+    pub async fn cleanup(&mut self) -> Result<()> {
+        let path = self.task.payload.local_path_display();
+        if let Some(session) = self.inventory.get_upload_session_by_path(&path)? {
+            let request = DeleteUploadSessionService {
+                id: session.session_id().to_string(),
+                uri: session.remote_uri.clone(),
+            };
+            if let Err(e) = self.cr_client.delete_upload_session(&request).await {
+                debug!(target: "tasks::upload", task_id = %self.task.task_id, error = %e, "Failed to delete remote upload session");
+            }
+            self.inventory.delete_upload_session(&session.id)?;
+        }
+
+        let placeholder = CrPlaceholder::new(
+            &self.task.payload.local_path,
+            self.sync_path.clone(),
+            Uuid::from_str(self.drive_id)?,
+        );
+        if placeholder.local_file_info.exists {
+            placeholder.update_transfer_progress(None)?;
+            placeholder.update_sync_error_state(true)?;
+        }
+        Ok(())
+    }
+
     async fn handle_error(&mut self, r: Result<()>) -> Result<()> {
         match r {
             Ok(()) => Ok(()),
+            // Stopped on request (e.g. paused by the queue), not a sync error
+            Err(e) if self.cancel_token.is_cancelled() => Err(e),
             Err(e) => {
                 // Check if the error is an ApiError with StaleVersion (40076)
                 // The error might be wrapped with anyhow context, so check the chain
@@ -204,6 +291,15 @@ impl<'a> UploadTask<'a> {
                             "Failed to mark file as conflicted"
                         );
                     }
+                    if let Err(record_err) = self.record_conflict(path_str) {
+                        warn!(
+                            target: "tasks::upload",
+                            task_id = %self.task.task_id,
+                            local_path = %self.task.payload.local_path_display(),
+                            error = ?record_err,
+                            "Failed to record conflict"
+                        );
+                    }
 
                     // Send conflict toast
                     send_conflict_toast(
@@ -324,7 +420,12 @@ impl<'a> UploadTask<'a> {
         let progress = InMemoryProgressReporter::new(
             self.task.task_id.clone(),
             Arc::clone(&self.progress_map),
-        );
+        )
+        .with_placeholder(CrPlaceholder::new(
+            &self.task.payload.local_path,
+            self.sync_path.clone(),
+            Uuid::from_str(self.drive_id)?,
+        ));
 
         // Execute upload
         uploader
@@ -411,19 +512,34 @@ impl<'a> UploadTask<'a> {
             "File uploaded"
         );
 
-        self.local_file = Some(
+        if file.file_type == file_type::FOLDER {
+            self.local_file = Some(
+                self.local_file
+                    .take()
+                    .unwrap()
+                    .with_mark_no_children(true)
+                    .with_remote_file(file),
+            );
             self.local_file
-                .take()
+                .as_mut()
                 .unwrap()
-                .with_mark_no_children(file.file_type == file_type::FOLDER)
-                .with_remote_file(file),
-        );
-
-        self.local_file
-            .as_mut()
-            .unwrap()
-            .commit(self.inventory.clone())
-            .context("failed to commit placeholder")?;
+                .commit(self.inventory.clone())
+                .context("failed to commit placeholder")?;
+        } else {
+            // Files go through the same path as a sync, which also stamps the identity
+            let file_meta = remote_file_meta(
+                &self.task.payload.local_path,
+                Uuid::from_str(self.drive_id)?,
+                file,
+            );
+            let batch = MetadataWriteBatch::new(self.inventory.clone());
+            let mut result =
+                CrPlaceholder::mark_in_sync_batch(&self.sync_path, vec![file_meta], &batch);
+            batch.flush().context("failed to write inventory")?;
+            if let Some((_, e)) = result.failed.pop() {
+                return Err(e).context("failed to commit placeholder");
+            }
+        }
 
         self.local_file
             .as_mut()
@@ -432,4 +548,18 @@ impl<'a> UploadTask<'a> {
             .context("failed to clear sync error state")?;
         Ok(())
     }
+
+    /// Record a content conflict for the conflict center, unless one is already pending
+    fn record_conflict(&self, path: &str) -> Result<()> {
+        if self.inventory.get_pending_conflict_by_path(path)?.is_some() {
+            return Ok(());
+        }
+        let mut record = NewConflictRecord::new(self.drive_id, path, ConflictKind::Content);
+        if let Some(etag) = self.inventory_meta.as_ref().map(|meta| &meta.etag) {
+            record = record.with_local_etag(etag.clone());
+        }
+        self.inventory.insert_conflict(&record)?;
+        refresh_shell_state(&self.task.payload.local_path);
+        Ok(())
+    }
 }
@@ -0,0 +1,383 @@
+//! Updates of the app. The update feed is a JSON document describing the newest
+//! release:
+//!
+//! ```json
+//! {
+//!     "version": "0.2.0",
+//!     "url": "https://example.com/Cloudreve_0.2.0.msixbundle",
+//!     "notes": "Bug fixes",
+//!     "rollout_percent": 20
+//! }
+//! ```
+//!
+//! Releases are rolled out in stages: a device is offered a release once its rollout
+//! bucket for the release is below `rollout_percent`. Packages are installed by the
+//! package manager, which downloads them right away and registers them once the app
+//! is closed, or closes and restarts the app to register them now.
+
+use crate::config::{ConfigManager, ProxyConfig, UpdateConfig};
+use crate::drive::manager::DriveManager;
+use crate::events::EventBroadcaster;
+use crate::tasks::TaskStatus;
+use crate::utils::toast;
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use windows::ApplicationModel::Package;
+use windows::Foundation::Uri;
+use windows::Management::Deployment::{AddPackageOptions, PackageManager};
+use windows::Win32::System::Recovery::{
+    REGISTER_APPLICATION_RESTART_FLAGS, RegisterApplicationRestart,
+};
+use windows_core::{HSTRING, PCWSTR};
+
+/// Version of the running app
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Feed built into release builds, used unless the settings name another one
+const BUILTIN_FEED_URL: Option<&str> = option_env!("CLOUDREVE_UPDATE_FEED");
+
+/// Delay of the first background check, keeping it out of the way of the startup
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+
+/// How often the transfers are checked while a restart waits for them to finish
+const RESTART_POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+const FEED_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The newest release, as described by the update feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    pub version: String,
+    /// URL of the MSIX package or bundle
+    pub url: String,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Percentage of devices the release is offered to
+    #[serde(default = "Release::full_rollout")]
+    pub rollout_percent: u8,
+}
+
+impl Release {
+    fn full_rollout() -> u8 {
+        100
+    }
+}
+
+/// What the update checks found
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UpdateStatus {
+    pub current_version: String,
+    /// Newer release offered to this device
+    pub available: Option<Release>,
+    /// Whether `available` is downloaded and gets installed on the next restart
+    pub ready: bool,
+    /// Unix timestamp of the last successful check
+    pub last_checked: Option<i64>,
+}
+
+/// Checks the update feed and installs newer releases
+pub struct Updater {
+    event_broadcaster: Arc<EventBroadcaster>,
+    status: Mutex<UpdateStatus>,
+    /// Held while a package is downloaded
+    installing: tokio::sync::Mutex<()>,
+}
+
+impl Updater {
+    pub fn new(event_broadcaster: Arc<EventBroadcaster>) -> Arc<Self> {
+        Arc::new(Self {
+            event_broadcaster,
+            status: Mutex::new(UpdateStatus {
+                current_version: CURRENT_VERSION.to_string(),
+                ..Default::default()
+            }),
+            installing: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// What the last check found
+    pub fn status(&self) -> UpdateStatus {
+        lock(&self.status).clone()
+    }
+
+    /// Fetch the update feed and see if a newer release is offered to this device
+    pub async fn check(&self) -> Result<UpdateStatus> {
+        let config = ConfigManager::try_get()
+            .map(|config| config.update_config())
+            .unwrap_or_default();
+        let feed_url = feed_url(&config).context("No update feed is configured")?;
+        let release = fetch_release(&feed_url).await?;
+        let offered = Some(release)
+            .filter(|release| is_newer(&release.version, CURRENT_VERSION) && in_rollout(release));
+
+        let (status, found) = {
+            let mut status = lock(&self.status);
+            let found = offered.as_ref().map(|release| &release.version)
+                != status.available.as_ref().map(|release| &release.version);
+            if found {
+                status.ready = false;
+            }
+            status.available = offered;
+            status.last_checked = Some(chrono::Utc::now().timestamp());
+            (status.clone(), found)
+        };
+        match &status.available {
+            Some(release) if found => {
+                tracing::info!(target: "updater", version = %release.version, "Update available");
+                self.event_broadcaster
+                    .update_available(&release.version, false);
+            }
+            Some(_) => {}
+            None => {
+                tracing::debug!(target: "updater", "No update available");
+            }
+        }
+        Ok(status)
+    }
+
+    /// Download the available release, checking for one first if needed. The package
+    /// is installed the next time the app closes.
+    pub async fn install(&self) -> Result<UpdateStatus> {
+        let _installing = self.installing.lock().await;
+        if Package::Current().is_err() {
+            return Err(anyhow!("Updates can only be installed by the packaged app"));
+        }
+        let status = self.status();
+        if status.ready {
+            return Ok(status);
+        }
+        let release = match status.available {
+            Some(release) => release,
+            None => self
+                .check()
+                .await?
+                .available
+                .context("No update is available")?,
+        };
+
+        tracing::info!(target: "updater", version = %release.version, "Downloading update");
+        let url = release.url.clone();
+        tokio::task::spawn_blocking(move || deploy(&url, false))
+            .await
+            .context("Update task panicked")??;
+
+        let status = {
+            let mut status = lock(&self.status);
+            if status.available.as_ref() == Some(&release) {
+                status.ready = true;
+            }
+            status.clone()
+        };
+        tracing::info!(target: "updater", version = %release.version, "Update ready");
+        self.event_broadcaster
+            .update_available(&release.version, true);
+        toast::send_update_ready_toast(&release.version);
+        Ok(status)
+    }
+
+    /// Install the downloaded release now. The package manager closes the app to
+    /// register the package and starts it again, so the caller shuts the drives down
+    /// first.
+    pub async fn apply(&self) -> Result<()> {
+        let release = {
+            let status = lock(&self.status);
+            status
+                .available
+                .clone()
+                .filter(|_| status.ready)
+                .context("No update is ready to install")?
+        };
+
+        tracing::info!(target: "updater", version = %release.version, "Restarting to install update");
+        unsafe {
+            RegisterApplicationRestart(PCWSTR::null(), REGISTER_APPLICATION_RESTART_FLAGS(0))
+        }
+        .context("Failed to register the app for restart")?;
+        tokio::task::spawn_blocking(move || deploy(&release.url, true))
+            .await
+            .context("Update task panicked")?
+    }
+
+    /// Check for updates in the background at the interval of the settings, downloading
+    /// the releases found
+    pub fn spawn_checker(self: &Arc<Self>) -> JoinHandle<()> {
+        let updater = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(FIRST_CHECK_DELAY).await;
+            loop {
+                let config = ConfigManager::try_get()
+                    .map(|config| config.update_config())
+                    .unwrap_or_default();
+                if config.auto_check && feed_url(&config).is_some() {
+                    updater.check_and_install().await;
+                }
+                let interval = u64::from(config.check_interval_hours.max(1)) * 3600;
+                tokio::time::sleep(Duration::from_secs(interval)).await;
+            }
+        })
+    }
+
+    async fn check_and_install(&self) {
+        match self.check().await {
+            Ok(status) if status.available.is_some() && !status.ready => {
+                if let Err(e) = self.install().await {
+                    tracing::warn!(target: "updater", error = %e, "Failed to download update");
+                }
+            }
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(target: "updater", error = %e, "Failed to check for updates");
+            }
+        }
+    }
+
+    /// Wait until no transfer is running, so that a restart does not interrupt one
+    pub async fn wait_for_idle(drive_manager: &DriveManager) {
+        loop {
+            match drive_manager.get_transfers(None).await {
+                Ok(transfers) if transfers.iter().any(|t| t.status == TaskStatus::Running) => {
+                    tracing::debug!(target: "updater", "Restart deferred while transfers are running");
+                }
+                Ok(_) => return,
+                Err(e) => {
+                    tracing::warn!(target: "updater", error = %e, "Failed to list transfers, restarting anyway");
+                    return;
+                }
+            }
+            tokio::time::sleep(RESTART_POLL_INTERVAL).await;
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn feed_url(config: &UpdateConfig) -> Option<String> {
+    config
+        .feed_url
+        .clone()
+        .or_else(|| BUILTIN_FEED_URL.map(String::from))
+}
+
+async fn fetch_release(feed_url: &str) -> Result<Release> {
+    let client = ProxyConfig::current()
+        .apply(reqwest::Client::builder())
+        .user_agent(crate::USER_AGENT)
+        .timeout(FEED_TIMEOUT)
+        .build()
+        .context("Failed to create HTTP client")?;
+    client
+        .get(feed_url)
+        .send()
+        .await
+        .context("Failed to fetch update feed")?
+        .error_for_status()
+        .context("Update feed returned an error")?
+        .json::<Release>()
+        .await
+        .context("Invalid update feed")
+}
+
+/// Add the package at `url` to the user's packages. Without `force_shutdown`, the
+/// package is registered once the app is closed; with it, the app is closed right
+/// away.
+fn deploy(url: &str, force_shutdown: bool) -> Result<()> {
+    let uri = Uri::CreateUri(&HSTRING::from(url)).context("Invalid package URL")?;
+    let options = AddPackageOptions::new()?;
+    if force_shutdown {
+        options.SetForceTargetAppShutdown(true)?;
+    } else {
+        options.SetDeferRegistrationWhenPackagesAreInUse(true)?;
+    }
+    let result = PackageManager::new()?
+        .AddPackageByUriAsync(&uri, &options)?
+        .get()
+        .context("Failed to install update")?;
+    if result.ExtendedErrorCode()?.is_err() {
+        return Err(anyhow!("Failed to install update: {}", result.ErrorText()?));
+    }
+    Ok(())
+}
+
+/// Numeric parts of a version, ignoring a leading `v` and any pre-release or build
+/// suffix
+fn version_parts(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    core.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Whether `version` is newer than `current`. Missing parts count as zero, so that
+/// `1.2` and `1.2.0` are the same version.
+fn is_newer(version: &str, current: &str) -> bool {
+    let (Some(mut version), Some(mut current)) = (version_parts(version), version_parts(current))
+    else {
+        return false;
+    };
+    let len = version.len().max(current.len());
+    version.resize(len, 0);
+    current.resize(len, 0);
+    version > current
+}
+
+/// Whether `release` is offered to this device
+fn in_rollout(release: &Release) -> bool {
+    release.rollout_percent >= 100
+        || rollout_bucket(&device_id(), &release.version) < release.rollout_percent
+}
+
+/// Bucket from 0 to 99 of a device for a release. A device keeps its bucket while the
+/// rollout of a release widens, and gets a new one for the next release, so the same
+/// devices are not always the first to update.
+fn rollout_bucket(device_id: &str, version: &str) -> u8 {
+    let digest = Sha256::new()
+        .chain_update(device_id)
+        .chain_update([0u8])
+        .chain_update(version)
+        .finalize();
+    let value = u32::from_le_bytes([digest[0], digest[1], digest[2], digest[3]]);
+    (value % 100) as u8
+}
+
+fn device_id() -> String {
+    let computer = std::env::var("COMPUTERNAME").unwrap_or_default();
+    let user = std::env::var("USERNAME").unwrap_or_default();
+    format!("{}\\{}", computer, user)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_versions_compare_numerically() {
+        assert!(is_newer("0.1.10", "0.1.9"));
+        assert!(is_newer("v1.0.0", "0.9.9"));
+        assert!(is_newer("1.2.1", "1.2"));
+        assert!(!is_newer("1.2.0", "1.2"));
+        assert!(!is_newer("1.2.0-beta", "1.2.0"));
+        assert!(!is_newer("0.1.0", "0.1.1"));
+        assert!(!is_newer("latest", "0.1.1"));
+    }
+
+    #[test]
+    fn rollout_bucket_is_stable_per_release() {
+        let bucket = rollout_bucket("PC\\user", "0.2.0");
+        assert!(bucket < 100);
+        assert_eq!(bucket, rollout_bucket("PC\\user", "0.2.0"));
+
+        let release = |rollout_percent| Release {
+            version: "0.2.0".to_string(),
+            url: "https://example.com/Cloudreve.msixbundle".to_string(),
+            notes: None,
+            rollout_percent,
+        };
+        assert!(in_rollout(&release(100)));
+        assert!(!in_rollout(&release(0)));
+    }
+}
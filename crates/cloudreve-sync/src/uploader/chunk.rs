@@ -1,5 +1,6 @@
 //! Chunk-based upload logic with streaming support and progress tracking
 
+use crate::tasks::{TransferDirection, TransferLimits};
 use crate::uploader::UploaderConfig;
 use crate::uploader::encrypt::EncryptionConfig;
 use crate::uploader::error::UploadError;
@@ -22,6 +23,7 @@ use std::time::Duration;
 use tokio::fs::File;
 use tokio::io::{AsyncRead, AsyncSeekExt, BufReader, ReadBuf, SeekFrom};
 use tokio::sync::{Mutex, Notify};
+use tokio::time::Sleep;
 use tokio_util::io::ReaderStream;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
@@ -258,6 +260,54 @@ where
     }
 }
 
+/// A stream wrapper that paces the bytes it yields to the bandwidth limits of a drive
+pub struct ThrottledStream<S> {
+    inner: S,
+    drive_id: String,
+    /// Holds back the next bytes until the ones yielded fit the limits
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl<S> ThrottledStream<S> {
+    pub fn new(inner: S, drive_id: String) -> Self {
+        Self {
+            inner,
+            drive_id,
+            delay: None,
+        }
+    }
+}
+
+impl<S> Stream for ThrottledStream<S>
+where
+    S: Stream<Item = Result<Bytes, io::Error>> + Unpin,
+{
+    type Item = Result<Bytes, io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(delay) = self.delay.as_mut() {
+            if delay.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            self.delay = None;
+        }
+        match Pin::new(&mut self.inner).poll_next(cx) {
+            Poll::Ready(Some(Ok(bytes))) => {
+                let wait = TransferLimits::get().reserve(
+                    &self.drive_id,
+                    TransferDirection::Upload,
+                    bytes.len(),
+                );
+                if !wait.is_zero() {
+                    self.delay = Some(Box::pin(tokio::time::sleep(wait)));
+                }
+                Poll::Ready(Some(Ok(bytes)))
+            }
+            other => other,
+        }
+    }
+}
+
 /// Chunk uploader that handles uploading chunks to different providers
 pub struct ChunkUploader {
     http_client: HttpClient,
@@ -512,7 +562,7 @@ impl ChunkUploader {
                     "Starting chunk upload"
                 );
 
-                // Upload the chunk with retries
+                // Failed chunks are retried with backoff before the whole upload fails
                 let result = upload_chunk_with_retry(
                     &http_client,
                     &cr_client,
@@ -726,13 +776,14 @@ async fn upload_chunk_with_retry(
         let progress_stream = ProgressStream::new(inner_stream, Arc::clone(tracker));
         // Capture bytes counter before stream is consumed
         let bytes_sent_counter = progress_stream.bytes_sent_counter();
+        let throttled_stream = ThrottledStream::new(progress_stream, session.drive_id.clone());
 
         match providers::upload_chunk_with_progress(
             http_client,
             cr_client,
             policy_type,
             chunk,
-            progress_stream,
+            throttled_stream,
             session.as_ref(),
         )
         .await
@@ -56,13 +56,6 @@ pub enum UploadError {
     #[error("Storage provider error ({provider}): {message}")]
     ProviderError { provider: String, message: String },
 
-    /// Maximum retries exceeded
-    #[error("Maximum retries ({max_retries}) exceeded for chunk {chunk_index}")]
-    MaxRetriesExceeded {
-        chunk_index: usize,
-        max_retries: u32,
-    },
-
     /// OneDrive specific: Empty file not supported
     #[error("OneDrive does not support empty file uploads")]
     OneDriveEmptyFile,
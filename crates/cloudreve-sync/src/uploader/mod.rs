@@ -13,9 +13,10 @@ mod session;
 use anyhow::{Context, Result};
 pub use chunk::{ChunkProgress, ChunkUploader};
 pub use error::{UploadError, UploadResult};
-pub use progress::{ProgressCallback, ProgressUpdate};
+pub use progress::{NoOpProgress, ProgressCallback, ProgressUpdate};
 pub use session::UploadSession;
 
+use crate::config::ProxyConfig;
 use crate::inventory::InventoryDb;
 use cloudreve_api::{Client as CrClient, api::ExplorerApi};
 use reqwest::Client as HttpClient;
@@ -93,8 +94,8 @@ impl Uploader {
         inventory: Arc<InventoryDb>,
         config: UploaderConfig,
     ) -> Self {
-        let http_client = HttpClient::builder()
-            .connect_timeout(config.request_timeout)
+        let http_client = ProxyConfig::current()
+            .apply(HttpClient::builder().connect_timeout(config.request_timeout))
             .build()
             .expect("Failed to create HTTP client");
 
@@ -120,7 +121,8 @@ impl Uploader {
     /// - Splitting the file into chunks
     /// - Uploading chunks with retries
     /// - Reporting progress
-    /// - Persisting state for resumability
+    /// - Persisting state for resumability, keeping the session when the upload is
+    ///   cancelled or fails so the next attempt continues from the uploaded chunks
     /// - Completing the upload
     pub async fn upload<P: ProgressCallback + 'static>(
         &self,
@@ -136,12 +138,23 @@ impl Uploader {
         );
 
         // Try to resume existing session or create new one
-        let mut session = match self.get_or_create_session(&params).await? {
+        let (mut session, resumed) = match self.get_or_create_session(&params).await? {
+            Some(mut session) if session_matches(&session, &params) => {
+                info!(
+                    target: "uploader",
+                    session_id = %session.session_id(),
+                    pending_chunks = session.pending_chunks().len(),
+                    "Resuming existing upload session"
+                );
+                session.task_id = params.task_id.clone();
+                self.save_session(&session)?;
+                (session, true)
+            }
             Some(session) => {
                 info!(
                     target: "uploader",
                     session_id = %session.session_id(),
-                    "Found existing upload session, removing it"
+                    "Existing upload session does not match the file, removing it"
                 );
                 if let Err(e) = self.delete_remote_session(&session).await {
                     warn!(
@@ -152,14 +165,14 @@ impl Uploader {
                     );
                 }
                 self.cleanup_session(&session).await?;
-                self.create_session(&params).await?
+                (self.create_session(&params).await?, false)
             }
             None => {
                 debug!(
                     target: "uploader",
                     "No existing session found, creating new one"
                 );
-                self.create_session(&params).await?
+                (self.create_session(&params).await?, false)
             }
         };
 
@@ -190,6 +203,20 @@ impl Uploader {
                 );
                 Ok(())
             }
+            Err(e) if self.cancel_token.is_cancelled() || !resumed => {
+                // Keep the session, so a paused, preempted or retried task continues
+                // from the uploaded chunks. A session that fails again after resuming
+                // is dropped below, so the next attempt starts over.
+                info!(
+                    target: "uploader",
+                    local_path = %params.local_path.display(),
+                    pending_chunks = session.pending_chunks().len(),
+                    error = %e,
+                    "Upload stopped, keeping session for resume"
+                );
+                self.save_session(&session)?;
+                Err(e.into())
+            }
             Err(e) => {
                 error!(
                     target: "uploader",
@@ -344,6 +371,13 @@ impl Uploader {
         providers::complete_upload(&self.http_client, &self.cr_client, session).await
     }
 
+    /// Persist the chunk progress of a session, so the upload can be resumed
+    fn save_session(&self, session: &UploadSession) -> UploadResult<()> {
+        self.inventory
+            .update_upload_session(session)
+            .map_err(|e| UploadError::DatabaseError(e.to_string()))
+    }
+
     /// Clean up session from database
     async fn cleanup_session(&self, session: &UploadSession) -> UploadResult<()> {
         self.inventory
@@ -369,3 +403,13 @@ impl Uploader {
         Ok(())
     }
 }
+
+/// Whether `session` was created for the file described by `params`: same destination and
+/// size, and the file was not modified after the session started
+fn session_matches(session: &UploadSession, params: &UploadParams) -> bool {
+    session.remote_uri == params.remote_uri
+        && session.file_size == params.file_size
+        && params
+            .last_modified
+            .is_none_or(|modified| modified <= session.created_at * 1000)
+}
@@ -133,7 +133,6 @@ pub trait ProgressCallback: Send + Sync {
 }
 
 /// No-op progress callback implementation
-#[allow(dead_code)]
 pub struct NoOpProgress;
 
 impl ProgressCallback for NoOpProgress {
@@ -32,4 +32,8 @@ impl AppRoot {
     pub fn image_path_general(&self) -> String {
         format!("{}\\Images", self.0.as_str())
     }
+
+    pub fn property_schema_path(&self) -> String {
+        format!("{}\\Cloudreve.propdesc", self.0.as_str())
+    }
 }
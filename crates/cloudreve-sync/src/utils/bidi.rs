@@ -0,0 +1,28 @@
+//! Bidirectional text helpers for strings shown by the Explorer integration.
+//!
+//! File names, user names and dates interpolated into a translated sentence keep their
+//! own direction when wrapped in isolates, so a right-to-left UI does not reorder them
+//! together with the surrounding text.
+
+use std::fmt::Display;
+
+/// U+2068 FIRST STRONG ISOLATE
+const FSI: char = '\u{2068}';
+/// U+2069 POP DIRECTIONAL ISOLATE
+const PDI: char = '\u{2069}';
+
+/// Wrap `value` in a first-strong isolate, for interpolation into localized text
+pub fn isolate(value: impl Display) -> String {
+    format!("{FSI}{value}{PDI}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn isolate_wraps_value() {
+        assert_eq!(isolate("report.pdf"), "\u{2068}report.pdf\u{2069}");
+        assert_eq!(isolate(42), "\u{2068}42\u{2069}");
+    }
+}
@@ -0,0 +1,64 @@
+use anyhow::{Context, Result, anyhow};
+use std::{thread, time::Duration};
+use windows::Win32::{
+    Foundation::{HANDLE, HWND},
+    System::{
+        DataExchange::{CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData},
+        Memory::{GMEM_MOVEABLE, GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock},
+        Ole::CF_UNICODETEXT,
+    },
+};
+
+/// Attempts to open the clipboard while another application holds it
+const OPEN_ATTEMPTS: u32 = 5;
+
+/// Replace the clipboard content with `text`
+pub fn set_text(text: &str) -> Result<()> {
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+
+    open_clipboard()?;
+    let result = unsafe { write_text(&wide) };
+    unsafe {
+        let _ = CloseClipboard();
+    }
+    result
+}
+
+fn open_clipboard() -> Result<()> {
+    let mut attempt = 1;
+    loop {
+        match unsafe { OpenClipboard(HWND::default()) } {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= OPEN_ATTEMPTS => {
+                return Err(e).context("failed to open clipboard");
+            }
+            Err(_) => {
+                attempt += 1;
+                thread::sleep(Duration::from_millis(20));
+            }
+        }
+    }
+}
+
+/// Copy the NUL-terminated UTF-16 `wide` into the open clipboard. The clipboard owns the
+/// memory once it accepted it.
+unsafe fn write_text(wide: &[u16]) -> Result<()> {
+    unsafe {
+        EmptyClipboard().context("failed to empty clipboard")?;
+        let memory = GlobalAlloc(GMEM_MOVEABLE, std::mem::size_of_val(wide))
+            .context("failed to allocate clipboard memory")?;
+        let target = GlobalLock(memory) as *mut u16;
+        if target.is_null() {
+            let _ = GlobalFree(memory);
+            return Err(anyhow!("failed to lock clipboard memory"));
+        }
+        std::ptr::copy_nonoverlapping(wide.as_ptr(), target, wide.len());
+        let _ = GlobalUnlock(memory);
+
+        if let Err(e) = SetClipboardData(CF_UNICODETEXT.0 as u32, HANDLE(memory.0)) {
+            let _ = GlobalFree(memory);
+            return Err(e).context("failed to set clipboard data");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use std::{ptr, slice};
+use widestring::U16CString;
+use windows::Win32::Foundation::ERROR_NOT_FOUND;
+use windows::Win32::Security::Credentials::{
+    CRED_FLAGS, CRED_PERSIST_LOCAL_MACHINE, CRED_TYPE_GENERIC, CREDENTIALW, CredDeleteW, CredFree,
+    CredReadW, CredWriteW,
+};
+use windows::core::{PCWSTR, PWSTR};
+
+/// Prefix for every credential target written by the app
+const TARGET_PREFIX: &str = "Cloudreve.Sync/";
+
+fn target_name(name: &str) -> Result<U16CString> {
+    U16CString::from_str(format!("{}{}", TARGET_PREFIX, name))
+        .context("Invalid credential target name")
+}
+
+/// Read a secret. Returns `None` if it has never been stored.
+pub fn read_secret(name: &str) -> Result<Option<Vec<u8>>> {
+    let target = target_name(name)?;
+    let mut credential: *mut CREDENTIALW = ptr::null_mut();
+    match unsafe {
+        CredReadW(
+            PCWSTR::from_raw(target.as_ptr()),
+            CRED_TYPE_GENERIC,
+            0,
+            &mut credential,
+        )
+    } {
+        Ok(()) => {
+            // Safety: CredReadW succeeded, so `credential` points to a valid CREDENTIALW
+            // whose blob stays alive until CredFree
+            let secret = unsafe {
+                let credential_ref = &*credential;
+                let secret = slice::from_raw_parts(
+                    credential_ref.CredentialBlob,
+                    credential_ref.CredentialBlobSize as usize,
+                )
+                .to_vec();
+                CredFree(credential as *const _);
+                secret
+            };
+            Ok(Some(secret))
+        }
+        Err(e) if e.code() == ERROR_NOT_FOUND.to_hresult() => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read credential {}", name)),
+    }
+}
+
+/// Store a secret for the current user on this machine, replacing any previous value
+pub fn write_secret(name: &str, secret: &[u8]) -> Result<()> {
+    let mut target = target_name(name)?;
+    let mut blob = secret.to_vec();
+    let credential = CREDENTIALW {
+        Flags: CRED_FLAGS(0),
+        Type: CRED_TYPE_GENERIC,
+        TargetName: PWSTR::from_raw(target.as_mut_ptr()),
+        CredentialBlobSize: blob.len() as u32,
+        CredentialBlob: blob.as_mut_ptr(),
+        Persist: CRED_PERSIST_LOCAL_MACHINE,
+        ..Default::default()
+    };
+    unsafe { CredWriteW(&credential, 0) }
+        .with_context(|| format!("Failed to write credential {}", name))
+}
+
+/// Remove a secret. Removing a secret that does not exist is not an error.
+pub fn delete_secret(name: &str) -> Result<()> {
+    let target = target_name(name)?;
+    match unsafe { CredDeleteW(PCWSTR::from_raw(target.as_ptr()), CRED_TYPE_GENERIC, 0) } {
+        Ok(()) => Ok(()),
+        Err(e) if e.code() == ERROR_NOT_FOUND.to_hresult() => Ok(()),
+        Err(e) => Err(e).with_context(|| format!("Failed to delete credential {}", name)),
+    }
+}
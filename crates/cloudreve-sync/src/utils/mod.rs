@@ -1,2 +1,6 @@
 pub mod app;
+pub mod bidi;
+pub mod clipboard;
+pub mod credential_store;
+pub mod notifications;
 pub mod toast;
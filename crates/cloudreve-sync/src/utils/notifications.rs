@@ -0,0 +1,233 @@
+//! Decides which events are shown as toasts and how, following the notification
+//! settings: the toggle of each event type, quiet hours, muted drives and the alert
+//! style of each category.
+
+use crate::config::{AlertCategory, AlertStyle, AppConfig, ConfigManager};
+use crate::events::Event;
+use crate::utils::bidi::isolate;
+use chrono::{Local, NaiveTime};
+use std::path::Path;
+
+/// The notification settings at one point in time
+pub struct NotificationPolicy {
+    config: AppConfig,
+}
+
+impl NotificationPolicy {
+    /// The policy of the current settings
+    pub fn current() -> Self {
+        Self::new(
+            ConfigManager::try_get()
+                .map(|config| config.get_config())
+                .unwrap_or_default(),
+        )
+    }
+
+    pub fn new(config: AppConfig) -> Self {
+        Self { config }
+    }
+
+    /// Whether the toast for the event named `event_name` (see [`Event::name`]) of
+    /// `drive_id` is shown at the local time `now`
+    pub fn allows(&self, event_name: &str, drive_id: Option<&str>, now: NaiveTime) -> bool {
+        let notifications = &self.config.notifications;
+        if !notifications.toast_events.iter().any(|n| n == event_name) {
+            return false;
+        }
+        if drive_id.is_some_and(|id| notifications.muted_drives.iter().any(|d| d == id)) {
+            return false;
+        }
+        !notifications
+            .quiet_hours
+            .as_ref()
+            .is_some_and(|quiet_hours| quiet_hours.contains(now))
+    }
+
+    /// How the event named `event_name` of `drive_id` gets the user's attention at
+    /// the local time `now`, [`AlertStyle::Off`] if [`NotificationPolicy::allows`]
+    /// rejects it
+    pub fn alert_style(
+        &self,
+        event_name: &str,
+        drive_id: Option<&str>,
+        now: NaiveTime,
+    ) -> AlertStyle {
+        if !self.allows(event_name, drive_id, now) {
+            return AlertStyle::Off;
+        }
+        AlertCategory::of(event_name)
+            .map(|category| self.config.notifications.alert_style(category))
+            .unwrap_or_default()
+    }
+
+    /// [`NotificationPolicy::allows`] at the current local time
+    pub fn allows_now(&self, event_name: &str, drive_id: Option<&str>) -> bool {
+        self.allows(event_name, drive_id, Local::now().time())
+    }
+
+    /// [`NotificationPolicy::alert_style`] at the current local time
+    pub fn alert_style_now(&self, event_name: &str, drive_id: Option<&str>) -> AlertStyle {
+        self.alert_style(event_name, drive_id, Local::now().time())
+    }
+}
+
+/// What the user is alerted of for an event
+pub struct EventAlert {
+    /// Tag of the toast, a newer alert with the same tag replaces it
+    pub tag: String,
+    pub title: String,
+    pub message: String,
+    /// Credential expiry and conflicts have their own toasts with actions, sent where
+    /// they are detected rather than from the event
+    pub has_own_toast: bool,
+}
+
+impl EventAlert {
+    fn new(tag: String, title: impl ToString, message: impl ToString) -> Self {
+        Self {
+            tag,
+            title: title.to_string(),
+            message: message.to_string(),
+            has_own_toast: false,
+        }
+    }
+}
+
+/// The alert of `event`, `None` for events without one
+pub fn event_alert(event: &Event) -> Option<EventAlert> {
+    let alert = match event {
+        Event::UploadFailed {
+            task_id,
+            local_path,
+            error,
+            ..
+        } => EventAlert::new(
+            format!("upload_failed_{}", task_id),
+            t!("uploadFailedToastTitle"),
+            t!("uploadFailedToastMessage", "file" => isolate(file_name(local_path)), "error" => isolate(error)),
+        ),
+        Event::FileDownloadFinished {
+            task_id,
+            local_path,
+            error: Some(error),
+            ..
+        } => EventAlert::new(
+            format!("download_failed_{}", task_id),
+            t!("downloadFailedToastTitle"),
+            t!("downloadFailedToastMessage", "file" => isolate(file_name(local_path)), "error" => isolate(error)),
+        ),
+        Event::QuotaWarning {
+            drive_id,
+            used,
+            total,
+        } => EventAlert::new(
+            format!("quota_warning_{}", drive_id),
+            t!("quotaWarningToastTitle"),
+            t!("quotaWarningToastMessage", "percent" => used * 100 / (*total).max(1)),
+        ),
+        Event::ShellServiceHealthChanged {
+            healthy: false,
+            error,
+            ..
+        } => EventAlert::new(
+            "shell_service".to_string(),
+            t!("shellServiceStoppedToastTitle"),
+            t!("shellServiceStoppedToastMessage", "error" => isolate(error.as_deref().unwrap_or_default())),
+        ),
+        Event::ConflictDetected {
+            conflict_id,
+            local_path,
+            ..
+        } => EventAlert {
+            has_own_toast: true,
+            ..EventAlert::new(
+                format!("conflict_{}", conflict_id),
+                t!("conflictToastTitle"),
+                file_name(local_path),
+            )
+        },
+        Event::CredentialExpired {
+            drive_id,
+            drive_name,
+        } => EventAlert {
+            has_own_toast: true,
+            ..EventAlert::new(
+                format!("token_expiry_{}", drive_id),
+                t!("credentialExpiredTitle"),
+                t!("credentialExpiredMessage", "drive" => isolate(drive_name)),
+            )
+        },
+        _ => return None,
+    };
+    Some(alert)
+}
+
+fn file_name(local_path: &str) -> &str {
+    Path::new(local_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(local_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::QuietHours;
+
+    #[test]
+    fn policy_follows_toggles_quiet_hours_and_muted_drives() {
+        let mut config = AppConfig::default();
+        config
+            .notifications
+            .toast_events
+            .retain(|name| name != "ConflictDetected");
+        config.notifications.muted_drives = vec!["muted".to_string()];
+        config.notifications.quiet_hours = Some(QuietHours {
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+        });
+        let policy = NotificationPolicy::new(config);
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        let night = NaiveTime::from_hms_opt(23, 0, 0).unwrap();
+
+        assert!(policy.allows("UploadFailed", Some("drive"), noon));
+        assert!(policy.allows("CredentialExpired", Some("drive"), noon));
+        assert!(!policy.allows("ConflictDetected", Some("drive"), noon));
+        assert!(!policy.allows("FileSynced", Some("drive"), noon));
+        assert!(!policy.allows("UploadFailed", Some("muted"), noon));
+        assert!(!policy.allows("UploadFailed", Some("drive"), night));
+        assert!(policy.allows("ShellServiceHealthChanged", None, noon));
+    }
+
+    #[test]
+    fn alert_style_follows_the_category() {
+        let mut config = AppConfig::default();
+        config
+            .notifications
+            .alerts
+            .insert(AlertCategory::Transfers, AlertStyle::Silent);
+        config
+            .notifications
+            .alerts
+            .insert(AlertCategory::Storage, AlertStyle::TrayFlash);
+        let policy = NotificationPolicy::new(config);
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+
+        assert_eq!(
+            policy.alert_style("UploadFailed", None, noon),
+            AlertStyle::Silent
+        );
+        assert_eq!(
+            policy.alert_style("QuotaWarning", None, noon),
+            AlertStyle::TrayFlash
+        );
+        assert_eq!(
+            policy.alert_style("ConflictDetected", None, noon),
+            AlertStyle::Toast
+        );
+        assert_eq!(
+            policy.alert_style("FileSynced", None, noon),
+            AlertStyle::Off
+        );
+    }
+}
@@ -1,128 +1,175 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::OnceLock};
 
 use base64::{Engine as _, engine::general_purpose::URL_SAFE};
 use win32_notif::{
-    NotificationBuilder, ToastsNotifier,
+    ManageNotification, NotificationBuilder, NotifierHandle,
     notification::{
         actions::{ActionButton, Input, input::Selection},
-        visual::{Image, Placement, Text, text::HintStyle},
+        visual::{Image, Placement},
     },
 };
 
-use crate::config::ConfigManager;
+use crate::config::AlertStyle;
+use crate::utils::notifications::NotificationPolicy;
 
 const APP_NAME: &str = "Cloudreve.Sync";
 
-pub fn send_general_text_toast(title: &str, message: &str) {
-    let notifier = ToastsNotifier::new(APP_NAME).unwrap();
+/// Shared notifier, toasts are built and shown on its thread so any task can send them.
+fn notifier() -> Option<&'static NotifierHandle> {
+    static NOTIFIER: OnceLock<Option<NotifierHandle>> = OnceLock::new();
+    NOTIFIER
+        .get_or_init(|| match NotifierHandle::new(APP_NAME) {
+            Ok(notifier) => Some(notifier),
+            Err(e) => {
+                tracing::error!(target: "toast", error = ?e, "Failed to create toast notifier");
+                None
+            }
+        })
+        .as_ref()
+}
+
+fn show<F>(tag: &str, group: &str, content: F)
+where
+    F: FnOnce() -> NotificationBuilder + Send + 'static,
+{
+    let Some(notifier) = notifier() else {
+        return;
+    };
+    if let Err(e) = notifier.show(0, tag, group, content) {
+        tracing::error!(target: "toast", error = ?e, "Failed to send toast");
+    }
+}
+
+/// Show the toast built by `content` in the given alert style: with a popup for
+/// [`AlertStyle::Toast`], straight into the action center for [`AlertStyle::Silent`].
+/// Other styles show nothing.
+fn show_styled<F>(style: AlertStyle, tag: &str, group: &str, content: F)
+where
+    F: FnOnce() -> NotificationBuilder + Send + 'static,
+{
+    match style {
+        AlertStyle::Toast => show(tag, group, content),
+        AlertStyle::Silent => {
+            let Some(notifier) = notifier() else {
+                return;
+            };
+            let (tag, group) = (tag.to_string(), group.to_string());
+            let result = notifier.run(move |notifier| {
+                let notification = content().build(0, notifier, &tag, &group)?;
+                notification.set_suppress_popup(true)?;
+                notification.show()
+            });
+            if let Err(e) = result {
+                tracing::error!(target: "toast", error = ?e, "Failed to send silent toast");
+            }
+        }
+        AlertStyle::TrayFlash | AlertStyle::Off => {}
+    }
+}
 
-    let notif = NotificationBuilder::new()
-        .visual(
-            Text::create(1, title)
-                .with_align_center(true)
-                .with_wrap(true)
-                .with_style(HintStyle::Title),
-        )
-        .visual(
-            Text::create(2, message)
-                .with_align_center(true)
-                .with_wrap(true)
-                .with_style(HintStyle::Body),
-        )
-        .build(0, &notifier, "01", "readme")
-        .unwrap();
+pub fn send_general_text_toast(title: &str, message: &str) {
+    let (title, message) = (title.to_string(), message.to_string());
+    show("01", "readme", move || {
+        NotificationBuilder::basic(&title, &message)
+    });
+}
 
-    notif.show().unwrap();
+/// Send a toast for an event in the given alert style, replacing the toast with the
+/// same tag. The caller checks the notification policy.
+pub fn send_event_toast(style: AlertStyle, tag: &str, title: &str, message: &str) {
+    let (title, message) = (title.to_string(), message.to_string());
+    show_styled(style, tag, "events", move || {
+        NotificationBuilder::basic(&title, &message)
+    });
 }
 
 /// Send a toast notification for token expiry.
 /// Uses drive_id as the tag to prevent duplicate notifications for the same drive.
-/// Respects the notify_credential_expired config setting.
+/// Respects the notification policy and the alert style of credentials.
 pub fn send_token_expiry_toast(drive_id: &str, title: &str, message: &str) {
-    // Check if credential expired notifications are enabled
-    if let Some(config) = ConfigManager::try_get() {
-        if !config.notify_credential_expired() {
-            tracing::debug!(target: "toast", "Token expiry notification suppressed by config");
-            return;
-        }
+    let style = NotificationPolicy::current().alert_style_now("CredentialExpired", Some(drive_id));
+    if !matches!(style, AlertStyle::Toast | AlertStyle::Silent) {
+        tracing::debug!(target: "toast", "Token expiry notification suppressed by config");
+        return;
     }
 
-    let notifier = ToastsNotifier::new(APP_NAME).unwrap();
+    let (title, message) = (title.to_string(), message.to_string());
+    let tag = format!("token_expiry_{}", drive_id);
+    show_styled(style, &tag, "token_expiry", move || {
+        NotificationBuilder::basic(&title, &message)
+            .visual(
+                Image::create(3, "ms-appx:///Images/warning.svg")
+                    .with_placement(Placement::AppLogoOverride),
+            )
+            .with_launch("action=settings")
+    });
+}
 
-    let notif = NotificationBuilder::new()
-        .visual(
-            Text::create(1, title)
-                .with_align_center(true)
-                .with_wrap(true)
-                .with_style(HintStyle::Title),
-        )
-        .visual(
-            Text::create(2, message)
-                .with_align_center(true)
-                .with_wrap(true)
-                .with_style(HintStyle::Body),
-        )
-        .visual(
-            Image::create(3, "ms-appx:///Images/warning.svg")
-                .with_placement(Placement::AppLogoOverride)
-        )
-        .with_launch("action=settings")
-        .build(0, &notifier, &format!("token_expiry_{}", drive_id), "token_expiry")
-        .unwrap();
+/// Send a toast offering to restart the app to install the downloaded update `version`
+pub fn send_update_ready_toast(version: &str) {
+    let title = t!("updateReadyToastTitle").to_string();
+    let message = t!("updateReadyToastMessage", "version" => version).to_string();
+    let restart = t!("restartToUpdate").to_string();
+    let dismiss = t!("dismiss").to_string();
 
-    notif.show().unwrap();
+    show("update_ready", "update", move || {
+        NotificationBuilder::basic(&title, &message).actions(vec![
+            Box::new(ActionButton::create(&restart).with_id("action=restart_update")),
+            Box::new(ActionButton::create(&dismiss).with_id("action=dismiss")),
+        ])
+    });
 }
 
 /// Send a toast notification for file conflicts.
-/// Respects the notify_file_conflict config setting.
+/// Respects the notification policy and the alert style of conflicts.
 pub fn send_conflict_toast(drive_id: &str, path: &PathBuf, inventory_id: i64) {
-    // Check if file conflict notifications are enabled
-    if let Some(config) = ConfigManager::try_get() {
-        if !config.notify_file_conflict() {
-            tracing::debug!(target: "toast", "Conflict notification suppressed by config");
-            return;
-        }
+    let style = NotificationPolicy::current().alert_style_now("ConflictDetected", Some(drive_id));
+    if !matches!(style, AlertStyle::Toast | AlertStyle::Silent) {
+        tracing::debug!(target: "toast", "Conflict notification suppressed by config");
+        return;
     }
 
-    let notifier = ToastsNotifier::new(APP_NAME).unwrap();
+    let title = t!("conflictToastTitle").to_string();
+    let file_name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_str()
+        .unwrap_or_default()
+        .to_string();
+    let select_action = t!("selectAction").to_string();
+    let accept_incoming = t!("acceptIncomming").to_string();
+    let overwrite_remote = t!("overwriteRemote").to_string();
+    let save_as_new = t!("saveAsNew").to_string();
+    let resolve = t!("resolveWithAction").to_string();
+    let resolve_tooltip = t!("resolveTooltip").to_string();
+    let dismiss = t!("dismiss").to_string();
+    let resolve_id = format!(
+        "action=resolve&drive_id={}&file_id={}&path={}",
+        drive_id,
+        inventory_id,
+        URL_SAFE.encode(path.display().to_string())
+    );
+    let tag = format!("conflict_{}", inventory_id);
 
-    let notif = NotificationBuilder::new()
-        .visual(
-            Text::create(1, t!("conflictToastTitle").as_ref())
-                .with_align_center(true)
-                .with_wrap(true)
-                .with_style(HintStyle::Title),
-        )
-        .visual(
-            Text::create(2, path.file_name().unwrap_or_default().to_str().unwrap_or_default())
-                .with_align_center(true)
-                .with_wrap(true)
-                .with_style(HintStyle::Body),
-        )
-        .actions(vec![
+    show_styled(style, &tag, "readme", move || {
+        NotificationBuilder::basic(&title, &file_name).actions(vec![
             Box::new(Input::create_selection_input(
                 "selection",
-                t!("selectAction").as_ref(),
-                t!("selectAction").as_ref(),
+                &select_action,
+                &select_action,
                 vec![
-                    Selection::new("keep_remote", t!("acceptIncomming").as_ref()),
-                    Selection::new("overwrite_remote", t!("overwriteRemote").as_ref()),
-                    Selection::new("save_as_new", t!("saveAsNew").as_ref()),
+                    Selection::new("keep_remote", &accept_incoming),
+                    Selection::new("overwrite_remote", &overwrite_remote),
+                    Selection::new("save_as_new", &save_as_new),
                 ],
                 "keep_remote",
             )),
             Box::new(
-                ActionButton::create(t!("resolveWithAction").as_ref())
-                    .with_id(&format!(
-                        "action=resolve&drive_id={}&file_id={}&path={}",
-                        drive_id, inventory_id, URL_SAFE.encode(path.display().to_string())
-                    ))
-                    .with_tooltip(t!("resolveTooltip").as_ref()),
+                ActionButton::create(&resolve)
+                    .with_id(&resolve_id)
+                    .with_tooltip(&resolve_tooltip),
             ),
-            Box::new(ActionButton::create(t!("dismiss").as_ref()).with_id("action=dismiss")),
+            Box::new(ActionButton::create(&dismiss).with_id("action=dismiss")),
         ])
-        .build(0, &notifier, &format!("conflict_{}", inventory_id), "readme")
-        .unwrap();
-
-    notif.show().unwrap();
+    });
 }
@@ -70,6 +70,27 @@ macro_rules! from_impl {
 pub enum NotifError {
   WindowsCore(windows::core::Error),
   DurationTooLong,
+  /// The toast could not be updated, e.g. because it is not a data bound toast
+  UpdateFailed,
+  /// No toast collection with the given id exists
+  CollectionNotFound,
+  /// The XML of a toast element is malformed, e.g. an attribute value with a stray quote
+  InvalidContent {
+    element: &'static str,
+    reason: String,
+  },
+  /// Windows rejected the generated XML, e.g. because an element is not allowed there
+  Rejected {
+    /// The call that failed, e.g. `LoadXml` or `CreateToastNotification`
+    stage: &'static str,
+    error: windows::core::Error,
+    xml: String,
+    /// The path of the element Windows reported the error at, e.g. `toast/visual/binding/text`
+    path: Option<String>,
+  },
+  /// The thread of a [`NotifierHandle`] could not be started or a job did not finish, e.g. because
+  /// it panicked
+  NotifierClosed,
   UnknownAndImpossible,
 }
 
@@ -0,0 +1,132 @@
+use quick_xml::events::BytesStart;
+use windows::{
+  core::HSTRING,
+  UI::Notifications::{BadgeNotification, BadgeUpdateManager, BadgeUpdater},
+};
+
+use crate::{
+  notification::xml::{self, ToastXml},
+  NotifError,
+};
+
+/// Learn more here
+/// <https://learn.microsoft.com/en-us/windows/apps/design/shell/tiles-and-notifications/badges>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Badge {
+  /// A number, shown as `99+` above 99, `0` clears the badge
+  Count(u32),
+  Glyph(BadgeGlyph),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadgeGlyph {
+  Activity,
+  Alarm,
+  Alert,
+  Attention,
+  Available,
+  Away,
+  Busy,
+  Error,
+  NewMessage,
+  Paused,
+  Playing,
+  Unavailable,
+}
+
+impl ToString for BadgeGlyph {
+  fn to_string(&self) -> String {
+    match self {
+      BadgeGlyph::Activity => "activity",
+      BadgeGlyph::Alarm => "alarm",
+      BadgeGlyph::Alert => "alert",
+      BadgeGlyph::Attention => "attention",
+      BadgeGlyph::Available => "available",
+      BadgeGlyph::Away => "away",
+      BadgeGlyph::Busy => "busy",
+      BadgeGlyph::Error => "error",
+      BadgeGlyph::NewMessage => "newMessage",
+      BadgeGlyph::Paused => "paused",
+      BadgeGlyph::Playing => "playing",
+      BadgeGlyph::Unavailable => "unavailable",
+    }
+    .to_string()
+  }
+}
+
+impl Badge {
+  /// Gets the badge document that [`BadgeNotifier::set`] loads
+  pub fn to_xml_string(&self) -> Result<String, NotifError> {
+    let value = match self {
+      Badge::Count(count) => count.to_string(),
+      Badge::Glyph(glyph) => glyph.to_string(),
+    };
+
+    let mut xml = ToastXml::new();
+    xml.empty(BytesStart::new("badge").with_attributes([("value", value.as_str())]))?;
+    Ok(xml.finish())
+  }
+}
+
+/// Shows a badge on the taskbar button and the tile of the application
+///
+/// Badges need the application to have package identity.
+///
+/// # Example
+/// ```rust
+/// use win32_notif::{Badge, BadgeNotifier};
+///
+/// fn main() {
+///   let notifier = BadgeNotifier::new("Microsoft.Windows.Explorer").unwrap();
+///   notifier.set(&Badge::Count(3)).unwrap();
+/// }
+/// ```
+pub struct BadgeNotifier {
+  _inner: BadgeUpdater,
+}
+
+impl BadgeNotifier {
+  pub fn new(app_id: &str) -> Result<Self, NotifError> {
+    Ok(Self {
+      _inner: BadgeUpdateManager::CreateBadgeUpdaterForApplicationWithId(&HSTRING::from(app_id))?,
+    })
+  }
+
+  /// Replaces the badge
+  pub fn set(&self, badge: &Badge) -> Result<(), NotifError> {
+    let content = badge.to_xml_string()?;
+    let doc = xml::load(&content)?;
+
+    let notification = BadgeNotification::CreateBadgeNotification(&doc)
+      .map_err(|e| xml::rejected("CreateBadgeNotification", e, &content))?;
+    Ok(self._inner.Update(&notification)?)
+  }
+
+  /// Removes the badge
+  pub fn clear(&self) -> Result<(), NotifError> {
+    Ok(self._inner.Clear()?)
+  }
+
+  pub unsafe fn as_raw(&self) -> &BadgeUpdater {
+    &self._inner
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Badge, BadgeGlyph};
+
+  #[test]
+  fn badge_xml() {
+    assert_eq!(
+      Badge::Count(12).to_xml_string().unwrap(),
+      r#"<badge value="12"/>"#
+    );
+    assert_eq!(
+      Badge::Glyph(BadgeGlyph::NewMessage)
+        .to_xml_string()
+        .unwrap(),
+      r#"<badge value="newMessage"/>"#
+    );
+  }
+}
@@ -17,6 +17,11 @@ impl NotificationDataSet {
     Ok(self._inner.Values()?.Insert(&k.into(), &v.into())?)
   }
 
+  /// Updates with a sequence number lower than the one of the toast are ignored
+  pub fn set_sequence_number(&self, sequence: u32) -> Result<(), NotifError> {
+    Ok(self._inner.SetSequenceNumber(sequence)?)
+  }
+
   pub fn inner_win32_type(&self) -> &NotificationData {
     &self._inner
   }
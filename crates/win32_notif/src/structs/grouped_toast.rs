@@ -0,0 +1,106 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+  notifier::ToastsManager, ManageNotification, NotifError, NotificationBuilder, ToastsNotifier,
+};
+
+const SUMMARY_TAG: &str = "summary";
+
+/// Keeps at most `limit` toasts of a group in the notification center
+///
+/// Older toasts are removed and counted in a single summary toast, e.g.
+/// "and 12 more files synced", so bulk operations do not flood the notification
+/// center. The summary is not counted in the limit and only pops up when it is
+/// first shown.
+///
+/// # Example
+/// ```rust
+/// use win32_notif::{notification::visual::Text, NotificationBuilder, ToastGroup, ToastsNotifier};
+///
+/// fn main() {
+///   let notifier = ToastsNotifier::new("Microsoft.Windows.Explorer").unwrap();
+///   let mut group = ToastGroup::new(notifier, "synced", 3, |count| {
+///     NotificationBuilder::new().visual(Text::create(1, &format!("and {count} more files synced")))
+///   });
+///
+///   for file in ["a.txt", "b.txt", "c.txt", "d.txt"] {
+///     let content = NotificationBuilder::new().visual(Text::create(1, file));
+///     group.show(file, content).unwrap();
+///   }
+/// }
+/// ```
+pub struct ToastGroup {
+  notifier: ToastsNotifier,
+  group: String,
+  limit: usize,
+  /// Tags shown by this helper that may still be visible, oldest first
+  shown: VecDeque<String>,
+  collapsed: usize,
+  summary: Box<dyn Fn(usize) -> NotificationBuilder + Send + Sync>,
+}
+
+impl ToastGroup {
+  /// `summary` builds the summary toast from the number of removed toasts
+  pub fn new<F>(notifier: ToastsNotifier, group: &str, limit: usize, summary: F) -> Self
+  where
+    F: Fn(usize) -> NotificationBuilder + Send + Sync + 'static,
+  {
+    Self {
+      notifier,
+      group: group.to_string(),
+      limit: limit.max(1),
+      shown: VecDeque::new(),
+      collapsed: 0,
+      summary: Box::new(summary),
+    }
+  }
+
+  /// Shows `content` with `tag`, collapsing the oldest toasts over the limit
+  ///
+  /// A toast with the same tag is replaced. Toasts the user dismissed no longer
+  /// count, and the count of the summary restarts once it is dismissed.
+  pub fn show(&mut self, tag: &str, content: NotificationBuilder) -> Result<(), NotifError> {
+    let manager = self.notifier.manager()?;
+    let visible = self.visible_tags(&manager)?;
+    self.shown.retain(|x| x != tag && visible.contains(x));
+    if !visible.contains(SUMMARY_TAG) {
+      self.collapsed = 0;
+    }
+
+    content.build(0, &self.notifier, tag, &self.group)?.show()?;
+    self.shown.push_back(tag.to_string());
+
+    let overflow = self.shown.len().saturating_sub(self.limit);
+    if overflow == 0 {
+      return Ok(());
+    }
+    for tag in self.shown.drain(..overflow) {
+      manager.remove_notification_with_gt(&tag, &self.group)?;
+    }
+    self.collapsed += overflow;
+
+    let summary =
+      (self.summary)(self.collapsed).build(0, &self.notifier, SUMMARY_TAG, &self.group)?;
+    summary.set_suppress_popup(visible.contains(SUMMARY_TAG))?;
+    summary.show()
+  }
+
+  /// Removes all toasts of the group, including the summary
+  pub fn clear(&mut self) -> Result<(), NotifError> {
+    self.notifier.manager()?.remove_group(&self.group)?;
+    self.shown.clear();
+    self.collapsed = 0;
+    Ok(())
+  }
+
+  fn visible_tags(&self, manager: &ToastsManager) -> Result<HashSet<String>, NotifError> {
+    let mut tags = HashSet::new();
+    for notification in manager.get_notification_history()? {
+      let notification = notification.get_partial();
+      if notification.get_group()? == self.group {
+        tags.insert(notification.get_tag()?);
+      }
+    }
+    Ok(tags)
+  }
+}
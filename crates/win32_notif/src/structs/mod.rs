@@ -1,18 +1,29 @@
+pub mod badge;
 pub mod data;
+pub mod grouped_toast;
 pub mod handler;
 pub mod notification;
 pub mod notifier;
+pub mod progress_toast;
+pub mod tile;
 
 use std::time::Duration;
 
+pub use badge::{Badge, BadgeGlyph, BadgeNotifier};
 pub use data::NotificationDataSet;
+pub use grouped_toast::ToastGroup;
 pub use handler::{
   NotificationActivatedEventHandler, NotificationDismissedEventHandler,
   NotificationFailedEventHandler,
 };
 
 pub use notification::{Notification, NotificationBuilder};
-pub use notifier::ToastsNotifier;
+pub use notifier::{
+  ActivationCallback, BackgroundActivation, BackgroundActivator, NotificationStatus,
+  NotificationsAvailability, NotifierHandle, ToastCollection, ToastsNotifier,
+};
+pub use progress_toast::ProgressToast;
+pub use tile::{Tile, TileBinding, TileNotifier, TileSize};
 use windows::{
   core::HSTRING,
   Foundation::{DateTime, IReference, PropertyValue},
@@ -9,20 +9,22 @@ use super::{
 use actions::ActionElement;
 use audio::Audio;
 use header::Header;
-use visual::VisualElement;
+use quick_xml::events::BytesStart;
+use visual::{image::ImageCrop, Image, VisualElement};
 use widgets::commands::Commands;
 use windows::{
-  core::HSTRING,
-  Data::Xml::Dom::XmlDocument,
   Foundation::{DateTime, IReference, PropertyValue},
   Globalization::Calendar,
   UI::Notifications::{NotificationData, ToastNotification},
 };
 use windows_core::Interface;
+use xml::ToastXml;
 
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+mod presets;
 mod widgets;
+pub(crate) mod xml;
 pub use widgets::*;
 
 /// This is a partial version of notification
@@ -125,10 +127,12 @@ pub struct NotificationBuilder {
   on_activated: Option<NotificationActivatedEventHandler>,
   on_failed: Option<NotificationFailedEventHandler>,
   on_dismissed: Option<NotificationDismissedEventHandler>,
-  duration: &'static str,
-  scenario: &'static str,
-  use_button_style: &'static str,
-  launch: String,  
+  duration: Option<&'static str>,
+  scenario: Option<&'static str>,
+  use_button_style: bool,
+  launch: Option<String>,
+  display_timestamp: Option<String>,
+  raw: Option<String>,
   pub values: HashMap<String, String>,
 }
 
@@ -164,24 +168,48 @@ impl NotificationBuilder {
       on_activated: None,
       on_dismissed: None,
       on_failed: None,
-      duration: "",
-      scenario: "",
-      use_button_style: "",
-      launch: String::new(),
+      duration: None,
+      scenario: None,
+      use_button_style: false,
+      launch: None,
+      display_timestamp: None,
+      raw: None,
       values: HashMap::new(),
     }
   }
 
+  /// Creates a builder from a complete toast document, e.g. a template or the
+  /// output of [`NotificationBuilder::to_xml_string`]
+  ///
+  /// The XML has to be well formed with a single `<toast>` root element. The widgets,
+  /// audio, header, commands and toast attributes set on the builder are ignored,
+  /// while the values, expiry and handlers still apply.
+  ///
+  /// # Safety
+  /// The content is not checked against the toast schema, an unsupported element
+  /// makes Windows drop the notification silently
+  #[cfg_attr(docsrs, doc(cfg(feature = "experimental")))]
+  #[cfg(feature = "experimental")]
+  pub unsafe fn from_xml(xml: &str) -> Result<Self, NotifError> {
+    let mut doc = ToastXml::new();
+    doc.fragment("toast", xml)?;
+    xml::check_root(xml)?;
+
+    let mut builder = Self::new();
+    builder.raw = Some(doc.finish());
+    Ok(builder)
+  }
+
   impl_mut!(audio -> Audio);
   impl_mut!(header -> Header);
   impl_mut!(commands -> Commands);
 
   pub fn with_duration(mut self, duration: ToastDuration) -> Self {
-    match duration {
-      ToastDuration::None => self.duration = "",
-      ToastDuration::Short => self.duration = "duration=\"short\"",
-      ToastDuration::Long => self.duration = "duration=\"long\"",
-    }
+    self.duration = match duration {
+      ToastDuration::None => None,
+      ToastDuration::Short => Some("short"),
+      ToastDuration::Long => Some("long"),
+    };
     self
   }
 
@@ -202,22 +230,18 @@ impl NotificationBuilder {
   }
 
   pub fn with_scenario(mut self, scenario: Scenario) -> Self {
-    match scenario {
-      Scenario::Default => self.scenario = "",
-      Scenario::Alarm => self.scenario = "scenario=\"alarm\"",
-      Scenario::Reminder => self.scenario = "scenario=\"reminder\"",
-      Scenario::IncomingCall => self.scenario = "scenario=\"incomingCall\"",
-      Scenario::Urgent => self.scenario = "scenario=\"urgent\"",
-    }
+    self.scenario = match scenario {
+      Scenario::Default => None,
+      Scenario::Alarm => Some("alarm"),
+      Scenario::Reminder => Some("reminder"),
+      Scenario::IncomingCall => Some("incomingCall"),
+      Scenario::Urgent => Some("urgent"),
+    };
     self
   }
 
   pub fn with_use_button_style(mut self, use_button_style: bool) -> Self {
-    if use_button_style {
-      self.use_button_style = "useButtonStyle=\"True\""
-    } else {
-      self.use_button_style = ""
-    }
+    self.use_button_style = use_button_style;
     self
   }
 
@@ -227,7 +251,16 @@ impl NotificationBuilder {
   }
 
   pub fn with_launch<T: Into<String>>(mut self, launch: T) -> Self {
-    self.launch = format!("launch=\"{}\"", launch.into());
+    self.launch = Some(launch.into());
+    self
+  }
+
+  /// Shows `timestamp` as the time of the notification instead of the time it was
+  /// delivered, e.g. when the event happened while the app was not running
+  ///
+  /// `chrono::DateTime` converts into a `SystemTime`, it is accurate upto **seconds only**
+  pub fn with_display_timestamp<T: Into<SystemTime>>(mut self, timestamp: T) -> Self {
+    self.display_timestamp = Some(iso8601(timestamp.into()));
     self
   }
 
@@ -251,6 +284,16 @@ impl NotificationBuilder {
     self
   }
 
+  /// Adds a hero image, see [`Image::hero`]
+  pub fn hero_image(self, src: &str) -> Self {
+    self.visual(Image::hero(src))
+  }
+
+  /// Replaces the app logo, see [`Image::app_logo`]
+  pub fn app_logo(self, src: &str, crop: ImageCrop) -> Self {
+    self.visual(Image::app_logo(src, crop))
+  }
+
   pub fn visuals(mut self, visual: Vec<Box<dyn ToastVisualableXML>>) -> Self {
     self.visual = visual;
     self
@@ -271,6 +314,67 @@ impl NotificationBuilder {
     self
   }
 
+  /// Gets the toast document that [`NotificationBuilder::build`] loads
+  pub fn to_xml_string(&self) -> Result<String, NotifError> {
+    if let Some(raw) = &self.raw {
+      return Ok(raw.clone());
+    }
+
+    let mut toast = BytesStart::new("toast");
+    if let Some(duration) = self.duration {
+      toast.push_attribute(("duration", duration));
+    }
+    if let Some(scenario) = self.scenario {
+      toast.push_attribute(("scenario", scenario));
+    }
+    if self.use_button_style {
+      toast.push_attribute(("useButtonStyle", "True"));
+    }
+    if let Some(launch) = &self.launch {
+      toast.push_attribute(("launch", launch.as_str()));
+    }
+    if let Some(timestamp) = &self.display_timestamp {
+      toast.push_attribute(("displayTimestamp", timestamp.as_str()));
+    }
+
+    let mut xml = ToastXml::new();
+    xml.start(toast)?;
+    if let Some(audio) = &self.audio {
+      let long_toast = self.duration == Some("long")
+        || matches!(self.scenario, Some("alarm") | Some("incomingCall"));
+      audio.validate(long_toast)?;
+      xml.fragment("audio", &audio.to_xml())?;
+    }
+    if let Some(commands) = &self.commands {
+      xml.start(BytesStart::new("commands"))?;
+      xml.fragment("commands", &map!(commands))?;
+      xml.end("commands")?;
+    }
+    if let Some(header) = &self.header {
+      xml.fragment("header", &header.to_xml())?;
+    }
+    xml.start(BytesStart::new("visual"))?;
+    xml.start(BytesStart::new("binding").with_attributes([("template", "ToastGeneric")]))?;
+    // Windows shows a single attribution text, the last one added wins
+    let attribution = self.visual.iter().rposition(|x| x.is_attribution());
+    for (i, visual) in self.visual.iter().enumerate() {
+      if visual.is_attribution() && Some(i) != attribution {
+        continue;
+      }
+      xml.fragment("visual", &visual.to_xml())?;
+    }
+    xml.end("binding")?;
+    xml.end("visual")?;
+    xml.start(BytesStart::new("actions"))?;
+    for action in &self.actions {
+      xml.fragment("actions", &action.to_xml())?;
+    }
+    xml.end("actions")?;
+    xml.end("toast")?;
+
+    Ok(xml.finish())
+  }
+
   pub fn build<'a>(
     self,
     sequence: u32,
@@ -278,50 +382,8 @@ impl NotificationBuilder {
     tag: &str,
     group: &str,
   ) -> Result<Notification<'a>, NotifError> {
-    let visual = map!(self.visual);
-    let actions = map!(self.actions);
-
-    let audio = self.audio.map_or_else(|| "".into(), |x| x.to_xml());
-    let header = self.header.map_or_else(|| "".into(), |x| x.to_xml());
-
-    let commands = self.commands.map_or_else(
-      || "".into(),
-      |x| {
-        format!(
-          r"
-        <commands>
-          {}
-        </commands>
-      ",
-          map!(x)
-        )
-      },
-    );
-
-    let _xml = format!(
-      r#"
-      <toast {dur} {scenario} {button_style} {launch}>
-        {audio}
-        {commands}
-        {header}
-        <visual>
-          <binding template='ToastGeneric'>
-            {visual}
-          </binding>
-        </visual>
-        <actions>
-          {actions}
-        </actions>
-      </toast>
-    "#,
-      dur = self.duration,
-      scenario = self.scenario,
-      button_style = self.use_button_style,
-      launch = self.launch
-    );
-
-    let doc = XmlDocument::new()?;
-    doc.LoadXml(&HSTRING::from(_xml))?;
+    let content = self.to_xml_string()?;
+    let doc = xml::load(&content)?;
 
     let data = NotificationData::new()?;
     data.SetSequenceNumber(sequence)?;
@@ -334,7 +396,8 @@ impl NotificationBuilder {
     let mut dismissed_event_handler_token = None;
     let mut failed_event_handler_token = None;
 
-    let toast = ToastNotification::CreateToastNotification(&doc)?;
+    let toast = ToastNotification::CreateToastNotification(&doc)
+      .map_err(|e| xml::rejected("CreateToastNotification", e, &content))?;
     if let Some(x) = self.on_activated {
       let token = toast.Activated(&x.handler)?;
       activated_event_handler_token = Some(token);
@@ -376,3 +439,113 @@ impl NotificationBuilder {
     })
   }
 }
+
+/// Formats `time` as `2017-04-15T19:45:00Z`, times before 1970 are clamped
+fn iso8601(time: SystemTime) -> String {
+  let secs = time
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_secs();
+  let (days, secs) = (secs / 86400, secs % 86400);
+
+  // Civil from days, <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+  let z = days + 719468;
+  let era = z / 146097;
+  let doe = z - era * 146097;
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + u64::from(month <= 2);
+
+  format!(
+    "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+    secs / 3600,
+    secs / 60 % 60,
+    secs % 60
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::{Duration, UNIX_EPOCH};
+
+  use super::{iso8601, visual::Text, NotificationBuilder};
+
+  #[test]
+  fn display_timestamp() {
+    assert_eq!(iso8601(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+    assert_eq!(
+      iso8601(UNIX_EPOCH + Duration::from_secs(1_492_285_500)),
+      "2017-04-15T19:45:00Z"
+    );
+    assert_eq!(
+      iso8601(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+      "2000-02-29T00:00:00Z"
+    );
+
+    let xml = NotificationBuilder::new()
+      .with_display_timestamp(UNIX_EPOCH + Duration::from_secs(1_492_285_500))
+      .to_xml_string()
+      .unwrap();
+    assert!(xml.contains(r#"displayTimestamp="2017-04-15T19:45:00Z""#));
+  }
+
+  #[test]
+  fn attribution_is_escaped_and_placed() {
+    let xml = NotificationBuilder::new()
+      .visual(Text::attribution("Via <Cloudreve> & Co"))
+      .to_xml_string()
+      .unwrap();
+
+    assert!(xml.contains(r#"placement="attribution""#));
+    assert!(xml.contains("Via &lt;Cloudreve&gt; &amp; Co"));
+  }
+
+  #[test]
+  fn only_last_attribution_is_emitted() {
+    let xml = NotificationBuilder::new()
+      .visual(Text::create(1, "Title"))
+      .visual(Text::attribution("First"))
+      .visual(Text::create(2, "Body"))
+      .visual(Text::attribution("Second"))
+      .to_xml_string()
+      .unwrap();
+
+    assert_eq!(xml.matches(r#"placement="attribution""#).count(), 1);
+    assert!(!xml.contains("First"));
+    assert!(xml.contains("Second"));
+    assert!(xml.contains("Title") && xml.contains("Body"));
+  }
+
+  #[cfg(feature = "experimental")]
+  #[test]
+  fn xml_round_trip() {
+    let xml = NotificationBuilder::new()
+      .visual(Text::create(1, "Uploaded <report>.pdf"))
+      .visual(Text::attribution("Cloudreve"))
+      .to_xml_string()
+      .unwrap();
+
+    let builder = unsafe { NotificationBuilder::from_xml(&xml) }.unwrap();
+    assert_eq!(builder.to_xml_string().unwrap(), xml);
+  }
+
+  #[cfg(feature = "experimental")]
+  #[test]
+  fn from_xml_rejects_invalid_documents() {
+    for xml in [
+      "",
+      "<visual />",
+      "<toast><visual></toast>",
+      "<toast /><toast />",
+      "<toast />text",
+    ] {
+      assert!(
+        unsafe { NotificationBuilder::from_xml(xml) }.is_err(),
+        "{xml} was accepted"
+      );
+    }
+  }
+}
@@ -0,0 +1,113 @@
+use super::{
+  actions::ActionButton,
+  visual::{progress::ProgressValue, text::HintStyle, Progress, Text},
+  AdaptiveText, NotificationBuilder,
+};
+
+/// Ready-made builders for the common toast layouts
+///
+/// They return a regular builder, so anything else can still be added to them.
+impl NotificationBuilder {
+  /// The data binding key of the progress value of [`NotificationBuilder::download_progress`]
+  pub const PROGRESS_VALUE: &'static str = "progressValue";
+  /// The data binding key of the progress status of [`NotificationBuilder::download_progress`]
+  pub const PROGRESS_STATUS: &'static str = "progressStatus";
+
+  /// A title and a body text
+  ///
+  /// # Example
+  /// ```rust
+  /// use win32_notif::{NotificationBuilder, ToastsNotifier};
+  ///
+  /// fn main() {
+  ///   let notifier = ToastsNotifier::new("Microsoft.Windows.Explorer").unwrap();
+  ///   NotificationBuilder::basic("Sync finished", "3 files uploaded")
+  ///     .build(0, &notifier, "sync", "status")
+  ///     .unwrap()
+  ///     .show()
+  ///     .unwrap();
+  /// }
+  /// ```
+  pub fn basic(title: &str, body: &str) -> Self {
+    Self::new()
+      .visual(
+        Text::create(1, title)
+          .with_align_center(true)
+          .with_wrap(true)
+          .with_style(HintStyle::Title),
+      )
+      .visual(
+        Text::create(2, body)
+          .with_align_center(true)
+          .with_wrap(true)
+          .with_style(HintStyle::Body),
+      )
+  }
+
+  /// A [`NotificationBuilder::basic`] toast with two buttons, given as
+  /// `(content, arguments)` pairs
+  ///
+  /// The arguments are passed to the activation handler of the clicked button.
+  pub fn with_two_actions(
+    title: &str,
+    body: &str,
+    primary: (&str, &str),
+    secondary: (&str, &str),
+  ) -> Self {
+    Self::basic(title, body)
+      .action(ActionButton::create(primary.0).with_id(primary.1))
+      .action(ActionButton::create(secondary.0).with_id(secondary.1))
+  }
+
+  /// A title and a progress bar for `file_name`, starting at `value` between 0 and 1
+  ///
+  /// The progress bar is bound to [`NotificationBuilder::PROGRESS_VALUE`] and
+  /// [`NotificationBuilder::PROGRESS_STATUS`], update them with a
+  /// [`NotificationDataSet`] or use a [`ProgressToast`] to track the toast.
+  ///
+  /// [`NotificationDataSet`]: crate::NotificationDataSet
+  /// [`ProgressToast`]: crate::ProgressToast
+  pub fn download_progress(title: &str, file_name: &str, value: f64, status: &str) -> Self {
+    Self::new()
+      .visual(
+        Text::create(1, title)
+          .with_wrap(true)
+          .with_style(HintStyle::Title),
+      )
+      .visual(
+        Progress::create(
+          AdaptiveText::BindTo(Self::PROGRESS_STATUS),
+          ProgressValue::BindTo(Self::PROGRESS_VALUE),
+        )
+        .with_title::<&str>(AdaptiveText::Text(file_name)),
+      )
+      .value(Self::PROGRESS_VALUE, value.clamp(0.0, 1.0).to_string())
+      .value(Self::PROGRESS_STATUS, status)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::NotificationBuilder;
+
+  #[test]
+  fn presets() {
+    let xml = NotificationBuilder::with_two_actions(
+      "Conflict",
+      "a & b.txt",
+      ("Keep", "keep"),
+      ("Skip", "skip"),
+    )
+    .to_xml_string()
+    .unwrap();
+    assert!(xml.contains("a &amp; b.txt"));
+    assert!(xml.contains(r#"arguments="keep""#));
+    assert!(xml.contains(r#"arguments="skip""#));
+
+    let builder = NotificationBuilder::download_progress("Downloading", "report.pdf", 1.5, "");
+    let xml = builder.to_xml_string().unwrap();
+    assert!(xml.contains(r#"title="report.pdf""#));
+    assert!(xml.contains(r#"value="{progressValue}""#));
+    assert_eq!(builder.values[NotificationBuilder::PROGRESS_VALUE], "1");
+  }
+}
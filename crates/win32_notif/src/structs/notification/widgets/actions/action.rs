@@ -38,6 +38,33 @@ impl ActionButton {
     }
   }
 
+  /// The system snooze button, snoozing for the interval picked in the selection
+  /// input `selection_input_id` or for the system default if it is empty
+  ///
+  /// The button text is localized by Windows. Pair it with [`CommandId::Snooze`]
+  /// in the [`Commands`] of reminder and alarm toasts.
+  ///
+  /// [`CommandId::Snooze`]: crate::notification::commands::CommandId::Snooze
+  /// [`Commands`]: crate::notification::commands::Commands
+  pub fn system_snooze(selection_input_id: &str) -> Self {
+    Self::create("")
+      .with_id("snooze")
+      .with_input_id(selection_input_id)
+      .with_activation_type(ActivationType::System)
+  }
+
+  /// The system dismiss button, its text is localized by Windows
+  ///
+  /// Pair it with [`CommandId::Dismiss`] in the [`Commands`] of reminder and alarm toasts.
+  ///
+  /// [`CommandId::Dismiss`]: crate::notification::commands::CommandId::Dismiss
+  /// [`Commands`]: crate::notification::commands::Commands
+  pub fn system_dismiss() -> Self {
+    Self::create("")
+      .with_id("dismiss")
+      .with_activation_type(ActivationType::System)
+  }
+
   pub fn with_id(mut self, id: &str) -> Self {
     self.arguments = escape(id).into();
     self
@@ -143,6 +170,9 @@ pub enum ActivationType {
   Foreground,
   Background,
   Protocol,
+  /// Handled by Windows, used by [`ActionButton::system_snooze`] and
+  /// [`ActionButton::system_dismiss`]
+  System,
 }
 
 impl Into<String> for ActivationType {
@@ -151,6 +181,7 @@ impl Into<String> for ActivationType {
       ActivationType::Foreground => "foreground".to_string(),
       ActivationType::Background => "background".to_string(),
       ActivationType::Protocol => "protocol".to_string(),
+      ActivationType::System => "system".to_string(),
     }
   }
 }
@@ -195,3 +226,22 @@ impl Into<String> for HintButtonStyle {
 
 impl ActionElement for ActionButton {}
 impl ActionableXML for ActionButton {}
+
+#[cfg(test)]
+mod tests {
+  use super::ActionButton;
+  use crate::ToXML;
+
+  #[test]
+  fn system_actions() {
+    let snooze = ActionButton::system_snooze("snoozeTime").to_xml();
+    assert!(snooze.contains(r#"content="""#));
+    assert!(snooze.contains(r#"arguments="snooze""#));
+    assert!(snooze.contains(r#"activationType="system""#));
+    assert!(snooze.contains(r#"hint-inputId="snoozeTime""#));
+
+    let dismiss = ActionButton::system_dismiss().to_xml();
+    assert!(dismiss.contains(r#"arguments="dismiss""#));
+    assert!(dismiss.contains(r#"activationType="system""#));
+  }
+}
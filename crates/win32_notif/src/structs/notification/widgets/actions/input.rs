@@ -48,7 +48,7 @@ impl Input {
       r#type: "selection".into(),
       placeHolder: escape(place_holder).into(),
       children: map!(selections),
-      defaultInput: default_input.into(),
+      defaultInput: escape(default_input).into(),
     }
   }
 
@@ -73,6 +73,18 @@ impl Input {
     }
   }
 
+  /// Preselects the selection `id` of a selection input
+  pub fn with_default_selection(mut self, id: &str) -> Self {
+    self.defaultInput = escape(id).into();
+    self
+  }
+
+  /// Prefills a text input with `value`
+  pub fn with_default_text(mut self, value: &str) -> Self {
+    self.defaultInput = escape(value).into();
+    self
+  }
+
   pub fn with_selection(&mut self, children: Vec<Selection>) -> &mut Self {
     self.children = map!(children);
     self
@@ -120,3 +132,31 @@ impl ToXML for Selection {
 }
 
 impl ActionableXML for Input {}
+
+#[cfg(test)]
+mod tests {
+  use super::{Input, Selection};
+  use crate::ToXML;
+
+  #[test]
+  fn default_input() {
+    let text = Input::create_text_input("reply", "Reply", "Type a reply")
+      .with_default_text("Thanks & see you")
+      .to_xml();
+    assert!(text.contains(r#"defaultInput="Thanks &amp; see you""#));
+
+    let selection = Input::create_selection_input(
+      "snoozeTime",
+      "Snooze for",
+      "",
+      vec![
+        Selection::new("15", "15 minutes"),
+        Selection::new("60", "1 hour"),
+      ],
+      "",
+    )
+    .with_default_selection("60")
+    .to_xml();
+    assert!(selection.contains(r#"defaultInput="60""#));
+  }
+}
@@ -1,20 +1,47 @@
-use crate::ToXML;
+use std::path::{Path, PathBuf};
+
+use quick_xml::escape::escape;
+use windows::Storage::ApplicationData;
+
+use crate::{NotifError, ToXML};
+
+/// Learn more here
+/// <https://learn.microsoft.com/en-us/windows/apps/design/shell/tiles-and-notifications/custom-audio-on-toasts>
+const SUPPORTED_EXTENSIONS: [&str; 6] = ["aac", "flac", "m4a", "mp3", "wav", "wma"];
 
 /// Learn More About this here
 /// <https://learn.microsoft.com/en-us/uwp/schemas/tiles/toastschema/element-audio>
 pub struct Audio {
-  src: String,
-  r#loop: String,
-  silent: String,
+  src: Src,
+  r#loop: bool,
+  silent: bool,
 }
 
 impl Audio {
   pub fn new(src: Src, r#loop: bool, silent: bool) -> Self {
     Self {
-      src: src.into(),
-      r#loop: r#loop.to_string(),
-      silent: silent.to_string(),
+      src,
+      r#loop,
+      silent,
+    }
+  }
+
+  /// Checks the custom file and that only a looping sound or a custom file loops,
+  /// on a toast that stays long enough for it
+  pub(crate) fn validate(&self, long_toast: bool) -> Result<(), NotifError> {
+    if let Src::Custom(src) = &self.src {
+      validate_custom(src)?;
     }
+
+    if self.r#loop && !self.src.can_loop() {
+      return Err(invalid("only looping sounds and custom files can loop"));
+    }
+    if self.r#loop && !long_toast {
+      return Err(invalid(
+        "looping audio needs a long duration or the alarm or incoming call scenario",
+      ));
+    }
+    Ok(())
   }
 }
 
@@ -24,11 +51,72 @@ impl ToXML for Audio {
       r#"
         <audio src="{}" loop="{}" silent="{}" />
       "#,
-      self.src, self.r#loop, self.silent
+      escape(self.src.uri()),
+      self.r#loop,
+      self.silent
     )
   }
 }
 
+fn validate_custom(src: &str) -> Result<(), NotifError> {
+  let uri = custom_uri(src);
+
+  let extension = Path::new(&uri)
+    .extension()
+    .and_then(|x| x.to_str())
+    .map(str::to_lowercase);
+  if !extension.is_some_and(|x| SUPPORTED_EXTENSIONS.contains(&x.as_str())) {
+    return Err(invalid(format!("unsupported audio format {uri}")));
+  }
+
+  // Files in the package (ms-appx) cannot be checked
+  let file = if let Some(path) = uri.strip_prefix("file:///") {
+    Some(PathBuf::from(path))
+  } else if let Some(path) = uri.strip_prefix("ms-appdata:///") {
+    resolve_app_data(path)
+  } else {
+    None
+  };
+  match file {
+    Some(file) if !file.is_file() => Err(invalid(format!("{} does not exist", file.display()))),
+    _ => Ok(()),
+  }
+}
+
+/// Resolves `local/sound.wav` to the local folder of the package, `None` without
+/// package identity
+fn resolve_app_data(path: &str) -> Option<PathBuf> {
+  let (folder, path) = path.split_once('/')?;
+  let data = ApplicationData::Current().ok()?;
+  let folder = match folder {
+    "local" => data.LocalFolder(),
+    "roaming" => data.RoamingFolder(),
+    "temp" => data.TemporaryFolder(),
+    _ => return None,
+  }
+  .ok()?;
+
+  Some(PathBuf::from(folder.Path().ok()?.to_string()).join(path))
+}
+
+/// Converts a plain path to a `file:///` URI, like the `src` of an image
+fn custom_uri(src: &str) -> String {
+  let protocols = ["file:///", "ms-appx:///", "ms-appdata:///"];
+
+  if !(protocols.iter().any(|x| src.starts_with(x))) {
+    return format!("file:///{}", src.replace('\\', "/"));
+  }
+
+  src.to_string()
+}
+
+fn invalid(reason: impl ToString) -> NotifError {
+  NotifError::InvalidContent {
+    element: "audio",
+    reason: reason.to_string(),
+  }
+}
+
 #[derive(Default)]
 /// Learn More About it here
 /// <https://learn.microsoft.com/en-us/uwp/schemas/tiles/toastschema/element-audio>
@@ -59,10 +147,23 @@ pub enum Src {
   Call8,
   Call9,
   Call10,
+  /// An audio file, either a path or an `ms-appx:///`, `ms-appdata:///` or `file:///`
+  /// URI
+  ///
+  /// Supported formats are `.aac`, `.flac`, `.m4a`, `.mp3`, `.wav` and `.wma`. The
+  /// file is checked when the notification is built.
+  Custom(String),
 }
 
-impl Into<String> for Src {
-  fn into(self) -> String {
+impl Src {
+  fn can_loop(&self) -> bool {
+    !matches!(
+      self,
+      Self::Default | Self::IM | Self::Mail | Self::Reminder | Self::Sms
+    )
+  }
+
+  fn uri(&self) -> String {
     match self {
       Self::Default => "ms-winsoundevent:Notification.Default",
       Self::IM => "ms-winsoundevent:Notification.IM",
@@ -89,7 +190,54 @@ impl Into<String> for Src {
       Self::Call8 => "ms-winsoundevent:Notification.Looping.Call8",
       Self::Call9 => "ms-winsoundevent:Notification.Looping.Call9",
       Self::Call10 => "ms-winsoundevent:Notification.Looping.Call10",
+      Self::Custom(src) => return custom_uri(src),
     }
     .into()
   }
 }
+
+impl Into<String> for Src {
+  fn into(self) -> String {
+    self.uri()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{custom_uri, Audio, Src};
+
+  #[test]
+  fn custom_uri_conversion() {
+    assert_eq!(
+      custom_uri(r"C:\Sounds\done.wav"),
+      "file:///C:/Sounds/done.wav"
+    );
+    assert_eq!(
+      custom_uri("ms-appx:///Assets/done.wav"),
+      "ms-appx:///Assets/done.wav"
+    );
+  }
+
+  #[test]
+  fn validates_custom_files() {
+    let file = std::env::temp_dir().join("win32_notif_audio_test.wav");
+    std::fs::write(&file, b"").unwrap();
+    let custom = |src: &str| Audio::new(Src::Custom(src.to_string()), false, false);
+
+    assert!(custom(file.to_str().unwrap()).validate(false).is_ok());
+    assert!(custom("ms-appx:///Assets/done.mp3").validate(false).is_ok());
+    assert!(custom("ms-appx:///Assets/done.ogg")
+      .validate(false)
+      .is_err());
+    assert!(custom(r"C:\missing\done.wav").validate(false).is_err());
+
+    std::fs::remove_file(file).unwrap();
+  }
+
+  #[test]
+  fn looping_rules() {
+    assert!(Audio::new(Src::Alarm, true, false).validate(true).is_ok());
+    assert!(Audio::new(Src::Alarm, true, false).validate(false).is_err());
+    assert!(Audio::new(Src::Mail, true, false).validate(true).is_err());
+  }
+}
@@ -21,6 +21,15 @@ impl IntoIterator for Commands {
   }
 }
 
+impl<'a> IntoIterator for &'a Commands {
+  type Item = &'a Command;
+  type IntoIter = std::slice::Iter<'a, Command>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.widgets.iter()
+  }
+}
+
 /// Learn more about it here
 /// <https://learn.microsoft.com/en-us/uwp/schemas/tiles/toastschema/element-command>
 pub struct Command {
@@ -112,6 +112,22 @@ impl Image {
     )
   }
 
+  /// Creates a hero image, shown prominently at the top of the toast
+  ///
+  /// The `src` follows the same rules as [`Image::create`]
+  pub fn hero(src: &str) -> Self {
+    Self::create(0, src).with_placement(Placement::Hero)
+  }
+
+  /// Creates an image that replaces the app logo of the toast
+  ///
+  /// The `src` follows the same rules as [`Image::create`]
+  pub fn app_logo(src: &str, crop: ImageCrop) -> Self {
+    Self::create(0, src)
+      .with_placement(Placement::AppLogoOverride)
+      .with_crop(crop)
+  }
+
   /// The `src` should be in the form of `file:///path/to/file`
   /// 
   /// Technically `https://` and `http://` too should work according to the
@@ -4,7 +4,12 @@ pub mod text;
 
 use crate::ToXML;
 
-pub trait VisualElement {}
+pub trait VisualElement {
+  /// Whether this is the attribution text, a toast shows only one of them
+  fn is_attribution(&self) -> bool {
+    false
+  }
+}
 pub trait TextOrImageElement: VisualElement + ToXML {}
 
 pub use image::{Image, Placement};
@@ -109,6 +109,13 @@ impl Text {
     unsafe { Self::new_unchecked(id, None, None, format!("{{{binds}}}")) }
   }
 
+  /// Creates the attribution text, shown below the body in a smaller font
+  ///
+  /// A toast shows a single attribution, only the last one added is kept.
+  pub fn attribution(body: &str) -> Self {
+    Self::create(0, body).with_placement(AttributionPlacement)
+  }
+
   pub fn with_align(mut self, align: HintAlign) -> Self {
     self.align = align;
     self
@@ -171,7 +178,11 @@ impl Text {
   }
 }
 
-impl VisualElement for Text {}
+impl VisualElement for Text {
+  fn is_attribution(&self) -> bool {
+    self.placement.is_some()
+  }
+}
 
 impl ToastVisualableXML for Text {}
 
@@ -0,0 +1,238 @@
+use quick_xml::{
+  escape::{resolve_xml_entity, unescape},
+  events::{BytesEnd, BytesStart, Event},
+  Reader, Writer,
+};
+
+use windows::{core::HSTRING, Data::Xml::Dom::XmlDocument};
+
+use crate::NotifError;
+
+/// Assembles the toast document, and the badge and tile documents
+///
+/// Toast level elements are written with typed attributes, while the XML of each
+/// widget is parsed and copied over, so malformed widget content is reported as
+/// [`NotifError::InvalidContent`] naming the element instead of failing at `LoadXml`.
+pub(crate) struct ToastXml {
+  writer: Writer<Vec<u8>>,
+}
+
+impl ToastXml {
+  pub fn new() -> Self {
+    Self {
+      writer: Writer::new(Vec::new()),
+    }
+  }
+
+  pub fn start(&mut self, start: BytesStart) -> Result<(), NotifError> {
+    self
+      .writer
+      .write_event(Event::Start(start))
+      .map_err(|e| invalid("toast", e))
+  }
+
+  pub fn empty(&mut self, start: BytesStart) -> Result<(), NotifError> {
+    self
+      .writer
+      .write_event(Event::Empty(start))
+      .map_err(|e| invalid("toast", e))
+  }
+
+  pub fn end(&mut self, name: &str) -> Result<(), NotifError> {
+    self
+      .writer
+      .write_event(Event::End(BytesEnd::new(name)))
+      .map_err(|e| invalid("toast", e))
+  }
+
+  /// Checks that `xml` is well formed and appends it, `element` names it in errors
+  pub fn fragment(&mut self, element: &'static str, xml: &str) -> Result<(), NotifError> {
+    let mut reader = Reader::from_str(xml);
+    let mut depth = 0usize;
+    loop {
+      // The reader rejects closing tags that do not match an open element
+      let event = reader.read_event().map_err(|e| invalid(element, e))?;
+      match &event {
+        Event::Eof => break,
+        Event::Start(start) => {
+          check_attributes(element, start)?;
+          depth += 1;
+        }
+        Event::Empty(start) => check_attributes(element, start)?,
+        Event::End(_) => depth -= 1,
+        Event::GeneralRef(reference) => {
+          let name = String::from_utf8_lossy(reference);
+          if reference.is_char_ref() {
+            reference
+              .resolve_char_ref()
+              .map_err(|e| invalid(element, e))?;
+          } else if resolve_xml_entity(&name).is_none() {
+            return Err(invalid(element, format!("unknown entity &{};", name)));
+          }
+        }
+        _ => {}
+      }
+      self
+        .writer
+        .write_event(event)
+        .map_err(|e| invalid(element, e))?;
+    }
+
+    if depth > 0 {
+      return Err(invalid(element, "unclosed element"));
+    }
+    Ok(())
+  }
+
+  pub fn finish(self) -> String {
+    String::from_utf8_lossy(&self.writer.into_inner()).into_owned()
+  }
+}
+
+/// Checks that the well formed `xml` has a single `<toast>` root element
+#[cfg(feature = "experimental")]
+pub fn check_root(xml: &str) -> Result<(), NotifError> {
+  let mut reader = Reader::from_str(xml);
+  let mut depth = 0usize;
+  let mut roots = 0usize;
+  loop {
+    let event = reader.read_event().map_err(|e| invalid("toast", e))?;
+    match &event {
+      Event::Eof => break,
+      Event::Start(start) | Event::Empty(start) if depth == 0 => {
+        if start.name().as_ref() != b"toast" {
+          return Err(invalid("toast", "the root element is not <toast>"));
+        }
+        roots += 1;
+      }
+      Event::Text(text) if depth == 0 && !text.iter().all(u8::is_ascii_whitespace) => {
+        return Err(invalid("toast", "text outside of <toast>"));
+      }
+      _ => {}
+    }
+    match event {
+      Event::Start(_) => depth += 1,
+      Event::End(_) => depth -= 1,
+      _ => {}
+    }
+  }
+
+  match roots {
+    0 => Err(invalid("toast", "missing <toast> element")),
+    1 => Ok(()),
+    _ => Err(invalid("toast", "more than one root element")),
+  }
+}
+
+/// Loads the generated `xml`, a rejection carries it along
+pub fn load(xml: &str) -> Result<XmlDocument, NotifError> {
+  let doc = XmlDocument::new()?;
+  doc
+    .LoadXml(&HSTRING::from(xml))
+    .map_err(|e| rejected("LoadXml", e, xml))?;
+  Ok(doc)
+}
+
+/// Wraps the error of Windows rejecting `xml` at `stage`
+pub fn rejected(stage: &'static str, error: windows::core::Error, xml: &str) -> NotifError {
+  let path = reported_offset(xml, &error.message()).map(|x| element_path(xml, x));
+  NotifError::Rejected {
+    stage,
+    error,
+    xml: xml.to_string(),
+    path,
+  }
+}
+
+/// Finds the offset of a `Line: 1, Position: 42` like location in `message`
+fn reported_offset(xml: &str, message: &str) -> Option<usize> {
+  let message = message.to_lowercase();
+  let number_after = |key: &str| {
+    let rest = &message[message.find(key)? + key.len()..];
+    let digits = rest
+      .trim_start_matches([' ', ':', '='])
+      .chars()
+      .take_while(char::is_ascii_digit)
+      .collect::<String>();
+    digits.parse::<usize>().ok()
+  };
+
+  let column = number_after("position").or_else(|| number_after("column"))?;
+  let line = number_after("line").unwrap_or(1);
+  let line_start = xml
+    .split_inclusive('\n')
+    .take(line.saturating_sub(1))
+    .map(str::len)
+    .sum::<usize>();
+  Some(line_start + column.saturating_sub(1))
+}
+
+/// Gets the path of the element around `offset`, e.g. `toast/visual/binding/text`
+fn element_path(xml: &str, offset: usize) -> String {
+  let mut reader = Reader::from_str(xml);
+  let mut path = vec![];
+  loop {
+    let event = match reader.read_event() {
+      Ok(Event::Eof) | Err(_) => break,
+      Ok(event) => event,
+    };
+    let contains = reader.buffer_position() as usize > offset;
+    match event {
+      Event::Start(start) => path.push(String::from_utf8_lossy(start.name().as_ref()).into_owned()),
+      Event::Empty(start) if contains => {
+        path.push(String::from_utf8_lossy(start.name().as_ref()).into_owned())
+      }
+      Event::End(_) if !contains => {
+        path.pop();
+      }
+      _ => {}
+    }
+    if contains {
+      break;
+    }
+  }
+  path.join("/")
+}
+
+fn check_attributes(element: &'static str, start: &BytesStart) -> Result<(), NotifError> {
+  for attribute in start.attributes() {
+    let attribute = attribute.map_err(|e| invalid(element, e))?;
+    unescape(&String::from_utf8_lossy(&attribute.value)).map_err(|e| invalid(element, e))?;
+  }
+  Ok(())
+}
+
+fn invalid(element: &'static str, reason: impl ToString) -> NotifError {
+  NotifError::InvalidContent {
+    element,
+    reason: reason.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{element_path, reported_offset};
+
+  const XML: &str = r#"<toast><visual><binding template="ToastGeneric"><text id="1">Hi</text>
+<image src="a.png"/></binding></visual></toast>"#;
+
+  #[test]
+  fn finds_reported_element() {
+    let image = XML.find("<image").unwrap();
+    assert_eq!(
+      reported_offset(XML, "Invalid attribute. Line: 2, Position: 3"),
+      Some(image + 2)
+    );
+    assert_eq!(reported_offset(XML, "Unspecified error"), None);
+
+    assert_eq!(element_path(XML, image + 2), "toast/visual/binding/image");
+    assert_eq!(
+      element_path(XML, XML.find("Hi").unwrap()),
+      "toast/visual/binding/text"
+    );
+    assert_eq!(
+      element_path(XML, XML.find("</visual>").unwrap()),
+      "toast/visual"
+    );
+  }
+}
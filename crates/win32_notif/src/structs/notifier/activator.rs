@@ -1,33 +1,285 @@
-use windows::core::implement;
-use windows::Win32::UI::Notifications::{
-  INotificationActivationCallback, INotificationActivationCallback_Impl,
-  NOTIFICATION_USER_INPUT_DATA,
+use std::{
+  collections::HashMap,
+  ffi::c_void,
+  panic::{catch_unwind, AssertUnwindSafe},
+  sync::{mpsc, Arc},
+  thread::{self, JoinHandle},
 };
 
+use windows::{
+  core::implement,
+  Win32::{
+    Foundation::{CLASS_E_NOAGGREGATION, LPARAM, WPARAM},
+    System::{
+      Com::{
+        CoInitializeEx, CoRegisterClassObject, CoRevokeClassObject, CoUninitialize, IClassFactory,
+        IClassFactory_Impl, CLSCTX_LOCAL_SERVER, COINIT_APARTMENTTHREADED, REGCLS_MULTIPLEUSE,
+      },
+      Threading::GetCurrentThreadId,
+    },
+    UI::{
+      Notifications::{
+        INotificationActivationCallback, INotificationActivationCallback_Impl,
+        NOTIFICATION_USER_INPUT_DATA,
+      },
+      WindowsAndMessaging::{
+        DispatchMessageW, GetMessageW, PeekMessageW, PostThreadMessageW, TranslateMessage, MSG,
+        PM_NOREMOVE, WM_QUIT, WM_USER,
+      },
+    },
+  },
+};
+use windows_core::{IUnknown, Interface, Ref, BOOL, GUID, PCWSTR};
+
+use crate::NotifError;
+
+/// The argument Windows starts the application with to deliver an activation,
+/// see [`BackgroundActivator::register`]
+pub const ACTIVATED_ARG: &str = "-ToastActivated";
+
+/// A toast, or one of its buttons, activated through the COM server
+#[derive(Debug, Clone, Default)]
+pub struct BackgroundActivation {
+  pub app_id: String,
+  /// The `launch` arguments of the toast or the `arguments` of the clicked button
+  pub arguments: String,
+  /// The values of the inputs of the toast, by input id
+  pub inputs: HashMap<String, String>,
+}
+
+/// Receives the activations of [`BackgroundActivator`]
+///
+/// It is called on the message loop thread of the activator, a panic is caught and
+/// the activation dropped.
+pub trait ActivationCallback: Send + Sync + 'static {
+  fn activated(&self, activation: BackgroundActivation);
+}
+
+impl<F: Fn(BackgroundActivation) + Send + Sync + 'static> ActivationCallback for F {
+  fn activated(&self, activation: BackgroundActivation) {
+    self(activation)
+  }
+}
+
+/// A COM server delivering toast activations while, or by starting, the application
+///
+/// Unlike the `on_activated` handler of a notification, it also receives activations
+/// after the application was restarted or from the notification center.
+///
+/// # Setup
+/// 1. Pick a CLSID for the application, it must never change
+/// 2. Call [`BackgroundActivator::register`] once, e.g. on install. It registers the
+///    AUMID with the `CustomActivator` CLSID and the current executable as the COM
+///    server of that CLSID, started with [`ACTIVATED_ARG`]
+/// 3. Create the notifier with the same AUMID
+/// 4. Call [`BackgroundActivator::start`] early on every start, in particular when
+///    started with [`ACTIVATED_ARG`]
+///
+/// The server stops when the activator is dropped.
+///
+/// # Example
+/// ```rust
+/// use win32_notif::{BackgroundActivator, ToastsNotifier};
+///
+/// const CLSID: u128 = 0x6a1e3b52_5c3f_4a0c_9a54_2f1c4b7f8e10;
+///
+/// fn main() {
+///   BackgroundActivator::register("Company.App", "App", CLSID, None).unwrap();
+///   let _activator = BackgroundActivator::start(CLSID, |activation| {
+///     println!("Activated with {}", activation.arguments);
+///   })
+///   .unwrap();
+///
+///   let notifier = ToastsNotifier::new("Company.App").unwrap();
+/// }
+/// ```
+pub struct BackgroundActivator {
+  thread_id: u32,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl BackgroundActivator {
+  /// Registers the COM class `clsid` and runs its message loop on a new thread
+  pub fn start<C: ActivationCallback>(clsid: u128, callback: C) -> Result<Self, NotifError> {
+    let callback: Arc<dyn ActivationCallback> = Arc::new(callback);
+    let (ready, started) = mpsc::channel();
+    let thread = thread::spawn(move || unsafe { serve(GUID::from_u128(clsid), callback, ready) });
+
+    match started.recv() {
+      Ok(Ok(thread_id)) => Ok(Self {
+        thread_id,
+        thread: Some(thread),
+      }),
+      Ok(Err(e)) => {
+        _ = thread.join();
+        Err(e)
+      }
+      Err(_) => Err(NotifError::UnknownAndImpossible),
+    }
+  }
+
+  /// Revokes the COM class and waits for the message loop to exit
+  pub fn stop(mut self) -> Result<(), NotifError> {
+    self.shutdown()
+  }
+
+  fn shutdown(&mut self) -> Result<(), NotifError> {
+    let Some(thread) = self.thread.take() else {
+      return Ok(());
+    };
+    unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0))? };
+    thread.join().map_err(|_| NotifError::UnknownAndImpossible)
+  }
+}
+
+impl Drop for BackgroundActivator {
+  fn drop(&mut self) {
+    _ = self.shutdown();
+  }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "registration")))]
+#[cfg(feature = "registration")]
+impl BackgroundActivator {
+  /// Registers the AUMID `app_id` of an unpackaged application for activations of
+  /// the COM class `clsid`, served by the current executable
+  ///
+  /// `icon` is the path of the icon shown on the toasts and in the settings.
+  pub fn register(
+    app_id: &str,
+    display_name: &str,
+    clsid: u128,
+    icon: Option<&str>,
+  ) -> Result<(), NotifError> {
+    use windows_registry::CURRENT_USER;
+
+    let clsid = format!("{{{:?}}}", GUID::from_u128(clsid));
+    let exe = std::env::current_exe().map_err(|_| NotifError::UnknownAndImpossible)?;
+
+    let app = CURRENT_USER.create(format!(r"Software\Classes\AppUserModelId\{app_id}"))?;
+    app.set_string("DisplayName", display_name)?;
+    app.set_string("CustomActivator", &clsid)?;
+    if let Some(icon) = icon {
+      app.set_string("IconUri", icon)?;
+    }
+
+    CURRENT_USER
+      .create(format!(r"Software\Classes\CLSID\{clsid}\LocalServer32"))?
+      .set_string("", format!("\"{}\" {ACTIVATED_ARG}", exe.display()))?;
+    Ok(())
+  }
+
+  /// Removes what [`BackgroundActivator::register`] added
+  pub fn unregister(app_id: &str, clsid: u128) -> Result<(), NotifError> {
+    use windows_registry::CURRENT_USER;
+
+    let clsid = format!("{{{:?}}}", GUID::from_u128(clsid));
+    CURRENT_USER.remove_tree(format!(r"Software\Classes\AppUserModelId\{app_id}"))?;
+    CURRENT_USER.remove_tree(format!(r"Software\Classes\CLSID\{clsid}"))?;
+    Ok(())
+  }
+}
+
+unsafe fn serve(
+  clsid: GUID,
+  callback: Arc<dyn ActivationCallback>,
+  ready: mpsc::Sender<Result<u32, NotifError>>,
+) {
+  if let Err(e) = CoInitializeEx(None, COINIT_APARTMENTTHREADED).ok() {
+    _ = ready.send(Err(e.into()));
+    return;
+  }
+
+  // Creates the message queue before `stop` can post to it
+  let mut msg = MSG::default();
+  _ = PeekMessageW(&mut msg, None, WM_USER, WM_USER, PM_NOREMOVE);
+
+  let factory: IUnknown = ActivatorFactory { callback }.into();
+  let cookie =
+    match CoRegisterClassObject(&clsid, &factory, CLSCTX_LOCAL_SERVER, REGCLS_MULTIPLEUSE) {
+      Ok(cookie) => cookie,
+      Err(e) => {
+        _ = ready.send(Err(e.into()));
+        CoUninitialize();
+        return;
+      }
+    };
+  _ = ready.send(Ok(GetCurrentThreadId()));
+
+  // Activations are dispatched by this loop, it ends with WM_QUIT or an error
+  while GetMessageW(&mut msg, None, 0, 0).0 > 0 {
+    _ = TranslateMessage(&msg);
+    DispatchMessageW(&msg);
+  }
+
+  _ = CoRevokeClassObject(cookie);
+  drop(factory);
+  CoUninitialize();
+}
+
+#[implement(IClassFactory)]
+struct ActivatorFactory {
+  callback: Arc<dyn ActivationCallback>,
+}
+
+impl IClassFactory_Impl for ActivatorFactory_Impl {
+  fn CreateInstance(
+    &self,
+    punkouter: Ref<IUnknown>,
+    riid: *const GUID,
+    ppvobject: *mut *mut c_void,
+  ) -> windows_core::Result<()> {
+    if !punkouter.is_null() {
+      return Err(CLASS_E_NOAGGREGATION.into());
+    }
+
+    let activator: IUnknown = ToastActivationManager {
+      callback: self.callback.clone(),
+    }
+    .into();
+    unsafe { activator.query(riid, ppvobject).ok() }
+  }
+
+  fn LockServer(&self, _flock: BOOL) -> windows_core::Result<()> {
+    Ok(())
+  }
+}
+
 #[implement(INotificationActivationCallback)]
-pub struct ToastActivationManager;
+struct ToastActivationManager {
+  callback: Arc<dyn ActivationCallback>,
+}
 
 impl INotificationActivationCallback_Impl for ToastActivationManager_Impl {
   fn Activate(
     &self,
-    _appusermodelid: &windows_core::PCWSTR,
-    invokedargs: &windows_core::PCWSTR,
-    _data: *const NOTIFICATION_USER_INPUT_DATA,
-    _count: u32,
+    appusermodelid: &PCWSTR,
+    invokedargs: &PCWSTR,
+    data: *const NOTIFICATION_USER_INPUT_DATA,
+    count: u32,
   ) -> windows_core::Result<()> {
-    println!("Called");
-
-    if invokedargs.is_null() {
-      println!("Toast activated (default click), no arguments.");
+    let inputs = if data.is_null() {
+      HashMap::new()
     } else {
-      // Convert invoked_args (LPCWSTR) to a Rust String/str
-      let args_str = unsafe { invokedargs.to_string() };
-      println!(
-        "Toast activated with arguments: {}",
-        args_str.unwrap_or_default()
-      );
-    }
+      unsafe { std::slice::from_raw_parts(data, count as usize) }
+        .iter()
+        .map(|x| (to_string(&x.Key), to_string(&x.Value)))
+        .collect()
+    };
 
+    let activation = BackgroundActivation {
+      app_id: to_string(appusermodelid),
+      arguments: to_string(invokedargs),
+      inputs,
+    };
+    _ = catch_unwind(AssertUnwindSafe(|| self.callback.activated(activation)));
     Ok(())
   }
 }
+
+fn to_string(value: &PCWSTR) -> String {
+  if value.is_null() {
+    return String::new();
+  }
+  unsafe { value.to_string() }.unwrap_or_default()
+}
@@ -0,0 +1,71 @@
+use windows::UI::Notifications::{
+  NotificationSetting, ToastNotificationManager, ToastNotificationMode,
+};
+
+use crate::NotifError;
+
+use super::ToastsNotifier;
+
+/// Whether notifications of an application reach the user
+///
+/// Showing a notification does not fail in any of the disabled cases, check this to
+/// fall back to in-app messages instead.
+///
+/// Learn more here
+/// <https://learn.microsoft.com/en-us/uwp/api/windows.ui.notifications.notificationsetting>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationsAvailability {
+  Enabled,
+  /// The user turned off notifications of this application
+  DisabledForApplication,
+  /// The user turned off all notifications
+  DisabledForUser,
+  /// A group policy turned off notifications
+  DisabledByGroupPolicy,
+  /// The application manifest does not allow toasts
+  DisabledByManifest,
+  /// Focus assist only lets priority notifications through
+  PriorityOnly,
+  /// Focus assist only lets alarms through
+  AlarmsOnly,
+}
+
+impl NotificationsAvailability {
+  /// Whether a regular notification shows a popup
+  pub fn is_enabled(&self) -> bool {
+    matches!(self, Self::Enabled)
+  }
+}
+
+impl ToastsNotifier {
+  /// Checks whether notifications of this application reach the user
+  ///
+  /// Disabled settings take precedence over the focus assist mode.
+  pub fn availability(&self) -> Result<NotificationsAvailability, NotifError> {
+    let availability = match self._inner.Setting()? {
+      NotificationSetting::Enabled => NotificationsAvailability::Enabled,
+      NotificationSetting::DisabledForApplication => {
+        NotificationsAvailability::DisabledForApplication
+      }
+      NotificationSetting::DisabledForUser => NotificationsAvailability::DisabledForUser,
+      NotificationSetting::DisabledByGroupPolicy => {
+        NotificationsAvailability::DisabledByGroupPolicy
+      }
+      NotificationSetting::DisabledByManifest => NotificationsAvailability::DisabledByManifest,
+      _ => return Err(NotifError::UnknownAndImpossible),
+    };
+    if !availability.is_enabled() {
+      return Ok(availability);
+    }
+
+    // The focus assist mode is not available before Windows 10 2004
+    let Ok(mode) = ToastNotificationManager::GetDefault().and_then(|x| x.NotificationMode()) else {
+      return Ok(availability);
+    };
+    Ok(match mode {
+      ToastNotificationMode::PriorityOnly => NotificationsAvailability::PriorityOnly,
+      ToastNotificationMode::AlarmsOnly => NotificationsAvailability::AlarmsOnly,
+      _ => availability,
+    })
+  }
+}
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use windows::{
+  core::HSTRING,
+  Foundation::Uri,
+  UI::Notifications::{
+    ToastCollection as WinToastCollection, ToastCollectionManager, ToastNotificationManager,
+  },
+};
+
+use crate::NotifError;
+
+use super::{ToastsManager, ToastsNotifier};
+
+/// A named group of notifications with its own title and icon in the notification
+/// center, e.g. one per account
+///
+/// Learn more here
+/// <https://learn.microsoft.com/en-us/uwp/api/windows.ui.notifications.toastcollection>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToastCollection {
+  pub id: String,
+  pub display_name: String,
+  /// Arguments the app is launched with when the collection title is clicked
+  pub launch_args: String,
+  /// Icon URI, e.g. `ms-appx:///Images/drive.png`
+  pub icon: String,
+}
+
+impl ToastCollection {
+  pub fn new(id: &str, display_name: &str, icon: &str) -> Self {
+    Self {
+      id: id.to_string(),
+      display_name: display_name.to_string(),
+      launch_args: String::new(),
+      icon: icon.to_string(),
+    }
+  }
+
+  pub fn with_launch_args(mut self, launch_args: &str) -> Self {
+    self.launch_args = launch_args.to_string();
+    self
+  }
+
+  fn to_win32_type(&self) -> Result<WinToastCollection, NotifError> {
+    Ok(WinToastCollection::CreateInstance(
+      &HSTRING::from(self.id.as_str()),
+      &HSTRING::from(self.display_name.as_str()),
+      &HSTRING::from(self.launch_args.as_str()),
+      &Uri::CreateUri(&HSTRING::from(self.icon.as_str()))?,
+    )?)
+  }
+
+  fn from_win32_type(collection: &WinToastCollection) -> Result<Self, NotifError> {
+    Ok(Self {
+      id: collection.Id()?.to_string(),
+      display_name: collection.DisplayName()?.to_string(),
+      launch_args: collection.LaunchArgs()?.to_string(),
+      icon: collection.Icon()?.AbsoluteUri()?.to_string(),
+    })
+  }
+}
+
+impl ToastsNotifier {
+  /// Creates a notifier whose notifications are shown in the collection `collection_id`
+  ///
+  /// The collection has to be created with [`ToastsManager::create_collection`] first.
+  pub fn new_for_collection<T: Into<String>>(
+    app_id: T,
+    collection_id: &str,
+  ) -> Result<Self, NotifError> {
+    let _inner = ToastNotificationManager::GetDefault()?
+      .GetToastNotifierForToastCollectionIdAsync(&HSTRING::from(collection_id))?
+      .join()?;
+
+    Ok(Self {
+      _inner,
+      app_id: Arc::new(app_id.into().into_boxed_str()),
+    })
+  }
+}
+
+impl ToastsManager {
+  fn collection_manager(&self) -> Result<ToastCollectionManager, NotifError> {
+    Ok(
+      ToastNotificationManager::GetDefault()?
+        .GetToastCollectionManagerWithAppId(&HSTRING::from(&**self.app_id))?,
+    )
+  }
+
+  /// Creates a collection, replacing one with the same id
+  pub fn create_collection(&self, collection: &ToastCollection) -> Result<(), NotifError> {
+    let collection = collection.to_win32_type()?;
+    Ok(
+      self
+        .collection_manager()?
+        .SaveToastCollectionAsync(&collection)?
+        .join()?,
+    )
+  }
+
+  /// Updates the title, icon and launch arguments of an existing collection
+  pub fn update_collection(&self, collection: &ToastCollection) -> Result<(), NotifError> {
+    if self.get_collection(&collection.id)?.is_none() {
+      return Err(NotifError::CollectionNotFound);
+    }
+    self.create_collection(collection)
+  }
+
+  /// Gets the collection `id` if it exists
+  pub fn get_collection(&self, id: &str) -> Result<Option<ToastCollection>, NotifError> {
+    let collection = self
+      .collection_manager()?
+      .GetToastCollectionAsync(&HSTRING::from(id))?
+      .join();
+
+    match collection {
+      Ok(collection) => Ok(Some(ToastCollection::from_win32_type(&collection)?)),
+      // A missing collection comes back as a null object
+      Err(e) if e.code().is_ok() => Ok(None),
+      Err(e) => Err(e.into()),
+    }
+  }
+
+  /// Gets all collections of this application
+  pub fn get_collections(&self) -> Result<Vec<ToastCollection>, NotifError> {
+    let collections = self
+      .collection_manager()?
+      .FindAllToastCollectionsAsync()?
+      .join()?;
+
+    collections
+      .into_iter()
+      .map(|x| ToastCollection::from_win32_type(&x))
+      .collect()
+  }
+
+  /// Removes the collection `id` along with its notifications
+  pub fn remove_collection(&self, id: &str) -> Result<(), NotifError> {
+    Ok(
+      self
+        .collection_manager()?
+        .RemoveToastCollectionAsync(&HSTRING::from(id))?
+        .join()?,
+    )
+  }
+
+  /// Removes all collections of this application
+  pub fn remove_all_collections(&self) -> Result<(), NotifError> {
+    Ok(
+      self
+        .collection_manager()?
+        .RemoveAllToastCollectionsAsync()?
+        .join()?,
+    )
+  }
+}
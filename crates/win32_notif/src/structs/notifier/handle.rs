@@ -0,0 +1,106 @@
+use std::{
+  panic::{catch_unwind, AssertUnwindSafe},
+  sync::mpsc,
+  thread,
+};
+
+use crate::{NotifError, NotificationBuilder};
+
+use super::ToastsNotifier;
+
+type Job = Box<dyn FnOnce(&ToastsNotifier) + Send>;
+
+/// A `Send + Sync` handle to a notifier living on a dedicated thread
+///
+/// The widgets of a [`NotificationBuilder`] are not `Send`, so notifications are
+/// built on the notifier thread from closures that are. Clones share the thread,
+/// which exits once every handle is dropped.
+///
+/// # Example
+/// ```rust
+/// use win32_notif::{notification::visual::Text, NotificationBuilder, NotifierHandle};
+///
+/// fn main() {
+///   let notifier = NotifierHandle::new("Microsoft.Windows.Explorer").unwrap();
+///
+///   let handle = notifier.clone();
+///   std::thread::spawn(move || {
+///     handle
+///       .show(0, "sync", "status", || {
+///         NotificationBuilder::new().visual(Text::create(1, "Sync finished"))
+///       })
+///       .unwrap();
+///   });
+/// }
+/// ```
+#[derive(Clone)]
+pub struct NotifierHandle {
+  jobs: mpsc::Sender<Job>,
+}
+
+impl NotifierHandle {
+  /// Starts the notifier thread and creates the notifier of `app_id` on it
+  pub fn new<T: Into<String>>(app_id: T) -> Result<Self, NotifError> {
+    let app_id = app_id.into();
+    let (jobs, queue) = mpsc::channel::<Job>();
+    let (ready, created) = mpsc::channel();
+
+    thread::Builder::new()
+      .name("win32_notif".into())
+      .spawn(move || {
+        let notifier = match ToastsNotifier::new(app_id) {
+          Ok(notifier) => notifier,
+          Err(e) => {
+            _ = ready.send(Err(e));
+            return;
+          }
+        };
+        _ = ready.send(Ok(()));
+
+        for job in queue {
+          // A panicking job must not take down the notifier of the other handles
+          _ = catch_unwind(AssertUnwindSafe(|| job(&notifier)));
+        }
+      })
+      .map_err(|_| NotifError::NotifierClosed)?;
+
+    created.recv().map_err(|_| NotifError::NotifierClosed)??;
+    Ok(Self { jobs })
+  }
+
+  /// Builds the notification returned by `content` and shows it, without waiting
+  ///
+  /// Errors while building or showing the notification are discarded, use
+  /// [`NotifierHandle::run`] to handle them.
+  pub fn show<F>(&self, sequence: u32, tag: &str, group: &str, content: F) -> Result<(), NotifError>
+  where
+    F: FnOnce() -> NotificationBuilder + Send + 'static,
+  {
+    let (tag, group) = (tag.to_string(), group.to_string());
+    self.execute(move |notifier| {
+      if let Ok(notification) = content().build(sequence, notifier, &tag, &group) {
+        _ = notification.show();
+      }
+    })
+  }
+
+  /// Runs `f` on the notifier thread and waits for its result
+  pub fn run<F, R>(&self, f: F) -> Result<R, NotifError>
+  where
+    F: FnOnce(&ToastsNotifier) -> Result<R, NotifError> + Send + 'static,
+    R: Send + 'static,
+  {
+    let (result, received) = mpsc::channel();
+    self.execute(move |notifier| {
+      _ = result.send(f(notifier));
+    })?;
+    received.recv().map_err(|_| NotifError::NotifierClosed)?
+  }
+
+  fn execute<F: FnOnce(&ToastsNotifier) + Send + 'static>(&self, job: F) -> Result<(), NotifError> {
+    self
+      .jobs
+      .send(Box::new(job))
+      .map_err(|_| NotifError::NotifierClosed)
+  }
+}
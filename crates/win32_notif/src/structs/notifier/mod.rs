@@ -1,31 +1,28 @@
-use std::{sync::Arc, thread};
+use std::sync::Arc;
 
 use windows::{
   core::HSTRING,
-  Win32::{
-    System::Com::{
-      CoInitializeEx, CoRegisterClassObject, CLSCTX_LOCAL_SERVER, COINIT_APARTMENTTHREADED,
-      REGCLS_MULTIPLEUSE,
-    },
-    UI::{
-      Shell::SetCurrentProcessExplicitAppUserModelID,
-      WindowsAndMessaging::{DispatchMessageW, GetMessageW, TranslateMessage, MSG},
-    },
-  },
   UI::Notifications::{
     NotificationData, NotificationUpdateResult, ToastNotificationHistory, ToastNotificationManager,
     ToastNotifier,
   },
 };
-use windows_core::{IUnknown, GUID};
 
-use crate::{
-  notification::OwnedPartialNotification, notifier::activator::ToastActivationManager, NotifError,
-};
+use crate::{notification::OwnedPartialNotification, NotifError};
 
 use super::NotificationDataSet;
 
 mod activator;
+mod availability;
+mod collection;
+mod handle;
+mod status;
+
+pub use activator::{ActivationCallback, BackgroundActivation, BackgroundActivator, ACTIVATED_ARG};
+pub use availability::NotificationsAvailability;
+pub use collection::ToastCollection;
+pub use handle::NotifierHandle;
+pub use status::NotificationStatus;
 
 pub struct ToastsNotifier {
   _inner: ToastNotifier,
@@ -34,62 +31,29 @@ pub struct ToastsNotifier {
 
 impl ToastsNotifier {
   pub fn new<T: Into<String>>(app_id: T) -> Result<Self, NotifError> {
-    Self::new_inner(app_id, None)
+    let string = app_id.into().into_boxed_str();
+
+    let id = HSTRING::from(string.as_ref());
+    let _inner = ToastNotificationManager::CreateToastNotifierWithId(&id)?;
+
+    Ok(Self {
+      _inner,
+      app_id: Arc::new(string),
+    })
   }
 
+  /// Creates the notifier and serves activations of the COM class `guid` for
+  /// the lifetime of the process, discarding them
   #[cfg(feature = "experimental")]
+  #[deprecated = "Use `BackgroundActivator` instead"]
   pub unsafe fn new_with_guid<T: Into<String>>(
     app_id: T,
     guid: Option<u128>,
   ) -> Result<Self, NotifError> {
-    Self::new_inner(app_id, guid)
-  }
-
-  pub(crate) fn new_inner<T: Into<String>>(
-    app_id: T,
-    guid: Option<u128>,
-  ) -> Result<Self, NotifError> {
-    let app_id = app_id.into();
     if let Some(guid) = guid {
-      let app_id = app_id.clone();
-      thread::spawn(move || {
-        // EXPERIMENTAL
-        // Basically setting up a whole XML Server like C# (Packaged Apps can do)
-        unsafe {
-          SetCurrentProcessExplicitAppUserModelID(&HSTRING::from(app_id.as_str())).unwrap();
-
-          _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED).ok().unwrap();
-
-          let factory: IUnknown = ToastActivationManager.into();
-
-          CoRegisterClassObject(
-            &GUID::from_u128(guid),
-            &factory,
-            CLSCTX_LOCAL_SERVER,
-            REGCLS_MULTIPLEUSE,
-          )
-          .unwrap();
-
-          let mut msg = MSG::default();
-          while GetMessageW(&mut msg, None, 0, 0).into() {
-            println!("Got Msg");
-            _ = TranslateMessage(&msg);
-            DispatchMessageW(&msg);
-          }
-        };
-      });
+      std::mem::forget(BackgroundActivator::start(guid, |_| {})?);
     }
-
-    let string: String = app_id.clone();
-    let string = string.into_boxed_str();
-
-    let id = HSTRING::from(string.as_ref());
-    let _inner = ToastNotificationManager::CreateToastNotifierWithId(&id)?;
-
-    Ok(Self {
-      _inner,
-      app_id: Arc::new(string),
-    })
+    Self::new(app_id)
   }
 
   pub fn manager(&self) -> Result<ToastsManager, NotifError> {
@@ -0,0 +1,103 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use windows::{Foundation::DateTime, UI::Notifications::ToastNotification};
+
+use crate::NotifError;
+
+use super::ToastsManager;
+
+/// `DateTime` ticks (100ns since 1601-01-01) at the unix epoch
+const UNIX_EPOCH_TICKS: i64 = 116_444_736_000_000_000;
+
+/// What the notification center knows about a notification
+///
+/// Returned by [`ToastsManager::status`], a notification that does not exist has
+/// every other field unset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NotificationStatus {
+  pub exists: bool,
+  /// When the notification is removed from the notification center
+  pub expiration: Option<SystemTime>,
+  /// Whether the notification went to the notification center without a popup
+  pub suppressed: bool,
+}
+
+impl ToastsManager {
+  /// Whether the notification identified by `tag` and `group` is still in the
+  /// notification center
+  ///
+  /// If so it can be updated with [`ToastsNotifier::update`], otherwise it has to be
+  /// shown again.
+  ///
+  /// [`ToastsNotifier::update`]: super::ToastsNotifier::update
+  pub fn contains(&self, tag: &str, group: &str) -> Result<bool, NotifError> {
+    Ok(self.find(tag, group)?.is_some())
+  }
+
+  /// Gets the status of the notification identified by `tag` and `group`
+  pub fn status(&self, tag: &str, group: &str) -> Result<NotificationStatus, NotifError> {
+    let Some(toast) = self.find(tag, group)? else {
+      return Ok(NotificationStatus::default());
+    };
+
+    let expiration = match toast.ExpirationTime().and_then(|x| x.Value()) {
+      Ok(x) => Some(to_system_time(x)),
+      // A notification without expiration has a null ExpirationTime
+      Err(e) if e.code().is_ok() => None,
+      Err(e) => return Err(e.into()),
+    };
+
+    Ok(NotificationStatus {
+      exists: true,
+      expiration,
+      suppressed: toast.SuppressPopup()?,
+    })
+  }
+
+  fn find(&self, tag: &str, group: &str) -> Result<Option<ToastNotification>, NotifError> {
+    for toast in self.inner.GetHistory()? {
+      if toast.Tag()? == tag && toast.Group()? == group {
+        return Ok(Some(toast));
+      }
+    }
+    Ok(None)
+  }
+}
+
+fn to_system_time(time: DateTime) -> SystemTime {
+  let ticks = time.UniversalTime - UNIX_EPOCH_TICKS;
+  let offset = Duration::from_nanos(ticks.unsigned_abs() * 100);
+  if ticks >= 0 {
+    UNIX_EPOCH + offset
+  } else {
+    UNIX_EPOCH - offset
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::{Duration, UNIX_EPOCH};
+
+  use windows::Foundation::DateTime;
+
+  use super::{to_system_time, UNIX_EPOCH_TICKS};
+
+  #[test]
+  fn converts_ticks_to_system_time() {
+    let time = |ticks| {
+      to_system_time(DateTime {
+        UniversalTime: ticks,
+      })
+    };
+
+    assert_eq!(time(UNIX_EPOCH_TICKS), UNIX_EPOCH);
+    assert_eq!(
+      time(UNIX_EPOCH_TICKS + 15_000_000),
+      UNIX_EPOCH + Duration::from_millis(1500)
+    );
+    assert_eq!(
+      time(UNIX_EPOCH_TICKS - 10),
+      UNIX_EPOCH - Duration::from_micros(1)
+    );
+  }
+}
@@ -0,0 +1,115 @@
+use windows::UI::Notifications::NotificationUpdateResult;
+
+use crate::{
+  notification::{
+    visual::{progress::ProgressValue, Progress},
+    AdaptiveText,
+  },
+  NotifError, NotificationBuilder, NotificationDataSet, ToastsNotifier,
+};
+
+const VALUE_KEY: &str = NotificationBuilder::PROGRESS_VALUE;
+const STATUS_KEY: &str = NotificationBuilder::PROGRESS_STATUS;
+
+/// A toast with a progress bar that is updated in place
+///
+/// It owns the tag, group and sequence number of the toast and the data binding of
+/// its progress bar. An update of a toast the user dismissed shows it again.
+///
+/// # Example
+/// ```rust
+/// use win32_notif::{notification::visual::Text, NotificationBuilder, ProgressToast, ToastsNotifier};
+///
+/// fn main() {
+///   let notifier = ToastsNotifier::new("Microsoft.Windows.Explorer").unwrap();
+///   let mut toast = ProgressToast::new(notifier, "upload", "transfers", || {
+///     NotificationBuilder::new().visual(Text::create(1, "Uploading report.pdf"))
+///   });
+///
+///   toast.update(0.3, "Uploading...").unwrap();
+///   toast.update(0.6, "Uploading...").unwrap();
+///   toast.complete().unwrap();
+/// }
+/// ```
+pub struct ProgressToast {
+  notifier: ToastsNotifier,
+  tag: String,
+  group: String,
+  sequence: u32,
+  value: f64,
+  status: String,
+  shown: bool,
+  content: Box<dyn Fn() -> NotificationBuilder + Send + Sync>,
+}
+
+impl ProgressToast {
+  /// Creates the toast without showing it
+  ///
+  /// `content` builds everything but the progress bar, which is added last. It is
+  /// called again whenever the toast has to be shown again.
+  pub fn new<F>(notifier: ToastsNotifier, tag: &str, group: &str, content: F) -> Self
+  where
+    F: Fn() -> NotificationBuilder + Send + Sync + 'static,
+  {
+    Self {
+      notifier,
+      tag: tag.to_string(),
+      group: group.to_string(),
+      sequence: 0,
+      value: 0.0,
+      status: String::new(),
+      shown: false,
+      content: Box::new(content),
+    }
+  }
+
+  /// Sets the progress to `value`, between 0 and 1, with the `status` text
+  ///
+  /// The toast is shown on the first update and shown again if it was dismissed.
+  pub fn update(&mut self, value: f64, status: &str) -> Result<(), NotifError> {
+    self.value = value.clamp(0.0, 1.0);
+    self.status = status.to_string();
+    self.push(true)
+  }
+
+  /// Fills the progress bar, keeping the last status text
+  ///
+  /// A toast the user dismissed is not shown again.
+  pub fn complete(&mut self) -> Result<(), NotifError> {
+    self.value = 1.0;
+    self.push(false)
+  }
+
+  fn push(&mut self, reshow: bool) -> Result<(), NotifError> {
+    self.sequence += 1;
+    if !self.shown {
+      return self.show();
+    }
+
+    let data = NotificationDataSet::new()?;
+    data.set_sequence_number(self.sequence)?;
+    data.insert(VALUE_KEY, &self.value.to_string())?;
+    data.insert(STATUS_KEY, &self.status)?;
+
+    match self.notifier.update(&data, &self.group, &self.tag)? {
+      NotificationUpdateResult::Succeeded => Ok(()),
+      NotificationUpdateResult::NotificationNotFound if reshow => self.show(),
+      NotificationUpdateResult::NotificationNotFound => Ok(()),
+      _ => Err(NotifError::UpdateFailed),
+    }
+  }
+
+  fn show(&mut self) -> Result<(), NotifError> {
+    let notification = (self.content)()
+      .visual(Progress::create(
+        AdaptiveText::BindTo(STATUS_KEY),
+        ProgressValue::BindTo(VALUE_KEY),
+      ))
+      .value(VALUE_KEY, self.value.to_string())
+      .value(STATUS_KEY, self.status.as_str())
+      .build(self.sequence, &self.notifier, &self.tag, &self.group)?;
+    notification.show()?;
+    self.shown = true;
+    Ok(())
+  }
+}
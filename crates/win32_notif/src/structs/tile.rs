@@ -0,0 +1,173 @@
+use quick_xml::events::BytesStart;
+use windows::{
+  core::HSTRING,
+  UI::Notifications::{TileNotification, TileUpdateManager, TileUpdater},
+};
+
+use crate::{
+  notification::{
+    visual::TextOrImageElement,
+    xml::{self, ToastXml},
+  },
+  NotifError, ToXML,
+};
+
+/// Learn more here
+/// <https://learn.microsoft.com/en-us/windows/apps/design/shell/tiles-and-notifications/create-adaptive-tiles>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileSize {
+  Small,
+  Medium,
+  Wide,
+  Large,
+}
+
+impl TileSize {
+  fn template(&self) -> &'static str {
+    match self {
+      TileSize::Small => "TileSmall",
+      TileSize::Medium => "TileMedium",
+      TileSize::Wide => "TileWide",
+      TileSize::Large => "TileLarge",
+    }
+  }
+}
+
+/// The content of the tile for one size
+pub struct TileBinding {
+  size: TileSize,
+  visual: Vec<Box<dyn TextOrImageElement>>,
+}
+
+impl TileBinding {
+  pub fn new(size: TileSize) -> Self {
+    Self {
+      size,
+      visual: vec![],
+    }
+  }
+
+  pub fn visual<T: TextOrImageElement + 'static>(mut self, visual: T) -> Self {
+    self.visual.push(Box::new(visual));
+    self
+  }
+}
+
+/// A live tile update, sizes without a binding keep the default tile
+///
+/// # Example
+/// ```rust
+/// use win32_notif::{notification::visual::Text, Tile, TileBinding, TileNotifier, TileSize};
+///
+/// fn main() {
+///   let notifier = TileNotifier::new("Microsoft.Windows.Explorer").unwrap();
+///   let tile = Tile::new()
+///     .binding(TileBinding::new(TileSize::Medium).visual(Text::create(1, "3 conflicts")))
+///     .binding(TileBinding::new(TileSize::Wide).visual(Text::create(1, "3 files in conflict")));
+///
+///   notifier.update(&tile).unwrap();
+/// }
+/// ```
+pub struct Tile {
+  bindings: Vec<TileBinding>,
+  tag: Option<String>,
+}
+
+impl Tile {
+  pub fn new() -> Self {
+    Self {
+      bindings: vec![],
+      tag: None,
+    }
+  }
+
+  pub fn binding(mut self, binding: TileBinding) -> Self {
+    self.bindings.push(binding);
+    self
+  }
+
+  /// Replaces the queued update with the same tag, see [`TileNotifier::enable_queue`]
+  pub fn with_tag(mut self, tag: &str) -> Self {
+    self.tag = Some(tag.to_string());
+    self
+  }
+
+  /// Gets the tile document that [`TileNotifier::update`] loads
+  pub fn to_xml_string(&self) -> Result<String, NotifError> {
+    let mut xml = ToastXml::new();
+    xml.start(BytesStart::new("tile"))?;
+    xml.start(BytesStart::new("visual"))?;
+    for binding in &self.bindings {
+      xml.start(
+        BytesStart::new("binding").with_attributes([("template", binding.size.template())]),
+      )?;
+      for visual in &binding.visual {
+        xml.fragment("visual", &visual.to_xml())?;
+      }
+      xml.end("binding")?;
+    }
+    xml.end("visual")?;
+    xml.end("tile")?;
+    Ok(xml.finish())
+  }
+}
+
+/// Updates the live tile of the application
+///
+/// Tiles need the application to have package identity and to be pinned to Start.
+pub struct TileNotifier {
+  _inner: TileUpdater,
+}
+
+impl TileNotifier {
+  pub fn new(app_id: &str) -> Result<Self, NotifError> {
+    Ok(Self {
+      _inner: TileUpdateManager::CreateTileUpdaterForApplicationWithId(&HSTRING::from(app_id))?,
+    })
+  }
+
+  pub fn update(&self, tile: &Tile) -> Result<(), NotifError> {
+    let content = tile.to_xml_string()?;
+    let doc = xml::load(&content)?;
+
+    let notification = TileNotification::CreateTileNotification(&doc)
+      .map_err(|e| xml::rejected("CreateTileNotification", e, &content))?;
+    if let Some(tag) = &tile.tag {
+      notification.SetTag(&HSTRING::from(tag.as_str()))?;
+    }
+    Ok(self._inner.Update(&notification)?)
+  }
+
+  /// Cycles through the last five updates instead of showing only the latest
+  pub fn enable_queue(&self, enable: bool) -> Result<(), NotifError> {
+    Ok(self._inner.EnableNotificationQueue(enable)?)
+  }
+
+  /// Restores the default tile
+  pub fn clear(&self) -> Result<(), NotifError> {
+    Ok(self._inner.Clear()?)
+  }
+
+  pub unsafe fn as_raw(&self) -> &TileUpdater {
+    &self._inner
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Tile, TileBinding, TileSize};
+  use crate::notification::visual::Text;
+
+  #[test]
+  fn tile_xml() {
+    let xml = Tile::new()
+      .binding(TileBinding::new(TileSize::Medium).visual(Text::create(1, "3 conflicts")))
+      .binding(TileBinding::new(TileSize::Wide))
+      .to_xml_string()
+      .unwrap();
+
+    assert!(xml.starts_with(r#"<tile><visual><binding template="TileMedium">"#));
+    assert!(xml.contains("3 conflicts"));
+    assert!(xml.ends_with(r#"<binding template="TileWide"></binding></visual></tile>"#));
+  }
+}
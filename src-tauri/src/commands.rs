@@ -2,7 +2,28 @@ use crate::AppStateHandle;
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use chrono::{Duration, Utc};
 use cloudreve_sync::{
-    config::LogLevel, ConfigManager, Credentials, DriveConfig, DriveInfo, StatusSummary,
+    cfapi::placeholder::PinState,
+    config::{
+        AlertCategory, AlertStyle, BandwidthConfig, BandwidthLimits, LogLevel, NotificationConfig,
+        NotificationPrefs, ProxyConfig, ScheduledJob, UpdateConfig, WebhookConfig,
+    },
+    drive::{
+        commands::ConflictChoice,
+        manager::{
+            ConnectionTest, DiagnosticsBundle, DriveDisplaySettings, FileStatus, FreedSpace,
+            InventoryHealth, PauseDuration, ReclaimedSpace, RemoteFolder, RemoteFolderNode,
+            ScheduledTaskInfo, StorageBreakdown,
+        },
+        mounts::SyncRootPolicy,
+    },
+    inventory::{
+        ActivityFilter, ActivityPage, CfapiFailureRecord, CfapiFailureSummary, ConflictPage, DeadLetterRecord, EntryFilter, EntryPage, EntrySort, PageRequest,
+        SnapshotImportStats,
+    },
+    events::{metrics::MetricsSnapshot, LoggedEvent},
+    tasks::{TaskKind, TaskStatistics, Transfer, TransferLimits},
+    updater::{UpdateStatus, Updater},
+    ConfigManager, Credentials, DriveConfig, DriveInfo, StatusSummary,
 };
 #[cfg(target_os = "macos")]
 use tauri::TitleBarStyle;
@@ -13,7 +34,10 @@ use tauri::{
 };
 use tauri_plugin_autostart::ManagerExt;
 use tauri_plugin_frame::WebviewWindowExt;
+use tauri_plugin_opener::OpenerExt;
 use tauri_plugin_positioner::{Position, WindowExt};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use uuid::Uuid;
 
 /// Result type for Tauri commands
@@ -132,10 +156,14 @@ pub async fn add_drive(
         sync_path: config.local_path.into(),
         icon_path: None,
         raw_icon_path: None,
+        color: None,
         enabled: true,
         user_id: config.user_id,
         sync_root_id: None,
         ignore_patterns: Vec::new(),
+        sync_root_policy: Default::default(),
+        navigation_pane: Default::default(),
+        sync_pause: None,
         extra: Default::default(),
     };
 
@@ -160,69 +188,866 @@ pub async fn add_drive(
 #[tauri::command]
 pub async fn remove_drive(
     state: State<'_, AppStateHandle>,
-    drive_id: String,
-) -> CommandResult<Option<DriveConfig>> {
+    drive_id: String,
+) -> CommandResult<Option<DriveConfig>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+
+    let result = app_state
+        .drive_manager
+        .remove_drive(&drive_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Persist drive configurations after removal
+    app_state
+        .drive_manager
+        .persist()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(result)
+}
+
+/// Get sync status for a drive
+#[tauri::command]
+pub async fn get_sync_status(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+) -> CommandResult<serde_json::Value> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .get_sync_status(&drive_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get status summary including all drives and recent tasks
+#[tauri::command]
+pub async fn get_status_summary(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+) -> CommandResult<StatusSummary> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .get_status_summary(drive_id.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Get all drives with their status information for the settings UI
+#[tauri::command]
+pub async fn get_drives_info(state: State<'_, AppStateHandle>) -> CommandResult<Vec<DriveInfo>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .get_drives_info()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Query inventory entries with filtering, sorting and pagination.
+/// Used by the activity and files views to browse synced items.
+#[tauri::command]
+pub async fn query_inventory_entries(
+    state: State<'_, AppStateHandle>,
+    filter: Option<EntryFilter>,
+    sort: Option<EntrySort>,
+    page: Option<PageRequest>,
+) -> CommandResult<EntryPage> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .query_inventory_entries(
+            &filter.unwrap_or_default(),
+            sort.unwrap_or_default(),
+            page.unwrap_or_default(),
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// List a page of the recorded conflicts for the conflict center, newest first.
+/// `drive_id` limits the result to one drive; `pending_only` hides resolved conflicts.
+/// `local_path` lists every conflict of that file instead, including conflict copies.
+#[tauri::command]
+pub async fn list_conflicts(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+    pending_only: Option<bool>,
+    local_path: Option<String>,
+    page: Option<PageRequest>,
+) -> CommandResult<ConflictPage> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    let page = page.unwrap_or_default().normalized();
+    let Some(local_path) = local_path else {
+        return app_state
+            .drive_manager
+            .query_conflicts(drive_id.as_deref(), pending_only.unwrap_or(true), page)
+            .map_err(|e| e.to_string());
+    };
+    let conflicts = app_state
+        .drive_manager
+        .list_conflicts_for_path(&local_path)
+        .map_err(|e| e.to_string())?;
+    Ok(ConflictPage {
+        total: conflicts.len() as i64,
+        conflicts: conflicts
+            .into_iter()
+            .skip(page.offset as usize)
+            .take(page.limit as usize)
+            .collect(),
+        offset: page.offset,
+        limit: page.limit,
+    })
+}
+
+/// List a page of the activity tab's timeline, newest first: finished uploads and
+/// downloads, deletions and conflicts. `drive_id` limits the result to one drive.
+#[tauri::command]
+pub async fn get_activity(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+    page: Option<PageRequest>,
+    filter: Option<ActivityFilter>,
+) -> CommandResult<ActivityPage> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .query_activity(
+            drive_id.as_deref(),
+            &filter.unwrap_or_default(),
+            page.unwrap_or_default(),
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Resolve the pending conflict `id` of a drive by keeping the local version, the
+/// remote version or both.
+#[tauri::command]
+pub async fn resolve_conflict(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    id: i64,
+    choice: ConflictChoice,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .resolve_conflict_by_id(&drive_id, id, choice)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List the folders directly inside the remote folder `uri` of a drive, for the
+/// "Send to Cloudreve" folder picker
+#[tauri::command]
+pub async fn list_remote_folders(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    uri: String,
+) -> CommandResult<Vec<RemoteFolder>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .list_remote_folders(&drive_id, &uri)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Remote folders below `path` (relative to the drive's remote root, the root if
+/// omitted) with their sizes, `depth` levels deep, for the selective sync picker.
+/// Listings are cached for a few minutes unless `refresh` is set.
+#[tauri::command]
+pub async fn get_remote_tree(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    path: Option<String>,
+    depth: Option<u32>,
+    refresh: Option<bool>,
+) -> CommandResult<Vec<RemoteFolderNode>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .get_remote_tree(
+            &drive_id,
+            path.as_deref().unwrap_or_default(),
+            depth.unwrap_or(1),
+            refresh.unwrap_or(false),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Upload files from outside the sync roots into the remote folder `folder_uri` of a
+/// drive. Returns once the uploads started; a notification reports the outcome.
+#[tauri::command]
+pub async fn send_to_drive(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    folder_uri: String,
+    paths: Vec<String>,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .send_to_drive(
+            &drive_id,
+            &folder_uri,
+            paths.into_iter().map(PathBuf::from).collect(),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Set the pin state of files and folders in sync folders: `Pinned` keeps them on this
+/// device, `Unpinned` frees up their space. Folders apply it to everything inside.
+#[tauri::command]
+pub async fn set_pin_state(
+    state: State<'_, AppStateHandle>,
+    paths: Vec<String>,
+    pin_state: PinState,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+    app_state
+        .drive_manager
+        .set_pin_state(&paths, pin_state)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Free up disk space by turning hydrated files back into cloud-only placeholders.
+/// `paths` are files or folders in sync folders; with `unused_days`, only files not
+/// used for that many days are dehydrated.
+#[tauri::command]
+pub async fn free_up_paths(
+    state: State<'_, AppStateHandle>,
+    paths: Vec<String>,
+    unused_days: Option<u32>,
+) -> CommandResult<FreedSpace> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+    let unused_for = unused_days.map(|days| std::time::Duration::from_secs(days as u64 * 86400));
+    app_state
+        .drive_manager
+        .free_up_paths(&paths, unused_for)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Free up the space a drive takes on this device: dehydrate its files, with
+/// `older_than_days` only those not used for that many days, and clear the thumbnail
+/// and folder listing caches. Returns the space reclaimed for the settings page.
+#[tauri::command]
+pub async fn free_up_space(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    older_than_days: Option<u32>,
+) -> CommandResult<ReclaimedSpace> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    let unused_for =
+        older_than_days.map(|days| std::time::Duration::from_secs(days as u64 * 86400));
+    app_state
+        .drive_manager
+        .free_up_space(&drive_id, unused_for)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Free up disk space by dehydrating every file not used for `unused_days` days.
+/// `drive_id` limits it to one drive.
+#[tauri::command]
+pub async fn free_up_unused_space(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+    unused_days: u32,
+) -> CommandResult<FreedSpace> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .free_up_unused_space(
+            drive_id.as_deref(),
+            std::time::Duration::from_secs(unused_days as u64 * 86400),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Storage of a drive for the disk usage bar in settings: the account's quota, the
+/// size of the synced files and how much of it is stored on this device
+#[tauri::command]
+pub async fn get_storage_breakdown(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+) -> CommandResult<StorageBreakdown> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .get_storage_breakdown(&drive_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List the pending and running uploads, downloads and hydrations for the transfers
+/// page, running ones first. `drive_id` limits the list to one drive.
+#[tauri::command]
+pub async fn get_transfers(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+) -> CommandResult<Vec<Transfer>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .get_transfers(drive_id.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Sync state of a local file for the file detail popover: hydration and pin state,
+/// pending task, last error and link to the file on the web
+#[tauri::command]
+pub async fn get_file_status(
+    state: State<'_, AppStateHandle>,
+    path: String,
+) -> CommandResult<FileStatus> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .get_file_status(std::path::Path::new(&path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Open the file or folder at a local path in the instance's web UI, as "View online"
+/// in Explorer does
+#[tauri::command]
+pub async fn open_online(
+    app: AppHandle,
+    state: State<'_, AppStateHandle>,
+    path: String,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    let url = app_state
+        .drive_manager
+        .online_url(std::path::Path::new(&path))
+        .await
+        .map_err(|e| e.to_string())?;
+    app.opener()
+        .open_url(url, None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
+/// Cancel a pending or running task. Returns `false` if it already finished.
+#[tauri::command]
+pub async fn cancel_task(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    task_id: String,
+) -> CommandResult<bool> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .cancel_task(&drive_id, &task_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Cancel every pending or running task in one call. `drive_id` limits it to one drive
+/// and `kind` to one task type. Returns the number of tasks cancelled.
+#[tauri::command]
+pub async fn cancel_tasks(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+    kind: Option<TaskKind>,
+) -> CommandResult<usize> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .cancel_tasks(drive_id.as_deref(), kind)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Run every failed or timed out task again. `drive_id` limits it to one drive.
+/// Returns the number of tasks requeued.
+#[tauri::command]
+pub async fn requeue_failed_tasks(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+) -> CommandResult<usize> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .requeue_failed_tasks(drive_id.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Pause syncing for `duration`, until resumed if not given. `drive_id` limits it to
+/// one drive. Files the user opens are still downloaded.
+#[tauri::command]
+pub async fn pause_sync(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+    duration: Option<PauseDuration>,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    let until = duration.unwrap_or(PauseDuration::Indefinitely).until_now();
+    app_state
+        .drive_manager
+        .pause_sync(drive_id.as_deref(), until)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Resume syncing paused by [`pause_sync`]. `drive_id` limits it to one drive.
+#[tauri::command]
+pub async fn resume_sync(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .resume_sync(drive_id.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Walk a drive again and reconcile it with the server in the background. The
+/// `FullResync` event reports when it starts and finishes.
+#[tauri::command]
+pub async fn force_resync(state: State<'_, AppStateHandle>, drive_id: String) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .force_resync(&drive_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Limit how many tasks of each type run at once on a drive, e.g. `{"Upload": 2}`.
+/// A limit of 0 removes the limit for that type.
+#[tauri::command]
+pub async fn set_task_type_limits(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    limits: HashMap<TaskKind, usize>,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .set_task_type_limits(&drive_id, limits)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Replay changes made to the sync folder while the app was not running, using the
+/// volume change journal.
+#[tauri::command]
+pub async fn set_usn_journal_enabled(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    enabled: bool,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .set_usn_journal_enabled(&drive_id, enabled)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Have Windows ask the app to validate downloaded file data before applications read it
+#[tauri::command]
+pub async fn set_data_validation_enabled(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    enabled: bool,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .set_data_validation_enabled(&drive_id, enabled)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Choose how Windows hydrates, dehydrates and populates placeholders of a drive
+#[tauri::command]
+pub async fn set_sync_root_policy(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    policy: SyncRootPolicy,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .set_sync_root_policy(&drive_id, policy)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Rename a drive and change its icon and color, in the app and in Explorer
+#[tauri::command]
+pub async fn update_drive_settings(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    settings: DriveDisplaySettings,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .update_drive_settings(&drive_id, settings)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Pin or unpin a drive in Explorer's navigation pane
+#[tauri::command]
+pub async fn set_navigation_pane_pinned(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    pinned: bool,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .set_navigation_pane_pinned(&drive_id, pinned)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Order the drives in Explorer's navigation pane, first drive on top
+#[tauri::command]
+pub async fn set_navigation_pane_order(
+    state: State<'_, AppStateHandle>,
+    drive_ids: Vec<String>,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .set_navigation_pane_order(&drive_ids)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Task counters, failure rates, run and queue wait times per task type, for dashboards.
+/// `drive_id` limits the result to one drive.
+#[tauri::command]
+pub async fn get_task_statistics(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+) -> CommandResult<Vec<TaskStatistics>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .task_statistics(drive_id.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List tasks that failed after all retries, newest first.
+/// `drive_id` limits the result to one drive.
+#[tauri::command]
+pub async fn list_dead_letter_tasks(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+) -> CommandResult<Vec<DeadLetterRecord>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .list_dead_letters(drive_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Run a dead-lettered task again. Returns the id of the new task.
+#[tauri::command]
+pub async fn requeue_dead_letter_task(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    task_id: String,
+) -> CommandResult<String> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .requeue_dead_letter(&drive_id, &task_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Drop a dead-lettered task. Returns `false` if it was already gone.
+#[tauri::command]
+pub async fn discard_dead_letter_task(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    task_id: String,
+) -> CommandResult<bool> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .discard_dead_letter(&drive_id, &task_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List recent cloud filter callback failures, newest first.
+/// `drive_id` limits the result to one drive.
+#[tauri::command]
+pub async fn list_cfapi_failures(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+    limit: Option<i64>,
+) -> CommandResult<Vec<CfapiFailureRecord>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .list_cfapi_failures(drive_id.as_deref(), limit.unwrap_or(200))
+        .map_err(|e| e.to_string())
+}
+
+/// Count cloud filter callback failures per class since `since` (Unix timestamp,
+/// defaults to the last 24 hours).
+#[tauri::command]
+pub async fn get_cfapi_failure_summary(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+    since: Option<i64>,
+) -> CommandResult<Vec<CfapiFailureSummary>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    let since = since.unwrap_or_else(|| (Utc::now() - Duration::hours(24)).timestamp());
+    app_state
+        .drive_manager
+        .cfapi_failure_summary(drive_id.as_deref(), since)
+        .map_err(|e| e.to_string())
+}
+
+/// Events logged before the window opened, oldest first, so it can show what it
+/// missed. With `after` (the id of the last event seen), only the events that followed.
+#[tauri::command]
+pub async fn list_recent_events(
+    state: State<'_, AppStateHandle>,
+    after: Option<i64>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<LoggedEvent>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    Ok(app_state
+        .event_broadcaster
+        .replay(after, limit.unwrap_or(100)))
+}
+
+/// Files synced, bytes transferred, errors and other counters since the app started
+#[tauri::command]
+pub async fn get_event_metrics(state: State<'_, AppStateHandle>) -> CommandResult<MetricsSnapshot> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    Ok(app_state.metrics.snapshot())
+}
+
+/// Remove recorded cloud filter callback failures. Returns how many were removed.
+#[tauri::command]
+pub async fn clear_cfapi_failures(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+) -> CommandResult<usize> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .clear_cfapi_failures(drive_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Advanced troubleshooting: check the inventory database of one drive or all drives.
+/// With `repair` set, problems found are fixed and a damaged database is rebuilt.
+#[tauri::command]
+pub async fn troubleshoot_inventory(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+    repair: Option<bool>,
+) -> CommandResult<Vec<InventoryHealth>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .troubleshoot_inventory(drive_id.as_deref(), repair.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// List recurring maintenance jobs with their schedule and last outcome
+#[tauri::command]
+pub async fn list_scheduled_tasks(
+    state: State<'_, AppStateHandle>,
+) -> CommandResult<Vec<ScheduledTaskInfo>> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    Ok(app_state.drive_manager.list_scheduled_tasks().await)
+}
+
+/// Schedule a recurring maintenance job, replacing its current schedule
+#[tauri::command]
+pub async fn schedule_task(
+    state: State<'_, AppStateHandle>,
+    job: ScheduledJob,
+    cron: String,
+) -> CommandResult<()> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .schedule_task(job, &cron)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Stop a recurring maintenance job and disable it until it is scheduled again
+#[tauri::command]
+pub async fn cancel_scheduled_task(
+    state: State<'_, AppStateHandle>,
+    job: ScheduledJob,
+) -> CommandResult<bool> {
     let app_state = state
         .get()
         .ok_or_else(|| "App not yet initialized".to_string())?;
-
-    let result = app_state
-        .drive_manager
-        .remove_drive(&drive_id)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Persist drive configurations after removal
     app_state
         .drive_manager
-        .persist()
+        .cancel_scheduled_task(job)
         .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(result)
+        .map_err(|e| e.to_string())
 }
 
-/// Get sync status for a drive
+/// Export a drive's inventory to a portable snapshot file
 #[tauri::command]
-pub async fn get_sync_status(
+pub async fn export_inventory_snapshot(
     state: State<'_, AppStateHandle>,
     drive_id: String,
-) -> CommandResult<serde_json::Value> {
+    path: String,
+) -> CommandResult<()> {
     let app_state = state
         .get()
         .ok_or_else(|| "App not yet initialized".to_string())?;
     app_state
         .drive_manager
-        .get_sync_status(&drive_id)
+        .export_inventory_snapshot(&drive_id, std::path::Path::new(&path))
         .await
         .map_err(|e| e.to_string())
 }
 
-/// Get status summary including all drives and recent tasks
+/// Write a zip archive of redacted logs, drive configuration and inventory state to
+/// attach to bug reports
 #[tauri::command]
-pub async fn get_status_summary(
+pub async fn export_diagnostics(
     state: State<'_, AppStateHandle>,
-    drive_id: Option<String>,
-) -> CommandResult<StatusSummary> {
+    path: String,
+) -> CommandResult<DiagnosticsBundle> {
     let app_state = state
         .get()
         .ok_or_else(|| "App not yet initialized".to_string())?;
     app_state
         .drive_manager
-        .get_status_summary(drive_id.as_deref())
+        .export_diagnostics(std::path::Path::new(&path))
         .await
         .map_err(|e| e.to_string())
 }
 
-/// Get all drives with their status information for the settings UI
+/// Import a previously exported inventory snapshot into a drive
 #[tauri::command]
-pub async fn get_drives_info(state: State<'_, AppStateHandle>) -> CommandResult<Vec<DriveInfo>> {
+pub async fn import_inventory_snapshot(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    path: String,
+) -> CommandResult<SnapshotImportStats> {
     let app_state = state
         .get()
         .ok_or_else(|| "App not yet initialized".to_string())?;
     app_state
         .drive_manager
-        .get_drives_info()
+        .import_inventory_snapshot(&drive_id, std::path::Path::new(&path))
         .await
         .map_err(|e| e.to_string())
 }
@@ -463,6 +1288,100 @@ pub fn show_settings_window_impl(app: &AppHandle) {
     }
 }
 
+/// Show or create the conflicts window, filtered to the conflicts of `local_path`
+pub fn show_conflicts_window_impl(app: &AppHandle, local_path: &str) {
+    // An open window follows the OpenConflictsWindow event to switch its filter
+    if let Some(window) = app.get_webview_window("conflicts") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        return;
+    }
+
+    let url_path = format!(
+        "index.html/#/conflicts?path={}",
+        urlencoding::encode(local_path)
+    );
+    let builder = WebviewWindowBuilder::new(
+        app,
+        "conflicts",
+        WebviewUrl::App(get_url_with_lang(&url_path).into()),
+    )
+    .title("Conflicts")
+    .inner_size(560.0, 480.0)
+    .min_inner_size(420.0, 320.0)
+    .visible(false)
+    .resizable(true)
+    .decorations(false)
+    .minimizable(true);
+
+    // Platform-specific: title_bar_style and hidden_title are macOS-only
+    #[cfg(target_os = "macos")]
+    let builder = builder
+        .title_bar_style(TitleBarStyle::Overlay)
+        .hidden_title(true);
+
+    match builder.build() {
+        Ok(window) => {
+            let _ = window.move_window(Position::Center);
+            let _ = window.create_overlay_titlebar();
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+        Err(e) => {
+            tracing::error!(target: "main", error = %e, "Failed to create conflicts window");
+        }
+    }
+}
+
+/// Show or create the "Send to Cloudreve" window for `local_paths`
+pub fn show_send_to_window_impl(app: &AppHandle, local_paths: &[String]) {
+    // An open window follows the OpenSendToWindow event to switch its files
+    if let Some(window) = app.get_webview_window("send-to") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        return;
+    }
+
+    let query = local_paths
+        .iter()
+        .map(|path| format!("path={}", urlencoding::encode(path)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let url_path = format!("index.html/#/send-to?{}", query);
+    let builder = WebviewWindowBuilder::new(
+        app,
+        "send-to",
+        WebviewUrl::App(get_url_with_lang(&url_path).into()),
+    )
+    .title("Send to Cloudreve")
+    .inner_size(470.0, 560.0)
+    .min_inner_size(400.0, 420.0)
+    .visible(false)
+    .resizable(true)
+    .decorations(false)
+    .minimizable(true);
+
+    // Platform-specific: title_bar_style and hidden_title are macOS-only
+    #[cfg(target_os = "macos")]
+    let builder = builder
+        .title_bar_style(TitleBarStyle::Overlay)
+        .hidden_title(true);
+
+    match builder.build() {
+        Ok(window) => {
+            let _ = window.move_window(Position::Center);
+            let _ = window.create_overlay_titlebar();
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+        Err(e) => {
+            tracing::error!(target: "main", error = %e, "Failed to create send to window");
+        }
+    }
+}
+
 /// Set auto-start configuration and persist to config file
 #[tauri::command]
 pub async fn set_auto_start(app: AppHandle, enabled: bool) -> CommandResult<()> {
@@ -482,22 +1401,6 @@ pub async fn set_auto_start(app: AppHandle, enabled: bool) -> CommandResult<()>
     Ok(())
 }
 
-/// Set notification settings for credential expiry
-#[tauri::command]
-pub async fn set_notify_credential_expired(enabled: bool) -> CommandResult<()> {
-    ConfigManager::get()
-        .set_notify_credential_expired(enabled)
-        .map_err(|e| e.to_string())
-}
-
-/// Set notification settings for file conflicts
-#[tauri::command]
-pub async fn set_notify_file_conflict(enabled: bool) -> CommandResult<()> {
-    ConfigManager::get()
-        .set_notify_file_conflict(enabled)
-        .map_err(|e| e.to_string())
-}
-
 /// Set fast popup launch setting
 #[tauri::command]
 pub async fn set_fast_popup_launch(enabled: bool) -> CommandResult<()> {
@@ -511,27 +1414,41 @@ pub async fn set_fast_popup_launch(enabled: bool) -> CommandResult<()> {
 pub async fn get_general_settings() -> CommandResult<GeneralSettings> {
     let config = ConfigManager::get().get_config();
     Ok(GeneralSettings {
-        notify_credential_expired: config.notify_credential_expired,
-        notify_file_conflict: config.notify_file_conflict,
         fast_popup_launch: config.fast_popup_launch,
         log_to_file: config.log_to_file,
         log_level: config.log_level.as_str().to_string(),
         log_max_files: config.log_max_files,
         log_dir: ConfigManager::get_log_dir().display().to_string(),
         language: config.language,
+        encrypt_inventory: config.encrypt_inventory,
+        task_history_retention_days: config.task_history_retention_days,
+        task_history_max_entries: config.task_history_max_entries,
+        thumbnail_cache_max_mb: config.thumbnail_cache_max_mb,
+        prefetch_enabled: config.prefetch_enabled,
+        prefetch_budget_mb: config.prefetch_budget_mb,
+        webhook: config.webhook,
+        notifications: config.notifications,
+        update: config.update,
     })
 }
 
 #[derive(serde::Serialize)]
 pub struct GeneralSettings {
-    pub notify_credential_expired: bool,
-    pub notify_file_conflict: bool,
     pub fast_popup_launch: bool,
     pub log_to_file: bool,
     pub log_level: String,
     pub log_max_files: usize,
     pub log_dir: String,
     pub language: Option<String>,
+    pub encrypt_inventory: bool,
+    pub task_history_retention_days: u32,
+    pub task_history_max_entries: usize,
+    pub thumbnail_cache_max_mb: u64,
+    pub prefetch_enabled: bool,
+    pub prefetch_budget_mb: u64,
+    pub webhook: Option<WebhookConfig>,
+    pub notifications: NotificationConfig,
+    pub update: UpdateConfig,
 }
 
 /// Set log to file setting
@@ -561,6 +1478,259 @@ pub async fn set_log_max_files(max_files: usize) -> CommandResult<()> {
         .map_err(|e| e.to_string())
 }
 
+/// Set inventory encryption setting
+#[tauri::command]
+pub async fn set_encrypt_inventory(enabled: bool) -> CommandResult<()> {
+    // Databases are converted when they are next opened (requires restart to take effect)
+    ConfigManager::get()
+        .set_encrypt_inventory(enabled)
+        .map_err(|e| e.to_string())
+}
+
+/// Set how long and how many finished tasks are kept in the task history
+#[tauri::command]
+pub async fn set_task_history_retention(days: u32, max_entries: usize) -> CommandResult<()> {
+    ConfigManager::get()
+        .set_task_history_retention(days, max_entries)
+        .map_err(|e| e.to_string())
+}
+
+/// Set the maximum size of the thumbnail cache, shrinking it right away if needed
+#[tauri::command]
+pub async fn set_thumbnail_cache_limit(
+    state: State<'_, AppStateHandle>,
+    max_mb: u64,
+) -> CommandResult<()> {
+    ConfigManager::get()
+        .set_thumbnail_cache_max_mb(max_mb)
+        .map_err(|e| e.to_string())?;
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .prune_thumbnail_cache(max_mb * 1024 * 1024)
+        .map_err(|e| e.to_string())
+}
+
+/// Set whether files likely to be opened next are hydrated in the background, and how
+/// much disk space they may take up
+#[tauri::command]
+pub async fn set_prefetch_settings(enabled: bool, budget_mb: u64) -> CommandResult<()> {
+    ConfigManager::get()
+        .set_prefetch(enabled, budget_mb)
+        .map_err(|e| e.to_string())
+}
+
+/// Set the webhook selected events are forwarded to, `None` disables forwarding
+#[tauri::command]
+pub async fn set_webhook(webhook: Option<WebhookConfig>) -> CommandResult<()> {
+    ConfigManager::get()
+        .set_webhook(webhook)
+        .map_err(|e| e.to_string())
+}
+
+/// Set whether and how often updates are checked for in the background
+#[tauri::command]
+pub async fn set_update_settings(update: UpdateConfig) -> CommandResult<()> {
+    ConfigManager::get()
+        .set_update_config(update)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the transfer rate limits shared by all drives and of single drives
+#[tauri::command]
+pub async fn get_bandwidth_limits() -> CommandResult<BandwidthConfig> {
+    Ok(ConfigManager::get().bandwidth())
+}
+
+/// Set the transfer rate limits in KB/s of a drive, or the ones shared by all drives
+/// if `drive_id` is omitted. Omitted rates are not limited. Running transfers follow
+/// the new limits right away.
+#[tauri::command]
+pub async fn set_bandwidth_limits(
+    drive_id: Option<String>,
+    upload_kbps: Option<u32>,
+    download_kbps: Option<u32>,
+    limit_on_metered: bool,
+) -> CommandResult<()> {
+    let config = ConfigManager::get();
+    config
+        .set_bandwidth_limits(
+            drive_id.as_deref(),
+            BandwidthLimits {
+                upload_kbps,
+                download_kbps,
+                limit_on_metered,
+            },
+        )
+        .map_err(|e| e.to_string())?;
+    TransferLimits::get().apply(&config.bandwidth());
+    Ok(())
+}
+
+/// Get the proxy for requests to the servers
+#[tauri::command]
+pub async fn get_proxy_settings() -> CommandResult<ProxyConfig> {
+    Ok(ConfigManager::get().proxy())
+}
+
+/// Set the proxy for requests to the servers. Drives pick it up when they are next
+/// started; transfers use it right away.
+#[tauri::command]
+pub async fn set_proxy_settings(proxy: ProxyConfig) -> CommandResult<()> {
+    ConfigManager::get()
+        .set_proxy(proxy)
+        .map_err(|e| e.to_string())
+}
+
+/// Check that the instance of a drive can be reached through `proxy`, the saved proxy
+/// if omitted
+#[tauri::command]
+pub async fn test_connection(
+    state: State<'_, AppStateHandle>,
+    drive_id: String,
+    proxy: Option<ProxyConfig>,
+) -> CommandResult<ConnectionTest> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .test_connection(&drive_id, proxy)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Set the event types shown as toasts, the quiet hours and the muted drives
+#[tauri::command]
+pub async fn set_notifications(notifications: NotificationConfig) -> CommandResult<()> {
+    ConfigManager::get()
+        .set_notifications(notifications)
+        .map_err(|e| e.to_string())
+}
+
+/// Get whether each event type with an alert is shown as a toast, and the quiet hours
+#[tauri::command]
+pub async fn get_notification_prefs() -> CommandResult<NotificationPrefs> {
+    Ok(ConfigManager::get().notification_prefs())
+}
+
+/// Turn the toasts of the event types in `prefs` on or off and set the quiet hours
+#[tauri::command]
+pub async fn set_notification_prefs(prefs: NotificationPrefs) -> CommandResult<()> {
+    ConfigManager::get()
+        .set_notification_prefs(prefs)
+        .map_err(|e| e.to_string())
+}
+
+/// Set how the events of an alert category get the user's attention
+#[tauri::command]
+pub async fn set_alert_style(category: AlertCategory, style: AlertStyle) -> CommandResult<()> {
+    ConfigManager::get()
+        .set_alert_style(category, style)
+        .map_err(|e| e.to_string())
+}
+
+/// Check the update feed for a newer release
+#[tauri::command]
+pub async fn check_for_updates(state: State<'_, AppStateHandle>) -> CommandResult<UpdateStatus> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state.updater.check().await.map_err(|e| e.to_string())
+}
+
+/// Download the newer release, then restart to install it once no transfer is running
+#[tauri::command]
+pub async fn install_update(
+    app: AppHandle,
+    state: State<'_, AppStateHandle>,
+) -> CommandResult<UpdateStatus> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    let status = app_state
+        .updater
+        .install()
+        .await
+        .map_err(|e| e.to_string())?;
+    restart_to_update_impl(&app);
+    Ok(status)
+}
+
+/// Restart the app to install the downloaded update. The restart waits for running
+/// transfers to finish, and the drives are shut down before the package manager
+/// closes the app.
+pub fn restart_to_update_impl(app: &AppHandle) {
+    let Some(app_state) = AppStateHandle.get() else {
+        return;
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        Updater::wait_for_idle(&app_state.drive_manager).await;
+        crate::shutdown().await;
+        if let Err(e) = app_state.updater.apply().await {
+            tracing::error!(target: "updater", error = %e, "Failed to install update, restarting");
+            app.restart();
+        }
+    });
+}
+
+/// Size of the thumbnail cache in bytes
+#[tauri::command]
+pub async fn get_thumbnail_cache_size(state: State<'_, AppStateHandle>) -> CommandResult<u64> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .thumbnail_cache_size()
+        .map_err(|e| e.to_string())
+}
+
+/// Remove all cached thumbnails, returning the number of bytes freed
+#[tauri::command]
+pub async fn clear_thumbnail_cache(state: State<'_, AppStateHandle>) -> CommandResult<u64> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .clear_thumbnail_cache()
+        .map_err(|e| e.to_string())
+}
+
+/// Count finished tasks kept in the task history of one drive or all drives
+#[tauri::command]
+pub async fn get_task_history_count(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+) -> CommandResult<usize> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .task_history_count(drive_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Delete all finished tasks of one drive or all drives. Returns the number deleted.
+#[tauri::command]
+pub async fn clear_task_history(
+    state: State<'_, AppStateHandle>,
+    drive_id: Option<String>,
+) -> CommandResult<usize> {
+    let app_state = state
+        .get()
+        .ok_or_else(|| "App not yet initialized".to_string())?;
+    app_state
+        .drive_manager
+        .clear_task_history(drive_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
 /// Set language setting and update rust_i18n locale
 #[tauri::command]
 pub async fn set_language(app: AppHandle, language: Option<String>) -> CommandResult<()> {
@@ -575,6 +1745,11 @@ pub async fn set_language(app: AppHandle, language: Option<String>) -> CommandRe
     });
     rust_i18n::set_locale(&locale);
 
+    // Apply the language to the Explorer integration
+    if let Some(app_state) = AppStateHandle.get() {
+        app_state.drive_manager.refresh_shell_language().await;
+    }
+
     // Close main window to force reload with new language
      // Check if window already exists
     if let Some(window) = app.get_webview_window("main_popup") {
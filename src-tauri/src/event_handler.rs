@@ -1,11 +1,24 @@
-use cloudreve_sync::events::Event;
-use tauri::{AppHandle, Emitter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
-use crate::commands::{show_add_drive_window_impl, show_main_window_center, show_settings_window_impl};
+use cloudreve_sync::config::{AlertCategory, AlertStyle};
+use cloudreve_sync::events::{Event, VersionedEvent};
+use cloudreve_sync::utils::notifications::{event_alert, NotificationPolicy};
+use cloudreve_sync::utils::toast;
+use tauri::{async_runtime::spawn, AppHandle, Emitter, EventTarget, Manager};
+
+use crate::AppStateHandle;
+use crate::commands::{
+    restart_to_update_impl, show_add_drive_window_impl, show_conflicts_window_impl,
+    show_main_window_center, show_reauthorize_window_impl, show_send_to_window_impl,
+    show_settings_window_impl,
+};
 
 /// Handle incoming events from the event broadcaster.
 /// Returns true if the event was handled, false otherwise.
 pub fn handle_event(app_handle: &AppHandle, event: &Event) {
+    alert(app_handle, event);
+
     match event {
         Event::NoDrive { .. } => handle_no_drive(app_handle),
         Event::ConnectionStatusChanged { .. } => {
@@ -13,6 +26,45 @@ pub fn handle_event(app_handle: &AppHandle, event: &Event) {
         }
         Event::OpenSyncStatusWindow => handle_open_sync_status_window(app_handle),
         Event::OpenSettingsWindow => handle_open_settings_window(app_handle),
+        Event::RestartToUpdate => restart_to_update_impl(app_handle),
+        Event::OpenConflictsWindow { local_path } => {
+            show_conflicts_window_impl(app_handle, local_path)
+        }
+        Event::OpenSendToWindow { local_paths } => {
+            show_send_to_window_impl(app_handle, local_paths)
+        }
+        Event::OpenReauthorizeWindow {
+            drive_id,
+            site_url,
+            drive_name,
+        } => show_reauthorize_window_impl(app_handle, drive_id, site_url, drive_name),
+        Event::DeadLettersChanged { .. } => update_tray_failed_tasks(app_handle),
+        Event::InventoryCleaned { .. }
+        | Event::InventoryEntriesChanged { .. }
+        | Event::TaskFinished { .. }
+        | Event::TasksUpdated { .. }
+        | Event::TaskProgress { .. }
+        | Event::SyncRootRepaired { .. }
+        | Event::ShellServiceHealthChanged { .. }
+        | Event::FileSynced { .. }
+        | Event::FileDownloadStarted { .. }
+        | Event::FileDownloadFinished { .. }
+        | Event::UploadFailed { .. }
+        | Event::ConflictDetected { .. }
+        | Event::DriveAdded { .. }
+        | Event::DriveRemoved { .. }
+        | Event::DriveUpdated { .. }
+        | Event::CredentialExpired { .. }
+        | Event::QuotaWarning { .. }
+        | Event::SyncPauseChanged { .. }
+        | Event::FullResync { .. }
+        | Event::FullResyncProgress { .. }
+        | Event::UpdateAvailable { .. }
+        | Event::Repeated { .. }
+        | Event::TaskProgressBatch { .. }
+        | Event::FilesSynced { .. } => {
+            // Currently just forwarded to frontend via emit
+        }
     }
 }
 
@@ -29,11 +81,160 @@ fn handle_open_settings_window(app_handle: &AppHandle) {
     show_settings_window_impl(app_handle);
 }
 
-/// Emit an event to the frontend
+/// Alert the user of `event` in the style chosen for its category
+fn alert(app_handle: &AppHandle, event: &Event) {
+    // Skip reading the settings for the events that never alert, such as progress
+    if AlertCategory::of(event.name()).is_none() {
+        return;
+    }
+    let Some(alert) = event_alert(event) else {
+        return;
+    };
+
+    let style = NotificationPolicy::current().alert_style_now(event.name(), event.drive_id());
+    match style {
+        AlertStyle::Toast | AlertStyle::Silent if !alert.has_own_toast => {
+            toast::send_event_toast(style, &alert.tag, &alert.title, &alert.message)
+        }
+        AlertStyle::TrayFlash => flash_tray(app_handle, &alert.title),
+        _ => {}
+    }
+}
+
+/// Times the tray icon flashes for an alert
+const TRAY_FLASHES: usize = 5;
+const TRAY_FLASH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Set while the tray icon flashes, alerts in the meantime only update the tooltip
+static TRAY_FLASHING: AtomicBool = AtomicBool::new(false);
+
+/// Flash the tray icon, showing `title` in its tooltip while it flashes
+fn flash_tray(app_handle: &AppHandle, title: &str) {
+    let Some(tray) = app_handle.tray_by_id(crate::TRAY_ID) else {
+        return;
+    };
+    if let Err(e) = tray.set_tooltip(Some(format!("Cloudreve\n{}", title))) {
+        tracing::warn!(target: "events", error = %e, "Failed to update tray tooltip");
+    }
+    if TRAY_FLASHING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    let icon = app_handle
+        .default_window_icon()
+        .map(|icon| icon.clone().to_owned());
+    spawn(async move {
+        for flash in 0..TRAY_FLASHES * 2 {
+            let shown = if flash % 2 == 0 { None } else { icon.clone() };
+            if let Err(e) = tray.set_icon(shown) {
+                tracing::warn!(target: "events", error = %e, "Failed to flash tray icon");
+                break;
+            }
+            tokio::time::sleep(TRAY_FLASH_INTERVAL).await;
+        }
+        if let Err(e) = tray.set_icon(icon) {
+            tracing::warn!(target: "events", error = %e, "Failed to restore tray icon");
+        }
+        TRAY_FLASHING.store(false, Ordering::Release);
+        update_tray_failed_tasks(&app_handle);
+    });
+}
+
+/// Badge the tray icon tooltip with the number of tasks that failed for good
+pub fn update_tray_failed_tasks(app_handle: &AppHandle) {
+    let Some(app_state) = AppStateHandle.get() else {
+        return;
+    };
+    let count = match app_state.drive_manager.dead_letter_count(None) {
+        Ok(count) => count,
+        Err(e) => {
+            tracing::warn!(target: "events", error = %e, "Failed to count dead-lettered tasks");
+            return;
+        }
+    };
+    let Some(tray) = app_handle.tray_by_id(crate::TRAY_ID) else {
+        return;
+    };
+
+    let tooltip = if count > 0 {
+        t!("failedTasksTooltip", "count" => count).to_string()
+    } else {
+        "Cloudreve".to_string()
+    };
+    if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+        tracing::warn!(target: "events", error = %e, "Failed to update tray tooltip");
+    }
+}
+
+/// Emit an event to the frontend. Windows listen on their own window
+/// (`getCurrentWindow().listen`), so they only get the events routed to them by
+/// [`window_receives`]; app-wide listeners get every event.
 pub fn emit_event(app_handle: &AppHandle, event: &Event) {
-    if let Err(e) = app_handle.emit(event.name(), event) {
+    let payload = VersionedEvent::current(event);
+    let result = app_handle.emit_filter(event.name(), &payload, |target| match target {
+        EventTarget::Window { label }
+        | EventTarget::Webview { label }
+        | EventTarget::WebviewWindow { label } => window_receives(label, event),
+        _ => true,
+    });
+    if let Err(e) = result {
         tracing::error!(target: "events", error = %e, "Failed to emit event to frontend");
     } else {
         tracing::trace!(target: "events", event = ?event, "Event emitted to frontend");
     }
 }
+
+/// Whether the window labeled `label` listens to `event`. During large sync bursts,
+/// most events only matter to the status popup.
+fn window_receives(label: &str, event: &Event) -> bool {
+    match label {
+        "main_popup" => !matches!(
+            event,
+            Event::NoDrive { .. }
+                | Event::OpenSettingsWindow
+                | Event::RestartToUpdate
+                | Event::OpenConflictsWindow { .. }
+                | Event::OpenSendToWindow { .. }
+                | Event::OpenReauthorizeWindow { .. }
+        ),
+        // Add drive and reauthorize
+        "add-drive" => matches!(
+            event,
+            Event::NoDrive { .. }
+                | Event::OpenReauthorizeWindow { .. }
+                | Event::DriveAdded { .. }
+                | Event::DriveRemoved { .. }
+                | Event::CredentialExpired { .. }
+        ),
+        "settings" => matches!(
+            event,
+            Event::OpenSettingsWindow
+                | Event::DriveAdded { .. }
+                | Event::DriveRemoved { .. }
+                | Event::DriveUpdated { .. }
+                | Event::CredentialExpired { .. }
+                | Event::QuotaWarning { .. }
+                | Event::SyncPauseChanged { .. }
+                | Event::FullResync { .. }
+                | Event::FullResyncProgress { .. }
+                | Event::UpdateAvailable { .. }
+                | Event::ShellServiceHealthChanged { .. }
+                | Event::InventoryCleaned { .. }
+        ),
+        "conflicts" => matches!(
+            event,
+            Event::OpenConflictsWindow { .. }
+                | Event::InventoryEntriesChanged { .. }
+                | Event::ConflictDetected { .. }
+        ),
+        "send-to" => matches!(
+            event,
+            Event::OpenSendToWindow { .. }
+                | Event::DriveAdded { .. }
+                | Event::DriveRemoved { .. }
+                | Event::DriveUpdated { .. }
+        ),
+        _ => true,
+    }
+}
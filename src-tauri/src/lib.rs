@@ -1,10 +1,12 @@
 use anyhow::Context;
-use cloudreve_sync::{ConfigManager, DriveManager, EventBroadcaster, LogConfig, LogGuard, shellext::shell_service::ServiceHandle};
+use cloudreve_sync::{ConfigManager, DriveManager, EventBroadcaster, LogConfig, LogGuard, events::{EventBatcher, EventDeduper, metrics::MetricsCollector}, inventory::InventoryDb, shellext::shell_service::ServiceHandle};
+use cloudreve_sync::drive::manager::PauseDuration;
+use cloudreve_sync::updater::Updater;
 use tauri_plugin_autostart::ManagerExt;
 use std::sync::{Arc, Mutex};
 use tauri::{
     async_runtime::spawn,
-    menu::{Menu, MenuItem},
+    menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     AppHandle, Emitter, Manager, RunEvent,
 };
@@ -45,6 +47,9 @@ pub fn get_effective_locale() -> String {
 pub struct AppState {
     pub drive_manager: Arc<DriveManager>,
     pub event_broadcaster: Arc<EventBroadcaster>,
+    /// Counters kept from the broadcast events
+    pub metrics: MetricsCollector,
+    pub updater: Arc<Updater>,
     // Keep the log guard alive for the entire application lifetime
     #[allow(dead_code)]
     log_guard: LogGuard,
@@ -67,13 +72,34 @@ async fn init_sync_service(app: AppHandle) -> anyhow::Result<()> {
 
     tracing::info!(target: "main", "Starting Cloudreve Sync Service (Tauri)...");
 
-    // Initialize EventBroadcaster
-    let event_broadcaster = Arc::new(EventBroadcaster::new(100));
+    // Initialize EventBroadcaster, keeping the last events so windows can replay them
+    // and collapsing repeating warnings and errors
+    let mut event_broadcaster =
+        EventBroadcaster::new(100).with_dedup(EventDeduper::DEFAULT_WINDOW);
+    let encrypt = ConfigManager::try_get().is_some_and(|config| config.encrypt_inventory());
+    match InventoryDb::for_event_log(encrypt) {
+        Ok(log) => event_broadcaster = event_broadcaster.with_log(log),
+        Err(e) => tracing::warn!(target: "main", error = ?e, "Failed to open event log, events will not be replayed"),
+    }
+    let event_broadcaster = Arc::new(event_broadcaster);
+    event_broadcaster.spawn_repeat_flusher();
     tracing::info!(target: "main", "Event broadcasting system initialized");
 
     // Spawn event bridge to forward events to tarui
     spawn_event_bridge(app.clone(), &event_broadcaster);
 
+    // Count synced files, transferred bytes and errors for `get_event_metrics`
+    let metrics = MetricsCollector::spawn(&event_broadcaster);
+
+    // Forward selected events to the webhook configured in the settings, if any
+    if let Err(e) = cloudreve_sync::events::webhook::spawn_dispatcher(&event_broadcaster) {
+        tracing::warn!(target: "main", error = ?e, "Failed to start webhook dispatcher");
+    }
+
+    // Serve the event stream and metrics to local dashboards, if enabled in the settings
+    if let Err(e) = cloudreve_sync::events::api::spawn_server(&event_broadcaster, &metrics) {
+        tracing::warn!(target: "main", error = ?e, "Failed to start local API");
+    }
     // Initialize DriveManager
     tracing::info!(target: "main", "Initializing DriveManager...");
     let drive_manager = Arc::new(
@@ -84,15 +110,24 @@ async fn init_sync_service(app: AppHandle) -> anyhow::Result<()> {
     drive_manager.spawn_command_processor().await;
     tracing::info!(target: "main", "DriveManager command processor started");
 
+    // Spawn periodic inventory maintenance (orphaned record cleanup)
+    drive_manager.spawn_maintenance_task().await;
+
+    // Register recurring jobs (full rescan, inventory vacuum, log rotation) from config
+    drive_manager.spawn_scheduled_tasks().await;
+
     // Load drive configurations from disk
     drive_manager
         .load()
         .await
         .context("Failed to load drive configurations")?;
 
-    // Initialize and start the shell services (context menu handler) in a separate thread
-    let mut shell_service =
-        cloudreve_sync::shellext::shell_service::init_and_start_service_task(drive_manager.clone());
+    // Initialize and start the shell services (context menu handler) in a separate thread,
+    // restarted automatically if it fails
+    let mut shell_service = cloudreve_sync::shellext::shell_service::init_and_start_service_task(
+        drive_manager.clone(),
+        event_broadcaster.clone(),
+    );
 
     // Wait for shell services to initialize
     if let Err(e) = shell_service.wait_for_init() {
@@ -102,6 +137,10 @@ async fn init_sync_service(app: AppHandle) -> anyhow::Result<()> {
         tracing::info!(target: "main", "Shell services initialized successfully!");
     }
 
+    // Check for updates at the interval of the settings
+    let updater = Updater::new(event_broadcaster.clone());
+    updater.spawn_checker();
+
     // Broadcast initial connection status
     event_broadcaster.connection_status_changed(true);
 
@@ -109,6 +148,8 @@ async fn init_sync_service(app: AppHandle) -> anyhow::Result<()> {
     let state = AppState {
         drive_manager,
         event_broadcaster: event_broadcaster.clone(),
+        metrics,
+        updater,
         log_guard,
         shell_service: Mutex::new(shell_service),
     };
@@ -120,6 +161,9 @@ async fn init_sync_service(app: AppHandle) -> anyhow::Result<()> {
     // Store in Tauri's managed state as well for commands
     app.manage(AppStateHandle);
 
+    // Show tasks that failed in earlier sessions on the tray icon
+    event_handler::update_tray_failed_tasks(&app);
+
     tracing::info!(target: "main", "Tauri application setup complete");
 
     Ok(())
@@ -141,18 +185,33 @@ fn spawn_event_bridge(app_handle: AppHandle, event_broadcaster: &EventBroadcaste
     spawn(async move {
         tracing::info!(target: "events", "Event bridge started");
 
+        // Progress and synced files are emitted in batches, keeping the webviews
+        // responsive while thousands of files are synced
+        let mut batcher = EventBatcher::new();
+        let mut flush = tokio::time::interval(EventBatcher::INTERVAL);
+        flush.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
         loop {
-            match receiver.recv().await {
-                Ok(event) => {
-                    event_handler::handle_event(&app_handle, &event);
-                    event_handler::emit_event(&app_handle, &event);
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
-                    tracing::warn!(target: "events", skipped = n, "Event receiver lagged, some events were skipped");
-                }
-                Err(tokio::sync::broadcast::error::RecvError::Closed) => {
-                    tracing::info!(target: "events", "Event broadcaster closed, stopping bridge");
-                    break;
+            tokio::select! {
+                received = receiver.recv() => match received {
+                    Ok(event) => {
+                        event_handler::handle_event(&app_handle, &event);
+                        if let Some(event) = batcher.push(event) {
+                            event_handler::emit_event(&app_handle, &event);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        tracing::warn!(target: "events", skipped = n, "Event receiver lagged, some events were skipped");
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                        tracing::info!(target: "events", "Event broadcaster closed, stopping bridge");
+                        break;
+                    }
+                },
+                _ = flush.tick() => {
+                    for event in batcher.flush() {
+                        event_handler::emit_event(&app_handle, &event);
+                    }
                 }
             }
         }
@@ -160,7 +219,7 @@ fn spawn_event_bridge(app_handle: AppHandle, event_broadcaster: &EventBroadcaste
 }
 
 /// Perform graceful shutdown
-async fn shutdown() {
+pub(crate) async fn shutdown() {
     tracing::info!(target: "main", "Initiating shutdown...");
 
     if let Some(state) = APP_STATE.get() {
@@ -183,6 +242,9 @@ async fn shutdown() {
     tracing::info!(target: "main", "Shutdown complete");
 }
 
+/// Id of the system tray icon
+pub(crate) const TRAY_ID: &str = "main";
+
 /// Setup the system tray icon
 fn setup_tray(app: &tauri::App) -> anyhow::Result<()> {
     // Create menu items
@@ -201,11 +263,51 @@ fn setup_tray(app: &tauri::App) -> anyhow::Result<()> {
         true,
         None::<&str>,
     )?;
+    let pause_i = Submenu::with_items(
+        app,
+        t!("pauseSyncing").as_ref(),
+        true,
+        &[
+            &MenuItem::with_id(
+                app,
+                "pause_one_hour",
+                t!("pauseOneHour").as_ref(),
+                true,
+                None::<&str>,
+            )?,
+            &MenuItem::with_id(
+                app,
+                "pause_until_tomorrow",
+                t!("pauseUntilTomorrow").as_ref(),
+                true,
+                None::<&str>,
+            )?,
+            &MenuItem::with_id(
+                app,
+                "pause_indefinitely",
+                t!("pauseIndefinitely").as_ref(),
+                true,
+                None::<&str>,
+            )?,
+            &PredefinedMenuItem::separator(app)?,
+            &MenuItem::with_id(
+                app,
+                "resume_sync",
+                t!("resumeSyncing").as_ref(),
+                true,
+                None::<&str>,
+            )?,
+        ],
+    )?;
     let quit_i = MenuItem::with_id(app, "quit", t!("quit").as_ref(), true, None::<&str>)?;
-    let menu = Menu::with_items(app, &[&show_i, &add_drive_i, &settings_i, &quit_i])?;
+    let menu = Menu::with_items(
+        app,
+        &[&show_i, &add_drive_i, &settings_i, &pause_i, &quit_i],
+    )?;
 
     // Build tray icon
-    TrayIconBuilder::new()
+    TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Cloudreve")
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&menu)
         .show_menu_on_left_click(false)
@@ -219,6 +321,10 @@ fn setup_tray(app: &tauri::App) -> anyhow::Result<()> {
             "settings" => {
                 show_settings_window_impl(app);
             }
+            "pause_one_hour" => pause_sync_from_tray(Some(PauseDuration::OneHour)),
+            "pause_until_tomorrow" => pause_sync_from_tray(Some(PauseDuration::UntilTomorrow)),
+            "pause_indefinitely" => pause_sync_from_tray(Some(PauseDuration::Indefinitely)),
+            "resume_sync" => pause_sync_from_tray(None),
             "quit" => {
                 app.exit(0);
             }
@@ -241,6 +347,27 @@ fn setup_tray(app: &tauri::App) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Pause syncing of every drive for `duration`, or resume it for `None`
+fn pause_sync_from_tray(duration: Option<PauseDuration>) {
+    let Some(app_state) = AppStateHandle.get() else {
+        return;
+    };
+    spawn(async move {
+        let result = match duration {
+            Some(duration) => {
+                app_state
+                    .drive_manager
+                    .pause_sync(None, duration.until_now())
+                    .await
+            }
+            None => app_state.drive_manager.resume_sync(None).await,
+        };
+        if let Err(e) = result {
+            tracing::error!(target: "main", error = %e, "Failed to pause or resume syncing");
+        }
+    });
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize config manager first so i18n can read language setting
@@ -308,19 +435,82 @@ pub fn run() {
             commands::get_sync_status,
             commands::get_status_summary,
             commands::get_drives_info,
+            commands::query_inventory_entries,
+            commands::list_conflicts,
+            commands::get_activity,
+            commands::resolve_conflict,
+            commands::list_remote_folders,
+            commands::get_remote_tree,
+            commands::send_to_drive,
+            commands::set_pin_state,
+            commands::free_up_paths,
+            commands::free_up_space,
+            commands::free_up_unused_space,
+            commands::get_storage_breakdown,
+            commands::get_transfers,
+            commands::get_file_status,
+            commands::open_online,
+            commands::cancel_task,
+            commands::cancel_tasks,
+            commands::requeue_failed_tasks,
+            commands::pause_sync,
+            commands::resume_sync,
+            commands::force_resync,
+            commands::set_task_type_limits,
+            commands::set_usn_journal_enabled,
+            commands::set_data_validation_enabled,
+            commands::set_sync_root_policy,
+            commands::update_drive_settings,
+            commands::set_navigation_pane_pinned,
+            commands::set_navigation_pane_order,
+            commands::get_task_statistics,
+            commands::list_dead_letter_tasks,
+            commands::requeue_dead_letter_task,
+            commands::discard_dead_letter_task,
+            commands::list_cfapi_failures,
+            commands::get_cfapi_failure_summary,
+            commands::clear_cfapi_failures,
+            commands::list_recent_events,
+            commands::get_event_metrics,
+            commands::troubleshoot_inventory,
+            commands::list_scheduled_tasks,
+            commands::schedule_task,
+            commands::cancel_scheduled_task,
+            commands::export_inventory_snapshot,
+            commands::import_inventory_snapshot,
+            commands::export_diagnostics,
             commands::get_file_icon,
             commands::show_file_in_explorer,
             commands::show_add_drive_window,
             commands::show_reauthorize_window,
             commands::show_settings_window,
             commands::set_auto_start,
-            commands::set_notify_credential_expired,
-            commands::set_notify_file_conflict,
             commands::set_fast_popup_launch,
             commands::get_general_settings,
             commands::set_log_to_file,
             commands::set_log_level,
             commands::set_log_max_files,
+            commands::set_encrypt_inventory,
+            commands::set_task_history_retention,
+            commands::set_thumbnail_cache_limit,
+            commands::get_thumbnail_cache_size,
+            commands::clear_thumbnail_cache,
+            commands::set_prefetch_settings,
+            commands::set_webhook,
+            commands::set_update_settings,
+            commands::get_bandwidth_limits,
+            commands::set_bandwidth_limits,
+            commands::get_proxy_settings,
+            commands::set_proxy_settings,
+            commands::test_connection,
+            commands::set_notifications,
+            commands::get_notification_prefs,
+            commands::set_notification_prefs,
+            commands::check_for_updates,
+            commands::install_update,
+            commands::set_alert_style,
+            commands::get_task_history_count,
+            commands::clear_task_history,
             commands::set_language,
             commands::open_log_folder,
         ])
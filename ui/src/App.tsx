@@ -15,6 +15,8 @@ import { createAppTheme } from "./theme";
 import AddDrive from "./pages/AddDrive";
 import Popup from "./pages/popup";
 import Settings from "./pages/settings";
+import Conflicts from "./pages/conflicts";
+import SendTo from "./pages/sendTo";
 
 function LoadingFallback() {
   return (
@@ -47,6 +49,8 @@ function App() {
             <Route path="/reauthorize/:driveId/:siteUrl/:driveName" element={<AddDrive mode="reauthorize" />} />
             <Route path="/popup" element={<Popup />} />
             <Route path="/settings" element={<Settings />} />
+            <Route path="/conflicts" element={<Conflicts />} />
+            <Route path="/send-to" element={<SendTo />} />
           </Routes>
         </HashRouter>
       </ThemeProvider>
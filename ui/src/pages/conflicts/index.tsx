@@ -0,0 +1,242 @@
+import {
+  Box,
+  Card,
+  CardContent,
+  Chip,
+  Stack,
+  Typography,
+} from "@mui/material";
+import { FolderOpenRounded } from "@mui/icons-material";
+import { useCallback, useEffect, useState } from "react";
+import { useSearchParams } from "react-router-dom";
+import { useTranslation } from "react-i18next";
+import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import CloudreveLogo from "../../common/CloudreveLogo";
+import { SecondaryButton } from "../../common/StyledComponent";
+import { getFileName } from "../popup/utils";
+
+interface ConflictRecord {
+  id: number;
+  drive_id: string;
+  original_path: string;
+  conflict_path?: string;
+  kind: "content" | "type_mismatch";
+  resolution:
+    | "pending"
+    | "keep_remote"
+    | "overwrite_remote"
+    | "save_as_new"
+    | "kept_both";
+  created_at: number;
+  resolved_at?: number;
+}
+
+interface OpenConflictsWindowEvent {
+  type: "OpenConflictsWindow";
+  data: {
+    local_path: string;
+  };
+}
+
+interface ConflictPage {
+  conflicts: ConflictRecord[];
+  total: number;
+  offset: number;
+  limit: number;
+}
+
+const PAGE_SIZE = 50;
+
+// Choices of resolve_conflict, labelled by the resolution they record
+const CHOICES = [
+  { choice: "keep_remote", action: "keep_remote" },
+  { choice: "keep_local", action: "overwrite_remote" },
+  { choice: "keep_both", action: "save_as_new" },
+] as const;
+
+export default function Conflicts() {
+  const { t } = useTranslation();
+  const [searchParams] = useSearchParams();
+  const [localPath, setLocalPath] = useState<string | null>(
+    searchParams.get("path")
+  );
+  const [conflicts, setConflicts] = useState<ConflictRecord[]>([]);
+  const [total, setTotal] = useState(0);
+  const [loading, setLoading] = useState(true);
+
+  // Reloads the first page, dropping the pages loaded with "Load more"
+  const fetchConflicts = useCallback(async () => {
+    try {
+      const result = await invoke<ConflictPage>("list_conflicts", {
+        localPath,
+        page: { offset: 0, limit: PAGE_SIZE },
+      });
+      setConflicts(result.conflicts);
+      setTotal(result.total);
+    } catch (error) {
+      console.error("Failed to fetch conflicts:", error);
+    } finally {
+      setLoading(false);
+    }
+  }, [localPath]);
+
+  const handleLoadMore = async () => {
+    try {
+      const result = await invoke<ConflictPage>("list_conflicts", {
+        localPath,
+        page: { offset: conflicts.length, limit: PAGE_SIZE },
+      });
+      setConflicts((current) => [...current, ...result.conflicts]);
+      setTotal(result.total);
+    } catch (error) {
+      console.error("Failed to fetch conflicts:", error);
+    }
+  };
+
+  useEffect(() => {
+    fetchConflicts();
+
+    const unlisten = getCurrentWindow().listen("InventoryEntriesChanged", () =>
+      fetchConflicts()
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [fetchConflicts]);
+
+  // The context menu reuses an open window for the next file
+  useEffect(() => {
+    const unlisten = getCurrentWindow().listen<OpenConflictsWindowEvent>(
+      "OpenConflictsWindow",
+      (event) => setLocalPath(event.payload.data.local_path)
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const handleResolve = async (conflict: ConflictRecord, choice: string) => {
+    try {
+      await invoke("resolve_conflict", {
+        driveId: conflict.drive_id,
+        id: conflict.id,
+        choice,
+      });
+      await fetchConflicts();
+    } catch (error) {
+      console.error("Failed to resolve conflict:", error);
+    }
+  };
+
+  const handleShowInExplorer = async (path: string) => {
+    try {
+      await invoke("show_file_in_explorer", { path });
+    } catch (error) {
+      console.error("Failed to show file in explorer:", error);
+    }
+  };
+
+  return (
+    <Box
+      sx={{
+        height: "100vh",
+        display: "flex",
+        flexDirection: "column",
+        bgcolor: "background.paper",
+        overflow: "hidden",
+      }}
+    >
+      {/* Title with drag region */}
+      <Box
+        data-tauri-drag-region
+        sx={{ px: 2, pt: 2, pb: 1, display: "flex", alignItems: "center", gap: 1.5 }}
+      >
+        <CloudreveLogo height={24} />
+        <Typography variant="subtitle1" fontWeight={500}>
+          {t("conflicts.title")}
+        </Typography>
+      </Box>
+
+      {localPath && (
+        <Box sx={{ px: 2, pb: 1 }}>
+          <Chip
+            size="small"
+            label={getFileName(localPath)}
+            onDelete={() => setLocalPath(null)}
+          />
+        </Box>
+      )}
+
+      <Box sx={{ flex: 1, overflow: "auto", px: 2, pb: 2 }}>
+        {!loading && conflicts.length === 0 && (
+          <Typography variant="body2" color="text.secondary" sx={{ mt: 2 }}>
+            {t("conflicts.empty")}
+          </Typography>
+        )}
+        <Stack spacing={1.5}>
+          {conflicts.map((conflict) => (
+            <Card key={`${conflict.drive_id}-${conflict.id}`} variant="outlined">
+              <CardContent>
+                <Typography variant="subtitle2" noWrap title={conflict.original_path}>
+                  {getFileName(conflict.original_path)}
+                </Typography>
+                <Typography variant="caption" color="text.secondary" component="div">
+                  {t(`conflicts.kind.${conflict.kind}`)}
+                  {" · "}
+                  {new Date(conflict.created_at * 1000).toLocaleString()}
+                </Typography>
+                {conflict.conflict_path && (
+                  <Typography
+                    variant="caption"
+                    color="text.secondary"
+                    component="div"
+                    noWrap
+                    title={conflict.conflict_path}
+                  >
+                    {t("conflicts.copy", { name: getFileName(conflict.conflict_path) })}
+                  </Typography>
+                )}
+                <Stack direction="row" spacing={1} sx={{ mt: 1.5 }} flexWrap="wrap" useFlexGap>
+                  {conflict.resolution === "pending" ? (
+                    CHOICES.map(({ choice, action }) => (
+                      <SecondaryButton
+                        key={choice}
+                        size="small"
+                        onClick={() => handleResolve(conflict, choice)}
+                      >
+                        {t(`conflicts.action.${action}`)}
+                      </SecondaryButton>
+                    ))
+                  ) : (
+                    <Chip
+                      size="small"
+                      label={t(`conflicts.resolution.${conflict.resolution}`)}
+                    />
+                  )}
+                  <Box sx={{ flex: 1 }} />
+                  <SecondaryButton
+                    size="small"
+                    startIcon={<FolderOpenRounded />}
+                    onClick={() =>
+                      handleShowInExplorer(conflict.conflict_path ?? conflict.original_path)
+                    }
+                  >
+                    {t("conflicts.showInExplorer")}
+                  </SecondaryButton>
+                </Stack>
+              </CardContent>
+            </Card>
+          ))}
+        </Stack>
+        {conflicts.length < total && (
+          <Box sx={{ display: "flex", justifyContent: "center", mt: 1.5 }}>
+            <SecondaryButton size="small" onClick={handleLoadMore}>
+              {t("conflicts.loadMore")}
+            </SecondaryButton>
+          </Box>
+        )}
+      </Box>
+    </Box>
+  );
+}
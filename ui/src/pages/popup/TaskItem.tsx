@@ -33,7 +33,7 @@ export default function TaskItem({ task, isActive = false }: TaskItemProps) {
   const isUpload = task.task_type === "upload";
   const fileName = getFileName(task.local_path);
   const parentFolderName = getParentFolderName(task.local_path);
-  const isFailed = task.status === "Failed";
+  const isFailed = task.status === "Failed" || task.status === "TimedOut";
 
   const timeAgoFormatter = (
     value: number,
@@ -66,6 +66,7 @@ export default function TaskItem({ task, isActive = false }: TaskItemProps) {
         return <CheckCircleIcon sx={{ fontSize: 14 }} color="success" />;
       case "Failed":
       case "Cancelled":
+      case "TimedOut":
         return <ErrorIcon sx={{ fontSize: 14 }} color="error" />;
       default:
         return null;
@@ -16,7 +16,7 @@ import { getCurrentWindow } from "@tauri-apps/api/window";
 import { useTranslation } from "react-i18next";
 import Settings from "../../common/icons/Settings";
 import CloudreveLogo from "../../common/CloudreveLogo";
-import type { StatusSummary } from "./types";
+import type { StatusSummary, TaskProgressBatchEvent } from "./types";
 import DriveChips from "./DriveChips";
 import TaskItem from "./TaskItem";
 
@@ -67,19 +67,63 @@ export default function Popup() {
     }
   }, [selectedDrive]);
 
-  // Initial fetch and polling
+  // Initial fetch, then refresh whenever the inventory reports a change
   useEffect(() => {
     fetchSummary();
 
-    const intervalId = setInterval(() => {
-      fetchSummary();
-    }, 1000);
+    const unlisteners = [
+      "InventoryEntriesChanged",
+      "TaskFinished",
+      "TasksUpdated",
+    ].map((name) => getCurrentWindow().listen(name, () => fetchSummary()));
 
     return () => {
-      clearInterval(intervalId);
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()));
     };
   }, [fetchSummary]);
 
+  const hasActiveTasks =
+    summary?.active_tasks && summary.active_tasks.length > 0;
+
+  const summaryRef = useRef(summary);
+  useEffect(() => {
+    summaryRef.current = summary;
+  }, [summary]);
+
+  // Apply live progress of running tasks; a task not listed yet was just started
+  useEffect(() => {
+    const unlisten = getCurrentWindow().listen<TaskProgressBatchEvent>(
+      "TaskProgressBatch",
+      (event) => {
+        const { drive_id, progress } = event.payload.data;
+        if (selectedDrive && drive_id !== selectedDrive) return;
+        const latest = new Map(progress.map((p) => [p.task_id, p]));
+        const known =
+          summaryRef.current?.active_tasks.filter((task) => latest.has(task.id))
+            .length ?? 0;
+        if (known < latest.size) {
+          fetchSummary();
+          return;
+        }
+        setSummary((current) =>
+          current
+            ? {
+                ...current,
+                active_tasks: current.active_tasks.map((task) => {
+                  const live_progress = latest.get(task.id);
+                  return live_progress ? { ...task, live_progress } : task;
+                }),
+              }
+            : current
+        );
+      }
+    );
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [fetchSummary, selectedDrive]);
+
   const handleDriveSelect = (driveId: string | null) => {
     setSelectedDrive(driveId);
   };
@@ -100,8 +144,6 @@ export default function Popup() {
     }
   };
 
-  const hasActiveTasks =
-    summary?.active_tasks && summary.active_tasks.length > 0;
   const hasFinishedTasks =
     summary?.finished_tasks && summary.finished_tasks.length > 0;
 
@@ -8,7 +8,7 @@ export interface DriveConfig {
 
 export interface TaskProgress {
   task_id: string;
-  kind: "Upload" | "Download";
+  kind: "Upload" | "Download" | "Hydrate";
   local_path: string;
   progress: number;
   processed_bytes?: number;
@@ -17,12 +17,20 @@ export interface TaskProgress {
   eta_seconds?: number;
 }
 
+export interface TaskProgressBatchEvent {
+  type: "TaskProgressBatch";
+  data: {
+    drive_id: string;
+    progress: TaskProgress[];
+  };
+}
+
 export interface TaskRecord {
   id: string;
   drive_id: string;
   task_type: string;
   local_path: string;
-  status: "Pending" | "Running" | "Completed" | "Failed" | "Cancelled";
+  status: "Pending" | "Running" | "Completed" | "Failed" | "Cancelled" | "TimedOut";
   progress: number;
   total_bytes: number;
   processed_bytes: number;
@@ -35,10 +43,20 @@ export interface TaskWithProgress extends TaskRecord {
   live_progress?: TaskProgress;
 }
 
+export interface TaskQueueStats {
+  drive_id: string;
+  workers: number;
+  min_workers: number;
+  max_workers: number;
+  running: number;
+  queued: number;
+}
+
 export interface StatusSummary {
   drives: DriveConfig[];
   active_tasks: TaskWithProgress[];
   finished_tasks: TaskRecord[];
+  queue_stats: TaskQueueStats[];
 }
 
 export interface FileIconResponse {
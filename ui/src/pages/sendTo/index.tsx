@@ -0,0 +1,249 @@
+import {
+  Box,
+  Button,
+  Chip,
+  CircularProgress,
+  List,
+  ListItemButton,
+  ListItemIcon,
+  ListItemText,
+  MenuItem,
+  Select,
+  Stack,
+  Typography,
+} from "@mui/material";
+import { ArrowUpwardRounded, FolderRounded } from "@mui/icons-material";
+import { useCallback, useEffect, useState } from "react";
+import { useSearchParams } from "react-router-dom";
+import { useTranslation } from "react-i18next";
+import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import CloudreveLogo from "../../common/CloudreveLogo";
+import { SecondaryButton } from "../../common/StyledComponent";
+import { getFileName } from "../popup/utils";
+import type { DriveInfo } from "../settings/types";
+
+interface RemoteFolder {
+  name: string;
+  uri: string;
+}
+
+interface OpenSendToWindowEvent {
+  type: "OpenSendToWindow";
+  data: {
+    local_paths: string[];
+  };
+}
+
+/** Root of the file system a drive syncs from, e.g. `cloudreve://my` */
+function rootUri(remotePath: string): string {
+  const match = remotePath.match(/^cloudreve:\/\/[^/?]+/);
+  return match ? match[0] : remotePath;
+}
+
+function parentUri(uri: string, root: string): string {
+  if (uri === root) {
+    return root;
+  }
+  const parent = uri.substring(0, uri.lastIndexOf("/"));
+  return parent.length < root.length ? root : parent;
+}
+
+function folderLabel(uri: string, root: string): string {
+  const path = decodeURIComponent(uri.substring(root.length));
+  return path === "" ? "/" : path;
+}
+
+export default function SendTo() {
+  const { t } = useTranslation();
+  const [searchParams] = useSearchParams();
+  const [paths, setPaths] = useState<string[]>(searchParams.getAll("path"));
+  const [drives, setDrives] = useState<DriveInfo[]>([]);
+  const [driveId, setDriveId] = useState<string>("");
+  const [uri, setUri] = useState<string>("");
+  const [folders, setFolders] = useState<RemoteFolder[]>([]);
+  const [loading, setLoading] = useState(false);
+  const [sending, setSending] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const drive = drives.find((d) => d.id === driveId);
+  const root = drive ? rootUri(drive.remote_path) : "";
+
+  useEffect(() => {
+    invoke<DriveInfo[]>("get_drives_info")
+      .then((result) => {
+        const available = result.filter(
+          (d) => d.enabled && d.status !== "credential_expired"
+        );
+        setDrives(available);
+        if (available.length > 0) {
+          setDriveId(available[0].id);
+          setUri(rootUri(available[0].remote_path));
+        }
+      })
+      .catch((e) => console.error("Failed to fetch drives:", e));
+  }, []);
+
+  // The context menu reuses an open window for the next selection
+  useEffect(() => {
+    const unlisten = getCurrentWindow().listen<OpenSendToWindowEvent>(
+      "OpenSendToWindow",
+      (event) => setPaths(event.payload.data.local_paths)
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  const fetchFolders = useCallback(async () => {
+    if (!driveId || !uri) {
+      return;
+    }
+    setLoading(true);
+    setError(null);
+    try {
+      setFolders(await invoke<RemoteFolder[]>("list_remote_folders", { driveId, uri }));
+    } catch (e) {
+      console.error("Failed to list remote folders:", e);
+      setFolders([]);
+      setError(String(e));
+    } finally {
+      setLoading(false);
+    }
+  }, [driveId, uri]);
+
+  useEffect(() => {
+    fetchFolders();
+  }, [fetchFolders]);
+
+  const handleDriveChange = (id: string) => {
+    const next = drives.find((d) => d.id === id);
+    if (!next) {
+      return;
+    }
+    setDriveId(id);
+    setUri(rootUri(next.remote_path));
+  };
+
+  const handleSend = async () => {
+    setSending(true);
+    setError(null);
+    try {
+      await invoke("send_to_drive", { driveId, folderUri: uri, paths });
+      await getCurrentWindow().close();
+    } catch (e) {
+      console.error("Failed to send files:", e);
+      setError(String(e));
+      setSending(false);
+    }
+  };
+
+  return (
+    <Box
+      sx={{
+        height: "100vh",
+        display: "flex",
+        flexDirection: "column",
+        bgcolor: "background.paper",
+        overflow: "hidden",
+      }}
+    >
+      {/* Title with drag region */}
+      <Box
+        data-tauri-drag-region
+        sx={{ px: 2, pt: 2, pb: 1, display: "flex", alignItems: "center", gap: 1.5 }}
+      >
+        <CloudreveLogo height={24} />
+        <Typography variant="subtitle1" fontWeight={500}>
+          {t("sendTo.title")}
+        </Typography>
+      </Box>
+
+      <Stack direction="row" spacing={1} sx={{ px: 2, pb: 1 }} flexWrap="wrap" useFlexGap>
+        {paths.map((path) => (
+          <Chip key={path} size="small" label={getFileName(path)} title={path} />
+        ))}
+      </Stack>
+
+      {drives.length === 0 ? (
+        <Typography variant="body2" color="text.secondary" sx={{ px: 2, mt: 2 }}>
+          {t("sendTo.noDrive")}
+        </Typography>
+      ) : (
+        <>
+          <Box sx={{ px: 2, pb: 1 }}>
+            <Select
+              size="small"
+              fullWidth
+              value={driveId}
+              onChange={(e) => handleDriveChange(e.target.value)}
+            >
+              {drives.map((d) => (
+                <MenuItem key={d.id} value={d.id}>
+                  {d.name}
+                </MenuItem>
+              ))}
+            </Select>
+          </Box>
+
+          <Box sx={{ px: 2, display: "flex", alignItems: "center", gap: 1 }}>
+            <SecondaryButton
+              size="small"
+              startIcon={<ArrowUpwardRounded />}
+              disabled={uri === root}
+              onClick={() => setUri(parentUri(uri, root))}
+            >
+              {t("sendTo.up")}
+            </SecondaryButton>
+            <Typography variant="body2" color="text.secondary" noWrap title={uri}>
+              {folderLabel(uri, root)}
+            </Typography>
+          </Box>
+
+          <Box sx={{ flex: 1, overflow: "auto", px: 1 }}>
+            {loading ? (
+              <Box sx={{ display: "flex", justifyContent: "center", mt: 3 }}>
+                <CircularProgress size={24} />
+              </Box>
+            ) : (
+              <List dense>
+                {folders.map((folder) => (
+                  <ListItemButton key={folder.uri} onClick={() => setUri(folder.uri)}>
+                    <ListItemIcon sx={{ minWidth: 36 }}>
+                      <FolderRounded fontSize="small" />
+                    </ListItemIcon>
+                    <ListItemText primary={folder.name} />
+                  </ListItemButton>
+                ))}
+                {folders.length === 0 && (
+                  <Typography variant="body2" color="text.secondary" sx={{ px: 1, mt: 1 }}>
+                    {t("sendTo.noFolders")}
+                  </Typography>
+                )}
+              </List>
+            )}
+          </Box>
+        </>
+      )}
+
+      {error && (
+        <Typography variant="caption" color="error" sx={{ px: 2 }}>
+          {error}
+        </Typography>
+      )}
+
+      <Stack direction="row" spacing={1} sx={{ p: 2 }} justifyContent="flex-end">
+        <SecondaryButton onClick={() => getCurrentWindow().close()}>
+          {t("sendTo.cancel")}
+        </SecondaryButton>
+        <Button
+          variant="contained"
+          disabled={!driveId || !uri || paths.length === 0 || sending}
+          onClick={handleSend}
+        >
+          {t("sendTo.send")}
+        </Button>
+      </Stack>
+    </Box>
+  );
+}
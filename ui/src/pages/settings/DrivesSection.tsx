@@ -8,6 +8,7 @@ import {
   Tooltip,
   Link,
   Divider,
+  IconButton,
 } from "@mui/material";
 import {
   FolderOpen as FolderOpenIcon,
@@ -16,6 +17,11 @@ import {
   Add as AddIcon,
   DeleteOutlineRounded,
   RefreshRounded,
+  PushPinOutlined,
+  PushPinRounded,
+  ArrowUpwardRounded,
+  ArrowDownwardRounded,
+  EditOutlined,
 } from "@mui/icons-material";
 import { useCallback, useEffect, useRef, useState } from "react";
 import { useTranslation } from "react-i18next";
@@ -25,6 +31,7 @@ import { openUrl } from "@tauri-apps/plugin-opener";
 import type { DriveInfo } from "./types";
 import {  SecondaryButton, SecondaryErrorButton } from "../../common/StyledComponent";
 import { ask } from '@tauri-apps/plugin-dialog';
+import EditDriveDialog from "./EditDriveDialog";
 
 interface DriveInfoResponse {
   id: string;
@@ -33,6 +40,7 @@ interface DriveInfoResponse {
   sync_path: string;
   icon_path?: string;
   raw_icon_path?: string;
+  color?: string;
   remote_path: string;
   enabled: boolean;
   user_id: string;
@@ -42,12 +50,17 @@ interface DriveInfoResponse {
     used: number;
     label: string;
   };
+  navigation_pane: {
+    pinned: boolean;
+    order: number;
+  };
 }
 
 export default function DrivesSection() {
   const { t } = useTranslation();
   const [drives, setDrives] = useState<DriveInfo[]>([]);
   const [loading, setLoading] = useState(true);
+  const [editingDrive, setEditingDrive] = useState<DriveInfo | null>(null);
   const isFetchingRef = useRef(false);
 
   const fetchDrives = useCallback(async () => {
@@ -102,6 +115,30 @@ export default function DrivesSection() {
     }
   };
 
+  const handleTogglePinned = async (drive: DriveInfo) => {
+    try {
+      await invoke("set_navigation_pane_pinned", {
+        driveId: drive.id,
+        pinned: !drive.navigation_pane.pinned,
+      });
+      await fetchDrives();
+    } catch (error) {
+      console.error("Failed to update navigation pane:", error);
+    }
+  };
+
+  const handleMove = async (index: number, offset: number) => {
+    const driveIds = drives.map((drive) => drive.id);
+    const [moved] = driveIds.splice(index, 1);
+    driveIds.splice(index + offset, 0, moved);
+    try {
+      await invoke("set_navigation_pane_order", { driveIds });
+      await fetchDrives();
+    } catch (error) {
+      console.error("Failed to reorder drives:", error);
+    }
+  };
+
   const handleOpenFolder = async (path: string) => {
     try {
       await invoke("show_file_in_explorer", { path });
@@ -172,7 +209,7 @@ export default function DrivesSection() {
         </Typography>
       ) : (
         <Stack spacing={2}>
-          {drives.map((drive) => (
+          {drives.map((drive, index) => (
             <Card key={drive.id} variant="outlined">
               <CardContent sx={{ pb: "16px!important" }}>
                 <Box
@@ -191,6 +228,7 @@ export default function DrivesSection() {
                       overflow: "hidden",
                       flexShrink: 0,
                       bgcolor: "action.hover",
+                      border: drive.color ? `2px solid ${drive.color}` : undefined,
                       display: "flex",
                       alignItems: "center",
                       justifyContent: "center",
@@ -359,8 +397,55 @@ export default function DrivesSection() {
                     </SecondaryButton>
                   )}
 
+                  <SecondaryButton
+                    size="small"
+                    startIcon={<EditOutlined />}
+                    onClick={() => setEditingDrive(drive)}
+                  >
+                    {t("settings.editDrive")}
+                  </SecondaryButton>
+
+                  <SecondaryButton
+                    size="small"
+                    startIcon={
+                      drive.navigation_pane.pinned ? <PushPinRounded /> : <PushPinOutlined />
+                    }
+                    onClick={() => handleTogglePinned(drive)}
+                  >
+                    {drive.navigation_pane.pinned
+                      ? t("settings.unpinFromNavigationPane")
+                      : t("settings.pinToNavigationPane")}
+                  </SecondaryButton>
+
                   <Box sx={{ flex: 1 }} />
 
+                  {drives.length > 1 && (
+                    <>
+                      <Tooltip title={t("settings.moveUp")}>
+                        <span>
+                          <IconButton
+                            size="small"
+                            disabled={index === 0}
+                            onClick={() => handleMove(index, -1)}
+                          >
+                            <ArrowUpwardRounded fontSize="small" />
+                          </IconButton>
+                        </span>
+                      </Tooltip>
+                      <Tooltip title={t("settings.moveDown")}>
+                        <span>
+                          <IconButton
+                            size="small"
+                            disabled={index === drives.length - 1}
+                            onClick={() => handleMove(index, 1)}
+                          >
+                            <ArrowDownwardRounded fontSize="small" />
+                          </IconButton>
+                        </span>
+                      </Tooltip>
+                    </>
+                  )}
+
                   <SecondaryErrorButton
                     size="small"
                     color="error"
@@ -383,6 +468,12 @@ export default function DrivesSection() {
       >
         {t("popup.newDrive")}
       </SecondaryButton>
+
+      <EditDriveDialog
+        drive={editingDrive}
+        onClose={() => setEditingDrive(null)}
+        onSaved={fetchDrives}
+      />
     </Box>
   );
 }
@@ -0,0 +1,157 @@
+import {
+  Box,
+  Dialog,
+  DialogActions,
+  DialogContent,
+  DialogTitle,
+  Stack,
+  TextField,
+  Tooltip,
+  Typography,
+} from "@mui/material";
+import { CheckRounded } from "@mui/icons-material";
+import { useEffect, useState } from "react";
+import { useTranslation } from "react-i18next";
+import { invoke } from "@tauri-apps/api/core";
+import { open as openDialog } from "@tauri-apps/plugin-dialog";
+import type { DriveInfo } from "./types";
+import { SecondaryButton } from "../../common/StyledComponent";
+
+type DriveIcon = { type: "site" } | { type: "file"; path: string };
+
+const COLORS = [
+  "#1976d2",
+  "#0097a7",
+  "#388e3c",
+  "#f57c00",
+  "#d32f2f",
+  "#c2185b",
+  "#7b1fa2",
+  "#5d4037",
+] as const;
+
+interface EditDriveDialogProps {
+  drive: DriveInfo | null;
+  onClose: () => void;
+  onSaved: () => void;
+}
+
+export default function EditDriveDialog({ drive, onClose, onSaved }: EditDriveDialogProps) {
+  const { t } = useTranslation();
+  const [name, setName] = useState("");
+  const [icon, setIcon] = useState<DriveIcon | null>(null);
+  const [color, setColor] = useState<string | null>(null);
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    if (drive) {
+      setName(drive.name);
+      setIcon(null);
+      setColor(drive.color ?? null);
+    }
+  }, [drive]);
+
+  const handleChooseIcon = async () => {
+    const selected = await openDialog({
+      multiple: false,
+      title: t("settings.chooseIconImage"),
+      filters: [{ name: "Images", extensions: ["png", "jpg", "jpeg", "ico", "bmp", "gif"] }],
+    });
+    if (selected) {
+      setIcon({ type: "file", path: selected });
+    }
+  };
+
+  const handleSave = async () => {
+    if (!drive) return;
+    setSaving(true);
+    try {
+      await invoke("update_drive_settings", {
+        driveId: drive.id,
+        settings: { name, icon, color },
+      });
+      onSaved();
+      onClose();
+    } catch (error) {
+      console.error("Failed to update drive settings:", error);
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  return (
+    <Dialog open={drive !== null} onClose={onClose} fullWidth maxWidth="xs">
+      <DialogTitle>{t("settings.editDrive")}</DialogTitle>
+      <DialogContent>
+        <Stack spacing={2} sx={{ pt: 1 }}>
+          <TextField
+            label={t("settings.driveName")}
+            size="small"
+            value={name}
+            onChange={(e) => setName(e.target.value)}
+            fullWidth
+          />
+
+          <Box>
+            <Typography variant="caption" color="text.secondary" component="div" sx={{ mb: 0.5 }}>
+              {t("settings.driveIcon")}
+            </Typography>
+            <Stack direction="row" spacing={1} alignItems="center">
+              <SecondaryButton size="small" onClick={handleChooseIcon}>
+                {t("settings.chooseIconImage")}
+              </SecondaryButton>
+              <SecondaryButton size="small" onClick={() => setIcon({ type: "site" })}>
+                {t("settings.useSiteIcon")}
+              </SecondaryButton>
+            </Stack>
+            {icon?.type === "file" && (
+              <Typography variant="caption" color="text.secondary" component="div" noWrap title={icon.path}>
+                {icon.path}
+              </Typography>
+            )}
+          </Box>
+
+          <Box>
+            <Typography variant="caption" color="text.secondary" component="div" sx={{ mb: 0.5 }}>
+              {t("settings.driveColor")}
+            </Typography>
+            <Stack direction="row" spacing={1} alignItems="center" flexWrap="wrap" useFlexGap>
+              <SecondaryButton
+                size="small"
+                startIcon={color === null ? <CheckRounded /> : undefined}
+                onClick={() => setColor(null)}
+              >
+                {t("settings.defaultColor")}
+              </SecondaryButton>
+              {COLORS.map((option) => (
+                <Tooltip key={option} title={option}>
+                  <Box
+                    onClick={() => setColor(option)}
+                    sx={{
+                      width: 24,
+                      height: 24,
+                      borderRadius: "50%",
+                      bgcolor: option,
+                      cursor: "pointer",
+                      display: "flex",
+                      alignItems: "center",
+                      justifyContent: "center",
+                    }}
+                  >
+                    {color === option && <CheckRounded sx={{ fontSize: 16, color: "#fff" }} />}
+                  </Box>
+                </Tooltip>
+              ))}
+            </Stack>
+          </Box>
+        </Stack>
+      </DialogContent>
+      <DialogActions>
+        <SecondaryButton onClick={onClose}>{t("settings.cancel")}</SecondaryButton>
+        <SecondaryButton onClick={handleSave} disabled={saving || name.trim() === ""}>
+          {t("settings.save")}
+        </SecondaryButton>
+      </DialogActions>
+    </Dialog>
+  );
+}
@@ -13,6 +13,7 @@ import { useTranslation } from "react-i18next";
 import { invoke } from "@tauri-apps/api/core";
 import { isEnabled } from "@tauri-apps/plugin-autostart";
 import { languages } from "../../i18n";
+import { formatBytes } from "../popup/utils";
 
 interface SettingItemProps {
   title: string;
@@ -203,17 +204,53 @@ function SettingsGroup({ title, children }: SettingsGroupProps) {
   );
 }
 
+type AlertCategory =
+  | "transfers"
+  | "conflicts"
+  | "credentials"
+  | "storage"
+  | "service";
+
+type AlertStyle = "toast" | "silent" | "tray_flash" | "off";
+
+interface NotificationPrefs {
+  events: Record<string, boolean>;
+  quiet_hours: { start: string; end: string } | null;
+}
+
 interface GeneralSettings {
-  notify_credential_expired: boolean;
-  notify_file_conflict: boolean;
   fast_popup_launch: boolean;
   log_to_file: boolean;
   log_level: string;
   log_max_files: number;
   log_dir: string;
   language: string | null;
+  encrypt_inventory: boolean;
+  task_history_retention_days: number;
+  task_history_max_entries: number;
+  thumbnail_cache_max_mb: number;
+  prefetch_enabled: boolean;
+  prefetch_budget_mb: number;
+  notifications: {
+    alerts: Partial<Record<AlertCategory, AlertStyle>>;
+  };
 }
 
+const ALERT_CATEGORIES: { category: AlertCategory; titleKey: string }[] = [
+  { category: "transfers", titleKey: "settings.alertTransfers" },
+  { category: "conflicts", titleKey: "settings.alertConflicts" },
+  { category: "credentials", titleKey: "settings.alertCredentials" },
+  { category: "storage", titleKey: "settings.alertStorage" },
+  { category: "service", titleKey: "settings.alertService" },
+];
+
+const ALERT_STYLES: { style: AlertStyle; labelKey: string }[] = [
+  { style: "toast", labelKey: "settings.alertStyleToast" },
+  { style: "silent", labelKey: "settings.alertStyleSilent" },
+  { style: "tray_flash", labelKey: "settings.alertStyleTrayFlash" },
+  { style: "off", labelKey: "settings.alertStyleOff" },
+];
+
 const LOG_LEVELS = [
   { value: "trace", label: "Trace" },
   { value: "debug", label: "Debug" },
@@ -222,6 +259,28 @@ const LOG_LEVELS = [
   { value: "error", label: "Error" },
 ];
 
+const HISTORY_DAYS_OPTIONS = ["7", "30", "90", "365"];
+
+const HISTORY_MAX_ENTRIES_OPTIONS = [
+  { value: "1000", label: "1,000" },
+  { value: "5000", label: "5,000" },
+  { value: "10000", label: "10,000" },
+];
+
+const THUMBNAIL_CACHE_OPTIONS = [
+  { value: "0", label: "Off" },
+  { value: "64", label: "64 MB" },
+  { value: "256", label: "256 MB" },
+  { value: "1024", label: "1 GB" },
+];
+
+const PREFETCH_BUDGET_OPTIONS = [
+  { value: "512", label: "512 MB" },
+  { value: "2048", label: "2 GB" },
+  { value: "5120", label: "5 GB" },
+  { value: "10240", label: "10 GB" },
+];
+
 const MAX_FILES_OPTIONS = [
   { value: "3", label: "3" },
   { value: "5", label: "5" },
@@ -234,30 +293,58 @@ export default function GeneralSection() {
   const [autoStart, setAutoStart] = useState(true);
   const [notifyCredentialExpired, setNotifyCredentialExpired] = useState(true);
   const [notifyFileConflict, setNotifyFileConflict] = useState(true);
+  const [quietHours, setQuietHours] =
+    useState<NotificationPrefs["quiet_hours"]>(null);
+  const [alertStyles, setAlertStyles] = useState<
+    Partial<Record<AlertCategory, AlertStyle>>
+  >({});
   const [fastPopupLaunch, setFastPopupLaunch] = useState(true);
   const [logToFile, setLogToFile] = useState(true);
   const [logLevel, setLogLevel] = useState("info");
   const [logMaxFiles, setLogMaxFiles] = useState(5);
   const [logDir, setLogDir] = useState("");
   const [language, setLanguage] = useState<string | null>(null);
+  const [encryptInventory, setEncryptInventory] = useState(false);
+  const [historyDays, setHistoryDays] = useState(30);
+  const [historyMaxEntries, setHistoryMaxEntries] = useState(5000);
+  const [historyCount, setHistoryCount] = useState(0);
+  const [thumbnailCacheMb, setThumbnailCacheMb] = useState(256);
+  const [thumbnailCacheSize, setThumbnailCacheSize] = useState(0);
+  const [prefetchEnabled, setPrefetchEnabled] = useState(false);
+  const [prefetchBudgetMb, setPrefetchBudgetMb] = useState(2048);
   const [loading, setLoading] = useState(true);
 
   useEffect(() => {
     const loadSettings = async () => {
       try {
-        const [enabled, settings] = await Promise.all([
+        const [enabled, settings, notificationPrefs] = await Promise.all([
           isEnabled(),
           invoke<GeneralSettings>("get_general_settings"),
+          invoke<NotificationPrefs>("get_notification_prefs"),
         ]);
         setAutoStart(enabled);
-        setNotifyCredentialExpired(settings.notify_credential_expired);
-        setNotifyFileConflict(settings.notify_file_conflict);
+        setNotifyCredentialExpired(
+          notificationPrefs.events["CredentialExpired"] ?? true
+        );
+        setNotifyFileConflict(
+          notificationPrefs.events["ConflictDetected"] ?? true
+        );
+        setQuietHours(notificationPrefs.quiet_hours);
+        setAlertStyles(settings.notifications.alerts);
         setFastPopupLaunch(settings.fast_popup_launch);
         setLogToFile(settings.log_to_file);
         setLogLevel(settings.log_level);
         setLogMaxFiles(settings.log_max_files);
         setLogDir(settings.log_dir);
         setLanguage(settings.language);
+        setEncryptInventory(settings.encrypt_inventory);
+        setHistoryDays(settings.task_history_retention_days);
+        setHistoryMaxEntries(settings.task_history_max_entries);
+        setHistoryCount(await invoke<number>("get_task_history_count"));
+        setThumbnailCacheMb(settings.thumbnail_cache_max_mb);
+        setPrefetchEnabled(settings.prefetch_enabled);
+        setPrefetchBudgetMb(settings.prefetch_budget_mb);
+        setThumbnailCacheSize(await invoke<number>("get_thumbnail_cache_size"));
       } catch (error) {
         console.error("Failed to load settings:", error);
       } finally {
@@ -282,7 +369,12 @@ export default function GeneralSection() {
     const previousValue = notifyCredentialExpired;
     setNotifyCredentialExpired(checked);
     try {
-      await invoke("set_notify_credential_expired", { enabled: checked });
+      await invoke("set_notification_prefs", {
+        prefs: {
+          events: { CredentialExpired: checked },
+          quiet_hours: quietHours,
+        },
+      });
     } catch (error) {
       console.error("Failed to change notification setting:", error);
       setNotifyCredentialExpired(previousValue);
@@ -293,13 +385,32 @@ export default function GeneralSection() {
     const previousValue = notifyFileConflict;
     setNotifyFileConflict(checked);
     try {
-      await invoke("set_notify_file_conflict", { enabled: checked });
+      await invoke("set_notification_prefs", {
+        prefs: {
+          events: { ConflictDetected: checked },
+          quiet_hours: quietHours,
+        },
+      });
     } catch (error) {
       console.error("Failed to change notification setting:", error);
       setNotifyFileConflict(previousValue);
     }
   };
 
+  const handleAlertStyleChange = async (
+    category: AlertCategory,
+    style: AlertStyle
+  ) => {
+    const previousValue = alertStyles;
+    setAlertStyles({ ...alertStyles, [category]: style });
+    try {
+      await invoke("set_alert_style", { category, style });
+    } catch (error) {
+      console.error("Failed to change alert style:", error);
+      setAlertStyles(previousValue);
+    }
+  };
+
   const handleFastPopupLaunchChange = async (checked: boolean) => {
     const previousValue = fastPopupLaunch;
     setFastPopupLaunch(checked);
@@ -345,6 +456,79 @@ export default function GeneralSection() {
     }
   };
 
+  const handleEncryptInventoryChange = async (checked: boolean) => {
+    const previousValue = encryptInventory;
+    setEncryptInventory(checked);
+    try {
+      await invoke("set_encrypt_inventory", { enabled: checked });
+    } catch (error) {
+      console.error("Failed to change inventory encryption setting:", error);
+      setEncryptInventory(previousValue);
+    }
+  };
+
+  const handleHistoryRetentionChange = async (
+    days: number,
+    maxEntries: number
+  ) => {
+    const previousDays = historyDays;
+    const previousMaxEntries = historyMaxEntries;
+    setHistoryDays(days);
+    setHistoryMaxEntries(maxEntries);
+    try {
+      await invoke("set_task_history_retention", { days, maxEntries });
+    } catch (error) {
+      console.error("Failed to change task history retention:", error);
+      setHistoryDays(previousDays);
+      setHistoryMaxEntries(previousMaxEntries);
+    }
+  };
+
+  const handleClearHistory = async () => {
+    try {
+      await invoke<number>("clear_task_history");
+      setHistoryCount(await invoke<number>("get_task_history_count"));
+    } catch (error) {
+      console.error("Failed to clear task history:", error);
+    }
+  };
+
+  const handleThumbnailCacheLimitChange = async (value: string) => {
+    const maxMb = parseInt(value, 10);
+    const previousValue = thumbnailCacheMb;
+    setThumbnailCacheMb(maxMb);
+    try {
+      await invoke("set_thumbnail_cache_limit", { maxMb });
+      setThumbnailCacheSize(await invoke<number>("get_thumbnail_cache_size"));
+    } catch (error) {
+      console.error("Failed to change thumbnail cache limit:", error);
+      setThumbnailCacheMb(previousValue);
+    }
+  };
+
+  const handleClearThumbnailCache = async () => {
+    try {
+      await invoke<number>("clear_thumbnail_cache");
+      setThumbnailCacheSize(await invoke<number>("get_thumbnail_cache_size"));
+    } catch (error) {
+      console.error("Failed to clear thumbnail cache:", error);
+    }
+  };
+
+  const handlePrefetchChange = async (enabled: boolean, budgetMb: number) => {
+    const previousEnabled = prefetchEnabled;
+    const previousBudget = prefetchBudgetMb;
+    setPrefetchEnabled(enabled);
+    setPrefetchBudgetMb(budgetMb);
+    try {
+      await invoke("set_prefetch_settings", { enabled, budgetMb });
+    } catch (error) {
+      console.error("Failed to change prefetch settings:", error);
+      setPrefetchEnabled(previousEnabled);
+      setPrefetchBudgetMb(previousBudget);
+    }
+  };
+
   const handleOpenLogFolder = async () => {
     try {
       await invoke("open_log_folder");
@@ -427,6 +611,124 @@ export default function GeneralSection() {
           checked={notifyFileConflict}
           onChange={handleNotifyFileConflictChange}
           disabled={loading}
+          isLast={false}
+        />
+        {ALERT_CATEGORIES.map(({ category, titleKey }, index) => (
+          <SettingSelectItem
+            key={category}
+            title={t(titleKey)}
+            description={t("settings.alertStyleDescription")}
+            value={alertStyles[category] ?? "toast"}
+            options={ALERT_STYLES.map(({ style, labelKey }) => ({
+              value: style,
+              label: t(labelKey),
+            }))}
+            onChange={(value) =>
+              handleAlertStyleChange(category, value as AlertStyle)
+            }
+            disabled={loading}
+            isLast={index === ALERT_CATEGORIES.length - 1}
+          />
+        ))}
+      </SettingsGroup>
+
+      <SettingsGroup title={t("settings.securitySettings")}>
+        <SettingItem
+          title={t("settings.encryptInventory")}
+          description={t("settings.encryptInventoryDescription")}
+          checked={encryptInventory}
+          onChange={handleEncryptInventoryChange}
+          disabled={loading}
+          isLast={true}
+        />
+      </SettingsGroup>
+
+      <SettingsGroup title={t("settings.historySettings")}>
+        <SettingSelectItem
+          title={t("settings.historyRetentionDays")}
+          description={t("settings.historyRetentionDaysDescription")}
+          value={historyDays.toString()}
+          options={HISTORY_DAYS_OPTIONS.map((days) => ({
+            value: days,
+            label: t("settings.historyDays", { count: parseInt(days, 10) }),
+          }))}
+          onChange={(value) =>
+            handleHistoryRetentionChange(parseInt(value, 10), historyMaxEntries)
+          }
+          disabled={loading}
+          isLast={false}
+        />
+        <SettingSelectItem
+          title={t("settings.historyMaxEntries")}
+          description={t("settings.historyMaxEntriesDescription")}
+          value={historyMaxEntries.toString()}
+          options={HISTORY_MAX_ENTRIES_OPTIONS}
+          onChange={(value) =>
+            handleHistoryRetentionChange(historyDays, parseInt(value, 10))
+          }
+          disabled={loading}
+          isLast={false}
+        />
+        <SettingActionItem
+          title={t("settings.clearHistory")}
+          description={t("settings.clearHistoryDescription", {
+            count: historyCount,
+          })}
+          actionLabel={t("settings.clearHistoryAction")}
+          onAction={handleClearHistory}
+          disabled={loading || historyCount === 0}
+          isLast={true}
+        />
+      </SettingsGroup>
+
+      <SettingsGroup title={t("settings.thumbnailSettings")}>
+        <SettingSelectItem
+          title={t("settings.thumbnailCacheLimit")}
+          description={t("settings.thumbnailCacheLimitDescription")}
+          value={thumbnailCacheMb.toString()}
+          options={THUMBNAIL_CACHE_OPTIONS.map((option) => ({
+            ...option,
+            label:
+              option.value === "0"
+                ? t("settings.thumbnailCacheOff")
+                : option.label,
+          }))}
+          onChange={handleThumbnailCacheLimitChange}
+          disabled={loading}
+          isLast={false}
+        />
+        <SettingActionItem
+          title={t("settings.clearThumbnailCache")}
+          description={t("settings.clearThumbnailCacheDescription", {
+            size: formatBytes(thumbnailCacheSize),
+          })}
+          actionLabel={t("settings.clearHistoryAction")}
+          onAction={handleClearThumbnailCache}
+          disabled={loading || thumbnailCacheSize === 0}
+          isLast={true}
+        />
+      </SettingsGroup>
+
+      <SettingsGroup title={t("settings.prefetchSettings")}>
+        <SettingItem
+          title={t("settings.prefetch")}
+          description={t("settings.prefetchDescription")}
+          checked={prefetchEnabled}
+          onChange={(checked) =>
+            handlePrefetchChange(checked, prefetchBudgetMb)
+          }
+          disabled={loading}
+          isLast={false}
+        />
+        <SettingSelectItem
+          title={t("settings.prefetchBudget")}
+          description={t("settings.prefetchBudgetDescription")}
+          value={prefetchBudgetMb.toString()}
+          options={PREFETCH_BUDGET_OPTIONS}
+          onChange={(value) =>
+            handlePrefetchChange(prefetchEnabled, parseInt(value, 10))
+          }
+          disabled={loading || !prefetchEnabled}
           isLast={true}
         />
       </SettingsGroup>
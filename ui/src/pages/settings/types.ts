@@ -5,11 +5,18 @@ export interface DriveInfo {
   sync_path: string;
   icon_path?: string;
   raw_icon_path?: string;
+  color?: string;
   enabled: boolean;
   user_id: string;
   remote_path: string
   status: DriveStatus;
   capacity?: CapacitySummary;
+  navigation_pane: NavigationPane;
+}
+
+export interface NavigationPane {
+  pinned: boolean;
+  order: number;
 }
 
 export type DriveStatus = "active" | "event_push_lost" | "credential_expired";